@@ -0,0 +1,380 @@
+//! 配置中的环境变量插值和 `*_FILE` 密钥文件
+//!
+//! - 字符串配置值中的 `${NAME}` 替换为环境变量 `NAME`，`${NAME:-default}` 在变量未设置或为空时使用默认值，
+//!   `$${` 输出字面量 `${`。引用了未设置且没有默认值的变量时加载失败，而不是静默替换为空字符串
+//! - 变量 `NAME` 未设置但 `NAME_FILE` 指向一个文件时，读取文件内容（去掉末尾换行）作为 `NAME` 的值，
+//!   适用于Docker/Kubernetes以文件形式挂载的密钥
+//!
+//! 数据库连接串、API密钥等因此不必明文写入配置文件。
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+/// 密钥文件环境变量的后缀
+pub const FILE_SUFFIX: &str = "_FILE";
+
+/// 环境变量解析错误
+#[derive(Debug, thiserror::Error)]
+pub enum EnvError {
+    /// 引用的环境变量未设置
+    #[error("Environment variable {0} is not set (and neither is {0}_FILE)")]
+    Undefined(String),
+    /// 读取 `*_FILE` 指向的文件失败
+    #[error("Failed to read {name} from {path}: {source}")]
+    SecretFile {
+        name: String,
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// `${...}` 语法错误
+    #[error("Invalid variable reference in {0:?}")]
+    Syntax(String),
+    /// 某个配置项插值失败
+    #[error("{pointer}: {error}")]
+    InValue { pointer: String, error: Box<EnvError> },
+}
+
+/// 读取环境变量，未设置时读取 `NAME_FILE` 指向的文件
+pub fn env_var(name: &str) -> Result<Option<String>, EnvError> {
+    lookup(name, |var| std::env::var(var).ok())
+}
+
+fn lookup(name: &str, get: impl Fn(&str) -> Option<String>) -> Result<Option<String>, EnvError> {
+    if let Some(value) = get(name) {
+        return Ok(Some(value));
+    }
+    match get(&format!("{}{}", name, FILE_SUFFIX)) {
+        Some(path) => read_secret_file(name, &path).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn read_secret_file(name: &str, path: &str) -> Result<String, EnvError> {
+    std::fs::read_to_string(path)
+        .map(|content| content.trim_end_matches(['\n', '\r']).to_string())
+        .map_err(|source| EnvError::SecretFile {
+            name: name.to_string(),
+            path: PathBuf::from(path),
+            source,
+        })
+}
+
+/// 展开字符串中的 `${NAME}`、`${NAME:-default}` 和 `$${`
+pub fn interpolate(text: &str) -> Result<String, EnvError> {
+    interpolate_with(text, &env_var)
+}
+
+fn interpolate_with(
+    text: &str,
+    resolve: &impl Fn(&str) -> Result<Option<String>, EnvError>,
+) -> Result<String, EnvError> {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        // `$${` 转义为字面量 `${`
+        if rest[..start].ends_with('$') {
+            output.push_str(&rest[..start - 1]);
+            output.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        output.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            return Err(EnvError::Syntax(text.to_string()));
+        };
+        let expression = &rest[start + 2..start + end];
+        let (name, default) = match expression.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expression, None),
+        };
+        if !is_var_name(name) {
+            return Err(EnvError::Syntax(text.to_string()));
+        }
+        let value = match (resolve(name)?, default) {
+            (Some(value), Some(default)) if value.is_empty() => default.to_string(),
+            (Some(value), _) => value,
+            (None, Some(default)) => default.to_string(),
+            (None, None) => return Err(EnvError::Undefined(name.to_string())),
+        };
+        output.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn is_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 展开配置中所有字符串值（不含键名）里的变量引用
+pub fn interpolate_value(value: &mut Value) -> Result<(), EnvError> {
+    interpolate_value_with(value, &env_var, &mut String::new())
+}
+
+fn interpolate_value_with(
+    value: &mut Value,
+    resolve: &impl Fn(&str) -> Result<Option<String>, EnvError>,
+    pointer: &mut String,
+) -> Result<(), EnvError> {
+    match value {
+        Value::String(text) if text.contains("${") => {
+            *text = interpolate_with(text, resolve).map_err(|error| EnvError::InValue {
+                pointer: pointer.clone(),
+                error: Box::new(error),
+            })?;
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                let len = pointer.len();
+                pointer.push_str(&format!("/{}", index));
+                interpolate_value_with(item, resolve, pointer)?;
+                pointer.truncate(len);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                interpolate_value_with(item, resolve, pointer)?;
+                pointer.truncate(len);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// 带前缀的环境变量覆盖（`config::Environment` 的变量来源）
+///
+/// `PREFIX_A__B_FILE=/run/secrets/x` 解析为 `PREFIX_A__B`，值为文件内容；已经显式设置 `PREFIX_A__B` 时以显式值为准。
+/// 配置本身就有以 `_file` 结尾的键（例如 `warmup.samples_file`）时，同名变量仍按普通覆盖处理。
+#[derive(Debug, Clone)]
+pub struct EnvOverrides {
+    prefix: String,
+    separator: String,
+    legacy_separator: Option<String>,
+}
+
+impl EnvOverrides {
+    /// `prefix` 不含结尾的 `_`，`separator` 是嵌套键的分隔符
+    pub fn new(prefix: impl Into<String>, separator: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            separator: separator.into(),
+            legacy_separator: None,
+        }
+    }
+
+    /// 兼容改用 `separator` 之前的分隔符
+    ///
+    /// 不含 `separator` 的变量按 `legacy` 拆分，能在文件配置中找到对应的嵌套键时改写为新格式，
+    /// 例如 `PREFIX_SERVER_PORT` 改写为 `PREFIX_SERVER__PORT`。键名本身含 `legacy` 时按配置结构匹配，
+    /// 只对文件配置（含默认值）中出现的键有效；新旧格式同时设置时以新格式为准。
+    pub fn with_legacy_separator(mut self, legacy: impl Into<String>) -> Self {
+        self.legacy_separator = Some(legacy.into());
+        self
+    }
+
+    /// 当前进程的环境变量，`*_FILE` 已替换为文件内容；`base` 是插值后的文件配置，用于识别真实的 `*_file` 键
+    pub fn vars(&self, base: &Value) -> Result<HashMap<String, String>, EnvError> {
+        self.resolve(std::env::vars(), base)
+    }
+
+    fn resolve(
+        &self,
+        vars: impl IntoIterator<Item = (String, String)>,
+        base: &Value,
+    ) -> Result<HashMap<String, String>, EnvError> {
+        let mut resolved: HashMap<String, String> = vars.into_iter().collect();
+        if let Some(legacy) = &self.legacy_separator {
+            let renamed: Vec<(String, String)> = resolved
+                .keys()
+                .filter_map(|name| self.legacy_rename(name, legacy, base).map(|target| (name.clone(), target)))
+                .collect();
+            for (name, target) in renamed {
+                if let Some(value) = resolved.remove(&name) {
+                    resolved.entry(target).or_insert(value);
+                }
+            }
+        }
+
+        let secret_vars: Vec<(String, String)> = resolved
+            .iter()
+            .filter(|(name, _)| name.ends_with(FILE_SUFFIX) && !self.is_config_key(name, base))
+            .filter_map(|(name, path)| {
+                let target = name.strip_suffix(FILE_SUFFIX)?;
+                self.key_path(target).map(|_| (target.to_string(), path.clone()))
+            })
+            .collect();
+
+        for (target, path) in secret_vars {
+            resolved.remove(&format!("{}{}", target, FILE_SUFFIX));
+            if let Entry::Vacant(entry) = resolved.entry(target) {
+                let value = read_secret_file(entry.key(), &path)?;
+                entry.insert(value);
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// 变量名对应的配置键路径，不带前缀的变量返回None
+    fn key_path(&self, name: &str) -> Option<Vec<String>> {
+        let rest = name.strip_prefix(&self.prefix)?.strip_prefix('_')?;
+        Some(rest.to_lowercase().split(self.separator.as_str()).map(str::to_string).collect())
+    }
+
+    /// 旧分隔符格式的变量改写后的名称，不需要改写时返回None
+    fn legacy_rename(&self, name: &str, legacy: &str, base: &Value) -> Option<String> {
+        let rest = name.strip_prefix(&self.prefix)?.strip_prefix('_')?;
+        if rest.contains(self.separator.as_str()) {
+            return None;
+        }
+        // 先按完整名称匹配（配置中真实的 `*_file` 键），再去掉 `_FILE` 后缀按密钥文件变量匹配
+        let (path, suffix) = match legacy_path(&rest.to_lowercase(), base, legacy) {
+            Some(path) => (path, ""),
+            None => {
+                let target = rest.strip_suffix(FILE_SUFFIX)?;
+                (legacy_path(&target.to_lowercase(), base, legacy)?, FILE_SUFFIX)
+            }
+        };
+        (path.len() > 1)
+            .then(|| format!("{}_{}{}", self.prefix, path.join(self.separator.as_str()).to_uppercase(), suffix))
+    }
+
+    fn is_config_key(&self, name: &str, base: &Value) -> bool {
+        self.key_path(name).is_some_and(|path| {
+            path.iter()
+                .try_fold(base, |value, key| value.get(key))
+                .is_some()
+        })
+    }
+}
+
+/// 按配置结构把用 `separator` 连接的键名拆分为嵌套键路径
+fn legacy_path(rest: &str, node: &Value, separator: &str) -> Option<Vec<String>> {
+    let map = node.as_object()?;
+    if map.contains_key(rest) {
+        return Some(vec![rest.to_string()]);
+    }
+    map.iter().find_map(|(key, child)| {
+        let tail = rest.strip_prefix(key.as_str())?.strip_prefix(separator)?;
+        let mut path = legacy_path(tail, child, separator)?;
+        path.insert(0, key.clone());
+        Some(path)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn resolver(vars: &[(&str, &str)]) -> impl Fn(&str) -> Result<Option<String>, EnvError> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |name| lookup(name, |var| vars.get(var).cloned())
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let secret = std::env::temp_dir().join(format!("config-schema-secret-{}", std::process::id()));
+        std::fs::write(&secret, "s3cr3t\n").unwrap();
+        let resolve = resolver(&[
+            ("DB_HOST", "db"),
+            ("EMPTY", ""),
+            ("DB_PASSWORD_FILE", secret.to_str().unwrap()),
+        ]);
+
+        let url = interpolate_with("postgres://app:${DB_PASSWORD}@${DB_HOST}:${DB_PORT:-5432}/app", &resolve).unwrap();
+        assert_eq!(url, "postgres://app:s3cr3t@db:5432/app");
+        assert_eq!(interpolate_with("${EMPTY:-fallback} $${literal} $2b$", &resolve).unwrap(), "fallback ${literal} $2b$");
+
+        assert!(matches!(interpolate_with("${MISSING}", &resolve), Err(EnvError::Undefined(name)) if name == "MISSING"));
+        assert!(matches!(interpolate_with("${DB_HOST", &resolve), Err(EnvError::Syntax(_))));
+        assert!(matches!(interpolate_with("${1BAD}", &resolve), Err(EnvError::Syntax(_))));
+
+        let mut value = json!({ "database": { "urls": ["${DB_HOST}", "${NOPE}"] } });
+        let err = interpolate_value_with(&mut value, &resolve, &mut String::new()).unwrap_err();
+        assert!(matches!(&err, EnvError::InValue { pointer, .. } if pointer == "/database/urls/1"), "{err}");
+
+        std::fs::remove_file(&secret).unwrap();
+    }
+
+    #[test]
+    fn test_env_overrides_resolve_secret_files() {
+        let secret = std::env::temp_dir().join(format!("config-schema-key-{}", std::process::id()));
+        std::fs::write(&secret, "api-key\r\n").unwrap();
+        let path = secret.to_str().unwrap().to_string();
+        let base = json!({ "warmup": { "samples_file": "samples.json" } });
+        let overrides = EnvOverrides::new("APP", "__");
+
+        let vars = overrides
+            .resolve(
+                [
+                    ("APP_SECURITY__API_KEY_FILE".to_string(), path.clone()),
+                    ("APP_WARMUP__SAMPLES_FILE".to_string(), "other.json".to_string()),
+                    ("APP_REDIS__URL".to_string(), "redis://explicit".to_string()),
+                    ("APP_REDIS__URL_FILE".to_string(), "/does/not/exist".to_string()),
+                    ("OTHER_FILE".to_string(), "/does/not/exist".to_string()),
+                ],
+                &base,
+            )
+            .unwrap();
+
+        assert_eq!(vars["APP_SECURITY__API_KEY"], "api-key");
+        assert!(!vars.contains_key("APP_SECURITY__API_KEY_FILE"));
+        // 配置中真实存在的 `*_file` 键按普通覆盖处理
+        assert_eq!(vars["APP_WARMUP__SAMPLES_FILE"], "other.json");
+        // 显式设置的变量优先
+        assert_eq!(vars["APP_REDIS__URL"], "redis://explicit");
+        assert_eq!(vars["OTHER_FILE"], "/does/not/exist");
+
+        std::fs::remove_file(&secret).unwrap();
+    }
+
+    #[test]
+    fn test_env_overrides_legacy_separator() {
+        let secret = std::env::temp_dir().join(format!("config-schema-legacy-{}", std::process::id()));
+        std::fs::write(&secret, "jwt\n").unwrap();
+        let base = json!({
+            "server": { "port": 8080 },
+            "cache": { "max_size": 1000 },
+            "security": { "jwt_secret": "default" },
+            "warmup": { "samples_file": "samples.json" },
+        });
+        let overrides = EnvOverrides::new("APP", "__").with_legacy_separator("_");
+
+        let vars = overrides
+            .resolve(
+                [
+                    ("APP_SERVER_PORT".to_string(), "9090".to_string()),
+                    ("APP_CACHE_MAX_SIZE".to_string(), "10".to_string()),
+                    ("APP_CACHE__MAX_SIZE".to_string(), "20".to_string()),
+                    ("APP_SECURITY_JWT_SECRET_FILE".to_string(), secret.to_str().unwrap().to_string()),
+                    ("APP_WARMUP_SAMPLES_FILE".to_string(), "other.json".to_string()),
+                    ("APP_UNKNOWN_KEY".to_string(), "x".to_string()),
+                ],
+                &base,
+            )
+            .unwrap();
+
+        assert_eq!(vars["APP_SERVER__PORT"], "9090");
+        assert!(!vars.contains_key("APP_SERVER_PORT"));
+        // 新格式优先
+        assert_eq!(vars["APP_CACHE__MAX_SIZE"], "20");
+        assert!(!vars.contains_key("APP_CACHE_MAX_SIZE"));
+        assert_eq!(vars["APP_SECURITY__JWT_SECRET"], "jwt");
+        assert_eq!(vars["APP_WARMUP__SAMPLES_FILE"], "other.json");
+        // 配置中找不到的键保持原样
+        assert_eq!(vars["APP_UNKNOWN_KEY"], "x");
+
+        std::fs::remove_file(&secret).unwrap();
+    }
+}
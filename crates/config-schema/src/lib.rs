@@ -0,0 +1,285 @@
+//! # Config Schema
+//!
+//! 各服务共用的配置文件加载和校验工具。
+//!
+//! 服务用 `schemars` 从配置结构体导出JSON Schema（`--print-config-schema`），
+//! 本crate把TOML/YAML/JSON配置文件解析为JSON后按该Schema校验，并把每个错误的
+//! JSON Pointer映射回源文件中的行号（`--validate-config <file>`），便于在部署前的CI中检查配置。
+//!
+//! 加载配置时，[`interpolate_value`] 展开字符串值中的 `${ENV_VAR}`，[`env_var`] 和 [`EnvOverrides`]
+//! 支持从 `*_FILE` 指向的文件读取密钥，详见 [`env`] 模块。
+
+pub mod env;
+mod locate;
+
+pub use env::{env_var, interpolate, interpolate_value, EnvError, EnvOverrides};
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use jsonschema::error::ValidationErrorKind;
+use jsonschema::JSONSchema;
+use serde_json::Value;
+
+/// 配置文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// 按文件扩展名判断格式
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// 配置校验过程中的错误（不包括Schema校验失败本身）
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigSchemaError {
+    /// 读取文件失败
+    #[error("Failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// 无法从扩展名判断格式
+    #[error("Unsupported config format: {0} (expected .toml, .yaml, .yml or .json)")]
+    UnsupportedFormat(PathBuf),
+    /// 语法错误
+    #[error("{}", fmt_location(.line, .column, .message))]
+    Parse {
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+    /// Schema本身无效
+    #[error("Invalid config schema: {0}")]
+    InvalidSchema(String),
+}
+
+fn fmt_location(line: &Option<usize>, column: &Option<usize>, message: &str) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!("line {}, column {}: {}", line, column, message),
+        (Some(line), None) => format!("line {}: {}", line, message),
+        _ => message.to_string(),
+    }
+}
+
+/// 一条Schema校验失败
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// 出错位置的JSON Pointer，例如 `/server/port`；根对象为空字符串
+    pub pointer: String,
+    /// 源文件中最接近出错位置的行号（从1开始）
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() { "/" } else { &self.pointer };
+        match self.line {
+            Some(line) => write!(f, "line {}: {}: {}", line, pointer, self.message),
+            None => write!(f, "{}: {}", pointer, self.message),
+        }
+    }
+}
+
+/// 把配置源码解析为JSON值
+pub fn parse(format: ConfigFormat, source: &str) -> Result<Value, ConfigSchemaError> {
+    match format {
+        ConfigFormat::Toml => {
+            let value: toml::Value = toml::from_str(source).map_err(|e| {
+                let (line, column) = e
+                    .span()
+                    .map(|span| offset_to_line_column(source, span.start))
+                    .unzip();
+                ConfigSchemaError::Parse {
+                    line,
+                    column,
+                    message: e.message().to_string(),
+                }
+            })?;
+            serde_json::to_value(value).map_err(|e| ConfigSchemaError::Parse {
+                line: None,
+                column: None,
+                message: e.to_string(),
+            })
+        }
+        ConfigFormat::Yaml => {
+            let value: Value = serde_yaml::from_str(source).map_err(|e| {
+                let location = e.location();
+                ConfigSchemaError::Parse {
+                    line: location.as_ref().map(|l| l.line()),
+                    column: location.as_ref().map(|l| l.column()),
+                    message: e.to_string(),
+                }
+            })?;
+            // 空文件解析为null，按空对象处理以便报告缺失的必填字段
+            Ok(if value.is_null() { Value::Object(Default::default()) } else { value })
+        }
+        ConfigFormat::Json => serde_json::from_str(source).map_err(|e| ConfigSchemaError::Parse {
+            line: Some(e.line()),
+            column: Some(e.column()),
+            message: e.to_string(),
+        }),
+    }
+}
+
+/// 按Schema校验配置源码，返回全部校验失败（为空表示通过）
+pub fn validate_str(
+    schema: &Value,
+    format: ConfigFormat,
+    source: &str,
+) -> Result<Vec<ConfigIssue>, ConfigSchemaError> {
+    let compiled = JSONSchema::compile(schema).map_err(|e| ConfigSchemaError::InvalidSchema(e.to_string()))?;
+    let instance = parse(format, source)?;
+    let index = locate::KeyIndex::build(format, source);
+
+    let mut issues = Vec::new();
+    if let Err(errors) = compiled.validate(&instance) {
+        for error in errors {
+            // `${VAR}` 占位符在加载时才展开，类型由加载器转换，这里不按字符串报告类型错误
+            let placeholder = error.instance.as_str().is_some_and(|s| s.contains("${"));
+            if placeholder && matches!(error.kind, ValidationErrorKind::Type { .. }) {
+                continue;
+            }
+            let mut pointer = error.instance_path.to_string();
+            // 多余字段报在父对象上，只有一个时直接定位到该字段
+            if let ValidationErrorKind::AdditionalProperties { unexpected } = &error.kind {
+                if let [name] = unexpected.as_slice() {
+                    pointer.push('/');
+                    pointer.push_str(&name.replace('~', "~0").replace('/', "~1"));
+                }
+            }
+            issues.push(ConfigIssue {
+                line: index.line_of(&pointer),
+                pointer,
+                message: error.to_string(),
+            });
+        }
+    }
+    issues.sort_by_key(|issue| issue.line);
+    Ok(issues)
+}
+
+/// 读取配置文件，按扩展名判断格式后校验
+pub fn validate_file(schema: &Value, path: impl AsRef<Path>) -> Result<Vec<ConfigIssue>, ConfigSchemaError> {
+    let path = path.as_ref();
+    let format = ConfigFormat::from_path(path).ok_or_else(|| ConfigSchemaError::UnsupportedFormat(path.to_path_buf()))?;
+    let source = std::fs::read_to_string(path).map_err(|source| ConfigSchemaError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    validate_str(schema, format, &source)
+}
+
+/// 命令行 `--validate-config` 的通用实现：打印结果到stderr/stdout，返回进程退出码
+pub fn run_validate(schema: &Value, path: impl AsRef<Path>) -> i32 {
+    let path = path.as_ref();
+    match validate_file(schema, path) {
+        Ok(issues) if issues.is_empty() => {
+            println!("{}: OK", path.display());
+            0
+        }
+        Ok(issues) => {
+            for issue in &issues {
+                eprintln!("{}:{}", path.display(), issue);
+            }
+            eprintln!("{}: {} error(s)", path.display(), issues.len());
+            1
+        }
+        Err(e) => {
+            eprintln!("{}: {}", path.display(), e);
+            1
+        }
+    }
+}
+
+fn offset_to_line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "server": {
+                    "type": "object",
+                    "properties": {
+                        "host": { "type": "string" },
+                        "port": { "type": "integer", "minimum": 1, "maximum": 65535 }
+                    },
+                    "required": ["host"],
+                    "additionalProperties": false
+                },
+                "workers": {
+                    "type": "array",
+                    "items": { "$ref": "#/$defs/Worker" }
+                }
+            },
+            "$defs": {
+                "Worker": {
+                    "type": "object",
+                    "properties": { "name": { "type": "string" } }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_toml_issue_locations() {
+        let source = "# comment\n[server]\nhost = \"0.0.0.0\"\nport = 70000\ntimeout = 5\n\n[[workers]]\nname = 1\n";
+        let issues = validate_str(&schema(), ConfigFormat::Toml, source).unwrap();
+
+        let found: Vec<_> = issues.iter().map(|i| (i.pointer.as_str(), i.line)).collect();
+        assert_eq!(
+            found,
+            vec![("/server/port", Some(4)), ("/server/timeout", Some(5)), ("/workers/0/name", Some(8))]
+        );
+        assert!(issues[0].to_string().starts_with("line 4: /server/port: 70000"));
+    }
+
+    #[test]
+    fn test_yaml_issue_locations() {
+        let source = "server:\n  # comment\n  port: \"80\"\nworkers:\n  - name: ok\n  - name: 2\n";
+        let issues = validate_str(&schema(), ConfigFormat::Yaml, source).unwrap();
+
+        let found: Vec<_> = issues.iter().map(|i| (i.pointer.as_str(), i.line)).collect();
+        assert_eq!(found, vec![("/server", Some(1)), ("/server/port", Some(3)), ("/workers/1/name", Some(6))]);
+
+        // 环境变量占位符不按类型报错
+        let issues = validate_str(&schema(), ConfigFormat::Yaml, "server:\n  host: h\n  port: \"${PORT}\"\n").unwrap();
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn test_parse_errors_have_locations() {
+        let err = validate_str(&schema(), ConfigFormat::Toml, "[server]\nport = \n").unwrap_err();
+        assert!(matches!(err, ConfigSchemaError::Parse { line: Some(2), .. }), "{err:?}");
+
+        let err = validate_str(&schema(), ConfigFormat::Yaml, "server:\n  port: [1\n").unwrap_err();
+        assert!(matches!(err, ConfigSchemaError::Parse { line: Some(_), .. }), "{err:?}");
+
+        assert_eq!(ConfigFormat::from_path(Path::new("config/default.yml")), Some(ConfigFormat::Yaml));
+        assert_eq!(ConfigFormat::from_path(Path::new("config.ini")), None);
+    }
+}
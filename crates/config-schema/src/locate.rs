@@ -0,0 +1,256 @@
+//! 把JSON Pointer映射回配置源文件的行号
+//!
+//! 不依赖解析器的位置信息，而是逐行扫描源文件记录每个键路径首次出现的行：
+//! TOML识别 `[table]`、`[[array]]` 表头和 `key = value` 行，YAML按缩进维护键栈。
+//! 查询时取与Pointer最长的已记录前缀，找不到精确位置时退回到所在的表或父键。
+
+use std::collections::HashMap;
+
+use crate::ConfigFormat;
+
+/// 键路径 -> 首次出现的行号（从1开始）
+#[derive(Debug, Default)]
+pub(crate) struct KeyIndex {
+    lines: HashMap<Vec<String>, usize>,
+}
+
+impl KeyIndex {
+    pub(crate) fn build(format: ConfigFormat, source: &str) -> Self {
+        let mut index = Self::default();
+        match format {
+            ConfigFormat::Toml => index.scan_toml(source),
+            ConfigFormat::Yaml => index.scan_yaml(source),
+            // JSON配置很少见，只报告Pointer
+            ConfigFormat::Json => {}
+        }
+        index
+    }
+
+    /// Pointer所在的行，逐级退回到最近的已知父路径
+    pub(crate) fn line_of(&self, pointer: &str) -> Option<usize> {
+        let segments: Vec<String> = pointer
+            .split('/')
+            .skip(1)
+            .map(|s| s.replace("~1", "/").replace("~0", "~"))
+            .collect();
+        (1..=segments.len())
+            .rev()
+            .find_map(|len| self.lines.get(&segments[..len]).copied())
+    }
+
+    fn record(&mut self, path: &[String], line: usize) {
+        self.lines.entry(path.to_vec()).or_insert(line);
+    }
+
+    fn scan_toml(&mut self, source: &str) {
+        let mut table: Vec<String> = Vec::new();
+        // 表数组路径 -> 已出现的元素数量
+        let mut arrays: HashMap<Vec<String>, usize> = HashMap::new();
+        // 多行数组/内联表的括号深度，以及未闭合的多行字符串分隔符
+        let mut depth = 0i32;
+        let mut open_string: Option<&str> = None;
+
+        for (number, raw) in source.lines().enumerate() {
+            let line = number + 1;
+            if let Some(delimiter) = open_string {
+                if raw.contains(delimiter) {
+                    open_string = None;
+                }
+                continue;
+            }
+            if depth > 0 {
+                depth += bracket_depth(raw);
+                continue;
+            }
+
+            let trimmed = raw.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = trimmed.strip_prefix("[[") {
+                let Some(end) = header.find("]]") else { continue };
+                let path = resolve_toml_table(split_key(&header[..end], '.'), &arrays);
+                let count = arrays.entry(path.clone()).or_insert(0);
+                table = path;
+                self.record(&table, line);
+                table.push(count.to_string());
+                *count += 1;
+                self.record(&table, line);
+            } else if let Some(header) = trimmed.strip_prefix('[') {
+                let Some(end) = header.find(']') else { continue };
+                table = resolve_toml_table(split_key(&header[..end], '.'), &arrays);
+                self.record(&table, line);
+            } else if let Some(eq) = find_unquoted(trimmed, '=') {
+                let mut path = table.clone();
+                for key in split_key(&trimmed[..eq], '.') {
+                    path.push(key);
+                    self.record(&path, line);
+                }
+
+                let value = trimmed[eq + 1..].trim_start();
+                for delimiter in ["\"\"\"", "'''"] {
+                    if value.starts_with(delimiter) && !value[3..].contains(delimiter) {
+                        open_string = Some(delimiter);
+                    }
+                }
+                if open_string.is_none() {
+                    depth = bracket_depth(value).max(0);
+                }
+            }
+        }
+    }
+
+    fn scan_yaml(&mut self, source: &str) {
+        // (缩进, 路径段, 是否为列表元素)
+        let mut stack: Vec<(usize, String, bool)> = Vec::new();
+        // 列表路径 -> 已出现的元素数量
+        let mut items: HashMap<Vec<String>, usize> = HashMap::new();
+        // 块标量（`|`、`>`）所属键的缩进，内容行直接跳过
+        let mut block_indent: Option<usize> = None;
+
+        for (number, raw) in source.lines().enumerate() {
+            let line = number + 1;
+            let trimmed = raw.trim_start();
+            let mut indent = raw.len() - trimmed.len();
+            if trimmed.is_empty() {
+                continue;
+            }
+            if let Some(block) = block_indent {
+                if indent > block {
+                    continue;
+                }
+                block_indent = None;
+            }
+            if trimmed.starts_with('#') || trimmed.starts_with("---") || trimmed.starts_with("...") {
+                continue;
+            }
+
+            let mut rest = trimmed;
+            if rest == "-" || rest.starts_with("- ") {
+                while stack.last().is_some_and(|(i, _, item)| *i > indent || (*i == indent && *item)) {
+                    stack.pop();
+                }
+                let parent: Vec<String> = stack.iter().map(|(_, s, _)| s.clone()).collect();
+                let count = items.entry(parent).or_insert(0);
+                stack.push((indent, count.to_string(), true));
+                *count += 1;
+                self.record(&path_of(&stack), line);
+
+                let after = rest[1..].trim_start();
+                indent += rest.len() - after.len();
+                rest = after;
+            }
+
+            let Some(colon) = find_yaml_colon(rest) else { continue };
+            let key = unquote(rest[..colon].trim());
+            while stack.last().is_some_and(|(i, _, _)| *i >= indent) {
+                stack.pop();
+            }
+            stack.push((indent, key, false));
+            self.record(&path_of(&stack), line);
+
+            let value = rest[colon + 1..].trim_start();
+            if value.starts_with('|') || value.starts_with('>') {
+                block_indent = Some(indent);
+            }
+        }
+    }
+}
+
+fn path_of(stack: &[(usize, String, bool)]) -> Vec<String> {
+    stack.iter().map(|(_, segment, _)| segment.clone()).collect()
+}
+
+/// `[a.b]` 中引用到的表数组取其最后一个元素，例如 `[workers.limits]` 属于最近的 `[[workers]]`
+fn resolve_toml_table(keys: Vec<String>, arrays: &HashMap<Vec<String>, usize>) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut prefix = Vec::new();
+    for key in keys {
+        prefix.push(key.clone());
+        path.push(key);
+        if let Some(count) = arrays.get(&prefix) {
+            path.push(count.saturating_sub(1).to_string());
+            prefix.push(count.saturating_sub(1).to_string());
+        }
+    }
+    path
+}
+
+/// 按分隔符拆分（可能带引号的）键
+fn split_key(key: &str, separator: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    for ch in key.chars() {
+        match quote {
+            Some(q) if ch == q => quote = None,
+            Some(_) => current.push(ch),
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == separator => parts.push(std::mem::take(&mut current).trim().to_string()),
+            None => current.push(ch),
+        }
+    }
+    parts.push(current.trim().to_string());
+    parts
+}
+
+fn unquote(key: &str) -> String {
+    let quoted = key.len() >= 2
+        && ((key.starts_with('"') && key.ends_with('"')) || (key.starts_with('\'') && key.ends_with('\'')));
+    if quoted {
+        key[1..key.len() - 1].to_string()
+    } else {
+        key.to_string()
+    }
+}
+
+/// 引号外第一个目标字符的位置
+fn find_unquoted(text: &str, target: char) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (pos, ch) in text.char_indices() {
+        match quote {
+            Some(q) if ch == q => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == '#' => return None,
+            None if ch == target => return Some(pos),
+            None => {}
+        }
+    }
+    None
+}
+
+/// YAML映射键后的冒号：后面是空白或行尾
+fn find_yaml_colon(text: &str) -> Option<usize> {
+    if text.starts_with('{') || text.starts_with('[') {
+        return None;
+    }
+    let mut offset = 0;
+    while let Some(pos) = find_unquoted(&text[offset..], ':') {
+        let pos = offset + pos;
+        if text[pos + 1..].chars().next().is_none_or(char::is_whitespace) {
+            return Some(pos);
+        }
+        offset = pos + 1;
+    }
+    None
+}
+
+/// 一行中引号外 `[`/`{` 与 `]`/`}` 的数量差
+fn bracket_depth(text: &str) -> i32 {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    for ch in text.chars() {
+        match quote {
+            Some(q) if ch == q => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == '#' => break,
+            None if ch == '[' || ch == '{' => depth += 1,
+            None if ch == ']' || ch == '}' => depth -= 1,
+            None => {}
+        }
+    }
+    depth
+}
@@ -0,0 +1,289 @@
+//! # Crash Report
+//!
+//! 各HTTP服务共用的崩溃处理。
+//!
+//! - [`PanicHook`]：全局panic钩子，记录错误日志，开启崩溃报告时写入包含panic信息、回溯和最近日志的报告文件
+//! - [`RecentLogs`]、[`RecentLogsLayer`]：把最近的日志保存在内存环形缓冲区中，只在写入崩溃报告时读取
+//! - [`panic_message`]：取出panic负载中的消息
+//!
+//! 请求处理中的panic如何转换为HTTP响应（问题详情格式、本地化、指标）由各服务在
+//! `CatchPanicLayer::custom` 中自行处理；请求的追踪ID和panic计数通过 [`PanicHook`] 的回调接入。
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// 最近日志环形缓冲区
+pub struct RecentLogs {
+    capacity: usize,
+    lines: Mutex<VecDeque<String>>,
+}
+
+impl RecentLogs {
+    /// 创建指定容量的缓冲区
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            lines: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    fn push(&self, line: String) {
+        // 日志层内部发生panic时锁可能已被本线程持有，此时丢弃这条日志
+        let Ok(mut lines) = self.lines.try_lock() else {
+            return;
+        };
+        if lines.len() >= self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    /// 按时间顺序返回缓冲区中的日志
+    pub fn snapshot(&self) -> Vec<String> {
+        match self.lines.try_lock() {
+            Ok(lines) => lines.iter().cloned().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/// 把日志事件写入 [`RecentLogs`] 的订阅层
+pub struct RecentLogsLayer {
+    logs: Arc<RecentLogs>,
+}
+
+impl RecentLogsLayer {
+    /// 创建写入指定缓冲区的订阅层
+    pub fn new(logs: Arc<RecentLogs>) -> Self {
+        Self { logs }
+    }
+}
+
+/// 把事件字段格式化为 `message key=value ...`
+#[derive(Default)]
+struct LineVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for LineVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RecentLogsLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = LineVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        self.logs.push(format!(
+            "{} {:>5} {}: {}{}",
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            metadata.level(),
+            metadata.target(),
+            visitor.message,
+            visitor.fields
+        ));
+    }
+}
+
+/// 全局panic钩子
+///
+/// 默认只记录错误日志；[`PanicHook::with_reports`] 开启崩溃报告文件。钩子在发生panic的线程上执行，
+/// 所以 [`PanicHook::with_trace_id`] 的回调可以读取当前请求的任务局部上下文。
+pub struct PanicHook {
+    product: String,
+    version: String,
+    report_directory: Option<PathBuf>,
+    recent_logs: Option<Arc<RecentLogs>>,
+    trace_id: Option<Box<dyn Fn() -> Option<String> + Send + Sync>>,
+    on_panic: Option<Box<dyn Fn() + Send + Sync>>,
+}
+
+impl PanicHook {
+    /// 创建钩子，`product` 和 `version` 写在崩溃报告的开头
+    pub fn new(product: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            product: product.into(),
+            version: version.into(),
+            report_directory: None,
+            recent_logs: None,
+            trace_id: None,
+            on_panic: None,
+        }
+    }
+
+    /// 每次panic在指定目录写入一个崩溃报告文件
+    pub fn with_reports(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.report_directory = Some(directory.into());
+        self
+    }
+
+    /// 崩溃报告附带缓冲区中的最近日志
+    pub fn with_recent_logs(mut self, recent_logs: Arc<RecentLogs>) -> Self {
+        self.recent_logs = Some(recent_logs);
+        self
+    }
+
+    /// 取得当前请求的追踪ID，写入错误日志和崩溃报告
+    pub fn with_trace_id(mut self, trace_id: impl Fn() -> Option<String> + Send + Sync + 'static) -> Self {
+        self.trace_id = Some(Box::new(trace_id));
+        self
+    }
+
+    /// 每次panic时调用，通常用于计数
+    pub fn with_on_panic(mut self, on_panic: impl Fn() + Send + Sync + 'static) -> Self {
+        self.on_panic = Some(Box::new(on_panic));
+        self
+    }
+
+    /// 替换进程的panic钩子
+    pub fn install(self) {
+        std::panic::set_hook(Box::new(move |info| {
+            let message = panic_message(info.payload());
+            let location = info.location().map(|l| l.to_string()).unwrap_or_else(|| "unknown".to_string());
+            let trace_id = self.trace_id.as_ref().and_then(|trace_id| trace_id());
+
+            tracing::error!(
+                panic.message = %message,
+                panic.location = %location,
+                trace_id = trace_id.as_deref().unwrap_or("-"),
+                "Panic occurred"
+            );
+            if let Some(on_panic) = &self.on_panic {
+                on_panic();
+            }
+
+            if let Some(directory) = &self.report_directory {
+                let report = CrashReport {
+                    product: &self.product,
+                    version: &self.version,
+                    message: &message,
+                    location: &location,
+                    trace_id: trace_id.as_deref(),
+                };
+                let logs = self.recent_logs.as_ref().map(|logs| logs.snapshot()).unwrap_or_default();
+                match report.write(directory, &logs) {
+                    Ok(path) => tracing::error!("Crash report written to {}", path.display()),
+                    Err(e) => tracing::error!("Failed to write crash report: {}", e),
+                }
+            }
+        }));
+    }
+}
+
+/// 取出panic负载中的消息
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+/// 一次panic的报告内容
+struct CrashReport<'a> {
+    product: &'a str,
+    version: &'a str,
+    message: &'a str,
+    location: &'a str,
+    trace_id: Option<&'a str>,
+}
+
+impl CrashReport<'_> {
+    /// 写入崩溃报告，返回报告文件路径
+    fn write(&self, directory: &Path, recent_logs: &[String]) -> std::io::Result<PathBuf> {
+        let now = chrono::Utc::now();
+        let thread = std::thread::current();
+        let mut report = String::new();
+        let _ = writeln!(report, "{} crash report", self.product);
+        let _ = writeln!(report, "version: {}", self.version);
+        let _ = writeln!(report, "time: {}", now.to_rfc3339());
+        let _ = writeln!(report, "pid: {}", std::process::id());
+        let _ = writeln!(report, "thread: {}", thread.name().unwrap_or("<unnamed>"));
+        let _ = writeln!(report, "trace_id: {}", self.trace_id.unwrap_or("-"));
+        let _ = writeln!(report, "message: {}", self.message);
+        let _ = writeln!(report, "location: {}", self.location);
+        let _ = writeln!(report, "\nbacktrace:\n{}", Backtrace::force_capture());
+        let _ = writeln!(report, "recent logs ({}):", recent_logs.len());
+        for line in recent_logs {
+            let _ = writeln!(report, "{}", line);
+        }
+
+        std::fs::create_dir_all(directory)?;
+        let path = directory.join(format!("crash-{}-{}.log", now.format("%Y%m%dT%H%M%S%.3fZ"), std::process::id()));
+        std::fs::write(&path, report)?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_recent_logs_ring_buffer() {
+        let logs = Arc::new(RecentLogs::new(2));
+        let subscriber = tracing_subscriber::registry().with(RecentLogsLayer::new(logs.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("first");
+            tracing::warn!(task_id = "t-1", "second");
+            tracing::error!("third");
+        });
+
+        let lines = logs.snapshot();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains(" WARN ") && lines[0].ends_with("second task_id=t-1"));
+        assert!(lines[1].contains("ERROR") && lines[1].ends_with("third"));
+    }
+
+    #[test]
+    fn test_panic_message() {
+        assert_eq!(panic_message(&"boom"), "boom");
+        assert_eq!(panic_message(&"boom".to_string()), "boom");
+        assert_eq!(panic_message(&42), "Box<dyn Any>");
+    }
+
+    #[test]
+    fn test_write_crash_report() {
+        let directory = std::env::temp_dir().join(format!("crash-report-test-{}", std::process::id()));
+        let report = CrashReport {
+            product: "Test Server",
+            version: "1.2.3",
+            message: "boom",
+            location: "src/lib.rs:1:1",
+            trace_id: Some("trace-1"),
+        };
+        let path = report.write(&directory, &["log line".to_string()]).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_dir_all(&directory);
+        assert!(content.starts_with("Test Server crash report\nversion: 1.2.3\n"));
+        assert!(content.contains("trace_id: trace-1\nmessage: boom\nlocation: src/lib.rs:1:1\n"));
+        assert!(content.ends_with("recent logs (1):\nlog line\n"));
+    }
+}
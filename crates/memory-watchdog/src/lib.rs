@@ -0,0 +1,233 @@
+//! # Memory Watchdog
+//!
+//! 各HTTP服务共用的内存自监控。
+//!
+//! [`MemoryWatchdog`] 定期读取进程常驻内存（RSS），按软、硬上限划分内存压力：
+//!
+//! - 超过软上限：清空进程内缓存，具体清空什么由服务在 [`MemoryWatchdog::start`] 中传入
+//! - 超过硬上限：同样清空缓存，服务的中间件再用 [`MemoryWatchdog::should_reject_body`] 拒绝较大的请求体，直到内存回落
+//!
+//! 缓存只在压力升高时清空一次，压力持续期间不会反复清空。
+//! RSS 从 `/proc/self/status` 读取，其他平台上读取不到时保持正常状态。
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// 内存压力
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemoryPressure {
+    /// 低于软上限
+    Normal,
+    /// 超过软上限
+    Elevated,
+    /// 超过硬上限
+    Critical,
+}
+
+impl MemoryPressure {
+    /// 压力名称，用于日志和统计
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MemoryPressure::Normal => "normal",
+            MemoryPressure::Elevated => "elevated",
+            MemoryPressure::Critical => "critical",
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            2 => MemoryPressure::Critical,
+            1 => MemoryPressure::Elevated,
+            _ => MemoryPressure::Normal,
+        }
+    }
+}
+
+/// 读取进程常驻内存（字节）
+pub fn resident_memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// 内存上限，上限为0表示不检查
+#[derive(Debug, Clone)]
+pub struct MemoryLimits {
+    /// 检查间隔
+    pub check_interval: Duration,
+    /// 软上限（字节），超过时清空缓存
+    pub soft_limit_bytes: u64,
+    /// 硬上限（字节），超过时拒绝大请求体
+    pub hard_limit_bytes: u64,
+    /// 超过硬上限时允许的最大请求体（字节）
+    pub max_body_bytes_under_pressure: u64,
+}
+
+/// 内存自监控统计快照
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryWatchdogStats {
+    pub rss_bytes: u64,
+    pub pressure: &'static str,
+    /// 因内存压力清空缓存的次数
+    pub evictions: u64,
+}
+
+/// 内存自监控
+pub struct MemoryWatchdog {
+    limits: MemoryLimits,
+    rss_bytes: AtomicU64,
+    pressure: AtomicU8,
+    evictions: AtomicU64,
+}
+
+impl MemoryWatchdog {
+    /// 使用上限创建内存自监控，需调用 [`start`](Self::start) 开始检查
+    pub fn new(limits: MemoryLimits) -> Self {
+        Self {
+            limits,
+            rss_bytes: AtomicU64::new(0),
+            pressure: AtomicU8::new(MemoryPressure::Normal as u8),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// 最近一次读取的常驻内存（字节）
+    pub fn rss_bytes(&self) -> u64 {
+        self.rss_bytes.load(Ordering::Relaxed)
+    }
+
+    /// 当前内存压力
+    pub fn pressure(&self) -> MemoryPressure {
+        MemoryPressure::from_u8(self.pressure.load(Ordering::Relaxed))
+    }
+
+    /// 获取统计快照
+    pub fn stats(&self) -> MemoryWatchdogStats {
+        MemoryWatchdogStats {
+            rss_bytes: self.rss_bytes(),
+            pressure: self.pressure().as_str(),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    fn classify(&self, rss: u64) -> MemoryPressure {
+        let over = |limit: u64| limit > 0 && rss >= limit;
+        if over(self.limits.hard_limit_bytes) {
+            MemoryPressure::Critical
+        } else if over(self.limits.soft_limit_bytes) {
+            MemoryPressure::Elevated
+        } else {
+            MemoryPressure::Normal
+        }
+    }
+
+    /// 记录一次读数，返回是否需要清空缓存（压力升高到软上限以上）
+    pub fn observe(&self, rss: u64) -> bool {
+        let pressure = self.classify(rss);
+        self.rss_bytes.store(rss, Ordering::Relaxed);
+        let previous = MemoryPressure::from_u8(self.pressure.swap(pressure as u8, Ordering::Relaxed));
+        if pressure != previous {
+            tracing::warn!(
+                rss_bytes = rss,
+                from = previous.as_str(),
+                to = pressure.as_str(),
+                "Memory pressure changed"
+            );
+        }
+        pressure > previous && pressure >= MemoryPressure::Elevated
+    }
+
+    /// 内存超过硬上限时，拒绝声明长度超过上限的请求体
+    pub fn should_reject_body(&self, content_length: u64) -> bool {
+        self.pressure() == MemoryPressure::Critical && content_length > self.limits.max_body_bytes_under_pressure
+    }
+
+    /// 启动后台检查，压力升高时等待 `evict` 清空缓存
+    ///
+    /// `on_sample` 在每次读数后调用，参数为本次是否清空了缓存，用于更新指标。
+    pub fn start<E, F, S>(self: Arc<Self>, evict: E, on_sample: S)
+    where
+        E: Fn() -> F + Send + Sync + 'static,
+        F: Future<Output = ()> + Send,
+        S: Fn(&MemoryWatchdog, bool) + Send + Sync + 'static,
+    {
+        let interval = self.limits.check_interval.max(Duration::from_secs(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                let Some(rss) = resident_memory_bytes() else {
+                    continue;
+                };
+                let evicted = self.observe(rss);
+                if evicted {
+                    evict().await;
+                    self.evictions.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!(rss_bytes = rss, pressure = self.pressure().as_str(), "Evicted in-memory caches under memory pressure");
+                }
+                on_sample(&self, evicted);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watchdog() -> MemoryWatchdog {
+        MemoryWatchdog::new(MemoryLimits {
+            check_interval: Duration::from_secs(10),
+            soft_limit_bytes: 100,
+            hard_limit_bytes: 200,
+            max_body_bytes_under_pressure: 10,
+        })
+    }
+
+    #[test]
+    fn test_pressure_transitions() {
+        let watchdog = watchdog();
+        assert!(!watchdog.observe(50));
+        assert_eq!(watchdog.pressure(), MemoryPressure::Normal);
+
+        // 只在压力升高时清空缓存
+        assert!(watchdog.observe(150));
+        assert!(!watchdog.observe(160));
+        assert!(!watchdog.should_reject_body(1000));
+        assert!(watchdog.observe(250));
+        assert_eq!(watchdog.pressure(), MemoryPressure::Critical);
+        assert!(watchdog.should_reject_body(11));
+        assert!(!watchdog.should_reject_body(10));
+
+        assert!(!watchdog.observe(150));
+        assert!(!watchdog.should_reject_body(1000));
+        assert!(!watchdog.observe(50));
+        assert!(watchdog.observe(150));
+        assert_eq!(watchdog.stats().rss_bytes, 150);
+        assert_eq!(watchdog.stats().pressure, "elevated");
+    }
+
+    #[test]
+    fn test_zero_limits_disable_checks() {
+        let watchdog = MemoryWatchdog::new(MemoryLimits {
+            check_interval: Duration::from_secs(10),
+            soft_limit_bytes: 0,
+            hard_limit_bytes: 0,
+            max_body_bytes_under_pressure: 0,
+        });
+        assert!(!watchdog.observe(u64::MAX));
+        assert_eq!(watchdog.pressure(), MemoryPressure::Normal);
+    }
+
+    #[test]
+    fn test_resident_memory_bytes() {
+        if cfg!(target_os = "linux") {
+            assert!(resident_memory_bytes().unwrap() > 0);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! # Message Catalog
+//!
+//! 各服务共用的本地化基础设施。
+//!
+//! - [`Locale`]：支持的语言，可从语言标签或 `Accept-Language` 协商
+//! - [`MessageCatalog`]：从嵌入的JSON消息目录加载，按语言查找并填充 `{0}`、`{1}` 等占位符
+//!
+//! 消息目录由各服务通过 `include_str!` 嵌入自己的 `locales/<tag>.json`。
+
+use std::collections::HashMap;
+
+/// 支持的语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[default]
+    En,
+    ZhCn,
+}
+
+impl Locale {
+    /// 全部支持的语言
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::ZhCn];
+
+    /// 解析语言标签，如 `zh`、`zh-CN`、`zh_Hans`、`en-US`
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag
+            .trim()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match primary.as_str() {
+            "zh" => Some(Locale::ZhCn),
+            "en" => Some(Locale::En),
+            _ => None,
+        }
+    }
+
+    /// 按 `Accept-Language` 的权重选择首个支持的语言
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        let mut candidates: Vec<(f32, Locale)> = header
+            .split(',')
+            .filter_map(|part| {
+                let mut pieces = part.split(';');
+                let locale = Self::from_tag(pieces.next()?)?;
+                let quality = pieces
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (quality > 0.0).then_some((quality, locale))
+            })
+            .collect();
+        // 稳定排序，同权重时保持原始顺序
+        candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        candidates.first().map(|(_, locale)| *locale)
+    }
+
+    /// 语言标签
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::ZhCn => "zh-CN",
+        }
+    }
+}
+
+/// 多语言消息目录
+pub struct MessageCatalog {
+    messages: HashMap<Locale, HashMap<String, String>>,
+}
+
+impl MessageCatalog {
+    /// 从各语言的JSON源（消息键 -> 模板）加载，源无效时panic：目录在编译期嵌入，无效即为程序缺陷
+    pub fn from_sources(sources: &[(Locale, &str)]) -> Self {
+        let messages = sources
+            .iter()
+            .map(|(locale, source)| {
+                let catalog = serde_json::from_str(source)
+                    .unwrap_or_else(|e| panic!("invalid {} message catalog: {}", locale.tag(), e));
+                (*locale, catalog)
+            })
+            .collect();
+        Self { messages }
+    }
+
+    /// 查找消息模板并填充 `{0}`、`{1}` 等占位符
+    ///
+    /// 当前语言缺少该消息时回退到英文，英文也缺少时返回消息键本身。
+    pub fn translate(&self, locale: Locale, key: &str, args: &[String]) -> String {
+        let template = self
+            .lookup(locale, key)
+            .or_else(|| self.lookup(Locale::En, key))
+            .unwrap_or(key);
+
+        args.iter()
+            .enumerate()
+            .fold(template.to_string(), |message, (i, arg)| message.replace(&format!("{{{}}}", i), arg))
+    }
+
+    /// 查找消息模板，不存在时返回 `None`
+    pub fn lookup(&self, locale: Locale, key: &str) -> Option<&str> {
+        self.messages.get(&locale)?.get(key).map(String::as_str)
+    }
+
+    /// 只在部分语言中出现的消息键，用于测试各语言目录是否同步
+    pub fn inconsistent_keys(&self) -> Vec<String> {
+        let mut all: Vec<&String> = self.messages.values().flat_map(|catalog| catalog.keys()).collect();
+        all.sort();
+        all.dedup();
+        all.into_iter()
+            .filter(|key| self.messages.values().any(|catalog| !catalog.contains_key(*key)))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_negotiation() {
+        assert_eq!(Locale::from_accept_language("zh-CN,zh;q=0.9,en;q=0.8"), Some(Locale::ZhCn));
+        assert_eq!(Locale::from_accept_language("fr-FR, en;q=0.5, zh;q=0.7"), Some(Locale::ZhCn));
+        assert_eq!(Locale::from_accept_language("fr-FR"), None);
+        assert_eq!(Locale::from_tag("zh_Hans"), Some(Locale::ZhCn));
+    }
+
+    #[test]
+    fn test_translate_with_fallback() {
+        let catalog = MessageCatalog::from_sources(&[
+            (Locale::En, r#"{"greet": "Hello {0}", "only_en": "English"}"#),
+            (Locale::ZhCn, r#"{"greet": "你好 {0}"}"#),
+        ]);
+        assert_eq!(catalog.translate(Locale::ZhCn, "greet", &["Ann".to_string()]), "你好 Ann");
+        assert_eq!(catalog.translate(Locale::ZhCn, "only_en", &[]), "English");
+        assert_eq!(catalog.translate(Locale::En, "missing.key", &[]), "missing.key");
+        assert_eq!(catalog.inconsistent_keys(), vec!["only_en".to_string()]);
+    }
+}
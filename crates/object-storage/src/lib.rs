@@ -0,0 +1,61 @@
+//! # Object Storage
+//!
+//! 各服务共用的对象存储访问层。
+//!
+//! - [`ObjectStore`]：对象存储接口，任务编排服务用它保存任务产物，JSON验证服务用它加载Schema
+//! - [`S3ObjectStore`]：S3兼容实现（AWS S3、MinIO、Ceph RGW等），使用 SigV4 签名
+//! - [`MemoryObjectStore`]：内存实现，适合测试
+//! - [`ObjectStoreError`]：统一的错误类型，各服务再转换为自己的应用错误
+
+use std::time::Duration;
+
+mod memory;
+mod s3;
+
+pub use memory::MemoryObjectStore;
+pub use s3::{S3ObjectStore, S3Settings};
+
+/// 对象存储特征
+#[async_trait::async_trait]
+pub trait ObjectStore: Send + Sync {
+    /// 上传对象
+    async fn put_object(&self, key: &str, data: Vec<u8>, content_type: &str) -> ObjectStoreResult<()>;
+
+    /// 下载对象，对象不存在时返回 `None`
+    async fn get_object(&self, key: &str) -> ObjectStoreResult<Option<Vec<u8>>>;
+
+    /// 对象是否存在
+    async fn head_object(&self, key: &str) -> ObjectStoreResult<bool>;
+
+    /// 在存储内部复制对象，保留原对象的内容类型；源对象不存在时返回 `Ok(false)`
+    async fn copy_object(&self, source: &str, destination: &str) -> ObjectStoreResult<bool>;
+
+    /// 删除对象
+    async fn delete_object(&self, key: &str) -> ObjectStoreResult<()>;
+
+    /// 列出指定前缀下的全部对象键（按字典序）
+    async fn list_objects(&self, prefix: &str) -> ObjectStoreResult<Vec<String>>;
+
+    /// 生成带签名的下载地址
+    fn presigned_get_url(&self, key: &str, expires_in: Duration) -> ObjectStoreResult<String>;
+}
+
+/// 对象存储错误
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ObjectStoreError {
+    /// 配置无效（端点地址等）
+    #[error("Invalid object storage configuration: {0}")]
+    Config(String),
+    /// 请求未能送达或响应读取失败
+    #[error("Object storage request failed: {0}")]
+    Request(String),
+    /// 服务端返回错误状态
+    #[error("Object storage returned {status}: {body}")]
+    Status { status: u16, body: String },
+    /// 响应内容不符合协议
+    #[error("Unexpected object storage response: {0}")]
+    Protocol(String),
+}
+
+/// 对象存储操作结果
+pub type ObjectStoreResult<T> = Result<T, ObjectStoreError>;
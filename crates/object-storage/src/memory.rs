@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::{ObjectStore, ObjectStoreResult};
+
+/// 内存对象存储
+///
+/// 签名URL使用 `memory://` 协议，只用于断言，无法真正下载。
+#[derive(Default)]
+pub struct MemoryObjectStore {
+    objects: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+impl MemoryObjectStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStore for MemoryObjectStore {
+    async fn put_object(&self, key: &str, data: Vec<u8>, _content_type: &str) -> ObjectStoreResult<()> {
+        self.objects.lock().unwrap().insert(key.to_string(), data);
+        Ok(())
+    }
+
+    async fn get_object(&self, key: &str) -> ObjectStoreResult<Option<Vec<u8>>> {
+        Ok(self.objects.lock().unwrap().get(key).cloned())
+    }
+
+    async fn head_object(&self, key: &str) -> ObjectStoreResult<bool> {
+        Ok(self.objects.lock().unwrap().contains_key(key))
+    }
+
+    async fn copy_object(&self, source: &str, destination: &str) -> ObjectStoreResult<bool> {
+        let mut objects = self.objects.lock().unwrap();
+        let Some(data) = objects.get(source).cloned() else {
+            return Ok(false);
+        };
+        objects.insert(destination.to_string(), data);
+        Ok(true)
+    }
+
+    async fn delete_object(&self, key: &str) -> ObjectStoreResult<()> {
+        self.objects.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn list_objects(&self, prefix: &str) -> ObjectStoreResult<Vec<String>> {
+        Ok(self
+            .objects
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+
+    fn presigned_get_url(&self, key: &str, expires_in: Duration) -> ObjectStoreResult<String> {
+        Ok(format!("memory://{}?expires={}", key, expires_in.as_secs()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_store_round_trip() {
+        let store = MemoryObjectStore::new();
+        store.put_object("a/1.json", b"{}".to_vec(), "application/json").await.unwrap();
+        store.put_object("b/2.json", b"[]".to_vec(), "application/json").await.unwrap();
+
+        assert!(store.head_object("a/1.json").await.unwrap());
+        assert_eq!(store.list_objects("a/").await.unwrap(), vec!["a/1.json".to_string()]);
+        assert!(store.copy_object("a/1.json", "c/1.json").await.unwrap());
+        assert!(!store.copy_object("missing.json", "c/2.json").await.unwrap());
+        assert_eq!(store.get_object("c/1.json").await.unwrap(), Some(b"{}".to_vec()));
+        store.delete_object("a/1.json").await.unwrap();
+        assert_eq!(store.get_object("a/1.json").await.unwrap(), None);
+    }
+}
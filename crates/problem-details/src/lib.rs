@@ -0,0 +1,222 @@
+//! # Problem Details
+//!
+//! 各HTTP服务共用的 RFC 7807 错误响应。
+//!
+//! - [`ProblemDetails`]：错误响应体，序列化为 `application/problem+json`，并在响应头中携带追踪ID
+//! - [`parse_trace_id`]、[`parse_correlation_id`]：校验调用方传入的追踪ID和关联ID
+//!
+//! 追踪ID默认随机生成，服务在请求上下文中拿到调用方的追踪ID后通过
+//! [`ProblemDetails::with_trace_id`] 沿用，错误标题的本地化等也由各服务自行处理。
+
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// RFC 7807 错误响应的内容类型
+pub const PROBLEM_JSON_CONTENT_TYPE: &str = "application/problem+json";
+
+/// 追踪ID请求/响应头
+pub const TRACE_ID_HEADER: &str = "x-trace-id";
+
+/// 追踪ID的备选请求头
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// 客户端关联ID请求/响应头
+pub const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+
+/// 错误码文档地址
+pub const ERROR_DOCS_BASE_URL: &str = "https://github.com/ModerRAS/RustMCPServers/blob/main/docs/errors.md";
+
+/// 追踪ID和关联ID的最大长度
+const MAX_ID_LEN: usize = 128;
+
+/// 获取错误码对应的文档地址
+pub fn error_docs_url(code: &str) -> String {
+    format!("{}#{}", ERROR_DOCS_BASE_URL, code.to_lowercase())
+}
+
+/// 检查调用方传入的ID，只接受不超过128个可见ASCII字符的值
+fn parse_id(value: &str) -> Option<String> {
+    let value = value.trim();
+    let valid = !value.is_empty() && value.len() <= MAX_ID_LEN && value.chars().all(|c| c.is_ascii_graphic());
+    valid.then(|| value.to_string())
+}
+
+/// 检查调用方传入的追踪ID，无效值返回 `None`
+pub fn parse_trace_id(value: &str) -> Option<String> {
+    parse_id(value)
+}
+
+/// 检查客户端传入的关联ID，无效值返回 `None`
+pub fn parse_correlation_id(value: &str) -> Option<String> {
+    parse_id(value)
+}
+
+/// 从请求头中取出调用方的追踪ID（`x-trace-id`，其次 `x-request-id`）
+pub fn incoming_trace_id(headers: &HeaderMap) -> Option<String> {
+    [TRACE_ID_HEADER, REQUEST_ID_HEADER]
+        .into_iter()
+        .filter_map(|name| headers.get(name)?.to_str().ok())
+        .find_map(parse_trace_id)
+}
+
+/// RFC 7807 问题详情
+///
+/// 时间戳类型可由服务替换为按请求协商格式输出的类型，默认序列化为RFC 3339。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProblemDetails<T = DateTime<Utc>> {
+    /// 错误码文档地址
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    /// 错误标题
+    pub title: String,
+    /// HTTP状态码
+    pub status: u16,
+    /// 错误详情
+    pub detail: String,
+    /// 出错的请求路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// 机器可读的错误码
+    pub code: String,
+    /// 追踪ID
+    pub trace_id: String,
+    /// 客户端传入的关联ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    /// 附加信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+    /// 时间戳
+    pub timestamp: T,
+    /// 标题和详情的语言，设置后写入 `Content-Language` 响应头
+    #[serde(skip)]
+    pub language: Option<String>,
+}
+
+impl<T: From<DateTime<Utc>>> ProblemDetails<T> {
+    /// 创建新的问题详情，标题取HTTP状态的标准描述，追踪ID随机生成
+    pub fn new(status: StatusCode, code: &str, detail: String) -> Self {
+        Self {
+            problem_type: error_docs_url(code),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            detail,
+            instance: None,
+            code: code.to_string(),
+            trace_id: uuid::Uuid::new_v4().to_string(),
+            correlation_id: None,
+            details: None,
+            timestamp: Utc::now().into(),
+            language: None,
+        }
+    }
+}
+
+impl<T> ProblemDetails<T> {
+    /// 替换错误标题（如本地化后的标题）
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// 带请求路径的问题详情
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// 沿用请求的追踪ID
+    pub fn with_trace_id(mut self, trace_id: impl Into<String>) -> Self {
+        self.trace_id = trace_id.into();
+        self
+    }
+
+    /// 带客户端关联ID的问题详情
+    pub fn with_correlation_id(mut self, correlation_id: Option<String>) -> Self {
+        self.correlation_id = correlation_id;
+        self
+    }
+
+    /// 带附加信息的问题详情
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// 设置标题和详情的语言
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+}
+
+impl<T: Serialize> IntoResponse for ProblemDetails<T> {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON_CONTENT_TYPE));
+        if let Ok(value) = HeaderValue::from_str(&self.trace_id) {
+            headers.insert(HeaderName::from_static(TRACE_ID_HEADER), value);
+        }
+        if let Some(value) = self.correlation_id.as_deref().and_then(|id| HeaderValue::from_str(id).ok()) {
+            headers.insert(HeaderName::from_static(CORRELATION_ID_HEADER), value);
+        }
+        if let Some(value) = self.language.as_deref().and_then(|lang| HeaderValue::from_str(lang).ok()) {
+            headers.insert(header::CONTENT_LANGUAGE, value);
+        }
+
+        let mut response = (status, axum::Json(self)).into_response();
+        // Json 会写入 application/json，这里再覆盖为 problem+json
+        response.headers_mut().extend(headers);
+        response
+    }
+}
+
+/// 辅助函数可以返回装箱的问题详情以减小 `Result` 的体积，处理器中用 `?` 直接解箱
+impl<T> From<Box<ProblemDetails<T>>> for ProblemDetails<T> {
+    fn from(problem: Box<ProblemDetails<T>>) -> Self {
+        *problem
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ids() {
+        assert_eq!(parse_trace_id(" abc-123 "), Some("abc-123".to_string()));
+        assert_eq!(parse_trace_id(""), None);
+        assert_eq!(parse_correlation_id("has space"), None);
+        assert_eq!(parse_correlation_id(&"x".repeat(129)), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static("req-1"));
+        assert_eq!(incoming_trace_id(&headers), Some("req-1".to_string()));
+        headers.insert(TRACE_ID_HEADER, HeaderValue::from_static("trace-1"));
+        assert_eq!(incoming_trace_id(&headers), Some("trace-1".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_into_response() {
+        let problem: ProblemDetails = ProblemDetails::new(StatusCode::NOT_FOUND, "NOT_FOUND", "missing".to_string())
+            .with_trace_id("trace-1")
+            .with_instance("/x")
+            .with_language("en");
+        let response = problem.into_response();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let headers = response.headers();
+        assert_eq!(headers[header::CONTENT_TYPE], PROBLEM_JSON_CONTENT_TYPE);
+        assert_eq!(headers[TRACE_ID_HEADER], "trace-1");
+        assert_eq!(headers[header::CONTENT_LANGUAGE], "en");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["type"], error_docs_url("NOT_FOUND"));
+        assert_eq!((json["title"].as_str(), json["instance"].as_str()), (Some("Not Found"), Some("/x")));
+        assert!(json.get("correlation_id").is_none());
+    }
+}
@@ -0,0 +1,223 @@
+//! # Request Decompression
+//!
+//! 各HTTP服务共用的压缩请求体解压。
+//!
+//! 客户端可以用 `Content-Encoding: gzip` 或 `deflate` 压缩请求体，服务在进入处理器之前解压，并去掉 `Content-Encoding` 头。
+//! 解压以流式进行，同时限制解压后大小和压缩比，超过任一限制立即停止，避免压缩炸弹占满内存。
+//! `deflate` 同时接受带zlib头（RFC 9110）和不带头的原始deflate数据。
+//!
+//! - [`Decompressor`]：带大小和压缩比上限的解压器
+//! - [`decompress_request`]：解压请求体并改写请求头，各服务在自己的中间件中调用
+//! - [`DecompressError`]：解压失败的原因，各服务再转换为自己的错误响应
+
+use std::io::Read;
+
+use axum::body::Body;
+use axum::http::{header, HeaderValue, Request};
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+
+/// 解压后不超过该大小时不检查压缩比，较小的JSON本身就可能有很高的压缩比
+const RATIO_CHECK_THRESHOLD: usize = 64 * 1024;
+
+/// 每次读取的解压块大小
+const CHUNK_SIZE: usize = 16 * 1024;
+
+/// 支持的内容编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncoding {
+    Gzip,
+    Deflate,
+}
+
+impl ContentEncoding {
+    /// 解析 `Content-Encoding` 头，`identity` 返回 `None`，不支持的编码返回错误
+    pub fn parse(value: &str) -> Result<Option<Self>, DecompressError> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "identity" => Ok(None),
+            "gzip" | "x-gzip" => Ok(Some(Self::Gzip)),
+            "deflate" => Ok(Some(Self::Deflate)),
+            other => Err(DecompressError::Unsupported(other.to_string())),
+        }
+    }
+}
+
+/// 解压失败的原因
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DecompressError {
+    /// 不支持或未启用的编码，携带编码名称
+    #[error("Unsupported Content-Encoding: {0}")]
+    Unsupported(String),
+    /// 压缩数据损坏
+    #[error("Failed to decompress request body: {0}")]
+    Corrupt(String),
+    /// 压缩或解压后的大小、压缩比超过上限
+    #[error("{0}")]
+    TooLarge(String),
+}
+
+/// 请求体解压器
+#[derive(Debug, Clone)]
+pub struct Decompressor {
+    max_decompressed_size: usize,
+    max_ratio: usize,
+}
+
+impl Decompressor {
+    /// 创建解压器，解压后最多 `max_decompressed_size` 字节、最多膨胀 `max_ratio` 倍
+    pub fn new(max_decompressed_size: usize, max_ratio: usize) -> Self {
+        Self {
+            max_decompressed_size,
+            max_ratio: max_ratio.max(1),
+        }
+    }
+
+    /// 解压后大小上限
+    pub fn max_decompressed_size(&self) -> usize {
+        self.max_decompressed_size
+    }
+
+    /// 解压数据，超过大小或压缩比限制时返回 [`DecompressError::TooLarge`]
+    pub fn decompress(&self, encoding: ContentEncoding, compressed: &[u8]) -> Result<Vec<u8>, DecompressError> {
+        let ratio_limit = compressed.len().saturating_mul(self.max_ratio).max(RATIO_CHECK_THRESHOLD);
+        let limit = self.max_decompressed_size.min(ratio_limit);
+
+        let mut decoder: Box<dyn Read + '_> = match encoding {
+            ContentEncoding::Gzip => Box::new(GzDecoder::new(compressed)),
+            ContentEncoding::Deflate if has_zlib_header(compressed) => Box::new(ZlibDecoder::new(compressed)),
+            ContentEncoding::Deflate => Box::new(DeflateDecoder::new(compressed)),
+        };
+
+        let mut output = Vec::new();
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        loop {
+            let read = decoder
+                .read(&mut chunk)
+                .map_err(|e| DecompressError::Corrupt(format!("{:?}: {}", encoding, e)))?;
+            if read == 0 {
+                return Ok(output);
+            }
+            if output.len() + read > limit {
+                return Err(DecompressError::TooLarge(if limit < self.max_decompressed_size {
+                    format!("Request body expands more than {}x when decompressed", self.max_ratio)
+                } else {
+                    format!("Decompressed request body exceeds {} bytes", self.max_decompressed_size)
+                }));
+            }
+            output.extend_from_slice(&chunk[..read]);
+        }
+    }
+}
+
+/// zlib头：压缩方法为deflate且头校验和正确
+fn has_zlib_header(data: &[u8]) -> bool {
+    match data {
+        [cmf, flg, ..] => cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0,
+        _ => false,
+    }
+}
+
+/// 解压带 `Content-Encoding` 的请求体，去掉该头并改写 `Content-Length`
+///
+/// 没有编码或编码为 `identity` 的请求原样返回；`decompressor` 为 `None`（未启用解压）时压缩请求体按不支持的编码拒绝。
+pub async fn decompress_request(
+    decompressor: Option<&Decompressor>,
+    request: Request<Body>,
+) -> Result<Request<Body>, DecompressError> {
+    let Some(value) = request.headers().get(header::CONTENT_ENCODING) else {
+        return Ok(request);
+    };
+    let value = value
+        .to_str()
+        .map_err(|_| DecompressError::Unsupported("<non-ascii>".to_string()))?;
+    let Some(encoding) = ContentEncoding::parse(value)? else {
+        return Ok(request);
+    };
+    let decompressor = decompressor.ok_or_else(|| DecompressError::Unsupported(value.trim().to_string()))?;
+
+    let (mut parts, body) = request.into_parts();
+    // 压缩数据本身不会超过解压后的大小上限
+    let compressed = axum::body::to_bytes(body, decompressor.max_decompressed_size)
+        .await
+        .map_err(|_| {
+            DecompressError::TooLarge(format!("Request body exceeds {} bytes", decompressor.max_decompressed_size))
+        })?;
+    let decompressed = decompressor.decompress(encoding, &compressed)?;
+
+    parts.headers.remove(header::CONTENT_ENCODING);
+    parts.headers.insert(header::CONTENT_LENGTH, HeaderValue::from(decompressed.len()));
+    Ok(Request::from_parts(parts, Body::from(decompressed)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Method;
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn request(encoding: &str, body: Vec<u8>) -> Request<Body> {
+        Request::builder()
+            .method(Method::POST)
+            .uri("/rpc")
+            .header(header::CONTENT_ENCODING, encoding)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_decompress_gzip_and_deflate() {
+        let body = br#"{"work_directory":"/srv","prompt":"hello"}"#;
+        let decompressor = Decompressor::new(1024, 10);
+        assert_eq!(decompressor.decompress(ContentEncoding::Gzip, &gzip(body)).unwrap(), body);
+
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(body).unwrap();
+        let zlib = zlib.finish().unwrap();
+        assert_eq!(decompressor.decompress(ContentEncoding::Deflate, &zlib).unwrap(), body);
+
+        let mut raw = flate2::write::DeflateEncoder::new(Vec::new(), Compression::default());
+        raw.write_all(body).unwrap();
+        assert_eq!(decompressor.decompress(ContentEncoding::Deflate, &raw.finish().unwrap()).unwrap(), body);
+
+        assert!(matches!(
+            decompressor.decompress(ContentEncoding::Gzip, b"not gzip"),
+            Err(DecompressError::Corrupt(_))
+        ));
+        assert_eq!(ContentEncoding::parse("br"), Err(DecompressError::Unsupported("br".to_string())));
+        assert_eq!(ContentEncoding::parse("identity").unwrap(), None);
+    }
+
+    #[test]
+    fn test_decompress_limits() {
+        // 1MB的零压缩后约1KB，压缩比约1000
+        let bomb = gzip(&vec![0u8; 1024 * 1024]);
+        let error = Decompressor::new(8 * 1024 * 1024, 100).decompress(ContentEncoding::Gzip, &bomb).unwrap_err();
+        assert!(error.to_string().contains("expands more than 100x"));
+
+        let error = Decompressor::new(512 * 1024, 10_000).decompress(ContentEncoding::Gzip, &bomb).unwrap_err();
+        assert!(error.to_string().contains("exceeds 524288 bytes"));
+
+        assert_eq!(Decompressor::new(8 * 1024 * 1024, 10_000).decompress(ContentEncoding::Gzip, &bomb).unwrap().len(), 1024 * 1024);
+    }
+
+    #[tokio::test]
+    async fn test_decompress_request_rewrites_headers() {
+        let body = br#"{"prompt":"hello"}"#;
+        let decompressed = decompress_request(Some(&Decompressor::new(1024, 10)), request("gzip", gzip(body))).await.unwrap();
+        assert!(decompressed.headers().get(header::CONTENT_ENCODING).is_none());
+        assert_eq!(decompressed.headers()[header::CONTENT_LENGTH], body.len().to_string().as_str());
+        assert_eq!(&axum::body::to_bytes(decompressed.into_body(), 1024).await.unwrap()[..], body);
+
+        // 未启用时拒绝压缩请求体，identity 原样通过
+        let result = decompress_request(None, request("gzip", gzip(body))).await;
+        assert_eq!(result.unwrap_err(), DecompressError::Unsupported("gzip".to_string()));
+        assert!(decompress_request(None, request("identity", body.to_vec())).await.is_ok());
+    }
+}
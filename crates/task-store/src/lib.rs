@@ -0,0 +1,47 @@
+//! # Task Store
+//!
+//! 各任务编排服务共用的存储抽象。
+//!
+//! - [`LockManager`]：分布式锁接口，SQLite、Redis等后端各实现一次即可在所有服务中使用
+//! - [`MemoryLockManager`]：分片的内存锁实现，适合单实例部署和测试
+//! - [`StoreError`]：存储后端统一返回的错误类型，各服务再转换为自己的应用错误
+//! - [`TaskRepository`]：任务仓库接口，只包含各服务共同需要的存取和调度操作
+//! - [`StoredTask`]、[`TaskPhase`]：存储后端读取任务所需的属性，各服务的任务模型实现 [`StoredTask`]
+//!   即可使用共享后端，任务本身的字段和序列化格式保持不变
+//! - [`MemoryTaskRepository`]：按工作目录维护优先队列的内存任务仓库
+//!
+//! `simple-task-orchestrator` 和 `task-orchestrator-mcp` 的内存存储直接使用 [`MemoryTaskRepository`]，
+//! `task-orchestrator` 的SQLite仓库也实现了 [`TaskRepository`]；各服务自己的仓库特征只保留列表过滤、
+//! 统计等服务特有的查询。
+
+mod lock;
+mod memory;
+mod task;
+
+pub use lock::{LockManager, MemoryLockManager, DEFAULT_LOCK_CLEANUP_INTERVAL};
+pub use memory::MemoryTaskRepository;
+pub use task::{StoredTask, TaskChange, TaskPhase, TaskPredicate, TaskRepository};
+
+/// 存储后端错误
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum StoreError {
+    /// 记录不存在
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// 并发修改冲突
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    /// 后端访问失败（连接、查询等）
+    #[error("Storage backend error: {0}")]
+    Backend(String),
+}
+
+impl StoreError {
+    /// 把后端的任意错误包装为 [`StoreError::Backend`]
+    pub fn backend(err: impl std::fmt::Display) -> Self {
+        Self::Backend(err.to_string())
+    }
+}
+
+/// 存储操作结果
+pub type StoreResult<T> = Result<T, StoreError>;
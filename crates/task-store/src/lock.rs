@@ -0,0 +1,212 @@
+//! 分布式锁接口和内存实现
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
+
+use crate::StoreResult;
+
+/// 锁管理器特征
+#[async_trait]
+pub trait LockManager: Send + Sync {
+    /// 尝试获取锁，同一持有者再次获取只延长过期时间
+    async fn try_acquire(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> StoreResult<bool>;
+
+    /// 续约锁（仅持有者可续约）
+    async fn renew(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> StoreResult<bool>;
+
+    /// 释放锁（仅持有者可释放）
+    async fn release(&self, resource_id: &str, owner_id: &str) -> StoreResult<bool>;
+
+    /// 未过期锁的持有者
+    async fn check_lock(&self, resource_id: &str) -> StoreResult<Option<String>>;
+
+    /// 清理过期锁，返回清理数量
+    async fn cleanup_expired_locks(&self) -> StoreResult<u64>;
+}
+
+/// 锁表默认分片数量
+const DEFAULT_LOCK_SHARDS: usize = 16;
+
+/// 默认的过期锁后台清理间隔
+pub const DEFAULT_LOCK_CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 单个锁表分片：资源ID -> (持有者, 过期时间)
+type LockShard = RwLock<HashMap<String, (String, DateTime<Utc>)>>;
+
+/// 内存锁管理器
+///
+/// 锁表按资源ID的哈希分片，不同分片上的获取互不阻塞。过期的锁在读写时按未持有处理，
+/// 实际删除由 [`MemoryLockManager::start_cleanup`] 启动的后台任务完成。
+pub struct MemoryLockManager {
+    shards: Vec<LockShard>,
+}
+
+impl Default for MemoryLockManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryLockManager {
+    pub fn new() -> Self {
+        Self::with_shards(DEFAULT_LOCK_SHARDS)
+    }
+
+    /// 使用指定分片数量创建锁管理器
+    pub fn with_shards(shards: usize) -> Self {
+        Self {
+            shards: (0..shards.max(1)).map(|_| RwLock::new(HashMap::new())).collect(),
+        }
+    }
+
+    /// 当前锁表中的条目数（包括尚未清理的过期锁）
+    pub async fn lock_count(&self) -> usize {
+        let mut count = 0;
+        for shard in &self.shards {
+            count += shard.read().await.len();
+        }
+        count
+    }
+
+    /// 启动过期锁的后台清理任务，锁管理器被释放后任务自动退出
+    pub fn start_cleanup(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // 第一次tick立即返回，跳过
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                if let Ok(cleaned) = manager.cleanup_expired_locks().await {
+                    if cleaned > 0 {
+                        tracing::debug!("Cleaned up {} expired locks", cleaned);
+                    }
+                }
+            }
+        })
+    }
+
+    fn shard(&self, resource_id: &str) -> &LockShard {
+        let mut hasher = DefaultHasher::new();
+        resource_id.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % self.shards.len()]
+    }
+}
+
+fn expires_at(now: DateTime<Utc>, ttl_seconds: u64) -> DateTime<Utc> {
+    now + chrono::Duration::seconds(ttl_seconds as i64)
+}
+
+#[async_trait]
+impl LockManager for MemoryLockManager {
+    async fn try_acquire(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> StoreResult<bool> {
+        let mut locks = self.shard(resource_id).write().await;
+        let now = Utc::now();
+
+        if let Some((current_owner, current_expires_at)) = locks.get(resource_id) {
+            // 其他持有者的锁未过期时获取失败；同一持有者再次获取只更新过期时间
+            if current_owner != owner_id && *current_expires_at > now {
+                return Ok(false);
+            }
+        }
+
+        locks.insert(resource_id.to_string(), (owner_id.to_string(), expires_at(now, ttl_seconds)));
+        Ok(true)
+    }
+
+    async fn renew(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> StoreResult<bool> {
+        let mut locks = self.shard(resource_id).write().await;
+        let now = Utc::now();
+
+        match locks.get_mut(resource_id) {
+            Some((current_owner, current_expires_at)) if current_owner == owner_id && *current_expires_at > now => {
+                *current_expires_at = expires_at(now, ttl_seconds);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn release(&self, resource_id: &str, owner_id: &str) -> StoreResult<bool> {
+        let mut locks = self.shard(resource_id).write().await;
+
+        if let Some((current_owner, _)) = locks.get(resource_id) {
+            if current_owner == owner_id {
+                locks.remove(resource_id);
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    async fn check_lock(&self, resource_id: &str) -> StoreResult<Option<String>> {
+        let locks = self.shard(resource_id).read().await;
+        let now = Utc::now();
+        Ok(locks.get(resource_id)
+            .filter(|(_, expires_at)| *expires_at > now)
+            .map(|(owner, _)| owner.clone()))
+    }
+
+    async fn cleanup_expired_locks(&self) -> StoreResult<u64> {
+        let now = Utc::now();
+        let mut cleaned = 0;
+
+        // 逐个分片清理，每次只持有一个分片的写锁
+        for shard in &self.shards {
+            let mut locks = shard.write().await;
+            let initial_count = locks.len();
+            locks.retain(|_, (_, expires_at)| *expires_at > now);
+            cleaned += (initial_count - locks.len()) as u64;
+        }
+
+        Ok(cleaned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_acquire_renew_release() {
+        let locks = MemoryLockManager::with_shards(2);
+
+        assert!(locks.try_acquire("leader", "node-a", 30).await.unwrap());
+        assert!(!locks.try_acquire("leader", "node-b", 30).await.unwrap());
+        assert!(locks.try_acquire("leader", "node-a", 30).await.unwrap());
+        assert!(locks.renew("leader", "node-a", 30).await.unwrap());
+        assert!(!locks.renew("leader", "node-b", 30).await.unwrap());
+        assert_eq!(locks.check_lock("leader").await.unwrap(), Some("node-a".to_string()));
+
+        assert!(!locks.release("leader", "node-b").await.unwrap());
+        assert!(locks.release("leader", "node-a").await.unwrap());
+        assert_eq!(locks.check_lock("leader").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_expired_locks() {
+        let locks = MemoryLockManager::new();
+
+        // 过期的锁可以被其他持有者接管，原持有者不能再续约
+        assert!(locks.try_acquire("leader", "node-a", 0).await.unwrap());
+        assert_eq!(locks.check_lock("leader").await.unwrap(), None);
+        assert!(!locks.renew("leader", "node-a", 30).await.unwrap());
+        assert!(locks.try_acquire("leader", "node-b", 30).await.unwrap());
+
+        assert!(locks.try_acquire("other", "node-a", 0).await.unwrap());
+        assert_eq!(locks.lock_count().await, 2);
+        assert_eq!(locks.cleanup_expired_locks().await.unwrap(), 1);
+        assert_eq!(locks.lock_count().await, 1);
+    }
+}
@@ -0,0 +1,318 @@
+//! 内存任务仓库
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
+
+use crate::task::{StoredTask, TaskChange, TaskPhase, TaskPredicate, TaskRepository};
+use crate::{StoreError, StoreResult};
+
+/// 等待队列条目
+///
+/// 堆顶是优先级最高、创建最早的任务。任务状态变化时不会立即从堆中删除条目，
+/// 而是在查询时与任务当前状态比对，不再匹配的条目直接丢弃（惰性删除）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QueuedTask<I> {
+    priority: i32,
+    created_at: DateTime<Utc>,
+    task_id: I,
+}
+
+impl<I: Ord> QueuedTask<I> {
+    fn of<T: StoredTask<Id = I>>(task: &T) -> Self {
+        Self {
+            priority: task.priority(),
+            created_at: task.created_at(),
+            task_id: task.id(),
+        }
+    }
+
+    /// 条目是否仍对应该目录下一个等待中的任务
+    fn matches<T: StoredTask<Id = I>>(&self, task: &T, work_directory: &str) -> bool {
+        task.phase() == TaskPhase::Queued
+            && task.work_directory() == work_directory
+            && task.priority() == self.priority
+            && task.created_at() == self.created_at
+    }
+}
+
+impl<I: Ord> Ord for QueuedTask<I> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // 高优先级在前，同优先级先创建的在前
+        self.priority.cmp(&other.priority)
+            .then_with(|| other.created_at.cmp(&self.created_at))
+            .then_with(|| other.task_id.cmp(&self.task_id))
+    }
+}
+
+impl<I: Ord> PartialOrd for QueuedTask<I> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+type Queues<I> = HashMap<String, BinaryHeap<QueuedTask<I>>>;
+
+/// 内存任务仓库
+///
+/// 任务保存在哈希表中，等待中的任务另外按工作目录放入优先队列，获取下一个任务不需要扫描全表。
+/// 适合单实例部署和测试。
+pub struct MemoryTaskRepository<T: StoredTask> {
+    tasks: RwLock<HashMap<T::Id, T>>,
+    /// 按工作目录划分的等待队列，锁顺序为 `tasks` -> `queues`
+    queues: RwLock<Queues<T::Id>>,
+}
+
+impl<T: StoredTask> Default for MemoryTaskRepository<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: StoredTask> MemoryTaskRepository<T> {
+    pub fn new() -> Self {
+        Self {
+            tasks: RwLock::new(HashMap::new()),
+            queues: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+/// 丢弃堆顶不再等待或暂不可获取的条目，返回目录下第一个可获取的任务
+fn next_in<T: StoredTask>(tasks: &HashMap<T::Id, T>, queues: &mut Queues<T::Id>, work_directory: &str) -> Option<T::Id> {
+    let queue = queues.get_mut(work_directory)?;
+    while let Some(entry) = queue.peek() {
+        let claimable = tasks.get(&entry.task_id)
+            .is_some_and(|task| entry.matches(task, work_directory) && task.is_claimable());
+        if claimable {
+            return Some(entry.task_id);
+        }
+        queue.pop();
+    }
+
+    queues.remove(work_directory);
+    None
+}
+
+/// 保存后的任务仍在等待时按需加入所属目录的队列
+fn requeue<T: StoredTask>(queues: &mut Queues<T::Id>, previous: Option<&T>, task: &T) {
+    if task.phase() != TaskPhase::Queued {
+        return;
+    }
+    // 排序键、目录和可获取性都未变化的等待任务已经在队列中
+    let queued = previous.is_some_and(|previous| {
+        previous.phase() == TaskPhase::Queued
+            && previous.work_directory() == task.work_directory()
+            && previous.is_claimable() == task.is_claimable()
+            && QueuedTask::of(previous) == QueuedTask::of(task)
+    });
+    if !queued {
+        queues.entry(task.work_directory().to_string())
+            .or_default()
+            .push(QueuedTask::of(task));
+    }
+}
+
+#[async_trait]
+impl<T: StoredTask> TaskRepository<T> for MemoryTaskRepository<T> {
+    async fn insert(&self, task: &T) -> StoreResult<()> {
+        let mut tasks = self.tasks.write().await;
+        if tasks.contains_key(&task.id()) {
+            return Err(StoreError::Conflict(format!("task {} already exists", task.id())));
+        }
+        tasks.insert(task.id(), task.clone());
+        requeue(&mut *self.queues.write().await, None, task);
+        Ok(())
+    }
+
+    async fn get(&self, id: &T::Id) -> StoreResult<Option<T>> {
+        Ok(self.tasks.read().await.get(id).cloned())
+    }
+
+    async fn update(&self, task: &T) -> StoreResult<()> {
+        let mut tasks = self.tasks.write().await;
+        let previous = tasks.get(&task.id()).ok_or_else(|| StoreError::NotFound(task.id().to_string()))?;
+        requeue(&mut *self.queues.write().await, Some(previous), task);
+        tasks.insert(task.id(), task.clone());
+        Ok(())
+    }
+
+    async fn remove(&self, id: &T::Id) -> StoreResult<bool> {
+        Ok(self.tasks.write().await.remove(id).is_some())
+    }
+
+    async fn scan(&self) -> StoreResult<Vec<T>> {
+        Ok(self.tasks.read().await.values().cloned().collect())
+    }
+
+    async fn next_queued(&self, work_directory: &str) -> StoreResult<Option<T>> {
+        let tasks = self.tasks.read().await;
+        let mut queues = self.queues.write().await;
+        Ok(next_in(&tasks, &mut queues, work_directory).and_then(|id| tasks.get(&id).cloned()))
+    }
+
+    async fn modify(&self, id: &T::Id, change: TaskChange<'_, T>) -> StoreResult<T> {
+        let mut tasks = self.tasks.write().await;
+        let previous = tasks.get(id).ok_or_else(|| StoreError::NotFound(id.to_string()))?;
+        let mut task = previous.clone();
+        change(&mut task)?;
+        requeue(&mut *self.queues.write().await, Some(previous), &task);
+        tasks.insert(*id, task.clone());
+        Ok(task)
+    }
+
+    async fn claim_next(&self, work_directory: &str, change: TaskChange<'_, T>) -> StoreResult<Option<T>> {
+        let mut tasks = self.tasks.write().await;
+        let mut queues = self.queues.write().await;
+        let Some(id) = next_in(&tasks, &mut queues, work_directory) else {
+            return Ok(None);
+        };
+        let previous = &tasks[&id];
+        let mut task = previous.clone();
+        change(&mut task)?;
+        requeue(&mut queues, Some(previous), &task);
+        tasks.insert(id, task.clone());
+        Ok(Some(task))
+    }
+
+    async fn retain(&self, keep: TaskPredicate<'_, T>) -> StoreResult<u64> {
+        let mut tasks = self.tasks.write().await;
+        let initial_count = tasks.len();
+        tasks.retain(|_, task| keep(task));
+
+        // 顺便清理队列中已失效的条目，避免无人拉取的目录持续占用内存
+        let mut queues = self.queues.write().await;
+        for (work_directory, queue) in queues.iter_mut() {
+            queue.retain(|entry| {
+                tasks.get(&entry.task_id).is_some_and(|task| entry.matches(task, work_directory))
+            });
+        }
+        queues.retain(|_, queue| !queue.is_empty());
+
+        Ok((initial_count - tasks.len()) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestTask {
+        id: u32,
+        work_directory: String,
+        phase: TaskPhase,
+        priority: i32,
+        created_at: DateTime<Utc>,
+        claimable: bool,
+    }
+
+    impl StoredTask for TestTask {
+        type Id = u32;
+
+        fn id(&self) -> u32 {
+            self.id
+        }
+
+        fn work_directory(&self) -> &str {
+            &self.work_directory
+        }
+
+        fn phase(&self) -> TaskPhase {
+            self.phase
+        }
+
+        fn priority(&self) -> i32 {
+            self.priority
+        }
+
+        fn created_at(&self) -> DateTime<Utc> {
+            self.created_at
+        }
+
+        fn is_claimable(&self) -> bool {
+            self.claimable
+        }
+    }
+
+    fn queued(id: u32, priority: i32, age_seconds: i64) -> TestTask {
+        TestTask {
+            id,
+            work_directory: "/work".to_string(),
+            phase: TaskPhase::Queued,
+            priority,
+            created_at: Utc::now() - chrono::Duration::seconds(age_seconds),
+            claimable: true,
+        }
+    }
+
+    fn start(task: &mut TestTask) -> StoreResult<()> {
+        task.phase = TaskPhase::Running;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_claim_in_priority_order() {
+        let repo = MemoryTaskRepository::new();
+        repo.insert(&queued(1, 1, 30)).await.unwrap();
+        repo.insert(&queued(2, 3, 10)).await.unwrap();
+        repo.insert(&queued(3, 3, 20)).await.unwrap();
+        assert!(matches!(repo.insert(&queued(1, 1, 0)).await, Err(StoreError::Conflict(_))));
+
+        // 同优先级先创建的在前，获取后不再出现在队列中
+        assert_eq!(repo.next_queued("/work").await.unwrap().unwrap().id, 3);
+        let claimed: Vec<u32> = [
+            repo.claim_next("/work", &start).await.unwrap().unwrap(),
+            repo.claim_next("/work", &start).await.unwrap().unwrap(),
+            repo.claim_next("/work", &start).await.unwrap().unwrap(),
+        ].iter().map(|task| task.id).collect();
+        assert_eq!(claimed, vec![3, 2, 1]);
+        assert!(repo.claim_next("/work", &start).await.unwrap().is_none());
+        assert!(repo.next_queued("/other").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_requeue_after_update() {
+        let repo = MemoryTaskRepository::new();
+        let mut task = queued(1, 2, 0);
+        repo.insert(&task).await.unwrap();
+        repo.claim_next("/work", &start).await.unwrap().unwrap();
+
+        // 重新回到等待状态的任务再次入队；暂不可获取的任务被跳过
+        task.claimable = false;
+        repo.update(&task).await.unwrap();
+        assert!(repo.next_queued("/work").await.unwrap().is_none());
+        task.claimable = true;
+        repo.update(&task).await.unwrap();
+        assert_eq!(repo.next_queued("/work").await.unwrap(), Some(task));
+
+        assert!(matches!(repo.update(&queued(9, 1, 0)).await, Err(StoreError::NotFound(_))));
+        assert!(repo.get(&9).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_modify_and_retain() {
+        let repo = MemoryTaskRepository::new();
+        repo.insert(&queued(1, 1, 0)).await.unwrap();
+        repo.insert(&queued(2, 1, 0)).await.unwrap();
+
+        // 回调返回错误时任务保持不变
+        let rejected = repo.modify(&1, &|_| Err(StoreError::Conflict("busy".to_string()))).await;
+        assert!(matches!(rejected, Err(StoreError::Conflict(_))));
+        assert_eq!(repo.get(&1).await.unwrap().unwrap().phase, TaskPhase::Queued);
+        let finished = repo.modify(&1, &|task| {
+            task.phase = TaskPhase::Finished;
+            Ok(())
+        }).await.unwrap();
+        assert_eq!(finished.phase, TaskPhase::Finished);
+
+        assert_eq!(repo.retain(&|task| task.phase != TaskPhase::Finished).await.unwrap(), 1);
+        assert_eq!(repo.scan().await.unwrap().len(), 1);
+        assert!(repo.remove(&2).await.unwrap());
+        assert!(!repo.remove(&2).await.unwrap());
+        assert!(repo.next_queued("/work").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,86 @@
+//! 任务仓库接口和各服务任务模型的共同抽象
+
+use std::fmt;
+use std::hash::Hash;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::StoreResult;
+
+/// 任务在存储后端中的调度阶段
+///
+/// 各服务的任务状态不同（待审批、待定等），存储后端只需要区分能否被获取、是否已结束。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskPhase {
+    /// 不参与调度（例如等待审批）
+    Held,
+    /// 等待工作节点获取
+    Queued,
+    /// 已被获取，正在执行
+    Running,
+    /// 已完成、失败或取消
+    Finished,
+}
+
+/// 存储后端读取的任务属性
+///
+/// 各服务的任务模型实现本特征后即可使用共享的存储后端，任务本身的序列化格式不受影响。
+pub trait StoredTask: Clone + Send + Sync + 'static {
+    type Id: Copy + Eq + Ord + Hash + fmt::Display + Send + Sync + 'static;
+
+    fn id(&self) -> Self::Id;
+
+    fn work_directory(&self) -> &str;
+
+    fn phase(&self) -> TaskPhase;
+
+    /// 调度优先级，数值越大越先被获取
+    fn priority(&self) -> i32;
+
+    fn created_at(&self) -> DateTime<Utc>;
+
+    /// 等待中的任务当前能否被获取，默认总是可以
+    fn is_claimable(&self) -> bool {
+        true
+    }
+}
+
+/// 在读取和保存之间修改任务的回调，返回错误时不保存
+pub type TaskChange<'a, T> = &'a (dyn Fn(&mut T) -> StoreResult<()> + Send + Sync);
+
+/// 按任务内容判断的条件
+pub type TaskPredicate<'a, T> = &'a (dyn Fn(&T) -> bool + Send + Sync);
+
+/// 任务仓库特征
+///
+/// 只包含所有服务都需要的存取和调度操作；列表过滤、统计等查询由各服务在此之上实现，
+/// 或由后端以更高效的方式（如SQL）另外提供。
+#[async_trait]
+pub trait TaskRepository<T: StoredTask>: Send + Sync {
+    /// 保存新任务，ID已存在时返回 [`crate::StoreError::Conflict`]
+    async fn insert(&self, task: &T) -> StoreResult<()>;
+
+    async fn get(&self, id: &T::Id) -> StoreResult<Option<T>>;
+
+    /// 覆盖已有任务，任务不存在时返回 [`crate::StoreError::NotFound`]
+    async fn update(&self, task: &T) -> StoreResult<()>;
+
+    /// 删除任务，返回任务是否存在
+    async fn remove(&self, id: &T::Id) -> StoreResult<bool>;
+
+    /// 全部任务，顺序不定
+    async fn scan(&self) -> StoreResult<Vec<T>>;
+
+    /// 目录下可获取的任务中优先级最高、创建最早的一个，不修改任务
+    async fn next_queued(&self, work_directory: &str) -> StoreResult<Option<T>>;
+
+    /// 原子地读取、修改并保存任务，返回修改后的任务
+    async fn modify(&self, id: &T::Id, change: TaskChange<'_, T>) -> StoreResult<T>;
+
+    /// 原子地取出目录下的下一个等待任务并修改（通常改为执行中），没有可获取的任务时返回 `None`
+    async fn claim_next(&self, work_directory: &str, change: TaskChange<'_, T>) -> StoreResult<Option<T>>;
+
+    /// 删除不满足 `keep` 的任务，返回删除数量
+    async fn retain(&self, keep: TaskPredicate<'_, T>) -> StoreResult<u64>;
+}
@@ -0,0 +1,38 @@
+//! # Unix Socket
+//!
+//! 各HTTP服务共用的Unix域套接字监听。
+//!
+//! 边车部署时API可以通过 `server.unix_socket` 同时在Unix域套接字上提供，
+//! 端点与TCP监听器完全相同，例如 `curl --unix-socket <path> http://localhost/health`。
+//! 通过套接字的请求没有客户端IP，按IP限流和IP白名单对其不生效。
+//!
+//! - [`bind_unix_socket`]：绑定文件套接字或Linux抽象套接字
+//! - [`serve_unix_socket`]：在套接字上提供 axum 路由
+//! - [`remove_unix_socket`]：退出时删除套接字文件
+//! - [`parse_socket_mode`]：解析配置中的套接字文件权限
+
+#[cfg(unix)]
+mod listener;
+
+#[cfg(unix)]
+pub use listener::{bind_unix_socket, remove_unix_socket, serve_unix_socket};
+
+/// 解析套接字文件权限，接受 `660`、`0o660` 形式的八进制字符串，无效时返回 `None`
+pub fn parse_socket_mode(mode: &str) -> Option<u32> {
+    u32::from_str_radix(mode.trim_start_matches("0o"), 8)
+        .ok()
+        .filter(|mode| *mode <= 0o777)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_socket_mode() {
+        assert_eq!(parse_socket_mode("660"), Some(0o660));
+        assert_eq!(parse_socket_mode("0o600"), Some(0o600));
+        assert_eq!(parse_socket_mode("999"), None);
+        assert_eq!(parse_socket_mode("1777"), None);
+    }
+}
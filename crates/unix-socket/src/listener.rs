@@ -0,0 +1,131 @@
+//! Unix域套接字的绑定、服务和清理
+
+use std::io;
+
+use axum::Router;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder;
+use hyper_util::service::TowerToHyperService;
+use tokio::net::UnixListener;
+
+/// 绑定Unix域套接字
+///
+/// 以 `@` 开头的地址绑定到Linux抽象命名空间，不产生文件，也不设置权限。
+/// 文件套接字绑定前会删除上次运行残留的套接字文件（非套接字文件不会被删除），绑定后按 `mode` 设置权限。
+pub fn bind_unix_socket(path: &str, mode: Option<u32>) -> io::Result<UnixListener> {
+    if let Some(name) = path.strip_prefix('@') {
+        return bind_abstract(name);
+    }
+
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path),
+            ));
+        }
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    if let Some(mode) = mode {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    }
+    Ok(listener)
+}
+
+#[cfg(target_os = "linux")]
+fn bind_abstract(name: &str) -> io::Result<UnixListener> {
+    use std::os::linux::net::SocketAddrExt;
+    let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())?;
+    let listener = std::os::unix::net::UnixListener::bind_addr(&addr)?;
+    listener.set_nonblocking(true)?;
+    UnixListener::from_std(listener)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn bind_abstract(_name: &str) -> io::Result<UnixListener> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "abstract sockets require Linux"))
+}
+
+/// 删除文件套接字，抽象套接字无需清理
+pub fn remove_unix_socket(path: &str) {
+    if !path.starts_with('@') {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// 在Unix域套接字上提供HTTP服务，直到监听器出错
+///
+/// 每个连接在独立任务中处理，同时支持HTTP/1.1和HTTP/2。
+pub async fn serve_unix_socket(listener: UnixListener, app: Router) -> io::Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let service = TowerToHyperService::new(app.clone());
+        tokio::spawn(async move {
+            if let Err(e) = Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!("Unix socket connection closed with error: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn get_health(mut stream: tokio::net::UnixStream) -> String {
+        stream
+            .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_health_over_unix_socket() {
+        let app = Router::new().route("/health", get(|| async { "ok" }));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api.sock").display().to_string();
+
+        // 普通文件不会被当作残留的套接字删除
+        std::fs::write(&path, b"").unwrap();
+        assert!(bind_unix_socket(&path, None).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        // 残留的套接字文件会被替换
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let listener = bind_unix_socket(&path, Some(0o660)).unwrap();
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o660);
+        let server = tokio::spawn(serve_unix_socket(listener, app.clone()));
+        let stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        let response = get_health(stream).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("ok"));
+        server.abort();
+        remove_unix_socket(&path);
+        assert!(!std::path::Path::new(&path).exists());
+
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::linux::net::SocketAddrExt;
+            let name = format!("unix-socket-test-{}", std::process::id());
+            let listener = bind_unix_socket(&format!("@{}", name), None).unwrap();
+            let server = tokio::spawn(serve_unix_socket(listener, app));
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes()).unwrap();
+            let stream = std::os::unix::net::UnixStream::connect_addr(&addr).unwrap();
+            stream.set_nonblocking(true).unwrap();
+            let stream = tokio::net::UnixStream::from_std(stream).unwrap();
+            assert!(get_health(stream).await.ends_with("ok"));
+            server.abort();
+        }
+    }
+}
@@ -122,7 +122,7 @@ fn bench_batch_validation(c: &mut Criterion) {
     c.bench_function("batch_validation_100_items", |b| {
         b.to_async(&rt).iter(|| {
             rt.block_on(async {
-                service.validate_json_batch(black_box(&items), black_box(&options)).await
+                service.validate_json_batch(black_box(&items), black_box(&options), false).await
             })
         });
     });
@@ -137,6 +137,12 @@ start_server() {
         args+=("--dev")
     fi
     
+    # Validate configuration and dependencies before starting
+    if ! /app/bin/json-validator-http --config "$JSON_VALIDATOR_CONFIG" --check; then
+        log "Self-check failed"
+        exit 1
+    fi
+    
     # Start the server
     log "Server arguments: ${args[*]}"
     exec /app/bin/json-validator-http "${args[@]}" &
@@ -0,0 +1,390 @@
+//! API密钥管理
+//!
+//! 通过管理端点签发的密钥保存在 [`ApiKeyStore`] 中，只存储SHA-256摘要，
+//! 明文密钥仅在创建或轮换时返回一次。配置了存储文件时每次变更都会写回文件，
+//! 重启后密钥仍然有效。配置文件 `security.api_keys` 中的静态密钥同样参与认证，
+//! 可用于签发第一个管理密钥；默认配置不带任何静态密钥，示例配置中公开过的密钥会被拒绝。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::SecurityConfig;
+
+/// API密钥请求头
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// 调用JSON-RPC端点所需的权限
+pub const SCOPE_READ: &str = "read";
+
+/// 管理API密钥所需的权限
+pub const SCOPE_ADMIN: &str = "admin";
+
+/// API密钥存储错误
+#[derive(Debug, thiserror::Error)]
+pub enum ApiKeyStoreError {
+    /// 密钥不存在
+    #[error("API key not found: {0}")]
+    NotFound(String),
+    /// 密钥已被吊销
+    #[error("API key already revoked: {0}")]
+    Revoked(String),
+    /// 读写存储文件失败
+    #[error("API key store I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// 存储文件格式错误
+    #[error("API key store is corrupted: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// 已签发的API密钥
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    /// 密钥ID
+    pub id: String,
+    /// 密钥名称
+    pub name: String,
+    /// 权限列表
+    pub scopes: Vec<String>,
+    /// 每分钟请求上限，未设置时使用全局限流
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<u32>,
+    /// 明文密钥的前几个字符，便于识别
+    pub key_prefix: String,
+    /// 明文密钥的SHA-256摘要
+    #[serde(skip_serializing, default)]
+    pub key_hash: String,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 过期时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 吊销时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// 轮换后取代该密钥的新密钥ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaced_by: Option<String>,
+}
+
+impl ApiKeyRecord {
+    /// 密钥当前是否可用
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+/// 存储文件中的密钥记录，与对外响应不同，需要保留摘要
+#[derive(Serialize, Deserialize)]
+struct StoredKey {
+    #[serde(flatten)]
+    record: ApiKeyRecord,
+    key_hash: String,
+}
+
+/// 创建API密钥请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateApiKeyRequest {
+    /// 密钥名称
+    pub name: String,
+    /// 权限列表，默认只读
+    #[serde(default = "default_scopes")]
+    pub scopes: Vec<String>,
+    /// 每分钟请求上限
+    #[serde(default)]
+    pub rate_limit: Option<u32>,
+    /// 过期时间
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// 轮换API密钥请求
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RotateApiKeyRequest {
+    /// 新密钥的过期时间，未设置时沿用旧密钥的过期时间
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+fn default_scopes() -> Vec<String> {
+    vec![SCOPE_READ.to_string()]
+}
+
+/// 新签发的密钥，`key` 只在此处返回一次
+#[derive(Debug, Clone, Serialize)]
+pub struct IssuedApiKey {
+    /// 明文密钥
+    pub key: String,
+    /// 密钥信息
+    #[serde(flatten)]
+    pub record: ApiKeyRecord,
+}
+
+/// 认证通过的调用方
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticatedKey {
+    /// 密钥ID（静态密钥为配置中的键名）
+    pub id: String,
+    /// 权限列表
+    pub scopes: Vec<String>,
+    /// 每分钟请求上限
+    pub rate_limit: Option<u32>,
+}
+
+impl AuthenticatedKey {
+    /// 是否拥有指定权限，`admin` 拥有全部权限
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope || s == SCOPE_ADMIN)
+    }
+}
+
+/// API密钥存储
+pub struct ApiKeyStore {
+    path: Option<PathBuf>,
+    key_prefix: String,
+    static_keys: HashMap<String, AuthenticatedKey>,
+    keys: RwLock<HashMap<String, ApiKeyRecord>>,
+}
+
+impl ApiKeyStore {
+    /// 创建只保存在内存中的存储
+    pub fn in_memory(config: &SecurityConfig) -> Self {
+        let static_keys = config
+            .api_keys
+            .iter()
+            .map(|(key, key_config)| {
+                let authenticated = AuthenticatedKey {
+                    id: key.clone(),
+                    scopes: key_config.permissions.clone(),
+                    rate_limit: key_config.rate_limit,
+                };
+                (key.clone(), authenticated)
+            })
+            .collect();
+
+        Self {
+            path: None,
+            key_prefix: config.api_key_prefix.clone(),
+            static_keys,
+            keys: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 按配置创建存储，配置了 `api_key_store` 时从文件加载
+    pub fn from_config(config: &SecurityConfig) -> Result<Self, ApiKeyStoreError> {
+        let mut store = Self::in_memory(config);
+        if let Some(path) = &config.api_key_store {
+            store.keys = RwLock::new(load(path)?);
+            store.path = Some(path.clone());
+        }
+        Ok(store)
+    }
+
+    /// 签发新密钥
+    pub fn create(&self, request: CreateApiKeyRequest) -> Result<IssuedApiKey, ApiKeyStoreError> {
+        let mut keys = self.keys.write();
+        let issued = self.issue(request.name, request.scopes, request.rate_limit, request.expires_at);
+        keys.insert(issued.record.id.clone(), issued.record.clone());
+        self.persist(&keys)?;
+        Ok(issued)
+    }
+
+    /// 全部密钥（含已吊销和已过期的），按创建时间排序
+    pub fn list(&self) -> Vec<ApiKeyRecord> {
+        let mut records: Vec<ApiKeyRecord> = self.keys.read().values().cloned().collect();
+        records.sort_by_key(|record| record.created_at);
+        records
+    }
+
+    /// 吊销密钥
+    pub fn revoke(&self, id: &str) -> Result<ApiKeyRecord, ApiKeyStoreError> {
+        let mut keys = self.keys.write();
+        let record = keys.get_mut(id).ok_or_else(|| ApiKeyStoreError::NotFound(id.to_string()))?;
+        if record.revoked_at.is_some() {
+            return Err(ApiKeyStoreError::Revoked(id.to_string()));
+        }
+        record.revoked_at = Some(Utc::now());
+        let record = record.clone();
+        self.persist(&keys)?;
+        Ok(record)
+    }
+
+    /// 轮换密钥：签发权限相同的新密钥并吊销旧密钥
+    pub fn rotate(&self, id: &str, request: RotateApiKeyRequest) -> Result<IssuedApiKey, ApiKeyStoreError> {
+        let mut keys = self.keys.write();
+        let old = keys.get(id).ok_or_else(|| ApiKeyStoreError::NotFound(id.to_string()))?;
+        if old.revoked_at.is_some() {
+            return Err(ApiKeyStoreError::Revoked(id.to_string()));
+        }
+
+        let issued = self.issue(
+            old.name.clone(),
+            old.scopes.clone(),
+            old.rate_limit,
+            request.expires_at.or(old.expires_at),
+        );
+        if let Some(old) = keys.get_mut(id) {
+            old.revoked_at = Some(Utc::now());
+            old.replaced_by = Some(issued.record.id.clone());
+        }
+        keys.insert(issued.record.id.clone(), issued.record.clone());
+        self.persist(&keys)?;
+        Ok(issued)
+    }
+
+    /// 校验明文密钥，未知、已吊销或已过期的密钥返回 `None`
+    pub fn authenticate(&self, key: &str) -> Option<AuthenticatedKey> {
+        if let Some(authenticated) = self.static_keys.get(key) {
+            return Some(authenticated.clone());
+        }
+
+        let hash = hash_key(key);
+        let now = Utc::now();
+        self.keys
+            .read()
+            .values()
+            .find(|record| record.key_hash == hash && record.is_active(now))
+            .map(|record| AuthenticatedKey {
+                id: record.id.clone(),
+                scopes: record.scopes.clone(),
+                rate_limit: record.rate_limit,
+            })
+    }
+
+    /// 从请求头中取出并校验密钥
+    pub fn authenticate_headers(&self, headers: &HeaderMap) -> Option<AuthenticatedKey> {
+        api_key_from_headers(headers).and_then(|key| self.authenticate(key))
+    }
+
+    fn issue(
+        &self,
+        name: String,
+        scopes: Vec<String>,
+        rate_limit: Option<u32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> IssuedApiKey {
+        let id = uuid::Uuid::new_v4().to_string();
+        let key = format!("{}_{}", self.key_prefix, uuid::Uuid::new_v4().simple());
+        let record = ApiKeyRecord {
+            id,
+            name,
+            scopes,
+            rate_limit,
+            key_prefix: key.chars().take(self.key_prefix.len() + 5).collect(),
+            key_hash: hash_key(&key),
+            created_at: Utc::now(),
+            expires_at,
+            revoked_at: None,
+            replaced_by: None,
+        };
+        IssuedApiKey { key, record }
+    }
+
+    /// 写回存储文件，先写临时文件再重命名，避免写入中断损坏存储
+    fn persist(&self, keys: &HashMap<String, ApiKeyRecord>) -> Result<(), ApiKeyStoreError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let stored: Vec<StoredKey> = keys
+            .values()
+            .map(|record| StoredKey { key_hash: record.key_hash.clone(), record: record.clone() })
+            .collect();
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&stored)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+/// 从 `x-api-key` 或 `Authorization: Bearer` 头中取出API密钥
+pub fn api_key_from_headers(headers: &HeaderMap) -> Option<&str> {
+    if let Some(key) = headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok()) {
+        return Some(key.trim());
+    }
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+fn load(path: &Path) -> Result<HashMap<String, ApiKeyRecord>, ApiKeyStoreError> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let stored: Vec<StoredKey> = serde_json::from_slice(&std::fs::read(path)?)?;
+    Ok(stored
+        .into_iter()
+        .map(|StoredKey { mut record, key_hash }| {
+            record.key_hash = key_hash;
+            (record.id.clone(), record)
+        })
+        .collect())
+}
+
+fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiKeyConfig;
+
+    fn create_request(name: &str) -> CreateApiKeyRequest {
+        serde_json::from_value(serde_json::json!({ "name": name })).unwrap()
+    }
+
+    #[test]
+    fn test_create_rotate_revoke_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let static_key = ApiKeyConfig {
+            name: "Bootstrap".to_string(),
+            permissions: vec![SCOPE_ADMIN.to_string()],
+            rate_limit: None,
+        };
+        let config = SecurityConfig {
+            api_keys: HashMap::from([("bootstrap-admin-key".to_string(), static_key)]),
+            api_key_store: Some(dir.path().join("api_keys.json")),
+            ..Default::default()
+        };
+
+        let store = ApiKeyStore::from_config(&config).unwrap();
+        let issued = store.create(create_request("ci")).unwrap();
+        assert!(issued.key.starts_with("json-val_"));
+        let authenticated = store.authenticate(&issued.key).unwrap();
+        assert!(authenticated.has_scope(SCOPE_READ));
+        assert!(!authenticated.has_scope(SCOPE_ADMIN));
+
+        let rotated = store.rotate(&issued.record.id, RotateApiKeyRequest::default()).unwrap();
+        assert!(store.authenticate(&issued.key).is_none());
+        assert!(matches!(
+            store.revoke(&issued.record.id),
+            Err(ApiKeyStoreError::Revoked(_))
+        ));
+
+        // 重新加载后密钥仍然有效
+        let reloaded = ApiKeyStore::from_config(&config).unwrap();
+        assert_eq!(reloaded.list().len(), 2);
+        assert!(reloaded.authenticate(&rotated.key).is_some());
+        reloaded.revoke(&rotated.record.id).unwrap();
+        assert!(reloaded.authenticate(&rotated.key).is_none());
+
+        // 静态密钥同样可用
+        assert!(reloaded.authenticate("bootstrap-admin-key").unwrap().has_scope(SCOPE_ADMIN));
+    }
+
+    #[test]
+    fn test_expired_key_rejected() {
+        let store = ApiKeyStore::in_memory(&SecurityConfig::default());
+        let mut request = create_request("temp");
+        request.expires_at = Some(Utc::now() - chrono::Duration::seconds(1));
+        let issued = store.create(request).unwrap();
+        assert!(store.authenticate(&issued.key).is_none());
+    }
+}
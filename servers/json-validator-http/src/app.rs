@@ -1,26 +1,160 @@
 //! 应用程序配置和路由
 
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, post, MethodRouter},
     Router,
     response::Json,
 };
-use crate::handlers::{json_rpc_handler, health_check};
+use crate::config::ServerConfig;
+use crate::handlers::{
+    api_keys_enforced, auto_validate_handler, create_api_key_handler, create_archive_revalidation_job_handler,
+    create_revalidation_job_handler, get_revalidation_job_handler, get_revalidation_report_handler, health_check,
+    json_rpc_handler, list_api_keys_handler, list_job_history_handler, not_found_handler, prometheus_metrics_handler, readiness_check,
+    revoke_api_key_handler, rotate_api_key_handler,
+};
+use crate::middleware::{
+    ApiKeyAuthLayer, DecompressionLayer, MemoryPressureLayer, PriorityLayer, PrometheusMetricsLayer,
+    RateLimitLayer, locale_middleware, trace_id_middleware,
+};
 use crate::models::AppState;
+use tower_http::catch_panic::CatchPanicLayer;
 
 /// 创建应用程序路由
 pub fn create_app() -> Router {
-    // 创建应用状态
-    let state = AppState::new();
-    
+    create_app_with_config(ServerConfig::default())
+}
+
+/// 使用指定配置创建应用程序路由，指标端点与API共用同一监听器
+pub fn create_app_with_config(config: ServerConfig) -> Router {
+    build_app(AppState::with_config(config), true)
+}
+
+/// 创建API路由和独立指标监听器的路由，两者共享状态，API路由不再暴露指标端点
+pub fn create_app_with_metrics_listener(config: ServerConfig) -> (Router, Router) {
+    let state = AppState::with_config(config);
+    (build_app(state.clone(), false), create_metrics_app(state))
+}
+
+/// 指标监听器路由，仅提供指标和健康检查端点
+pub fn create_metrics_app(state: AppState) -> Router {
+    let mut router = Router::new()
+        .route("/health", get(health_check))
+        .route("/ready", get(readiness_check));
+    if prometheus_enabled(&state.config) {
+        router = router.route(&state.config.metrics.path, get(prometheus_metrics_handler));
+    }
+    router
+        .fallback(not_found_handler)
+        .layer(axum::middleware::from_fn(locale_middleware))
+        .layer(axum::middleware::from_fn(trace_id_middleware))
+        .with_state(state)
+}
+
+fn build_app(state: AppState, include_metrics: bool) -> Router {
+    let prometheus_enabled = prometheus_enabled(&state.config);
+
+    // 压缩请求体在认证和限流之后解压，未通过认证的请求不会触发解压
+    let decompression = DecompressionLayer::from_config(&state.config.request_decompression);
+
+    // 交互和批量请求各占独立的并发额度，在认证和限流之后才进入通道排队
+    let priority = state
+        .priority_lanes
+        .is_enabled()
+        .then(|| PriorityLayer::new(state.priority_lanes.clone(), state.prometheus.clone()));
+
+    let mut rpc_route = post(json_rpc_handler).layer(decompression.clone());
+    let mut auto_route = post(auto_validate_handler).layer(decompression.clone());
+    if let Some(priority) = priority {
+        rpc_route = rpc_route.layer(priority.clone());
+        auto_route = auto_route.layer(priority);
+    }
+    let metrics = |endpoint| PrometheusMetricsLayer::for_endpoint(state.prometheus.clone(), endpoint);
+    let rpc_route = guard(&state, rpc_route, PrometheusMetricsLayer::new(state.prometheus.clone(), state.rpc_router.clone()));
+    let auto_route = guard(&state, auto_route, metrics("validate/auto"));
+    let revalidate_route = guard(
+        &state,
+        post(create_revalidation_job_handler).layer(decompression),
+        metrics("jobs/revalidate"),
+    );
+    let archive_route = guard(
+        &state,
+        post(create_archive_revalidation_job_handler)
+            .layer(axum::extract::DefaultBodyLimit::max(state.jobs.max_archive_size())),
+        metrics("jobs/revalidate/archive"),
+    );
+    let jobs_route = guard(&state, get(list_job_history_handler), metrics("jobs"));
+    let job_route = guard(&state, get(get_revalidation_job_handler), metrics("jobs/:job_id"));
+    let report_route = guard(&state, get(get_revalidation_report_handler), metrics("jobs/:job_id/report"));
+
     // 创建路由
-    Router::new()
+    let mut router = Router::new()
         .route("/", get(root_handler))
         .route("/health", get(health_check))
-        .route("/rpc", post(json_rpc_handler))
+        .route("/ready", get(readiness_check))
+        .route("/rpc", rpc_route)
+        .route("/validate/auto", auto_route)
+        .route("/jobs/revalidate", revalidate_route)
+        .route("/jobs/revalidate/archive", archive_route)
+        .route("/jobs", jobs_route)
+        .route("/jobs/:job_id", job_route)
+        .route("/jobs/:job_id/report", report_route)
+        .route("/admin/api-keys", get(list_api_keys_handler).post(create_api_key_handler))
+        .route("/admin/api-keys/:key_id", delete(revoke_api_key_handler))
+        .route("/admin/api-keys/:key_id/rotate", post(rotate_api_key_handler));
+    if prometheus_enabled && include_metrics {
+        router = router.route(&state.config.metrics.path, get(prometheus_metrics_handler));
+    }
+
+    // 内存超过硬上限时，在读取请求体之前拒绝大请求体；压力升高时清空编译Schema缓存
+    if let Some(watchdog) = state.memory_watchdog.clone() {
+        router = router.layer(MemoryPressureLayer::new(watchdog.clone()));
+        let validator = state.validator_service.clone();
+        let prometheus = state.prometheus.clone();
+        watchdog.start(
+            move || {
+                let validator = validator.clone();
+                async move {
+                    let cleared = validator.clear_schema_cache().await;
+                    tracing::warn!(cleared, "Cleared schema cache under memory pressure");
+                }
+            },
+            move |watchdog, evicted| prometheus.record_memory_sample(watchdog.rss_bytes(), watchdog.pressure(), evicted),
+        );
+    }
+
+    // 预热完成前 /ready 返回503
+    crate::warmup::start_warmup(&state);
+
+    // 请求处理中的panic返回500问题详情，不直接断开连接
+    let metrics = state.prometheus.clone();
+    router
+        .fallback(not_found_handler)
+        .layer(CatchPanicLayer::custom(move |payload| crate::crash::panic_response(payload, &metrics)))
+        .layer(axum::middleware::from_fn(locale_middleware))
+        .layer(axum::middleware::from_fn(trace_id_middleware))
         .with_state(state)
 }
 
+/// 为校验和任务端点套上限流、API密钥认证和Prometheus指标层
+///
+/// 按API密钥或IP限流；认证在限流之外，限流按认证后的密钥计数；被拒绝的请求同样计入指标。
+fn guard(state: &AppState, mut route: MethodRouter<AppState>, metrics: PrometheusMetricsLayer) -> MethodRouter<AppState> {
+    if state.config.security.enabled && state.rate_limiter.is_enabled() {
+        route = route.layer(RateLimitLayer::new(state.rate_limiter.clone()));
+    }
+    if api_keys_enforced(&state.config) {
+        route = route.layer(ApiKeyAuthLayer::new(state.api_keys.clone()));
+    }
+    if prometheus_enabled(&state.config) {
+        route = route.layer(metrics);
+    }
+    route
+}
+
+fn prometheus_enabled(config: &ServerConfig) -> bool {
+    config.metrics.enabled && config.metrics.prometheus_enabled
+}
+
 /// 根路径处理器
 async fn root_handler() -> Json<serde_json::Value> {
     Json(serde_json::json!({
@@ -29,7 +163,12 @@ async fn root_handler() -> Json<serde_json::Value> {
         "description": "HTTP protocol JSON validation MCP server",
         "endpoints": {
             "rpc": "/rpc - JSON-RPC 2.0 endpoint",
-            "health": "/health - Health check endpoint"
+            "validate_auto": "/validate/auto - Validate a JSON, NDJSON, YAML or TOML body with format auto-detection",
+            "health": "/health - Health check endpoint",
+            "ready": "/ready - Readiness check, 503 until startup warm-up completes",
+            "metrics": "/metrics - Prometheus metrics endpoint",
+            "jobs": "/jobs/revalidate - Background revalidation jobs, /jobs?since= - Job history",
+            "api_keys": "/admin/api-keys - API key management"
         }
     }))
 }
@@ -38,12 +177,41 @@ async fn root_handler() -> Json<serde_json::Value> {
 mod tests {
     use super::*;
     use axum::body::Body;
-    use http::Request;
+    use axum::http::{Request, StatusCode};
     use tower::ServiceExt;
 
+    const USER_KEY: &str = "test-user-key";
+    const ADMIN_KEY: &str = "test-admin-key";
+
+    /// 带测试专用静态密钥的配置，默认配置不含任何静态密钥
+    fn test_config() -> ServerConfig {
+        let mut config = ServerConfig::default();
+        for (key, permissions) in [(USER_KEY, vec!["read", "write"]), (ADMIN_KEY, vec!["read", "write", "admin"])] {
+            let key_config = crate::config::ApiKeyConfig {
+                name: key.to_string(),
+                permissions: permissions.into_iter().map(str::to_string).collect(),
+                rate_limit: None,
+            };
+            config.security.api_keys.insert(key.to_string(), key_config);
+        }
+        config
+    }
+
+    fn test_app() -> Router {
+        create_app_with_config(test_config())
+    }
+
+    fn get_request(uri: &str) -> Request<Body> {
+        Request::builder().uri(uri).method("GET").body(Body::empty()).unwrap()
+    }
+
+    fn user_request(uri: &str) -> Request<Body> {
+        Request::builder().uri(uri).method("GET").header("x-api-key", USER_KEY).body(Body::empty()).unwrap()
+    }
+
     #[tokio::test]
     async fn test_create_app() {
-        let app = create_app();
+        let app = test_app();
         
         // 测试根路径
         let request = Request::builder()
@@ -58,7 +226,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_health_endpoint() {
-        let app = create_app();
+        let app = test_app();
         
         // 测试健康检查端点
         let request = Request::builder()
@@ -70,4 +238,313 @@ mod tests {
         let response = app.oneshot(request).await.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_ready_after_warmup() {
+        let app = test_app();
+        let mut status = StatusCode::SERVICE_UNAVAILABLE;
+        for _ in 0..50 {
+            status = app.clone().oneshot(get_request("/ready")).await.unwrap().status();
+            if status == StatusCode::OK {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(status, StatusCode::OK);
+
+        let mut config = test_config();
+        config.warmup.enabled = false;
+        let response = create_app_with_config(config).oneshot(get_request("/ready")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_listener_split() {
+        let (app, metrics_app) = create_app_with_metrics_listener(test_config());
+
+        let response = app.oneshot(get_request("/metrics")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = metrics_app.clone().oneshot(get_request("/metrics")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = metrics_app.clone().oneshot(get_request("/health")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = metrics_app.oneshot(get_request("/rpc")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_validate_auto_detects_format() {
+        let app = test_app();
+        let post = |content_type: &str, body: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/validate/auto")
+                .header("x-api-key", USER_KEY)
+                .header("content-type", content_type)
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let read = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let response = app.clone().oneshot(post("text/plain", "name: demo
+ports: [80]
+")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = read(response).await;
+        assert_eq!(body["format"], "yaml");
+        assert_eq!(body["result"]["valid"], true);
+
+        let response = app.clone().oneshot(post("application/octet-stream", "{\"a\":1}\n{\"a\":\n")).await.unwrap();
+        let body = read(response).await;
+        assert_eq!(body["format"], "ndjson");
+        assert_eq!(body["summary"]["failed"], 1);
+        assert_eq!(body["results"][1]["id"], "2");
+        assert_eq!(body["results"][1]["result"]["errors"][0]["error_code"], "PARSE_ERROR");
+
+        let response = app.oneshot(post("text/plain", "just some text")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_validate_auto_requires_api_key() {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/validate/auto")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"a":1}"#))
+            .unwrap();
+        let response = test_app().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_jobs_require_api_key() {
+        let app = test_app();
+        let post = |uri: &str| {
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from("{}"))
+                .unwrap()
+        };
+        for request in [
+            post("/jobs/revalidate"),
+            post("/jobs/revalidate/archive"),
+            get_request("/jobs"),
+            get_request("/jobs/missing"),
+            get_request("/jobs/missing/report"),
+        ] {
+            let uri = request.uri().to_string();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+        }
+
+        let response = app.oneshot(user_request("/jobs")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_batch_job_history() {
+        let app = test_app();
+        let batch = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "validate_json_batch",
+            "params": {"items": [
+                {"id": "ok", "json_data": {"name": "a"}, "schema": {"type": "object"}},
+                {"id": "bad", "json_data": 1, "schema": {"type": "object"}}
+            ]},
+            "id": 1
+        });
+        let request = Request::builder()
+            .method("POST")
+            .uri("/rpc")
+            .header("x-api-key", USER_KEY)
+            .header("content-type", "application/json")
+            .body(Body::from(batch.to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let job_id = body["result"]["job_id"].as_str().unwrap().to_string();
+
+        let response = app.clone().oneshot(user_request("/jobs?kind=batch")).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["jobs"][0]["job_id"], job_id.as_str());
+        assert_eq!((body["jobs"][0]["passed"].as_u64(), body["jobs"][0]["failed"].as_u64()), (Some(1), Some(1)));
+
+        let response = app.oneshot(user_request(&format!("/jobs/{}/report?format=csv", job_id))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let csv = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(csv.lines().count(), 2);
+        assert!(csv.lines().nth(1).unwrap().starts_with("bad,"));
+    }
+
+    #[tokio::test]
+    async fn test_correlation_id_echoed() {
+        let app = test_app();
+        let rpc = |params: serde_json::Value, header: Option<&str>| {
+            let mut builder = Request::builder()
+                .method("POST")
+                .uri("/rpc")
+                .header("x-api-key", USER_KEY)
+                .header("content-type", "application/json");
+            if let Some(value) = header {
+                builder = builder.header("x-correlation-id", value);
+            }
+            let body = serde_json::json!({"jsonrpc": "2.0", "method": "validate_json", "params": params, "id": 1});
+            builder.body(Body::from(body.to_string())).unwrap()
+        };
+
+        let params = serde_json::json!({"json_data": {"a": 1}, "correlation_id": "order-42"});
+        let response = app.clone().oneshot(rpc(params, Some("from-header"))).await.unwrap();
+        assert_eq!(response.headers()["x-correlation-id"], "order-42");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["correlation_id"], "order-42");
+        assert_eq!(body["result"]["valid"], true);
+
+        let response = app.clone().oneshot(rpc(serde_json::json!({}), Some("from-header"))).await.unwrap();
+        assert_eq!(response.headers()["x-correlation-id"], "from-header");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["correlation_id"], "from-header");
+        assert!(body["error"].is_object());
+
+        // 不合法的关联ID被忽略
+        let response = app.oneshot(rpc(serde_json::json!({"json_data": 1}), Some("has space"))).await.unwrap();
+        assert!(response.headers().get("x-correlation-id").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_localized_errors() {
+        let app = test_app();
+        let rpc = |options: serde_json::Value| {
+            let body = serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "validate_json_with_schema",
+                "params": {"json_data": {}, "schema": {"required": ["name"]}, "options": options},
+                "id": 1
+            });
+            Request::builder()
+                .method("POST")
+                .uri("/rpc")
+                .header("x-api-key", USER_KEY)
+                .header("content-type", "application/json")
+                .header("accept-language", "zh-CN,zh;q=0.9,en;q=0.8")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let message = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            body["result"]["errors"][0]["message"].as_str().unwrap().to_string()
+        };
+
+        let response = app.clone().oneshot(rpc(serde_json::json!({}))).await.unwrap();
+        assert_eq!(message(response).await, "缺少必需属性 \"name\"");
+
+        // 验证选项指定的语言优先于 Accept-Language
+        let response = app.clone().oneshot(rpc(serde_json::json!({"locale": "en"}))).await.unwrap();
+        assert_eq!(message(response).await, "\"name\" is a required property");
+
+        let response = app.oneshot(get_request("/no-such-route?lang=zh-CN")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()["content-language"], "zh-CN");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["title"], "未找到");
+    }
+
+    #[tokio::test]
+    async fn test_validate_multi_resolves_registered_schemas() {
+        let directory = tempfile::tempdir().unwrap();
+        std::fs::write(
+            directory.path().join("user.schema.json"),
+            r#"{"type": "object", "required": ["name"]}"#,
+        )
+        .unwrap();
+        let mut config = test_config();
+        config.schema_registry.directory = Some(directory.path().to_path_buf());
+        let app = create_app_with_config(config);
+        let rpc = |schemas: serde_json::Value| {
+            let body = serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "validate_multi",
+                "params": {"json_data": {"age": 1}, "schemas": schemas, "mode": "all"},
+                "id": 1
+            });
+            Request::builder()
+                .method("POST")
+                .uri("/rpc")
+                .header("x-api-key", USER_KEY)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let body = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let schemas = serde_json::json!([{"id": "user"}, {"id": "inline", "schema": {"type": "object"}}]);
+        let result = body(app.clone().oneshot(rpc(schemas)).await.unwrap()).await;
+        assert_eq!(result["result"]["valid"], false);
+        assert_eq!(result["result"]["results"][0]["id"], "user");
+        assert_eq!(result["result"]["results"][0]["result"]["valid"], false);
+        assert_eq!(result["result"]["results"][1]["result"]["valid"], true);
+
+        let result = body(app.clone().oneshot(rpc(serde_json::json!([{"id": "missing"}]))).await.unwrap()).await;
+        assert_eq!(result["error"]["code"], -32602);
+        let result = body(app.oneshot(rpc(serde_json::json!([{}]))).await.unwrap()).await;
+        assert_eq!(result["error"]["code"], -32602);
+    }
+
+    #[tokio::test]
+    async fn test_api_key_management() {
+        let app = test_app();
+        let admin_request = |method: &str, uri: &str, key: &str, body: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("x-api-key", key)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let rpc_request = |key: &str| {
+            admin_request("POST", "/rpc", key, r#"{"jsonrpc":"2.0","method":"ping","id":1}"#)
+        };
+
+        let response = app.clone().oneshot(admin_request("GET", "/admin/api-keys", USER_KEY, "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app
+            .clone()
+            .oneshot(admin_request("POST", "/admin/api-keys", ADMIN_KEY, r#"{"name":"ci"}"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let issued: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let key = issued["key"].as_str().unwrap().to_string();
+        let id = issued["id"].as_str().unwrap().to_string();
+        assert!(issued.get("key_hash").is_none());
+
+        let response = app.clone().oneshot(rpc_request(&key)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let uri = format!("/admin/api-keys/{}", id);
+        let response = app.clone().oneshot(admin_request("DELETE", &uri, ADMIN_KEY, "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.oneshot(rpc_request(&key)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
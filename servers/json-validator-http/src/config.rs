@@ -1,5 +1,6 @@
 //! 服务器配置管理
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -7,7 +8,7 @@ use crate::tls::TlsVersion;
 use crate::performance::PerformanceConfig as OptimizedPerformanceConfig;
 
 /// 服务器配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ServerConfig {
     /// 服务器配置
     pub server: ServerSettings,
@@ -37,10 +38,28 @@ pub struct ServerConfig {
     pub backup: BackupConfig,
     /// 审计配置
     pub audit: AuditConfig,
+    /// JSON-RPC方法路由配置
+    #[serde(default)]
+    pub rpc: RpcConfig,
+    /// Schema注册表配置
+    #[serde(default)]
+    pub schema_registry: SchemaRegistryConfig,
+    /// 命名验证配置档（名称 -> 配置档）
+    #[serde(default)]
+    pub validation_profiles: HashMap<String, ValidationProfile>,
+    /// 后台重新验证任务配置
+    #[serde(default)]
+    pub jobs: JobsConfig,
+    /// 压缩请求体配置
+    #[serde(default)]
+    pub request_decompression: RequestDecompressionConfig,
+    /// 启动预热配置
+    #[serde(default)]
+    pub warmup: WarmupConfig,
 }
 
 /// 服务器基础设置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ServerSettings {
     /// 监听地址
     pub host: String,
@@ -66,6 +85,12 @@ pub struct ServerSettings {
     pub client_auth_required: bool,
     /// 客户端CA证书路径
     pub client_ca_path: String,
+    /// Unix域套接字路径，设置后在TCP之外同时监听；以 `@` 开头表示Linux抽象命名空间
+    #[serde(default)]
+    pub unix_socket: Option<String>,
+    /// 套接字文件的八进制权限，如 `660`；未设置时由umask决定
+    #[serde(default)]
+    pub unix_socket_mode: Option<String>,
 }
 
 impl Default for ServerSettings {
@@ -83,12 +108,26 @@ impl Default for ServerSettings {
             key_path: "certs/server.key".to_string(),
             client_auth_required: false,
             client_ca_path: "certs/client-ca.crt".to_string(),
+            unix_socket: None,
+            unix_socket_mode: None,
         }
     }
 }
 
+impl ServerSettings {
+    /// 解析套接字文件权限，接受 `660`、`0o660` 形式的八进制字符串
+    pub fn socket_mode(&self) -> anyhow::Result<Option<u32>> {
+        let Some(mode) = &self.unix_socket_mode else {
+            return Ok(None);
+        };
+        unix_socket::parse_socket_mode(mode)
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("Invalid unix socket mode '{}': expected octal permissions such as 660", mode))
+    }
+}
+
 /// 缓存配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CacheConfig {
     /// 是否启用缓存
     pub enabled: bool,
@@ -102,6 +141,9 @@ pub struct CacheConfig {
     pub max_size: usize,
     /// 缓存键前缀
     pub key_prefix: String,
+    /// 验证任务历史
+    #[serde(default)]
+    pub job_history: JobHistoryConfig,
 }
 
 impl Default for CacheConfig {
@@ -113,12 +155,41 @@ impl Default for CacheConfig {
             ttl: 3600,
             max_size: 1000,
             key_prefix: "json_validator:".to_string(),
+            job_history: JobHistoryConfig::default(),
+        }
+    }
+}
+
+/// 验证任务历史配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct JobHistoryConfig {
+    /// 是否记录批量验证和重新验证任务的历史
+    pub enabled: bool,
+    /// 历史和失败报告的存储目录，未设置时只保存在内存中
+    pub directory: Option<PathBuf>,
+    /// 历史保留天数
+    pub retention_days: u64,
+    /// 保留的历史条数上限
+    pub max_entries: usize,
+    /// 每个任务报告中保留的失败文档数上限
+    pub max_reported_failures: usize,
+}
+
+impl Default for JobHistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            directory: None,
+            retention_days: 7,
+            max_entries: 1000,
+            max_reported_failures: 1000,
         }
     }
 }
 
 /// 安全配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SecurityConfig {
     /// 是否启用安全功能
     pub enabled: bool,
@@ -146,27 +217,29 @@ pub struct SecurityConfig {
     pub tls: TlsConfig,
     /// IP白名单配置
     pub allowed_ips: IpWhitelistConfig,
+    /// 管理端点签发的API密钥存储文件，未设置时只保存在内存中
+    #[serde(default)]
+    pub api_key_store: Option<PathBuf>,
+}
+
+/// 早期示例配置中公开过的静态API密钥，出现在配置中时拒绝启动
+pub const SAMPLE_API_KEYS: &[&str] = &["admin_key", "user_key", "readonly_key"];
+
+impl SecurityConfig {
+    /// 检查静态API密钥，示例配置中公开过的密钥任何人都能使用，必须替换
+    pub fn check_static_api_keys(&self) -> anyhow::Result<()> {
+        match SAMPLE_API_KEYS.iter().find(|key| self.api_keys.contains_key(**key)) {
+            Some(key) => Err(anyhow::anyhow!(
+                "Static API key '{}' was published in the sample configuration and must be replaced",
+                key
+            )),
+            None => Ok(()),
+        }
+    }
 }
 
 impl Default for SecurityConfig {
     fn default() -> Self {
-        let mut api_keys = HashMap::new();
-        api_keys.insert("admin_key".to_string(), ApiKeyConfig {
-            name: "Admin Key".to_string(),
-            permissions: vec!["read".to_string(), "write".to_string(), "admin".to_string()],
-            rate_limit: Some(1000),
-        });
-        api_keys.insert("user_key".to_string(), ApiKeyConfig {
-            name: "User Key".to_string(),
-            permissions: vec!["read".to_string(), "write".to_string()],
-            rate_limit: Some(100),
-        });
-        api_keys.insert("readonly_key".to_string(), ApiKeyConfig {
-            name: "Readonly Key".to_string(),
-            permissions: vec!["read".to_string()],
-            rate_limit: Some(50),
-        });
-
         Self {
             enabled: true,
             jwt_secret: "your-secret-key-here-change-in-production".to_string(),
@@ -178,15 +251,16 @@ impl Default for SecurityConfig {
             strict_mode: true,
             cors: CorsConfig::default(),
             rate_limiting: RateLimitingConfig::default(),
-            api_keys,
+            api_keys: HashMap::new(),
             tls: TlsConfig::default(),
             allowed_ips: IpWhitelistConfig::default(),
+            api_key_store: None,
         }
     }
 }
 
 /// API密钥配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ApiKeyConfig {
     /// 密钥名称
     pub name: String,
@@ -197,7 +271,7 @@ pub struct ApiKeyConfig {
 }
 
 /// CORS配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CorsConfig {
     /// 是否启用CORS
     pub enabled: bool,
@@ -227,7 +301,7 @@ impl Default for CorsConfig {
 }
 
 /// 速率限制配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RateLimitingConfig {
     /// 限流算法
     pub algorithm: String,
@@ -237,6 +311,9 @@ pub struct RateLimitingConfig {
     pub whitelist: Vec<String>,
     /// 限流错误消息
     pub error_message: String,
+    /// 令牌桶容量（允许的突发请求数），0 表示与每分钟速率相同
+    #[serde(default)]
+    pub burst: u32,
 }
 
 impl Default for RateLimitingConfig {
@@ -246,12 +323,13 @@ impl Default for RateLimitingConfig {
             global_limit: 1000,
             whitelist: vec!["127.0.0.1".to_string(), "::1".to_string()],
             error_message: "Rate limit exceeded. Please try again later.".to_string(),
+            burst: 0,
         }
     }
 }
 
 /// TLS配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TlsConfig {
     /// 最小TLS版本
     pub min_version: String,
@@ -275,7 +353,7 @@ impl Default for TlsConfig {
 }
 
 /// IP白名单配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct IpWhitelistConfig {
     /// IP地址列表
     pub ips: Vec<String>,
@@ -293,7 +371,7 @@ impl Default for IpWhitelistConfig {
 }
 
 /// 日志配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LoggingConfig {
     /// 日志级别
     pub level: String,
@@ -307,6 +385,30 @@ pub struct LoggingConfig {
     pub stderr: bool,
     /// 日志轮转配置
     pub rotation: LogRotationConfig,
+    /// 崩溃报告配置
+    #[serde(default)]
+    pub crash_report: CrashReportConfig,
+}
+
+/// 崩溃报告配置
+///
+/// 发生panic时写入包含panic信息、回溯和最近日志的报告文件。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct CrashReportConfig {
+    /// 是否写入崩溃报告
+    pub enabled: bool,
+    /// 报告文件目录
+    pub directory: PathBuf,
+}
+
+impl Default for CrashReportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: PathBuf::from("crash-reports"),
+        }
+    }
 }
 
 impl Default for LoggingConfig {
@@ -318,12 +420,13 @@ impl Default for LoggingConfig {
             stdout: true,
             stderr: false,
             rotation: LogRotationConfig::default(),
+            crash_report: CrashReportConfig::default(),
         }
     }
 }
 
 /// 日志轮转配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LogRotationConfig {
     /// 是否启用日志轮转
     pub enabled: bool,
@@ -347,7 +450,7 @@ impl Default for LogRotationConfig {
 }
 
 /// 指标配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MetricsConfig {
     /// 是否启用指标收集
     pub enabled: bool,
@@ -377,7 +480,7 @@ impl Default for MetricsConfig {
 }
 
 /// 监控配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MonitoringConfig {
     /// 健康检查间隔（秒）
     pub health_check_interval: u64,
@@ -387,6 +490,9 @@ pub struct MonitoringConfig {
     pub error_tracking: bool,
     /// 是否启用请求追踪
     pub request_tracing: bool,
+    /// 内存自监控配置
+    #[serde(default)]
+    pub memory_watchdog: MemoryWatchdogConfig,
 }
 
 impl Default for MonitoringConfig {
@@ -396,12 +502,55 @@ impl Default for MonitoringConfig {
             performance_monitoring: true,
             error_tracking: true,
             request_tracing: true,
+            memory_watchdog: MemoryWatchdogConfig::default(),
+        }
+    }
+}
+
+/// 内存自监控配置
+///
+/// 常驻内存超过软上限时清空编译Schema缓存，超过硬上限时拒绝较大的请求体。上限为0表示不检查。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct MemoryWatchdogConfig {
+    /// 是否启用内存自监控
+    pub enabled: bool,
+    /// 检查间隔（秒）
+    pub check_interval: u64,
+    /// 软上限（字节），超过时清空缓存
+    pub soft_limit_bytes: u64,
+    /// 硬上限（字节），超过时拒绝大请求体
+    pub hard_limit_bytes: u64,
+    /// 超过硬上限时允许的最大请求体（字节）
+    pub max_body_bytes_under_pressure: u64,
+}
+
+impl Default for MemoryWatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_interval: 10,
+            soft_limit_bytes: 512 * 1024 * 1024, // 512MB
+            hard_limit_bytes: 768 * 1024 * 1024, // 768MB
+            max_body_bytes_under_pressure: 64 * 1024, // 64KB
+        }
+    }
+}
+
+impl MemoryWatchdogConfig {
+    /// 内存自监控使用的上限
+    pub fn limits(&self) -> memory_watchdog::MemoryLimits {
+        memory_watchdog::MemoryLimits {
+            check_interval: std::time::Duration::from_secs(self.check_interval),
+            soft_limit_bytes: self.soft_limit_bytes,
+            hard_limit_bytes: self.hard_limit_bytes,
+            max_body_bytes_under_pressure: self.max_body_bytes_under_pressure,
         }
     }
 }
 
 /// JSON验证配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ValidationConfig {
     /// 最大JSON大小（字节）
     pub max_json_size: usize,
@@ -436,8 +585,195 @@ impl Default for ValidationConfig {
     }
 }
 
+/// JSON-RPC方法路由配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct RpcConfig {
+    /// 是否接受MCP标准的 `tools/call` 信封调用
+    pub enable_tools_call: bool,
+    /// 是否接受旧版直接方法调用（如 `validate_json`）
+    pub enable_direct_methods: bool,
+    /// 禁用的方法（规范方法名，同时作用于直接调用和工具调用）
+    pub disabled_methods: Vec<String>,
+    /// 方法别名（别名 -> 规范方法名）
+    pub aliases: HashMap<String, String>,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            enable_tools_call: true,
+            enable_direct_methods: true,
+            disabled_methods: Vec::new(),
+            aliases: HashMap::new(),
+        }
+    }
+}
+
+/// Schema注册表配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SchemaRegistryConfig {
+    /// 启动时加载的Schema目录
+    pub directory: Option<std::path::PathBuf>,
+    /// 是否监听目录变化并热加载
+    pub watch: bool,
+    /// 从S3兼容对象存储加载Schema
+    pub object_storage: SchemaObjectStorageConfig,
+}
+
+impl Default for SchemaRegistryConfig {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            watch: true,
+            object_storage: SchemaObjectStorageConfig::default(),
+        }
+    }
+}
+
+/// Schema对象存储配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SchemaObjectStorageConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 服务端点
+    pub endpoint: String,
+    /// 存储桶
+    pub bucket: String,
+    /// 区域
+    pub region: String,
+    /// 访问密钥ID
+    pub access_key_id: String,
+    /// 访问密钥
+    pub secret_access_key: String,
+    /// 是否使用路径风格地址（MinIO等需要开启）
+    pub path_style: bool,
+    /// 对象键前缀，前缀下的 `<name>.schema.json` 以 `<name>` 注册
+    pub prefix: String,
+    /// 重新同步间隔（秒），0表示只在启动时加载一次
+    pub refresh_interval: u64,
+}
+
+impl Default for SchemaObjectStorageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "http://127.0.0.1:9000".to_string(),
+            bucket: "schemas".to_string(),
+            region: "us-east-1".to_string(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            path_style: true,
+            prefix: String::new(),
+            refresh_interval: 60,
+        }
+    }
+}
+
+/// 验证配置档：Schema引用、自定义规则、格式严格度和大小限制的命名组合
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ValidationProfile {
+    /// 注册表中的Schema名称
+    pub schema_ref: Option<String>,
+    /// 内联Schema，未设置 `schema_ref` 时使用
+    pub schema: Option<serde_json::Value>,
+    /// 自定义规则
+    pub rules: Vec<ProfileRule>,
+    /// 是否校验 `format` 关键字
+    pub strict_formats: bool,
+    /// 最大JSON大小（字节）
+    pub max_json_size: Option<usize>,
+    /// 最大嵌套深度
+    pub max_depth: Option<usize>,
+}
+
+/// 配置档自定义规则
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProfileRule {
+    /// 目标字段的JSON Pointer
+    pub pointer: String,
+    /// 字段是否必须存在
+    #[serde(default)]
+    pub required: bool,
+    /// 字符串字段必须匹配的正则表达式
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// 自定义错误信息
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// 后台重新验证任务配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct JobsConfig {
+    /// 允许作为数据源扫描的目录，为空时禁用目录数据源
+    pub allowed_directories: Vec<std::path::PathBuf>,
+    /// 上传归档的大小上限（字节）
+    pub max_archive_size: usize,
+    /// 报告中保留的失败文档数上限
+    pub max_reported_failures: usize,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_directories: Vec::new(),
+            max_archive_size: 64 * 1024 * 1024, // 64MB
+            max_reported_failures: 10_000,
+        }
+    }
+}
+
+/// 压缩请求体配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct RequestDecompressionConfig {
+    /// 是否接受 `Content-Encoding: gzip` / `deflate` 的请求体
+    pub enabled: bool,
+    /// 解压后请求体的大小上限（字节）
+    pub max_decompressed_size: usize,
+    /// 解压后大小与压缩大小之比的上限，解压后不超过64KB的请求体不检查
+    pub max_ratio: usize,
+}
+
+impl Default for RequestDecompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_decompressed_size: 10 * 1024 * 1024, // 10MB
+            max_ratio: 100,
+        }
+    }
+}
+
+/// 启动预热配置
+///
+/// 预热期间 `/ready` 返回503，完成后才接收负载均衡流量。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct WarmupConfig {
+    /// 是否在启动时预编译注册表和验证配置档中的Schema
+    pub enabled: bool,
+    /// 预热时重放的样本文件（JSON Lines），每行为
+    /// `{"schema_ref": "...", "json_data": ...}` 或 `{"profile": "...", "json_data": ...}`
+    pub samples_file: Option<std::path::PathBuf>,
+}
+
+impl Default for WarmupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            samples_file: None,
+        }
+    }
+}
+
 /// 性能配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PerformanceConfig {
     /// 基础性能配置
     pub basic: BasicPerformanceConfig,
@@ -449,6 +785,12 @@ pub struct PerformanceConfig {
     pub concurrency: ConcurrencyConfig,
     /// 缓存优化配置
     pub cache_optimization: CacheOptimizationConfig,
+    /// 大文档验证线程池配置
+    #[serde(default)]
+    pub validation_pool: ValidationPoolConfig,
+    /// 请求优先级通道配置
+    #[serde(default)]
+    pub priority_lanes: PriorityLanesConfig,
 }
 
 impl Default for PerformanceConfig {
@@ -459,12 +801,14 @@ impl Default for PerformanceConfig {
             memory: MemoryConfig::default(),
             concurrency: ConcurrencyConfig::default(),
             cache_optimization: CacheOptimizationConfig::default(),
+            validation_pool: ValidationPoolConfig::default(),
+            priority_lanes: PriorityLanesConfig::default(),
         }
     }
 }
 
 /// 基础性能配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BasicPerformanceConfig {
     /// 连接池大小
     pub connection_pool_size: usize,
@@ -491,7 +835,7 @@ impl Default for BasicPerformanceConfig {
 }
 
 /// 内存管理配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MemoryConfig {
     /// 内存限制 (MB)
     pub memory_limit_mb: usize,
@@ -518,7 +862,7 @@ impl Default for MemoryConfig {
 }
 
 /// 并发控制配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ConcurrencyConfig {
     /// 最大并发请求数
     pub max_concurrent_requests: usize,
@@ -545,7 +889,7 @@ impl Default for ConcurrencyConfig {
 }
 
 /// 缓存优化配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CacheOptimizationConfig {
     /// 缓存预热
     pub cache_warmup: bool,
@@ -571,8 +915,61 @@ impl Default for CacheOptimizationConfig {
     }
 }
 
+/// 大文档验证线程池配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ValidationPoolConfig {
+    /// 是否把大文档的验证移到线程池
+    pub enabled: bool,
+    /// 同时执行验证的线程数，0表示CPU核心数
+    pub threads: usize,
+    /// 估算大小达到此字节数的文档交给线程池验证
+    pub min_document_bytes: usize,
+    /// 等待线程的验证数上限，达到后拒绝新的大文档验证
+    pub max_queue: usize,
+}
+
+impl Default for ValidationPoolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threads: 0,
+            min_document_bytes: 64 * 1024,
+            max_queue: 64,
+        }
+    }
+}
+
+/// 请求优先级通道配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct PriorityLanesConfig {
+    /// 是否按优先级通道限制并发
+    pub enabled: bool,
+    /// 交互通道同时处理的请求数
+    pub interactive_concurrency: usize,
+    /// 批量通道同时处理的请求数
+    pub batch_concurrency: usize,
+    /// 未标记优先级的请求使用的通道
+    pub default_lane: crate::middleware::Lane,
+    /// 等待通道空闲的最长时间（毫秒），超时返回503
+    pub queue_timeout_ms: u64,
+}
+
+impl Default for PriorityLanesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interactive_concurrency: 256,
+            batch_concurrency: 16,
+            default_lane: crate::middleware::Lane::Interactive,
+            queue_timeout_ms: 30_000,
+        }
+    }
+}
+
 /// 部署配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DeploymentConfig {
     /// 环境类型
     pub environment: String,
@@ -599,7 +996,7 @@ impl Default for DeploymentConfig {
 }
 
 /// 数据库配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DatabaseConfig {
     /// 是否启用数据库
     pub enabled: bool,
@@ -626,7 +1023,7 @@ impl Default for DatabaseConfig {
 }
 
 /// Redis配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RedisConfig {
     /// 是否启用Redis
     pub enabled: bool,
@@ -653,7 +1050,7 @@ impl Default for RedisConfig {
 }
 
 /// 通知配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct NotificationConfig {
     /// 是否启用通知
     pub enabled: bool,
@@ -674,7 +1071,7 @@ impl Default for NotificationConfig {
 }
 
 /// 告警阈值
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AlertThresholds {
     /// 错误率阈值
     pub error_rate: f64,
@@ -695,7 +1092,7 @@ impl Default for AlertThresholds {
 }
 
 /// 备份配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BackupConfig {
     /// 是否启用备份
     pub enabled: bool,
@@ -719,7 +1116,7 @@ impl Default for BackupConfig {
 }
 
 /// 审计配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AuditConfig {
     /// 是否启用审计日志
     pub enabled: bool,
@@ -764,11 +1161,22 @@ impl Default for ServerConfig {
             notifications: NotificationConfig::default(),
             backup: BackupConfig::default(),
             audit: AuditConfig::default(),
+            rpc: RpcConfig::default(),
+            schema_registry: SchemaRegistryConfig::default(),
+            validation_profiles: HashMap::new(),
+            jobs: JobsConfig::default(),
+            request_decompression: RequestDecompressionConfig::default(),
+            warmup: WarmupConfig::default(),
         }
     }
 }
 
 impl ServerConfig {
+    /// 配置文件的JSON Schema（`--print-config-schema` 输出，`--validate-config` 按它校验）
+    pub fn json_schema() -> serde_json::Value {
+        schemars::schema_for!(ServerConfig).to_value()
+    }
+
     /// 获取服务器监听地址
     pub fn listen_address(&self) -> String {
         format!("{}:{}", self.server.host, self.server.port)
@@ -817,6 +1225,8 @@ impl ServerConfig {
             return Err(anyhow::anyhow!("Timeout must be greater than 0"));
         }
 
+        self.server.socket_mode()?;
+
         // 安全配置验证
         if self.security.enabled {
             if self.security.jwt_secret == "your-secret-key-here-change-in-production" && 
@@ -827,6 +1237,8 @@ impl ServerConfig {
             if self.security.api_key_enabled && self.security.api_keys.is_empty() {
                 return Err(anyhow::anyhow!("API keys must be configured when API key authentication is enabled"));
             }
+
+            self.security.check_static_api_keys()?;
         }
 
         // 验证配置验证
@@ -916,4 +1328,20 @@ impl EnvironmentConfig {
     pub fn enable_performance_monitoring(&self) -> bool {
         !self.is_development || self.debug_mode
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_matches_schema() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("config/default.toml");
+        let issues = config_schema::validate_file(&ServerConfig::json_schema(), &path).unwrap();
+        assert!(issues.is_empty(), "{:?}", issues);
+
+        // 缺少不带默认值的字段、类型错误都会定位到具体行
+        let source = "[server]\nhost = \"0.0.0.0\"\nport = \"8080\"\n";
+        let issues = config_schema::validate_str(&ServerConfig::json_schema(), config_schema::ConfigFormat::Toml, source).unwrap();
+        assert!(issues.iter().any(|i| i.pointer == "/server/port" && i.line == Some(3)), "{:?}", issues);
+    }
+}
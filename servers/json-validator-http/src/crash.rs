@@ -0,0 +1,58 @@
+//! 崩溃处理
+//!
+//! 请求处理中的panic由 `CatchPanicLayer` 捕获，经 [`panic_response`] 返回带追踪ID的500问题详情，
+//! 连接不会被直接断开，并计入 `json_validator_panics_total`。全局panic钩子（[`install_panic_hook`]，
+//! 基于共享的 `crash-report` crate）记录错误日志，开启崩溃报告时写入包含panic信息、回溯和最近日志的报告文件。
+
+use std::any::Any;
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use crash_report::{panic_message, PanicHook, RecentLogs};
+
+use crate::config::CrashReportConfig;
+use crate::middleware::PrometheusMetrics;
+use crate::models::request_problem;
+
+/// 崩溃报告附带的最近日志条数
+pub const RECENT_LOG_LINES: usize = 200;
+
+/// 安装全局panic钩子
+pub fn install_panic_hook(config: CrashReportConfig, recent_logs: Arc<RecentLogs>) {
+    let mut hook = PanicHook::new("JSON Validator HTTP", env!("CARGO_PKG_VERSION")).with_recent_logs(recent_logs);
+    if config.enabled {
+        hook = hook.with_reports(config.directory);
+    }
+    hook.install();
+}
+
+/// 请求处理panic时返回的响应，panic消息只写入日志，不返回给客户端
+pub fn panic_response(payload: Box<dyn Any + Send + 'static>, metrics: &PrometheusMetrics) -> Response {
+    metrics.record_panic();
+    let problem = request_problem(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "INTERNAL_ERROR",
+        "The server hit an unexpected error while handling the request".to_string(),
+    );
+    tracing::error!(
+        trace_id = %problem.trace_id,
+        panic.message = %panic_message(payload.as_ref()),
+        "Request handler panicked"
+    );
+    problem.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panic_response() {
+        let metrics = PrometheusMetrics::new();
+        let response = panic_response(Box::new("boom"), &metrics);
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(response.headers().contains_key(crate::models::TRACE_ID_HEADER));
+        assert!(metrics.render().contains("json_validator_panics_total 1"));
+    }
+}
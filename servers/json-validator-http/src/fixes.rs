@@ -0,0 +1,197 @@
+//! 验证错误修复建议
+//!
+//! 验证失败且请求设置了 `suggest_fixes` 时，根据Schema验证错误生成可以直接应用到被验证文档上的
+//! JSON Patch（RFC 6902）操作，便于调用方（尤其是由LLM驱动的纠错循环）自动修正后重新验证。
+//!
+//! 只处理能够确定修正方式的错误：缺少必填字段时按字段模式的 `default`、`const`、第一个枚举值或类型零值补上，
+//! 类型不符时尝试转换（如 `"42"` 转为 `42`），枚举值只有大小写或首尾空白不同时替换为模式中的值，
+//! `additionalProperties: false` 不允许的属性直接删除。其他错误没有建议。应用建议后的文档仍需重新验证。
+
+use jsonschema::error::{ValidationError, ValidationErrorKind};
+use serde_json::{json, Value};
+
+/// 根据校验错误生成 JSON Patch 操作，同一路径只保留第一个操作
+pub fn suggest_fixes(document: &Value, errors: &[ValidationError<'_>]) -> Vec<Value> {
+    let mut patch: Vec<Value> = Vec::new();
+    for error in errors {
+        for op in fixes_for(document, error) {
+            if !patch.iter().any(|existing| existing["path"] == op["path"]) {
+                patch.push(op);
+            }
+        }
+    }
+    patch
+}
+
+fn fixes_for(document: &Value, error: &ValidationError<'_>) -> Vec<Value> {
+    let path = error.instance_path.to_string();
+    match &error.kind {
+        ValidationErrorKind::Required { property } => {
+            let Some(property) = property.as_str() else {
+                return Vec::new();
+            };
+            keyword_parent(document, error)
+                .and_then(|schema| schema.get("properties")?.get(property))
+                .and_then(default_value)
+                .map(|value| vec![json!({"op": "add", "path": child_path(&path, property), "value": value})])
+                .unwrap_or_default()
+        }
+        ValidationErrorKind::AdditionalProperties { unexpected } => unexpected
+            .iter()
+            .map(|key| json!({"op": "remove", "path": child_path(&path, key)}))
+            .collect(),
+        ValidationErrorKind::Type { .. } => keyword_parent(document, error)
+            .and_then(|schema| cast(&error.instance, &types(schema)))
+            .map(|value| vec![json!({"op": "replace", "path": path, "value": value})])
+            .unwrap_or_default(),
+        ValidationErrorKind::Enum { options } => {
+            let Some(text) = error.instance.as_str() else {
+                return Vec::new();
+            };
+            let normalized = text.trim().to_lowercase();
+            options
+                .as_array()
+                .and_then(|options| {
+                    options.iter().find(|o| o.as_str().is_some_and(|o| o.to_lowercase() == normalized))
+                })
+                .map(|value| vec![json!({"op": "replace", "path": path, "value": value})])
+                .unwrap_or_default()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// 出错关键字所在的模式对象，经过 `$ref` 等无法在文档中直接定位时为 `None`
+fn keyword_parent<'a>(document: &'a Value, error: &ValidationError<'_>) -> Option<&'a Value> {
+    let mut segments = error.schema_path.clone().into_vec();
+    segments.pop()?;
+    segments.iter().try_fold(document, |node, segment| match node {
+        Value::Object(map) => map.get(segment),
+        Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+/// 模式允许的类型，按声明顺序
+fn types(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(ts)) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// 缺少字段时补上的值
+fn default_value(schema: &Value) -> Option<Value> {
+    if let Some(value) = schema.get("default").or_else(|| schema.get("const")) {
+        return Some(value.clone());
+    }
+    if let Some(first) = schema.get("enum").and_then(Value::as_array).and_then(|options| options.first()) {
+        return Some(first.clone());
+    }
+    types(schema).into_iter().find(|t| *t != "null").and_then(|t| match t {
+        "string" => Some(json!("")),
+        "integer" | "number" => Some(json!(0)),
+        "boolean" => Some(json!(false)),
+        "array" => Some(json!([])),
+        "object" => Some(json!({})),
+        _ => None,
+    })
+}
+
+/// 尝试把值转换为允许的类型之一
+fn cast(value: &Value, targets: &[&str]) -> Option<Value> {
+    targets.iter().find_map(|target| match (*target, value) {
+        ("integer", Value::String(s)) => s.trim().parse::<i64>().ok().map(Value::from),
+        ("integer", Value::Number(n)) => n
+            .as_f64()
+            .filter(|f| f.fract() == 0.0 && f.abs() < i64::MAX as f64)
+            .map(|f| Value::from(f as i64)),
+        ("number", Value::String(s)) => {
+            let s = s.trim();
+            s.parse::<i64>()
+                .ok()
+                .map(Value::from)
+                .or_else(|| s.parse::<f64>().ok().filter(|f| f.is_finite()).map(Value::from))
+        }
+        ("boolean", Value::String(s)) => match s.trim().to_lowercase().as_str() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        ("string", Value::Number(n)) => Some(Value::String(n.to_string())),
+        ("string", Value::Bool(b)) => Some(Value::String(b.to_string())),
+        ("array", v) if !v.is_null() && !v.is_array() => Some(json!([v])),
+        _ => None,
+    })
+}
+
+/// 追加一级 JSON Pointer 路径，按RFC 6901转义
+fn child_path(parent: &str, key: &str) -> String {
+    format!("{}/{}", parent, key.replace('~', "~0").replace('/', "~1"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonschema::JSONSchema;
+
+    fn fixes(schema: Value, instance: Value) -> Vec<Value> {
+        let validator = JSONSchema::compile(&schema).unwrap();
+        let errors: Vec<_> = match validator.validate(&instance) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.collect(),
+        };
+        suggest_fixes(&schema, &errors)
+    }
+
+    #[test]
+    fn test_suggests_patch_operations() {
+        let schema = json!({
+            "type": "object",
+            "required": ["owner", "ticket", "env"],
+            "additionalProperties": false,
+            "properties": {
+                "owner": {"type": "string"},
+                "ticket": {"type": "integer"},
+                "env": {"enum": ["prod", "staging"], "default": "staging"},
+                "urgent": {"type": "boolean"},
+                "labels": {"type": "array", "items": {"type": "string"}},
+                "a/b": {"type": "number"}
+            }
+        });
+        let patch = fixes(schema, json!({
+            "ticket": "42",
+            "urgent": "TRUE",
+            "labels": "ops",
+            "a/b": "1.5",
+            "extra": 1
+        }));
+
+        assert!(patch.contains(&json!({"op": "add", "path": "/owner", "value": ""})));
+        assert!(patch.contains(&json!({"op": "add", "path": "/env", "value": "staging"})));
+        assert!(patch.contains(&json!({"op": "replace", "path": "/ticket", "value": 42})));
+        assert!(patch.contains(&json!({"op": "replace", "path": "/urgent", "value": true})));
+        assert!(patch.contains(&json!({"op": "replace", "path": "/labels", "value": ["ops"]})));
+        assert!(patch.contains(&json!({"op": "replace", "path": "/a~1b", "value": 1.5})));
+        assert!(patch.contains(&json!({"op": "remove", "path": "/extra"})));
+        assert_eq!(patch.len(), 7);
+    }
+
+    #[test]
+    fn test_nested_enum_and_unfixable_errors() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "deploy": {
+                    "type": "object",
+                    "properties": {"env": {"enum": ["prod", "staging"]}, "replicas": {"type": "integer", "minimum": 1}}
+                }
+            }
+        });
+        let patch = fixes(schema.clone(), json!({"deploy": {"env": " Prod ", "replicas": 0}}));
+        assert_eq!(patch, vec![json!({"op": "replace", "path": "/deploy/env", "value": "prod"})]);
+
+        assert!(fixes(schema, json!({"deploy": {"replicas": "many"}})).is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+//! 请求体格式识别
+//!
+//! `/validate/auto` 端点接受 JSON、NDJSON、YAML 和 TOML 格式的请求体。`Content-Type` 明确声明格式时按声明解析，
+//! 否则（如 `text/plain`、`application/octet-stream` 或未声明）根据内容判断：
+//!
+//! 1. 整体是合法JSON时为JSON
+//! 2. 第一个非空行是JSON对象或数组且不止一行时为NDJSON，每个非空行是一个文档
+//! 3. 能解析为非空TOML表时为TOML
+//! 4. 能解析为YAML映射或序列，或以 `---` 开头时为YAML
+//! 5. 都不满足但以 `{` 或 `[` 开头时按JSON处理，以便返回JSON解析错误的位置
+//!
+//! TOML 的日期时间转换为RFC3339字符串，YAML 和 TOML 都转换为JSON值后再验证。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::models::ErrorLocation;
+
+/// 请求体格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadFormat {
+    Json,
+    Ndjson,
+    Yaml,
+    Toml,
+}
+
+impl PayloadFormat {
+    /// 根据 `Content-Type` 判断格式，未声明具体格式时返回 `None`
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime: mime::Mime = content_type.parse().ok()?;
+        let essence = mime.essence_str();
+        match essence {
+            "application/json" => Some(Self::Json),
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl" | "application/x-jsonlines" => {
+                Some(Self::Ndjson)
+            }
+            "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => Some(Self::Yaml),
+            "application/toml" | "text/toml" => Some(Self::Toml),
+            _ if mime.suffix() == Some(mime::JSON) => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// 根据内容判断格式
+    pub fn sniff(body: &str) -> Option<Self> {
+        let body = body.trim_start_matches('\u{feff}');
+        let trimmed = body.trim_start();
+        if trimmed.is_empty() {
+            return None;
+        }
+        if serde_json::from_str::<Value>(body).is_ok() {
+            return Some(Self::Json);
+        }
+
+        let mut lines = body.lines().filter(|line| !line.trim().is_empty());
+        let first = lines.next().map(str::trim);
+        let structured = |line: &str| {
+            serde_json::from_str::<Value>(line).is_ok_and(|value| value.is_object() || value.is_array())
+        };
+        if first.is_some_and(structured) && lines.next().is_some() {
+            return Some(Self::Ndjson);
+        }
+
+        if toml::from_str::<toml::Table>(body).is_ok_and(|table| !table.is_empty()) {
+            return Some(Self::Toml);
+        }
+        if trimmed.starts_with("---")
+            || serde_yaml::from_str::<Value>(body).is_ok_and(|value| value.is_object() || value.is_array())
+        {
+            return Some(Self::Yaml);
+        }
+        if trimmed.starts_with(['{', '[']) {
+            return Some(Self::Json);
+        }
+        None
+    }
+
+    /// 先按 `Content-Type`，再按内容判断格式
+    pub fn detect(content_type: Option<&str>, body: &str) -> Option<Self> {
+        content_type.and_then(Self::from_content_type).or_else(|| Self::sniff(body))
+    }
+}
+
+/// 解析失败
+#[derive(Debug, Clone)]
+pub struct ParseFailure {
+    pub message: String,
+    pub location: Option<ErrorLocation>,
+}
+
+/// 请求体中的一个文档
+#[derive(Debug, Clone)]
+pub struct Document {
+    /// 文档起始行号（从1开始），NDJSON为所在行
+    pub line: usize,
+    pub value: Result<Value, ParseFailure>,
+}
+
+/// 按格式解析请求体，JSON、YAML和TOML只产生一个文档，NDJSON每个非空行产生一个文档
+pub fn parse(format: PayloadFormat, body: &str) -> Vec<Document> {
+    let body = body.trim_start_matches('\u{feff}');
+    match format {
+        PayloadFormat::Json => vec![Document { line: 1, value: parse_json(body, 0) }],
+        PayloadFormat::Ndjson => body
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| Document { line: index + 1, value: parse_json(line, index) })
+            .collect(),
+        PayloadFormat::Yaml => vec![Document { line: 1, value: parse_yaml(body) }],
+        PayloadFormat::Toml => vec![Document { line: 1, value: parse_toml(body) }],
+    }
+}
+
+/// 解析JSON，`line_offset` 为该文本在请求体中之前的行数
+fn parse_json(text: &str, line_offset: usize) -> Result<Value, ParseFailure> {
+    serde_json::from_str(text).map_err(|e| ParseFailure {
+        message: e.to_string(),
+        location: Some(ErrorLocation { line: e.line() + line_offset, column: e.column() }),
+    })
+}
+
+fn parse_yaml(text: &str) -> Result<Value, ParseFailure> {
+    serde_yaml::from_str(text).map_err(|e| ParseFailure {
+        location: e.location().map(|l| ErrorLocation { line: l.line(), column: l.column() }),
+        message: e.to_string(),
+    })
+}
+
+fn parse_toml(text: &str) -> Result<Value, ParseFailure> {
+    let table: toml::Table = toml::from_str(text).map_err(|e| ParseFailure {
+        location: e.span().map(|span| location_at(text, span.start)),
+        message: e.message().to_string(),
+    })?;
+    toml_to_json(toml::Value::Table(table)).map_err(|message| ParseFailure { message, location: None })
+}
+
+/// TOML值转换为JSON值，日期时间转为字符串，非有限浮点数无法表示
+fn toml_to_json(value: toml::Value) -> Result<Value, String> {
+    Ok(match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => serde_json::Number::from_f64(f)
+            .map(Value::Number)
+            .ok_or_else(|| format!("TOML float {} cannot be represented in JSON", f))?,
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect::<Result<_, _>>()?),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| Ok((key, toml_to_json(value)?)))
+                .collect::<Result<_, String>>()?,
+        ),
+    })
+}
+
+/// 字节偏移对应的行列号（从1开始，列按字符计）
+fn location_at(text: &str, offset: usize) -> ErrorLocation {
+    let before = text.get(..offset).unwrap_or(text);
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    ErrorLocation {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_detect_formats() {
+        assert_eq!(PayloadFormat::sniff(r#"{"a": 1}"#), Some(PayloadFormat::Json));
+        assert_eq!(PayloadFormat::sniff("42"), Some(PayloadFormat::Json));
+        assert_eq!(PayloadFormat::sniff("{\"a\":1}\n{\"a\":2}\n"), Some(PayloadFormat::Ndjson));
+        assert_eq!(PayloadFormat::sniff("{\"a\":1}\n{\"a\":\n"), Some(PayloadFormat::Ndjson));
+        assert_eq!(PayloadFormat::sniff("name = \"x\"\n[server]\nport = 80\n"), Some(PayloadFormat::Toml));
+        assert_eq!(PayloadFormat::sniff("name: x\nports:\n  - 80\n"), Some(PayloadFormat::Yaml));
+        assert_eq!(PayloadFormat::sniff("- a\n- b\n"), Some(PayloadFormat::Yaml));
+        assert_eq!(PayloadFormat::sniff("{\"a\": "), Some(PayloadFormat::Json));
+        assert_eq!(PayloadFormat::sniff("just some text"), None);
+        assert_eq!(PayloadFormat::sniff("  \n"), None);
+
+        // 明确声明的格式优先，通用类型仍按内容判断
+        assert_eq!(PayloadFormat::detect(Some("application/x-yaml"), "{\"a\": 1}"), Some(PayloadFormat::Yaml));
+        assert_eq!(PayloadFormat::detect(Some("application/vnd.api+json"), "a: 1"), Some(PayloadFormat::Json));
+        assert_eq!(PayloadFormat::detect(Some("text/plain; charset=utf-8"), "a = 1"), Some(PayloadFormat::Toml));
+    }
+
+    #[test]
+    fn test_parse_documents() {
+        let documents = parse(PayloadFormat::Ndjson, "{\"a\":1}\n\n{\"a\":\n");
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].value.as_ref().unwrap(), &json!({"a": 1}));
+        assert_eq!(documents[1].line, 3);
+        assert_eq!(documents[1].value.as_ref().unwrap_err().location.as_ref().unwrap().line, 3);
+
+        let toml = parse(PayloadFormat::Toml, "when = 1979-05-27T07:32:00Z\n[server]\nport = 80\n");
+        assert_eq!(
+            toml[0].value.as_ref().unwrap(),
+            &json!({"when": "1979-05-27T07:32:00Z", "server": {"port": 80}})
+        );
+        let broken = parse(PayloadFormat::Toml, "a = 1\nb = \n");
+        assert_eq!(broken[0].value.as_ref().unwrap_err().location.as_ref().unwrap().line, 2);
+
+        let yaml = parse(PayloadFormat::Yaml, "name: x\nports: [80, 443]\n");
+        assert_eq!(yaml[0].value.as_ref().unwrap(), &json!({"name": "x", "ports": [80, 443]}));
+    }
+}
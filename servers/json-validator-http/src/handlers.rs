@@ -1,15 +1,17 @@
 //! HTTP请求处理器
 
 use axum::{
-    extract::State,
+    extract::{rejection::JsonRejection, State},
     http::StatusCode,
     response::{IntoResponse, Json},
 };
 use serde::Deserialize;
-use tracing::{debug, warn, error};
+use tracing::{debug, warn, error, Instrument};
 use std::collections::HashMap;
 
+use crate::formats::PayloadFormat;
 use crate::models::*;
+use crate::rpc::RpcMethod;
 
 // 导入日志宏
 use crate::{log_request, log_validation};
@@ -23,30 +25,81 @@ pub async fn health_check() -> impl IntoResponse {
     }))
 }
 
+/// 就绪检查处理器，启动预热完成前返回503
+pub async fn readiness_check(State(state): State<AppState>) -> impl IntoResponse {
+    let (status, body) = if state.warmup.is_ready() {
+        (StatusCode::OK, serde_json::json!({"status": "ready", "warmup": state.warmup.report()}))
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, serde_json::json!({"status": "warming_up"}))
+    };
+    (status, Json(body))
+}
+
 /// JSON-RPC请求处理器
+///
+/// 客户端可以通过 `params.correlation_id` 或 `x-correlation-id` 头传入关联ID（参数优先），
+/// 关联ID会记录在本次请求的日志中，并在响应体和响应头中原样返回。
+/// 追踪ID沿用调用方传入的 `x-trace-id`（见 [`crate::middleware::trace_id`]）。
 pub async fn json_rpc_handler(
     State(state): State<AppState>,
-    request: axum::Json<JsonRpcRequest>,
+    headers: axum::http::HeaderMap,
+    request: Result<axum::Json<JsonRpcRequest>, JsonRejection>,
 ) -> impl IntoResponse {
+    let request_id = crate::middleware::request_trace_id();
+    let header_correlation_id = headers
+        .get(CORRELATION_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_correlation_id);
+
+    // 请求体无法解析时按JSON-RPC规范返回解析错误
+    let request = match request {
+        Ok(request) => request,
+        Err(rejection) => {
+            warn!("Failed to parse JSON-RPC request: {}", rejection.body_text());
+            let error = JsonRpcError::parse_error().with_trace_id(&request_id);
+            let response = create_error_response(error, serde_json::Value::Null);
+            return rpc_response(&request_id, header_correlation_id, response);
+        }
+    };
+
+    let correlation_id = request
+        .params
+        .as_ref()
+        .and_then(|params| params.get("correlation_id"))
+        .and_then(|v| v.as_str())
+        .and_then(parse_correlation_id)
+        .or(header_correlation_id);
+    let span = tracing::info_span!(
+        "rpc",
+        trace_id = %request_id,
+        correlation_id = correlation_id.as_deref().unwrap_or("-"),
+    );
+    let response = dispatch_rpc(&state, &request, &request_id).instrument(span).await;
+    rpc_response(&request_id, correlation_id, response)
+}
+
+/// 校验并分发JSON-RPC请求，错误响应统一附加追踪ID
+async fn dispatch_rpc(state: &AppState, request: &JsonRpcRequest, request_id: &str) -> Json<JsonRpcResponse> {
     let start_time = std::time::Instant::now();
-    let request_id = crate::utils::utils::generate_request_id();
-    
     debug!("Received JSON-RPC request: {:?}", request);
     
     // 验证请求格式
     if let Err(err) = request.validate() {
         warn!("Invalid JSON-RPC request: {}", err.message);
-        return create_error_response(err, request.id.clone());
+        return create_error_response(err.with_trace_id(request_id), request.id.clone());
     }
     
     // 处理请求
-    let response = match request.method.as_str() {
-        "tools/call" => handle_tool_call(&state, &request, &request_id).await,
-        "ping" => handle_ping(&request),
-        "validate_json" => handle_validate_json(&state, &request, &request_id).await,
-        "validate_json_with_schema" => handle_validate_json_with_schema(&state, &request, &request_id).await,
-        "validate_json_batch" => handle_validate_json_batch(&state, &request, &request_id).await,
-        _ => {
+    let response = match state.rpc_router.resolve(&request.method) {
+        Some(RpcMethod::ToolsCall) => handle_tool_call(state, request, request_id).await,
+        Some(RpcMethod::ToolsList) => handle_tools_list(state, request),
+        Some(RpcMethod::Ping) => handle_ping(request),
+        Some(RpcMethod::ValidateJson) => handle_validate_json(state, request, request_id).await,
+        Some(RpcMethod::ValidateJsonWithSchema) => handle_validate_json_with_schema(state, request, request_id).await,
+        Some(RpcMethod::ValidateJsonBatch) => handle_validate_json_batch(state, request, request_id).await,
+        Some(RpcMethod::ValidateMulti) => handle_validate_multi(state, request, request_id).await,
+        Some(RpcMethod::ValidateWithProfile) => handle_validate_with_profile(state, request, request_id).await,
+        None => {
             warn!("Unknown method: {}", request.method);
             create_error_response(
                 JsonRpcError::method_not_found(request.method.clone()),
@@ -64,9 +117,40 @@ pub async fn json_rpc_handler(
         duration
     );
     
+    let mut response = response;
+    if let Some(error) = response.0.error.take() {
+        response.0.error = Some(error.with_trace_id(request_id));
+    }
     response
 }
 
+/// JSON-RPC响应附加追踪ID头，以及客户端传入的关联ID
+fn rpc_response(
+    request_id: &str,
+    correlation_id: Option<String>,
+    mut response: Json<JsonRpcResponse>,
+) -> (axum::http::HeaderMap, Json<JsonRpcResponse>) {
+    let mut headers = axum::http::HeaderMap::new();
+    if let Ok(value) = axum::http::HeaderValue::from_str(request_id) {
+        headers.insert(TRACE_ID_HEADER, value);
+    }
+    if let Some(value) = correlation_id.as_deref().and_then(|id| axum::http::HeaderValue::from_str(id).ok()) {
+        headers.insert(CORRELATION_ID_HEADER, value);
+    }
+    response.0.correlation_id = correlation_id;
+    (headers, response)
+}
+
+/// 未匹配路由处理器
+pub async fn not_found_handler(uri: axum::http::Uri) -> ProblemDetails {
+    request_problem(
+        StatusCode::NOT_FOUND,
+        "NOT_FOUND",
+        format!("No route for {}", uri.path()),
+    )
+    .with_instance(uri.path().to_string())
+}
+
 /// 处理工具调用
 async fn handle_tool_call(
     state: &AppState,
@@ -90,8 +174,8 @@ async fn handle_tool_call(
     debug!("Tool call: {} -> {}", tool_call.name, tool_call.arguments);
     
     // 根据工具名称分发处理
-    match tool_call.name.as_str() {
-        "validate_json" => {
+    match state.rpc_router.resolve_tool(&tool_call.name) {
+        Some(RpcMethod::ValidateJson) => {
             let args: ValidateJsonRequest = match serde_json::from_value(tool_call.arguments) {
                 Ok(args) => args,
                 Err(e) => {
@@ -105,7 +189,7 @@ async fn handle_tool_call(
             
             handle_validate_json_request(state, args, request_id).await
         }
-        "validate_json_with_schema" => {
+        Some(RpcMethod::ValidateJsonWithSchema) => {
             let args: ValidateJsonWithSchemaRequest = match serde_json::from_value(tool_call.arguments) {
                 Ok(args) => args,
                 Err(e) => {
@@ -119,7 +203,7 @@ async fn handle_tool_call(
             
             handle_validate_json_with_schema_request(state, args, request_id).await
         }
-        "validate_json_batch" => {
+        Some(RpcMethod::ValidateJsonBatch) => {
             let args: ValidateJsonBatchRequest = match serde_json::from_value(tool_call.arguments) {
                 Ok(args) => args,
                 Err(e) => {
@@ -133,6 +217,34 @@ async fn handle_tool_call(
             
             handle_validate_json_batch_request(state, args, request_id).await
         }
+        Some(RpcMethod::ValidateMulti) => {
+            let args: ValidateMultiRequest = match serde_json::from_value(tool_call.arguments) {
+                Ok(args) => args,
+                Err(e) => {
+                    error!("Failed to parse validate_multi arguments: {}", e);
+                    return create_error_response(
+                        JsonRpcError::invalid_params("Invalid validate_multi arguments".to_string()),
+                        request.id.clone(),
+                    );
+                }
+            };
+            
+            handle_validate_multi_request(state, args, request_id).await
+        }
+        Some(RpcMethod::ValidateWithProfile) => {
+            let args: ValidateWithProfileRequest = match serde_json::from_value(tool_call.arguments) {
+                Ok(args) => args,
+                Err(e) => {
+                    error!("Failed to parse validate_with_profile arguments: {}", e);
+                    return create_error_response(
+                        JsonRpcError::invalid_params("Invalid validate_with_profile arguments".to_string()),
+                        request.id.clone(),
+                    );
+                }
+            };
+            
+            handle_validate_with_profile_request(state, args, request_id).await
+        }
         _ => {
             warn!("Unknown tool: {}", tool_call.name);
             create_error_response(
@@ -156,6 +268,17 @@ fn handle_ping(request: &JsonRpcRequest) -> Json<JsonRpcResponse> {
     create_success_response(result, request.id.clone())
 }
 
+/// 处理tools/list请求，返回已启用工具及其由参数和结果类型生成的Schema
+fn handle_tools_list(state: &AppState, request: &JsonRpcRequest) -> Json<JsonRpcResponse> {
+    debug!("Handling tools/list request");
+    
+    let result = serde_json::json!({
+        "tools": state.rpc_router.tool_definitions(),
+    });
+    
+    create_success_response(result, request.id.clone())
+}
+
 /// 处理validate_json请求
 async fn handle_validate_json(
     state: &AppState,
@@ -222,6 +345,50 @@ async fn handle_validate_json_batch(
     handle_validate_json_batch_request(state, args, request_id).await
 }
 
+/// 处理validate_multi请求
+async fn handle_validate_multi(
+    state: &AppState,
+    request: &JsonRpcRequest,
+    request_id: &str,
+) -> Json<JsonRpcResponse> {
+    let params = request.params.as_ref().unwrap_or(&serde_json::Value::Null);
+    
+    let args: ValidateMultiRequest = match serde_json::from_value(params.clone()) {
+        Ok(args) => args,
+        Err(e) => {
+            error!("Failed to parse validate_multi arguments: {}", e);
+            return create_error_response(
+                JsonRpcError::invalid_params("Invalid validate_multi arguments".to_string()),
+                request.id.clone(),
+            );
+        }
+    };
+    
+    handle_validate_multi_request(state, args, request_id).await
+}
+
+/// 处理validate_with_profile请求
+async fn handle_validate_with_profile(
+    state: &AppState,
+    request: &JsonRpcRequest,
+    request_id: &str,
+) -> Json<JsonRpcResponse> {
+    let params = request.params.as_ref().unwrap_or(&serde_json::Value::Null);
+    
+    let args: ValidateWithProfileRequest = match serde_json::from_value(params.clone()) {
+        Ok(args) => args,
+        Err(e) => {
+            error!("Failed to parse validate_with_profile arguments: {}", e);
+            return create_error_response(
+                JsonRpcError::invalid_params("Invalid validate_with_profile arguments".to_string()),
+                request.id.clone(),
+            );
+        }
+    };
+    
+    handle_validate_with_profile_request(state, args, request_id).await
+}
+
 /// 处理validate_json请求的具体逻辑
 async fn handle_validate_json_request(
     state: &AppState,
@@ -241,13 +408,13 @@ async fn handle_validate_json_request(
                 result.cache_hit
             );
             
-            let result_value = serde_json::to_value(result).unwrap_or_default();
+            let result_value = result.to_value(options.output);
             create_success_response(result_value, serde_json::Value::String(request_id.to_string()))
         }
         Err(e) => {
             error!("JSON validation failed: {}", e);
             create_error_response(
-                JsonRpcError::internal_error(format!("Validation failed: {}", e)),
+                validation_error("Validation failed", e),
                 serde_json::Value::String(request_id.to_string()),
             )
         }
@@ -264,9 +431,32 @@ async fn handle_validate_json_with_schema_request(
     
     debug!("Validating JSON with schema, options: {:?}", options);
     
+    // 优先使用注册表中的命名Schema
+    let schema = match &args.schema_ref {
+        Some(name) => match state.schema_registry.get(name) {
+            Some(schema) => {
+                spawn_shadow_validation(state, name, &args.json_data);
+                schema
+            }
+            None => {
+                return create_error_response(
+                    JsonRpcError::invalid_params(format!("Unknown schema reference '{}'", name)),
+                    serde_json::Value::String(request_id.to_string()),
+                );
+            }
+        },
+        None if args.schema.is_null() => {
+            return create_error_response(
+                JsonRpcError::invalid_params("Either schema or schema_ref is required".to_string()),
+                serde_json::Value::String(request_id.to_string()),
+            );
+        }
+        None => std::sync::Arc::new(args.schema),
+    };
+    
     match state
         .validator_service
-        .validate_json_with_schema_simple(&args.json_data, &args.schema, &options)
+        .validate_json_with_schema_simple(&args.json_data, &schema, &options)
         .await
     {
         Ok(result) => {
@@ -277,13 +467,13 @@ async fn handle_validate_json_with_schema_request(
                 result.cache_hit
             );
             
-            let result_value = serde_json::to_value(result).unwrap_or_default();
+            let result_value = result.to_value(options.output);
             create_success_response(result_value, serde_json::Value::String(request_id.to_string()))
         }
         Err(e) => {
             error!("JSON schema validation failed: {}", e);
             create_error_response(
-                JsonRpcError::internal_error(format!("Schema validation failed: {}", e)),
+                validation_error("Schema validation failed", e),
                 serde_json::Value::String(request_id.to_string()),
             )
         }
@@ -300,32 +490,28 @@ async fn handle_validate_json_batch_request(
     
     debug!("Validating JSON batch with {} items", args.items.len());
     
+    let submitted_at = chrono::Utc::now();
+    let started = std::time::Instant::now();
     match state
         .validator_service
-        .validate_json_batch(&args.items, &options)
+        .validate_json_batch(&args.items, &options, args.use_result_cache)
         .await
     {
-        Ok(results) => {
-            let success_count = results.iter().filter(|r| r.result.valid).count();
-            let total_count = results.len();
+        Ok(response) => {
             
             log_validation!(
                 tracing::Level::INFO,
-                success_count == total_count,
+                response.summary.failed == 0,
                 0, // 批量验证时间在服务内部计算
                 false
             );
             
-            let result_value = serde_json::json!({
-                "results": results,
-                "summary": {
-                    "total": total_count,
-                    "success": success_count,
-                    "failed": total_count - success_count,
-                }
-            });
-            
-            create_success_response(result_value, serde_json::Value::String(request_id.to_string()))
+            let mut value = response.to_value(options.output);
+            if state.job_history.is_enabled() {
+                let job_id = record_batch_history(state, &response, submitted_at, started.elapsed());
+                value["job_id"] = serde_json::Value::String(job_id);
+            }
+            create_success_response(value, serde_json::Value::String(request_id.to_string()))
         }
         Err(e) => {
             error!("JSON batch validation failed: {}", e);
@@ -337,6 +523,501 @@ async fn handle_validate_json_batch_request(
     }
 }
 
+/// 记录批量验证的任务历史，返回任务ID
+fn record_batch_history(
+    state: &AppState,
+    response: &BatchValidationResponse,
+    submitted_at: chrono::DateTime<chrono::Utc>,
+    duration: std::time::Duration,
+) -> String {
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let failures = response
+        .results
+        .iter()
+        .filter(|r| !r.result.valid)
+        .map(|r| crate::jobs::DocumentFailure { document: r.id.clone(), errors: r.result.errors.clone() })
+        .collect();
+    let entry = crate::job_history::JobHistoryEntry {
+        job_id: job_id.clone(),
+        kind: crate::job_history::JobKind::Batch,
+        schema_id: None,
+        source: None,
+        submitted_at,
+        duration_ms: duration.as_millis() as u64,
+        total: response.summary.total,
+        passed: response.summary.success,
+        failed: response.summary.failed,
+        error: None,
+    };
+    state.job_history.record(entry, failures);
+    job_id
+}
+
+/// 处理validate_multi请求的具体逻辑
+async fn handle_validate_multi_request(
+    state: &AppState,
+    args: ValidateMultiRequest,
+    request_id: &str,
+) -> Json<JsonRpcResponse> {
+    let options = args.options.unwrap_or_default();
+    let mut schemas = args.schemas;
+    
+    // 未内联Schema的项按标识引用注册表中的Schema
+    for entry in schemas.iter_mut().filter(|entry| entry.schema.is_null()) {
+        let Some(id) = entry.id.as_deref() else {
+            return create_error_response(
+                JsonRpcError::invalid_params("Each schema entry requires either 'schema' or 'id'".to_string()),
+                serde_json::Value::String(request_id.to_string()),
+            );
+        };
+        let Some(schema) = state.schema_registry.get(id) else {
+            return create_error_response(
+                JsonRpcError::invalid_params(format!("Unknown schema reference '{}'", id)),
+                serde_json::Value::String(request_id.to_string()),
+            );
+        };
+        spawn_shadow_validation(state, id, &args.json_data);
+        entry.schema = schema.as_ref().clone();
+    }
+    
+    debug!("Validating JSON against {} schemas, mode: {:?}", schemas.len(), args.mode);
+    
+    match state
+        .validator_service
+        .validate_json_multi(&args.json_data, &schemas, args.mode, &options)
+        .await
+    {
+        Ok(result) => {
+            log_validation!(
+                tracing::Level::INFO,
+                result.valid.unwrap_or(true),
+                0, // 各Schema的验证时间见单项结果
+                false
+            );
+            
+            create_success_response(result.to_value(options.output), serde_json::Value::String(request_id.to_string()))
+        }
+        Err(e) => {
+            warn!("Multi-schema validation rejected: {}", e);
+            create_error_response(
+                JsonRpcError::invalid_params(e),
+                serde_json::Value::String(request_id.to_string()),
+            )
+        }
+    }
+}
+
+/// 处理validate_with_profile请求的具体逻辑
+async fn handle_validate_with_profile_request(
+    state: &AppState,
+    args: ValidateWithProfileRequest,
+    request_id: &str,
+) -> Json<JsonRpcResponse> {
+    let options = args.options.unwrap_or_default();
+    let response_id = serde_json::Value::String(request_id.to_string());
+    
+    let Some(profile) = state.config.validation_profiles.get(&args.profile) else {
+        return create_error_response(
+            JsonRpcError::invalid_params(format!("Unknown validation profile '{}'", args.profile)),
+            response_id,
+        );
+    };
+    
+    // 配置档的Schema引用优先于内联Schema
+    let schema = match (&profile.schema_ref, &profile.schema) {
+        (Some(name), _) => {
+            spawn_shadow_validation(state, name, &args.json_data);
+            state.schema_registry.get(name)
+        }
+        (None, Some(schema)) => Some(std::sync::Arc::new(schema.clone())),
+        (None, None) => None,
+    };
+    let Some(schema) = schema else {
+        error!("Validation profile '{}' has no resolvable schema", args.profile);
+        return create_error_response(
+            JsonRpcError::internal_error(format!("Validation profile '{}' has no resolvable schema", args.profile)),
+            response_id,
+        );
+    };
+    
+    debug!("Validating JSON with profile '{}'", args.profile);
+    
+    match state
+        .validator_service
+        .validate_with_profile(&args.json_data, &schema, profile, &options)
+        .await
+    {
+        Ok(result) => {
+            log_validation!(
+                tracing::Level::INFO,
+                result.valid,
+                result.execution_time,
+                result.cache_hit
+            );
+            
+            create_success_response(result.to_value(options.output), response_id)
+        }
+        Err(e) => {
+            error!("Profile validation failed: {}", e);
+            create_error_response(
+                validation_error("Profile validation failed", e),
+                response_id,
+            )
+        }
+    }
+}
+
+/// 注册Schema存在候选版本时，在后台比较当前版本与候选版本的结论，
+/// 不一致时记录日志和指标，不影响返回给客户端的结果
+fn spawn_shadow_validation(state: &AppState, name: &str, json_data: &serde_json::Value) {
+    let (Some(active), Some(candidate)) = (state.schema_registry.get(name), state.schema_registry.candidate(name)) else {
+        return;
+    };
+    let service = state.validator_service.clone();
+    let prometheus = state.prometheus.clone();
+    let name = name.to_string();
+    let json_data = json_data.clone();
+    
+    tokio::spawn(async move {
+        match service.shadow_validate(&json_data, &active, &candidate).await {
+            Ok((active_valid, candidate_valid)) => {
+                if active_valid != candidate_valid {
+                    warn!(
+                        "Shadow validation mismatch for schema '{}': active={}, candidate={}",
+                        name, active_valid, candidate_valid
+                    );
+                }
+                prometheus.record_shadow(&name, active_valid == candidate_valid);
+            }
+            Err(e) => warn!("Shadow validation for schema '{}' failed: {}", name, e),
+        }
+    });
+}
+
+/// `/validate/auto` 的查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct AutoValidateQuery {
+    /// 注册表中的命名Schema，未指定时只检查格式
+    pub schema_ref: Option<String>,
+    /// 结果输出格式
+    pub output: Option<OutputFormat>,
+    /// 验证失败时返回修复建议
+    #[serde(default)]
+    pub suggest_fixes: bool,
+    /// 验证通过时返回规范化后的文档
+    #[serde(default)]
+    pub normalize: bool,
+}
+
+/// 自动识别请求体格式（JSON、NDJSON、YAML、TOML）并验证，响应中返回识别出的格式
+pub async fn auto_validate_handler(
+    State(state): State<AppState>,
+    axum::extract::Query(query): axum::extract::Query<AutoValidateQuery>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<impl IntoResponse, ProblemDetails> {
+    let body = std::str::from_utf8(&body).map_err(|e| {
+        request_problem(StatusCode::BAD_REQUEST, "VALIDATION_ERROR", format!("Request body is not valid UTF-8: {}", e))
+    })?;
+    if body.trim().is_empty() {
+        return Err(request_problem(StatusCode::BAD_REQUEST, "VALIDATION_ERROR", "Request body is empty".to_string()));
+    }
+    let content_type = headers.get(axum::http::header::CONTENT_TYPE).and_then(|v| v.to_str().ok());
+    let format = PayloadFormat::detect(content_type, body).ok_or_else(|| {
+        request_problem(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "UNSUPPORTED_FORMAT",
+            "Unable to detect payload format, expected JSON, NDJSON, YAML or TOML".to_string(),
+        )
+    })?;
+    let schema = match &query.schema_ref {
+        Some(name) => Some(registered_schema(&state, name)?),
+        None => None,
+    };
+    let options = ValidationOptions {
+        output: query.output,
+        suggest_fixes: query.suggest_fixes,
+        normalize: query.normalize,
+        ..Default::default()
+    };
+
+    let mut results = Vec::new();
+    for document in crate::formats::parse(format, body) {
+        let result = match document.value {
+            Ok(value) => {
+                if let Some(name) = &query.schema_ref {
+                    spawn_shadow_validation(&state, name, &value);
+                }
+                state
+                    .validator_service
+                    .validate_json(&value, schema.as_deref(), &options)
+                    .await
+                    .unwrap_or_else(|e| document_failure(e, "VALIDATION_ERROR", None))
+            }
+            Err(failure) => document_failure(failure.message, "PARSE_ERROR", failure.location),
+        };
+        results.push((document.line, result));
+    }
+
+    let mut response = match format {
+        PayloadFormat::Ndjson => BatchValidationResponse::new(
+            results
+                .into_iter()
+                .map(|(line, result)| BatchValidationResult { id: line.to_string(), result })
+                .collect(),
+        )
+        .to_value(query.output),
+        _ => serde_json::json!({ "result": results[0].1.to_value(query.output) }),
+    };
+    response["format"] = serde_json::json!(format);
+    Ok(Json(response))
+}
+
+/// 无法验证的文档（解析失败或Schema无法编译）
+fn document_failure(message: String, error_code: &str, location: Option<ErrorLocation>) -> ValidationResult {
+    ValidationResult::failure(
+        vec![ValidationError {
+            instance_path: "".to_string(),
+            schema_path: "".to_string(),
+            message,
+            error_code: error_code.to_string(),
+            location,
+        }],
+        0,
+        false,
+    )
+}
+
+/// 创建重新验证任务（目录或S3数据源）
+pub async fn create_revalidation_job_handler(
+    State(state): State<AppState>,
+    request: Result<Json<crate::jobs::CreateJobRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, ProblemDetails> {
+    let Json(request) = request.map_err(|rejection| {
+        request_problem(StatusCode::BAD_REQUEST, "VALIDATION_ERROR", rejection.body_text())
+    })?;
+    let schema = registered_schema(&state, &request.schema_id)?;
+
+    // 目录扫描是阻塞IO
+    let jobs = state.jobs.clone();
+    let source = request.source.clone();
+    let documents = tokio::task::spawn_blocking(move || jobs.collect_documents(&source))
+        .await
+        .map_err(|e| request_problem(StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", e.to_string()))?
+        .map_err(source_problem)?;
+
+    let progress = state.jobs.start(
+        request.schema_id,
+        schema,
+        request.source.describe(),
+        documents,
+        state.validator_service.clone(),
+    );
+    Ok((StatusCode::ACCEPTED, Json(progress)))
+}
+
+/// 上传tar或tar.gz归档创建重新验证任务
+pub async fn create_archive_revalidation_job_handler(
+    State(state): State<AppState>,
+    axum::extract::Query(query): axum::extract::Query<crate::jobs::ArchiveJobQuery>,
+    archive: axum::body::Bytes,
+) -> Result<impl IntoResponse, ProblemDetails> {
+    let schema = registered_schema(&state, &query.schema_id)?;
+
+    let jobs = state.jobs.clone();
+    let documents = tokio::task::spawn_blocking(move || jobs.unpack_archive(&archive))
+        .await
+        .map_err(|e| request_problem(StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", e.to_string()))?
+        .map_err(source_problem)?;
+
+    let progress = state.jobs.start(
+        query.schema_id,
+        schema,
+        "archive".to_string(),
+        documents,
+        state.validator_service.clone(),
+    );
+    Ok((StatusCode::ACCEPTED, Json(progress)))
+}
+
+/// 查询任务历史
+pub async fn list_job_history_handler(
+    State(state): State<AppState>,
+    axum::extract::Query(query): axum::extract::Query<crate::job_history::JobHistoryQuery>,
+) -> impl IntoResponse {
+    Json(serde_json::json!({ "jobs": state.job_history.list(&query) }))
+}
+
+/// 查询重新验证任务进度，已结束并移出内存的任务返回任务历史
+pub async fn get_revalidation_job_handler(
+    State(state): State<AppState>,
+    axum::extract::Path(job_id): axum::extract::Path<String>,
+) -> Result<axum::response::Response, ProblemDetails> {
+    if let Some(progress) = state.jobs.progress(&job_id) {
+        return Ok(Json(progress).into_response());
+    }
+    state
+        .job_history
+        .get(&job_id)
+        .map(|entry| Json(entry).into_response())
+        .ok_or_else(|| job_not_found(&job_id))
+}
+
+/// 失败报告下载格式
+#[derive(Debug, Default, Deserialize)]
+pub struct JobReportQuery {
+    /// `json`（默认）或 `csv`
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// 下载任务的失败报告，运行中的重新验证任务返回当前报告，其他任务从任务历史读取
+pub async fn get_revalidation_report_handler(
+    State(state): State<AppState>,
+    axum::extract::Path(job_id): axum::extract::Path<String>,
+    axum::extract::Query(query): axum::extract::Query<JobReportQuery>,
+) -> Result<axum::response::Response, ProblemDetails> {
+    let csv = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("csv") => true,
+        Some(other) => {
+            return Err(request_problem(
+                StatusCode::BAD_REQUEST,
+                "VALIDATION_ERROR",
+                format!("Unsupported report format '{}', expected json or csv", other),
+            ));
+        }
+    };
+
+    let (report, failures) = match state.jobs.report(&job_id) {
+        Some(report) => (serde_json::to_value(&report), report.failures),
+        None => {
+            let report = state.job_history.report(&job_id).ok_or_else(|| job_not_found(&job_id))?;
+            (serde_json::to_value(&report), report.failures)
+        }
+    };
+
+    if csv {
+        let disposition = format!("attachment; filename=\"job-{}.csv\"", job_id);
+        return Ok((
+            [
+                (axum::http::header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (axum::http::header::CONTENT_DISPOSITION, disposition),
+            ],
+            crate::job_history::report_to_csv(&failures),
+        )
+            .into_response());
+    }
+    let report = report
+        .map_err(|e| request_problem(StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", e.to_string()))?;
+    let disposition = format!("attachment; filename=\"job-{}.json\"", job_id);
+    Ok(([(axum::http::header::CONTENT_DISPOSITION, disposition)], Json(report)).into_response())
+}
+
+fn registered_schema(state: &AppState, schema_id: &str) -> Result<std::sync::Arc<serde_json::Value>, Box<ProblemDetails>> {
+    state.schema_registry.get(schema_id).ok_or_else(|| {
+        Box::new(request_problem(StatusCode::NOT_FOUND, "NOT_FOUND", format!("Schema not registered: {}", schema_id)))
+    })
+}
+
+fn job_not_found(job_id: &str) -> ProblemDetails {
+    request_problem(StatusCode::NOT_FOUND, "NOT_FOUND", format!("Revalidation job not found: {}", job_id))
+}
+
+fn source_problem(error: crate::jobs::SourceError) -> ProblemDetails {
+    let (status, code) = match error {
+        crate::jobs::SourceError::Forbidden(_) => (StatusCode::FORBIDDEN, "FORBIDDEN"),
+        crate::jobs::SourceError::Invalid(_) | crate::jobs::SourceError::Unsupported(_) => {
+            (StatusCode::BAD_REQUEST, "VALIDATION_ERROR")
+        }
+    };
+    request_problem(status, code, error.to_string())
+}
+
+/// 签发API密钥
+pub async fn create_api_key_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    request: Result<Json<crate::api_keys::CreateApiKeyRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, ProblemDetails> {
+    require_admin(&state, &headers)?;
+    let Json(request) = request.map_err(|rejection| {
+        request_problem(StatusCode::BAD_REQUEST, "VALIDATION_ERROR", rejection.body_text())
+    })?;
+    let issued = state.api_keys.create(request).map_err(api_key_problem)?;
+    Ok((StatusCode::CREATED, Json(issued)))
+}
+
+/// 列出API密钥（不含明文密钥）
+pub async fn list_api_keys_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<impl IntoResponse, ProblemDetails> {
+    require_admin(&state, &headers)?;
+    Ok(Json(state.api_keys.list()))
+}
+
+/// 吊销API密钥
+pub async fn revoke_api_key_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(key_id): axum::extract::Path<String>,
+) -> Result<impl IntoResponse, ProblemDetails> {
+    require_admin(&state, &headers)?;
+    let record = state.api_keys.revoke(&key_id).map_err(api_key_problem)?;
+    Ok(Json(record))
+}
+
+/// 轮换API密钥，返回新密钥并吊销旧密钥
+pub async fn rotate_api_key_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(key_id): axum::extract::Path<String>,
+    request: Option<Json<crate::api_keys::RotateApiKeyRequest>>,
+) -> Result<impl IntoResponse, ProblemDetails> {
+    require_admin(&state, &headers)?;
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let issued = state.api_keys.rotate(&key_id, request).map_err(api_key_problem)?;
+    Ok((StatusCode::CREATED, Json(issued)))
+}
+
+/// 启用API密钥认证时，管理端点要求具有 `admin` 权限的密钥
+fn require_admin(state: &AppState, headers: &axum::http::HeaderMap) -> Result<(), Box<ProblemDetails>> {
+    if !api_keys_enforced(&state.config) {
+        return Ok(());
+    }
+    match state.api_keys.authenticate_headers(headers) {
+        Some(key) if key.has_scope(crate::api_keys::SCOPE_ADMIN) => Ok(()),
+        Some(key) => Err(Box::new(request_problem(
+            StatusCode::FORBIDDEN,
+            "FORBIDDEN",
+            format!("API key '{}' lacks the 'admin' scope", key.id),
+        ))),
+        None => Err(Box::new(request_problem(
+            StatusCode::UNAUTHORIZED,
+            "UNAUTHORIZED",
+            "Missing or invalid API key".to_string(),
+        ))),
+    }
+}
+
+/// 是否对JSON-RPC和管理端点强制API密钥认证
+pub fn api_keys_enforced(config: &crate::config::ServerConfig) -> bool {
+    config.security.enabled && config.security.api_key_enabled
+}
+
+fn api_key_problem(error: crate::api_keys::ApiKeyStoreError) -> ProblemDetails {
+    use crate::api_keys::ApiKeyStoreError;
+    let (status, code) = match error {
+        ApiKeyStoreError::NotFound(_) => (StatusCode::NOT_FOUND, "NOT_FOUND"),
+        ApiKeyStoreError::Revoked(_) => (StatusCode::CONFLICT, "CONFLICT"),
+        ApiKeyStoreError::Io(_) | ApiKeyStoreError::Serialization(_) => {
+            (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR")
+        }
+    };
+    request_problem(status, code, error.to_string())
+}
+
 /// 健康检查处理器
 pub async fn health_handler(State(state): State<AppState>) -> impl IntoResponse {
     let health_response = HealthCheckResponse {
@@ -390,11 +1071,7 @@ pub async fn server_info_handler(State(state): State<AppState>) -> impl IntoResp
         build_time: std::env::var("BUILD_TIME").unwrap_or_else(|_| "unknown".to_string()),
         build_hash: std::env::var("BUILD_HASH").unwrap_or_else(|_| "unknown".to_string()),
         capabilities: ServerCapabilities {
-            tools: vec![
-                "validate_json".to_string(),
-                "validate_json_with_schema".to_string(),
-                "validate_json_batch".to_string(),
-            ],
+            tools: state.rpc_router.enabled_tools().iter().map(|t| t.to_string()).collect(),
             formats: vec!["JSON".to_string(), "JSON Schema".to_string()],
             cache: state.config.cache.enabled,
             batch: true,
@@ -421,6 +1098,21 @@ pub async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse
     Json(metrics)
 }
 
+/// Prometheus指标处理器，导出前刷新Schema缓存统计和验证线程池状态
+pub async fn prometheus_metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let stats = state.validator_service.get_stats().await;
+    let cache_entries = state.validator_service.schema_cache_size().await;
+    state.prometheus.set_cache_stats(stats.cache_hits, stats.cache_misses, cache_entries);
+    if let Some(pool_stats) = state.validator_service.validation_pool_stats() {
+        state.prometheus.set_validation_pool_stats(&pool_stats);
+    }
+
+    (
+        [(axum::http::header::CONTENT_TYPE, crate::middleware::prometheus_metrics::PROMETHEUS_CONTENT_TYPE)],
+        state.prometheus.render(),
+    )
+}
+
 /// 工具调用请求
 #[derive(Debug, Deserialize)]
 struct ToolCallRequest {
@@ -436,6 +1128,15 @@ fn create_success_response(result: serde_json::Value, id: serde_json::Value) ->
 }
 
 /// 创建错误响应
+/// 验证失败的JSON-RPC错误，线程池排队已满时返回过载错误
+fn validation_error(context: &str, message: String) -> JsonRpcError {
+    if message == crate::validation_pool::REJECTED_MESSAGE {
+        JsonRpcError::overloaded(message)
+    } else {
+        JsonRpcError::internal_error(format!("{}: {}", context, message))
+    }
+}
+
 fn create_error_response(error: JsonRpcError, id: serde_json::Value) -> Json<JsonRpcResponse> {
     Json(JsonRpcResponse::error(error, id))
 }
@@ -0,0 +1,128 @@
+//! 验证错误和API错误消息的本地化
+//!
+//! 消息目录嵌入在 `locales/<tag>.json` 中，目前支持 `en` 和 `zh-CN`。请求语言按以下顺序确定：
+//! 验证选项的 `locale` 字段、`lang` 查询参数、`Accept-Language` 请求头，都未指定时使用英文。
+
+use std::sync::OnceLock;
+
+use jsonschema::error::{TypeKind, ValidationErrorKind};
+use message_catalog::MessageCatalog;
+
+pub use message_catalog::Locale;
+
+fn catalog() -> &'static MessageCatalog {
+    static CATALOG: OnceLock<MessageCatalog> = OnceLock::new();
+    CATALOG.get_or_init(|| {
+        MessageCatalog::from_sources(&[
+            (Locale::En, include_str!("../locales/en.json")),
+            (Locale::ZhCn, include_str!("../locales/zh-CN.json")),
+        ])
+    })
+}
+
+/// 查找消息模板并填充 `{0}`、`{1}` 等占位符，缺少时回退到英文
+pub fn translate(locale: Locale, key: &str, args: &[String]) -> String {
+    catalog().translate(locale, key, args)
+}
+
+/// 查找消息模板，不存在时返回 `None`
+pub fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    catalog().lookup(locale, key)
+}
+
+/// 验证选项指定的语言优先，否则使用当前请求协商出的语言
+pub fn resolve_locale(requested: Option<&str>) -> Locale {
+    requested
+        .and_then(Locale::from_tag)
+        .unwrap_or_else(crate::middleware::request_locale)
+}
+
+/// Schema验证错误的本地化消息
+///
+/// 英文沿用jsonschema的原文（含单复数处理），其他语言按消息目录翻译；
+/// 目录未覆盖的错误类型同样回退到英文原文。
+pub fn schema_error_message(error: &jsonschema::ValidationError, locale: Locale) -> String {
+    if locale == Locale::En {
+        return error.to_string();
+    }
+
+    let instance = error.instance.to_string();
+    let (key, args): (&str, Vec<String>) = match &error.kind {
+        ValidationErrorKind::Required { property } => ("schema.required", vec![property.to_string()]),
+        ValidationErrorKind::Type { kind } => {
+            let types = match kind {
+                TypeKind::Single(primitive) => format!("\"{}\"", primitive),
+                TypeKind::Multiple(primitives) => (*primitives)
+                    .into_iter()
+                    .map(|primitive| format!("\"{}\"", primitive))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            };
+            ("schema.type", vec![instance, types])
+        }
+        ValidationErrorKind::Minimum { limit } => ("schema.minimum", vec![instance, limit.to_string()]),
+        ValidationErrorKind::Maximum { limit } => ("schema.maximum", vec![instance, limit.to_string()]),
+        ValidationErrorKind::ExclusiveMinimum { limit } => ("schema.exclusive_minimum", vec![instance, limit.to_string()]),
+        ValidationErrorKind::ExclusiveMaximum { limit } => ("schema.exclusive_maximum", vec![instance, limit.to_string()]),
+        ValidationErrorKind::MinLength { limit } => ("schema.min_length", vec![instance, limit.to_string()]),
+        ValidationErrorKind::MaxLength { limit } => ("schema.max_length", vec![instance, limit.to_string()]),
+        ValidationErrorKind::MinItems { limit } => ("schema.min_items", vec![instance, limit.to_string()]),
+        ValidationErrorKind::MaxItems { limit } => ("schema.max_items", vec![instance, limit.to_string()]),
+        ValidationErrorKind::MinProperties { limit } => ("schema.min_properties", vec![instance, limit.to_string()]),
+        ValidationErrorKind::MaxProperties { limit } => ("schema.max_properties", vec![instance, limit.to_string()]),
+        ValidationErrorKind::Enum { options } => ("schema.enum", vec![instance, options.to_string()]),
+        ValidationErrorKind::Constant { expected_value } => ("schema.const", vec![expected_value.to_string()]),
+        ValidationErrorKind::Pattern { pattern } => ("schema.pattern", vec![instance, pattern.clone()]),
+        ValidationErrorKind::Format { format } => ("schema.format", vec![instance, format.to_string()]),
+        ValidationErrorKind::AdditionalProperties { unexpected } => {
+            let unexpected = unexpected.iter().map(|name| format!("'{}'", name)).collect::<Vec<_>>().join(", ");
+            ("schema.additional_properties", vec![unexpected])
+        }
+        ValidationErrorKind::MultipleOf { multiple_of } => ("schema.multiple_of", vec![instance, multiple_of.to_string()]),
+        ValidationErrorKind::AnyOf => ("schema.any_of", vec![instance]),
+        ValidationErrorKind::OneOfNotValid => ("schema.one_of_not_valid", vec![instance]),
+        ValidationErrorKind::OneOfMultipleValid => ("schema.one_of_multiple_valid", vec![instance]),
+        ValidationErrorKind::Not { schema } => ("schema.not", vec![schema.to_string(), instance]),
+        ValidationErrorKind::UniqueItems => ("schema.unique_items", vec![instance]),
+        ValidationErrorKind::Contains => ("schema.contains", vec![instance]),
+        ValidationErrorKind::FalseSchema => ("schema.false_schema", vec![instance]),
+        _ => return error.to_string(),
+    };
+    translate(locale, key, &args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalogs_have_same_keys() {
+        let missing = catalog().inconsistent_keys();
+        assert!(missing.is_empty(), "catalog keys differ: {:?}", missing);
+    }
+
+    #[test]
+    fn test_schema_error_message() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": {"age": {"type": "integer", "minimum": 0}}
+        });
+        let compiled = jsonschema::JSONSchema::compile(&schema).unwrap();
+        let instance = serde_json::json!({"age": -1});
+        let mut messages: Vec<_> = compiled
+            .validate(&instance)
+            .unwrap_err()
+            .map(|e| (schema_error_message(&e, Locale::ZhCn), schema_error_message(&e, Locale::En)))
+            .collect();
+        messages.sort();
+
+        assert_eq!(
+            messages,
+            vec![
+                ("-1 小于最小值 0".to_string(), "-1 is less than the minimum of 0".to_string()),
+                ("缺少必需属性 \"name\"".to_string(), "\"name\" is a required property".to_string()),
+            ]
+        );
+    }
+}
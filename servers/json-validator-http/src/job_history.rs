@@ -0,0 +1,332 @@
+//! 验证任务历史
+//!
+//! `validate_json_batch` 批量验证和后台重新验证任务结束后各记录一条历史（提交时间、耗时、通过/失败数），
+//! 可按时间查询，并下载JSON或CSV格式的失败报告。配置了 `cache.job_history.directory` 时每条历史连同失败报告
+//! 写入目录中的 `<job_id>.json`，重启后仍可查询；未配置时只保存在内存中。
+//! 超过保留期或条数上限的历史在写入新记录时删除。
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::JobHistoryConfig;
+use crate::jobs::DocumentFailure;
+
+/// 任务类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// `validate_json_batch` 批量验证
+    Batch,
+    /// 后台重新验证任务
+    Revalidation,
+}
+
+/// 一条任务历史
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobHistoryEntry {
+    /// 任务ID
+    pub job_id: String,
+    /// 任务类型
+    pub kind: JobKind,
+    /// Schema名称（重新验证任务）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_id: Option<String>,
+    /// 数据源描述（重新验证任务）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// 提交时间
+    pub submitted_at: DateTime<Utc>,
+    /// 耗时（毫秒）
+    pub duration_ms: u64,
+    /// 文档总数
+    pub total: usize,
+    /// 通过验证的文档数
+    pub passed: usize,
+    /// 验证失败的文档数
+    pub failed: usize,
+    /// 任务异常终止的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 任务历史报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobHistoryReport {
+    #[serde(flatten)]
+    pub entry: JobHistoryEntry,
+    /// 失败文档（超过上限的部分不保留）
+    pub failures: Vec<DocumentFailure>,
+    /// 报告是否因上限被截断
+    pub truncated: bool,
+}
+
+/// 任务历史查询条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct JobHistoryQuery {
+    /// 只返回此时间之后提交的任务
+    pub since: Option<DateTime<Utc>>,
+    /// 只返回指定类型的任务
+    pub kind: Option<JobKind>,
+    /// 返回条数上限
+    pub limit: Option<usize>,
+}
+
+/// 任务历史存储
+pub struct JobHistory {
+    config: JobHistoryConfig,
+    /// 按提交时间排序的历史；未配置目录时报告也保存在这里
+    entries: RwLock<VecDeque<JobHistoryReport>>,
+}
+
+impl JobHistory {
+    /// 创建任务历史存储，配置了目录时加载其中的历史
+    pub fn from_config(config: &JobHistoryConfig) -> Self {
+        let history = Self {
+            config: config.clone(),
+            entries: RwLock::new(VecDeque::new()),
+        };
+        if let Some(directory) = config.directory.as_deref().filter(|_| config.enabled) {
+            match load(directory) {
+                Ok(mut entries) => {
+                    entries.sort_by_key(|report| report.entry.submitted_at);
+                    *history.entries.write() = entries.into();
+                    history.prune(Utc::now());
+                }
+                Err(e) => warn!("Failed to load job history from {}: {}", directory.display(), e),
+            }
+        }
+        history
+    }
+
+    /// 是否记录任务历史
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// 记录一个已结束的任务
+    pub fn record(&self, entry: JobHistoryEntry, mut failures: Vec<DocumentFailure>) {
+        if !self.config.enabled {
+            return;
+        }
+        failures.truncate(self.config.max_reported_failures);
+        let truncated = failures.len() < entry.failed;
+        let mut report = JobHistoryReport { entry, failures, truncated };
+
+        if let Some(directory) = &self.config.directory {
+            if let Err(e) = persist(directory, &report) {
+                warn!("Failed to persist job history {}: {}", report.entry.job_id, e);
+            }
+            // 报告只保存在文件中
+            report.failures = Vec::new();
+        }
+        {
+            let mut entries = self.entries.write();
+            let index = entries.partition_point(|r| r.entry.submitted_at <= report.entry.submitted_at);
+            entries.insert(index, report);
+        }
+        self.prune(Utc::now());
+    }
+
+    /// 按条件查询历史，最新的在前
+    pub fn list(&self, query: &JobHistoryQuery) -> Vec<JobHistoryEntry> {
+        self.entries
+            .read()
+            .iter()
+            .rev()
+            .filter(|r| query.since.is_none_or(|since| r.entry.submitted_at >= since))
+            .filter(|r| query.kind.is_none_or(|kind| r.entry.kind == kind))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .map(|r| r.entry.clone())
+            .collect()
+    }
+
+    /// 查询单个任务的历史
+    pub fn get(&self, job_id: &str) -> Option<JobHistoryEntry> {
+        self.entries.read().iter().find(|r| r.entry.job_id == job_id).map(|r| r.entry.clone())
+    }
+
+    /// 读取任务的失败报告
+    pub fn report(&self, job_id: &str) -> Option<JobHistoryReport> {
+        let report = self.entries.read().iter().find(|r| r.entry.job_id == job_id).cloned()?;
+        match &self.config.directory {
+            // 只读取索引中存在的任务，任务ID不会被拼接成任意路径
+            Some(directory) => match read_report(&report_path(directory, job_id)) {
+                Ok(stored) => Some(stored),
+                Err(e) => {
+                    warn!("Failed to read job history report {}: {}", job_id, e);
+                    None
+                }
+            },
+            None => Some(report),
+        }
+    }
+
+    /// 删除超过保留期或条数上限的历史
+    fn prune(&self, now: DateTime<Utc>) {
+        let cutoff = now - chrono::Duration::days(self.config.retention_days as i64);
+        let mut removed = Vec::new();
+        {
+            let mut entries = self.entries.write();
+            while let Some(oldest) = entries.front() {
+                if oldest.entry.submitted_at >= cutoff && entries.len() <= self.config.max_entries {
+                    break;
+                }
+                removed.extend(entries.pop_front().map(|r| r.entry.job_id));
+            }
+        }
+        if let Some(directory) = &self.config.directory {
+            for job_id in removed {
+                if let Err(e) = std::fs::remove_file(report_path(directory, &job_id)) {
+                    warn!("Failed to remove expired job history {}: {}", job_id, e);
+                }
+            }
+        }
+    }
+}
+
+/// 失败报告转换为CSV，每个验证错误一行
+pub fn report_to_csv(failures: &[DocumentFailure]) -> String {
+    let mut csv = String::from("document,instance_path,schema_path,error_code,message\n");
+    for failure in failures {
+        for error in &failure.errors {
+            let fields = [
+                failure.document.as_str(),
+                error.instance_path.as_str(),
+                error.schema_path.as_str(),
+                error.error_code.as_str(),
+                error.message.as_str(),
+            ];
+            let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+    }
+    csv
+}
+
+/// 按RFC 4180转义CSV字段
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn report_path(directory: &Path, job_id: &str) -> PathBuf {
+    directory.join(format!("{}.json", job_id))
+}
+
+fn persist(directory: &Path, report: &JobHistoryReport) -> std::io::Result<()> {
+    std::fs::create_dir_all(directory)?;
+    let path = report_path(directory, &report.entry.job_id);
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_vec(report)?)?;
+    std::fs::rename(&tmp, path)
+}
+
+fn read_report(path: &Path) -> std::io::Result<JobHistoryReport> {
+    Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+}
+
+/// 加载目录中的历史，只保留摘要，损坏的文件跳过
+fn load(directory: &Path) -> std::io::Result<Vec<JobHistoryReport>> {
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    for file in std::fs::read_dir(directory)? {
+        let path = file?.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        match read_report(&path) {
+            Ok(report) => entries.push(JobHistoryReport { failures: Vec::new(), ..report }),
+            Err(e) => warn!("Skipping corrupted job history file {}: {}", path.display(), e),
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ValidationError;
+
+    fn entry(job_id: &str, submitted_at: DateTime<Utc>, failed: usize) -> JobHistoryEntry {
+        JobHistoryEntry {
+            job_id: job_id.to_string(),
+            kind: JobKind::Batch,
+            schema_id: None,
+            source: None,
+            submitted_at,
+            duration_ms: 5,
+            total: 3,
+            passed: 3 - failed,
+            failed,
+            error: None,
+        }
+    }
+
+    fn failure(document: &str, message: &str) -> DocumentFailure {
+        DocumentFailure {
+            document: document.to_string(),
+            errors: vec![ValidationError {
+                instance_path: "/name".to_string(),
+                schema_path: "/properties/name/type".to_string(),
+                message: message.to_string(),
+                error_code: "SCHEMA_VALIDATION_ERROR".to_string(),
+                location: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_persisted_history_and_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = JobHistoryConfig {
+            directory: Some(dir.path().to_path_buf()),
+            max_entries: 2,
+            ..Default::default()
+        };
+        let now = Utc::now();
+        let history = JobHistory::from_config(&config);
+        history.record(entry("expired", now - chrono::Duration::days(30), 0), Vec::new());
+        history.record(entry("a", now - chrono::Duration::minutes(2), 1), vec![failure("item-1", "1 is not a string")]);
+        history.record(entry("b", now - chrono::Duration::minutes(1), 0), Vec::new());
+        history.record(entry("c", now, 0), Vec::new());
+
+        // 过期的和超过条数上限的最旧记录被删除
+        let reloaded = JobHistory::from_config(&config);
+        let ids: Vec<_> = reloaded.list(&JobHistoryQuery::default()).into_iter().map(|e| e.job_id).collect();
+        assert_eq!(ids, vec!["c", "b"]);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+
+        let since = JobHistoryQuery { since: Some(now - chrono::Duration::seconds(30)), ..Default::default() };
+        assert_eq!(reloaded.list(&since).len(), 1);
+        assert!(reloaded.report("a").is_none());
+    }
+
+    #[test]
+    fn test_report_and_csv() {
+        let history = JobHistory::from_config(&JobHistoryConfig { max_reported_failures: 1, ..Default::default() });
+        history.record(
+            entry("a", Utc::now(), 2),
+            vec![failure("item-1", "says \"hi\", twice"), failure("item-2", "other")],
+        );
+
+        let report = history.report("a").unwrap();
+        assert!(report.truncated);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(
+            report_to_csv(&report.failures),
+            "document,instance_path,schema_path,error_code,message\n\
+             item-1,/name,/properties/name/type,SCHEMA_VALIDATION_ERROR,\"says \"\"hi\"\", twice\"\n"
+        );
+    }
+}
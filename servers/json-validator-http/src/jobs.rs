@@ -0,0 +1,451 @@
+//! 后台重新验证任务
+//!
+//! 客户端指定注册表中的Schema和文档数据源（服务器目录或上传的tar/tar.gz归档），
+//! 服务器在后台逐个验证其中的 `.json` 文档，提供进度查询和失败文档报告下载。
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::config::JobsConfig;
+use crate::job_history::{JobHistory, JobHistoryEntry, JobKind};
+use crate::models::{ValidationError, ValidationOptions};
+use crate::services::JsonValidatorService;
+
+/// 创建重新验证任务请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateJobRequest {
+    /// 注册表中的Schema名称
+    pub schema_id: String,
+    /// 文档数据源
+    pub source: DocumentSource,
+}
+
+/// 上传归档创建任务的查询参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveJobQuery {
+    /// 注册表中的Schema名称
+    pub schema_id: String,
+}
+
+/// 文档数据源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DocumentSource {
+    /// 服务器本地目录（递归扫描）
+    Directory { path: PathBuf },
+    /// S3前缀
+    S3 { bucket: String, prefix: String },
+}
+
+/// 待验证的文档集合
+pub enum Documents {
+    /// 本地文件
+    Files(Vec<PathBuf>),
+    /// 已解包的归档条目（名称，内容）
+    Inline(Vec<(String, Vec<u8>)>),
+}
+
+impl DocumentSource {
+    /// 进度和报告中展示的数据源描述
+    pub fn describe(&self) -> String {
+        match self {
+            DocumentSource::Directory { path } => format!("directory:{}", path.display()),
+            DocumentSource::S3 { bucket, prefix } => format!("s3://{}/{}", bucket, prefix),
+        }
+    }
+}
+
+impl Documents {
+    fn len(&self) -> usize {
+        match self {
+            Documents::Files(files) => files.len(),
+            Documents::Inline(entries) => entries.len(),
+        }
+    }
+}
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// 运行中
+    Running,
+    /// 已完成
+    Completed,
+    /// 异常终止
+    Failed,
+}
+
+/// 任务进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobProgress {
+    /// 任务ID
+    pub job_id: String,
+    /// Schema名称
+    pub schema_id: String,
+    /// 数据源描述
+    pub source: String,
+    /// 任务状态
+    pub status: JobStatus,
+    /// 文档总数
+    pub total: usize,
+    /// 已处理文档数
+    pub processed: usize,
+    /// 验证失败的文档数
+    pub failed: usize,
+    /// 异常终止的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 创建时间
+    pub created_at: String,
+    /// 完成时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+}
+
+/// 失败文档
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentFailure {
+    /// 文档路径或归档内名称
+    pub document: String,
+    /// 验证错误
+    pub errors: Vec<ValidationError>,
+}
+
+/// 任务报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobReport {
+    /// 任务进度
+    #[serde(flatten)]
+    pub progress: JobProgress,
+    /// 失败文档（超过上限的部分不保留）
+    pub failures: Vec<DocumentFailure>,
+    /// 报告是否因上限被截断
+    pub truncated: bool,
+}
+
+struct Job {
+    progress: RwLock<JobProgress>,
+    failures: RwLock<Vec<DocumentFailure>>,
+}
+
+/// 重新验证任务管理器
+pub struct JobManager {
+    config: JobsConfig,
+    jobs: RwLock<HashMap<String, Arc<Job>>>,
+    /// 任务结束后记录历史
+    history: Option<Arc<JobHistory>>,
+}
+
+impl JobManager {
+    /// 创建任务管理器
+    pub fn new(config: JobsConfig) -> Self {
+        Self {
+            config,
+            jobs: RwLock::new(HashMap::new()),
+            history: None,
+        }
+    }
+
+    /// 任务结束后写入任务历史
+    pub fn with_history(mut self, history: Arc<JobHistory>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// 上传归档的大小上限
+    pub fn max_archive_size(&self) -> usize {
+        self.config.max_archive_size
+    }
+
+    /// 解析数据源中的文档列表
+    pub fn collect_documents(&self, source: &DocumentSource) -> Result<Documents, SourceError> {
+        match source {
+            DocumentSource::Directory { path } => {
+                let path = self.allowed_directory(path)?;
+                let files = walkdir::WalkDir::new(&path)
+                    .into_iter()
+                    .filter_map(Result::ok)
+                    .filter(|entry| entry.file_type().is_file() && is_json_document(entry.path()))
+                    .map(|entry| entry.into_path())
+                    .collect();
+                Ok(Documents::Files(files))
+            }
+            DocumentSource::S3 { .. } => Err(SourceError::Unsupported(
+                "S3 sources are not available in this server; upload an archive instead".to_string(),
+            )),
+        }
+    }
+
+    /// 解包上传的tar或tar.gz归档，只保留 `.json` 文档
+    pub fn unpack_archive(&self, archive: &[u8]) -> Result<Documents, SourceError> {
+        let reader: Box<dyn Read + '_> = if archive.starts_with(&[0x1f, 0x8b]) {
+            Box::new(flate2::read::GzDecoder::new(archive))
+        } else {
+            Box::new(archive)
+        };
+
+        let mut entries = Vec::new();
+        let mut tar = tar::Archive::new(reader);
+        for entry in tar.entries().map_err(|e| SourceError::Invalid(e.to_string()))? {
+            let mut entry = entry.map_err(|e| SourceError::Invalid(e.to_string()))?;
+            let name = entry
+                .path()
+                .map_err(|e| SourceError::Invalid(e.to_string()))?
+                .to_string_lossy()
+                .into_owned();
+            if !entry.header().entry_type().is_file() || !is_json_document(Path::new(&name)) {
+                continue;
+            }
+            let mut content = Vec::new();
+            entry
+                .read_to_end(&mut content)
+                .map_err(|e| SourceError::Invalid(e.to_string()))?;
+            entries.push((name, content));
+        }
+        Ok(Documents::Inline(entries))
+    }
+
+    /// 启动后台任务，立即返回初始进度
+    pub fn start(
+        self: &Arc<Self>,
+        schema_id: String,
+        schema: Arc<serde_json::Value>,
+        source: String,
+        documents: Documents,
+        service: JsonValidatorService,
+    ) -> JobProgress {
+        let progress = JobProgress {
+            job_id: uuid::Uuid::new_v4().to_string(),
+            schema_id,
+            source,
+            status: JobStatus::Running,
+            total: documents.len(),
+            processed: 0,
+            failed: 0,
+            error: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+        };
+        let job = Arc::new(Job {
+            progress: RwLock::new(progress.clone()),
+            failures: RwLock::new(Vec::new()),
+        });
+        self.jobs.write().insert(progress.job_id.clone(), job.clone());
+
+        let max_failures = self.config.max_reported_failures;
+        let history = self.history.clone();
+        let submitted_at = chrono::Utc::now();
+        tokio::spawn(async move {
+            let started = std::time::Instant::now();
+            run_job(&job, &schema, documents, &service, max_failures).await;
+            let progress = job.progress.read().clone();
+            info!(
+                "Revalidation job {} finished: {} of {} document(s) failed",
+                progress.job_id, progress.failed, progress.total
+            );
+            if let Some(history) = history {
+                let entry = JobHistoryEntry {
+                    job_id: progress.job_id,
+                    kind: JobKind::Revalidation,
+                    schema_id: Some(progress.schema_id),
+                    source: Some(progress.source),
+                    submitted_at,
+                    duration_ms: started.elapsed().as_millis() as u64,
+                    total: progress.total,
+                    passed: progress.processed - progress.failed,
+                    failed: progress.failed,
+                    error: progress.error,
+                };
+                history.record(entry, job.failures.read().clone());
+            }
+        });
+
+        progress
+    }
+
+    /// 查询任务进度
+    pub fn progress(&self, job_id: &str) -> Option<JobProgress> {
+        self.jobs.read().get(job_id).map(|job| job.progress.read().clone())
+    }
+
+    /// 生成任务报告
+    pub fn report(&self, job_id: &str) -> Option<JobReport> {
+        let job = self.jobs.read().get(job_id).cloned()?;
+        let progress = job.progress.read().clone();
+        let failures = job.failures.read().clone();
+        Some(JobReport {
+            truncated: failures.len() < progress.failed,
+            progress,
+            failures,
+        })
+    }
+
+    /// 目录必须位于允许的根目录之下
+    fn allowed_directory(&self, path: &Path) -> Result<PathBuf, SourceError> {
+        let path = path
+            .canonicalize()
+            .map_err(|e| SourceError::Invalid(format!("{}: {}", path.display(), e)))?;
+        let allowed = self
+            .config
+            .allowed_directories
+            .iter()
+            .filter_map(|root| root.canonicalize().ok())
+            .any(|root| path.starts_with(root));
+        if allowed {
+            Ok(path)
+        } else {
+            Err(SourceError::Forbidden(format!(
+                "Directory {} is not in jobs.allowed_directories",
+                path.display()
+            )))
+        }
+    }
+}
+
+/// 数据源错误
+#[derive(Debug, thiserror::Error)]
+pub enum SourceError {
+    /// 数据源无效或无法读取
+    #[error("{0}")]
+    Invalid(String),
+    /// 数据源不在允许范围内
+    #[error("{0}")]
+    Forbidden(String),
+    /// 不支持的数据源类型
+    #[error("{0}")]
+    Unsupported(String),
+}
+
+async fn run_job(
+    job: &Job,
+    schema: &serde_json::Value,
+    documents: Documents,
+    service: &JsonValidatorService,
+    max_failures: usize,
+) {
+    let options = ValidationOptions::default();
+    let items: Vec<(String, Option<PathBuf>, Vec<u8>)> = match documents {
+        Documents::Files(files) => files
+            .into_iter()
+            .map(|path| (path.display().to_string(), Some(path), Vec::new()))
+            .collect(),
+        Documents::Inline(entries) => entries.into_iter().map(|(name, content)| (name, None, content)).collect(),
+    };
+
+    for (name, path, content) in items {
+        let content = match path {
+            Some(path) => tokio::fs::read(&path).await.map_err(|e| e.to_string()),
+            None => Ok(content),
+        };
+        let errors = match content.and_then(|c| serde_json::from_slice::<serde_json::Value>(&c).map_err(|e| e.to_string())) {
+            Ok(document) => match service.validate_json(&document, Some(schema), &options).await {
+                Ok(result) => result.errors,
+                Err(e) => {
+                    warn!("Revalidation aborted: {}", e);
+                    let mut progress = job.progress.write();
+                    progress.status = JobStatus::Failed;
+                    progress.error = Some(e);
+                    progress.finished_at = Some(chrono::Utc::now().to_rfc3339());
+                    return;
+                }
+            },
+            Err(e) => vec![ValidationError {
+                instance_path: "".to_string(),
+                schema_path: "".to_string(),
+                message: e,
+                error_code: "INVALID_JSON_FORMAT".to_string(),
+                location: None,
+            }],
+        };
+
+        let mut progress = job.progress.write();
+        progress.processed += 1;
+        if !errors.is_empty() {
+            progress.failed += 1;
+            let mut failures = job.failures.write();
+            if failures.len() < max_failures {
+                failures.push(DocumentFailure { document: name, errors });
+            }
+        }
+    }
+
+    let mut progress = job.progress.write();
+    progress.status = JobStatus::Completed;
+    progress.finished_at = Some(chrono::Utc::now().to_rfc3339());
+}
+
+fn is_json_document(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, content) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, content.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_archive_job_reports_failures() {
+        let manager = Arc::new(JobManager::new(JobsConfig::default()));
+        let documents = manager
+            .unpack_archive(&archive(&[
+                ("ok.json", r#"{"name":"a"}"#),
+                ("bad.json", r#"{"name":1}"#),
+                ("broken.json", "{"),
+                ("README.md", "ignored"),
+            ]))
+            .unwrap();
+        let schema = Arc::new(serde_json::json!({
+            "type": "object",
+            "properties": { "name": { "type": "string" } }
+        }));
+
+        let job = manager.start("user".to_string(), schema, "archive".to_string(), documents, JsonValidatorService::new());
+        assert_eq!(job.total, 3);
+
+        let report = loop {
+            let progress = manager.progress(&job.job_id).unwrap();
+            if progress.status != JobStatus::Running {
+                break manager.report(&job.job_id).unwrap();
+            }
+            tokio::task::yield_now().await;
+        };
+        assert_eq!(report.progress.status, JobStatus::Completed);
+        assert_eq!(report.progress.failed, 2);
+        let documents: Vec<_> = report.failures.iter().map(|f| f.document.as_str()).collect();
+        assert_eq!(documents, vec!["bad.json", "broken.json"]);
+    }
+
+    #[test]
+    fn test_directory_must_be_allowed() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = DocumentSource::Directory { path: dir.path().to_path_buf() };
+
+        let manager = JobManager::new(JobsConfig::default());
+        assert!(matches!(manager.collect_documents(&source), Err(SourceError::Forbidden(_))));
+
+        std::fs::write(dir.path().join("a.json"), "{}").unwrap();
+        let manager = JobManager::new(JobsConfig {
+            allowed_directories: vec![dir.path().to_path_buf()],
+            ..Default::default()
+        });
+        assert!(matches!(manager.collect_documents(&source), Ok(Documents::Files(files)) if files.len() == 1));
+    }
+}
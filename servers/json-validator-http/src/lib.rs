@@ -3,16 +3,30 @@
 //! 这是一个基于Axum和Tower的高性能HTTP JSON验证服务器，
 //! 实现了JSON-RPC over HTTP协议，提供了企业级的性能、安全性和可扩展性。
 
+pub mod api_keys;
 pub mod app;
 pub mod config;
+pub mod crash;
+pub mod fixes;
+pub mod formats;
 pub mod handlers;
+pub mod i18n;
+pub mod job_history;
+pub mod jobs;
+pub mod middleware;
 pub mod models;
+pub mod normalize;
+pub mod rpc;
 pub mod services;
 pub mod tls;
 pub mod performance;
+pub mod registry;
+pub mod self_check;
 pub mod utils;
+pub mod validation_pool;
+pub mod warmup;
 
-pub use app::create_app;
+pub use app::{create_app, create_app_with_config, create_app_with_metrics_listener};
 pub use config::ServerConfig;
 pub use models::*;
 pub use services::JsonValidatorService;
@@ -1,8 +1,12 @@
 use anyhow::Result;
 use clap::Parser;
 use config::Config;
-use json_validator_http::app::create_app;
+use json_validator_http::app::{create_app_with_config, create_app_with_metrics_listener};
 use json_validator_http::config::ServerConfig;
+use json_validator_http::crash::install_panic_hook;
+use json_validator_http::self_check::run_self_check;
+#[cfg(unix)]
+use unix_socket::{bind_unix_socket, remove_unix_socket, serve_unix_socket};
 use json_validator_http::utils::logging::setup_logging;
 use std::net::SocketAddr;
 use tokio::signal;
@@ -24,21 +28,57 @@ struct Args {
     listen: SocketAddr,
     
     /// 日志级别
-    #[arg(short, long, default_value = "info")]
+    #[arg(long, default_value = "info")]
     log_level: String,
     
     /// 启用开发模式
     #[arg(long, default_value = "false")]
     dev: bool,
+
+    /// 只运行启动自检，打印报告后退出，失败时退出码非零
+    #[arg(long)]
+    check: bool,
+
+    /// 打印配置文件的JSON Schema后退出
+    #[arg(long)]
+    print_config_schema: bool,
+
+    /// 按JSON Schema校验TOML/YAML配置文件后退出，有错误时打印所在行并以非零退出码退出
+    #[arg(long, value_name = "FILE")]
+    validate_config: Option<String>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // 解析命令行参数
     let args = Args::parse();
+
+    if args.print_config_schema {
+        println!("{}", serde_json::to_string_pretty(&ServerConfig::json_schema())?);
+        return Ok(());
+    }
+
+    if let Some(path) = &args.validate_config {
+        std::process::exit(config_schema::run_validate(&ServerConfig::json_schema(), path));
+    }
+    
+    if args.check {
+        let passed = match load_config(&args.config) {
+            Ok(config) => {
+                let report = run_self_check(&config).await;
+                println!("{}", report);
+                report.passed()
+            }
+            Err(e) => {
+                println!("[FAIL] config  {:#}", e);
+                false
+            }
+        };
+        std::process::exit(if passed { 0 } else { 1 });
+    }
     
     // 设置日志系统
-    setup_logging(&args.log_level)?;
+    let recent_logs = setup_logging(&args.log_level)?;
     
     info!("Starting JSON Validator HTTP MCP server");
     info!("Version: {}", env!("CARGO_PKG_VERSION"));
@@ -47,9 +87,19 @@ async fn main() -> Result<()> {
     // 加载配置
     let config = load_config(&args.config)?;
     info!("Configuration loaded from: {}", args.config);
-    
-    // 创建应用
-    let app = create_app();
+    config.security.check_static_api_keys()?;
+    install_panic_hook(config.logging.crash_report.clone(), recent_logs);
+    let unix_socket = config.server.unix_socket.clone();
+    let unix_socket_mode = config.server.socket_mode()?;
+    
+    // 指标端口与API端口不同时，指标和健康检查由独立监听器提供
+    let metrics_addr = SocketAddr::new(args.listen.ip(), config.metrics.port);
+    let (app, metrics_app) = if config.metrics.enabled && config.metrics.port != args.listen.port() {
+        let (app, metrics_app) = create_app_with_metrics_listener(config);
+        (app, Some(metrics_app))
+    } else {
+        (create_app_with_config(config), None)
+    };
     
     // 添加追踪层
     let app = app.layer(
@@ -74,6 +124,35 @@ async fn main() -> Result<()> {
     let listener = tokio::net::TcpListener::bind(args.listen).await?;
     info!("Server listening on {}", args.listen);
     
+    let metrics_server = match metrics_app {
+        Some(metrics_app) => {
+            let metrics_listener = tokio::net::TcpListener::bind(metrics_addr).await?;
+            info!("Metrics listening on {}", metrics_listener.local_addr()?);
+            Some(tokio::spawn(async move {
+                if let Err(e) = axum::serve(metrics_listener, metrics_app).await {
+                    error!("Metrics listener failed: {}", e);
+                }
+            }))
+        }
+        None => None,
+    };
+    
+    // Unix域套接字监听器，供同一主机或Pod内的边车访问API（包括健康检查）
+    #[cfg(unix)]
+    let unix_socket_server = match &unix_socket {
+        Some(path) => {
+            let unix_listener = bind_unix_socket(path, unix_socket_mode)?;
+            info!("Unix socket listening on {}", path);
+            let unix_app = app.clone();
+            Some(tokio::spawn(async move {
+                if let Err(e) = serve_unix_socket(unix_listener, unix_app).await {
+                    error!("Unix socket listener failed: {}", e);
+                }
+            }))
+        }
+        None => None,
+    };
+    
     // 设置优雅关闭
     let graceful_shutdown = async {
         shutdown_signal().await;
@@ -81,43 +160,64 @@ async fn main() -> Result<()> {
     };
     
     // 运行服务器
-    axum::serve(listener, app)
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(graceful_shutdown)
         .await?;
     
+    if let Some(metrics_server) = metrics_server {
+        metrics_server.abort();
+    }
+    #[cfg(unix)]
+    if let Some(unix_socket_server) = unix_socket_server {
+        unix_socket_server.abort();
+        if let Some(path) = &unix_socket {
+            remove_unix_socket(path);
+        }
+    }
+    
     info!("Server shutdown complete");
     Ok(())
 }
 
 /// 加载配置文件
 fn load_config(config_path: &str) -> Result<ServerConfig> {
-    let mut settings = Config::builder()
+    // 默认值必须在构建前设置：构建后再调用 set_default 会覆盖配置文件中的整个嵌套表
+    let files = Config::builder()
+        .set_default("server.host", "127.0.0.1")?
+        .set_default("server.port", 8080)?
+        .set_default("server.workers", 4)?
+        .set_default("server.max_connections", 1000)?
+        .set_default("server.timeout", 30)?
+        .set_default("cache.enabled", false)?
+        .set_default("cache.ttl", 3600)?
+        .set_default("cache.max_size", 1000)?
+        .set_default("security.enabled", false)?
+        .set_default("security.jwt_secret", "default-secret")?
+        .set_default("security.rate_limit", 100)?
+        .set_default("security.cors.enabled", true)?
+        .set_default("logging.level", "info")?
+        .set_default("logging.format", "json")?
+        .set_default("metrics.enabled", true)?
+        .set_default("metrics.port", 9090)?
         .add_source(config::File::with_name(config_path).required(false))
-        .add_source(config::Environment::with_prefix("JSON_VALIDATOR"))
         .build()?;
-    
-    // 设置默认值
-    settings.set_default("server.host", "127.0.0.1")?;
-    settings.set_default("server.port", 8080)?;
-    settings.set_default("server.workers", 4)?;
-    settings.set_default("server.max_connections", 1000)?;
-    settings.set_default("server.timeout", 30)?;
-    
-    settings.set_default("cache.enabled", false)?;
-    settings.set_default("cache.ttl", 3600)?;
-    settings.set_default("cache.max_size", 1000)?;
-    
-    settings.set_default("security.enabled", false)?;
-    settings.set_default("security.jwt_secret", "default-secret")?;
-    settings.set_default("security.rate_limit", 100)?;
-    settings.set_default("security.cors.enabled", true)?;
-    
-    settings.set_default("logging.level", "info")?;
-    settings.set_default("logging.format", "json")?;
-    
-    settings.set_default("metrics.enabled", true)?;
-    settings.set_default("metrics.port", 9090)?;
-    
+
+    // 展开配置文件中的 `${ENV_VAR}`，再叠加环境变量覆盖（`*_FILE` 变量从文件读取密钥）
+    let mut base: serde_json::Value = files.try_deserialize()?;
+    config_schema::interpolate_value(&mut base)?;
+    // 旧版本的变量以单个 `_` 分隔嵌套键，按配置结构兼容
+    let env = config_schema::EnvOverrides::new("JSON_VALIDATOR", "__").with_legacy_separator("_").vars(&base)?;
+    let settings = Config::builder()
+        // 通过JSON源回填而不是 Config::try_from，后者会丢弃空表
+        .add_source(config::File::from_str(&base.to_string(), config::FileFormat::Json))
+        .add_source(
+            config::Environment::with_prefix("JSON_VALIDATOR")
+                .prefix_separator("_")
+                .separator("__")
+                .source(Some(env)),
+        )
+        .build()?;
+
     let config: ServerConfig = settings.try_deserialize()?;
     Ok(config)
 }
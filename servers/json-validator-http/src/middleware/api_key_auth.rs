@@ -0,0 +1,150 @@
+//! JSON-RPC端点API密钥认证中间件
+//!
+//! 请求需要通过 `x-api-key` 或 `Authorization: Bearer` 头携带有效且具有 `read`
+//! 权限的密钥。认证通过后，[`AuthenticatedKey`] 写入请求扩展，供限流等内层中间件使用；
+//! 失败时返回HTTP 401/403和 `UNAUTHORIZED` / `FORBIDDEN` JSON-RPC错误。
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use tower::{Layer, Service};
+
+use crate::api_keys::{ApiKeyStore, SCOPE_READ};
+use crate::models::{JsonRpcError, JsonRpcResponse, TRACE_ID_HEADER};
+
+/// API密钥认证层
+#[derive(Clone)]
+pub struct ApiKeyAuthLayer {
+    store: Arc<ApiKeyStore>,
+}
+
+impl ApiKeyAuthLayer {
+    /// 使用共享的密钥存储创建认证层
+    pub fn new(store: Arc<ApiKeyStore>) -> Self {
+        Self { store }
+    }
+}
+
+impl<S> Layer<S> for ApiKeyAuthLayer {
+    type Service = ApiKeyAuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ApiKeyAuthService {
+            inner,
+            store: self.store.clone(),
+        }
+    }
+}
+
+/// API密钥认证服务
+#[derive(Clone)]
+pub struct ApiKeyAuthService<S> {
+    inner: S,
+    store: Arc<ApiKeyStore>,
+}
+
+impl<S> Service<Request<Body>> for ApiKeyAuthService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        // 取出已就绪的服务，留下克隆供下次调用
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let authenticated = self.store.authenticate_headers(request.headers());
+
+        Box::pin(async move {
+            let error = match authenticated {
+                Some(key) if key.has_scope(SCOPE_READ) => {
+                    request.extensions_mut().insert(key);
+                    return inner.call(request).await;
+                }
+                Some(key) => (
+                    StatusCode::FORBIDDEN,
+                    JsonRpcError::forbidden(format!("API key '{}' lacks the '{}' scope", key.id, SCOPE_READ)),
+                ),
+                None => (
+                    StatusCode::UNAUTHORIZED,
+                    JsonRpcError::unauthorized("Missing or invalid API key".to_string()),
+                ),
+            };
+            Ok(rejection_response(request, error).await)
+        })
+    }
+}
+
+/// 构造认证失败响应，沿用请求中的JSON-RPC `id`
+async fn rejection_response(request: Request<Body>, (status, error): (StatusCode, JsonRpcError)) -> Response {
+    let body = axum::body::to_bytes(request.into_body(), usize::MAX).await.unwrap_or_default();
+    let id = serde_json::from_slice::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|r| r.get("id").cloned())
+        .unwrap_or(serde_json::Value::Null);
+
+    let trace_id = crate::middleware::request_trace_id();
+    let error = error.with_trace_id(&trace_id);
+    (
+        status,
+        [(TRACE_ID_HEADER, trace_id)],
+        axum::Json(JsonRpcResponse::error(error, id)),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_keys::{CreateApiKeyRequest, API_KEY_HEADER};
+    use crate::config::SecurityConfig;
+    use axum::routing::post;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_rejects_missing_and_insufficient_keys() {
+        let store = Arc::new(ApiKeyStore::in_memory(&SecurityConfig::default()));
+        let request: CreateApiKeyRequest =
+            serde_json::from_value(serde_json::json!({ "name": "writer", "scopes": ["write"] })).unwrap();
+        let writer = store.create(request).unwrap();
+        let request: CreateApiKeyRequest = serde_json::from_value(serde_json::json!({ "name": "reader" })).unwrap();
+        let reader = store.create(request).unwrap();
+
+        let app = Router::new()
+            .route("/rpc", post(|| async { r#"{"jsonrpc":"2.0","result":{},"id":1}"# }))
+            .layer(ApiKeyAuthLayer::new(store));
+        let request = |key: Option<&str>| {
+            let mut builder = Request::builder().method("POST").uri("/rpc");
+            if let Some(key) = key {
+                builder = builder.header(API_KEY_HEADER, key);
+            }
+            builder.body(Body::from(r#"{"jsonrpc":"2.0","method":"ping","id":1}"#)).unwrap()
+        };
+
+        let response = app.clone().oneshot(request(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["id"], 1);
+        assert_eq!(body["error"]["data"]["code"], "UNAUTHORIZED");
+
+        let response = app.clone().oneshot(request(Some(&writer.key))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app.oneshot(request(Some(&reader.key))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
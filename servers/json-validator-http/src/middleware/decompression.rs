@@ -0,0 +1,175 @@
+//! 压缩请求体解压中间件
+//!
+//! 接受 `Content-Encoding: gzip` / `deflate` 的请求体，在到达处理器之前解压并去掉 `Content-Encoding` 头。
+//! 解压由 `request-decompression` 完成，解压后大小或压缩比超过上限时立即停止，返回 `413 PAYLOAD_TOO_LARGE`，防止压缩炸弹：
+//!
+//! ```ignore
+//! let layer = DecompressionLayer::from_config(&config.request_decompression);
+//! let app = Router::new().route("/rpc", post(json_rpc_handler).layer(layer));
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use request_decompression::{decompress_request, DecompressError, Decompressor};
+use tower::{Layer, Service};
+
+use crate::config::RequestDecompressionConfig;
+use crate::models::request_problem;
+
+/// 请求体解压层
+#[derive(Debug, Clone)]
+pub struct DecompressionLayer {
+    /// 未启用时为 `None`，拒绝带压缩编码的请求体
+    decompressor: Option<Decompressor>,
+}
+
+impl DecompressionLayer {
+    /// 按配置创建解压层，未启用时拒绝带压缩编码的请求体
+    pub fn from_config(config: &RequestDecompressionConfig) -> Self {
+        Self {
+            decompressor: config
+                .enabled
+                .then(|| Decompressor::new(config.max_decompressed_size, config.max_ratio)),
+        }
+    }
+}
+
+impl<S> Layer<S> for DecompressionLayer {
+    type Service = DecompressionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DecompressionService {
+            inner,
+            config: self.clone(),
+        }
+    }
+}
+
+/// 请求体解压服务
+#[derive(Debug, Clone)]
+pub struct DecompressionService<S> {
+    inner: S,
+    config: DecompressionLayer,
+}
+
+impl<S> Service<Request<Body>> for DecompressionService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // 取出已就绪的服务，留下克隆供下次调用
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let config = self.config.clone();
+        Box::pin(async move {
+            let path = request.uri().path().to_string();
+            match decompress_request(config.decompressor.as_ref(), request).await {
+                Ok(request) => inner.call(request).await,
+                Err(e) => Ok(problem(e, &path)),
+            }
+        })
+    }
+}
+
+fn problem(error: DecompressError, path: &str) -> Response {
+    let (status, code, detail) = match error {
+        DecompressError::Unsupported(encoding) => (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "UNSUPPORTED_CONTENT_ENCODING",
+            format!("Unsupported Content-Encoding: {} (supported: gzip, deflate)", encoding),
+        ),
+        e @ DecompressError::Corrupt(_) => (StatusCode::BAD_REQUEST, "INVALID_CONTENT_ENCODING", e.to_string()),
+        DecompressError::TooLarge(detail) => (StatusCode::PAYLOAD_TOO_LARGE, "PAYLOAD_TOO_LARGE", detail),
+    };
+    request_problem(status, code, detail)
+        .with_instance(path.to_string())
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{header, Method};
+    use axum::routing::post;
+    use axum::Router;
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+    use std::io::Write;
+    use tower::ServiceExt;
+
+    fn app(enabled: bool) -> Router {
+        let layer = DecompressionLayer::from_config(&RequestDecompressionConfig {
+            enabled,
+            max_decompressed_size: 512 * 1024,
+            max_ratio: 100,
+        });
+        Router::new()
+            .route("/rpc", post(|body: String| async move { body }))
+            .layer(layer)
+    }
+
+    fn request(encoding: &str, body: Vec<u8>) -> Request<Body> {
+        Request::builder()
+            .method(Method::POST)
+            .uri("/rpc")
+            .header(header::CONTENT_ENCODING, encoding)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    async fn body_text(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_decompresses_gzip_and_deflate() {
+        let body = br#"{"jsonrpc":"2.0","method":"ping","id":1}"#;
+        let response = app(true).oneshot(request("gzip", gzip(body))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_text(response).await.as_bytes(), body);
+
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(body).unwrap();
+        let response = app(true).oneshot(request("deflate", zlib.finish().unwrap())).await.unwrap();
+        assert_eq!(body_text(response).await.as_bytes(), body);
+
+        let response = app(true).oneshot(request("gzip", b"garbage".to_vec())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_bombs_and_unsupported_encodings() {
+        // 1MB的零压缩后约1KB，压缩比超过100
+        let response = app(true).oneshot(request("gzip", gzip(&vec![0u8; 1024 * 1024]))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(body_text(response).await.contains("expands more than 100x"));
+
+        let response = app(true).oneshot(request("br", b"{}".to_vec())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let response = app(false).oneshot(request("gzip", gzip(b"{}"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+}
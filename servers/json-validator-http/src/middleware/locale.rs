@@ -0,0 +1,77 @@
+//! 语言协商中间件
+//!
+//! 按 `lang` 查询参数或 `Accept-Language` 请求头确定本次请求的语言，
+//! 验证错误和问题详情的消息都按该语言输出。
+
+use axum::extract::Request;
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::i18n::Locale;
+
+tokio::task_local! {
+    static LOCALE: Locale;
+}
+
+/// 当前请求的语言，不在请求处理中时为英文
+pub fn request_locale() -> Locale {
+    LOCALE.try_with(|locale| *locale).unwrap_or_default()
+}
+
+/// 从 `lang` 查询参数或 `Accept-Language` 请求头协商语言
+fn negotiate_locale(request: &Request) -> Locale {
+    let from_query = request.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "lang")
+            .and_then(|(_, value)| Locale::from_tag(value))
+    });
+
+    from_query
+        .or_else(|| {
+            request
+                .headers()
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(Locale::from_accept_language)
+        })
+        .unwrap_or_default()
+}
+
+/// 语言协商中间件
+pub async fn locale_middleware(request: Request, next: Next) -> Response {
+    let locale = negotiate_locale(&request);
+    LOCALE.scope(locale, next.run(request)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_locale_negotiation() {
+        let app = Router::new()
+            .route("/", get(|| async { request_locale().tag() }))
+            .layer(axum::middleware::from_fn(locale_middleware));
+
+        let cases = [
+            ("/", None, "en"),
+            ("/", Some("zh-CN,zh;q=0.9,en;q=0.8"), "zh-CN"),
+            ("/?lang=en", Some("zh-CN"), "en"),
+            ("/?lang=zh", None, "zh-CN"),
+        ];
+        for (uri, accept_language, expected) in cases {
+            let mut request = Request::builder().uri(uri);
+            if let Some(value) = accept_language {
+                request = request.header(header::ACCEPT_LANGUAGE, value);
+            }
+            let response = app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            assert_eq!(&body[..], expected.as_bytes(), "{}", uri);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! 内存压力下的请求体限制中间件
+//!
+//! 常驻内存超过硬上限时，在读取请求体之前拒绝声明长度超过上限的请求体以及长度未知的分块请求体，
+//! 返回HTTP 503 `SERVICE_UNAVAILABLE` 问题详情和 `Retry-After`。
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::body::Body;
+use axum::http::{header, HeaderMap, HeaderValue, Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use tower::{Layer, Service};
+
+use memory_watchdog::MemoryWatchdog;
+use crate::models::request_problem;
+
+/// 内存压力请求体限制层
+#[derive(Clone)]
+pub struct MemoryPressureLayer {
+    watchdog: Arc<MemoryWatchdog>,
+}
+
+impl MemoryPressureLayer {
+    /// 使用共享的内存自监控创建限制层
+    pub fn new(watchdog: Arc<MemoryWatchdog>) -> Self {
+        Self { watchdog }
+    }
+}
+
+impl<S> Layer<S> for MemoryPressureLayer {
+    type Service = MemoryPressureService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MemoryPressureService {
+            inner,
+            watchdog: self.watchdog.clone(),
+        }
+    }
+}
+
+/// 内存压力请求体限制服务
+#[derive(Clone)]
+pub struct MemoryPressureService<S> {
+    inner: S,
+    watchdog: Arc<MemoryWatchdog>,
+}
+
+/// 请求体声明的长度，分块传输时长度未知，视为无限大
+fn declared_body_length(headers: &HeaderMap) -> Option<u64> {
+    if let Some(length) = headers.get(header::CONTENT_LENGTH) {
+        return length.to_str().ok()?.parse().ok();
+    }
+    headers.contains_key(header::TRANSFER_ENCODING).then_some(u64::MAX)
+}
+
+impl<S> Service<Request<Body>> for MemoryPressureService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // 取出已就绪的服务，留下克隆供下次调用
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let rejected = declared_body_length(request.headers()).is_some_and(|len| self.watchdog.should_reject_body(len));
+        if !rejected {
+            return Box::pin(async move { inner.call(request).await });
+        }
+
+        tracing::warn!(
+            rss_bytes = self.watchdog.rss_bytes(),
+            path = %request.uri().path(),
+            "Rejected request body under memory pressure"
+        );
+        let mut response = request_problem(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "SERVICE_UNAVAILABLE",
+            "Server is under memory pressure, large request bodies are rejected".to_string(),
+        )
+        .with_instance(request.uri().path().to_string())
+        .into_response();
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from_static("30"));
+        Box::pin(async move { Ok(response) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MemoryWatchdogConfig;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_rejects_large_bodies_under_pressure() {
+        let watchdog = Arc::new(MemoryWatchdog::new(MemoryWatchdogConfig {
+            enabled: true,
+            soft_limit_bytes: 100,
+            hard_limit_bytes: 200,
+            max_body_bytes_under_pressure: 10,
+            ..MemoryWatchdogConfig::default()
+        }.limits()));
+        let app = axum::Router::new()
+            .route("/rpc", axum::routing::post(|| async { "ok" }))
+            .layer(MemoryPressureLayer::new(watchdog.clone()));
+        let request = |body: &'static str| {
+            Request::post("/rpc")
+                .header(header::CONTENT_LENGTH, body.len())
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(request("a large request body")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        watchdog.observe(250);
+        let response = app.clone().oneshot(request("small")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.oneshot(request("a large request body")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "30");
+    }
+}
@@ -0,0 +1,21 @@
+//! 可复用的Tower中间件
+
+pub mod api_key_auth;
+pub mod decompression;
+pub mod locale;
+pub mod memory_pressure;
+pub mod priority;
+pub mod prometheus_metrics;
+pub mod rate_limit;
+pub mod trace_id;
+pub mod validation;
+
+pub use api_key_auth::{ApiKeyAuthLayer, ApiKeyAuthService};
+pub use decompression::{DecompressionLayer, DecompressionService};
+pub use locale::{locale_middleware, request_locale};
+pub use memory_pressure::{MemoryPressureLayer, MemoryPressureService};
+pub use priority::{Lane, PriorityLanes, PriorityLayer, PriorityService};
+pub use prometheus_metrics::{PrometheusMetrics, PrometheusMetricsLayer, PrometheusMetricsService};
+pub use rate_limit::{RateLimitLayer, RateLimitService, RateLimiter};
+pub use trace_id::{request_trace_id, trace_id_middleware};
+pub use validation::{ValidationLayer, ValidationService};
@@ -0,0 +1,257 @@
+//! 请求优先级通道中间件
+//!
+//! 请求通过 `X-Priority` 头或 `priority` 查询参数声明自己属于 `interactive`（交互）还是 `batch`（批量）通道，
+//! 未声明或取值无法识别时使用 `default_lane`。两个通道各有独立的并发上限，大批量请求占满批量通道时，
+//! 交互请求不受影响。通道已满的请求排队等待，超过 `queue_timeout_ms` 仍未轮到时返回HTTP 503
+//! `SERVICE_UNAVAILABLE` 问题详情。各通道的排队时间、处理延迟和在途请求数记入Prometheus指标。
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use axum::body::Body;
+use axum::http::{header, HeaderValue, Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tower::{Layer, Service};
+
+use crate::config::PriorityLanesConfig;
+use crate::middleware::PrometheusMetrics;
+use crate::models::request_problem;
+
+/// 声明优先级的请求头
+pub const PRIORITY_HEADER: &str = "x-priority";
+
+/// 优先级通道
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Lane {
+    /// 交互请求，延迟敏感
+    Interactive,
+    /// 批量请求，吞吐优先
+    Batch,
+}
+
+impl Lane {
+    /// 通道名，用作指标标签
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Interactive => "interactive",
+            Self::Batch => "batch",
+        }
+    }
+
+    /// 解析通道名，不区分大小写
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "interactive" => Some(Self::Interactive),
+            "batch" => Some(Self::Batch),
+            _ => None,
+        }
+    }
+
+    /// 请求声明的通道，请求头优先于查询参数
+    fn from_request(request: &Request<Body>) -> Option<Self> {
+        if let Some(value) = request.headers().get(PRIORITY_HEADER) {
+            return value.to_str().ok().and_then(Self::parse);
+        }
+        request
+            .uri()
+            .query()?
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "priority")
+            .and_then(|(_, value)| Self::parse(value))
+    }
+}
+
+/// 各优先级通道的并发额度
+pub struct PriorityLanes {
+    config: PriorityLanesConfig,
+    interactive: Arc<Semaphore>,
+    batch: Arc<Semaphore>,
+}
+
+impl PriorityLanes {
+    /// 根据配置创建通道
+    pub fn from_config(config: &PriorityLanesConfig) -> Self {
+        Self {
+            config: config.clone(),
+            interactive: Arc::new(Semaphore::new(config.interactive_concurrency)),
+            batch: Arc::new(Semaphore::new(config.batch_concurrency)),
+        }
+    }
+
+    /// 是否启用优先级通道
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// 请求使用的通道
+    pub fn lane_for(&self, request: &Request<Body>) -> Lane {
+        Lane::from_request(request).unwrap_or(self.config.default_lane)
+    }
+
+    /// 等待通道空闲，超时返回 `None`
+    pub async fn acquire(&self, lane: Lane) -> Option<OwnedSemaphorePermit> {
+        let semaphore = match lane {
+            Lane::Interactive => self.interactive.clone(),
+            Lane::Batch => self.batch.clone(),
+        };
+        let timeout = Duration::from_millis(self.config.queue_timeout_ms);
+        tokio::time::timeout(timeout, semaphore.acquire_owned()).await.ok()?.ok()
+    }
+}
+
+/// 优先级通道层
+#[derive(Clone)]
+pub struct PriorityLayer {
+    lanes: Arc<PriorityLanes>,
+    metrics: Arc<PrometheusMetrics>,
+}
+
+impl PriorityLayer {
+    /// 使用共享的通道和指标创建优先级层
+    pub fn new(lanes: Arc<PriorityLanes>, metrics: Arc<PrometheusMetrics>) -> Self {
+        Self { lanes, metrics }
+    }
+}
+
+impl<S> Layer<S> for PriorityLayer {
+    type Service = PriorityService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PriorityService {
+            inner,
+            lanes: self.lanes.clone(),
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+/// 优先级通道服务
+#[derive(Clone)]
+pub struct PriorityService<S> {
+    inner: S,
+    lanes: Arc<PriorityLanes>,
+    metrics: Arc<PrometheusMetrics>,
+}
+
+impl<S> Service<Request<Body>> for PriorityService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // 取出已就绪的服务，留下克隆供下次调用
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let lanes = self.lanes.clone();
+        let metrics = self.metrics.clone();
+        let lane = lanes.lane_for(&request);
+
+        Box::pin(async move {
+            let queued_at = Instant::now();
+            let Some(_permit) = lanes.acquire(lane).await else {
+                metrics.record_lane_rejected(lane.name());
+                tracing::warn!(lane = lane.name(), path = %request.uri().path(), "Priority lane queue timed out");
+                let mut response = request_problem(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "SERVICE_UNAVAILABLE",
+                    format!("Too many concurrent {} requests, retry later", lane.name()),
+                )
+                .with_instance(request.uri().path().to_string())
+                .into_response();
+                response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from_static("1"));
+                return Ok(response);
+            };
+
+            let started_at = Instant::now();
+            metrics.record_lane_started(lane.name(), (started_at - queued_at).as_secs_f64());
+            let response = inner.call(request).await;
+            metrics.record_lane_finished(lane.name(), started_at.elapsed().as_secs_f64());
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_lane_from_request() {
+        let request = |uri: &str, header: Option<&str>| {
+            let mut builder = Request::post(uri);
+            if let Some(value) = header {
+                builder = builder.header(PRIORITY_HEADER, value);
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+        assert_eq!(Lane::from_request(&request("/rpc", Some("Batch"))), Some(Lane::Batch));
+        assert_eq!(Lane::from_request(&request("/rpc?x=1&priority=batch", None)), Some(Lane::Batch));
+        assert_eq!(Lane::from_request(&request("/rpc?priority=batch", Some("interactive"))), Some(Lane::Interactive));
+        assert_eq!(Lane::from_request(&request("/rpc?priority=urgent", None)), None);
+
+        let lanes = PriorityLanes::from_config(&PriorityLanesConfig {
+            default_lane: Lane::Batch,
+            ..Default::default()
+        });
+        assert_eq!(lanes.lane_for(&request("/rpc", None)), Lane::Batch);
+    }
+
+    #[tokio::test]
+    async fn test_busy_batch_lane_does_not_block_interactive() {
+        let lanes = Arc::new(PriorityLanes::from_config(&PriorityLanesConfig {
+            interactive_concurrency: 1,
+            batch_concurrency: 1,
+            queue_timeout_ms: 50,
+            ..Default::default()
+        }));
+        let metrics = Arc::new(PrometheusMetrics::new());
+        let release = Arc::new(tokio::sync::Notify::new());
+        let app = axum::Router::new()
+            .route(
+                "/rpc",
+                axum::routing::post({
+                    let release = release.clone();
+                    move |request: Request<Body>| async move {
+                        if request.uri().query() == Some("priority=batch&hold") {
+                            release.notified().await;
+                        }
+                        "ok"
+                    }
+                }),
+            )
+            .layer(PriorityLayer::new(lanes, metrics.clone()));
+        let request = |uri: &str| Request::post(uri).body(Body::empty()).unwrap();
+
+        // 占满批量通道
+        let held = tokio::spawn(app.clone().oneshot(request("/rpc?priority=batch&hold")));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let response = app.clone().oneshot(request("/rpc?priority=batch")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let response = app.clone().oneshot(request("/rpc")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        release.notify_one();
+        assert_eq!(held.await.unwrap().unwrap().status(), StatusCode::OK);
+        let output = metrics.render();
+        assert!(output.contains("json_validator_lane_rejected_total{lane=\"batch\"} 1"));
+        assert!(output.contains("json_validator_lane_request_duration_seconds_count{lane=\"interactive\"} 1"));
+        assert!(output.contains("json_validator_lane_in_flight{lane=\"batch\"} 0"));
+    }
+}
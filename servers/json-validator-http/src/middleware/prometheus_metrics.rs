@@ -0,0 +1,465 @@
+//! Prometheus指标中间件
+//!
+//! 挂载在JSON-RPC端点上，按方法（`tools/call` 额外按工具名）记录请求数、
+//! 延迟直方图和请求/响应体大小。方法名经 [`MethodRouter`] 归一化，
+//! 未知方法统一记为 `unknown`，避免标签基数失控。
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use axum::body::Body;
+use axum::http::Request;
+use axum::response::Response;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use tower::{Layer, Service};
+
+use memory_watchdog::MemoryPressure;
+use crate::rpc::{MethodRouter, RpcMethod};
+
+/// Prometheus文本格式的Content-Type
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// 指标名前缀
+const NAMESPACE: &str = "json_validator";
+
+/// JSON-RPC指标集合
+pub struct PrometheusMetrics {
+    registry: Registry,
+    requests_total: IntCounterVec,
+    request_duration: HistogramVec,
+    request_size: HistogramVec,
+    response_size: HistogramVec,
+    cache_hits: IntGauge,
+    cache_misses: IntGauge,
+    cache_entries: IntGauge,
+    shadow_validations: IntCounterVec,
+    panics_total: IntCounter,
+    memory_rss_bytes: IntGauge,
+    memory_pressure: IntGauge,
+    memory_cache_evictions: IntCounter,
+    validation_pool_running: IntGauge,
+    validation_pool_queued: IntGauge,
+    validation_pool_rejected: IntGauge,
+    lane_queue_wait: HistogramVec,
+    lane_request_duration: HistogramVec,
+    lane_in_flight: IntGaugeVec,
+    lane_rejected: IntCounterVec,
+}
+
+impl PrometheusMetrics {
+    /// 创建并注册全部指标
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        let labels = &["method", "tool"];
+
+        let requests_total = IntCounterVec::new(
+            Opts::new("rpc_requests_total", "JSON-RPC requests by method and outcome").namespace(NAMESPACE),
+            &["method", "tool", "status"],
+        )
+        .expect("valid metric");
+        let request_duration = HistogramVec::new(
+            HistogramOpts::new("rpc_request_duration_seconds", "JSON-RPC request latency").namespace(NAMESPACE),
+            labels,
+        )
+        .expect("valid metric");
+        let request_size = HistogramVec::new(
+            HistogramOpts::new("rpc_request_size_bytes", "JSON-RPC request body size")
+                .namespace(NAMESPACE)
+                .buckets(prometheus::exponential_buckets(64.0, 4.0, 8).expect("valid buckets")),
+            labels,
+        )
+        .expect("valid metric");
+        let response_size = HistogramVec::new(
+            HistogramOpts::new("rpc_response_size_bytes", "JSON-RPC response body size")
+                .namespace(NAMESPACE)
+                .buckets(prometheus::exponential_buckets(64.0, 4.0, 8).expect("valid buckets")),
+            labels,
+        )
+        .expect("valid metric");
+        let cache_hits = IntGauge::with_opts(
+            Opts::new("schema_cache_hits", "Compiled schema cache hits").namespace(NAMESPACE),
+        )
+        .expect("valid metric");
+        let cache_misses = IntGauge::with_opts(
+            Opts::new("schema_cache_misses", "Compiled schema cache misses").namespace(NAMESPACE),
+        )
+        .expect("valid metric");
+        let cache_entries = IntGauge::with_opts(
+            Opts::new("schema_cache_entries", "Compiled schemas currently cached").namespace(NAMESPACE),
+        )
+        .expect("valid metric");
+
+        let shadow_validations = IntCounterVec::new(
+            Opts::new("shadow_validations_total", "Shadow validations against candidate schema versions")
+                .namespace(NAMESPACE),
+            &["schema", "outcome"],
+        )
+        .expect("valid metric");
+        let panics_total = IntCounter::with_opts(
+            Opts::new("panics_total", "Request handler panics caught and answered with 500").namespace(NAMESPACE),
+        )
+        .expect("valid metric");
+        let memory_rss_bytes = IntGauge::with_opts(
+            Opts::new("memory_rss_bytes", "Resident memory of the process").namespace(NAMESPACE),
+        )
+        .expect("valid metric");
+        let memory_pressure = IntGauge::with_opts(
+            Opts::new("memory_pressure", "Memory pressure level (0 normal, 1 elevated, 2 critical)").namespace(NAMESPACE),
+        )
+        .expect("valid metric");
+        let memory_cache_evictions = IntCounter::with_opts(
+            Opts::new("memory_cache_evictions_total", "Schema cache evictions triggered by memory pressure")
+                .namespace(NAMESPACE),
+        )
+        .expect("valid metric");
+
+        let validation_pool_running = IntGauge::with_opts(
+            Opts::new("validation_pool_running", "Large-document validations running on the validation pool")
+                .namespace(NAMESPACE),
+        )
+        .expect("valid metric");
+        let validation_pool_queued = IntGauge::with_opts(
+            Opts::new("validation_pool_queued", "Large-document validations waiting for a pool thread")
+                .namespace(NAMESPACE),
+        )
+        .expect("valid metric");
+        let validation_pool_rejected = IntGauge::with_opts(
+            Opts::new("validation_pool_rejected", "Large-document validations rejected because the queue was full")
+                .namespace(NAMESPACE),
+        )
+        .expect("valid metric");
+
+        let lane_queue_wait = HistogramVec::new(
+            HistogramOpts::new("lane_queue_wait_seconds", "Time requests waited for a priority lane slot")
+                .namespace(NAMESPACE),
+            &["lane"],
+        )
+        .expect("valid metric");
+        let lane_request_duration = HistogramVec::new(
+            HistogramOpts::new("lane_request_duration_seconds", "Request latency by priority lane").namespace(NAMESPACE),
+            &["lane"],
+        )
+        .expect("valid metric");
+        let lane_in_flight = IntGaugeVec::new(
+            Opts::new("lane_in_flight", "Requests currently processed by priority lane").namespace(NAMESPACE),
+            &["lane"],
+        )
+        .expect("valid metric");
+        let lane_rejected = IntCounterVec::new(
+            Opts::new("lane_rejected_total", "Requests rejected after waiting too long for a priority lane")
+                .namespace(NAMESPACE),
+            &["lane"],
+        )
+        .expect("valid metric");
+
+        registry.register(Box::new(requests_total.clone())).expect("unique metric");
+        registry.register(Box::new(request_duration.clone())).expect("unique metric");
+        registry.register(Box::new(request_size.clone())).expect("unique metric");
+        registry.register(Box::new(response_size.clone())).expect("unique metric");
+        registry.register(Box::new(cache_hits.clone())).expect("unique metric");
+        registry.register(Box::new(cache_misses.clone())).expect("unique metric");
+        registry.register(Box::new(cache_entries.clone())).expect("unique metric");
+        registry.register(Box::new(shadow_validations.clone())).expect("unique metric");
+        registry.register(Box::new(panics_total.clone())).expect("unique metric");
+        registry.register(Box::new(memory_rss_bytes.clone())).expect("unique metric");
+        registry.register(Box::new(memory_pressure.clone())).expect("unique metric");
+        registry.register(Box::new(memory_cache_evictions.clone())).expect("unique metric");
+        registry.register(Box::new(validation_pool_running.clone())).expect("unique metric");
+        registry.register(Box::new(validation_pool_queued.clone())).expect("unique metric");
+        registry.register(Box::new(validation_pool_rejected.clone())).expect("unique metric");
+        registry.register(Box::new(lane_queue_wait.clone())).expect("unique metric");
+        registry.register(Box::new(lane_request_duration.clone())).expect("unique metric");
+        registry.register(Box::new(lane_in_flight.clone())).expect("unique metric");
+        registry.register(Box::new(lane_rejected.clone())).expect("unique metric");
+
+        Self {
+            registry,
+            requests_total,
+            request_duration,
+            request_size,
+            response_size,
+            cache_hits,
+            cache_misses,
+            cache_entries,
+            shadow_validations,
+            panics_total,
+            memory_rss_bytes,
+            memory_pressure,
+            memory_cache_evictions,
+            validation_pool_running,
+            validation_pool_queued,
+            validation_pool_rejected,
+            lane_queue_wait,
+            lane_request_duration,
+            lane_in_flight,
+            lane_rejected,
+        }
+    }
+
+    /// 记录一次JSON-RPC调用
+    pub fn record(&self, call: &RpcCall, success: bool, duration_secs: f64, request_bytes: usize, response_bytes: usize) {
+        let labels = [call.method, call.tool];
+        let status = if success { "success" } else { "error" };
+        self.requests_total.with_label_values(&[call.method, call.tool, status]).inc();
+        self.request_duration.with_label_values(&labels).observe(duration_secs);
+        self.request_size.with_label_values(&labels).observe(request_bytes as f64);
+        self.response_size.with_label_values(&labels).observe(response_bytes as f64);
+    }
+
+    /// 更新Schema缓存统计
+    pub fn set_cache_stats(&self, hits: u64, misses: u64, entries: usize) {
+        self.cache_hits.set(hits as i64);
+        self.cache_misses.set(misses as i64);
+        self.cache_entries.set(entries as i64);
+    }
+
+    /// 更新大文档验证线程池状态
+    pub fn set_validation_pool_stats(&self, stats: &crate::validation_pool::ValidationPoolStats) {
+        self.validation_pool_running.set(stats.running as i64);
+        self.validation_pool_queued.set(stats.queued as i64);
+        self.validation_pool_rejected.set(stats.rejected as i64);
+    }
+
+    /// 记录请求进入优先级通道，`wait_secs` 为排队时间
+    pub fn record_lane_started(&self, lane: &str, wait_secs: f64) {
+        self.lane_queue_wait.with_label_values(&[lane]).observe(wait_secs);
+        self.lane_in_flight.with_label_values(&[lane]).inc();
+    }
+
+    /// 记录请求在优先级通道中处理完成
+    pub fn record_lane_finished(&self, lane: &str, duration_secs: f64) {
+        self.lane_in_flight.with_label_values(&[lane]).dec();
+        self.lane_request_duration.with_label_values(&[lane]).observe(duration_secs);
+    }
+
+    /// 记录请求因等待优先级通道超时被拒绝
+    pub fn record_lane_rejected(&self, lane: &str) {
+        self.lane_rejected.with_label_values(&[lane]).inc();
+    }
+
+    /// 记录一次影子验证，`matched` 表示候选版本与当前版本结论一致
+    pub fn record_shadow(&self, schema: &str, matched: bool) {
+        let outcome = if matched { "match" } else { "mismatch" };
+        self.shadow_validations.with_label_values(&[schema, outcome]).inc();
+    }
+
+    /// 记录一次请求处理panic
+    pub fn record_panic(&self) {
+        self.panics_total.inc();
+    }
+
+    /// 记录一次内存读数，`evicted` 表示本次因内存压力清空了缓存
+    pub fn record_memory_sample(&self, rss_bytes: u64, pressure: MemoryPressure, evicted: bool) {
+        self.memory_rss_bytes.set(rss_bytes as i64);
+        self.memory_pressure.set(pressure as i64);
+        if evicted {
+            self.memory_cache_evictions.inc();
+        }
+    }
+
+    /// 以Prometheus文本格式导出
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding does not fail");
+        String::from_utf8(buffer).expect("prometheus text output is utf-8")
+    }
+}
+
+impl Default for PrometheusMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 归一化后的调用标签
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RpcCall {
+    /// 方法名，无法解析的请求为 `invalid`，未注册的方法为 `unknown`
+    pub method: &'static str,
+    /// `tools/call` 的工具名，其他方法为空
+    pub tool: &'static str,
+}
+
+impl RpcCall {
+    /// 从请求体解析调用标签
+    pub fn from_body(router: &MethodRouter, body: &[u8]) -> Self {
+        let Ok(request) = serde_json::from_slice::<serde_json::Value>(body) else {
+            return Self { method: "invalid", tool: "" };
+        };
+        let method = request.get("method").and_then(|m| m.as_str()).unwrap_or_default();
+        match router.resolve(method) {
+            Some(RpcMethod::ToolsCall) => {
+                let tool = request
+                    .pointer("/params/name")
+                    .and_then(|n| n.as_str())
+                    .and_then(|n| router.resolve_tool(n))
+                    .map_or("unknown", |t| t.name());
+                Self { method: RpcMethod::ToolsCall.name(), tool }
+            }
+            Some(resolved) => Self { method: resolved.name(), tool: "" },
+            None => Self { method: "unknown", tool: "" },
+        }
+    }
+}
+
+/// 调用标签的来源
+#[derive(Clone)]
+enum CallLabels {
+    /// 从JSON-RPC请求体解析，方法名按路由表归一化
+    Rpc(Arc<MethodRouter>),
+    /// 非JSON-RPC端点，所有请求使用同一标签
+    Fixed(RpcCall),
+}
+
+impl CallLabels {
+    fn resolve(&self, body: &[u8]) -> RpcCall {
+        match self {
+            CallLabels::Rpc(router) => RpcCall::from_body(router, body),
+            CallLabels::Fixed(call) => *call,
+        }
+    }
+}
+
+/// Prometheus指标层
+#[derive(Clone)]
+pub struct PrometheusMetricsLayer {
+    metrics: Arc<PrometheusMetrics>,
+    labels: CallLabels,
+}
+
+impl PrometheusMetricsLayer {
+    /// 创建指标层，方法名按给定路由表归一化
+    pub fn new(metrics: Arc<PrometheusMetrics>, router: Arc<MethodRouter>) -> Self {
+        Self { metrics, labels: CallLabels::Rpc(router) }
+    }
+
+    /// 创建非JSON-RPC端点的指标层，`method` 标签固定为端点名
+    pub fn for_endpoint(metrics: Arc<PrometheusMetrics>, endpoint: &'static str) -> Self {
+        Self { metrics, labels: CallLabels::Fixed(RpcCall { method: endpoint, tool: "" }) }
+    }
+}
+
+impl<S> Layer<S> for PrometheusMetricsLayer {
+    type Service = PrometheusMetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PrometheusMetricsService {
+            inner,
+            metrics: self.metrics.clone(),
+            labels: self.labels.clone(),
+        }
+    }
+}
+
+/// Prometheus指标服务
+#[derive(Clone)]
+pub struct PrometheusMetricsService<S> {
+    inner: S,
+    metrics: Arc<PrometheusMetrics>,
+    labels: CallLabels,
+}
+
+impl<S> Service<Request<Body>> for PrometheusMetricsService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // 取出已就绪的服务，留下克隆供下次调用
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let metrics = self.metrics.clone();
+        let labels = self.labels.clone();
+
+        Box::pin(async move {
+            let start = Instant::now();
+            let (parts, body) = request.into_parts();
+            // 请求体大小由外层限制中间件约束，读取失败时交由处理器返回解析错误
+            let request_bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap_or_default();
+            let call = labels.resolve(&request_bytes);
+
+            let response = inner
+                .call(Request::from_parts(parts, Body::from(request_bytes.clone())))
+                .await?;
+
+            let (parts, body) = response.into_parts();
+            let response_bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap_or_default();
+            // JSON-RPC错误的HTTP状态也是200，按响应中的 `error` 判断；其他端点只看状态码（报告可能是CSV）
+            let success = parts.status.is_success()
+                && (matches!(labels, CallLabels::Fixed(_))
+                    || serde_json::from_slice::<serde_json::Value>(&response_bytes)
+                        .is_ok_and(|r| r.get("error").is_none_or(|e| e.is_null())));
+
+            metrics.record(
+                &call,
+                success,
+                start.elapsed().as_secs_f64(),
+                request_bytes.len(),
+                response_bytes.len(),
+            );
+            Ok(Response::from_parts(parts, Body::from(response_bytes)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RpcConfig;
+
+    #[test]
+    fn test_rpc_call_labels() {
+        let router = MethodRouter::from_config(&RpcConfig::default());
+
+        let call = RpcCall::from_body(&router, br#"{"jsonrpc":"2.0","method":"ping","id":1}"#);
+        assert_eq!(call, RpcCall { method: "ping", tool: "" });
+
+        let body = br#"{"jsonrpc":"2.0","method":"tools/call","params":{"name":"validate_json","arguments":{}},"id":1}"#;
+        assert_eq!(RpcCall::from_body(&router, body).tool, "validate_json");
+
+        assert_eq!(RpcCall::from_body(&router, br#"{"method":"drop_tables"}"#).method, "unknown");
+        assert_eq!(RpcCall::from_body(&router, b"{").method, "invalid");
+    }
+
+    #[tokio::test]
+    async fn test_layer_records_calls() {
+        use axum::routing::post;
+        use axum::Router;
+        use tower::ServiceExt;
+
+        let metrics = Arc::new(PrometheusMetrics::new());
+        let router = Arc::new(MethodRouter::from_config(&RpcConfig::default()));
+        let app = Router::new()
+            .route("/rpc", post(|| async { r#"{"jsonrpc":"2.0","error":{"code":-32601},"id":1}"# }))
+            .layer(PrometheusMetricsLayer::new(metrics.clone(), router));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/rpc")
+            .body(Body::from(r#"{"jsonrpc":"2.0","method":"ping","id":1}"#))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(body.starts_with(br#"{"jsonrpc""#));
+
+        let output = metrics.render();
+        assert!(output.contains(r#"json_validator_rpc_requests_total{method="ping",status="error",tool=""} 1"#));
+        assert!(output.contains(r#"json_validator_rpc_request_duration_seconds_count{method="ping",tool=""} 1"#));
+    }
+}
@@ -0,0 +1,359 @@
+//! JSON-RPC端点限流中间件
+//!
+//! 按客户端维护令牌桶：已通过认证的请求，或携带已配置API密钥（`x-api-key` 或
+//! `Authorization: Bearer`）的请求按密钥计数并使用密钥自身的速率，其他请求按客户端IP计数。
+//! 白名单中的IP不受限制。被限流的请求返回HTTP 429和 `RATE_LIMIT` JSON-RPC错误，
+//! 所有经过限流的响应都带有 `X-RateLimit-Limit` / `X-RateLimit-Remaining` 头。
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderMap, HeaderValue, Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use parking_lot::Mutex;
+use tower::{Layer, Service};
+
+use crate::api_keys::{api_key_from_headers, AuthenticatedKey};
+use crate::config::SecurityConfig;
+use crate::models::{JsonRpcError, JsonRpcResponse, TRACE_ID_HEADER};
+
+/// 速率上限响应头
+pub const RATE_LIMIT_LIMIT_HEADER: &str = "x-ratelimit-limit";
+
+/// 剩余请求数响应头
+pub const RATE_LIMIT_REMAINING_HEADER: &str = "x-ratelimit-remaining";
+
+/// 令牌桶数量超过该值时清理已回满的桶
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// 单个客户端的令牌桶
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// 单次限流判定结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    /// 是否放行
+    pub allowed: bool,
+    /// 每分钟请求上限
+    pub limit: u32,
+    /// 剩余可用请求数
+    pub remaining: u32,
+    /// 被限流时建议的重试等待秒数
+    pub retry_after: u64,
+}
+
+/// 按API密钥或IP计数的令牌桶限流器
+pub struct RateLimiter {
+    rate_per_minute: u32,
+    burst: u32,
+    api_key_enabled: bool,
+    api_key_rates: HashMap<String, u32>,
+    whitelist: Vec<IpAddr>,
+    error_message: String,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    /// 根据安全配置创建限流器，速率取 `rate_limit`，桶容量取 `rate_limiting.burst`
+    pub fn from_config(config: &SecurityConfig) -> Self {
+        let api_key_rates = config
+            .api_keys
+            .iter()
+            .map(|(key, key_config)| (key.clone(), key_config.rate_limit.unwrap_or(config.rate_limit)))
+            .collect();
+        let whitelist = config
+            .rate_limiting
+            .whitelist
+            .iter()
+            .filter_map(|ip| ip.parse().ok())
+            .collect();
+
+        Self {
+            rate_per_minute: config.rate_limit,
+            burst: config.rate_limiting.burst,
+            api_key_enabled: config.api_key_enabled,
+            api_key_rates,
+            whitelist,
+            error_message: config.rate_limiting.error_message.clone(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 是否启用限流（速率为0时不限流）
+    pub fn is_enabled(&self) -> bool {
+        self.rate_per_minute > 0
+    }
+
+    /// 限流错误消息
+    pub fn error_message(&self) -> &str {
+        &self.error_message
+    }
+
+    /// 确定请求的计数键和速率，白名单IP返回 `None`
+    pub fn client_key(
+        &self,
+        headers: &HeaderMap,
+        ip: Option<IpAddr>,
+        authenticated: Option<&AuthenticatedKey>,
+    ) -> Option<(String, u32)> {
+        if let Some(key) = authenticated {
+            return Some((format!("key:{}", key.id), key.rate_limit.unwrap_or(self.rate_per_minute)));
+        }
+        if self.api_key_enabled {
+            if let Some(key) = api_key_from_headers(headers) {
+                // 只认可已配置的密钥，避免伪造密钥绕过IP限流
+                if let Some(rate) = self.api_key_rates.get(key) {
+                    return Some((format!("key:{}", key), *rate));
+                }
+            }
+        }
+
+        let ip = ip.or_else(|| forwarded_ip(headers));
+        match ip {
+            Some(ip) if self.whitelist.contains(&ip) => None,
+            Some(ip) => Some((format!("ip:{}", ip), self.rate_per_minute)),
+            None => Some(("ip:unknown".to_string(), self.rate_per_minute)),
+        }
+    }
+
+    /// 从客户端的令牌桶中取出一个令牌
+    pub fn check(&self, client: &str, rate_per_minute: u32) -> RateLimitDecision {
+        let capacity = f64::from(if self.burst > 0 { self.burst } else { rate_per_minute });
+        let refill_per_sec = f64::from(rate_per_minute) / 60.0;
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock();
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * refill_per_sec < capacity
+            });
+        }
+
+        let bucket = buckets
+            .entry(client.to_string())
+            .or_insert(Bucket { tokens: capacity, updated: now });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        let retry_after = if allowed || refill_per_sec <= 0.0 {
+            0
+        } else {
+            ((1.0 - bucket.tokens) / refill_per_sec).ceil().max(1.0) as u64
+        };
+
+        RateLimitDecision {
+            allowed,
+            limit: rate_per_minute,
+            remaining: bucket.tokens.floor() as u32,
+            retry_after,
+        }
+    }
+}
+
+/// 没有连接信息时（如嵌入到其他服务中）退回 `x-forwarded-for` 的第一个地址
+fn forwarded_ip(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .and_then(|ip| ip.trim().parse().ok())
+}
+
+/// 限流层
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitLayer {
+    /// 使用共享的限流器创建限流层
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        Self { limiter }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+/// 限流服务
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // 取出已就绪的服务，留下克隆供下次调用
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let limiter = self.limiter.clone();
+
+        Box::pin(async move {
+            let ip = request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip());
+            let authenticated = request.extensions().get::<AuthenticatedKey>();
+            let Some((client, rate)) = limiter.client_key(request.headers(), ip, authenticated) else {
+                return inner.call(request).await;
+            };
+
+            let decision = limiter.check(&client, rate);
+            if !decision.allowed {
+                tracing::warn!("Rate limit exceeded for {}", client);
+                return Ok(rate_limited_response(request, &limiter, decision).await);
+            }
+
+            let mut response = inner.call(request).await?;
+            insert_limit_headers(response.headers_mut(), decision);
+            Ok(response)
+        })
+    }
+}
+
+/// 构造限流响应，沿用请求中的JSON-RPC `id`
+async fn rate_limited_response(request: Request<Body>, limiter: &RateLimiter, decision: RateLimitDecision) -> Response {
+    let body = axum::body::to_bytes(request.into_body(), usize::MAX).await.unwrap_or_default();
+    let id = serde_json::from_slice::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|r| r.get("id").cloned())
+        .unwrap_or(serde_json::Value::Null);
+
+    let trace_id = crate::middleware::request_trace_id();
+    let error = JsonRpcError::rate_limited(limiter.error_message().to_string()).with_trace_id(&trace_id);
+    let mut response = (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(TRACE_ID_HEADER, trace_id)],
+        axum::Json(JsonRpcResponse::error(error, id)),
+    )
+        .into_response();
+
+    let headers = response.headers_mut();
+    insert_limit_headers(headers, decision);
+    headers.insert(header::RETRY_AFTER, HeaderValue::from(decision.retry_after));
+    response
+}
+
+fn insert_limit_headers(headers: &mut HeaderMap, decision: RateLimitDecision) {
+    headers.insert(RATE_LIMIT_LIMIT_HEADER, HeaderValue::from(decision.limit));
+    headers.insert(RATE_LIMIT_REMAINING_HEADER, HeaderValue::from(decision.remaining));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_keys::API_KEY_HEADER;
+    use crate::config::{ApiKeyConfig, RateLimitingConfig};
+    use axum::routing::post;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    fn limiter(rate: u32, burst: u32) -> RateLimiter {
+        let reader = ApiKeyConfig { name: "Reader".to_string(), permissions: vec!["read".to_string()], rate_limit: Some(50) };
+        let config = SecurityConfig {
+            rate_limit: rate,
+            rate_limiting: RateLimitingConfig { burst, ..Default::default() },
+            api_keys: HashMap::from([("reader-key".to_string(), reader)]),
+            ..Default::default()
+        };
+        RateLimiter::from_config(&config)
+    }
+
+    #[test]
+    fn test_client_key() {
+        let limiter = limiter(100, 0);
+        let mut headers = HeaderMap::new();
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("reader-key"));
+        assert_eq!(limiter.client_key(&headers, None, None), Some(("key:reader-key".to_string(), 50)));
+
+        // 未配置的密钥按IP计数
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("made_up"));
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        assert_eq!(limiter.client_key(&headers, Some(ip), None), Some(("ip:10.0.0.1".to_string(), 100)));
+
+        assert_eq!(limiter.client_key(&HeaderMap::new(), Some("127.0.0.1".parse().unwrap()), None), None);
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter = limiter(60, 2);
+        assert!(limiter.check("ip:10.0.0.1", 60).allowed);
+        let decision = limiter.check("ip:10.0.0.1", 60);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+
+        let decision = limiter.check("ip:10.0.0.1", 60);
+        assert!(!decision.allowed);
+        assert_eq!(decision.retry_after, 1);
+
+        // 其他客户端不受影响
+        assert!(limiter.check("ip:10.0.0.2", 60).allowed);
+    }
+
+    #[tokio::test]
+    async fn test_layer_rejects_with_json_rpc_error() {
+        let app = Router::new()
+            .route("/rpc", post(|| async { r#"{"jsonrpc":"2.0","result":{},"id":7}"# }))
+            .layer(RateLimitLayer::new(Arc::new(limiter(60, 1))));
+
+        let request = || {
+            Request::builder()
+                .method("POST")
+                .uri("/rpc")
+                .header("x-forwarded-for", "10.0.0.1")
+                .body(Body::from(r#"{"jsonrpc":"2.0","method":"ping","id":7}"#))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[RATE_LIMIT_LIMIT_HEADER], "60");
+        assert_eq!(response.headers()[RATE_LIMIT_REMAINING_HEADER], "0");
+
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["id"], 7);
+        assert_eq!(body["error"]["code"], crate::models::RATE_LIMIT_ERROR_CODE);
+        assert_eq!(body["error"]["data"]["code"], "RATE_LIMIT");
+    }
+}
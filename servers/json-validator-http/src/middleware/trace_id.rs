@@ -0,0 +1,62 @@
+//! 追踪ID中间件
+//!
+//! 沿用调用方传入的 `x-trace-id`（或 `x-request-id`），否则生成新的追踪ID。
+//! 本次请求内产生的问题详情、JSON-RPC错误和日志都使用该追踪ID，响应头同样携带，
+//! 便于把调用方与本服务的日志串联起来。
+
+use axum::extract::Request;
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::models::TRACE_ID_HEADER;
+
+tokio::task_local! {
+    static TRACE_ID: String;
+}
+
+/// 当前请求的追踪ID，不在请求处理中时生成新的追踪ID
+pub fn request_trace_id() -> String {
+    TRACE_ID
+        .try_with(Clone::clone)
+        .unwrap_or_else(|_| crate::utils::utils::generate_request_id())
+}
+
+/// 追踪ID中间件
+pub async fn trace_id_middleware(request: Request, next: Next) -> Response {
+    let trace_id = problem_details::incoming_trace_id(request.headers())
+        .unwrap_or_else(crate::utils::utils::generate_request_id);
+    let mut response = TRACE_ID.scope(trace_id.clone(), next.run(request)).await;
+    if let Ok(value) = HeaderValue::from_str(&trace_id) {
+        response.headers_mut().entry(TRACE_ID_HEADER).or_insert(value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_incoming_trace_id_is_reused() {
+        let app = Router::new()
+            .route("/", get(|| async { request_trace_id() }))
+            .layer(axum::middleware::from_fn(trace_id_middleware));
+
+        let request = Request::builder().uri("/").header(TRACE_ID_HEADER, "upstream-1").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[TRACE_ID_HEADER], "upstream-1");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"upstream-1");
+
+        // 无效的追踪ID被忽略，改为生成新的追踪ID
+        let request = Request::builder().uri("/").header(TRACE_ID_HEADER, "has space").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let generated = response.headers()[TRACE_ID_HEADER].to_str().unwrap().to_string();
+        assert_ne!(generated, "has space");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, generated.as_bytes());
+    }
+}
@@ -0,0 +1,265 @@
+//! 请求体JSON Schema验证中间件
+//!
+//! 按方法和路径注册Schema，匹配的请求在到达处理器之前完成请求体验证，
+//! 失败时返回 `application/problem+json` 错误：
+//!
+//! ```ignore
+//! let layer = ValidationLayer::new()
+//!     .with_schema(Method::POST, "/api/v1/tasks", &task_schema)?
+//!     .with_schema(Method::PATCH, "/api/v1/tasks/:task_id", &update_schema)?;
+//! let app = Router::new().route("/api/v1/tasks", post(create_task)).layer(layer);
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use tower::{Layer, Service};
+
+use crate::models::request_problem;
+
+/// 默认的请求体大小上限（2MB）
+const DEFAULT_MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// 已注册的路由Schema
+struct RouteSchema {
+    method: Method,
+    segments: Vec<String>,
+    schema: jsonschema::JSONSchema,
+}
+
+impl RouteSchema {
+    /// 按路径段匹配，`:name` 段匹配任意单个路径段
+    fn matches(&self, method: &Method, path: &str) -> bool {
+        if self.method != method {
+            return false;
+        }
+        let mut segments = path.trim_matches('/').split('/');
+        let matched = self.segments.iter().all(|expected| match segments.next() {
+            Some(actual) => expected.starts_with(':') || expected == actual,
+            None => false,
+        });
+        matched && segments.next().is_none()
+    }
+}
+
+/// 请求体验证层
+///
+/// 克隆后的验证层各自独立，在其中一个上继续注册Schema不影响另一个。
+#[derive(Clone)]
+pub struct ValidationLayer {
+    routes: Arc<Vec<Arc<RouteSchema>>>,
+    max_body_size: usize,
+}
+
+impl ValidationLayer {
+    /// 创建空的验证层
+    pub fn new() -> Self {
+        Self {
+            routes: Arc::new(Vec::new()),
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+
+    /// 为指定方法和路径注册Schema，Schema无法编译时返回错误
+    pub fn with_schema(mut self, method: Method, path: &str, schema: &serde_json::Value) -> Result<Self, String> {
+        let compiled = jsonschema::JSONSchema::compile(schema)
+            .map_err(|e| format!("Invalid schema for {} {}: {}", method, path, e))?;
+
+        Arc::make_mut(&mut self.routes).push(Arc::new(RouteSchema {
+            method,
+            segments: path.trim_matches('/').split('/').map(str::to_string).collect(),
+            schema: compiled,
+        }));
+        Ok(self)
+    }
+
+    /// 设置请求体大小上限
+    pub fn with_max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+}
+
+impl Default for ValidationLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for ValidationLayer {
+    type Service = ValidationService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ValidationService {
+            inner,
+            routes: self.routes.clone(),
+            max_body_size: self.max_body_size,
+        }
+    }
+}
+
+/// 请求体验证服务
+#[derive(Clone)]
+pub struct ValidationService<S> {
+    inner: S,
+    routes: Arc<Vec<Arc<RouteSchema>>>,
+    max_body_size: usize,
+}
+
+impl<S> Service<Request<Body>> for ValidationService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // 取出已就绪的服务，留下克隆供下次调用
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let route = self
+            .routes
+            .iter()
+            .position(|r| r.matches(request.method(), request.uri().path()));
+        let Some(route) = route else {
+            return Box::pin(inner.call(request));
+        };
+
+        let routes = self.routes.clone();
+        let max_body_size = self.max_body_size;
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let path = parts.uri.path().to_string();
+
+            let bytes = match axum::body::to_bytes(body, max_body_size).await {
+                Ok(bytes) => bytes,
+                Err(_) => {
+                    return Ok(problem(
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        "PAYLOAD_TOO_LARGE",
+                        format!("Request body exceeds {} bytes", max_body_size),
+                        &path,
+                    ));
+                }
+            };
+
+            let document: serde_json::Value = match serde_json::from_slice(&bytes) {
+                Ok(document) => document,
+                Err(e) => {
+                    return Ok(problem(StatusCode::BAD_REQUEST, "INVALID_JSON", e.to_string(), &path));
+                }
+            };
+
+            if let Err(errors) = routes[route].schema.validate(&document) {
+                let errors: Vec<_> = errors
+                    .map(|e| serde_json::json!({
+                        "instanceLocation": e.instance_path.to_string(),
+                        "keywordLocation": e.schema_path.to_string(),
+                        "error": e.to_string(),
+                    }))
+                    .collect();
+                return Ok(request_problem(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "SCHEMA_VALIDATION_FAILED",
+                    format!("Request body failed schema validation with {} error(s)", errors.len()),
+                )
+                .with_instance(path)
+                .with_details(serde_json::Value::Array(errors))
+                .into_response());
+            }
+
+            inner.call(Request::from_parts(parts, Body::from(bytes))).await
+        })
+    }
+}
+
+fn problem(status: StatusCode, code: &str, detail: String, path: &str) -> Response {
+    request_problem(status, code, detail)
+        .with_instance(path.to_string())
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::post;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": { "name": { "type": "string" } },
+            "required": ["name"]
+        });
+        let layer = ValidationLayer::new()
+            .with_schema(Method::POST, "/items/:id", &schema)
+            .unwrap();
+        Router::new()
+            .route("/items/:id", post(|body: String| async move { body }))
+            .route("/other", post(|| async { "ok" }))
+            .layer(layer)
+    }
+
+    fn post_request(uri: &str, body: &str) -> Request<Body> {
+        Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_valid_body_reaches_handler() {
+        let response = app().oneshot(post_request("/items/1", r#"{"name":"a"}"#)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], br#"{"name":"a"}"#);
+
+        let response = app().oneshot(post_request("/other", "not json")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_body_returns_problem_json() {
+        let response = app().oneshot(post_request("/items/1", r#"{"name":1}"#)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response.headers()["content-type"], "application/problem+json");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["code"], "SCHEMA_VALIDATION_FAILED");
+        assert_eq!(problem["details"][0]["instanceLocation"], "/name");
+
+        let response = app().oneshot(post_request("/items/1", "{")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_cloned_layer_can_be_extended() {
+        let base = ValidationLayer::new();
+        let shared = base.clone();
+        let layer = base
+            .with_schema(Method::POST, "/items/:id", &serde_json::json!({ "type": "object" }))
+            .unwrap();
+
+        let router = |layer: ValidationLayer| {
+            Router::new().route("/items/:id", post(|| async { "ok" })).layer(layer)
+        };
+        let response = router(layer).oneshot(post_request("/items/1", "[]")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = router(shared).oneshot(post_request("/items/1", "[]")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -1,5 +1,6 @@
 //! 数据模型定义
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -62,6 +63,9 @@ pub struct JsonRpcResponse {
     pub error: Option<JsonRpcError>,
     /// 请求ID
     pub id: serde_json::Value,
+    /// 客户端传入的关联ID，原样返回
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
 }
 
 impl JsonRpcResponse {
@@ -72,6 +76,7 @@ impl JsonRpcResponse {
             result: Some(result),
             error: None,
             id,
+            correlation_id: None,
         }
     }
     
@@ -82,10 +87,23 @@ impl JsonRpcResponse {
             result: None,
             error: Some(error),
             id,
+            correlation_id: None,
         }
     }
 }
 
+/// 请求被限流时的JSON-RPC错误码（服务器自定义错误区间）
+pub const RATE_LIMIT_ERROR_CODE: i32 = -32029;
+
+/// 缺少或无效API密钥时的JSON-RPC错误码
+pub const UNAUTHORIZED_ERROR_CODE: i32 = -32001;
+
+/// API密钥权限不足时的JSON-RPC错误码
+pub const FORBIDDEN_ERROR_CODE: i32 = -32003;
+
+/// 大文档验证线程池排队已满时的JSON-RPC错误码
+pub const OVERLOADED_ERROR_CODE: i32 = -32005;
+
 /// JSON-RPC错误
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonRpcError {
@@ -108,6 +126,42 @@ impl JsonRpcError {
         }
     }
     
+    /// 机器可读的错误码（与REST问题详情中的 `code` 保持一致）
+    pub fn error_code(&self) -> &'static str {
+        match self.code {
+            -32700 => "PARSE_ERROR",
+            -32600 => "INVALID_REQUEST",
+            -32601 => "METHOD_NOT_FOUND",
+            -32602 => "INVALID_PARAMS",
+            -32603 => "INTERNAL_ERROR",
+            RATE_LIMIT_ERROR_CODE => "RATE_LIMIT",
+            UNAUTHORIZED_ERROR_CODE => "UNAUTHORIZED",
+            FORBIDDEN_ERROR_CODE => "FORBIDDEN",
+            OVERLOADED_ERROR_CODE => "OVERLOADED",
+            _ => "SERVER_ERROR",
+        }
+    }
+
+    /// 附加追踪ID，`data` 统一为 `{detail, code, type, trace_id}` 结构
+    pub fn with_trace_id(mut self, trace_id: &str) -> Self {
+        let detail = match self.data.take() {
+            Some(serde_json::Value::String(detail)) => serde_json::Value::String(detail),
+            Some(serde_json::Value::Object(mut data)) => data
+                .remove("detail")
+                .unwrap_or(serde_json::Value::Null),
+            Some(other) => other,
+            None => serde_json::Value::String(self.message.clone()),
+        };
+        let code = self.error_code();
+        self.data = Some(serde_json::json!({
+            "detail": detail,
+            "code": code,
+            "type": error_docs_url(code),
+            "trace_id": trace_id,
+        }));
+        self
+    }
+
     /// 解析错误
     pub fn parse_error() -> Self {
         Self::new(
@@ -152,10 +206,46 @@ impl JsonRpcError {
             Some(message),
         )
     }
+
+    /// 限流错误
+    pub fn rate_limited(message: String) -> Self {
+        Self::new(
+            RATE_LIMIT_ERROR_CODE,
+            "Rate limit exceeded".to_string(),
+            Some(message),
+        )
+    }
+
+    /// 未认证错误
+    pub fn unauthorized(message: String) -> Self {
+        Self::new(
+            UNAUTHORIZED_ERROR_CODE,
+            "Unauthorized".to_string(),
+            Some(message),
+        )
+    }
+
+    /// 权限不足错误
+    pub fn forbidden(message: String) -> Self {
+        Self::new(
+            FORBIDDEN_ERROR_CODE,
+            "Forbidden".to_string(),
+            Some(message),
+        )
+    }
+
+    /// 服务器过载错误
+    pub fn overloaded(message: String) -> Self {
+        Self::new(
+            OVERLOADED_ERROR_CODE,
+            "Server overloaded".to_string(),
+            Some(message),
+        )
+    }
 }
 
 /// JSON验证请求
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ValidateJsonRequest {
     /// JSON数据
     pub json_data: serde_json::Value,
@@ -165,29 +255,145 @@ pub struct ValidateJsonRequest {
 }
 
 /// JSON Schema验证请求
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ValidateJsonWithSchemaRequest {
     /// JSON数据
     pub json_data: serde_json::Value,
-    /// JSON Schema
+    /// JSON Schema，与 `schema_ref` 二选一
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
     pub schema: serde_json::Value,
+    /// 注册表中的Schema名称，与 `schema` 二选一
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_ref: Option<String>,
+    /// 验证选项
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<ValidationOptions>,
+}
+
+/// 按验证配置档验证请求
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ValidateWithProfileRequest {
+    /// JSON数据
+    pub json_data: serde_json::Value,
+    /// 配置档名称
+    pub profile: String,
     /// 验证选项
     #[serde(skip_serializing_if = "Option::is_none")]
     pub options: Option<ValidationOptions>,
 }
 
 /// 批量JSON验证请求
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ValidateJsonBatchRequest {
     /// 验证项列表
     pub items: Vec<BatchValidationItem>,
     /// 验证选项
     #[serde(skip_serializing_if = "Option::is_none")]
     pub options: Option<ValidationOptions>,
+    /// 复用之前请求中相同项的验证结果（需要服务器启用缓存）
+    #[serde(default)]
+    pub use_result_cache: bool,
+}
+
+/// 多Schema验证请求
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ValidateMultiRequest {
+    /// JSON数据
+    pub json_data: serde_json::Value,
+    /// Schema列表
+    pub schemas: Vec<SchemaEntry>,
+    /// 组合方式
+    #[serde(default)]
+    pub mode: CombineMode,
+    /// 验证选项
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<ValidationOptions>,
+}
+
+/// 多Schema验证中的单个Schema
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SchemaEntry {
+    /// Schema标识（可选）。未提供 `schema` 时按该标识从Schema注册表中取Schema；
+    /// 结果中未指定时使用Schema的 `$id`，再退回到列表下标
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// 内联JSON Schema，省略时按 `id` 引用注册表中的Schema
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub schema: serde_json::Value,
+}
+
+impl SchemaEntry {
+    /// 结果中使用的标识
+    pub fn label(&self, index: usize) -> String {
+        self.id
+            .clone()
+            .or_else(|| self.schema.get("$id").and_then(|v| v.as_str()).map(str::to_string))
+            .unwrap_or_else(|| index.to_string())
+    }
+}
+
+/// 多Schema验证的组合方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CombineMode {
+    /// 全部通过才算通过（allOf）
+    #[default]
+    All,
+    /// 任一通过即算通过（anyOf）
+    Any,
+    /// 仅报告各Schema的结果，不给出总体结论
+    Report,
+}
+
+impl CombineMode {
+    /// 根据各Schema结果得出总体结论
+    pub fn combine(&self, results: &[bool]) -> Option<bool> {
+        match self {
+            CombineMode::All => Some(results.iter().all(|v| *v)),
+            CombineMode::Any => Some(results.iter().any(|v| *v)),
+            CombineMode::Report => None,
+        }
+    }
+}
+
+/// 单个Schema的验证结果
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SchemaValidationResult {
+    /// Schema标识
+    pub id: String,
+    /// 验证结果
+    pub result: ValidationResult,
+}
+
+/// 多Schema验证结果
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MultiValidationResult {
+    /// 总体结论，`report` 模式下为空
+    pub valid: Option<bool>,
+    /// 组合方式
+    pub mode: CombineMode,
+    /// 各Schema的验证结果
+    pub results: Vec<SchemaValidationResult>,
+}
+
+impl MultiValidationResult {
+    /// 序列化验证结果，各Schema结果使用指定的输出格式
+    pub fn to_value(&self, format: Option<OutputFormat>) -> serde_json::Value {
+        let results: Vec<_> = self
+            .results
+            .iter()
+            .map(|r| serde_json::json!({ "id": r.id, "result": r.result.to_value(format) }))
+            .collect();
+        serde_json::json!({
+            "valid": self.valid,
+            "mode": self.mode,
+            "results": results,
+        })
+    }
 }
 
 /// 批量验证项
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BatchValidationItem {
     /// 项目ID
     pub id: String,
@@ -198,8 +404,58 @@ pub struct BatchValidationItem {
     pub schema: Option<serde_json::Value>,
 }
 
+/// 批量验证汇总
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BatchValidationSummary {
+    /// 验证项总数
+    pub total: usize,
+    /// 通过验证的项数
+    pub success: usize,
+    /// 未通过验证的项数
+    pub failed: usize,
+    /// 与批内其他项重复或命中结果缓存、没有实际执行验证的项数
+    #[serde(default)]
+    pub deduplicated: usize,
+}
+
+/// 批量验证响应
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BatchValidationResponse {
+    /// 各项的验证结果
+    pub results: Vec<BatchValidationResult>,
+    /// 汇总
+    pub summary: BatchValidationSummary,
+}
+
+impl BatchValidationResponse {
+    /// 根据各项结果生成响应
+    pub fn new(results: Vec<BatchValidationResult>) -> Self {
+        let success = results.iter().filter(|r| r.result.valid).count();
+        let summary = BatchValidationSummary {
+            total: results.len(),
+            success,
+            failed: results.len() - success,
+            deduplicated: 0,
+        };
+        Self { results, summary }
+    }
+
+    /// 序列化批量验证响应，各项结果使用指定的输出格式
+    pub fn to_value(&self, format: Option<OutputFormat>) -> serde_json::Value {
+        let results: Vec<_> = self
+            .results
+            .iter()
+            .map(|r| serde_json::json!({ "id": r.id, "result": r.result.to_value(format) }))
+            .collect();
+        serde_json::json!({
+            "results": results,
+            "summary": self.summary,
+        })
+    }
+}
+
 /// 验证选项
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ValidationOptions {
     /// 是否启用严格模式
     #[serde(default = "default_strict_mode")]
@@ -219,6 +475,18 @@ pub struct ValidationOptions {
     /// 缓存键（可选）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cache_key: Option<String>,
+    /// 结果输出格式（可选），未指定时返回默认的验证结果结构
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<OutputFormat>,
+    /// 验证失败时返回修复建议（JSON Patch），仅Schema验证有效
+    #[serde(default)]
+    pub suggest_fixes: bool,
+    /// 验证通过时返回按Schema规范化后的文档，仅Schema验证有效
+    #[serde(default)]
+    pub normalize: bool,
+    /// 错误消息的语言（如 `zh-CN`、`en`），未指定时按 `lang` 查询参数或 `Accept-Language` 请求头确定
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
 }
 
 fn default_strict_mode() -> bool {
@@ -246,12 +514,276 @@ impl Default for ValidationOptions {
             enable_custom_formats: false,
             detailed_errors: true,
             cache_key: None,
+            output: None,
+            suggest_fixes: false,
+            normalize: false,
+            locale: None,
+        }
+    }
+}
+
+/// JSON Schema 规范定义的标准输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// 仅返回 `{"valid": bool}`
+    Flag,
+    /// 扁平的错误单元列表
+    Basic,
+    /// 按模式位置组织的错误树，仅有一个子节点的中间节点会被折叠
+    Detailed,
+    /// 按Schema结构展开的完整输出树，包含通过验证的关键字单元及其注解
+    Verbose,
+}
+
+/// 标准输出单元
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputUnit {
+    /// 该单元是否通过验证
+    pub valid: bool,
+    /// 模式中的关键字位置（JSON Pointer）
+    pub keyword_location: String,
+    /// 实例中的位置（JSON Pointer）
+    pub instance_location: String,
+    /// 错误消息（仅叶子单元）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 注解关键字的值（仅 `verbose` 输出中通过验证的叶子单元）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<serde_json::Value>,
+    /// 子单元
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<OutputUnit>,
+}
+
+impl OutputUnit {
+    fn leaf(error: &ValidationError) -> Self {
+        Self {
+            valid: false,
+            keyword_location: error.schema_path.clone(),
+            instance_location: error.instance_path.clone(),
+            error: Some(error.message.clone()),
+            annotation: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// 按Schema结构构建完整输出树
+    ///
+    /// 每个参与验证的关键字生成一个单元，`properties`、`items`（单个Schema）和 `allOf`
+    /// 按子Schema和实例位置展开；通过验证的注解关键字带上注解值。错误挂在对应的关键字单元下，
+    /// 对应不到Schema结构的错误（如自定义规则错误）挂在根单元下。
+    pub fn verbose(schema: &serde_json::Value, instance: &serde_json::Value, errors: &[ValidationError]) -> Self {
+        let mut placed = vec![false; errors.len()];
+        let mut root = Self::schema_unit(schema, String::new(), String::new(), instance, errors, &mut placed);
+        let unplaced: Vec<_> = errors.iter().zip(placed).filter(|(_, placed)| !placed).map(|(e, _)| e).collect();
+        root.attach_errors(unplaced);
+        root
+    }
+
+    /// 把错误作为叶子单元挂在当前单元下
+    pub fn attach_errors<'a>(&mut self, errors: impl IntoIterator<Item = &'a ValidationError>) {
+        for error in errors {
+            self.valid = false;
+            self.errors.push(Self::leaf(error));
+        }
+    }
+
+    /// 子Schema单元
+    fn schema_unit(
+        schema: &serde_json::Value,
+        keyword_location: String,
+        instance_location: String,
+        instance: &serde_json::Value,
+        errors: &[ValidationError],
+        placed: &mut [bool],
+    ) -> Self {
+        let mut children = Vec::new();
+        for (keyword, value) in schema.as_object().into_iter().flatten() {
+            if NON_ASSERTION_KEYWORDS.contains(&keyword.as_str()) {
+                continue;
+            }
+            let location = format!("{}/{}", keyword_location, keyword);
+            let subschemas: Vec<_> = match (keyword.as_str(), value) {
+                ("properties", serde_json::Value::Object(properties)) => properties
+                    .iter()
+                    .filter_map(|(name, subschema)| {
+                        let value = instance.get(name)?;
+                        let instance_location = format!("{}/{}", instance_location, name);
+                        Some((format!("{}/{}", location, name), instance_location, subschema, value))
+                    })
+                    .collect(),
+                ("items", serde_json::Value::Object(_) | serde_json::Value::Bool(_)) => instance
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .enumerate()
+                    .map(|(index, item)| (location.clone(), format!("{}/{}", instance_location, index), value, item))
+                    .collect(),
+                ("allOf", serde_json::Value::Array(subschemas)) => subschemas
+                    .iter()
+                    .enumerate()
+                    .map(|(index, subschema)| {
+                        (format!("{}/{}", location, index), instance_location.clone(), subschema, instance)
+                    })
+                    .collect(),
+                _ => {
+                    children.push(Self::keyword_unit(keyword, value, location, &instance_location, errors, placed));
+                    continue;
+                }
+            };
+            let units: Vec<_> = subschemas
+                .into_iter()
+                .map(|(location, instance_location, subschema, value)| {
+                    Self::schema_unit(subschema, location, instance_location, value, errors, placed)
+                })
+                .collect();
+            children.push(Self {
+                valid: units.iter().all(|unit| unit.valid),
+                keyword_location: location,
+                instance_location: instance_location.clone(),
+                error: None,
+                annotation: None,
+                errors: units,
+            });
+        }
+
+        // 布尔Schema等直接落在子Schema位置上的错误
+        let own = take_errors(errors, placed, |e| {
+            e.schema_path == keyword_location && e.instance_path == instance_location
+        });
+        children.extend(own.into_iter().map(Self::leaf));
+
+        Self {
+            valid: children.iter().all(|unit| unit.valid),
+            keyword_location,
+            instance_location,
+            error: None,
+            annotation: None,
+            errors: children,
+        }
+    }
+
+    /// 关键字单元，关键字下（包括 `$ref` 等未展开的子Schema中）的错误作为其错误
+    fn keyword_unit(
+        keyword: &str,
+        value: &serde_json::Value,
+        keyword_location: String,
+        instance_location: &str,
+        errors: &[ValidationError],
+        placed: &mut [bool],
+    ) -> Self {
+        let matched = take_errors(errors, placed, |e| {
+            pointer_has_prefix(&e.schema_path, &keyword_location) && pointer_has_prefix(&e.instance_path, instance_location)
+        });
+
+        let mut unit = Self {
+            valid: matched.is_empty(),
+            keyword_location,
+            instance_location: instance_location.to_string(),
+            error: None,
+            annotation: None,
+            errors: Vec::new(),
+        };
+        match matched.as_slice() {
+            [] => {
+                if ANNOTATION_KEYWORDS.contains(&keyword) {
+                    unit.annotation = Some(value.clone());
+                }
+            }
+            [error] if error.schema_path == unit.keyword_location => unit.error = Some(error.message.clone()),
+            _ => unit.attach_errors(matched),
+        }
+        unit
+    }
+
+    /// 根据模式路径构建错误树
+    fn tree(location: String, errors: Vec<PathedError<'_>>, depth: usize, condense: bool) -> Self {
+        let instance_location = common_pointer_prefix(errors.iter().map(|(_, e)| e.instance_path.as_str()));
+
+        let mut children = Vec::new();
+        let mut groups: Vec<(&str, Vec<PathedError<'_>>)> = Vec::new();
+        for (segments, error) in errors {
+            match segments.get(depth).copied() {
+                None => children.push(Self::leaf(error)),
+                Some(segment) => match groups.iter_mut().find(|(s, _)| *s == segment) {
+                    Some((_, group)) => group.push((segments, error)),
+                    None => groups.push((segment, vec![(segments, error)])),
+                },
+            }
+        }
+        for (segment, group) in groups {
+            children.push(Self::tree(format!("{}/{}", location, segment), group, depth + 1, condense));
+        }
+
+        if condense && depth > 0 && children.len() == 1 {
+            return children.remove(0);
         }
+
+        Self {
+            valid: false,
+            keyword_location: location,
+            instance_location,
+            error: None,
+            annotation: None,
+            errors: children,
+        }
+    }
+}
+
+/// 按模式路径段拆分后的错误
+type PathedError<'a> = (Vec<&'a str>, &'a ValidationError);
+
+/// 注解关键字，`verbose` 输出中通过验证时返回其值
+const ANNOTATION_KEYWORDS: &[&str] =
+    &["title", "description", "default", "examples", "deprecated", "readOnly", "writeOnly", "$comment"];
+
+/// 不参与验证的关键字，`verbose` 输出中不生成单元
+const NON_ASSERTION_KEYWORDS: &[&str] = &["$schema", "$id", "$anchor", "$defs", "definitions"];
+
+/// 取出尚未挂到输出树上、且满足条件的错误
+fn take_errors<'a>(
+    errors: &'a [ValidationError],
+    placed: &mut [bool],
+    matches: impl Fn(&ValidationError) -> bool,
+) -> Vec<&'a ValidationError> {
+    let mut taken = Vec::new();
+    for (error, placed) in errors.iter().zip(placed.iter_mut()) {
+        if !*placed && matches(error) {
+            *placed = true;
+            taken.push(error);
+        }
+    }
+    taken
+}
+
+/// 按路径段判断JSON Pointer是否以 `prefix` 开头
+fn pointer_has_prefix(pointer: &str, prefix: &str) -> bool {
+    pointer
+        .strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// 多个JSON Pointer的最长公共前缀（按路径段）
+fn common_pointer_prefix<'a>(mut pointers: impl Iterator<Item = &'a str>) -> String {
+    let Some(first) = pointers.next() else {
+        return String::new();
+    };
+    let mut prefix: Vec<&str> = first.split('/').skip(1).collect();
+    for pointer in pointers {
+        let common = prefix
+            .iter()
+            .zip(pointer.split('/').skip(1))
+            .take_while(|(a, b)| *a == b)
+            .count();
+        prefix.truncate(common);
     }
+    prefix.iter().map(|segment| format!("/{}", segment)).collect()
 }
 
 /// 验证结果
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ValidationResult {
     /// 验证是否成功
     pub valid: bool,
@@ -266,6 +798,15 @@ pub struct ValidationResult {
     /// 缓存键（可选）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cache_key: Option<String>,
+    /// 修复建议，可直接应用到被验证文档上的 JSON Patch 操作，仅在请求 `suggest_fixes` 时返回
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixes: Vec<serde_json::Value>,
+    /// 规范化后的文档，仅在请求 `normalize` 且验证通过时返回
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalized: Option<serde_json::Value>,
+    /// `verbose` 输出树，仅在请求 `verbose` 输出格式时由Schema验证生成
+    #[serde(skip)]
+    pub verbose: Option<OutputUnit>,
 }
 
 impl ValidationResult {
@@ -278,6 +819,9 @@ impl ValidationResult {
             execution_time,
             cache_hit,
             cache_key: None,
+            fixes: Vec::new(),
+            normalized: None,
+            verbose: None,
         }
     }
     
@@ -290,12 +834,68 @@ impl ValidationResult {
             execution_time,
             cache_hit,
             cache_key: None,
+            fixes: Vec::new(),
+            normalized: None,
+            verbose: None,
+        }
+    }
+
+    /// 按标准输出格式生成根输出单元
+    ///
+    /// `verbose` 格式优先使用验证时按Schema结构生成的输出树，没有时（如未带Schema的验证）退回到错误树。
+    pub fn to_output(&self, format: OutputFormat) -> OutputUnit {
+        if let (OutputFormat::Verbose, Some(tree)) = (format, &self.verbose) {
+            return tree.clone();
+        }
+        let root = |errors| OutputUnit {
+            valid: self.valid,
+            keyword_location: String::new(),
+            instance_location: String::new(),
+            error: None,
+            annotation: None,
+            errors,
+        };
+
+        match format {
+            OutputFormat::Flag => root(Vec::new()),
+            OutputFormat::Basic => root(self.errors.iter().map(OutputUnit::leaf).collect()),
+            OutputFormat::Detailed | OutputFormat::Verbose if !self.valid => {
+                let errors = self
+                    .errors
+                    .iter()
+                    .map(|e| (e.schema_path.split('/').filter(|s| !s.is_empty()).collect(), e))
+                    .collect();
+                let mut tree = OutputUnit::tree(String::new(), errors, 0, format == OutputFormat::Detailed);
+                tree.instance_location = String::new();
+                tree
+            }
+            OutputFormat::Detailed | OutputFormat::Verbose => root(Vec::new()),
+        }
+    }
+
+    /// 序列化验证结果，指定输出格式时返回标准输出结构，除 `flag` 外附带修复建议和规范化文档
+    pub fn to_value(&self, format: Option<OutputFormat>) -> serde_json::Value {
+        match format {
+            Some(OutputFormat::Flag) => serde_json::json!({ "valid": self.valid }),
+            Some(format) => {
+                let mut value = serde_json::to_value(self.to_output(format)).unwrap_or_default();
+                if let Some(object) = value.as_object_mut() {
+                    if !self.fixes.is_empty() {
+                        object.insert("fixes".to_string(), serde_json::Value::from(self.fixes.clone()));
+                    }
+                    if let Some(normalized) = &self.normalized {
+                        object.insert("normalized".to_string(), normalized.clone());
+                    }
+                }
+                value
+            }
+            None => serde_json::to_value(self).unwrap_or_default(),
         }
     }
 }
 
 /// 验证错误
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ValidationError {
     /// 实例路径
     pub instance_path: String,
@@ -311,7 +911,7 @@ pub struct ValidationError {
 }
 
 /// 错误位置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ErrorLocation {
     /// 行号
     pub line: usize,
@@ -320,7 +920,7 @@ pub struct ErrorLocation {
 }
 
 /// 验证警告
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ValidationWarning {
     /// 警告消息
     pub message: String,
@@ -331,7 +931,7 @@ pub struct ValidationWarning {
 }
 
 /// 批量验证结果
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BatchValidationResult {
     /// 项目ID
     pub id: String,
@@ -462,6 +1062,25 @@ impl ApiErrorResponse {
     }
 }
 
+pub use problem_details::{
+    error_docs_url, parse_correlation_id, CORRELATION_ID_HEADER, PROBLEM_JSON_CONTENT_TYPE, TRACE_ID_HEADER,
+};
+
+/// RFC 7807 问题详情（REST端点统一错误格式）
+pub type ProblemDetails = problem_details::ProblemDetails;
+
+/// 创建问题详情，沿用当前请求的追踪ID，标题按当前请求的语言本地化
+pub fn request_problem(status: axum::http::StatusCode, code: &str, detail: String) -> ProblemDetails {
+    let locale = crate::middleware::request_locale();
+    let problem = ProblemDetails::new(status, code, detail)
+        .with_trace_id(crate::middleware::trace_id::request_trace_id())
+        .with_language(locale.tag());
+    match crate::i18n::lookup(locale, &format!("title.{}", code)) {
+        Some(title) => problem.with_title(title),
+        None => problem,
+    }
+}
+
 /// 应用状态
 #[derive(Clone)]
 pub struct AppState {
@@ -469,22 +1088,186 @@ pub struct AppState {
     pub validator_service: crate::services::JsonValidatorService,
     /// 服务器配置
     pub config: crate::config::ServerConfig,
+    /// JSON-RPC方法路由表
+    pub rpc_router: std::sync::Arc<crate::rpc::MethodRouter>,
+    /// Prometheus指标
+    pub prometheus: std::sync::Arc<crate::middleware::PrometheusMetrics>,
+    /// 命名Schema注册表
+    pub schema_registry: std::sync::Arc<crate::registry::SchemaRegistry>,
+    /// 后台重新验证任务
+    pub jobs: std::sync::Arc<crate::jobs::JobManager>,
+    /// 批量验证和重新验证任务的历史
+    pub job_history: std::sync::Arc<crate::job_history::JobHistory>,
+    /// 请求优先级通道
+    pub priority_lanes: std::sync::Arc<crate::middleware::PriorityLanes>,
+    /// JSON-RPC端点限流器
+    pub rate_limiter: std::sync::Arc<crate::middleware::RateLimiter>,
+    /// API密钥存储
+    pub api_keys: std::sync::Arc<crate::api_keys::ApiKeyStore>,
+    /// 内存自监控，未启用时为 `None`
+    pub memory_watchdog: Option<std::sync::Arc<memory_watchdog::MemoryWatchdog>>,
+    /// 启动预热状态
+    pub warmup: std::sync::Arc<crate::warmup::WarmupStatus>,
 }
 
 impl AppState {
     /// 创建新的应用状态
     pub fn new() -> Self {
-        Self {
-            validator_service: crate::services::JsonValidatorService::new(),
-            config: crate::config::ServerConfig::default(),
-        }
+        Self::with_config(crate::config::ServerConfig::default())
     }
 
     /// 使用配置创建新的应用状态
     pub fn with_config(config: crate::config::ServerConfig) -> Self {
+        let job_history = std::sync::Arc::new(crate::job_history::JobHistory::from_config(&config.cache.job_history));
+        let validation_pool = crate::validation_pool::ValidationPool::from_config(&config.performance.validation_pool);
+        let mut validator_service =
+            crate::services::JsonValidatorService::new().with_validation_pool(std::sync::Arc::new(validation_pool));
+        if config.cache.enabled {
+            validator_service = validator_service
+                .with_result_cache(config.cache.max_size, std::time::Duration::from_secs(config.cache.ttl));
+        }
         Self {
-            validator_service: crate::services::JsonValidatorService::new(),
+            validator_service,
+            rpc_router: std::sync::Arc::new(crate::rpc::MethodRouter::from_config(&config.rpc)),
+            prometheus: std::sync::Arc::new(crate::middleware::PrometheusMetrics::new()),
+            schema_registry: crate::registry::SchemaRegistry::from_config(&config.schema_registry),
+            jobs: std::sync::Arc::new(crate::jobs::JobManager::new(config.jobs.clone()).with_history(job_history.clone())),
+            job_history,
+            priority_lanes: std::sync::Arc::new(crate::middleware::PriorityLanes::from_config(
+                &config.performance.priority_lanes,
+            )),
+            rate_limiter: std::sync::Arc::new(crate::middleware::RateLimiter::from_config(&config.security)),
+            api_keys: std::sync::Arc::new(
+                crate::api_keys::ApiKeyStore::from_config(&config.security).unwrap_or_else(|e| {
+                    // 存储文件损坏时不覆盖它，本次运行签发的密钥只保存在内存中
+                    tracing::error!("Failed to load API key store, falling back to in-memory keys: {}", e);
+                    crate::api_keys::ApiKeyStore::in_memory(&config.security)
+                }),
+            ),
+            warmup: std::sync::Arc::new(crate::warmup::WarmupStatus::new(!config.warmup.enabled)),
+            memory_watchdog: config.monitoring.memory_watchdog.enabled.then(|| {
+                std::sync::Arc::new(memory_watchdog::MemoryWatchdog::new(config.monitoring.memory_watchdog.limits()))
+            }),
             config,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(schema_path: &str, instance_path: &str) -> ValidationError {
+        ValidationError {
+            instance_path: instance_path.to_string(),
+            schema_path: schema_path.to_string(),
+            message: format!("failed at {}", schema_path),
+            error_code: "SCHEMA_VALIDATION_ERROR".to_string(),
+            location: None,
+        }
+    }
+
+    #[test]
+    fn test_flag_and_basic_output() {
+        let result = ValidationResult::failure(
+            vec![error("/properties/age/type", "/age"), error("/required", "")],
+            1,
+            false,
+        );
+
+        assert_eq!(result.to_value(Some(OutputFormat::Flag)), serde_json::json!({ "valid": false }));
+
+        let basic = result.to_value(Some(OutputFormat::Basic));
+        assert_eq!(basic["errors"].as_array().unwrap().len(), 2);
+        assert_eq!(basic["errors"][0]["keywordLocation"], "/properties/age/type");
+        assert_eq!(basic["errors"][0]["instanceLocation"], "/age");
+        assert!(result.to_value(None).get("execution_time").is_some());
+    }
+
+    #[test]
+    fn test_detailed_and_verbose_trees() {
+        let result = ValidationResult::failure(
+            vec![
+                error("/properties/address/properties/zip/type", "/address/zip"),
+                error("/properties/address/required", "/address"),
+            ],
+            1,
+            false,
+        );
+
+        let detailed = result.to_output(OutputFormat::Detailed);
+        let address = &detailed.errors[0];
+        assert_eq!(address.keyword_location, "/properties/address");
+        assert_eq!(address.instance_location, "/address");
+        assert_eq!(address.errors.len(), 2);
+        assert_eq!(address.errors[0].keyword_location, "/properties/address/properties/zip/type");
+
+        let verbose = result.to_output(OutputFormat::Verbose);
+        assert_eq!(verbose.errors[0].keyword_location, "/properties");
+        assert_eq!(verbose.errors[0].errors[0].keyword_location, "/properties/address");
+
+        let valid = ValidationResult::success(1, false).to_output(OutputFormat::Detailed);
+        assert!(valid.valid && valid.errors.is_empty());
+    }
+
+    #[test]
+    fn test_verbose_tree_from_schema() {
+        let schema = serde_json::json!({
+            "title": "User",
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "description": "Display name"},
+                "age": {"type": "integer", "minimum": 0}
+            },
+            "required": ["name"]
+        });
+        let instance = serde_json::json!({"name": "a", "age": -1});
+
+        let tree = OutputUnit::verbose(&schema, &instance, &[error("/properties/age/minimum", "/age")]);
+        assert!(!tree.valid);
+        let unit = |location: &str| {
+            fn find<'a>(unit: &'a OutputUnit, location: &str) -> Option<&'a OutputUnit> {
+                if unit.keyword_location == location && unit.errors.is_empty() {
+                    return Some(unit);
+                }
+                unit.errors.iter().find_map(|child| find(child, location))
+            }
+            find(&tree, location).cloned().unwrap()
+        };
+        assert_eq!(unit("/title").annotation, Some(serde_json::json!("User")));
+        assert!(unit("/type").valid);
+        assert_eq!(unit("/properties/name/description").instance_location, "/name");
+        assert!(unit("/properties/age/type").valid);
+        let minimum = unit("/properties/age/minimum");
+        assert!(!minimum.valid);
+        assert_eq!(minimum.error.as_deref(), Some("failed at /properties/age/minimum"));
+
+        // 通过验证时仍返回完整的树，对应不到Schema结构的错误挂在根单元下
+        let valid = OutputUnit::verbose(&schema, &serde_json::json!({"name": "a"}), &[]);
+        assert!(valid.valid);
+        assert_eq!(valid.errors.len(), 4);
+        let orphan = OutputUnit::verbose(&schema, &instance, &[error("", "")]);
+        assert!(!orphan.valid);
+        assert_eq!(orphan.errors.last().unwrap().error.as_deref(), Some("failed at "));
+
+        let mut result = ValidationResult::success(1, false);
+        result.verbose = Some(valid.clone());
+        assert_eq!(result.to_output(OutputFormat::Verbose), valid);
+    }
+
+    #[test]
+    fn test_combine_modes_and_schema_labels() {
+        assert_eq!(CombineMode::All.combine(&[true, false]), Some(false));
+        assert_eq!(CombineMode::Any.combine(&[true, false]), Some(true));
+        assert_eq!(CombineMode::Report.combine(&[true, false]), None);
+
+        let named = SchemaEntry { id: Some("v2".to_string()), schema: serde_json::json!({}) };
+        let referenced: SchemaEntry = serde_json::from_value(serde_json::json!({ "id": "user" })).unwrap();
+        assert!(referenced.schema.is_null());
+        let with_id = SchemaEntry { id: None, schema: serde_json::json!({ "$id": "https://example.com/user.v1" }) };
+        let anonymous = SchemaEntry { id: None, schema: serde_json::json!({}) };
+        assert_eq!(named.label(0), "v2");
+        assert_eq!(with_id.label(1), "https://example.com/user.v1");
+        assert_eq!(anonymous.label(2), "2");
+    }
+}
@@ -0,0 +1,109 @@
+//! 验证并规范化
+//!
+//! 请求设置 `normalize` 且Schema验证通过时，按Schema把文档整理为规范形式随结果返回，
+//! 下游系统拿到的同一份数据总是相同的写法：
+//!
+//! - 缺少的属性按模式中的 `default` 补上
+//! - 整数值的浮点数写为整数（`5.0` → `5`，`1e2` → `100`）
+//! - `format: date-time` 的字符串转换为UTC的RFC3339格式（`2025-01-01T08:00:00+08:00` → `2025-01-01T00:00:00Z`）
+//! - 对象的键按字典序排列（`serde_json::Map` 未启用 `preserve_order`，序列化时总是有序）
+//!
+//! 只沿 `properties`、`additionalProperties` 和 `items` 递归，`$ref`、`allOf` 等组合关键字下的子模式不参与。
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::Value;
+
+/// 浮点数能精确表示的最大整数
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// 按模式就地规范化
+pub fn normalize(schema: &Value, value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            if let Some(properties) = properties {
+                for (key, property) in properties {
+                    if !map.contains_key(key) {
+                        if let Some(default) = property.get("default") {
+                            map.insert(key.clone(), default.clone());
+                        }
+                    }
+                }
+            }
+            let additional = schema.get("additionalProperties").filter(|s| s.is_object());
+            for (key, child) in map.iter_mut() {
+                match properties.and_then(|p| p.get(key)).or(additional) {
+                    Some(child_schema) => normalize(child_schema, child),
+                    None => normalize(&Value::Null, child),
+                }
+            }
+        }
+        Value::Array(items) => {
+            let item_schema = schema.get("items").filter(|s| s.is_object()).unwrap_or(&Value::Null);
+            for item in items {
+                normalize(item_schema, item);
+            }
+        }
+        Value::Number(number) => {
+            if let Some(f) = number.as_f64().filter(|_| number.is_f64()) {
+                if f.fract() == 0.0 && f.abs() <= MAX_SAFE_INTEGER {
+                    *value = Value::from(f as i64);
+                }
+            }
+        }
+        Value::String(text) => {
+            if schema.get("format").and_then(Value::as_str) == Some("date-time") {
+                if let Ok(parsed) = DateTime::parse_from_rfc3339(text) {
+                    *text = parsed.with_timezone(&Utc).to_rfc3339_opts(SecondsFormat::AutoSi, true);
+                }
+            }
+        }
+        Value::Null | Value::Bool(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalize() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "env": {"type": "string", "default": "staging"},
+                "replicas": {"type": "integer"},
+                "deadline": {"type": "string", "format": "date-time"},
+                "steps": {"type": "array", "items": {"type": "object", "properties": {"at": {"format": "date-time"}}}}
+            },
+            "additionalProperties": {"type": "number"}
+        });
+        let mut value = json!({
+            "replicas": 3.0,
+            "deadline": "2025-01-01T08:00:00.500+08:00",
+            "steps": [{"at": "2025-06-01T12:00:00-02:00"}],
+            "ratio": 1e2,
+            "weight": 0.25
+        });
+        normalize(&schema, &mut value);
+
+        assert_eq!(value, json!({
+            "env": "staging",
+            "replicas": 3,
+            "deadline": "2025-01-01T00:00:00.500Z",
+            "steps": [{"at": "2025-06-01T14:00:00Z"}],
+            "ratio": 100,
+            "weight": 0.25
+        }));
+        assert_eq!(serde_json::to_string(&value["replicas"]).unwrap(), "3");
+    }
+
+    #[test]
+    fn test_leaves_unparseable_values() {
+        let schema = json!({"properties": {"deadline": {"format": "date-time"}, "huge": {"type": "number"}}});
+        let mut value = json!({"deadline": "next tuesday", "huge": 1e300});
+        normalize(&schema, &mut value);
+        assert_eq!(value, json!({"deadline": "next tuesday", "huge": 1e300}));
+    }
+}
@@ -10,7 +10,7 @@ use serde_json::Value;
 use anyhow::Result;
 
 /// 性能优化配置
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct PerformanceConfig {
     /// 并发限制
     pub max_concurrent_requests: usize,
@@ -0,0 +1,354 @@
+//! 命名Schema注册表
+//!
+//! Schema可以通过代码注册，也可以从目录中的 `<name>.schema.json` 文件加载。
+//! 每个Schema可以额外登记一个候选版本（文件名为 `<name>.candidate.schema.json`），
+//! 候选版本不影响验证结果，只用于影子验证以评估Schema升级的影响。
+//! 开启监听后，目录中的文件新增、修改或删除会实时同步到注册表，
+//! 无法解析或编译的文件只记录警告，保留注册表中的旧版本。
+//!
+//! Schema也可以保存在S3兼容对象存储中，按相同的文件名规则从指定前缀下加载并定期重新同步。
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use object_storage::{ObjectStore, S3ObjectStore, S3Settings};
+use parking_lot::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::config::{SchemaObjectStorageConfig, SchemaRegistryConfig};
+
+/// Schema文件后缀
+pub const SCHEMA_FILE_SUFFIX: &str = ".schema.json";
+
+/// 候选版本Schema文件名中的标记
+const CANDIDATE_MARKER: &str = ".candidate";
+
+/// 命名Schema注册表
+#[derive(Default)]
+pub struct SchemaRegistry {
+    schemas: RwLock<HashMap<String, Arc<serde_json::Value>>>,
+    candidates: RwLock<HashMap<String, Arc<serde_json::Value>>>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    /// 上次从对象存储同步到的Schema（名称，是否候选版本）
+    remote: Mutex<HashSet<(String, bool)>>,
+}
+
+impl SchemaRegistry {
+    /// 创建空注册表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 根据配置创建注册表，加载并按需监听Schema目录；目录不可用时记录警告并返回空注册表
+    pub fn from_config(config: &SchemaRegistryConfig) -> Arc<Self> {
+        let registry = Arc::new(Self::new());
+        if let Some(directory) = &config.directory {
+            match registry.load_dir(directory) {
+                Ok(count) => info!("Loaded {} schema(s) from {}", count, directory.display()),
+                Err(e) => warn!("Failed to load schema directory {}: {}", directory.display(), e),
+            }
+            if config.watch {
+                if let Err(e) = registry.watch_dir(directory) {
+                    warn!("Failed to watch schema directory {}: {}", directory.display(), e);
+                }
+            }
+        }
+        if config.object_storage.enabled {
+            if let Err(e) = registry.start_object_storage(&config.object_storage) {
+                warn!("Failed to load schemas from object storage: {}", e);
+            }
+        }
+        registry
+    }
+
+    /// 按配置连接对象存储并在后台同步
+    fn start_object_storage(self: &Arc<Self>, config: &SchemaObjectStorageConfig) -> Result<(), String> {
+        let store = S3ObjectStore::new(S3Settings {
+            endpoint: config.endpoint.clone(),
+            bucket: config.bucket.clone(),
+            region: config.region.clone(),
+            access_key_id: config.access_key_id.clone(),
+            secret_access_key: config.secret_access_key.clone(),
+            path_style: config.path_style,
+            // 注册表只读取对象，不会上传
+            multipart_threshold: usize::MAX,
+            part_size: 0,
+        })
+        .map_err(|e| e.to_string())?;
+        if tokio::runtime::Handle::try_current().is_err() {
+            return Err("no async runtime available".to_string());
+        }
+        self.start_object_store_sync(
+            Arc::new(store),
+            config.prefix.clone(),
+            Duration::from_secs(config.refresh_interval),
+        );
+        Ok(())
+    }
+
+    /// 注册Schema，Schema无法编译时返回错误
+    pub fn register(&self, name: impl Into<String>, schema: serde_json::Value) -> Result<(), String> {
+        let name = name.into();
+        jsonschema::JSONSchema::compile(&schema)
+            .map_err(|e| format!("Invalid schema '{}': {}", name, e))?;
+        self.schemas.write().insert(name, Arc::new(schema));
+        Ok(())
+    }
+
+    /// 移除Schema及其候选版本
+    pub fn remove(&self, name: &str) -> bool {
+        self.candidates.write().remove(name);
+        self.schemas.write().remove(name).is_some()
+    }
+
+    /// 登记候选版本，Schema无法编译时返回错误
+    pub fn set_candidate(&self, name: impl Into<String>, schema: serde_json::Value) -> Result<(), String> {
+        let name = name.into();
+        jsonschema::JSONSchema::compile(&schema)
+            .map_err(|e| format!("Invalid candidate schema '{}': {}", name, e))?;
+        self.candidates.write().insert(name, Arc::new(schema));
+        Ok(())
+    }
+
+    /// 移除候选版本
+    pub fn clear_candidate(&self, name: &str) -> bool {
+        self.candidates.write().remove(name).is_some()
+    }
+
+    /// 查找候选版本
+    pub fn candidate(&self, name: &str) -> Option<Arc<serde_json::Value>> {
+        self.candidates.read().get(name).cloned()
+    }
+
+    /// 按名称查找Schema
+    pub fn get(&self, name: &str) -> Option<Arc<serde_json::Value>> {
+        self.schemas.read().get(name).cloned()
+    }
+
+    /// 已注册的Schema名称（按字母排序）
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.schemas.read().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// 加载目录中的全部Schema文件，返回成功加载的数量；单个文件失败只记录警告
+    pub fn load_dir(&self, directory: &Path) -> Result<usize, String> {
+        let entries = std::fs::read_dir(directory).map_err(|e| e.to_string())?;
+        let mut loaded = 0;
+        for entry in entries {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            if schema_file(&path).is_none() {
+                continue;
+            }
+            match self.load_file(&path) {
+                Ok(_) => loaded += 1,
+                Err(e) => warn!("Skipping schema file {}: {}", path.display(), e),
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// 加载单个Schema文件（当前版本或候选版本），返回对应的Schema名称
+    pub fn load_file(&self, path: &Path) -> Result<String, String> {
+        let (name, is_candidate) = schema_file(path)
+            .ok_or_else(|| format!("File name must end with {}", SCHEMA_FILE_SUFFIX))?;
+        let content = std::fs::read(path).map_err(|e| e.to_string())?;
+        self.load_bytes(&name, is_candidate, &content)?;
+        Ok(name)
+    }
+
+    /// 解析并登记一份Schema内容
+    fn load_bytes(&self, name: &str, is_candidate: bool, content: &[u8]) -> Result<(), String> {
+        let schema: serde_json::Value = serde_json::from_slice(content).map_err(|e| e.to_string())?;
+        if is_candidate {
+            self.set_candidate(name, schema)
+        } else {
+            self.register(name, schema)
+        }
+    }
+
+    /// 从对象存储同步前缀下的全部Schema，返回成功加载的数量
+    ///
+    /// 无法解析或编译的对象保留旧版本并记录警告；上次同步存在、本次已删除的对象对应的Schema会被移除。
+    pub async fn sync_object_store(&self, store: &dyn ObjectStore, prefix: &str) -> Result<usize, String> {
+        let keys = store.list_objects(prefix).await.map_err(|e| e.to_string())?;
+        let mut seen = HashSet::new();
+        let mut loaded = 0;
+        for key in keys {
+            let Some((name, is_candidate)) = schema_file(Path::new(&key)) else {
+                continue;
+            };
+            seen.insert((name.clone(), is_candidate));
+            let result = match store.get_object(&key).await {
+                Ok(Some(content)) => self.load_bytes(&name, is_candidate, &content),
+                Ok(None) => Err("object disappeared during sync".to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            match result {
+                Ok(()) => loaded += 1,
+                Err(e) => warn!("Keeping previous schema '{}' from object {}: {}", name, key, e),
+            }
+        }
+
+        let removed: Vec<(String, bool)> = self.remote.lock().difference(&seen).cloned().collect();
+        for (name, is_candidate) in removed {
+            if is_candidate {
+                self.clear_candidate(&name);
+                info!("Candidate schema '{}' removed from object storage", name);
+            } else {
+                self.schemas.write().remove(&name);
+                info!("Schema '{}' removed from object storage", name);
+            }
+        }
+        *self.remote.lock() = seen;
+        Ok(loaded)
+    }
+
+    /// 启动后台同步：立即同步一次，之后按间隔重新同步；间隔为0时只同步一次，注册表释放后自动退出
+    pub fn start_object_store_sync(
+        self: &Arc<Self>,
+        store: Arc<dyn ObjectStore>,
+        prefix: String,
+        interval: Duration,
+    ) -> JoinHandle<()> {
+        let registry = Arc::downgrade(self);
+        tokio::spawn(async move {
+            loop {
+                let Some(current) = registry.upgrade() else { break };
+                match current.sync_object_store(store.as_ref(), &prefix).await {
+                    Ok(count) => info!("Synced {} schema(s) from object storage prefix '{}'", count, prefix),
+                    Err(e) => warn!("Failed to sync schemas from object storage: {}", e),
+                }
+                drop(current);
+                if interval.is_zero() {
+                    break;
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+
+    /// 监听目录变化：文件新增或修改时重新加载，删除时移除对应Schema
+    pub fn watch_dir(self: &Arc<Self>, directory: &Path) -> Result<(), String> {
+        let registry = Arc::downgrade(self);
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Some(registry) = registry.upgrade() else {
+                return;
+            };
+            match event {
+                Ok(event) => {
+                    for path in &event.paths {
+                        registry.sync_path(path);
+                    }
+                }
+                Err(e) => warn!("Schema directory watch error: {}", e),
+            }
+        })
+        .map_err(|e| e.to_string())?;
+        watcher
+            .watch(directory, RecursiveMode::NonRecursive)
+            .map_err(|e| e.to_string())?;
+
+        *self.watcher.lock() = Some(watcher);
+        info!("Watching schema directory {}", directory.display());
+        Ok(())
+    }
+
+    /// 按文件当前状态同步单个路径
+    fn sync_path(&self, path: &Path) {
+        let Some((name, is_candidate)) = schema_file(path) else {
+            return;
+        };
+        if !path.exists() {
+            if is_candidate && self.clear_candidate(&name) {
+                info!("Candidate schema '{}' removed", name);
+            } else if !is_candidate && self.schemas.write().remove(&name).is_some() {
+                info!("Schema '{}' removed", name);
+            }
+            return;
+        }
+        match self.load_file(path) {
+            Ok(name) => info!("Schema '{}' reloaded from {}", name, path.display()),
+            Err(e) => warn!("Keeping previous schema '{}': {}", name, e),
+        }
+    }
+}
+
+/// 从文件名中取出Schema名称以及是否为候选版本，非Schema文件返回 `None`
+fn schema_file(path: &Path) -> Option<(String, bool)> {
+    let stem = path.file_name()?.to_str()?.strip_suffix(SCHEMA_FILE_SUFFIX)?;
+    let (name, is_candidate) = match stem.strip_suffix(CANDIDATE_MARKER) {
+        Some(name) => (name, true),
+        None => (stem, false),
+    };
+    (!name.is_empty()).then(|| (name.to_string(), is_candidate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_dir_and_sync() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("user.schema.json"), r#"{"type":"object"}"#).unwrap();
+        std::fs::write(dir.path().join("broken.schema.json"), "{").unwrap();
+        std::fs::write(dir.path().join("notes.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("user.candidate.schema.json"), r#"{"type":"array"}"#).unwrap();
+
+        let registry = SchemaRegistry::new();
+        assert_eq!(registry.load_dir(dir.path()).unwrap(), 2);
+        assert_eq!(registry.names(), vec!["user".to_string()]);
+        assert_eq!(registry.candidate("user").unwrap()["type"], "array");
+
+        let candidate = dir.path().join("user.candidate.schema.json");
+        std::fs::remove_file(&candidate).unwrap();
+        registry.sync_path(&candidate);
+        assert!(registry.candidate("user").is_none());
+        assert!(registry.get("user").is_some());
+
+        // 修改为无效内容时保留旧版本
+        let path = dir.path().join("user.schema.json");
+        std::fs::write(&path, r#"{"type":"no-such-type"}"#).unwrap();
+        registry.sync_path(&path);
+        assert_eq!(registry.get("user").unwrap()["type"], "object");
+
+        std::fs::remove_file(&path).unwrap();
+        registry.sync_path(&path);
+        assert!(registry.get("user").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sync_object_store() {
+        let store = object_storage::MemoryObjectStore::new();
+        for (key, content) in [
+            ("schemas/user.schema.json", r#"{"type":"object"}"#),
+            ("schemas/user.candidate.schema.json", r#"{"type":"array"}"#),
+            ("schemas/readme.md", "ignored"),
+            ("other/order.schema.json", r#"{"type":"object"}"#),
+        ] {
+            store.put_object(key, content.as_bytes().to_vec(), "application/json").await.unwrap();
+        }
+
+        let registry = SchemaRegistry::new();
+        assert_eq!(registry.sync_object_store(&store, "schemas/").await.unwrap(), 2);
+        assert_eq!(registry.names(), vec!["user".to_string()]);
+        assert_eq!(registry.candidate("user").unwrap()["type"], "array");
+
+        // 无效内容保留旧版本，删除的对象随之移除
+        store.put_object("schemas/user.schema.json", b"{".to_vec(), "application/json").await.unwrap();
+        store.delete_object("schemas/user.candidate.schema.json").await.unwrap();
+        assert_eq!(registry.sync_object_store(&store, "schemas/").await.unwrap(), 0);
+        assert_eq!(registry.get("user").unwrap()["type"], "object");
+        assert!(registry.candidate("user").is_none());
+
+        store.delete_object("schemas/user.schema.json").await.unwrap();
+        registry.sync_object_store(&store, "schemas/").await.unwrap();
+        assert!(registry.get("user").is_none());
+    }
+}
@@ -0,0 +1,278 @@
+//! JSON-RPC方法路由表
+//!
+//! 同时支持MCP标准的 `tools/call` 信封调用和旧版直接方法调用（如 `validate_json`），
+//! 并根据配置启用/禁用单个方法以及解析方法别名。
+//!
+//! `tools/list` 返回的输入/输出Schema由工具的参数和结果类型通过 `schemars` 派生，
+//! 修改请求或结果结构体时Schema随之更新，不需要手写维护。
+
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::config::RpcConfig;
+use crate::models::{
+    BatchValidationResponse, MultiValidationResult, ValidateJsonBatchRequest, ValidateJsonRequest,
+    ValidateJsonWithSchemaRequest, ValidateMultiRequest, ValidateWithProfileRequest, ValidationResult,
+};
+
+/// JSON-RPC方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RpcMethod {
+    /// MCP标准工具调用信封
+    ToolsCall,
+    /// MCP标准工具列表
+    ToolsList,
+    /// 心跳
+    Ping,
+    /// 验证JSON
+    ValidateJson,
+    /// 使用Schema验证JSON
+    ValidateJsonWithSchema,
+    /// 批量验证JSON
+    ValidateJsonBatch,
+    /// 使用多个Schema验证JSON
+    ValidateMulti,
+    /// 按验证配置档验证JSON
+    ValidateWithProfile,
+}
+
+impl RpcMethod {
+    /// 全部方法
+    pub const ALL: [RpcMethod; 8] = [
+        RpcMethod::ToolsCall,
+        RpcMethod::ToolsList,
+        RpcMethod::Ping,
+        RpcMethod::ValidateJson,
+        RpcMethod::ValidateJsonWithSchema,
+        RpcMethod::ValidateJsonBatch,
+        RpcMethod::ValidateMulti,
+        RpcMethod::ValidateWithProfile,
+    ];
+
+    /// 规范方法名
+    pub fn name(&self) -> &'static str {
+        match self {
+            RpcMethod::ToolsCall => "tools/call",
+            RpcMethod::ToolsList => "tools/list",
+            RpcMethod::Ping => "ping",
+            RpcMethod::ValidateJson => "validate_json",
+            RpcMethod::ValidateJsonWithSchema => "validate_json_with_schema",
+            RpcMethod::ValidateJsonBatch => "validate_json_batch",
+            RpcMethod::ValidateMulti => "validate_multi",
+            RpcMethod::ValidateWithProfile => "validate_with_profile",
+        }
+    }
+
+    /// 是否为可通过 `tools/call` 调用的工具
+    pub fn is_tool(&self) -> bool {
+        matches!(
+            self,
+            RpcMethod::ValidateJson
+                | RpcMethod::ValidateJsonWithSchema
+                | RpcMethod::ValidateJsonBatch
+                | RpcMethod::ValidateMulti
+                | RpcMethod::ValidateWithProfile
+        )
+    }
+
+    /// 工具说明
+    pub fn description(&self) -> &'static str {
+        match self {
+            RpcMethod::ToolsCall => "Call a tool",
+            RpcMethod::ToolsList => "List available tools",
+            RpcMethod::Ping => "Check that the server is alive",
+            RpcMethod::ValidateJson => "Validate that a JSON value is well-formed",
+            RpcMethod::ValidateJsonWithSchema => "Validate a JSON value against an inline or registered JSON Schema",
+            RpcMethod::ValidateJsonBatch => "Validate multiple JSON values, each with an optional schema",
+            RpcMethod::ValidateMulti => "Validate a JSON value against several schemas and combine the results",
+            RpcMethod::ValidateWithProfile => "Validate a JSON value with a configured validation profile",
+        }
+    }
+
+    /// 工具的参数和结果Schema，非工具方法返回 `None`
+    ///
+    /// 结果Schema描述未指定 `options.output` 时的默认结果结构。
+    pub fn schemas(&self) -> Option<(serde_json::Value, serde_json::Value)> {
+        match self {
+            RpcMethod::ValidateJson => Some(schemas::<ValidateJsonRequest, ValidationResult>()),
+            RpcMethod::ValidateJsonWithSchema => Some(schemas::<ValidateJsonWithSchemaRequest, ValidationResult>()),
+            RpcMethod::ValidateJsonBatch => Some(schemas::<ValidateJsonBatchRequest, BatchValidationResponse>()),
+            RpcMethod::ValidateMulti => Some(schemas::<ValidateMultiRequest, MultiValidationResult>()),
+            RpcMethod::ValidateWithProfile => Some(schemas::<ValidateWithProfileRequest, ValidationResult>()),
+            RpcMethod::ToolsCall | RpcMethod::ToolsList | RpcMethod::Ping => None,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|m| m.name() == name)
+    }
+}
+
+fn schemas<Input: JsonSchema, Output: JsonSchema>() -> (serde_json::Value, serde_json::Value) {
+    (
+        schemars::schema_for!(Input).to_value(),
+        schemars::schema_for!(Output).to_value(),
+    )
+}
+
+/// `tools/list` 中的工具描述
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolDefinition {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub input_schema: serde_json::Value,
+    pub output_schema: serde_json::Value,
+}
+
+/// 方法路由表
+#[derive(Debug, Clone)]
+pub struct MethodRouter {
+    /// 顶层方法名（含别名）到方法的映射
+    methods: HashMap<String, RpcMethod>,
+    /// 工具名（含别名）到方法的映射
+    tools: HashMap<String, RpcMethod>,
+}
+
+impl MethodRouter {
+    /// 根据配置构建路由表
+    pub fn from_config(config: &RpcConfig) -> Self {
+        let enabled = |method: &RpcMethod| !config.disabled_methods.iter().any(|m| m == method.name());
+
+        let mut methods = HashMap::new();
+        let mut tools = HashMap::new();
+        for method in RpcMethod::ALL.iter().filter(|m| enabled(m)) {
+            if method.is_tool() {
+                tools.insert(method.name().to_string(), *method);
+                if config.enable_direct_methods {
+                    methods.insert(method.name().to_string(), *method);
+                }
+            } else if !matches!(method, RpcMethod::ToolsCall | RpcMethod::ToolsList) || config.enable_tools_call {
+                methods.insert(method.name().to_string(), *method);
+            }
+        }
+
+        for (alias, target) in &config.aliases {
+            match RpcMethod::from_name(target) {
+                Some(method) if enabled(&method) => {
+                    if method.is_tool() {
+                        tools.insert(alias.clone(), method);
+                    }
+                    if !method.is_tool() || config.enable_direct_methods {
+                        methods.insert(alias.clone(), method);
+                    }
+                }
+                Some(_) => {}
+                None => tracing::warn!("Ignoring RPC alias '{}' for unknown method '{}'", alias, target),
+            }
+        }
+
+        Self { methods, tools }
+    }
+
+    /// 解析顶层JSON-RPC方法
+    pub fn resolve(&self, method: &str) -> Option<RpcMethod> {
+        self.methods.get(method).copied()
+    }
+
+    /// 解析 `tools/call` 中的工具名
+    pub fn resolve_tool(&self, name: &str) -> Option<RpcMethod> {
+        self.tools.get(name).copied()
+    }
+
+    /// 已启用工具的规范名称
+    pub fn enabled_tools(&self) -> Vec<&'static str> {
+        RpcMethod::ALL
+            .iter()
+            .filter(|m| m.is_tool() && self.tools.get(m.name()) == Some(m))
+            .map(|m| m.name())
+            .collect()
+    }
+
+    /// 已启用工具的描述和Schema
+    pub fn tool_definitions(&self) -> Vec<ToolDefinition> {
+        RpcMethod::ALL
+            .iter()
+            .filter(|m| self.tools.get(m.name()) == Some(m))
+            .filter_map(|m| {
+                let (input_schema, output_schema) = m.schemas()?;
+                Some(ToolDefinition {
+                    name: m.name(),
+                    description: m.description(),
+                    input_schema,
+                    output_schema,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Default for MethodRouter {
+    fn default() -> Self {
+        Self::from_config(&RpcConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_routes_accept_both_styles() {
+        let router = MethodRouter::default();
+        assert_eq!(router.resolve("tools/call"), Some(RpcMethod::ToolsCall));
+        assert_eq!(router.resolve("validate_json"), Some(RpcMethod::ValidateJson));
+        assert_eq!(router.resolve_tool("validate_json_batch"), Some(RpcMethod::ValidateJsonBatch));
+        assert_eq!(router.resolve("validate_multi"), Some(RpcMethod::ValidateMulti));
+        assert_eq!(router.resolve_tool("validate_with_profile"), Some(RpcMethod::ValidateWithProfile));
+        assert_eq!(router.resolve_tool("ping"), None);
+        assert_eq!(router.resolve("tools/list"), Some(RpcMethod::ToolsList));
+    }
+
+    #[test]
+    fn test_tool_definitions_use_generated_schemas() {
+        let config = RpcConfig {
+            disabled_methods: vec!["validate_multi".to_string()],
+            ..RpcConfig::default()
+        };
+        let tools = MethodRouter::from_config(&config).tool_definitions();
+        let names: Vec<_> = tools.iter().map(|t| t.name).collect();
+        assert_eq!(
+            names,
+            ["validate_json", "validate_json_with_schema", "validate_json_batch", "validate_with_profile"]
+        );
+
+        let with_schema = &tools[1];
+        assert_eq!(with_schema.input_schema["type"], "object");
+        assert_eq!(with_schema.input_schema["required"], serde_json::json!(["json_data"]));
+        assert!(with_schema.input_schema["properties"]["schema_ref"].is_object());
+        assert!(with_schema.output_schema["properties"]["cache_hit"].is_object());
+
+        let batch = serde_json::to_value(&tools[2]).unwrap();
+        assert_eq!(batch["inputSchema"]["required"], serde_json::json!(["items"]));
+        assert!(batch["outputSchema"]["properties"]["summary"].is_object());
+    }
+
+    #[test]
+    fn test_disabled_methods_and_aliases() {
+        let config = RpcConfig {
+            enable_tools_call: true,
+            enable_direct_methods: false,
+            disabled_methods: vec!["validate_json_batch".to_string()],
+            aliases: HashMap::from([
+                ("validateJson".to_string(), "validate_json".to_string()),
+                ("batch".to_string(), "validate_json_batch".to_string()),
+            ]),
+        };
+        let router = MethodRouter::from_config(&config);
+
+        assert_eq!(router.resolve("validate_json"), None);
+        assert_eq!(router.resolve("validateJson"), None);
+        assert_eq!(router.resolve_tool("validateJson"), Some(RpcMethod::ValidateJson));
+        assert_eq!(router.resolve_tool("validate_json_batch"), None);
+        assert_eq!(router.resolve_tool("batch"), None);
+        assert_eq!(router.resolve("ping"), Some(RpcMethod::Ping));
+    }
+}
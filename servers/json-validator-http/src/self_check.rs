@@ -0,0 +1,173 @@
+//! 启动自检
+//!
+//! `json-validator-http --check` 加载配置后依次校验配置、TLS证书、Schema目录以及
+//! 已启用的数据库和Redis连接，打印报告后退出，任一项失败时退出码非零。
+//! 本服务没有数据库迁移和外部执行器，因此不包含这两类检查。
+
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+
+use crate::config::ServerConfig;
+use crate::registry::SchemaRegistry;
+use crate::tls::TlsConfig;
+
+/// 单项检查结果
+#[derive(Debug, Clone)]
+pub struct CheckItem {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// 自检报告
+#[derive(Debug, Clone, Default)]
+pub struct CheckReport {
+    pub items: Vec<CheckItem>,
+}
+
+impl CheckReport {
+    /// 记录一项检查，成功时使用返回的说明
+    pub fn record(&mut self, name: &str, result: Result<String>) {
+        let (passed, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(e) => (false, format!("{:#}", e)),
+        };
+        self.items.push(CheckItem { name: name.to_string(), passed, detail });
+    }
+
+    /// 没有失败项时为真
+    pub fn passed(&self) -> bool {
+        self.items.iter().all(|item| item.passed)
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.items.iter().map(|item| item.name.len()).max().unwrap_or(0);
+        for item in &self.items {
+            let status = if item.passed { "PASS" } else { "FAIL" };
+            writeln!(f, "[{}] {:<width$}  {}", status, item.name, item.detail, width = width)?;
+        }
+        let failed = self.items.iter().filter(|item| !item.passed).count();
+        if failed == 0 {
+            write!(f, "Self-check passed ({} checks)", self.items.len())
+        } else {
+            write!(f, "Self-check failed: {} of {} checks failed", failed, self.items.len())
+        }
+    }
+}
+
+/// 对已加载的配置执行自检
+pub async fn run_self_check(config: &ServerConfig) -> CheckReport {
+    let mut report = CheckReport::default();
+
+    report.record("config", config.validate().map(|_| {
+        format!("environment {}, listen {}", config.deployment.environment, config.listen_address())
+    }));
+
+    if config.server.https_enabled {
+        report.record("tls", check_tls(config));
+    }
+
+    if let Some(directory) = &config.schema_registry.directory {
+        report.record("schema_registry", check_schema_directory(directory));
+    }
+
+    if config.database.enabled {
+        report.record("database", check_database(config).await);
+    }
+
+    if config.redis.enabled {
+        report.record("redis", check_redis(config).await);
+    }
+
+    report
+}
+
+/// 校验证书文件存在且能被解析
+fn check_tls(config: &ServerConfig) -> Result<String> {
+    let client_ca = config.server.client_auth_required.then(|| config.server.client_ca_path.clone());
+    let tls = TlsConfig::new(config.server.cert_path.clone(), config.server.key_path.clone())
+        .with_client_auth(config.server.client_auth_required, client_ca);
+    tls.validate()?;
+    let certificates = tls.load_certificates()?;
+    tls.load_private_key()?;
+    Ok(format!("{} certificate(s) loaded from {}", certificates.len(), config.server.cert_path))
+}
+
+/// 加载Schema目录，目录不可读时失败
+fn check_schema_directory(directory: &Path) -> Result<String> {
+    let registry = SchemaRegistry::new();
+    let loaded = registry
+        .load_dir(directory)
+        .map_err(|e| anyhow!("Failed to read {}: {}", directory.display(), e))?;
+    Ok(format!("{} schema(s) loaded from {}", loaded, directory.display()))
+}
+
+#[cfg(feature = "postgres")]
+async fn check_database(config: &ServerConfig) -> Result<String> {
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(Duration::from_secs(config.database.connection_timeout))
+        .connect(&config.database.url)
+        .await?;
+    sqlx::query("SELECT 1").execute(&pool).await?;
+    pool.close().await;
+    Ok("connected".to_string())
+}
+
+#[cfg(not(feature = "postgres"))]
+async fn check_database(_config: &ServerConfig) -> Result<String> {
+    Err(anyhow!("Database is enabled but this build does not include the `postgres` feature"))
+}
+
+async fn check_redis(config: &ServerConfig) -> Result<String> {
+    let client = redis::Client::open(config.redis.url.as_str())?;
+    let ping = async {
+        let mut connection = client.get_multiplexed_tokio_connection().await?;
+        redis::cmd("PING").query_async::<_, String>(&mut connection).await
+    };
+    let reply = tokio::time::timeout(Duration::from_secs(config.redis.connection_timeout), ping)
+        .await
+        .map_err(|_| anyhow!("Timed out after {}s", config.redis.connection_timeout))??;
+    Ok(format!("PING replied {}", reply))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_self_check_report() {
+        let mut config = ServerConfig::default();
+        let report = run_self_check(&config).await;
+        assert!(!report.passed(), "default config ships no static API keys");
+
+        let bootstrap = crate::config::ApiKeyConfig {
+            name: "Bootstrap".to_string(),
+            permissions: vec!["admin".to_string()],
+            rate_limit: None,
+        };
+        config.security.api_keys.insert("bootstrap-admin-key".to_string(), bootstrap.clone());
+        let report = run_self_check(&config).await;
+        assert!(report.passed(), "{}", report);
+
+        // 示例配置中公开过的密钥被拒绝
+        let mut sample = config.clone();
+        sample.security.api_keys.insert("admin_key".to_string(), bootstrap);
+        assert!(!run_self_check(&sample).await.passed());
+
+        let dir = tempfile::tempdir().unwrap();
+        config.server.https_enabled = true;
+        config.server.cert_path = dir.path().join("missing.crt").display().to_string();
+        config.schema_registry.directory = Some(dir.path().to_path_buf());
+        let report = run_self_check(&config).await;
+        assert!(!report.passed());
+        assert_eq!(report.items.len(), 3);
+        assert!(report.items[2].passed);
+        assert!(report.to_string().ends_with("1 of 3 checks failed"));
+    }
+}
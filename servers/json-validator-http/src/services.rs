@@ -1,6 +1,8 @@
 //! JSON验证服务
 
+use crate::config::ValidationProfile;
 use crate::models::*;
+use crate::validation_pool::ValidationPool;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -14,6 +16,16 @@ pub struct JsonValidatorService {
     stats: Arc<RwLock<ServiceStats>>,
     /// Schema缓存
     schema_cache: Arc<RwLock<HashMap<String, Arc<jsonschema::JSONSchema>>>>,
+    /// 验证结果缓存，用于批量验证的跨请求去重，未启用时为 `None`
+    result_cache: Option<Arc<ResultCache>>,
+    /// 大文档验证线程池，未设置时所有验证都在异步任务中执行
+    validation_pool: Option<Arc<ValidationPool>>,
+}
+
+/// 验证结果缓存（文档、Schema和选项的摘要 -> 结果）
+struct ResultCache {
+    entries: parking_lot::Mutex<lru::LruCache<String, (Instant, ValidationResult)>>,
+    ttl: Duration,
 }
 
 /// 服务统计信息
@@ -45,8 +57,27 @@ impl JsonValidatorService {
         Self {
             stats: Arc::new(RwLock::new(ServiceStats::default())),
             schema_cache: Arc::new(RwLock::new(HashMap::new())),
+            result_cache: None,
+            validation_pool: None,
         }
     }
+
+    /// 使用线程池验证大文档
+    pub fn with_validation_pool(mut self, pool: Arc<ValidationPool>) -> Self {
+        self.validation_pool = Some(pool);
+        self
+    }
+
+    /// 启用验证结果缓存，`capacity` 为最多缓存的结果数，结果超过 `ttl` 后失效
+    pub fn with_result_cache(mut self, capacity: usize, ttl: Duration) -> Self {
+        self.result_cache = std::num::NonZeroUsize::new(capacity).map(|capacity| {
+            Arc::new(ResultCache {
+                entries: parking_lot::Mutex::new(lru::LruCache::new(capacity)),
+                ttl,
+            })
+        });
+        self
+    }
     
     /// 验证JSON
     pub async fn validate_json(
@@ -55,43 +86,94 @@ impl JsonValidatorService {
         schema: Option<&serde_json::Value>,
         options: &ValidationOptions,
     ) -> Result<ValidationResult, String> {
-        let start_time = Instant::now();
-        
-        // 更新统计
-        {
-            let mut stats = self.stats.write().await;
-            stats.requests_total += 1;
-            stats.validations_total += 1;
-        }
+        let start_time = self.start_validation().await;
         
         let result = if let Some(schema) = schema {
             // 使用schema验证
-            self.validate_with_schema(json_data, schema, options).await
+            self.validate_with_schema(json_data, schema, None, options).await
         } else {
             // 基本JSON格式验证
             self.validate_basic(json_data, options).await
         };
         
-        // 更新统计
-        {
-            let mut stats = self.stats.write().await;
-            stats.total_response_time += start_time.elapsed();
-            
-            if let Ok(ref result) = result {
-                if result.valid {
-                    stats.requests_success += 1;
-                    stats.validations_success += 1;
-                } else {
-                    stats.requests_failed += 1;
-                    stats.validations_failed += 1;
-                }
+        self.finish_validation(start_time, &result).await;
+        result
+    }
+    
+    /// 按验证配置档验证JSON：先检查大小和深度限制，再执行Schema验证和自定义规则
+    pub async fn validate_with_profile(
+        &self,
+        json_data: &serde_json::Value,
+        schema: &serde_json::Value,
+        profile: &ValidationProfile,
+        options: &ValidationOptions,
+    ) -> Result<ValidationResult, String> {
+        let start_time = self.start_validation().await;
+        let locale = crate::i18n::resolve_locale(options.locale.as_deref());
+        
+        let result = match profile_limit_violation(json_data, profile, locale) {
+            Some(error) => Ok(ValidationResult::failure(vec![error], 0, false)),
+            None => match self
+                .validate_with_schema(json_data, schema, Some(profile.strict_formats), options)
+                .await
+            {
+                Ok(mut result) => check_profile_rules(json_data, profile, locale).map(|errors| {
+                    result.valid &= errors.is_empty();
+                    if !result.valid {
+                        result.normalized = None;
+                    }
+                    if let Some(tree) = &mut result.verbose {
+                        tree.attach_errors(&errors);
+                    }
+                    result.errors.extend(errors);
+                    result
+                }),
+                Err(e) => Err(e),
+            },
+        };
+        
+        self.finish_validation(start_time, &result).await;
+        result
+    }
+    
+    /// 影子验证：分别使用当前版本和候选版本验证，返回两者的结论，不计入服务统计
+    pub async fn shadow_validate(
+        &self,
+        json_data: &serde_json::Value,
+        active: &serde_json::Value,
+        candidate: &serde_json::Value,
+    ) -> Result<(bool, bool), String> {
+        let options = ValidationOptions::default();
+        let active = self.validate_with_schema(json_data, active, None, &options).await?;
+        let candidate = self.validate_with_schema(json_data, candidate, None, &options).await?;
+        Ok((active.valid, candidate.valid))
+    }
+    
+    /// 记录一次验证开始
+    async fn start_validation(&self) -> Instant {
+        let mut stats = self.stats.write().await;
+        stats.requests_total += 1;
+        stats.validations_total += 1;
+        Instant::now()
+    }
+    
+    /// 记录一次验证结果
+    async fn finish_validation(&self, start_time: Instant, result: &Result<ValidationResult, String>) {
+        let mut stats = self.stats.write().await;
+        stats.total_response_time += start_time.elapsed();
+        
+        if let Ok(ref result) = result {
+            if result.valid {
+                stats.requests_success += 1;
+                stats.validations_success += 1;
             } else {
                 stats.requests_failed += 1;
                 stats.validations_failed += 1;
             }
+        } else {
+            stats.requests_failed += 1;
+            stats.validations_failed += 1;
         }
-        
-        result
     }
     
     /// 使用schema验证JSON
@@ -99,10 +181,11 @@ impl JsonValidatorService {
         &self,
         json_data: &serde_json::Value,
         schema: &serde_json::Value,
+        validate_formats: Option<bool>,
         options: &ValidationOptions,
     ) -> Result<ValidationResult, String> {
         // 编译schema
-        let compiled_schema = match self.get_or_compile_schema(schema).await {
+        let compiled_schema = match self.get_or_compile_schema(schema, validate_formats).await {
             Ok(schema) => schema,
             Err(e) => {
                 error!("Failed to compile schema: {}", e);
@@ -110,41 +193,16 @@ impl JsonValidatorService {
             }
         };
         
-        // 执行验证
-        let start_time = Instant::now();
-        let validation_result = compiled_schema.validate(json_data);
-        let validation_time = start_time.elapsed();
+        // 语言在进入线程池之前确定，线程池中取不到请求的语言
+        let locale = crate::i18n::resolve_locale(options.locale.as_deref());
         
-        match validation_result {
-            Ok(_) => Ok(ValidationResult {
-                valid: true,
-                errors: vec![],
-                warnings: vec![],
-                execution_time: validation_time.as_millis() as u64,
-                cache_hit: false,
-                cache_key: None,
-            }),
-            Err(errors) => {
-                let error_messages: Vec<ValidationError> = errors
-                    .into_iter()
-                    .map(|e| ValidationError {
-                        instance_path: e.instance_path.to_string(),
-                        schema_path: e.schema_path.to_string(),
-                        message: e.to_string(),
-                        error_code: "SCHEMA_VALIDATION_ERROR".to_string(),
-                        location: None,
-                    })
-                    .collect();
-                
-                Ok(ValidationResult {
-                    valid: false,
-                    errors: error_messages,
-                    warnings: vec![],
-                    execution_time: validation_time.as_millis() as u64,
-                    cache_hit: false,
-                    cache_key: None,
-                })
+        // 大文档在线程池中验证，避免阻塞异步工作线程
+        match &self.validation_pool {
+            Some(pool) if pool.should_offload(json_data) => {
+                let (json_data, schema, options) = (json_data.clone(), schema.clone(), options.clone());
+                pool.run(move || check_schema(&compiled_schema, &json_data, &schema, &options, locale)).await
             }
+            _ => Ok(check_schema(&compiled_schema, json_data, schema, options, locale)),
         }
     }
     
@@ -174,15 +232,22 @@ impl JsonValidatorService {
             execution_time: start_time.elapsed().as_millis() as u64,
             cache_hit: false,
             cache_key: None,
+            fixes: Vec::new(),
+            normalized: None,
+            verbose: None,
         })
     }
     
-    /// 获取或编译schema
+    /// 获取或编译schema，`validate_formats` 为 `None` 时使用jsonschema的默认格式校验行为
     async fn get_or_compile_schema(
         &self,
         schema: &serde_json::Value,
+        validate_formats: Option<bool>,
     ) -> Result<Arc<jsonschema::JSONSchema>, String> {
-        let schema_key = schema.to_string();
+        let schema_key = match validate_formats {
+            Some(validate_formats) => format!("formats={}:{}", validate_formats, schema),
+            None => schema.to_string(),
+        };
         
         // 检查缓存
         {
@@ -195,7 +260,12 @@ impl JsonValidatorService {
         }
         
         // 编译schema
-        let compiled_schema = jsonschema::JSONSchema::compile(schema)
+        let mut compile_options = jsonschema::JSONSchema::options();
+        if let Some(validate_formats) = validate_formats {
+            compile_options.should_validate_formats(validate_formats);
+        }
+        let compiled_schema = compile_options
+            .compile(schema)
             .map_err(|e| format!("Schema compilation failed: {}", e))?;
         
         // 缓存schema
@@ -216,6 +286,42 @@ impl JsonValidatorService {
         self.stats.read().await.clone()
     }
 
+    /// 大文档验证线程池的状态，未启用线程池时为 `None`
+    pub fn validation_pool_stats(&self) -> Option<crate::validation_pool::ValidationPoolStats> {
+        self.validation_pool.as_ref().map(|pool| pool.stats())
+    }
+
+    /// 已缓存的编译Schema数量
+    pub async fn schema_cache_size(&self) -> usize {
+        self.schema_cache.read().await.len()
+    }
+
+    /// 预编译Schema并用样本数据试运行验证，不计入服务统计
+    pub async fn warm_up_schema(
+        &self,
+        schema: &serde_json::Value,
+        validate_formats: Option<bool>,
+        samples: &[&serde_json::Value],
+    ) -> Result<(), String> {
+        let compiled_schema = self.get_or_compile_schema(schema, validate_formats).await?;
+        for sample in samples {
+            // 只为预热验证路径，结果不重要
+            let _ = compiled_schema.is_valid(sample);
+        }
+        Ok(())
+    }
+
+    /// 清空编译Schema缓存和验证结果缓存，返回清除的编译Schema数
+    pub async fn clear_schema_cache(&self) -> usize {
+        if let Some(result_cache) = &self.result_cache {
+            result_cache.entries.lock().clear();
+        }
+        let mut cache = self.schema_cache.write().await;
+        let cleared = cache.len();
+        cache.clear();
+        cleared
+    }
+
     /// 验证JSON（简化版本）
     pub async fn validate_json_simple(
         &self,
@@ -236,44 +342,124 @@ impl JsonValidatorService {
     }
 
     /// 批量验证JSON
+    ///
+    /// 文档、Schema和选项都相同的项只验证一次，结果复制给其余各项；`use_result_cache` 为 `true`
+    /// 且启用了结果缓存时，还会复用之前请求中相同项的结果（此时 `cache_hit` 为 `true`）。
+    /// 响应的 `summary.deduplicated` 为没有实际执行验证的项数。
     pub async fn validate_json_batch(
         &self,
         items: &[crate::models::BatchValidationItem],
         options: &ValidationOptions,
-    ) -> Result<Vec<crate::models::BatchValidationResult>, String> {
-        let mut results = Vec::new();
+        use_result_cache: bool,
+    ) -> Result<crate::models::BatchValidationResponse, String> {
+        let result_cache = self.result_cache.as_ref().filter(|_| use_result_cache);
+        let mut unique: HashMap<String, ValidationResult> = HashMap::new();
+        let mut results = Vec::with_capacity(items.len());
+        let mut deduplicated = 0;
+        let locale = crate::i18n::resolve_locale(options.locale.as_deref());
         
         for item in items {
-            let result = if let Some(ref schema) = item.schema {
-                self.validate_json(&item.json_data, Some(schema), options).await
-            } else {
-                self.validate_json(&item.json_data, None, options).await
-            };
+            let key = batch_item_key(item, options, locale);
+            if let Some(result) = unique.get(&key) {
+                deduplicated += 1;
+                results.push(crate::models::BatchValidationResult { id: item.id.clone(), result: result.clone() });
+                continue;
+            }
             
-            let validation_result = result.unwrap_or_else(|e| {
-                ValidationResult {
-                    valid: false,
-                    errors: vec![ValidationError {
-                        instance_path: "".to_string(),
-                        schema_path: "".to_string(),
-                        message: e,
-                        error_code: "VALIDATION_ERROR".to_string(),
-                        location: None,
-                    }],
-                    warnings: vec![],
-                    execution_time: 0,
-                    cache_hit: false,
-                    cache_key: None,
+            let cached = result_cache.and_then(|cache| {
+                let mut entries = cache.entries.lock();
+                match entries.get(&key) {
+                    Some((stored_at, result)) if stored_at.elapsed() < cache.ttl => Some(result.clone()),
+                    Some(_) => {
+                        entries.pop(&key);
+                        None
+                    }
+                    None => None,
                 }
             });
+            let validation_result = match cached {
+                Some(mut result) => {
+                    deduplicated += 1;
+                    result.cache_hit = true;
+                    result
+                }
+                None => {
+                    let result = self
+                        .validate_json(&item.json_data, item.schema.as_ref(), options)
+                        .await
+                        .unwrap_or_else(|e| ValidationResult::failure(
+                            vec![ValidationError {
+                                instance_path: "".to_string(),
+                                schema_path: "".to_string(),
+                                message: e,
+                                error_code: "VALIDATION_ERROR".to_string(),
+                                location: None,
+                            }],
+                            0,
+                            false,
+                        ));
+                    if let Some(cache) = result_cache {
+                        cache.entries.lock().put(key.clone(), (Instant::now(), result.clone()));
+                    }
+                    result
+                }
+            };
             
+            unique.insert(key, validation_result.clone());
             results.push(crate::models::BatchValidationResult {
                 id: item.id.clone(),
                 result: validation_result,
             });
         }
         
-        Ok(results)
+        let mut response = crate::models::BatchValidationResponse::new(results);
+        response.summary.deduplicated = deduplicated;
+        Ok(response)
+    }
+
+    /// 使用多个Schema验证同一JSON，按组合方式给出总体结论
+    ///
+    /// Schema编译失败时该项记为验证失败，不影响其他Schema。
+    pub async fn validate_json_multi(
+        &self,
+        json_data: &serde_json::Value,
+        schemas: &[crate::models::SchemaEntry],
+        mode: crate::models::CombineMode,
+        options: &ValidationOptions,
+    ) -> Result<crate::models::MultiValidationResult, String> {
+        if schemas.is_empty() {
+            return Err("At least one schema is required".to_string());
+        }
+
+        let mut results = Vec::with_capacity(schemas.len());
+        for (index, entry) in schemas.iter().enumerate() {
+            let result = self
+                .validate_json(json_data, Some(&entry.schema), options)
+                .await
+                .unwrap_or_else(|e| ValidationResult::failure(
+                    vec![ValidationError {
+                        instance_path: "".to_string(),
+                        schema_path: "".to_string(),
+                        message: e,
+                        error_code: "INVALID_SCHEMA".to_string(),
+                        location: None,
+                    }],
+                    0,
+                    false,
+                ));
+
+            results.push(crate::models::SchemaValidationResult {
+                id: entry.label(index),
+                result,
+            });
+        }
+
+        let verdicts: Vec<bool> = results.iter().map(|r| r.result.valid).collect();
+        Ok(crate::models::MultiValidationResult {
+            valid: mode.combine(&verdicts),
+            mode,
+            results,
+        })
     }
 
     /// 获取请求总数
@@ -328,6 +514,175 @@ impl JsonValidatorService {
     }
 }
 
+/// 使用编译后的Schema验证文档，同时生成修复建议和规范化文档
+fn check_schema(
+    compiled_schema: &jsonschema::JSONSchema,
+    json_data: &serde_json::Value,
+    schema: &serde_json::Value,
+    options: &ValidationOptions,
+    locale: crate::i18n::Locale,
+) -> ValidationResult {
+    let start_time = Instant::now();
+    let validation_result = compiled_schema.validate(json_data);
+    let validation_time = start_time.elapsed();
+    let verbose = |errors: &[ValidationError]| {
+        (options.output == Some(OutputFormat::Verbose)).then(|| OutputUnit::verbose(schema, json_data, errors))
+    };
+    
+    match validation_result {
+        Ok(_) => ValidationResult {
+            valid: true,
+            errors: vec![],
+            warnings: vec![],
+            execution_time: validation_time.as_millis() as u64,
+            cache_hit: false,
+            cache_key: None,
+            fixes: Vec::new(),
+            normalized: options.normalize.then(|| {
+                let mut normalized = json_data.clone();
+                crate::normalize::normalize(schema, &mut normalized);
+                normalized
+            }),
+            verbose: verbose(&[]),
+        },
+        Err(errors) => {
+            let errors: Vec<_> = errors.collect();
+            let fixes = if options.suggest_fixes {
+                crate::fixes::suggest_fixes(schema, &errors)
+            } else {
+                Vec::new()
+            };
+            let error_messages: Vec<ValidationError> = errors
+                .into_iter()
+                .map(|e| ValidationError {
+                    instance_path: e.instance_path.to_string(),
+                    schema_path: e.schema_path.to_string(),
+                    message: crate::i18n::schema_error_message(&e, locale),
+                    error_code: "SCHEMA_VALIDATION_ERROR".to_string(),
+                    location: None,
+                })
+                .collect();
+            let verbose = verbose(&error_messages);
+            
+            ValidationResult {
+                valid: false,
+                errors: error_messages,
+                warnings: vec![],
+                execution_time: validation_time.as_millis() as u64,
+                cache_hit: false,
+                cache_key: None,
+                fixes,
+                normalized: None,
+                verbose,
+            }
+        }
+    }
+}
+
+/// 批量验证项的去重键：文档、Schema、影响结果的选项和错误消息语言的SHA-256摘要
+///
+/// `serde_json` 的对象按键排序序列化，键顺序不同的相同文档得到相同的键。
+fn batch_item_key(
+    item: &crate::models::BatchValidationItem,
+    options: &ValidationOptions,
+    locale: crate::i18n::Locale,
+) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(item.json_data.to_string());
+    hasher.update([0]);
+    hasher.update(item.schema.as_ref().map(|schema| schema.to_string()).unwrap_or_default());
+    hasher.update([
+        0,
+        options.suggest_fixes as u8,
+        options.normalize as u8,
+        (options.output == Some(OutputFormat::Verbose)) as u8,
+    ]);
+    hasher.update(locale.tag());
+    hex::encode(hasher.finalize())
+}
+
+/// 检查配置档的大小和深度限制
+fn profile_limit_violation(
+    json_data: &serde_json::Value,
+    profile: &ValidationProfile,
+    locale: crate::i18n::Locale,
+) -> Option<ValidationError> {
+    let violation = |message: String, error_code: &str| ValidationError {
+        instance_path: "".to_string(),
+        schema_path: "".to_string(),
+        message,
+        error_code: error_code.to_string(),
+        location: None,
+    };
+    
+    if let Some(max_size) = profile.max_json_size {
+        let size = serde_json::to_vec(json_data).map(|bytes| bytes.len()).unwrap_or(0);
+        if size > max_size {
+            return Some(violation(
+                crate::i18n::translate(locale, "profile.max_size_exceeded", &[size.to_string(), max_size.to_string()]),
+                "MAX_SIZE_EXCEEDED",
+            ));
+        }
+    }
+    if let Some(max_depth) = profile.max_depth {
+        let depth = json_depth(json_data);
+        if depth > max_depth {
+            return Some(violation(
+                crate::i18n::translate(locale, "profile.max_depth_exceeded", &[depth.to_string(), max_depth.to_string()]),
+                "MAX_DEPTH_EXCEEDED",
+            ));
+        }
+    }
+    None
+}
+
+/// 执行配置档的自定义规则，返回违反规则的错误
+fn check_profile_rules(
+    json_data: &serde_json::Value,
+    profile: &ValidationProfile,
+    locale: crate::i18n::Locale,
+) -> Result<Vec<ValidationError>, String> {
+    let mut errors = Vec::new();
+    for (index, rule) in profile.rules.iter().enumerate() {
+        let violation = match (json_data.pointer(&rule.pointer), &rule.pattern) {
+            (None, _) if rule.required => Some("profile.rule_required"),
+            (Some(value), Some(pattern)) => {
+                let pattern = regex::Regex::new(pattern)
+                    .map_err(|e| format!("Invalid pattern in rule {}: {}", index, e))?;
+                match value.as_str() {
+                    Some(text) if pattern.is_match(text) => None,
+                    _ => Some("profile.rule_pattern"),
+                }
+            }
+            _ => None,
+        };
+        
+        if let Some(key) = violation {
+            errors.push(ValidationError {
+                instance_path: rule.pointer.clone(),
+                schema_path: format!("/rules/{}", index),
+                message: rule
+                    .message
+                    .clone()
+                    .unwrap_or_else(|| crate::i18n::translate(locale, key, std::slice::from_ref(&rule.pointer))),
+                error_code: "PROFILE_RULE_VIOLATION".to_string(),
+                location: None,
+            });
+        }
+    }
+    Ok(errors)
+}
+
+/// JSON嵌套深度，标量为0
+fn json_depth(value: &serde_json::Value) -> usize {
+    match value {
+        serde_json::Value::Array(items) => 1 + items.iter().map(json_depth).max().unwrap_or(0),
+        serde_json::Value::Object(map) => 1 + map.values().map(json_depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -347,7 +702,7 @@ mod tests {
         let json_data = serde_json::json!({"test": "value"});
         let options = ValidationOptions::default();
         
-        let result = service.validate_json(&json_data, None, &options).await;
+        let result = service.validate_json(&json_data, None, &options).await.unwrap();
         
         assert!(result.valid);
         assert!(result.errors.is_empty());
@@ -367,9 +722,181 @@ mod tests {
         });
         let options = ValidationOptions::default();
         
-        let result = service.validate_json(&json_data, Some(&schema), &options).await;
+        let result = service.validate_json(&json_data, Some(&schema), &options).await.unwrap();
         
         assert!(result.valid);
         assert!(result.errors.is_empty());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_fix_suggestions() {
+        let service = JsonValidatorService::new();
+        let schema = serde_json::json!({
+            "type": "object",
+            "required": ["name", "age"],
+            "properties": { "name": { "type": "string" }, "age": { "type": "integer" } }
+        });
+        let json_data = serde_json::json!({"age": "42"});
+
+        let result = service.validate_json(&json_data, Some(&schema), &ValidationOptions::default()).await.unwrap();
+        assert!(!result.valid);
+        assert!(result.fixes.is_empty());
+
+        let options = ValidationOptions { suggest_fixes: true, ..Default::default() };
+        let result = service.validate_json(&json_data, Some(&schema), &options).await.unwrap();
+        assert_eq!(result.fixes, vec![
+            serde_json::json!({"op": "replace", "path": "/age", "value": 42}),
+            serde_json::json!({"op": "add", "path": "/name", "value": ""}),
+        ]);
+        assert_eq!(result.to_value(Some(OutputFormat::Basic))["fixes"], serde_json::json!(result.fixes));
+    }
+
+    #[tokio::test]
+    async fn test_verbose_output_tree() {
+        let service = JsonValidatorService::new();
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "age": { "allOf": [{ "type": "integer" }, { "minimum": 0 }] }
+            },
+            "required": ["age"]
+        });
+        let options = ValidationOptions { output: Some(OutputFormat::Verbose), ..Default::default() };
+
+        let json_data = serde_json::json!({"tags": ["a", 1], "age": -1});
+        let result = service.validate_json(&json_data, Some(&schema), &options).await.unwrap();
+        let tree = result.to_value(Some(OutputFormat::Verbose));
+        assert_eq!(tree["valid"], false);
+        // 错误都挂在对应的关键字单元下，而不是根单元下
+        let units = tree["errors"].as_array().unwrap();
+        assert!(units.iter().all(|unit| !unit["keywordLocation"].as_str().unwrap().is_empty()));
+        let properties = units.iter().find(|unit| unit["keywordLocation"] == "/properties").unwrap();
+        let items = &properties["errors"][1]["errors"][0];
+        assert_eq!(items["keywordLocation"], "/properties/tags/items");
+        assert_eq!(items["errors"][0]["valid"], true);
+        assert_eq!(items["errors"][1]["instanceLocation"], "/tags/1");
+        assert_eq!(items["errors"][1]["errors"][0]["error"], "1 is not of type \"string\"");
+        let minimum = &properties["errors"][0]["errors"][0]["errors"][1]["errors"][0];
+        assert_eq!(minimum["keywordLocation"], "/properties/age/allOf/1/minimum");
+        let required = units.iter().find(|unit| unit["keywordLocation"] == "/required").unwrap();
+        assert_eq!(required["valid"], true);
+
+        let json_data = serde_json::json!({"age": 1});
+        let result = service.validate_json(&json_data, Some(&schema), &options).await.unwrap();
+        assert!(result.verbose.as_ref().is_some_and(|tree| tree.valid && !tree.errors.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_normalized_output() {
+        let service = JsonValidatorService::new();
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "retries": { "type": "integer", "default": 3 },
+                "at": { "type": "string", "format": "date-time" }
+            }
+        });
+        let options = ValidationOptions { normalize: true, ..Default::default() };
+
+        let json_data = serde_json::json!({"at": "2025-01-01T08:00:00+08:00", "count": 2.0});
+        let result = service.validate_json(&json_data, Some(&schema), &options).await.unwrap();
+        assert_eq!(
+            serde_json::to_string(&result.normalized.unwrap()).unwrap(),
+            r#"{"at":"2025-01-01T00:00:00Z","count":2,"retries":3}"#
+        );
+
+        let invalid = serde_json::json!({"retries": "many"});
+        let result = service.validate_json(&invalid, Some(&schema), &options).await.unwrap();
+        assert!(result.normalized.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_batch_deduplication() {
+        let service = JsonValidatorService::new().with_result_cache(16, Duration::from_secs(60));
+        let schema = serde_json::json!({"type": "object", "required": ["name"]});
+        let item = |id: &str, json_data: serde_json::Value| crate::models::BatchValidationItem {
+            id: id.to_string(),
+            json_data,
+            schema: Some(schema.clone()),
+        };
+        let items = vec![
+            item("1", serde_json::json!({"name": "a", "n": 1})),
+            item("2", serde_json::json!({"n": 1, "name": "a"})),
+            item("3", serde_json::json!({})),
+            item("4", serde_json::json!({})),
+        ];
+        let options = ValidationOptions::default();
+
+        let response = service.validate_json_batch(&items, &options, false).await.unwrap();
+        assert_eq!(response.summary.deduplicated, 2);
+        assert_eq!(response.summary.failed, 2);
+        assert_eq!(response.results[3].id, "4");
+        assert!(!response.results[3].result.valid);
+        assert_eq!(service.get_stats().await.validations_total, 2);
+
+        // 跨请求去重只在请求方要求时使用缓存
+        let response = service.validate_json_batch(&items[..1], &options, true).await.unwrap();
+        assert_eq!(response.summary.deduplicated, 0);
+        let response = service.validate_json_batch(&items, &options, true).await.unwrap();
+        assert_eq!(response.summary.deduplicated, 3);
+        assert!(response.results[0].result.cache_hit);
+        assert!(!response.results[2].result.cache_hit);
+    }
+
+    #[tokio::test]
+    async fn test_profile_validation() {
+        let service = JsonValidatorService::new();
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": { "email": { "type": "string", "format": "email" } }
+        });
+        let profile = ValidationProfile {
+            rules: vec![crate::config::ProfileRule {
+                pointer: "/team".to_string(),
+                required: true,
+                pattern: Some("^[a-z]+$".to_string()),
+                message: None,
+            }],
+            strict_formats: true,
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        let options = ValidationOptions::default();
+
+        let valid = serde_json::json!({"email": "a@example.com", "team": "core"});
+        assert!(service.validate_with_profile(&valid, &schema, &profile, &options).await.unwrap().valid);
+
+        let invalid = serde_json::json!({"email": "not-an-email"});
+        let result = service.validate_with_profile(&invalid, &schema, &profile, &options).await.unwrap();
+        let codes: Vec<_> = result.errors.iter().map(|e| e.error_code.as_str()).collect();
+        assert_eq!(codes, vec!["SCHEMA_VALIDATION_ERROR", "PROFILE_RULE_VIOLATION"]);
+
+        let too_deep = serde_json::json!({"a": {"b": {"c": 1}}});
+        let result = service.validate_with_profile(&too_deep, &schema, &profile, &options).await.unwrap();
+        assert_eq!(result.errors[0].error_code, "MAX_DEPTH_EXCEEDED");
+        assert_eq!(service.get_stats().await.validations_total, 3);
+    }
+
+    #[tokio::test]
+    async fn test_localized_messages() {
+        let service = JsonValidatorService::new();
+        let schema = serde_json::json!({"type": "object", "required": ["name"]});
+        let profile = ValidationProfile { max_depth: Some(1), ..Default::default() };
+        let options = ValidationOptions { locale: Some("zh-CN".to_string()), ..Default::default() };
+
+        let result = service.validate_json(&serde_json::json!({}), Some(&schema), &options).await.unwrap();
+        assert_eq!(result.errors[0].message, "缺少必需属性 \"name\"");
+
+        let too_deep = serde_json::json!({"a": {"b": 1}});
+        let result = service.validate_with_profile(&too_deep, &schema, &profile, &options).await.unwrap();
+        assert_eq!(result.errors[0].message, "JSON嵌套深度 2 超过配置档限制 1");
+
+        // 未指定语言且不在请求处理中时使用英文
+        let result = service
+            .validate_json(&serde_json::json!({}), Some(&schema), &ValidationOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(result.errors[0].message, "\"name\" is a required property");
+    }
+}
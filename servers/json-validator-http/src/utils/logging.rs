@@ -1,12 +1,19 @@
 //! 日志工具模块
 
+use std::sync::Arc;
+
 use chrono::Utc;
 use tracing_subscriber::{
     fmt::{self},
-    layer::Layer,
+    layer::{Layer, SubscriberExt},
     util::SubscriberInitExt,
+    EnvFilter,
 };
 
+use crash_report::{RecentLogs, RecentLogsLayer};
+
+use crate::crash::RECENT_LOG_LINES;
+
 /// 日志配置
 pub struct LogConfig {
     /// 日志级别
@@ -26,8 +33,8 @@ pub enum LogFormat {
     Text,
 }
 
-/// 设置日志系统
-pub fn setup_logging(level: &str) -> anyhow::Result<()> {
+/// 设置日志系统，返回崩溃报告使用的最近日志缓冲区
+pub fn setup_logging(level: &str) -> anyhow::Result<Arc<RecentLogs>> {
     let log_config = LogConfig {
         level: level.to_string(),
         format: LogFormat::Text,
@@ -37,15 +44,18 @@ pub fn setup_logging(level: &str) -> anyhow::Result<()> {
     setup_logging_with_config(log_config)
 }
 
-/// 使用配置设置日志系统
-pub fn setup_logging_with_config(config: LogConfig) -> anyhow::Result<()> {
-    // 简化的日志配置
-    tracing_subscriber::fmt()
-        .with_env_filter(&config.level)
+/// 使用配置设置日志系统，返回崩溃报告使用的最近日志缓冲区
+pub fn setup_logging_with_config(config: LogConfig) -> anyhow::Result<Arc<RecentLogs>> {
+    // 简化的日志配置，同时在内存中保留最近的日志
+    let recent_logs = Arc::new(RecentLogs::new(RECENT_LOG_LINES));
+    tracing_subscriber::registry()
+        .with(EnvFilter::new(&config.level))
+        .with(fmt::layer())
+        .with(RecentLogsLayer::new(recent_logs.clone()))
         .init();
     
     println!("Logging system initialized with level: {}", config.level);
-    Ok(())
+    Ok(recent_logs)
 }
 
 /// 创建标准输出日志层
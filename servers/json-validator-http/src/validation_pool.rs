@@ -0,0 +1,191 @@
+//! 大文档验证线程池
+//!
+//! 大文档的Schema验证是CPU密集操作，直接在异步任务中执行会阻塞tokio工作线程，拖慢同一线程上的其他请求。
+//! 估算大小达到 `performance.validation_pool.min_document_bytes` 的文档改用 `spawn_blocking` 在阻塞线程上验证，
+//! 同时执行的验证数不超过 `threads`，其余排队等待；排队数达到 `max_queue` 时直接拒绝，避免积压拖垮小请求的延迟。
+//! 小文档仍在异步任务中直接验证。
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::Semaphore;
+
+use crate::config::ValidationPoolConfig;
+
+/// 线程池已满时返回的错误消息
+pub const REJECTED_MESSAGE: &str = "Validation pool queue is full, retry later";
+
+/// 线程池状态
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ValidationPoolStats {
+    /// 线程数
+    pub threads: usize,
+    /// 正在执行的验证数
+    pub running: usize,
+    /// 等待线程的验证数
+    pub queued: usize,
+    /// 因队列已满被拒绝的验证数
+    pub rejected: u64,
+}
+
+/// 大文档验证线程池
+pub struct ValidationPool {
+    config: ValidationPoolConfig,
+    threads: usize,
+    permits: Arc<Semaphore>,
+    running: Arc<AtomicUsize>,
+    queued: AtomicUsize,
+    rejected: AtomicU64,
+}
+
+impl ValidationPool {
+    /// 按配置创建线程池，`threads` 为0时使用CPU核心数
+    pub fn from_config(config: &ValidationPoolConfig) -> Self {
+        let threads = match config.threads {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            threads => threads,
+        };
+        Self {
+            config: config.clone(),
+            threads,
+            permits: Arc::new(Semaphore::new(threads)),
+            running: Arc::new(AtomicUsize::new(0)),
+            queued: AtomicUsize::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// 文档是否应交给线程池验证
+    pub fn should_offload(&self, document: &Value) -> bool {
+        self.config.enabled && exceeds_size(document, self.config.min_document_bytes)
+    }
+
+    /// 在线程池中执行验证，排队数已达上限时返回 [`REJECTED_MESSAGE`]
+    pub async fn run<T, F>(&self, task: F) -> Result<T, String>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let max_queue = self.config.max_queue;
+        if self
+            .queued
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |queued| (queued < max_queue).then_some(queued + 1))
+            .is_err()
+        {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(REJECTED_MESSAGE.to_string());
+        }
+
+        // 请求在排队时被取消也要减少排队数
+        let queue_slot = CounterGuard(&self.queued);
+        let permit = self.permits.clone().acquire_owned().await.map_err(|e| e.to_string())?;
+        drop(queue_slot);
+
+        let running = self.running.clone();
+        running.fetch_add(1, Ordering::AcqRel);
+        // 许可随任务一起移动，请求被取消时验证仍占用线程直到完成
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            let _running = CounterGuard(&running);
+            task()
+        })
+        .await
+        .map_err(|e| format!("Validation task failed: {}", e))
+    }
+
+    /// 当前状态
+    pub fn stats(&self) -> ValidationPoolStats {
+        ValidationPoolStats {
+            threads: self.threads,
+            running: self.running.load(Ordering::Acquire),
+            queued: self.queued.load(Ordering::Acquire),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 离开作用域时减一的计数
+struct CounterGuard<'a>(&'a AtomicUsize);
+
+impl Drop for CounterGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// 文档的估算大小（序列化后的字节数）是否达到 `limit`，达到后立即停止遍历
+fn exceeds_size(document: &Value, limit: usize) -> bool {
+    let mut size = 0;
+    let mut pending = vec![document];
+    while let Some(value) = pending.pop() {
+        size += match value {
+            Value::Null | Value::Bool(_) => 5,
+            Value::Number(_) => 8,
+            Value::String(s) => s.len() + 2,
+            Value::Array(items) => {
+                pending.extend(items);
+                items.len() + 2
+            }
+            Value::Object(map) => {
+                pending.extend(map.values());
+                map.keys().map(|key| key.len() + 4).sum::<usize>() + 2
+            }
+        };
+        if size >= limit {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_size_threshold() {
+        let pool = ValidationPool::from_config(&ValidationPoolConfig { min_document_bytes: 1024, ..Default::default() });
+        assert!(!pool.should_offload(&json!({"name": "small"})));
+        assert!(pool.should_offload(&json!({"items": vec!["x".repeat(100); 20]})));
+
+        let disabled = ValidationPool::from_config(&ValidationPoolConfig { enabled: false, min_document_bytes: 0, ..Default::default() });
+        assert!(!disabled.should_offload(&json!({"items": vec!["x".repeat(100); 20]})));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_when_queue_full() {
+        let pool = Arc::new(ValidationPool::from_config(&ValidationPoolConfig {
+            threads: 1,
+            max_queue: 1,
+            ..Default::default()
+        }));
+        let (release, wait) = std::sync::mpsc::channel::<()>();
+
+        // 第一个任务占用唯一的线程，第二个任务排队
+        let busy = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(move || wait.recv().is_ok()).await }
+        });
+        while pool.stats().running == 0 {
+            tokio::task::yield_now().await;
+        }
+        let queued = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(|| 42).await }
+        });
+        while pool.stats().queued == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        assert_eq!(pool.run(|| 0).await.unwrap_err(), REJECTED_MESSAGE);
+        release.send(()).unwrap();
+        assert!(busy.await.unwrap().unwrap());
+        assert_eq!(queued.await.unwrap().unwrap(), 42);
+
+        let stats = pool.stats();
+        assert_eq!((stats.running, stats.queued, stats.rejected), (0, 0, 1));
+    }
+}
@@ -0,0 +1,234 @@
+//! 启动预热
+//!
+//! 服务器开始监听后在后台预编译注册表中的Schema（含候选版本）和验证配置档引用的Schema，
+//! 并按配置重放代表性样本，使首批请求不再承担编译开销。预热完成前 `/ready` 返回503，
+//! 完成后才把实例加入负载均衡；`/health` 只表示进程存活，不受预热影响。
+//! 无法编译的Schema只记录警告，不会阻止实例就绪。
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::models::AppState;
+
+/// 预热样本，`schema_ref` 与 `profile` 二选一
+#[derive(Debug, Clone, Deserialize)]
+pub struct WarmupSample {
+    /// 注册表中的Schema名称
+    #[serde(default)]
+    pub schema_ref: Option<String>,
+    /// 验证配置档名称
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// 样本数据
+    pub json_data: serde_json::Value,
+}
+
+/// 预热结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WarmupReport {
+    /// 预编译的Schema数量
+    pub schemas: usize,
+    /// 重放的样本数量
+    pub samples: usize,
+    /// 预热失败的Schema或配置档，形如 `schema:<name>` / `profile:<name>`
+    pub failed: Vec<String>,
+    /// 预热耗时（毫秒）
+    pub duration_ms: u64,
+}
+
+/// 预热状态，决定 `/ready` 的结果
+#[derive(Debug, Default)]
+pub struct WarmupStatus {
+    ready: AtomicBool,
+    report: RwLock<Option<WarmupReport>>,
+}
+
+impl WarmupStatus {
+    /// 创建预热状态，不需要预热时直接就绪
+    pub fn new(ready: bool) -> Self {
+        Self {
+            ready: AtomicBool::new(ready),
+            report: RwLock::new(None),
+        }
+    }
+
+    /// 是否已就绪
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    /// 最近一次预热结果
+    pub fn report(&self) -> Option<WarmupReport> {
+        self.report.read().clone()
+    }
+
+    fn complete(&self, report: WarmupReport) {
+        *self.report.write() = Some(report);
+        self.ready.store(true, Ordering::Release);
+    }
+}
+
+/// 从JSON Lines文件读取预热样本，跳过空行
+pub fn load_samples(path: &Path) -> Result<Vec<WarmupSample>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| format!("{}:{}: invalid warm-up sample: {}", path.display(), index + 1, e))
+        })
+        .collect()
+}
+
+/// 在后台执行预热，未启用预热或已就绪时不做任何事
+pub fn start_warmup(state: &AppState) {
+    if state.warmup.is_ready() {
+        return;
+    }
+    let state = state.clone();
+    tokio::spawn(async move {
+        let report = run_warmup(&state).await;
+        state.warmup.complete(report);
+    });
+}
+
+/// 执行一次预热并返回结果
+pub async fn run_warmup(state: &AppState) -> WarmupReport {
+    let started = Instant::now();
+    let samples = match &state.config.warmup.samples_file {
+        Some(path) => load_samples(path).unwrap_or_else(|e| {
+            warn!("Skipping warm-up samples: {}", e);
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    let mut by_schema: HashMap<&str, Vec<&serde_json::Value>> = HashMap::new();
+    let mut by_profile: HashMap<&str, Vec<&serde_json::Value>> = HashMap::new();
+    for sample in &samples {
+        match (&sample.schema_ref, &sample.profile) {
+            (Some(name), _) => by_schema.entry(name.as_str()).or_default().push(&sample.json_data),
+            (None, Some(name)) => by_profile.entry(name.as_str()).or_default().push(&sample.json_data),
+            (None, None) => warn!("Ignoring warm-up sample without schema_ref or profile"),
+        }
+    }
+
+    let mut report = WarmupReport::default();
+    let service = &state.validator_service;
+    for name in state.schema_registry.names() {
+        let Some(schema) = state.schema_registry.get(&name) else {
+            continue;
+        };
+        let schema_samples = by_schema.remove(name.as_str()).unwrap_or_default();
+        let mut result = service.warm_up_schema(&schema, None, &schema_samples).await;
+        if let (Ok(()), Some(candidate)) = (&result, state.schema_registry.candidate(&name)) {
+            result = service.warm_up_schema(&candidate, None, &schema_samples).await;
+        }
+        record(&mut report, format!("schema:{}", name), schema_samples.len(), result);
+    }
+
+    for (name, profile) in &state.config.validation_profiles {
+        let schema = match (&profile.schema_ref, &profile.schema) {
+            (Some(schema_ref), _) => state.schema_registry.get(schema_ref),
+            (None, Some(schema)) => Some(std::sync::Arc::new(schema.clone())),
+            (None, None) => None,
+        };
+        let profile_samples = by_profile.remove(name.as_str()).unwrap_or_default();
+        let result = match schema {
+            Some(schema) => service.warm_up_schema(&schema, Some(profile.strict_formats), &profile_samples).await,
+            None => Err("no resolvable schema".to_string()),
+        };
+        record(&mut report, format!("profile:{}", name), profile_samples.len(), result);
+    }
+
+    for name in by_schema.keys() {
+        warn!("Ignoring warm-up samples for unknown schema '{}'", name);
+    }
+    for name in by_profile.keys() {
+        warn!("Ignoring warm-up samples for unknown validation profile '{}'", name);
+    }
+
+    report.duration_ms = started.elapsed().as_millis() as u64;
+    info!(
+        "Warm-up finished: {} schema(s), {} sample(s), {} failure(s) in {}ms",
+        report.schemas,
+        report.samples,
+        report.failed.len(),
+        report.duration_ms
+    );
+    report
+}
+
+fn record(report: &mut WarmupReport, target: String, samples: usize, result: Result<(), String>) {
+    match result {
+        Ok(()) => {
+            report.schemas += 1;
+            report.samples += samples;
+        }
+        Err(e) => {
+            warn!("Warm-up failed for {}: {}", target, e);
+            report.failed.push(target);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ServerConfig, ValidationProfile};
+
+    #[tokio::test]
+    async fn test_run_warmup() {
+        let samples = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            samples.path(),
+            concat!(
+                "{\"schema_ref\": \"user\", \"json_data\": {\"name\": \"a\"}}\n",
+                "\n",
+                "{\"profile\": \"signup\", \"json_data\": {\"name\": \"b\"}}\n",
+                "{\"schema_ref\": \"missing\", \"json_data\": {}}\n",
+            ),
+        )
+        .unwrap();
+
+        let mut config = ServerConfig::default();
+        config.warmup.samples_file = Some(samples.path().to_path_buf());
+        config.validation_profiles.insert(
+            "signup".to_string(),
+            ValidationProfile {
+                schema_ref: Some("user".to_string()),
+                strict_formats: true,
+                ..ValidationProfile::default()
+            },
+        );
+        config.validation_profiles.insert("broken".to_string(), ValidationProfile::default());
+        let state = AppState::with_config(config);
+        assert!(!state.warmup.is_ready());
+        state
+            .schema_registry
+            .register("user", serde_json::json!({"type": "object", "required": ["name"]}))
+            .unwrap();
+
+        let report = run_warmup(&state).await;
+        assert_eq!(report.schemas, 2);
+        assert_eq!(report.samples, 2);
+        assert_eq!(report.failed, vec!["profile:broken".to_string()]);
+        // 默认格式校验和严格格式校验各编译一份
+        assert_eq!(state.validator_service.schema_cache_size().await, 2);
+        assert_eq!(state.validator_service.get_stats().await.validations_total, 0);
+    }
+
+    #[test]
+    fn test_load_samples_reports_line() {
+        let samples = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(samples.path(), "{\"schema_ref\": \"user\", \"json_data\": 1}\nnot json\n").unwrap();
+        let error = load_samples(samples.path()).unwrap_err();
+        assert!(error.contains(":2: invalid warm-up sample"));
+    }
+}
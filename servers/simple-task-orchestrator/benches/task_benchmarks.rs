@@ -1,5 +1,8 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
 use simple_task_orchestrator::*;
+use simple_task_orchestrator::domain::{Task, TaskId, TaskStatus, WorkerId};
+use simple_task_orchestrator::infrastructure::TaskRepository;
+use std::collections::HashMap;
 use tokio::runtime::Runtime;
 
 fn benchmark_task_creation(c: &mut Criterion) {
@@ -138,6 +141,63 @@ fn benchmark_task_listing(c: &mut Criterion) {
     }
 }
 
+/// 旧实现：每次获取都扫描并排序全部任务，作为对照
+fn scan_next_task(tasks: &HashMap<TaskId, Task>, work_directory: &str) -> Option<Task> {
+    let mut candidates: Vec<_> = tasks.values()
+        .filter(|task| task.work_directory == work_directory && task.status == TaskStatus::Waiting)
+        .collect();
+    candidates.sort_by(|a, b| b.priority.cmp(&a.priority));
+    candidates.first().map(|task| (*task).clone())
+}
+
+fn benchmark_next_task_lookup(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let priority = |i: usize| match i % 3 {
+        0 => TaskPriority::Low,
+        1 => TaskPriority::Medium,
+        _ => TaskPriority::High,
+    };
+
+    let mut group = c.benchmark_group("next_task_lookup");
+
+    for &count in &[1_000usize, 10_000, 50_000] {
+        let repository = InMemoryTaskRepository::new();
+        let mut snapshot = HashMap::new();
+        rt.block_on(async {
+            for i in 0..count {
+                // 一半任务在其他目录，模拟多目录共享仓库
+                let directory = if i % 2 == 0 { "/test" } else { "/other" };
+                let task = Task::new(directory.to_string(), format!("Queued task {}", i), priority(i), vec![]);
+                repository.create_task(&task).await.unwrap();
+                snapshot.insert(task.id, task);
+            }
+        });
+
+        group.bench_with_input(BenchmarkId::new("linear_scan", count), &count, |b, _| {
+            b.iter(|| scan_next_task(black_box(&snapshot), "/test"));
+        });
+
+        group.bench_with_input(BenchmarkId::new("priority_queue", count), &count, |b, _| {
+            b.to_async(&rt).iter(|| async {
+                repository.get_next_task(black_box("/test"), "worker").await
+            });
+        });
+
+        // 完整的获取周期：取出堆顶任务、开始执行并补充一个新任务，保持队列规模不变
+        group.bench_with_input(BenchmarkId::new("acquire_cycle", count), &count, |b, _| {
+            b.to_async(&rt).iter(|| async {
+                let mut task = repository.get_next_task("/test", "worker").await.unwrap().unwrap();
+                task.start(WorkerId::new("worker".to_string()).unwrap()).unwrap();
+                repository.update_task(&task).await.unwrap();
+                let refill = Task::new("/test".to_string(), "Refill task".to_string(), task.priority.clone(), vec![]);
+                repository.create_task(&refill).await.unwrap();
+            });
+        });
+    }
+
+    group.finish();
+}
+
 fn benchmark_lock_operations(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
     let lock_manager = Arc::new(SimpleLockManager::new());
@@ -173,6 +233,7 @@ criterion_group!(
     benchmark_task_creation,
     benchmark_task_acquisition,
     benchmark_task_listing,
+    benchmark_next_task_lookup,
     benchmark_lock_operations
 );
 criterion_main!(benches);
\ No newline at end of file
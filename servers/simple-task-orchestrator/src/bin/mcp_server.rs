@@ -2,9 +2,10 @@ use std::sync::Arc;
 use rmcp::{ServiceExt, transport::stdio};
 
 use simple_task_orchestrator::config::ConfigManager;
-use simple_task_orchestrator::infrastructure::{InMemoryTaskRepository, SimpleLockManager};
+use simple_task_orchestrator::infrastructure::{ArtifactStore, InMemoryTaskRepository, SimpleLockManager, DEFAULT_LOCK_CLEANUP_INTERVAL};
 use simple_task_orchestrator::services::{TaskService, TaskScheduler, TaskMonitor, TaskExecutionService};
 use simple_task_orchestrator::mcp_server::TaskOrchestratorServer;
+use simple_task_orchestrator::execution::PostProcessorRegistry;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -24,6 +25,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     
     // 创建锁管理器
     let lock_manager = Arc::new(SimpleLockManager::new());
+    lock_manager.start_cleanup(DEFAULT_LOCK_CLEANUP_INTERVAL);
     
     // 创建任务服务
     let task_service = Arc::new(TaskService::new(
@@ -48,7 +50,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     
     // 创建执行服务
-    let execution_service = Arc::new(TaskExecutionService::new(task_repository.clone()));
+    let post_processors = PostProcessorRegistry::from_config(&config.post_processors)?;
+    let artifact_store = Arc::new(ArtifactStore::new(&config.artifacts.directory));
+    let execution_service = Arc::new(
+        TaskExecutionService::new(task_repository.clone())
+            .with_post_processors(post_processors)
+            .with_artifact_store(artifact_store, config.artifacts.workspace_snapshots)
+    );
     
     // 创建MCP服务器
     let mcp_server = TaskOrchestratorServer::new(
@@ -4,9 +4,9 @@ use sqlx::{migrate::MigrateDatabase, Sqlite};
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     println!("🔄 Running database migrations...");
     
-    // 从环境变量获取数据库URL
-    let database_url = std::env::var("DATABASE_URL")
-        .unwrap_or_else(|_| "sqlite:///data/tasks.db".to_string());
+    // 从环境变量获取数据库URL，也可以用 DATABASE_URL_FILE 指向密钥文件
+    let database_url = config_schema::env_var("DATABASE_URL")?
+        .unwrap_or_else(|| "sqlite:///data/tasks.db".to_string());
     
     println!("📊 Using database: {}", database_url);
     
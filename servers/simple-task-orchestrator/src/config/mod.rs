@@ -21,6 +21,8 @@
 //! - `LoggingConfig`: 日志记录配置
 //! - `SecurityConfig`: 安全相关配置
 //! - `MonitoringConfig`: 监控相关配置
+//! - `post_processors`: 按执行模式配置的结果后处理流水线
+//! - `ArtifactConfig`: 任务产物存储和工作目录快照配置
 //! 
 //! ## 使用示例
 //! 
@@ -48,10 +50,19 @@
 //! - `RUST_LOG`: 日志级别
 //! - `APP_SECURITY_RATE_LIMIT`: 安全限流设置
 //! - `APP_MONITORING_METRICS_INTERVAL`: 监控指标收集间隔
+//! - `APP_POST_PROCESSORS_FILE`: 结果后处理流水线配置文件（JSON）
+//! - `APP_ARTIFACT_DIR`: 任务产物存储目录
+//! - `APP_WORKSPACE_SNAPSHOTS`: ClaudeCode执行前后是否对工作目录做快照
+//!
+//! 除 `APP_POST_PROCESSORS_FILE` 外，以上变量未设置时都会读取 `<变量名>_FILE` 指向的文件内容，
+//! 便于使用Docker secrets。
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 
+use crate::execution::PostProcessStep;
+
 /// 应用主配置结构
 /// 
 /// 包含服务器的所有配置项，支持从环境变量加载和覆盖默认值。
@@ -64,6 +75,8 @@ use std::env;
 /// - `logging`: 日志记录配置
 /// - `security`: 安全相关配置（API密钥、限流等）
 /// - `monitoring`: 监控相关配置
+/// - `post_processors`: 执行模式名称到结果后处理步骤的映射，`default` 用于未单独配置的模式
+/// - `artifacts`: 任务产物存储和工作目录快照配置
 /// 
 /// # 示例
 /// 
@@ -79,6 +92,10 @@ pub struct AppConfig {
     pub logging: LoggingConfig,
     pub security: SecurityConfig,
     pub monitoring: MonitoringConfig,
+    #[serde(default)]
+    pub post_processors: HashMap<String, Vec<PostProcessStep>>,
+    #[serde(default)]
+    pub artifacts: ArtifactConfig,
 }
 
 impl Default for AppConfig {
@@ -90,10 +107,17 @@ impl Default for AppConfig {
             logging: LoggingConfig::default(),
             security: SecurityConfig::default(),
             monitoring: MonitoringConfig::default(),
+            post_processors: HashMap::new(),
+            artifacts: ArtifactConfig::default(),
         }
     }
 }
 
+/// 读取环境变量，未设置时读取 `NAME_FILE` 指向的文件（Docker secrets）
+fn env_var(name: &str) -> Result<Option<String>, String> {
+    config_schema::env_var(name).map_err(|e| e.to_string())
+}
+
 impl AppConfig {
     /// 从环境变量创建配置实例
     /// 
@@ -117,46 +141,63 @@ impl AppConfig {
         let mut config = Self::default();
         
         // 服务器配置
-        if let Ok(host) = env::var("APP_SERVER_HOST") {
+        if let Some(host) = env_var("APP_SERVER_HOST")? {
             config.server.host = host;
         }
         
-        if let Ok(port) = env::var("APP_SERVER_PORT") {
+        if let Some(port) = env_var("APP_SERVER_PORT")? {
             config.server.port = port.parse().map_err(|e| format!("Invalid port: {}", e))?;
         }
         
-        if let Ok(timeout) = env::var("APP_SERVER_TIMEOUT") {
+        if let Some(timeout) = env_var("APP_SERVER_TIMEOUT")? {
             config.server.timeout = timeout.parse().map_err(|e| format!("Invalid timeout: {}", e))?;
         }
         
         // 任务配置
-        if let Ok(max_retries) = env::var("APP_TASK_MAX_RETRIES") {
+        if let Some(max_retries) = env_var("APP_TASK_MAX_RETRIES")? {
             config.task.max_retries = max_retries.parse().map_err(|e| format!("Invalid max retries: {}", e))?;
         }
         
-        if let Ok(timeout) = env::var("APP_TASK_TIMEOUT") {
+        if let Some(timeout) = env_var("APP_TASK_TIMEOUT")? {
             config.task.timeout = timeout.parse().map_err(|e| format!("Invalid task timeout: {}", e))?;
         }
         
-        if let Ok(cleanup_interval) = env::var("APP_TASK_CLEANUP_INTERVAL") {
+        if let Some(cleanup_interval) = env_var("APP_TASK_CLEANUP_INTERVAL")? {
             config.task.cleanup_interval = cleanup_interval.parse().map_err(|e| format!("Invalid cleanup interval: {}", e))?;
         }
         
         // 日志配置
-        if let Ok(level) = env::var("RUST_LOG") {
+        if let Some(level) = env_var("RUST_LOG")? {
             config.logging.level = level;
         }
         
         // 安全配置
-        if let Ok(rate_limit) = env::var("APP_SECURITY_RATE_LIMIT") {
+        if let Some(rate_limit) = env_var("APP_SECURITY_RATE_LIMIT")? {
             config.security.rate_limit = rate_limit.parse().map_err(|e| format!("Invalid rate limit: {}", e))?;
         }
         
         // 监控配置
-        if let Ok(metrics_interval) = env::var("APP_MONITORING_METRICS_INTERVAL") {
+        if let Some(metrics_interval) = env_var("APP_MONITORING_METRICS_INTERVAL")? {
             config.monitoring.metrics_interval = metrics_interval.parse().map_err(|e| format!("Invalid metrics interval: {}", e))?;
         }
         
+        // 结果后处理配置
+        if let Ok(path) = env::var("APP_POST_PROCESSORS_FILE") {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read post-processors file {}: {}", path, e))?;
+            config.post_processors = serde_json::from_str(&content)
+                .map_err(|e| format!("Invalid post-processors file {}: {}", path, e))?;
+        }
+        
+        // 产物配置
+        if let Some(directory) = env_var("APP_ARTIFACT_DIR")? {
+            config.artifacts.directory = directory;
+        }
+        
+        if let Some(enabled) = env_var("APP_WORKSPACE_SNAPSHOTS")? {
+            config.artifacts.workspace_snapshots = enabled.parse().map_err(|e| format!("Invalid workspace snapshots flag: {}", e))?;
+        }
+        
         Ok(config)
     }
 }
@@ -476,6 +517,32 @@ impl Default for MonitoringConfig {
     }
 }
 
+/// 产物配置
+/// 
+/// # 字段说明
+/// 
+/// - `directory`: 产物存储目录，产物保存在 `<directory>/<任务ID>/<名称>`
+/// - `workspace_snapshots`: ClaudeCode执行前后对工作目录做快照，变更补丁保存为 `workspace.diff` 产物（需要 `git`）
+/// 
+/// # 默认值
+/// 
+/// - `directory`: "./artifacts"
+/// - `workspace_snapshots`: false
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactConfig {
+    pub directory: String,
+    pub workspace_snapshots: bool,
+}
+
+impl Default for ArtifactConfig {
+    fn default() -> Self {
+        Self {
+            directory: "./artifacts".to_string(),
+            workspace_snapshots: false,
+        }
+    }
+}
+
 /// 配置管理器
 /// 
 /// 提供配置的统一访问和管理接口。
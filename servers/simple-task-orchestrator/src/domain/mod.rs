@@ -26,7 +26,9 @@
 //! - `AcquireTaskRequest`: 获取任务请求
 //! - `CompleteTaskRequest`: 完成任务请求
 //! - `TaskFilter`: 任务过滤器
+//! - `TaskSort`: 任务列表的多键排序规则
 //! - `TaskStatistics`: 任务统计信息
+//! - `StatisticsQuery` / `StatisticsReport`: 按时间范围和维度分组的统计查询
 //! - `ApiResponse`: 统一的API响应格式
 //! 
 //! ## 使用示例
@@ -82,7 +84,7 @@ use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 
 /// 任务ID
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct TaskId(Uuid);
 
 impl TaskId {
@@ -245,6 +247,54 @@ impl TaskResult {
             metadata: None,
         }
     }
+
+    /// 执行器记录的资源用量
+    pub fn resource_usage(&self) -> Option<ResourceUsage> {
+        let value = self.metadata.as_ref()?.get(RESOURCE_USAGE_KEY)?;
+        serde_json::from_value(value.clone()).ok()
+    }
+
+    /// 记录资源用量到 `metadata.resource_usage`
+    pub fn set_resource_usage(&mut self, usage: &ResourceUsage) {
+        if let Ok(value) = serde_json::to_value(usage) {
+            self.metadata.get_or_insert_with(Default::default).insert(RESOURCE_USAGE_KEY.to_string(), value);
+        }
+    }
+}
+
+/// 任务结果元数据中资源用量的键
+pub const RESOURCE_USAGE_KEY: &str = "resource_usage";
+
+/// 一次执行的资源用量，由执行器从子进程采集
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub wall_time_ms: u64,
+    /// 用户态与内核态CPU时间之和
+    pub cpu_time_ms: Option<u64>,
+    pub max_rss_bytes: Option<u64>,
+    pub bytes_written: Option<u64>,
+}
+
+/// 多次执行的资源用量汇总
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceUsageTotals {
+    /// 记录了资源用量的执行次数
+    pub measured_executions: u64,
+    pub wall_time_ms: u64,
+    pub cpu_time_ms: u64,
+    pub bytes_written: u64,
+    /// 单次执行的最大峰值内存
+    pub peak_rss_bytes: u64,
+}
+
+impl ResourceUsageTotals {
+    pub fn add(&mut self, usage: &ResourceUsage) {
+        self.measured_executions += 1;
+        self.wall_time_ms += usage.wall_time_ms;
+        self.cpu_time_ms += usage.cpu_time_ms.unwrap_or(0);
+        self.bytes_written += usage.bytes_written.unwrap_or(0);
+        self.peak_rss_bytes = self.peak_rss_bytes.max(usage.max_rss_bytes.unwrap_or(0));
+    }
 }
 
 /// 任务
@@ -364,6 +414,11 @@ impl Task {
             false
         }
     }
+    
+    /// 最近一次活动时间：完成时间，其次开始时间，最后创建时间
+    pub fn last_activity_at(&self) -> DateTime<Utc> {
+        self.completed_at.or(self.started_at).unwrap_or(self.created_at)
+    }
 }
 
 /// 创建任务请求
@@ -374,6 +429,9 @@ pub struct CreateTaskRequest {
     pub priority: Option<TaskPriority>,
     pub execution_mode: Option<ExecutionMode>,
     pub tags: Option<Vec<String>>,
+    /// 任务元数据，例如 `git_ref` 指定执行前检出的引用
+    #[serde(default)]
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
 }
 
 /// 获取任务请求
@@ -398,6 +456,8 @@ pub struct TaskFilter {
     pub worker_id: Option<String>,
     pub limit: Option<u32>,
     pub offset: Option<u32>,
+    /// 排序规则，末尾总是追加 `task_id` 保证顺序稳定
+    pub sort: TaskSort,
 }
 
 impl TaskFilter {
@@ -429,6 +489,141 @@ impl TaskFilter {
         self.offset = Some(offset);
         self
     }
+    
+    pub fn with_sort(mut self, sort: TaskSort) -> Self {
+        self.sort = sort;
+        self
+    }
+}
+
+/// 最多排序键数（不含追加的 `task_id`）
+pub const MAX_SORT_KEYS: usize = 5;
+
+/// 可排序的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    TaskId,
+    CreatedAt,
+    StartedAt,
+    CompletedAt,
+    Priority,
+    Status,
+    WorkDirectory,
+    RetryCount,
+}
+
+impl SortField {
+    /// 白名单中的字段名
+    pub const NAMES: &'static [&'static str] = &[
+        "task_id",
+        "created_at",
+        "started_at",
+        "completed_at",
+        "priority",
+        "status",
+        "work_directory",
+        "retry_count",
+    ];
+    
+    fn parse(name: &str) -> Option<Self> {
+        let field = match name {
+            "task_id" => Self::TaskId,
+            "created_at" => Self::CreatedAt,
+            "started_at" => Self::StartedAt,
+            "completed_at" => Self::CompletedAt,
+            "priority" => Self::Priority,
+            "status" => Self::Status,
+            "work_directory" => Self::WorkDirectory,
+            "retry_count" => Self::RetryCount,
+            _ => return None,
+        };
+        Some(field)
+    }
+    
+    fn compare(self, a: &Task, b: &Task) -> std::cmp::Ordering {
+        // 状态按小写名称比较，与 task-orchestrator 的SQLite实现一致
+        let status = |task: &Task| format!("{:?}", task.status).to_lowercase();
+        match self {
+            Self::TaskId => a.id.to_string().cmp(&b.id.to_string()),
+            Self::CreatedAt => a.created_at.cmp(&b.created_at),
+            Self::StartedAt => a.started_at.cmp(&b.started_at),
+            Self::CompletedAt => a.completed_at.cmp(&b.completed_at),
+            Self::Priority => a.priority.as_i32().cmp(&b.priority.as_i32()),
+            Self::Status => status(a).cmp(&status(b)),
+            Self::WorkDirectory => a.work_directory.cmp(&b.work_directory),
+            Self::RetryCount => a.retry_count.cmp(&b.retry_count),
+        }
+    }
+}
+
+/// 单个排序键
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    /// 是否倒序
+    pub descending: bool,
+}
+
+/// 任务列表排序规则
+///
+/// 格式为 `field[:asc|desc],...`，例如 `priority:desc,created_at:asc`。排序值全部相同时按 `task_id` 升序，
+/// 多次查询和分页之间顺序不变。空时间小于任何时间。默认按创建时间倒序。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskSort {
+    keys: Vec<SortKey>,
+}
+
+impl TaskSort {
+    /// 解析排序参数，字段必须在 [`SortField::NAMES`] 白名单内
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut keys: Vec<SortKey> = Vec::new();
+        for part in input.split(',') {
+            let part = part.trim();
+            let (name, direction) = match part.split_once(':') {
+                Some((name, direction)) => (name.trim(), direction.trim()),
+                None => (part, "asc"),
+            };
+            let field = SortField::parse(name).ok_or_else(|| {
+                format!("Invalid sort field: '{}' (allowed: {})", name, SortField::NAMES.join(", "))
+            })?;
+            let descending = match direction.to_lowercase().as_str() {
+                "asc" => false,
+                "desc" => true,
+                _ => return Err(format!("Invalid sort direction: '{}' (expected asc or desc)", direction)),
+            };
+            if keys.iter().any(|k| k.field == field) {
+                return Err(format!("Duplicate sort field: {}", name));
+            }
+            keys.push(SortKey { field, descending });
+        }
+        if keys.len() > MAX_SORT_KEYS {
+            return Err(format!("Too many sort keys (max {})", MAX_SORT_KEYS));
+        }
+        Ok(Self { keys })
+    }
+    
+    pub fn keys(&self) -> &[SortKey] {
+        &self.keys
+    }
+    
+    /// 按排序规则比较两个任务
+    pub fn compare(&self, a: &Task, b: &Task) -> std::cmp::Ordering {
+        self.keys
+            .iter()
+            .map(|key| if key.descending { key.field.compare(b, a) } else { key.field.compare(a, b) })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| SortField::TaskId.compare(a, b))
+    }
+    
+    pub fn sort(&self, tasks: &mut [Task]) {
+        tasks.sort_by(|a, b| self.compare(a, b));
+    }
+}
+
+impl Default for TaskSort {
+    fn default() -> Self {
+        Self { keys: vec![SortKey { field: SortField::CreatedAt, descending: true }] }
+    }
 }
 
 /// 任务统计
@@ -455,6 +650,280 @@ impl TaskStatistics {
     }
 }
 
+/// 统计时间范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatisticsRange {
+    Today,
+    Yesterday,
+    Last24h,
+    Last7d,
+    Last30d,
+    All,
+}
+
+impl StatisticsRange {
+    /// 解析时间范围名称，同时接受 `24h`、`7d` 这样的简写
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+            "today" => Some(StatisticsRange::Today),
+            "yesterday" => Some(StatisticsRange::Yesterday),
+            "last_24h" | "24h" | "last_day" => Some(StatisticsRange::Last24h),
+            "last_7d" | "7d" | "last_week" => Some(StatisticsRange::Last7d),
+            "last_30d" | "30d" | "last_month" => Some(StatisticsRange::Last30d),
+            "all" => Some(StatisticsRange::All),
+            _ => None,
+        }
+    }
+
+    /// 相对于 `now` 的起止时间（UTC），左闭右开
+    pub fn bounds(&self, now: DateTime<Utc>) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+        let midnight = now.date_naive().and_hms_opt(0, 0, 0).map(|t| t.and_utc());
+        match self {
+            StatisticsRange::Today => (midnight, None),
+            StatisticsRange::Yesterday => (midnight.map(|m| m - chrono::Duration::days(1)), midnight),
+            StatisticsRange::Last24h => (Some(now - chrono::Duration::hours(24)), None),
+            StatisticsRange::Last7d => (Some(now - chrono::Duration::days(7)), None),
+            StatisticsRange::Last30d => (Some(now - chrono::Duration::days(30)), None),
+            StatisticsRange::All => (None, None),
+        }
+    }
+}
+
+/// 统计分组维度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatisticsGroupBy {
+    Status,
+    Priority,
+    WorkDirectory,
+    Tag,
+    Day,
+}
+
+impl StatisticsGroupBy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+            "status" => Some(StatisticsGroupBy::Status),
+            "priority" => Some(StatisticsGroupBy::Priority),
+            "work_directory" | "directory" | "dir" => Some(StatisticsGroupBy::WorkDirectory),
+            "tag" | "tags" => Some(StatisticsGroupBy::Tag),
+            "day" | "date" => Some(StatisticsGroupBy::Day),
+            _ => None,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            StatisticsGroupBy::Status => "status",
+            StatisticsGroupBy::Priority => "priority",
+            StatisticsGroupBy::WorkDirectory => "work directory",
+            StatisticsGroupBy::Tag => "tag",
+            StatisticsGroupBy::Day => "day",
+        }
+    }
+
+    fn keys(&self, task: &Task) -> Vec<String> {
+        match self {
+            StatisticsGroupBy::Status => vec![format!("{:?}", task.status)],
+            StatisticsGroupBy::Priority => vec![format!("{:?}", task.priority)],
+            StatisticsGroupBy::WorkDirectory => vec![task.work_directory.clone()],
+            StatisticsGroupBy::Tag if task.tags.is_empty() => vec!["(untagged)".to_string()],
+            StatisticsGroupBy::Tag => task.tags.clone(),
+            StatisticsGroupBy::Day => vec![task.last_activity_at().format("%Y-%m-%d").to_string()],
+        }
+    }
+}
+
+/// 统计查询
+///
+/// 任务按最近一次活动时间（完成时间，其次开始时间，最后创建时间）归入时间范围，
+/// 因此"昨天失败的任务"指昨天进入失败状态的任务。
+#[derive(Debug, Clone, Default)]
+pub struct StatisticsQuery {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// 工作目录前缀（按路径组件匹配）
+    pub work_directory: Option<String>,
+    pub status: Option<TaskStatus>,
+    pub group_by: Option<StatisticsGroupBy>,
+}
+
+/// 报告中列出的失败任务上限
+const MAX_FAILURE_SAMPLES: usize = 5;
+
+/// 分组统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatisticsBucket {
+    pub key: String,
+    pub total: u64,
+    pub completed: u64,
+    pub failed: u64,
+    pub cancelled: u64,
+    pub active: u64,
+    /// 已执行任务的资源用量
+    #[serde(default)]
+    pub resources: ResourceUsageTotals,
+}
+
+impl StatisticsBucket {
+    fn add(&mut self, task: &Task) {
+        self.total += 1;
+        if let Some(usage) = task.result.as_ref().and_then(TaskResult::resource_usage) {
+            self.resources.add(&usage);
+        }
+        match task.status {
+            TaskStatus::Completed => self.completed += 1,
+            TaskStatus::Failed => self.failed += 1,
+            TaskStatus::Cancelled => self.cancelled += 1,
+            TaskStatus::Waiting | TaskStatus::Working => self.active += 1,
+        }
+    }
+
+    /// 已结束任务中的成功率
+    pub fn success_rate(&self) -> Option<f64> {
+        let finished = self.completed + self.failed + self.cancelled;
+        (finished > 0).then(|| self.completed as f64 / finished as f64)
+    }
+}
+
+/// 失败任务摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureSample {
+    pub task_id: TaskId,
+    pub work_directory: String,
+    pub failed_at: DateTime<Utc>,
+    pub error: Option<String>,
+}
+
+/// 统计报告：一段便于阅读的摘要加原始数字
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatisticsReport {
+    pub summary: String,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub work_directory: Option<String>,
+    pub group_by: Option<StatisticsGroupBy>,
+    pub totals: StatisticsBucket,
+    pub buckets: Vec<StatisticsBucket>,
+    /// 最近的失败任务
+    pub recent_failures: Vec<FailureSample>,
+}
+
+impl StatisticsQuery {
+    /// 任务是否符合查询条件
+    pub fn matches(&self, task: &Task) -> bool {
+        let at = task.last_activity_at();
+        self.since.map_or(true, |since| at >= since)
+            && self.until.map_or(true, |until| at < until)
+            && self.status.as_ref().map_or(true, |status| &task.status == status)
+            && self.work_directory.as_deref().map_or(true, |dir| {
+                std::path::Path::new(&task.work_directory).starts_with(dir)
+            })
+    }
+
+    /// 汇总任务生成报告
+    pub fn report(&self, tasks: &[Task]) -> StatisticsReport {
+        let mut totals = StatisticsBucket { key: "total".to_string(), ..Default::default() };
+        let mut buckets: HashMap<String, StatisticsBucket> = HashMap::new();
+        let mut failures = Vec::new();
+
+        for task in tasks.iter().filter(|task| self.matches(task)) {
+            totals.add(task);
+            if let Some(group_by) = self.group_by {
+                for key in group_by.keys(task) {
+                    buckets
+                        .entry(key.clone())
+                        .or_insert_with(|| StatisticsBucket { key, ..Default::default() })
+                        .add(task);
+                }
+            }
+            if task.status == TaskStatus::Failed {
+                failures.push(FailureSample {
+                    task_id: task.id,
+                    work_directory: task.work_directory.clone(),
+                    failed_at: task.last_activity_at(),
+                    error: task.error_message.clone(),
+                });
+            }
+        }
+
+        let mut buckets: Vec<_> = buckets.into_values().collect();
+        match self.group_by {
+            Some(StatisticsGroupBy::Day) => buckets.sort_by(|a, b| a.key.cmp(&b.key)),
+            _ => buckets.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.key.cmp(&b.key))),
+        }
+        failures.sort_by(|a, b| b.failed_at.cmp(&a.failed_at));
+        failures.truncate(MAX_FAILURE_SAMPLES);
+
+        StatisticsReport {
+            summary: self.summarize(&totals, &buckets, &failures),
+            since: self.since,
+            until: self.until,
+            work_directory: self.work_directory.clone(),
+            group_by: self.group_by,
+            totals,
+            buckets,
+            recent_failures: failures,
+        }
+    }
+
+    fn summarize(&self, totals: &StatisticsBucket, buckets: &[StatisticsBucket], failures: &[FailureSample]) -> String {
+        let format_time = |t: DateTime<Utc>| t.format("%Y-%m-%d %H:%M UTC").to_string();
+        let mut scope = String::new();
+        if let Some(status) = &self.status {
+            scope.push_str(&format!(" {:?}", status));
+        }
+        let mut summary = format!("{}{} task(s)", totals.total, scope);
+        if let Some(dir) = &self.work_directory {
+            summary.push_str(&format!(" in {}", dir));
+        }
+        match (self.since, self.until) {
+            (Some(since), Some(until)) => summary.push_str(&format!(" from {} to {}", format_time(since), format_time(until))),
+            (Some(since), None) => summary.push_str(&format!(" since {}", format_time(since))),
+            (None, Some(until)) => summary.push_str(&format!(" before {}", format_time(until))),
+            (None, None) => {}
+        }
+        summary.push_str(&format!(
+            ": {} completed, {} failed, {} cancelled, {} active",
+            totals.completed, totals.failed, totals.cancelled, totals.active
+        ));
+        if let Some(rate) = totals.success_rate() {
+            summary.push_str(&format!(" (success rate {:.0}%)", rate * 100.0));
+        }
+        summary.push('.');
+        let resources = &totals.resources;
+        if resources.measured_executions > 0 {
+            summary.push_str(&format!(
+                " Resources over {} measured execution(s): {:.1}s CPU, {:.1}s wall, peak RSS {:.1} MiB, {:.1} MiB written.",
+                resources.measured_executions,
+                resources.cpu_time_ms as f64 / 1000.0,
+                resources.wall_time_ms as f64 / 1000.0,
+                resources.peak_rss_bytes as f64 / (1024.0 * 1024.0),
+                resources.bytes_written as f64 / (1024.0 * 1024.0),
+            ));
+        }
+
+        if let Some(group_by) = self.group_by {
+            let groups: Vec<String> = buckets
+                .iter()
+                .map(|b| format!("{} {} ({} failed)", b.key, b.total, b.failed))
+                .collect();
+            if !groups.is_empty() {
+                summary.push_str(&format!(" By {}: {}.", group_by.label(), groups.join(", ")));
+            }
+        }
+        if !failures.is_empty() {
+            let samples: Vec<String> = failures
+                .iter()
+                .map(|f| format!("{} in {}: {}", f.task_id, f.work_directory, f.error.as_deref().unwrap_or("no error message")))
+                .collect();
+            summary.push_str(&format!(" Recent failures: {}.", samples.join("; ")));
+        }
+        summary
+    }
+}
+
 /// 分页信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pagination {
@@ -1,8 +1,9 @@
 use std::process::Command;
 use std::time::Duration;
 use serde::{Serialize, Deserialize};
-use crate::domain::{Task, TaskResult};
+use crate::domain::{ResourceUsage, Task, TaskResult};
 use anyhow::{Result, Context, anyhow};
+use super::resource_usage::run_measured;
 use super::TaskExecutor;
 
 /// Claude Code 配置
@@ -62,10 +63,11 @@ impl ClaudeCodeExecutor {
         let mut command = self.build_claude_command(&system_prompt)?;
         
         // 执行命令
-        let output = self.execute_command(command).await?;
+        let (output, usage) = self.execute_command(command).await?;
         
         // 解析结果
-        let result = self.parse_output(output, start_time.elapsed())?;
+        let mut result = self.parse_output(output, start_time.elapsed())?;
+        result.set_resource_usage(&usage);
         
         Ok(result)
     }
@@ -127,11 +129,11 @@ impl ClaudeCodeExecutor {
         Ok(command)
     }
 
-    /// 执行命令
-    async fn execute_command(&self, mut command: Command) -> Result<String> {
+    /// 执行命令，同时采集进程的资源用量
+    async fn execute_command(&self, command: Command) -> Result<(String, ResourceUsage)> {
         // 使用 tokio 运行命令
         let output = tokio::task::spawn_blocking(move || {
-            command.output()
+            run_measured(command)
         })
         .await
         .context("Failed to spawn Claude Code process")?
@@ -143,7 +145,7 @@ impl ClaudeCodeExecutor {
         }
         
         let stdout = String::from_utf8_lossy(&output.stdout);
-        Ok(stdout.to_string())
+        Ok((stdout.to_string(), output.usage))
     }
 
     /// 解析输出
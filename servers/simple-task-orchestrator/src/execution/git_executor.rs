@@ -0,0 +1,298 @@
+//! # Git感知执行
+//!
+//! 任务元数据中指定 `git_ref` 时，执行前先检出该引用，执行后在结果中记录提交SHA：
+//!
+//! - `git_ref`: 要检出的分支、标签或提交
+//! - `git_clone`: 为 `true` 时在临时目录中克隆工作目录后执行，不改动工作目录本身
+//! - `git_repository`: 克隆来源（URL或路径），设置时总是在临时克隆中执行
+//! - `git_remote`: 就地检出时拉取引用的远程，默认 `origin`
+//!
+//! 就地检出要求工作目录没有未提交的修改，检出后工作目录处于分离HEAD状态。
+//! 结果元数据中记录 `git_base_commit`（检出的提交）、`git_commit`（执行后的HEAD）和 `git_dirty`（是否留有未提交的修改）。
+//! 临时克隆在执行结束后删除，其中新建的提交只以SHA的形式保留在结果中。
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+
+use super::{TaskExecutor, TaskExecutorFactory};
+use crate::domain::{Task, TaskResult};
+
+/// 启用Git感知执行的元数据键
+pub const GIT_REF_KEY: &str = "git_ref";
+
+/// 任务的Git检出要求
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitRefSpec {
+    pub git_ref: String,
+    pub remote: String,
+    /// 克隆来源，为 `None` 时就地检出
+    pub clone_from: Option<String>,
+}
+
+impl GitRefSpec {
+    /// 任务元数据是否指定了 `git_ref`
+    pub fn is_requested(task: &Task) -> bool {
+        task.metadata.as_ref().is_some_and(|metadata| metadata.contains_key(GIT_REF_KEY))
+    }
+
+    /// 从任务元数据解析，未指定 `git_ref` 时返回 `None`
+    pub fn from_task(task: &Task) -> Result<Option<Self>> {
+        let Some(metadata) = &task.metadata else {
+            return Ok(None);
+        };
+        let Some(git_ref) = metadata.get(GIT_REF_KEY) else {
+            return Ok(None);
+        };
+        let string = |key: &str| -> Result<Option<String>> {
+            match metadata.get(key) {
+                None | Some(serde_json::Value::Null) => Ok(None),
+                Some(serde_json::Value::String(value)) => Ok(Some(value.clone())),
+                Some(other) => Err(anyhow!("Task metadata {} must be a string, got {}", key, other)),
+            }
+        };
+
+        let git_ref = git_ref
+            .as_str()
+            .ok_or_else(|| anyhow!("Task metadata {} must be a string", GIT_REF_KEY))?
+            .to_string();
+        validate_argument("git_ref", &git_ref)?;
+        let remote = string("git_remote")?.unwrap_or_else(|| "origin".to_string());
+        validate_argument("git_remote", &remote)?;
+        let clone = metadata.get("git_clone").and_then(|v| v.as_bool()).unwrap_or(false);
+        let clone_from = match string("git_repository")? {
+            Some(repository) => Some(repository),
+            None if clone => Some(task.work_directory.clone()),
+            None => None,
+        };
+        if let Some(source) = &clone_from {
+            validate_argument("git_repository", source)?;
+        }
+        Ok(Some(Self { git_ref, remote, clone_from }))
+    }
+}
+
+/// 拒绝会被git当作选项或多个参数的值
+fn validate_argument(name: &str, value: &str) -> Result<()> {
+    if value.is_empty() || value.starts_with('-') || value.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(anyhow!("Invalid {}: {:?}", name, value));
+    }
+    Ok(())
+}
+
+/// 已检出的工作目录，临时克隆在释放时删除
+pub struct GitCheckout {
+    path: PathBuf,
+    temporary: bool,
+    base_commit: String,
+}
+
+impl GitCheckout {
+    /// 按要求准备工作目录并检出引用
+    pub fn prepare(task: &Task, spec: &GitRefSpec) -> Result<Self> {
+        let (path, temporary, remote) = match &spec.clone_from {
+            Some(source) => {
+                let path = std::env::temp_dir().join(format!("git-task-{}-{}", task.id, uuid::Uuid::new_v4()));
+                run_git(Path::new("."), &["clone", "--quiet", "--no-checkout", "--", source, &path.to_string_lossy()])
+                    .with_context(|| format!("Failed to clone {}", source))?;
+                (path, true, "origin")
+            }
+            None => {
+                let path = PathBuf::from(&task.work_directory);
+                let status = run_git(&path, &["status", "--porcelain", "--untracked-files=no"])?;
+                if !status.trim().is_empty() {
+                    return Err(anyhow!("Work directory {} has uncommitted changes", task.work_directory));
+                }
+                (path, false, spec.remote.as_str())
+            }
+        };
+        // 先构造，检出失败时临时克隆也会被删除
+        let mut checkout = Self { path, temporary, base_commit: String::new() };
+
+        // 能拉取时以远程为准，否则（无远程、离线、服务端不允许按SHA拉取）使用本地已有的引用
+        let has_remote = run_git(&checkout.path, &["remote", "get-url", remote]).is_ok();
+        let fetched = has_remote && run_git(&checkout.path, &["fetch", "--quiet", remote, &spec.git_ref]).is_ok();
+        let candidates = if fetched {
+            vec!["FETCH_HEAD^{commit}".to_string()]
+        } else {
+            vec![format!("{}^{{commit}}", spec.git_ref), format!("{}/{}^{{commit}}", remote, spec.git_ref)]
+        };
+        let commit = candidates
+            .iter()
+            .find_map(|target| run_git(&checkout.path, &["rev-parse", "--verify", "--quiet", target]).ok())
+            .ok_or_else(|| anyhow!("Git ref {} not found", spec.git_ref))?
+            .trim()
+            .to_string();
+        run_git(&checkout.path, &["checkout", "--quiet", "--detach", &commit])
+            .with_context(|| format!("Failed to check out {}", spec.git_ref))?;
+
+        checkout.base_commit = commit;
+        Ok(checkout)
+    }
+
+    /// 执行任务使用的目录
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 检出的提交
+    pub fn base_commit(&self) -> &str {
+        &self.base_commit
+    }
+
+    /// 当前HEAD提交
+    pub fn head(&self) -> Result<String> {
+        Ok(run_git(&self.path, &["rev-parse", "HEAD"])?.trim().to_string())
+    }
+
+    /// 是否有未提交的修改（含未跟踪文件）
+    pub fn is_dirty(&self) -> Result<bool> {
+        Ok(!run_git(&self.path, &["status", "--porcelain"])?.trim().is_empty())
+    }
+}
+
+impl Drop for GitCheckout {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = std::fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        // 不交互式询问凭据，避免拉取私有仓库时挂起
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Git感知执行器：检出引用后交给任务执行模式对应的执行器
+pub struct GitExecutor {
+    inner: Box<dyn TaskExecutor>,
+}
+
+impl GitExecutor {
+    pub fn new(inner: Box<dyn TaskExecutor>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskExecutor for GitExecutor {
+    async fn execute(&self, task: &Task) -> Result<TaskResult> {
+        let spec = GitRefSpec::from_task(task)?
+            .ok_or_else(|| anyhow!("Task {} does not specify {}", task.id, GIT_REF_KEY))?;
+
+        let prepared = {
+            let (task, spec) = (task.clone(), spec.clone());
+            tokio::task::spawn_blocking(move || GitCheckout::prepare(&task, &spec))
+                .await
+                .context("Failed to spawn git checkout")??
+        };
+
+        // 执行器按工作目录构造，检出目录可能是临时克隆
+        let mut checked_out = task.clone();
+        checked_out.work_directory = prepared.path().to_string_lossy().into_owned();
+        let executor = TaskExecutorFactory::create_mode_executor(&checked_out);
+        let result = executor.execute(&checked_out).await;
+
+        // 临时克隆随 `prepared` 在阻塞线程中删除
+        let base_commit = prepared.base_commit().to_string();
+        let (head, dirty) = tokio::task::spawn_blocking(move || (prepared.head(), prepared.is_dirty()))
+            .await
+            .context("Failed to spawn git status")?;
+        let mut result = result?;
+
+        let metadata = result.metadata.get_or_insert_with(Default::default);
+        metadata.insert("git_ref".to_string(), serde_json::json!(spec.git_ref));
+        metadata.insert("git_base_commit".to_string(), serde_json::json!(base_commit));
+        metadata.insert("git_commit".to_string(), serde_json::json!(head?));
+        metadata.insert("git_dirty".to_string(), serde_json::json!(dirty?));
+        Ok(result)
+    }
+
+    async fn validate(&self) -> Result<bool> {
+        let git_available = tokio::task::spawn_blocking(|| run_git(Path::new("."), &["--version"]).is_ok())
+            .await
+            .context("Failed to spawn git validation")?;
+        Ok(git_available && self.inner.validate().await?)
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::TaskPriority;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_git_executor_checks_out_ref() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        git(repo, &["init", "--quiet"]);
+        std::fs::write(repo.join("file.txt"), "v1\n").unwrap();
+        git(repo, &["add", "file.txt"]);
+        git(repo, &["commit", "--quiet", "-m", "v1"]);
+        let first = git(repo, &["rev-parse", "HEAD"]);
+        git(repo, &["tag", "v1"]);
+        std::fs::write(repo.join("file.txt"), "v2\n").unwrap();
+        git(repo, &["commit", "--quiet", "-am", "v2"]);
+
+        let mut task = Task::new(repo.display().to_string(), "Test".to_string(), TaskPriority::Medium, vec![]);
+        assert!(GitRefSpec::from_task(&task).unwrap().is_none());
+
+        // 临时克隆，不改动原工作目录
+        task.metadata = Some(
+            [("git_ref".to_string(), serde_json::json!("v1")), ("git_clone".to_string(), serde_json::json!(true))]
+                .into(),
+        );
+        let executor = TaskExecutorFactory::create_executor(&task);
+        assert_eq!(executor.name(), "standard");
+        let result = executor.execute(&task).await.unwrap();
+        let metadata = result.metadata.unwrap();
+        assert_eq!(metadata["git_base_commit"], serde_json::json!(first));
+        assert_eq!(metadata["git_commit"], serde_json::json!(first));
+        assert_eq!(metadata["git_dirty"], serde_json::json!(false));
+        assert!(!result.output.contains(&repo.display().to_string()));
+        assert_eq!(std::fs::read_to_string(repo.join("file.txt")).unwrap(), "v2\n");
+
+        // 就地检出
+        task.metadata = Some([("git_ref".to_string(), serde_json::json!(first.clone()))].into());
+        let result = executor.execute(&task).await.unwrap();
+        assert_eq!(result.metadata.unwrap()["git_commit"], serde_json::json!(first));
+        assert_eq!(std::fs::read_to_string(repo.join("file.txt")).unwrap(), "v1\n");
+
+        // 有未提交修改时拒绝就地检出
+        std::fs::write(repo.join("file.txt"), "local edit\n").unwrap();
+        assert!(executor.execute(&task).await.is_err());
+
+        task.metadata = Some([("git_ref".to_string(), serde_json::json!("--upload-pack=evil"))].into());
+        assert!(GitRefSpec::from_task(&task).is_err());
+    }
+}
@@ -20,6 +20,10 @@
 //! 
 //! - `TaskExecutor`: 任务执行器特征，定义了执行器的基本接口
 //! - `TaskExecutorFactory`: 执行器工厂，根据任务配置创建合适的执行器
+//! - `PostProcessorRegistry`: 按执行模式对执行结果做后处理（去除ANSI、截断转存、提取JSON、正则脱敏）
+//! - `WorkspaceSnapshot`: ClaudeCode执行前后的工作目录快照，生成变更补丁
+//! - `GitExecutor`: 任务元数据指定 `git_ref` 时先检出该引用再执行，并记录执行后的提交SHA
+//! - `run_measured` / `ResourceMetrics`: 采集执行器子进程的资源用量（墙钟时间、CPU时间、峰值内存、写入字节数）并导出Prometheus直方图
 //! 
 //! ## 使用示例
 //! 
@@ -93,9 +97,19 @@
 //! - 支持执行器的动态注册和发现
 //! - 提供了灵活的执行器选择机制
 
+#[cfg(feature = "claude-executor")]
 pub mod claude_code_executor;
+pub mod git_executor;
+pub mod post_processing;
+pub mod resource_usage;
+pub mod workspace_snapshot;
 
+#[cfg(feature = "claude-executor")]
 pub use claude_code_executor::{ClaudeCodeExecutor, ClaudeCodeConfig};
+pub use git_executor::{GitCheckout, GitExecutor, GitRefSpec};
+pub use post_processing::{PostProcessStep, PostProcessorRegistry};
+pub use resource_usage::{run_measured, MeasuredOutput, ResourceMetrics};
+pub use workspace_snapshot::{WorkspaceDiff, WorkspaceSnapshot};
 
 use crate::domain::{Task, TaskResult};
 use anyhow::Result;
@@ -136,16 +150,50 @@ impl TaskExecutor for StandardExecutor {
     }
 }
 
+/// 未编译进当前二进制的执行器，执行时返回错误，任务按失败处理
+#[cfg(not(feature = "claude-executor"))]
+pub struct UnavailableExecutor {
+    name: &'static str,
+    feature: &'static str,
+}
+
+#[cfg(not(feature = "claude-executor"))]
+#[async_trait::async_trait]
+impl TaskExecutor for UnavailableExecutor {
+    async fn execute(&self, _task: &Task) -> Result<TaskResult> {
+        anyhow::bail!("Executor '{}' is not available: built without the `{}` feature", self.name, self.feature)
+    }
+
+    async fn validate(&self) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
 /// 任务执行器工厂
 pub struct TaskExecutorFactory;
 
 impl TaskExecutorFactory {
-    /// 创建任务执行器
+    /// 创建任务执行器，任务元数据指定 `git_ref` 时包装为Git感知执行器
     pub fn create_executor(task: &Task) -> Box<dyn TaskExecutor> {
+        let executor = Self::create_mode_executor(task);
+        if GitRefSpec::is_requested(task) {
+            Box::new(GitExecutor::new(executor))
+        } else {
+            executor
+        }
+    }
+
+    /// 创建任务执行模式对应的执行器
+    pub fn create_mode_executor(task: &Task) -> Box<dyn TaskExecutor> {
         match task.execution_mode {
             crate::domain::ExecutionMode::Standard => {
                 Box::new(StandardExecutor)
             }
+            #[cfg(feature = "claude-executor")]
             crate::domain::ExecutionMode::ClaudeCode => {
                 let config = ClaudeCodeConfig {
                     work_directory: task.work_directory.clone(),
@@ -153,6 +201,10 @@ impl TaskExecutorFactory {
                 };
                 Box::new(ClaudeCodeExecutor::new(config))
             }
+            #[cfg(not(feature = "claude-executor"))]
+            crate::domain::ExecutionMode::ClaudeCode => {
+                Box::new(UnavailableExecutor { name: "claude_code", feature: "claude-executor" })
+            }
             crate::domain::ExecutionMode::Custom(ref executor_name) => {
                 match executor_name.as_str() {
                     "standard" => Box::new(StandardExecutor),
@@ -0,0 +1,267 @@
+//! # 执行结果后处理
+//!
+//! 执行器返回的 `TaskResult` 在保存前按执行模式依次经过配置的后处理步骤：
+//!
+//! - `strip_ansi`: 去除ANSI转义序列（颜色、光标控制等）
+//! - `truncate`: 输出超过 `max_bytes` 时截断，可将完整输出转存到 `offload_dir` 下的产物文件
+//! - `extract_json`: 从混合文本中提取第一个完整的JSON对象或数组，写入 `metadata.extracted_json`
+//! - `regex_redact`: 按自定义正则表达式替换敏感内容
+//!
+//! 流水线按执行模式名称（`standard`、`claude_code` 或自定义执行器名称）选择，
+//! 未单独配置的模式使用 `default` 流水线。
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{ExecutionMode, Task, TaskResult};
+
+/// 未单独配置时使用的流水线名称
+pub const DEFAULT_PIPELINE: &str = "default";
+
+/// 后处理步骤配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PostProcessStep {
+    /// 去除ANSI转义序列
+    StripAnsi,
+    /// 截断过长的输出
+    Truncate {
+        max_bytes: usize,
+        #[serde(default)]
+        offload_dir: Option<PathBuf>,
+    },
+    /// 从混合文本中提取JSON
+    ExtractJson,
+    /// 按正则表达式替换
+    RegexRedact {
+        pattern: String,
+        #[serde(default = "default_replacement")]
+        replacement: String,
+    },
+}
+
+fn default_replacement() -> String {
+    "[REDACTED]".to_string()
+}
+
+impl PostProcessStep {
+    fn name(&self) -> &'static str {
+        match self {
+            PostProcessStep::StripAnsi => "strip_ansi",
+            PostProcessStep::Truncate { .. } => "truncate",
+            PostProcessStep::ExtractJson => "extract_json",
+            PostProcessStep::RegexRedact { .. } => "regex_redact",
+        }
+    }
+}
+
+/// 已编译的后处理步骤
+enum CompiledStep {
+    StripAnsi(Regex),
+    Truncate { max_bytes: usize, offload_dir: Option<PathBuf> },
+    ExtractJson,
+    RegexRedact { pattern: Regex, replacement: String },
+}
+
+/// 后处理流水线
+pub struct PostProcessingPipeline {
+    steps: Vec<(&'static str, CompiledStep)>,
+}
+
+impl PostProcessingPipeline {
+    /// 编译步骤配置，正则表达式无效时返回错误
+    pub fn new(steps: &[PostProcessStep]) -> Result<Self> {
+        let steps = steps
+            .iter()
+            .map(|step| {
+                let compiled = match step {
+                    PostProcessStep::StripAnsi => CompiledStep::StripAnsi(
+                        Regex::new(r"\x1b(\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(\x07|\x1b\\)|[@-Z\\-_])")
+                            .expect("valid ANSI pattern"),
+                    ),
+                    PostProcessStep::Truncate { max_bytes, offload_dir } => CompiledStep::Truncate {
+                        max_bytes: *max_bytes,
+                        offload_dir: offload_dir.clone(),
+                    },
+                    PostProcessStep::ExtractJson => CompiledStep::ExtractJson,
+                    PostProcessStep::RegexRedact { pattern, replacement } => CompiledStep::RegexRedact {
+                        pattern: Regex::new(pattern)
+                            .with_context(|| format!("Invalid regex_redact pattern: {}", pattern))?,
+                        replacement: replacement.clone(),
+                    },
+                };
+                Ok((step.name(), compiled))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { steps })
+    }
+
+    /// 依次执行所有步骤，在 `metadata.post_processing` 中记录已执行的步骤
+    pub fn apply(&self, task: &Task, mut result: TaskResult) -> Result<TaskResult> {
+        if self.steps.is_empty() {
+            return Ok(result);
+        }
+        let mut metadata = result.metadata.take().unwrap_or_default();
+        for (_, step) in &self.steps {
+            match step {
+                CompiledStep::StripAnsi(pattern) => {
+                    result.output = pattern.replace_all(&result.output, "").into_owned();
+                }
+                CompiledStep::Truncate { max_bytes, offload_dir } => {
+                    truncate(task, &mut result.output, *max_bytes, offload_dir.as_ref(), &mut metadata)?;
+                }
+                CompiledStep::ExtractJson => {
+                    if let Some(value) = extract_json(&result.output) {
+                        metadata.insert("extracted_json".to_string(), value);
+                    }
+                }
+                CompiledStep::RegexRedact { pattern, replacement } => {
+                    result.output = pattern.replace_all(&result.output, replacement.as_str()).into_owned();
+                }
+            }
+        }
+        let applied: Vec<&str> = self.steps.iter().map(|(name, _)| *name).collect();
+        metadata.insert("post_processing".to_string(), serde_json::json!(applied));
+        result.metadata = Some(metadata);
+        Ok(result)
+    }
+}
+
+/// 截断输出，配置了转存目录时先把完整输出写入 `<offload_dir>/<task_id>.log`
+fn truncate(
+    task: &Task,
+    output: &mut String,
+    max_bytes: usize,
+    offload_dir: Option<&PathBuf>,
+    metadata: &mut HashMap<String, serde_json::Value>,
+) -> Result<()> {
+    if output.len() <= max_bytes {
+        return Ok(());
+    }
+    let original_bytes = output.len();
+    let artifact = match offload_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create offload directory {}", dir.display()))?;
+            let path = dir.join(format!("{}.log", task.id));
+            std::fs::write(&path, output.as_bytes())
+                .with_context(|| format!("Failed to offload output to {}", path.display()))?;
+            Some(path.display().to_string())
+        }
+        None => None,
+    };
+
+    let mut cut = max_bytes;
+    while !output.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    output.truncate(cut);
+    match &artifact {
+        Some(path) => output.push_str(&format!("\n[truncated {} bytes, full output: {}]", original_bytes - cut, path)),
+        None => output.push_str(&format!("\n[truncated {} bytes]", original_bytes - cut)),
+    }
+
+    metadata.insert("original_output_bytes".to_string(), serde_json::json!(original_bytes));
+    if let Some(path) = artifact {
+        metadata.insert("output_artifact".to_string(), serde_json::json!(path));
+    }
+    Ok(())
+}
+
+/// 提取文本中第一个能完整解析的JSON对象或数组
+fn extract_json(text: &str) -> Option<serde_json::Value> {
+    text.char_indices()
+        .filter(|(_, c)| *c == '{' || *c == '[')
+        .find_map(|(start, _)| {
+            let mut stream = serde_json::Deserializer::from_str(&text[start..]).into_iter::<serde_json::Value>();
+            stream.next()?.ok().filter(|value| value.is_object() || value.is_array())
+        })
+}
+
+/// 按执行模式选择的后处理流水线集合
+#[derive(Default)]
+pub struct PostProcessorRegistry {
+    pipelines: HashMap<String, PostProcessingPipeline>,
+}
+
+impl PostProcessorRegistry {
+    /// 根据配置（执行模式名称 → 步骤列表）构建
+    pub fn from_config(config: &HashMap<String, Vec<PostProcessStep>>) -> Result<Self> {
+        let pipelines = config
+            .iter()
+            .map(|(mode, steps)| {
+                let pipeline = PostProcessingPipeline::new(steps)
+                    .with_context(|| format!("Invalid post-processing pipeline for '{}'", mode))?;
+                Ok((mode.clone(), pipeline))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+        Ok(Self { pipelines })
+    }
+
+    /// 执行模式对应的流水线名称
+    pub fn mode_name(mode: &ExecutionMode) -> &str {
+        match mode {
+            ExecutionMode::Standard => "standard",
+            ExecutionMode::ClaudeCode => "claude_code",
+            ExecutionMode::Custom(name) => name,
+        }
+    }
+
+    /// 对任务结果执行其执行模式对应的流水线
+    pub fn process(&self, task: &Task, result: TaskResult) -> Result<TaskResult> {
+        let pipeline = self
+            .pipelines
+            .get(Self::mode_name(&task.execution_mode))
+            .or_else(|| self.pipelines.get(DEFAULT_PIPELINE));
+        match pipeline {
+            Some(pipeline) => pipeline.apply(task, result),
+            None => Ok(result),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::TaskPriority;
+
+    #[test]
+    fn test_post_processing_pipeline() {
+        let dir = tempfile::tempdir().unwrap();
+        let config: HashMap<String, Vec<PostProcessStep>> = serde_json::from_value(serde_json::json!({
+            "claude_code": [
+                { "type": "strip_ansi" },
+                { "type": "regex_redact", "pattern": "sk-[A-Za-z0-9]+" },
+                { "type": "extract_json" },
+                { "type": "truncate", "max_bytes": 40, "offload_dir": dir.path() }
+            ]
+        }))
+        .unwrap();
+        let registry = PostProcessorRegistry::from_config(&config).unwrap();
+
+        let mut task = Task::new("/test".to_string(), "Test".to_string(), TaskPriority::Medium, vec![]);
+        let output = "\x1b[32mdone\x1b[0m key sk-abc123 result: {\"files\": 3} trailing text that is long";
+        let unchanged = registry.process(&task, TaskResult::success(output.to_string())).unwrap();
+        assert_eq!(unchanged.output, output);
+
+        task.execution_mode = ExecutionMode::ClaudeCode;
+        let result = registry.process(&task, TaskResult::success(output.to_string())).unwrap();
+        let metadata = result.metadata.unwrap();
+        assert!(result.output.starts_with("done key [REDACTED] result: {\"files\":"));
+        assert!(result.output.contains("[truncated"));
+        assert_eq!(metadata["extracted_json"], serde_json::json!({ "files": 3 }));
+        let artifact = metadata["output_artifact"].as_str().unwrap();
+        assert!(std::fs::read_to_string(artifact).unwrap().ends_with("that is long"));
+        assert_eq!(metadata["post_processing"][0], "strip_ansi");
+
+        let invalid = HashMap::from([(
+            DEFAULT_PIPELINE.to_string(),
+            vec![PostProcessStep::RegexRedact { pattern: "(".to_string(), replacement: String::new() }],
+        )]);
+        assert!(PostProcessorRegistry::from_config(&invalid).is_err());
+    }
+}
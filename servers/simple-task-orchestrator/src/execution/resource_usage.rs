@@ -0,0 +1,181 @@
+//! # 执行资源用量
+//!
+//! 执行器启动的子进程退出时通过 `wait4` 取得内核统计的资源用量（墙钟时间、CPU时间、峰值内存、写入字节数），
+//! 记录在任务结果的 `metadata.resource_usage` 中（见 [`ResourceUsage`]），汇总到执行统计，并以Prometheus直方图导出，供容量规划使用。
+//!
+//! CPU时间、峰值内存和写入字节数只在Unix平台上采集，其他平台只有墙钟时间。
+//! 写入字节数来自 `ru_oublock`（按512字节块计），只统计实际落到存储设备的写入。
+
+use std::io::{self, Read};
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::Instant;
+
+use prometheus::{Encoder, HistogramOpts, HistogramVec, Registry, TextEncoder};
+
+use crate::domain::ResourceUsage;
+
+/// 子进程的输出和资源用量
+pub struct MeasuredOutput {
+    pub status: ExitStatus,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub usage: ResourceUsage,
+}
+
+/// 运行命令并采集资源用量，阻塞直到子进程退出
+pub fn run_measured(mut command: Command) -> io::Result<MeasuredOutput> {
+    let started = Instant::now();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // 分别读取两个管道，避免任一管道写满后子进程阻塞
+    let stdout = child.stdout.take().map(read_in_background);
+    let stderr = child.stderr.take().map(read_in_background);
+
+    #[cfg(unix)]
+    let (status, mut usage) = wait_with_rusage(&child)?;
+    #[cfg(not(unix))]
+    let (status, mut usage) = (child.wait()?, ResourceUsage::default());
+    usage.wall_time_ms = started.elapsed().as_millis() as u64;
+
+    let join = |reader: Option<std::thread::JoinHandle<io::Result<Vec<u8>>>>| -> io::Result<Vec<u8>> {
+        match reader {
+            Some(handle) => handle.join().map_err(|_| io::Error::other("output reader panicked"))?,
+            None => Ok(Vec::new()),
+        }
+    };
+    Ok(MeasuredOutput { status, stdout: join(stdout)?, stderr: join(stderr)?, usage })
+}
+
+fn read_in_background(mut pipe: impl Read + Send + 'static) -> std::thread::JoinHandle<io::Result<Vec<u8>>> {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        pipe.read_to_end(&mut buffer)?;
+        Ok(buffer)
+    })
+}
+
+/// 用 `wait4` 回收子进程，同时取得该进程的资源用量
+#[cfg(unix)]
+fn wait_with_rusage(child: &std::process::Child) -> io::Result<(ExitStatus, ResourceUsage)> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let pid = child.id() as libc::pid_t;
+    let mut status = 0;
+    // SAFETY: rusage 是纯数据结构，全零是合法值；wait4 只写入传入的两个指针
+    let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        let ret = unsafe { libc::wait4(pid, &mut status, 0, &mut rusage) };
+        if ret == pid {
+            break;
+        }
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+
+    let millis = |time: libc::timeval| time.tv_sec as u64 * 1000 + time.tv_usec as u64 / 1000;
+    // Linux 上 ru_maxrss 以KB为单位，macOS 上以字节为单位
+    let max_rss_bytes = if cfg!(target_os = "macos") { rusage.ru_maxrss as u64 } else { rusage.ru_maxrss as u64 * 1024 };
+    let usage = ResourceUsage {
+        wall_time_ms: 0,
+        cpu_time_ms: Some(millis(rusage.ru_utime) + millis(rusage.ru_stime)),
+        max_rss_bytes: Some(max_rss_bytes),
+        bytes_written: Some(rusage.ru_oublock as u64 * 512),
+    };
+    Ok((ExitStatus::from_raw(status), usage))
+}
+
+/// 资源用量的Prometheus直方图，按执行模式区分
+pub struct ResourceMetrics {
+    registry: Registry,
+    wall_time: HistogramVec,
+    cpu_time: HistogramVec,
+    max_rss: HistogramVec,
+    bytes_written: HistogramVec,
+}
+
+impl ResourceMetrics {
+    pub fn new() -> Self {
+        let histogram = |name: &str, help: &str, buckets: Vec<f64>| {
+            HistogramVec::new(HistogramOpts::new(name, help).buckets(buckets), &["execution_mode"])
+                .expect("valid histogram options")
+        };
+        let seconds = vec![1.0, 5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0, 3600.0];
+        let bytes = prometheus::exponential_buckets(1024.0 * 1024.0, 4.0, 8).expect("valid buckets");
+
+        let metrics = Self {
+            registry: Registry::new(),
+            wall_time: histogram("task_execution_wall_seconds", "Wall time of task executions", seconds.clone()),
+            cpu_time: histogram("task_execution_cpu_seconds", "CPU time (user + system) of task executions", seconds),
+            max_rss: histogram("task_execution_max_rss_bytes", "Peak resident memory of task executions", bytes.clone()),
+            bytes_written: histogram("task_execution_written_bytes", "Bytes written to storage by task executions", bytes),
+        };
+        for collector in [&metrics.wall_time, &metrics.cpu_time, &metrics.max_rss, &metrics.bytes_written] {
+            metrics.registry.register(Box::new(collector.clone())).expect("unique metric names");
+        }
+        metrics
+    }
+
+    /// 记录一次执行的资源用量
+    pub fn observe(&self, execution_mode: &str, usage: &ResourceUsage) {
+        self.wall_time.with_label_values(&[execution_mode]).observe(usage.wall_time_ms as f64 / 1000.0);
+        if let Some(cpu_time_ms) = usage.cpu_time_ms {
+            self.cpu_time.with_label_values(&[execution_mode]).observe(cpu_time_ms as f64 / 1000.0);
+        }
+        if let Some(max_rss_bytes) = usage.max_rss_bytes {
+            self.max_rss.with_label_values(&[execution_mode]).observe(max_rss_bytes as f64);
+        }
+        if let Some(bytes_written) = usage.bytes_written {
+            self.bytes_written.with_label_values(&[execution_mode]).observe(bytes_written as f64);
+        }
+    }
+
+    /// Prometheus文本格式
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            tracing::warn!("Failed to encode resource metrics: {}", e);
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+impl Default for ResourceMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_measured_reports_usage() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "i=0; while [ $i -lt 20000 ]; do i=$((i+1)); done; echo done; echo warn >&2"]);
+        let output = run_measured(command).unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"done\n");
+        assert_eq!(output.stderr, b"warn\n");
+        assert!(output.usage.cpu_time_ms.is_some());
+        assert!(output.usage.max_rss_bytes.unwrap() > 0);
+
+        let mut result = crate::domain::TaskResult::success("done".to_string());
+        result.set_resource_usage(&output.usage);
+        assert_eq!(result.resource_usage(), Some(output.usage.clone()));
+
+        let metrics = ResourceMetrics::new();
+        metrics.observe("claude_code", &output.usage);
+        let text = metrics.render();
+        assert!(text.contains("task_execution_cpu_seconds_count{execution_mode=\"claude_code\"} 1"));
+        assert!(text.contains("task_execution_max_rss_bytes_bucket"));
+    }
+}
@@ -0,0 +1,146 @@
+//! # 工作目录快照
+//!
+//! ClaudeCode执行前后对工作目录各做一次快照，生成执行期间的变更补丁，供审计时查看AI实际修改了哪些文件。
+//!
+//! 快照使用临时目录中的独立git仓库（`--git-dir` 指向临时目录，`--work-tree` 指向工作目录），
+//! 通过 `git add -A` + `git write-tree` 记录整个目录树，不会改动工作目录自身的git索引、分支或暂存区；
+//! 工作目录不是git仓库时同样适用。工作目录中的 `.gitignore` 规则仍然生效。
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// 执行期间的工作目录变更
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceDiff {
+    /// 变更的文件（相对工作目录）
+    pub files: Vec<String>,
+    pub insertions: u64,
+    pub deletions: u64,
+    /// `git diff --binary` 格式的补丁，可用 `git apply` 重放
+    pub patch: String,
+}
+
+impl WorkspaceDiff {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// 写入任务结果元数据的变更摘要
+    pub fn summary(&self) -> serde_json::Value {
+        serde_json::json!({
+            "files_changed": self.files.len(),
+            "insertions": self.insertions,
+            "deletions": self.deletions,
+            "files": self.files,
+        })
+    }
+}
+
+/// 执行前的工作目录快照，释放时删除临时仓库
+pub struct WorkspaceSnapshot {
+    git_dir: PathBuf,
+    work_tree: PathBuf,
+    tree: String,
+}
+
+impl WorkspaceSnapshot {
+    /// 记录工作目录当前状态
+    pub fn capture(work_directory: &str) -> Result<Self> {
+        let work_tree = Path::new(work_directory)
+            .canonicalize()
+            .with_context(|| format!("Work directory {} is not accessible", work_directory))?;
+        let git_dir = std::env::temp_dir().join(format!("workspace-snapshot-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&git_dir)
+            .with_context(|| format!("Failed to create snapshot repository {}", git_dir.display()))?;
+
+        let mut snapshot = Self { git_dir, work_tree, tree: String::new() };
+        snapshot.git(&["init", "--quiet"])?;
+        snapshot.tree = snapshot.write_tree()?;
+        Ok(snapshot)
+    }
+
+    /// 再次记录工作目录并与执行前的快照比较
+    pub fn diff(&self) -> Result<WorkspaceDiff> {
+        let after = self.write_tree()?;
+        let numstat = self.git(&["diff", "--numstat", "-z", &self.tree, &after])?;
+        let patch = self.git(&["diff", "--binary", &self.tree, &after])?;
+
+        let mut diff = WorkspaceDiff { files: Vec::new(), insertions: 0, deletions: 0, patch };
+        for entry in numstat.split('\0').filter(|entry| !entry.is_empty()) {
+            let mut fields = entry.splitn(3, '\t');
+            // 二进制文件的行数统计为 "-"
+            diff.insertions += fields.next().and_then(|n| n.parse::<u64>().ok()).unwrap_or(0);
+            diff.deletions += fields.next().and_then(|n| n.parse::<u64>().ok()).unwrap_or(0);
+            if let Some(path) = fields.next() {
+                diff.files.push(path.to_string());
+            }
+        }
+        Ok(diff)
+    }
+
+    fn write_tree(&self) -> Result<String> {
+        self.git(&["add", "--all"])?;
+        Ok(self.git(&["write-tree"])?.trim().to_string())
+    }
+
+    fn git(&self, args: &[&str]) -> Result<String> {
+        let output = Command::new("git")
+            .arg("--git-dir")
+            .arg(&self.git_dir)
+            .arg("--work-tree")
+            .arg(&self.work_tree)
+            .args(["-c", "core.autocrlf=false", "-c", "core.quotepath=false"])
+            .args(args)
+            .current_dir(&self.work_tree)
+            .output()
+            .context("Failed to run git for workspace snapshot")?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl Drop for WorkspaceSnapshot {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.git_dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_snapshot_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(dir.path().join("old.txt"), "remove me\n").unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+
+        let snapshot = WorkspaceSnapshot::capture(dir.path().to_str().unwrap()).unwrap();
+        assert!(snapshot.diff().unwrap().is_empty());
+
+        std::fs::write(dir.path().join("main.rs"), "fn main() {\n    println!(\"hi\");\n}\n").unwrap();
+        std::fs::remove_file(dir.path().join("old.txt")).unwrap();
+        std::fs::create_dir(dir.path().join("target")).unwrap();
+        std::fs::write(dir.path().join("target/build.log"), "ignored\n").unwrap();
+
+        let diff = snapshot.diff().unwrap();
+        assert_eq!(diff.files, vec!["main.rs".to_string(), "old.txt".to_string()]);
+        assert_eq!((diff.insertions, diff.deletions), (3, 2));
+        assert!(diff.patch.contains("+    println!(\"hi\");"));
+        assert!(!dir.path().join(".git").exists());
+
+        let git_dir = snapshot.git_dir.clone();
+        drop(snapshot);
+        assert!(!git_dir.exists());
+    }
+}
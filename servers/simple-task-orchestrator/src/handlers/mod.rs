@@ -1,19 +1,22 @@
+use std::any::Any;
 use std::sync::Arc;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::{get, post, delete},
     Router,
 };
 use serde::Deserialize;
+use tower_http::catch_panic::CatchPanicLayer;
 use uuid::Uuid;
 
 use crate::domain::{
     TaskId, TaskStatus, TaskPriority, ExecutionMode,
     CreateTaskRequest, CompleteTaskRequest, AcquireTaskRequest,
-    TaskFilter, ApiResponse, ApiError, Task, TaskStatistics, TaskResult,
+    TaskFilter, TaskSort, ApiResponse, ApiError, Task, TaskStatistics, TaskResult,
 };
+use crate::infrastructure::ArtifactInfo;
 use crate::services::{TaskService, TaskExecutionService};
 
 /// API状态
@@ -31,6 +34,8 @@ pub struct TaskQuery {
     pub worker_id: Option<String>,
     pub limit: Option<u32>,
     pub offset: Option<u32>,
+    /// 多键排序，如 `priority:desc,created_at:asc`
+    pub sort: Option<String>,
 }
 
 /// 获取下一个任务查询参数
@@ -41,6 +46,8 @@ pub struct NextTaskQuery {
 }
 
 /// 创建API路由
+///
+/// 处理器中的panic由 `CatchPanicLayer` 转换为500响应，不会直接断开连接。
 pub fn create_routes() -> Router<ApiState> {
     Router::new()
         // 任务管理
@@ -53,10 +60,26 @@ pub fn create_routes() -> Router<ApiState> {
         .route("/api/v1/tasks/:id/cancel", post(cancel_task))
         .route("/api/v1/tasks/:id/retry", post(retry_task))
         .route("/api/v1/tasks/:id/execute", post(execute_task))
+        .route("/api/v1/tasks/:id/artifacts", get(list_artifacts))
+        .route("/api/v1/tasks/:id/artifacts/:name", get(get_artifact))
         .route("/api/v1/execute/directory/:work_directory", post(execute_tasks_in_directory))
         // 系统管理
         .route("/api/v1/statistics", get(get_statistics))
         .route("/health", get(health_check))
+        .route("/metrics", get(metrics))
+        .layer(CatchPanicLayer::custom(panic_response))
+}
+
+/// 处理器panic时返回的响应，panic消息只写入日志，不返回给客户端
+fn panic_response(payload: Box<dyn Any + Send + 'static>) -> Response {
+    tracing::error!(
+        panic.message = %crash_report::panic_message(payload.as_ref()),
+        "Request handler panicked"
+    );
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(ApiError::new(
+        "INTERNAL_ERROR".to_string(),
+        "The server hit an unexpected error while handling the request".to_string(),
+    )))).into_response()
 }
 
 /// 创建任务
@@ -269,6 +292,13 @@ async fn list_tasks(
         filter = filter.with_offset(offset);
     }
     
+    if let Some(sort) = &params.sort {
+        match TaskSort::parse(sort) {
+            Ok(sort) => filter = filter.with_sort(sort),
+            Err(e) => return Ok(Json(ApiResponse::error(ApiError::new("INVALID_SORT".to_string(), e)))),
+        }
+    }
+    
     match state.task_service.list_tasks(filter).await {
         Ok((tasks, _total)) => {
             // 在实际应用中，这里应该返回分页信息
@@ -310,6 +340,15 @@ async fn health_check() -> Json<ApiResponse<serde_json::Value>> {
     Json(ApiResponse::success(health_data))
 }
 
+/// 执行资源用量的Prometheus指标
+async fn metrics(State(state): State<ApiState>) -> Response {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        state.execution_service.resource_metrics().render(),
+    )
+        .into_response()
+}
+
 /// 执行单个任务
 async fn execute_task(
     State(state): State<ApiState>,
@@ -366,4 +405,84 @@ async fn execute_tasks_in_directory(
             Ok(Json(ApiResponse::error(error)))
         }
     }
-}
\ No newline at end of file
+}
+
+/// 未配置产物存储时的错误
+fn artifacts_disabled() -> ApiError {
+    ApiError::new("ARTIFACTS_DISABLED".to_string(), "Artifact storage is not configured".to_string())
+}
+
+/// 列出任务产物
+async fn list_artifacts(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Vec<ArtifactInfo>>>, StatusCode> {
+    let task_id = match Uuid::parse_str(&id) {
+        Ok(uuid) => TaskId::from_uuid(uuid),
+        Err(_) => return Ok(Json(ApiResponse::error(ApiError::new(
+            "INVALID_ID".to_string(),
+            "Invalid task ID format".to_string(),
+        )))),
+    };
+    let Some(artifact_store) = state.execution_service.artifact_store() else {
+        return Ok(Json(ApiResponse::error(artifacts_disabled())));
+    };
+
+    match artifact_store.list(&task_id) {
+        Ok(artifacts) => Ok(Json(ApiResponse::success(artifacts))),
+        Err(e) => Ok(Json(ApiResponse::error(ApiError::new("ARTIFACT_ERROR".to_string(), e)))),
+    }
+}
+
+/// 下载任务产物，`.diff` 产物以纯文本返回
+async fn get_artifact(
+    State(state): State<ApiState>,
+    Path((id, name)): Path<(String, String)>,
+) -> Response {
+    let task_id = match Uuid::parse_str(&id) {
+        Ok(uuid) => TaskId::from_uuid(uuid),
+        Err(_) => return (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(ApiError::new(
+            "INVALID_ID".to_string(),
+            "Invalid task ID format".to_string(),
+        )))).into_response(),
+    };
+    let Some(artifact_store) = state.execution_service.artifact_store() else {
+        return (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error(artifacts_disabled()))).into_response();
+    };
+
+    match artifact_store.get(&task_id, &name) {
+        Ok(Some(content)) => {
+            let content_type = if name.ends_with(".diff") || name.ends_with(".log") {
+                "text/plain; charset=utf-8"
+            } else {
+                "application/octet-stream"
+            };
+            ([(header::CONTENT_TYPE, content_type)], content).into_response()
+        }
+        Ok(None) => (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error(ApiError::new(
+            "NOT_FOUND".to_string(),
+            format!("Artifact {} not found", name),
+        )))).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(ApiError::new(
+            "ARTIFACT_ERROR".to_string(),
+            e,
+        )))).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_panic_response() {
+        let response = panic_response(Box::new("handler bug"));
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error"]["code"], "INTERNAL_ERROR");
+        assert!(!body["error"]["message"].as_str().unwrap().contains("handler bug"));
+    }
+}
@@ -1,15 +1,19 @@
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
 use chrono::{DateTime, Utc};
 use async_trait::async_trait;
+use task_store::{StoreError, StoredTask, TaskPhase};
 
 use crate::domain::{
-    Task, TaskId, TaskStatus, 
+    Task, TaskId, TaskStatus,
     TaskFilter, TaskStatistics,
 };
 
+/// 等待超过该时长（秒）仍未完成的任务不再分配
+const TASK_CLAIM_TIMEOUT_SECONDS: u64 = 3600;
+
 /// 任务仓库特征
+///
+/// 存取和调度由 `task-store` 的共享仓库提供，这里在其上实现本服务的过滤、统计和批量操作：
+/// 任何实现了 [`task_store::TaskRepository`] 的后端都自动实现本特征。
 #[async_trait]
 pub trait TaskRepository: Send + Sync {
     async fn create_task(&self, task: &Task) -> Result<TaskId, String>;
@@ -25,87 +29,70 @@ pub trait TaskRepository: Send + Sync {
     async fn retry_failed_tasks(&self, max_retries: u32) -> Result<u64, String>;
 }
 
-/// 内存任务仓库实现
-pub struct InMemoryTaskRepository {
-    tasks: Arc<RwLock<HashMap<TaskId, Task>>>,
-    worker_tasks: Arc<RwLock<HashMap<String, Vec<TaskId>>>>,
-}
+impl StoredTask for Task {
+    type Id = TaskId;
 
-impl InMemoryTaskRepository {
-    pub fn new() -> Self {
-        Self {
-            tasks: Arc::new(RwLock::new(HashMap::new())),
-            worker_tasks: Arc::new(RwLock::new(HashMap::new())),
-        }
+    fn id(&self) -> TaskId {
+        self.id
     }
-    
-    async fn find_next_task(&self, work_directory: &str, worker_id: &str) -> Option<Task> {
-        let tasks = self.tasks.read().await;
-        let worker_tasks = self.worker_tasks.read().await;
-        
-        // 找出指定工作目录的等待任务
-        let mut candidates: Vec<_> = tasks.iter()
-            .filter(|(_, task)| {
-                task.work_directory == work_directory 
-                && task.status == TaskStatus::Waiting
-                && !task.is_expired(3600) // 1小时超时
-            })
-            .collect();
-        
-        // 按优先级排序
-        candidates.sort_by(|a, b| {
-            b.1.priority.cmp(&a.1.priority) // 高优先级在前
-        });
-        
-        // 检查是否有任务已经被分配给当前worker
-        if let Some(worker_task_ids) = worker_tasks.get(worker_id) {
-            for task_id in worker_task_ids {
-                if let Some(task) = tasks.get(task_id) {
-                    if task.status == TaskStatus::Waiting {
-                        return Some(task.clone());
-                    }
-                }
-            }
+
+    fn work_directory(&self) -> &str {
+        &self.work_directory
+    }
+
+    fn phase(&self) -> TaskPhase {
+        match self.status {
+            TaskStatus::Waiting => TaskPhase::Queued,
+            TaskStatus::Working => TaskPhase::Running,
+            TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled => TaskPhase::Finished,
         }
-        
-        // 返回最高优先级的任务
-        candidates.first().map(|(_, task)| task.clone()).cloned()
+    }
+
+    fn priority(&self) -> i32 {
+        self.priority.as_i32()
+    }
+
+    fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    fn is_claimable(&self) -> bool {
+        !self.is_expired(TASK_CLAIM_TIMEOUT_SECONDS)
     }
 }
 
+/// 内存任务仓库，使用 `task-store` 的共享实现
+pub type InMemoryTaskRepository = task_store::MemoryTaskRepository<Task>;
+
 #[async_trait]
-impl TaskRepository for InMemoryTaskRepository {
+impl<R> TaskRepository for R
+where
+    R: task_store::TaskRepository<Task> + ?Sized,
+{
     async fn create_task(&self, task: &Task) -> Result<TaskId, String> {
-        let mut tasks = self.tasks.write().await;
-        let task_id = task.id.clone();
-        tasks.insert(task_id.clone(), task.clone());
-        Ok(task_id)
+        self.insert(task).await.map_err(|e| e.to_string())?;
+        Ok(task.id)
     }
     
     async fn get_task(&self, task_id: &TaskId) -> Result<Option<Task>, String> {
-        let tasks = self.tasks.read().await;
-        Ok(tasks.get(task_id).cloned())
+        self.get(task_id).await.map_err(|e| e.to_string())
     }
     
     async fn update_task(&self, task: &Task) -> Result<(), String> {
-        let mut tasks = self.tasks.write().await;
-        tasks.insert(task.id.clone(), task.clone());
-        Ok(())
+        self.update(task).await.map_err(|e| e.to_string())
     }
     
     async fn delete_task(&self, task_id: &TaskId) -> Result<(), String> {
-        let mut tasks = self.tasks.write().await;
-        tasks.remove(task_id);
+        self.remove(task_id).await.map_err(|e| e.to_string())?;
         Ok(())
     }
     
-    async fn get_next_task(&self, work_directory: &str, worker_id: &str) -> Result<Option<Task>, String> {
-        Ok(self.find_next_task(work_directory, worker_id).await)
+    async fn get_next_task(&self, work_directory: &str, _worker_id: &str) -> Result<Option<Task>, String> {
+        self.next_queued(work_directory).await.map_err(|e| e.to_string())
     }
     
     async fn list_tasks(&self, filter: &TaskFilter) -> Result<(Vec<Task>, u64), String> {
-        let tasks = self.tasks.read().await;
-        let mut filtered: Vec<Task> = tasks.values().cloned().collect();
+        let mut filtered = self.scan().await.map_err(|e| e.to_string())?;
         
         // 应用过滤器
         if let Some(status) = &filter.status {
@@ -122,8 +109,8 @@ impl TaskRepository for InMemoryTaskRepository {
             });
         }
         
-        // 按创建时间排序
-        filtered.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        // 排序，末尾按 task_id 保证顺序稳定
+        filter.sort.sort(&mut filtered);
         
         let total = filtered.len() as u64;
         
@@ -138,26 +125,20 @@ impl TaskRepository for InMemoryTaskRepository {
     }
     
     async fn get_tasks_by_work_directory(&self, work_directory: &str) -> Result<Vec<Task>, String> {
-        let tasks = self.tasks.read().await;
-        let filtered: Vec<Task> = tasks.values()
-            .filter(|task| task.work_directory == work_directory)
-            .cloned()
-            .collect();
-        
-        Ok(filtered)
+        let mut tasks = self.scan().await.map_err(|e| e.to_string())?;
+        tasks.retain(|task| task.work_directory == work_directory);
+        Ok(tasks)
     }
     
     async fn get_all_tasks(&self) -> Result<Vec<Task>, String> {
-        let tasks = self.tasks.read().await;
-        let all_tasks: Vec<Task> = tasks.values().cloned().collect();
-        Ok(all_tasks)
+        self.scan().await.map_err(|e| e.to_string())
     }
     
     async fn get_statistics(&self) -> Result<TaskStatistics, String> {
-        let tasks = self.tasks.read().await;
+        let tasks = self.scan().await.map_err(|e| e.to_string())?;
         let mut stats = TaskStatistics::new();
         
-        for task in tasks.values() {
+        for task in &tasks {
             stats.total_tasks += 1;
             
             match task.status {
@@ -176,25 +157,24 @@ impl TaskRepository for InMemoryTaskRepository {
     }
     
     async fn cleanup_expired_tasks(&self, older_than: DateTime<Utc>) -> Result<u64, String> {
-        let mut tasks = self.tasks.write().await;
-        let initial_count = tasks.len();
-        
-        tasks.retain(|_, task| {
-            task.created_at > older_than && !task.status.is_terminal()
-        });
-        
-        Ok((initial_count - tasks.len()) as u64)
+        self.retain(&|task| task.created_at > older_than && !task.status.is_terminal())
+            .await
+            .map_err(|e| e.to_string())
     }
     
     async fn retry_failed_tasks(&self, max_retries: u32) -> Result<u64, String> {
-        let mut tasks = self.tasks.write().await;
+        let tasks = self.scan().await.map_err(|e| e.to_string())?;
         let mut retried = 0;
         
-        for task in tasks.values_mut() {
-            if task.status == TaskStatus::Failed && task.retry_count < max_retries {
-                if let Err(_) = task.retry() {
-                    continue;
+        for task in tasks.iter().filter(|task| task.status == TaskStatus::Failed && task.retry_count < max_retries) {
+            // 扫描之后任务可能已被其他请求重试，修改时按当前状态重新检查
+            let result = self.modify(&task.id, &|task| {
+                if task.retry_count >= max_retries {
+                    return Err(StoreError::Conflict("Maximum retry count exceeded".to_string()));
                 }
+                task.retry().map_err(StoreError::Conflict)
+            }).await;
+            if result.is_ok() {
                 retried += 1;
             }
         }
@@ -203,81 +183,73 @@ impl TaskRepository for InMemoryTaskRepository {
     }
 }
 
-/// 简单的锁管理器
-pub struct SimpleLockManager {
-    locks: Arc<RwLock<HashMap<String, (String, DateTime<Utc>)>>>,
-}
+/// 锁管理器由各编排服务共用的 `task-store` 提供，内存实现沿用原来的名字
+pub use task_store::{LockManager, MemoryLockManager as SimpleLockManager, DEFAULT_LOCK_CLEANUP_INTERVAL};
 
-impl SimpleLockManager {
-    pub fn new() -> Self {
-        Self {
-            locks: Arc::new(RwLock::new(HashMap::new())),
-        }
-    }
-    
-    async fn cleanup_expired_locks(&self) {
-        let mut locks = self.locks.write().await;
-        let now = Utc::now();
-        locks.retain(|_, (_, expires_at)| *expires_at > now);
-    }
+/// 产物元信息
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ArtifactInfo {
+    pub name: String,
+    pub size: u64,
 }
 
-#[async_trait]
-pub trait LockManager: Send + Sync {
-    async fn try_acquire(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> Result<bool, String>;
-    async fn release(&self, resource_id: &str, owner_id: &str) -> Result<bool, String>;
-    async fn check_lock(&self, resource_id: &str) -> Result<Option<String>, String>;
-    async fn cleanup_expired_locks(&self) -> Result<u64, String>;
+/// 本地目录产物存储
+///
+/// 产物保存在 `<root>/<任务ID>/<名称>`，名称只允许字母、数字、`.`、`-` 和 `_`，且不能以 `.` 开头。
+pub struct ArtifactStore {
+    root: std::path::PathBuf,
 }
 
-#[async_trait]
-impl LockManager for SimpleLockManager {
-    async fn try_acquire(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> Result<bool, String> {
-        self.cleanup_expired_locks().await;
-        
-        let mut locks = self.locks.write().await;
-        let expires_at = Utc::now() + chrono::Duration::seconds(ttl_seconds as i64);
-        
-        if let Some((current_owner, _)) = locks.get(resource_id) {
-            if current_owner == owner_id {
-                // 已经是锁的所有者，更新过期时间
-                locks.insert(resource_id.to_string(), (owner_id.to_string(), expires_at));
-                return Ok(true);
-            }
-            return Ok(false);
+impl ArtifactStore {
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, task_id: &TaskId, name: &str) -> Result<std::path::PathBuf, String> {
+        let valid = !name.is_empty()
+            && !name.starts_with('.')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+        if !valid {
+            return Err(format!("Invalid artifact name: {}", name));
         }
-        
-        locks.insert(resource_id.to_string(), (owner_id.to_string(), expires_at));
-        Ok(true)
+        Ok(self.root.join(task_id.to_string()).join(name))
     }
-    
-    async fn release(&self, resource_id: &str, owner_id: &str) -> Result<bool, String> {
-        let mut locks = self.locks.write().await;
-        
-        if let Some((current_owner, _)) = locks.get(resource_id) {
-            if current_owner == owner_id {
-                locks.remove(resource_id);
-                return Ok(true);
-            }
+
+    /// 保存产物，同名产物被覆盖
+    pub fn put(&self, task_id: &TaskId, name: &str, content: &[u8]) -> Result<(), String> {
+        let path = self.path(task_id, name)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
         }
-        
-        Ok(false)
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
     }
-    
-    async fn check_lock(&self, resource_id: &str) -> Result<Option<String>, String> {
-        self.cleanup_expired_locks().await;
-        
-        let locks = self.locks.read().await;
-        Ok(locks.get(resource_id).map(|(owner, _)| owner.clone()))
+
+    /// 读取产物，不存在时返回 `None`
+    pub fn get(&self, task_id: &TaskId, name: &str) -> Result<Option<Vec<u8>>, String> {
+        let path = self.path(task_id, name)?;
+        match std::fs::read(&path) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+        }
     }
-    
-    async fn cleanup_expired_locks(&self) -> Result<u64, String> {
-        let mut locks = self.locks.write().await;
-        let now = Utc::now();
-        let initial_count = locks.len();
-        
-        locks.retain(|_, (_, expires_at)| *expires_at > now);
-        
-        Ok((initial_count - locks.len()) as u64)
+
+    /// 列出任务的所有产物
+    pub fn list(&self, task_id: &TaskId) -> Result<Vec<ArtifactInfo>, String> {
+        let dir = self.root.join(task_id.to_string());
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to list {}: {}", dir.display(), e)),
+        };
+        let mut artifacts: Vec<ArtifactInfo> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let size = entry.metadata().ok().filter(|m| m.is_file())?.len();
+                Some(ArtifactInfo { name: entry.file_name().to_string_lossy().into_owned(), size })
+            })
+            .collect();
+        artifacts.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(artifacts)
     }
-}
\ No newline at end of file
+}
@@ -4,9 +4,12 @@ use tokio::signal;
 use tower_http::{trace::TraceLayer, cors::CorsLayer};
 
 use crate::config::ConfigManager;
-use crate::infrastructure::{InMemoryTaskRepository, SimpleLockManager};
+use crate::infrastructure::{ArtifactStore, InMemoryTaskRepository, SimpleLockManager, DEFAULT_LOCK_CLEANUP_INTERVAL};
 use crate::services::{TaskService, TaskScheduler, TaskMonitor, TaskExecutionService};
 use crate::handlers::{create_routes, ApiState};
+use crate::execution::{TaskExecutor, StandardExecutor, PostProcessorRegistry};
+#[cfg(feature = "claude-executor")]
+use crate::execution::{ClaudeCodeExecutor, ClaudeCodeConfig};
 use crate::utils::RateLimiter;
 
 mod config;
@@ -20,12 +23,19 @@ mod execution;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // 自检模式：校验配置和执行器后退出，适合CI和容器入口
+    if std::env::args().skip(1).any(|arg| arg == "--check") {
+        let passed = run_self_check().await;
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+
     // 初始化配置
     let config_manager = ConfigManager::new()?;
     let config = config_manager.config().clone();
 
     // 初始化日志
     init_logging(&config.logging);
+    crash_report::PanicHook::new("Simple Task Orchestrator", env!("CARGO_PKG_VERSION")).install();
 
     println!("🚀 Starting Simple Task Orchestrator MCP Server");
     println!("📋 Configuration loaded successfully");
@@ -36,6 +46,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     
     // 创建锁管理器
     let lock_manager = Arc::new(SimpleLockManager::new());
+    lock_manager.start_cleanup(DEFAULT_LOCK_CLEANUP_INTERVAL);
     
     // 创建任务服务
     let task_service = Arc::new(TaskService::new(
@@ -62,7 +73,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let _rate_limiter = Arc::new(RateLimiter::new(config.security.rate_limit));
 
     // 创建执行服务
-    let execution_service = Arc::new(TaskExecutionService::new(task_repository.clone()));
+    let post_processors = PostProcessorRegistry::from_config(&config.post_processors)?;
+    let artifact_store = Arc::new(ArtifactStore::new(&config.artifacts.directory));
+    let execution_service = Arc::new(
+        TaskExecutionService::new(task_repository.clone())
+            .with_post_processors(post_processors)
+            .with_artifact_store(artifact_store, config.artifacts.workspace_snapshots)
+    );
     
     // 创建API状态
     let api_state = ApiState {
@@ -130,6 +147,58 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     Ok(())
 }
 
+/// 启动自检，打印每项检查结果，全部通过时返回true
+///
+/// 任务存储在内存中，没有数据库连接和迁移需要检查。
+async fn run_self_check() -> bool {
+    let mut passed = true;
+    let mut report = |name: &str, ok: bool, detail: String| {
+        println!("{} {:<12} {}", if ok { "✅" } else { "❌" }, name, detail);
+        passed &= ok;
+    };
+
+    match ConfigManager::new() {
+        Ok(manager) => {
+            let server = &manager.config().server;
+            match server.host.parse::<std::net::IpAddr>() {
+                Ok(_) => report("config", true, format!("listen address {}:{}", server.host, server.port)),
+                Err(e) => report("config", false, format!("invalid server host '{}': {}", server.host, e)),
+            }
+            let post_processors = &manager.config().post_processors;
+            match PostProcessorRegistry::from_config(post_processors) {
+                Ok(_) => report("postprocess", true, format!("{} pipeline(s) configured", post_processors.len())),
+                Err(e) => report("postprocess", false, format!("{:#}", e)),
+            }
+            if manager.config().artifacts.workspace_snapshots {
+                match std::process::Command::new("git").arg("--version").output() {
+                    Ok(output) if output.status.success() => {
+                        report("snapshots", true, String::from_utf8_lossy(&output.stdout).trim().to_string())
+                    }
+                    _ => report("snapshots", false, "workspace snapshots require `git` on PATH".to_string()),
+                }
+            }
+        }
+        Err(e) => report("config", false, e),
+    }
+    report("storage", true, "in-memory repository, no database or migrations".to_string());
+
+    let executors: Vec<(Box<dyn TaskExecutor>, &str)> = vec![
+        (Box::new(StandardExecutor), "built in"),
+        #[cfg(feature = "claude-executor")]
+        (Box::new(ClaudeCodeExecutor::new(ClaudeCodeConfig::default())), "claude CLI (`claude --version`)"),
+    ];
+    for (executor, requirement) in executors {
+        match executor.validate().await {
+            Ok(true) => report(executor.name(), true, format!("available: {}", requirement)),
+            Ok(false) => report(executor.name(), false, format!("unavailable: {}", requirement)),
+            Err(e) => report(executor.name(), false, e.to_string()),
+        }
+    }
+
+    println!("{}", if passed { "🎉 Self-check passed" } else { "🚫 Self-check failed" });
+    passed
+}
+
 /// 初始化日志系统
 fn init_logging(config: &crate::config::LoggingConfig) {
     use tracing_subscriber::{fmt, EnvFilter, prelude::*};
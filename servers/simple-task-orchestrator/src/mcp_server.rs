@@ -11,7 +11,8 @@ use uuid::Uuid;
 
 use crate::domain::{TaskId, TaskStatus, TaskPriority, CreateTaskRequest, CompleteTaskRequest, AcquireTaskRequest, TaskResult, ExecutionMode};
 use crate::services::{TaskService, TaskExecutionService};
-use crate::domain::TaskFilter;
+use crate::domain::{TaskFilter, TaskSort, StatisticsQuery, StatisticsRange, StatisticsGroupBy};
+use chrono::{DateTime, Utc};
 
 // 任务创建请求参数
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -26,6 +27,8 @@ pub struct CreateTaskParams {
     pub execution_mode: Option<String>,
     #[schemars(description = "任务标签")]
     pub tags: Option<Vec<String>>,
+    #[schemars(description = "执行前检出的Git分支、标签或提交")]
+    pub git_ref: Option<String>,
 }
 
 // 获取任务请求参数
@@ -80,6 +83,25 @@ pub struct ListTasksParams {
     pub priority: Option<String>,
     #[schemars(description = "限制数量")]
     pub limit: Option<u32>,
+    #[schemars(description = "排序，如 priority:desc,created_at:asc；可用字段 task_id、created_at、started_at、completed_at、priority、status、work_directory、retry_count")]
+    pub sort: Option<String>,
+}
+
+// 统计查询请求参数
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct QueryStatisticsParams {
+    #[schemars(description = "时间范围 (today, yesterday, last_24h, last_7d, last_30d, all)，默认all")]
+    pub time_range: Option<String>,
+    #[schemars(description = "起始时间（RFC 3339），优先于time_range")]
+    pub since: Option<String>,
+    #[schemars(description = "结束时间（RFC 3339，不含），优先于time_range")]
+    pub until: Option<String>,
+    #[schemars(description = "分组维度 (status, priority, work_directory, tag, day)")]
+    pub group_by: Option<String>,
+    #[schemars(description = "工作目录前缀过滤")]
+    pub work_directory: Option<String>,
+    #[schemars(description = "任务状态过滤 (Waiting, Working, Completed, Failed, Cancelled)")]
+    pub status: Option<String>,
 }
 
 /// 任务编排MCP服务器
@@ -126,6 +148,9 @@ impl TaskOrchestratorServer {
             priority: Some(priority),
             execution_mode: Some(execution_mode),
             tags: params.tags,
+            metadata: params.git_ref.map(|git_ref| {
+                std::collections::HashMap::from([("git_ref".to_string(), serde_json::json!(git_ref))])
+            }),
         };
 
         match self.task_service.create_task(request).await {
@@ -281,6 +306,13 @@ impl TaskOrchestratorServer {
             filter = filter.with_limit(limit);
         }
         
+        if let Some(sort) = params.sort {
+            match TaskSort::parse(&sort) {
+                Ok(sort) => filter = filter.with_sort(sort),
+                Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+            }
+        }
+        
         match self.task_service.list_tasks(filter).await {
             Ok((tasks, _total)) => {
                 let result = serde_json::to_string_pretty(&tasks)
@@ -293,6 +325,80 @@ impl TaskOrchestratorServer {
         }
     }
 
+    // 按条件查询统计
+    #[tool(description = "按时间范围、工作目录和分组维度查询任务统计，返回摘要和原始数字，例如查询昨天某目录下失败的任务")]
+    async fn query_statistics(&self, Parameters(params): Parameters<QueryStatisticsParams>) -> Result<CallToolResult, ErrorData> {
+        let range = match params.time_range.as_deref() {
+            Some(value) => match StatisticsRange::parse(value) {
+                Some(range) => range,
+                None => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Invalid time_range '{}', expected today, yesterday, last_24h, last_7d, last_30d or all",
+                        value
+                    ))]))
+                }
+            },
+            None => StatisticsRange::All,
+        };
+        let (mut since, mut until) = range.bounds(Utc::now());
+
+        for (name, value, bound) in [("since", &params.since, &mut since), ("until", &params.until, &mut until)] {
+            if let Some(value) = value {
+                match DateTime::parse_from_rfc3339(value) {
+                    Ok(time) => *bound = Some(time.with_timezone(&Utc)),
+                    Err(_) => {
+                        return Ok(CallToolResult::error(vec![Content::text(format!(
+                            "Invalid {} '{}', expected an RFC 3339 timestamp",
+                            name, value
+                        ))]))
+                    }
+                }
+            }
+        }
+
+        let group_by = match params.group_by.as_deref() {
+            Some(value) => match StatisticsGroupBy::parse(value) {
+                Some(group_by) => Some(group_by),
+                None => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Invalid group_by '{}', expected status, priority, work_directory, tag or day",
+                        value
+                    ))]))
+                }
+            },
+            None => None,
+        };
+
+        let status = match params.status.as_deref() {
+            Some("Waiting") => Some(TaskStatus::Waiting),
+            Some("Working") => Some(TaskStatus::Working),
+            Some("Completed") => Some(TaskStatus::Completed),
+            Some("Failed") => Some(TaskStatus::Failed),
+            Some("Cancelled") => Some(TaskStatus::Cancelled),
+            Some(_) => return Ok(CallToolResult::error(vec![Content::text("Invalid status")])),
+            None => None,
+        };
+
+        let query = StatisticsQuery {
+            since,
+            until,
+            work_directory: params.work_directory,
+            status,
+            group_by,
+        };
+
+        match self.task_service.query_statistics(query).await {
+            Ok(report) => {
+                let raw = serde_json::to_string_pretty(&report)
+                    .unwrap_or_else(|_| "Statistics retrieved".to_string());
+                Ok(CallToolResult::success(vec![Content::text(report.summary), Content::text(raw)]))
+            }
+            Err(e) => {
+                Ok(CallToolResult::error(vec![Content::text(format!("Failed to query statistics: {}", e))]))
+            }
+        }
+    }
+
     // 获取统计信息
     #[tool(description = "获取统计信息")]
     async fn get_statistics(&self) -> Result<CallToolResult, ErrorData> {
@@ -1,22 +1,57 @@
 use std::sync::Arc;
 use chrono::Utc;
-use crate::domain::{Task, TaskId, TaskResult, TaskStatus};
-use crate::infrastructure::TaskRepository;
-use crate::execution::{TaskExecutor, TaskExecutorFactory};
+use crate::domain::{ExecutionMode, ResourceUsageTotals, Task, TaskId, TaskResult, TaskStatus};
+use crate::infrastructure::{ArtifactStore, TaskRepository};
+use crate::execution::{GitRefSpec, PostProcessorRegistry, ResourceMetrics, TaskExecutor, TaskExecutorFactory, WorkspaceSnapshot};
 use anyhow::{Result, Context};
 
 /// 任务执行服务
 pub struct TaskExecutionService {
     task_repository: Arc<dyn TaskRepository>,
+    post_processors: PostProcessorRegistry,
+    artifact_store: Option<Arc<ArtifactStore>>,
+    workspace_snapshots: bool,
+    resource_metrics: Arc<ResourceMetrics>,
 }
 
+/// 工作目录变更补丁的产物名称
+pub const WORKSPACE_DIFF_ARTIFACT: &str = "workspace.diff";
+
 impl TaskExecutionService {
     pub fn new(task_repository: Arc<dyn TaskRepository>) -> Self {
         Self {
             task_repository,
+            post_processors: PostProcessorRegistry::default(),
+            artifact_store: None,
+            workspace_snapshots: false,
+            resource_metrics: Arc::new(ResourceMetrics::new()),
         }
     }
 
+    /// 执行资源用量的Prometheus直方图
+    pub fn resource_metrics(&self) -> &Arc<ResourceMetrics> {
+        &self.resource_metrics
+    }
+
+    /// 设置产物存储；`workspace_snapshots` 为真时在ClaudeCode执行前后对工作目录做快照，
+    /// 变更补丁保存为 `workspace.diff` 产物
+    pub fn with_artifact_store(mut self, artifact_store: Arc<ArtifactStore>, workspace_snapshots: bool) -> Self {
+        self.artifact_store = Some(artifact_store);
+        self.workspace_snapshots = workspace_snapshots;
+        self
+    }
+
+    /// 产物存储，未配置时为 `None`
+    pub fn artifact_store(&self) -> Option<&Arc<ArtifactStore>> {
+        self.artifact_store.as_ref()
+    }
+
+    /// 设置执行结果后处理流水线，结果在保存前按任务的执行模式处理
+    pub fn with_post_processors(mut self, post_processors: PostProcessorRegistry) -> Self {
+        self.post_processors = post_processors;
+        self
+    }
+
     /// 执行单个任务
     pub async fn execute_task(&self, task_id: &TaskId) -> Result<TaskResult> {
         // 获取任务
@@ -38,9 +73,24 @@ impl TaskExecutionService {
             return Err(anyhow::anyhow!("Executor {} is not available", executor.name()));
         }
 
+        // 执行前记录工作目录快照
+        let snapshot = self.capture_workspace(&task).await;
+
         // 执行任务
-        let result = executor.execute(&task).await
-            .context("Failed to execute task")?;
+        let result = executor.execute(&task).await;
+        let workspace_changes = match snapshot {
+            Some(snapshot) => self.store_workspace_diff(&task, snapshot).await,
+            None => None,
+        };
+        let mut result = result.context("Failed to execute task")?;
+        if let Some(usage) = result.resource_usage() {
+            self.resource_metrics.observe(PostProcessorRegistry::mode_name(&task.execution_mode), &usage);
+        }
+        if let Some(changes) = workspace_changes {
+            result.metadata.get_or_insert_with(Default::default).insert("workspace_changes".to_string(), changes);
+        }
+        let result = self.post_processors.process(&task, result)
+            .context("Failed to post-process task result")?;
 
         // 更新任务状态
         task.status = TaskStatus::Completed;
@@ -54,6 +104,55 @@ impl TaskExecutionService {
         Ok(result)
     }
 
+    /// ClaudeCode任务执行前的工作目录快照，快照失败只记录警告，不影响执行
+    ///
+    /// 指定了 `git_ref` 的任务不做快照：检出本身会改动工作目录，执行结果已记录提交SHA。
+    async fn capture_workspace(&self, task: &Task) -> Option<WorkspaceSnapshot> {
+        if !self.workspace_snapshots || self.artifact_store.is_none() || task.execution_mode != ExecutionMode::ClaudeCode {
+            return None;
+        }
+        if GitRefSpec::is_requested(task) {
+            return None;
+        }
+        let work_directory = task.work_directory.clone();
+        let snapshot = tokio::task::spawn_blocking(move || WorkspaceSnapshot::capture(&work_directory))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|snapshot| snapshot);
+        match snapshot {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                tracing::warn!("Failed to snapshot work directory for task {}: {:#}", task.id, e);
+                None
+            }
+        }
+    }
+
+    /// 比较执行前后的工作目录，有变更时保存补丁产物，返回变更摘要
+    async fn store_workspace_diff(&self, task: &Task, snapshot: WorkspaceSnapshot) -> Option<serde_json::Value> {
+        let artifact_store = self.artifact_store.as_ref()?;
+        let diff = tokio::task::spawn_blocking(move || snapshot.diff())
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|diff| diff);
+        let diff = match diff {
+            Ok(diff) => diff,
+            Err(e) => {
+                tracing::warn!("Failed to diff work directory for task {}: {:#}", task.id, e);
+                return None;
+            }
+        };
+
+        let mut summary = diff.summary();
+        if !diff.is_empty() {
+            match artifact_store.put(&task.id, WORKSPACE_DIFF_ARTIFACT, diff.patch.as_bytes()) {
+                Ok(()) => summary["artifact"] = serde_json::json!(WORKSPACE_DIFF_ARTIFACT),
+                Err(e) => tracing::warn!("Failed to store workspace diff for task {}: {}", task.id, e),
+            }
+        }
+        Some(summary)
+    }
+
     /// 批量执行任务
     pub async fn execute_tasks(&self, task_ids: &[TaskId]) -> Vec<(TaskId, Result<TaskResult>)> {
         let mut results = Vec::new();
@@ -127,6 +226,9 @@ impl TaskExecutionService {
             
             // 计算平均执行时间
             if let Some(ref result) = task.result {
+                if let Some(usage) = result.resource_usage() {
+                    stats.resources.add(&usage);
+                }
                 stats.total_execution_time += result.duration_ms;
                 if stats.completed_tasks > 0 {
                     stats.average_execution_time = stats.total_execution_time / stats.completed_tasks;
@@ -150,6 +252,8 @@ pub struct ExecutionStats {
     pub cancelled_tasks: u64,
     pub total_execution_time: u64,
     pub average_execution_time: u64,
+    /// 执行器记录的资源用量汇总
+    pub resources: ResourceUsageTotals,
 }
 
 impl ExecutionStats {
@@ -81,6 +81,7 @@ use crate::domain::{
     Task, TaskId, TaskStatus, WorkerId, 
     CreateTaskRequest, CompleteTaskRequest, AcquireTaskRequest,
     TaskFilter, TaskStatistics, TaskResult,
+    StatisticsQuery, StatisticsReport,
 };
 use crate::infrastructure::{TaskRepository, LockManager};
 
@@ -153,6 +154,7 @@ impl TaskService {
             task.execution_mode = execution_mode;
         }
         
+        task.metadata = request.metadata;
         task.max_retries = self.max_retries;
         
         // 保存到仓库
@@ -189,7 +191,8 @@ impl TaskService {
             // 尝试获取锁
             let lock_acquired = self.lock_manager
                 .try_acquire(&task.id.to_string(), &request.worker_id, 3600)
-                .await?;
+                .await
+                .map_err(|e| e.to_string())?;
             
             if !lock_acquired {
                 return Ok(None);
@@ -313,6 +316,12 @@ impl TaskService {
         self.task_repository.get_statistics().await
     }
     
+    /// 按时间范围、工作目录和分组维度查询统计
+    pub async fn query_statistics(&self, query: StatisticsQuery) -> Result<StatisticsReport, String> {
+        let (tasks, _) = self.task_repository.list_tasks(&TaskFilter::new()).await?;
+        Ok(query.report(&tasks))
+    }
+    
     /// 清理过期任务
     pub async fn cleanup_expired_tasks(&self, older_than: DateTime<Utc>) -> Result<u64, String> {
         self.task_repository.cleanup_expired_tasks(older_than).await
@@ -26,6 +26,7 @@ async fn test_concurrent_task_creation() {
                 prompt: format!("Concurrent task {}", i),
                 priority: Some(if i % 3 == 0 { TaskPriority::High } else { TaskPriority::Medium }),
                 tags: Some(vec!["concurrent".to_string()]),
+                metadata: None,
             };
             
             service.create_task(request).await
@@ -72,6 +73,7 @@ async fn test_concurrent_task_acquisition() {
             prompt: format!("Task for acquisition {}", i),
             priority: Some(TaskPriority::High),
             tags: None,
+            metadata: None,
         };
         task_service.create_task(request).await.unwrap();
     }
@@ -195,6 +197,7 @@ async fn test_concurrent_task_completion() {
             prompt: format!("Task for completion {}", i),
             priority: Some(TaskPriority::Medium),
             tags: None,
+            metadata: None,
         };
         
         let task = task_service.create_task(request).await.unwrap();
@@ -255,6 +258,7 @@ async fn test_concurrent_task_listing() {
             prompt: format!("Task for listing {}", i),
             priority: Some(if i % 4 == 0 { TaskPriority::High } else { TaskPriority::Medium }),
             tags: Some(vec!["listing".to_string()]),
+            metadata: None,
         };
         task_service.create_task(request).await.unwrap();
     }
@@ -326,6 +330,58 @@ async fn test_concurrent_statistics() {
     }
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn test_lock_manager_stress() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let lock_manager = Arc::new(SimpleLockManager::new());
+    let num_resources = 32;
+    let num_workers = 64;
+    let iterations = 200;
+
+    // 每个资源当前的持有者数量，任何时刻都不应超过1
+    let holders: Arc<Vec<AtomicUsize>> = Arc::new((0..num_resources).map(|_| AtomicUsize::new(0)).collect());
+    let violations = Arc::new(AtomicUsize::new(0));
+    let acquisitions = Arc::new(AtomicUsize::new(0));
+
+    let mut workers = JoinSet::new();
+    for worker in 0..num_workers {
+        let lock_manager = lock_manager.clone();
+        let holders = holders.clone();
+        let violations = violations.clone();
+        let acquisitions = acquisitions.clone();
+
+        workers.spawn(async move {
+            let owner_id = format!("worker_{}", worker);
+            for i in 0..iterations {
+                let resource = (worker * 7 + i) % num_resources;
+                let resource_id = format!("resource_{}", resource);
+                if !lock_manager.try_acquire(&resource_id, &owner_id, 60).await.unwrap() {
+                    tokio::task::yield_now().await;
+                    continue;
+                }
+
+                acquisitions.fetch_add(1, Ordering::SeqCst);
+                if holders[resource].fetch_add(1, Ordering::SeqCst) != 0 {
+                    violations.fetch_add(1, Ordering::SeqCst);
+                }
+                tokio::task::yield_now().await;
+                holders[resource].fetch_sub(1, Ordering::SeqCst);
+
+                assert!(lock_manager.release(&resource_id, &owner_id).await.unwrap());
+            }
+        });
+    }
+
+    while let Some(result) = workers.join_next().await {
+        result.unwrap();
+    }
+
+    assert_eq!(violations.load(Ordering::SeqCst), 0);
+    assert!(acquisitions.load(Ordering::SeqCst) > 0);
+    assert_eq!(lock_manager.lock_count().await, 0);
+}
+
 #[tokio::test]
 async fn test_concurrent_task_creation_and_listing() {
     let task_repository = Arc::new(InMemoryTaskRepository::new());
@@ -352,6 +408,7 @@ async fn test_concurrent_task_creation_and_listing() {
                     prompt: format!("Mixed operation task {}", i),
                     priority: Some(TaskPriority::Medium),
                     tags: None,
+                    metadata: None,
                 };
                 
                 service.create_task(request).await
@@ -454,6 +511,7 @@ async fn test_concurrent_task_with_same_worker() {
             prompt: format!("Task for worker {}", i),
             priority: Some(TaskPriority::High),
             tags: None,
+            metadata: None,
         };
         task_service.create_task(request).await.unwrap();
     }
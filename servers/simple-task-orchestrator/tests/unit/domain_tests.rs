@@ -391,6 +391,7 @@ mod tests {
             prompt: "test prompt".to_string(),
             priority: Some(TaskPriority::High),
             tags: Some(vec!["tag1".to_string()]),
+            metadata: None,
         };
 
         assert_eq!(request.work_directory, "/test");
@@ -421,4 +422,42 @@ mod tests {
         assert_eq!(request.original_prompt, Some("original".to_string()));
         assert!(request.result.is_some());
     }
+
+    #[test]
+    fn test_statistics_query_report() {
+        let now = Utc::now();
+        let task = |dir: &str, tags: &[&str]| {
+            Task::new(dir.to_string(), "prompt".to_string(), TaskPriority::Medium, tags.iter().map(|t| t.to_string()).collect())
+        };
+        let mut failed = task("/repo/x/api", &["build"]);
+        failed.start(WorkerId::new("worker1".to_string()).unwrap()).unwrap();
+        failed.fail("compile error".to_string()).unwrap();
+        let mut done = task("/repo/x", &[]);
+        done.start(WorkerId::new("worker1".to_string()).unwrap()).unwrap();
+        done.complete(TaskResult::success("ok".to_string())).unwrap();
+        let mut old = task("/repo/x", &["build"]);
+        old.created_at = now - chrono::Duration::days(3);
+        let tasks = vec![failed.clone(), done, old, task("/repo/xy", &[])];
+
+        let (since, until) = StatisticsRange::parse("last 24h").unwrap().bounds(now);
+        let report = StatisticsQuery {
+            since,
+            until,
+            work_directory: Some("/repo/x".to_string()),
+            group_by: Some(StatisticsGroupBy::Tag),
+            ..Default::default()
+        }
+        .report(&tasks);
+
+        assert_eq!((report.totals.total, report.totals.completed, report.totals.failed), (2, 1, 1));
+        assert_eq!(report.buckets.len(), 2);
+        assert_eq!(report.recent_failures[0].task_id, failed.id);
+        assert!(report.summary.starts_with("2 task(s) in /repo/x since "));
+        assert!(report.summary.contains("(success rate 50%)"));
+        assert!(report.summary.contains("compile error"));
+
+        let (since, until) = StatisticsRange::Yesterday.bounds(now);
+        assert_eq!(until.unwrap() - since.unwrap(), chrono::Duration::days(1));
+        assert!(StatisticsGroupBy::parse("weekday").is_none());
+    }
 }
\ No newline at end of file
@@ -72,6 +72,7 @@ mod tests {
             prompt: "test prompt".to_string(),
             priority: Some(TaskPriority::Medium),
             tags: Some(vec!["test".to_string()]),
+            metadata: None,
         };
 
         let response = app
@@ -113,6 +114,7 @@ mod tests {
             prompt: "test prompt".to_string(),
             priority: None,
             tags: None,
+            metadata: None,
         };
 
         let response = app
@@ -4,6 +4,7 @@ mod tests {
     use crate::domain::*;
     use chrono::{DateTime, Utc};
     use std::collections::HashMap;
+    use std::sync::Arc;
     use tokio::time::{sleep, Duration};
 
     #[tokio::test]
@@ -132,6 +133,43 @@ mod tests {
         assert_eq!(result.unwrap().prompt, "test prompt 2");
     }
 
+    #[tokio::test]
+    async fn test_get_next_task_queue_order() {
+        let repo = InMemoryTaskRepository::new();
+        let task = |prompt: &str, priority| {
+            Task::new("/test".to_string(), prompt.to_string(), priority, vec![])
+        };
+
+        let first_medium = task("medium 1", TaskPriority::Medium);
+        sleep(Duration::from_millis(2)).await;
+        let second_medium = task("medium 2", TaskPriority::Medium);
+        let mut high = task("high", TaskPriority::High);
+        for task in [&first_medium, &second_medium, &high] {
+            repo.create_task(task).await.unwrap();
+        }
+
+        // 高优先级优先，开始执行后出队
+        let next = repo.get_next_task("/test", "worker1").await.unwrap().unwrap();
+        assert_eq!(next.id, high.id);
+        high.start(WorkerId::new("worker1".to_string()).unwrap()).unwrap();
+        repo.update_task(&high).await.unwrap();
+
+        // 同优先级按创建时间先后
+        let next = repo.get_next_task("/test", "worker1").await.unwrap().unwrap();
+        assert_eq!(next.id, first_medium.id);
+
+        // 失败后重试的任务重新入队
+        high.fail("boom".to_string()).unwrap();
+        repo.update_task(&high).await.unwrap();
+        assert_eq!(repo.retry_failed_tasks(3).await.unwrap(), 1);
+        let next = repo.get_next_task("/test", "worker1").await.unwrap().unwrap();
+        assert_eq!(next.id, high.id);
+
+        repo.delete_task(&high.id).await.unwrap();
+        let next = repo.get_next_task("/test", "worker1").await.unwrap().unwrap();
+        assert_eq!(next.id, first_medium.id);
+    }
+
     #[tokio::test]
     async fn test_get_next_task_wrong_directory() {
         let repo = InMemoryTaskRepository::new();
@@ -207,6 +245,40 @@ mod tests {
         assert_eq!(tasks[0].status, TaskStatus::Completed);
     }
 
+    #[tokio::test]
+    async fn test_list_tasks_multi_key_sort_is_stable() {
+        let repo = InMemoryTaskRepository::new();
+        let created_at = Utc::now();
+        
+        let mut tasks = Vec::new();
+        for priority in [TaskPriority::Low, TaskPriority::High, TaskPriority::High, TaskPriority::Medium] {
+            let mut task = Task::new("/test".to_string(), "test prompt".to_string(), priority, vec![]);
+            task.created_at = created_at;
+            repo.create_task(&task).await.unwrap();
+            tasks.push(task);
+        }
+        
+        let sort = TaskSort::parse("priority:desc,created_at").unwrap();
+        let (listed, _) = repo.list_tasks(&TaskFilter::new().with_sort(sort.clone())).await.unwrap();
+        let priorities: Vec<_> = listed.iter().map(|t| t.priority.as_i32()).collect();
+        assert_eq!(priorities, vec![3, 3, 2, 1]);
+        
+        // 排序值相同的任务按 task_id 排序，分页之间顺序不变
+        assert!(listed[0].id.to_string() < listed[1].id.to_string());
+        let (first, _) = repo.list_tasks(&TaskFilter::new().with_sort(sort.clone()).with_limit(2)).await.unwrap();
+        let (second, _) = repo.list_tasks(&TaskFilter::new().with_sort(sort).with_limit(2).with_offset(2)).await.unwrap();
+        let paged: Vec<_> = first.iter().chain(second.iter()).map(|t| t.id).collect();
+        assert_eq!(paged, listed.iter().map(|t| t.id).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_task_sort_rejects_unknown_fields() {
+        assert!(TaskSort::parse("prompt").unwrap_err().contains("allowed: task_id"));
+        assert!(TaskSort::parse("created_at:sideways").is_err());
+        assert!(TaskSort::parse("priority,priority:desc").is_err());
+        assert!(TaskSort::parse("created_at; DROP TABLE tasks").is_err());
+    }
+
     #[tokio::test]
     async fn test_list_tasks_with_priority_filter() {
         let repo = InMemoryTaskRepository::new();
@@ -384,7 +456,7 @@ mod tests {
     #[tokio::test]
     async fn test_simple_lock_manager_new() {
         let lock_manager = SimpleLockManager::new();
-        assert_eq!(lock_manager.locks.read().await.len(), 0);
+        assert_eq!(lock_manager.lock_count().await, 0);
     }
 
     #[tokio::test]
@@ -394,9 +466,7 @@ mod tests {
         let result = lock_manager.try_acquire("resource1", "owner1", 60).await.unwrap();
         assert!(result);
 
-        let locks = lock_manager.locks.read().await;
-        assert!(locks.contains_key("resource1"));
-        assert_eq!(locks.get("resource1").unwrap().0, "owner1");
+        assert_eq!(lock_manager.check_lock("resource1").await.unwrap(), Some("owner1".to_string()));
     }
 
     #[tokio::test]
@@ -430,11 +500,11 @@ mod tests {
         let lock_manager = SimpleLockManager::new();
         
         lock_manager.try_acquire("resource1", "owner1", 60).await.unwrap();
-        assert_eq!(lock_manager.locks.read().await.len(), 1);
+        assert_eq!(lock_manager.lock_count().await, 1);
 
         let result = lock_manager.release("resource1", "owner1").await.unwrap();
         assert!(result);
-        assert_eq!(lock_manager.locks.read().await.len(), 0);
+        assert_eq!(lock_manager.lock_count().await, 0);
     }
 
     #[tokio::test]
@@ -445,7 +515,7 @@ mod tests {
         
         let result = lock_manager.release("resource1", "owner2").await.unwrap();
         assert!(!result);
-        assert_eq!(lock_manager.locks.read().await.len(), 1);
+        assert_eq!(lock_manager.lock_count().await, 1);
     }
 
     #[tokio::test]
@@ -476,6 +546,25 @@ mod tests {
         assert!(owner.is_none());
     }
 
+    #[tokio::test]
+    async fn test_background_lock_cleanup() {
+        let lock_manager = Arc::new(SimpleLockManager::new());
+        lock_manager.try_acquire("resource1", "owner1", 1).await.unwrap();
+        lock_manager.try_acquire("resource2", "owner2", 60).await.unwrap();
+
+        // 过期的锁在清理前就不再阻止其他持有者获取
+        sleep(Duration::from_secs(2)).await;
+        assert!(lock_manager.try_acquire("resource1", "owner3", 1).await.unwrap());
+
+        let cleanup = lock_manager.start_cleanup(Duration::from_millis(100));
+        sleep(Duration::from_secs(2)).await;
+        assert_eq!(lock_manager.lock_count().await, 1);
+
+        // 锁管理器释放后清理任务退出
+        drop(lock_manager);
+        tokio::time::timeout(Duration::from_secs(1), cleanup).await.unwrap().unwrap();
+    }
+
     #[tokio::test]
     async fn test_cleanup_expired_locks() {
         let lock_manager = SimpleLockManager::new();
@@ -489,8 +578,7 @@ mod tests {
         let cleaned = lock_manager.cleanup_expired_locks().await.unwrap();
         assert_eq!(cleaned, 1);
         
-        let locks = lock_manager.locks.read().await;
-        assert_eq!(locks.len(), 1);
-        assert!(locks.contains_key("resource2"));
+        assert_eq!(lock_manager.lock_count().await, 1);
+        assert_eq!(lock_manager.check_lock("resource2").await.unwrap(), Some("owner2".to_string()));
     }
 }
\ No newline at end of file
@@ -5,6 +5,7 @@ mod tests {
     use crate::infrastructure::*;
     use chrono::Utc;
     use mockall::mock;
+    use task_store::StoreResult;
     use std::sync::Arc;
 
     mock! {
@@ -27,10 +28,11 @@ mod tests {
         pub LockManager {}
         #[async_trait]
         impl LockManager for LockManager {
-            async fn try_acquire(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> Result<bool, String>;
-            async fn release(&self, resource_id: &str, owner_id: &str) -> Result<bool, String>;
-            async fn check_lock(&self, resource_id: &str) -> Result<Option<String>, String>;
-            async fn cleanup_expired_locks(&self) -> Result<u64, String>;
+            async fn try_acquire(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> StoreResult<bool>;
+            async fn renew(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> StoreResult<bool>;
+            async fn release(&self, resource_id: &str, owner_id: &str) -> StoreResult<bool>;
+            async fn check_lock(&self, resource_id: &str) -> StoreResult<Option<String>>;
+            async fn cleanup_expired_locks(&self) -> StoreResult<u64>;
         }
     }
 
@@ -55,6 +57,7 @@ mod tests {
             prompt: "test prompt".to_string(),
             priority: Some(TaskPriority::High),
             tags: Some(vec!["tag1".to_string()]),
+            metadata: None,
         };
 
         let expected_task_id = TaskId::new();
@@ -87,6 +90,7 @@ mod tests {
             prompt: "test prompt".to_string(),
             priority: None,
             tags: None,
+            metadata: None,
         };
 
         let result = service.create_task(request).await;
@@ -103,6 +107,7 @@ mod tests {
             prompt: "test prompt".to_string(),
             priority: None,
             tags: None,
+            metadata: None,
         };
 
         let result = service.create_task(request).await;
@@ -119,6 +124,7 @@ mod tests {
             prompt: "".to_string(), // 空提示
             priority: None,
             tags: None,
+            metadata: None,
         };
 
         let result = service.create_task(request).await;
@@ -135,6 +141,7 @@ mod tests {
             prompt: "test prompt".to_string(),
             priority: None,
             tags: Some(vec!["".to_string()]), // 空标签
+            metadata: None,
         };
 
         let result = service.create_task(request).await;
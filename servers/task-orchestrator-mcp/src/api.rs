@@ -1,15 +1,17 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    response::Json,
-    routing::{get, post},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post, put},
     Router,
 };
 use serde::Deserialize;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::storage::{InMemoryTaskRepository, TaskRepository};
+use crate::storage::{InMemoryTaskRepository, RepositoryError, TaskRepository};
+use crate::tools::ToolAvailability;
 use crate::models::{CreateTaskRequest, TaskFilter, TaskResult, TaskPriority, TaskStatus};
 
 #[derive(Deserialize)]
@@ -30,6 +32,11 @@ pub struct CompleteTaskParams {
     pub metadata: Option<serde_json::Value>,
 }
 
+#[derive(Deserialize)]
+pub struct SetToolParams {
+    pub enabled: bool,
+}
+
 #[derive(Deserialize)]
 pub struct ListTasksQuery {
     pub status: Option<String>,
@@ -39,6 +46,41 @@ pub struct ListTasksQuery {
     pub offset: Option<u32>,
 }
 
+/// RFC 7807 问题详情
+pub type ProblemDetails = problem_details::ProblemDetails;
+
+impl From<RepositoryError> for ProblemDetails {
+    fn from(err: RepositoryError) -> Self {
+        let (status, code) = match err {
+            RepositoryError::TaskNotFound(_) => (StatusCode::NOT_FOUND, "NOT_FOUND"),
+            RepositoryError::InvalidStateTransition | RepositoryError::TaskLocked => {
+                (StatusCode::CONFLICT, "CONFLICT")
+            }
+            RepositoryError::Storage(_) => (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR"),
+        };
+        Self::new(status, code, err.to_string())
+    }
+}
+
+/// 进程启动以来发生的panic次数，由panic钩子累加
+pub static PANICS_TOTAL: AtomicU64 = AtomicU64::new(0);
+
+/// 请求处理panic时返回500问题详情，panic消息只写入日志
+pub fn panic_response(payload: Box<dyn std::any::Any + Send + 'static>) -> Response {
+    let problem = ProblemDetails::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "INTERNAL_ERROR",
+        "The server hit an unexpected error while handling the request".to_string(),
+    );
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_default();
+    tracing::error!(trace_id = %problem.trace_id, panic.message = %message, "Request handler panicked");
+    problem.into_response()
+}
+
 pub fn create_api_routes(task_repository: Arc<InMemoryTaskRepository>) -> Router {
     Router::new()
         .route("/tasks", post(create_task).get(list_tasks))
@@ -49,10 +91,25 @@ pub fn create_api_routes(task_repository: Arc<InMemoryTaskRepository>) -> Router
         .with_state(task_repository)
 }
 
+/// 工具开关接口，切换后已连接的MCP会话会收到 `tools/list_changed` 和服务状态通知
+pub fn create_tool_routes(tools: Arc<ToolAvailability>) -> Router {
+    Router::new()
+        .route("/tools", get(list_tools))
+        .route("/tools/:name", put(set_tool))
+        .with_state(tools)
+}
+
+/// Routes served on the dedicated metrics listener.
+pub fn create_metrics_routes(task_repository: Arc<InMemoryTaskRepository>) -> Router {
+    Router::new()
+        .route("/metrics", get(get_statistics))
+        .with_state(task_repository)
+}
+
 async fn create_task(
     State(task_repository): State<Arc<InMemoryTaskRepository>>,
     Json(params): Json<CreateTaskParams>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ProblemDetails> {
     let task_priority = match params.priority.as_deref() {
         Some("low") => TaskPriority::Low,
         Some("medium") => TaskPriority::Medium,
@@ -75,29 +132,30 @@ async fn create_task(
             "success": true,
             "task": task
         }))),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => Err(e.into()),
     }
 }
 
 async fn get_task(
     State(task_repository): State<Arc<InMemoryTaskRepository>>,
     Path(id): Path<String>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let task_id = Uuid::parse_str(&id).map_err(|_| StatusCode::BAD_REQUEST)?;
+) -> Result<Json<serde_json::Value>, ProblemDetails> {
+    let task_id = Uuid::parse_str(&id)
+        .map_err(|_| ProblemDetails::new(StatusCode::BAD_REQUEST, "VALIDATION_ERROR", format!("Invalid task ID: {}", id)))?;
 
     match task_repository.get_task(task_id).await {
         Ok(task) => Ok(Json(serde_json::json!({
             "success": true,
             "task": task
         }))),
-        Err(_) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(e.into()),
     }
 }
 
 async fn list_tasks(
     State(task_repository): State<Arc<InMemoryTaskRepository>>,
     Query(params): Query<ListTasksQuery>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ProblemDetails> {
     let mut filter = TaskFilter {
         status: None,
         priority: None,
@@ -134,7 +192,7 @@ async fn list_tasks(
             "tasks": tasks,
             "count": tasks.len()
         }))),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => Err(e.into()),
     }
 }
 
@@ -142,8 +200,9 @@ async fn complete_task(
     State(task_repository): State<Arc<InMemoryTaskRepository>>,
     Path(id): Path<String>,
     Json(params): Json<CompleteTaskParams>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let task_id = Uuid::parse_str(&id).map_err(|_| StatusCode::BAD_REQUEST)?;
+) -> Result<Json<serde_json::Value>, ProblemDetails> {
+    let task_id = Uuid::parse_str(&id)
+        .map_err(|_| ProblemDetails::new(StatusCode::BAD_REQUEST, "VALIDATION_ERROR", format!("Invalid task ID: {}", id)))?;
 
     let result = TaskResult {
         status: params.status.clone(),
@@ -155,7 +214,13 @@ async fn complete_task(
     let result = match params.status.as_str() {
         "success" => task_repository.complete_task(task_id, result).await,
         "failed" => task_repository.fail_task(task_id, params.output).await,
-        _ => return Err(StatusCode::BAD_REQUEST),
+        other => {
+            return Err(ProblemDetails::new(
+                StatusCode::BAD_REQUEST,
+                "VALIDATION_ERROR",
+                format!("Invalid completion status: {}", other),
+            ))
+        }
     };
 
     match result {
@@ -163,14 +228,14 @@ async fn complete_task(
             "success": true,
             "task": task
         }))),
-        Err(_) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(e.into()),
     }
 }
 
 async fn acquire_task(
     State(task_repository): State<Arc<InMemoryTaskRepository>>,
     Path(id): Path<String>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ProblemDetails> {
     let worker_id = id;
     let work_directory = "/tmp".to_string(); // 简化版本，使用固定目录
 
@@ -183,33 +248,65 @@ async fn acquire_task(
             "success": false,
             "message": "No tasks available"
         }))),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => Err(e.into()),
     }
 }
 
 async fn retry_task(
     State(task_repository): State<Arc<InMemoryTaskRepository>>,
     Path(id): Path<String>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let task_id = Uuid::parse_str(&id).map_err(|_| StatusCode::BAD_REQUEST)?;
+) -> Result<Json<serde_json::Value>, ProblemDetails> {
+    let task_id = Uuid::parse_str(&id)
+        .map_err(|_| ProblemDetails::new(StatusCode::BAD_REQUEST, "VALIDATION_ERROR", format!("Invalid task ID: {}", id)))?;
 
     match task_repository.retry_task(task_id).await {
         Ok(task) => Ok(Json(serde_json::json!({
             "success": true,
             "task": task
         }))),
-        Err(_) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(e.into()),
     }
 }
 
 async fn get_statistics(
     State(task_repository): State<Arc<InMemoryTaskRepository>>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ProblemDetails> {
     match task_repository.get_statistics().await {
         Ok(stats) => Ok(Json(serde_json::json!({
             "success": true,
-            "statistics": stats
+            "statistics": stats,
+            "panics_total": PANICS_TOTAL.load(Ordering::Relaxed)
         }))),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => Err(e.into()),
     }
-}
\ No newline at end of file
+}
+
+async fn list_tools(State(tools): State<Arc<ToolAvailability>>) -> Json<serde_json::Value> {
+    let list: Vec<_> = tools
+        .tools()
+        .into_iter()
+        .map(|(name, enabled)| serde_json::json!({"name": name, "enabled": enabled}))
+        .collect();
+    Json(serde_json::json!({
+        "success": true,
+        "tools": list,
+        "status": tools.status()
+    }))
+}
+
+async fn set_tool(
+    State(tools): State<Arc<ToolAvailability>>,
+    Path(name): Path<String>,
+    Json(params): Json<SetToolParams>,
+) -> Result<Json<serde_json::Value>, ProblemDetails> {
+    let changed = tools
+        .set_enabled(&name, params.enabled)
+        .map_err(|e| ProblemDetails::new(StatusCode::NOT_FOUND, "NOT_FOUND", e.to_string()))?;
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "tool": name,
+        "enabled": params.enabled,
+        "changed": changed,
+        "status": tools.status()
+    })))
+}
@@ -20,6 +20,8 @@ pub struct Config {
     pub logging: LoggingConfig,
     pub task: TaskConfig,
     pub monitoring: MonitoringConfig,
+    #[serde(default)]
+    pub tools: ToolsConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,6 +119,9 @@ pub struct MonitoringConfig {
     pub metrics_interval_seconds: u32,
     pub health_check_enabled: bool,
     pub health_check_interval_seconds: u32,
+    /// Dedicated port serving `/metrics` and `/health` only
+    #[serde(default)]
+    pub metrics_port: Option<u16>,
 }
 
 impl Default for MonitoringConfig {
@@ -126,45 +131,58 @@ impl Default for MonitoringConfig {
             metrics_interval_seconds: 60,
             health_check_enabled: true,
             health_check_interval_seconds: 30,
+            metrics_port: None,
         }
     }
 }
 
+/// MCP工具开关，收到SIGHUP时重新读取
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ToolsConfig {
+    /// 停用的工具名，停用的工具不出现在 `tools/list` 中，调用时返回错误
+    pub disabled: Vec<String>,
+}
+
+/// 读取环境变量，未设置时读取 `NAME_FILE` 指向的文件（Docker secrets）
+fn env_var(name: &str) -> Result<Option<String>, ConfigError> {
+    config_schema::env_var(name).map_err(|e| ConfigError::Invalid(e.to_string()))
+}
 
 impl Config {
     pub fn from_env() -> Result<Self, ConfigError> {
         let mut config = Config::default();
 
         // Server configuration
-        if let Ok(host) = std::env::var("SERVER_HOST") {
+        if let Some(host) = env_var("SERVER_HOST")? {
             config.server.host = host;
         }
-        if let Ok(port_str) = std::env::var("SERVER_PORT") {
+        if let Some(port_str) = env_var("SERVER_PORT")? {
             config.server.port = port_str.parse().map_err(|e| {
                 ConfigError::Invalid(format!("Invalid SERVER_PORT: {e}"))
             })?;
         }
-        if let Ok(timeout_str) = std::env::var("SERVER_TIMEOUT") {
+        if let Some(timeout_str) = env_var("SERVER_TIMEOUT")? {
             config.server.timeout_seconds = timeout_str.parse().map_err(|e| {
                 ConfigError::Invalid(format!("Invalid SERVER_TIMEOUT: {e}"))
             })?;
         }
 
         // Database configuration
-        if let Ok(database_url) = std::env::var("DATABASE_URL") {
+        if let Some(database_url) = env_var("DATABASE_URL")? {
             config.database.url = database_url;
         }
-        if let Ok(max_connections_str) = std::env::var("DATABASE_MAX_CONNECTIONS") {
+        if let Some(max_connections_str) = env_var("DATABASE_MAX_CONNECTIONS")? {
             config.database.max_connections = max_connections_str.parse().map_err(|e| {
                 ConfigError::Invalid(format!("Invalid DATABASE_MAX_CONNECTIONS: {e}"))
             })?;
         }
 
         // Logging configuration
-        if let Ok(log_level) = std::env::var("RUST_LOG") {
+        if let Some(log_level) = env_var("RUST_LOG")? {
             config.logging.level = log_level;
         }
-        if let Ok(log_format) = std::env::var("LOG_FORMAT") {
+        if let Some(log_format) = env_var("LOG_FORMAT")? {
             config.logging.format = match log_format.as_str() {
                 "json" => LogFormat::Json,
                 "pretty" => LogFormat::Pretty,
@@ -173,17 +191,34 @@ impl Config {
         }
 
         // Task configuration
-        if let Ok(max_concurrent_str) = std::env::var("TASK_MAX_CONCURRENT") {
+        if let Some(max_concurrent_str) = env_var("TASK_MAX_CONCURRENT")? {
             config.task.max_concurrent_tasks = max_concurrent_str.parse().map_err(|e| {
                 ConfigError::Invalid(format!("Invalid TASK_MAX_CONCURRENT: {e}"))
             })?;
         }
-        if let Ok(max_retries_str) = std::env::var("TASK_MAX_RETRIES") {
+        if let Some(max_retries_str) = env_var("TASK_MAX_RETRIES")? {
             config.task.max_retries = max_retries_str.parse().map_err(|e| {
                 ConfigError::Invalid(format!("Invalid TASK_MAX_RETRIES: {e}"))
             })?;
         }
 
+        // Monitoring configuration
+        if let Some(metrics_port_str) = env_var("METRICS_PORT")? {
+            config.monitoring.metrics_port = Some(metrics_port_str.parse().map_err(|e| {
+                ConfigError::Invalid(format!("Invalid METRICS_PORT: {e}"))
+            })?);
+        }
+
+        // Tools configuration
+        if let Some(disabled) = env_var("TOOLS_DISABLED")? {
+            config.tools.disabled = disabled
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(String::from)
+                .collect();
+        }
+
         Ok(config)
     }
 
@@ -196,11 +231,15 @@ impl Config {
             ConfigError::Invalid(format!("Failed to read config file: {e}"))
         })?;
 
-        let config: Config = toml::from_str(&content).map_err(|e| {
+        let mut value: serde_json::Value = toml::from_str(&content).map_err(|e| {
             ConfigError::Invalid(format!("Failed to parse config file: {e}"))
         })?;
+        // 展开字符串值中的 `${ENV_VAR}`，数据库连接串等密钥不必写进配置文件
+        config_schema::interpolate_value(&mut value).map_err(|e| ConfigError::Invalid(e.to_string()))?;
 
-        Ok(config)
+        serde_json::from_value(value).map_err(|e| {
+            ConfigError::Invalid(format!("Failed to parse config file: {e}"))
+        })
     }
 
     pub fn from_file_or_env(path: &PathBuf) -> Result<Self, ConfigError> {
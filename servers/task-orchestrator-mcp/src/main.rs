@@ -49,29 +49,31 @@
 //! # 健康检查
 //! curl http://127.0.0.1:8080/health
 //! 
-//! # MCP工具调用
-//! curl -X POST http://127.0.0.1:8080/ \
-//!   -H "Content-Type: application/json" \
-//!   -d '{"jsonrpc":"2.0","method":"create_task","params":{"title":"Test Task","description":"Test Description"},"id":1}'
+//! # MCP端点（Streamable HTTP），工具列表中的Schema由参数和结果类型派生
+//! curl -X POST http://127.0.0.1:8080/mcp \
+//!   -H "Content-Type: application/json" -H "Accept: application/json, text/event-stream" \
+//!   -d '{"jsonrpc":"2.0","method":"initialize","params":{"protocolVersion":"2025-03-26","capabilities":{},"clientInfo":{"name":"curl","version":"1"}},"id":1}'
 //! ```
 
 use std::sync::Arc;
 use std::path::PathBuf;
 use std::net::SocketAddr;
 use tokio::signal;
-use tower_http::{trace::TraceLayer, cors::CorsLayer, compression::CompressionLayer};
+use tower_http::{trace::TraceLayer, cors::CorsLayer, compression::CompressionLayer, catch_panic::CatchPanicLayer};
 use tower::ServiceBuilder;
 
 use crate::config::Config;
 use crate::storage::InMemoryTaskRepository;
 use crate::server::TaskOrchestratorServer;
-use crate::api::create_api_routes;
+use crate::api::{create_api_routes, create_metrics_routes, create_tool_routes, panic_response, PANICS_TOTAL};
+use crate::tools::ToolAvailability;
 
 mod config;
 mod models;
 mod storage;
 mod server;
 mod api;
+mod tools;
 
 /// 应用程序主入口点
 /// 
@@ -116,6 +118,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     
     // Initialize logging
     init_logging(&config.logging);
+    install_panic_hook();
 
     println!("🚀 Starting Task Orchestrator MCP Server");
     println!("📋 Configuration loaded successfully");
@@ -125,23 +128,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Create task repository
     let task_repository = Arc::new(InMemoryTaskRepository::new());
 
-    // Create MCP server (reserved for future MCP service integration)
-    let _mcp_server = TaskOrchestratorServer::new(task_repository.clone());
+    // Create MCP server, served over Streamable HTTP at /mcp
+    let mcp_server = TaskOrchestratorServer::new(task_repository.clone());
+    let tools = mcp_server.tool_availability();
+    tools.set_disabled(&config.tools.disabled);
+    spawn_config_reload(config_path.clone(), tools.clone());
 
-    // Create HTTP service for MCP (not used in current implementation)
-
-    // Create HTTP router with API routes only (for testing)
+    // Create HTTP router with MCP and REST API routes
     let app = axum::Router::new()
         .route("/", axum::routing::get(|| async { "Task Orchestrator MCP Server" }))
         .route("/health", axum::routing::get(health_check))
-        .nest("/api", create_api_routes(task_repository.clone()))
+        .nest_service("/mcp", mcp_server.create_http_service())
+        .nest("/api", create_api_routes(task_repository.clone()).merge(create_tool_routes(tools.clone())))
         .layer(
             ServiceBuilder::new()
                 .layer(TraceLayer::new_for_http())
                 .layer(CorsLayer::permissive())
                 .layer(CompressionLayer::new())
+                .layer(CatchPanicLayer::custom(panic_response))
         );
 
+    // Dedicated metrics listener, so scrape endpoints can be firewalled separately
+    let metrics_app = config.monitoring.metrics_port.map(|port| {
+        let router = axum::Router::new()
+            .route("/health", axum::routing::get(health_check))
+            .merge(create_metrics_routes(task_repository.clone()));
+        (port, router)
+    });
+
     // Configure server address
     let addr = SocketAddr::new(
         config.server.host.parse()?,
@@ -153,12 +167,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     println!("   GET  /health - Health check");
     println!("   POST /      - MCP JSON-RPC over HTTP");
     println!("   GET  /      - MCP SSE stream");
+    println!("   GET  /api/tools       - Tool availability");
+    println!("   PUT  /api/tools/:name - Enable or disable a tool");
 
     // Start server
     let listener = tokio::net::TcpListener::bind(addr).await?;
+
+    let metrics_server = match metrics_app {
+        Some((port, metrics_app)) => {
+            let metrics_addr = SocketAddr::new(addr.ip(), port);
+            let metrics_listener = tokio::net::TcpListener::bind(metrics_addr).await?;
+            println!("📈 Metrics listening on {metrics_addr} (GET /metrics, GET /health)");
+            Some(tokio::spawn(async move {
+                if let Err(e) = axum::serve(metrics_listener, metrics_app).await {
+                    eprintln!("❌ Metrics listener failed: {e}");
+                }
+            }))
+        }
+        None => None,
+    };
     
     // Graceful shutdown handling
-    let shutdown_signal = async {
+    let shutdown_signal = async move {
         let ctrl_c = async {
             signal::ctrl_c()
                 .await
@@ -182,6 +212,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         }
 
         println!("🛑 Shutdown signal received");
+        tools.announce_shutdown();
     };
 
     // Start server
@@ -189,11 +220,41 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         .with_graceful_shutdown(shutdown_signal)
         .await?;
 
+    if let Some(metrics_server) = metrics_server {
+        metrics_server.abort();
+    }
+
     println!("✅ Server shutdown completed");
 
     Ok(())
 }
 
+/// 收到SIGHUP时重新读取配置文件中的 `[tools]`，工具集合变化后通知已连接的会话
+#[cfg(unix)]
+fn spawn_config_reload(config_path: PathBuf, tools: Arc<ToolAvailability>) {
+    tokio::spawn(async move {
+        let mut hangup = match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to install SIGHUP handler, config reload disabled");
+                return;
+            }
+        };
+        while hangup.recv().await.is_some() {
+            match Config::from_file_or_env(&config_path) {
+                Ok(config) => {
+                    let changed = tools.set_disabled(&config.tools.disabled);
+                    tracing::info!(changed, "Configuration reloaded");
+                }
+                Err(e) => tracing::error!(error = %e, "Failed to reload configuration"),
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+fn spawn_config_reload(_config_path: PathBuf, _tools: Arc<ToolAvailability>) {}
+
 /// 健康检查端点处理器
 /// 
 /// 该函数处理 `/health` 端点的GET请求，返回服务的健康状态信息。
@@ -297,6 +358,20 @@ fn init_logging(config: &crate::config::LoggingConfig) {
     tracing::info!("Logging initialized with level: {}", config.level);
 }
 
+/// 安装panic钩子：panic写入错误日志并计入 `panics_total`（见 `/api/statistics`）
+fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        PANICS_TOTAL.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let location = info.location().map(|l| l.to_string()).unwrap_or_default();
+        tracing::error!(
+            panic.location = %location,
+            backtrace = %std::backtrace::Backtrace::capture(),
+            "Panic occurred: {}",
+            info
+        );
+    }));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
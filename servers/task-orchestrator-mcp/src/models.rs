@@ -141,7 +141,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct Task {
     pub id: Uuid,
     pub work_directory: String,
@@ -217,7 +217,7 @@ impl From<i32> for TaskStatus {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct TaskResult {
     pub status: String,
     pub output: String,
@@ -262,7 +262,7 @@ pub struct TaskFilter {
     pub offset: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct TaskStatistics {
     pub total_tasks: u64,
     pub pending_tasks: u64,
@@ -273,4 +273,84 @@ pub struct TaskStatistics {
     pub cancelled_tasks: u64,
     pub average_completion_time_ms: u64,
     pub success_rate: f64,
-}
\ No newline at end of file
+}
+
+// MCP工具参数与结果
+//
+// 工具的 inputSchema / outputSchema 由以下类型派生，tools/list 直接返回生成的Schema。
+
+#[derive(Debug, Clone, Deserialize, schemars::JsonSchema)]
+pub struct TaskIdParams {
+    #[schemars(description = "Task ID (UUID)")]
+    pub task_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize, schemars::JsonSchema)]
+pub struct AcquireTaskParams {
+    #[schemars(description = "ID of the worker acquiring the task")]
+    pub worker_id: String,
+    #[schemars(description = "Only acquire tasks in this working directory")]
+    pub work_directory: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionStatus {
+    #[schemars(description = "The task succeeded")]
+    Success,
+    #[schemars(description = "The task failed; output is recorded as the error message")]
+    Failed,
+}
+
+#[derive(Debug, Clone, Deserialize, schemars::JsonSchema)]
+pub struct CompleteTaskParams {
+    #[schemars(description = "Task ID (UUID)")]
+    pub task_id: String,
+    #[schemars(description = "Outcome of the task")]
+    pub status: CompletionStatus,
+    #[schemars(description = "Task output, or the error message when the task failed")]
+    pub output: String,
+    #[serde(default)]
+    #[schemars(description = "Execution time in milliseconds")]
+    pub duration_ms: Option<u64>,
+    #[serde(default)]
+    #[schemars(description = "Additional result metadata")]
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, schemars::JsonSchema)]
+pub struct ListTasksParams {
+    #[serde(default)]
+    #[schemars(description = "Only list tasks with this status")]
+    pub status: Option<TaskStatus>,
+    #[serde(default)]
+    #[schemars(description = "Only list tasks with this priority")]
+    pub priority: Option<TaskPriority>,
+    #[serde(default)]
+    #[schemars(description = "Only list tasks acquired by this worker")]
+    pub worker_id: Option<String>,
+    #[serde(default)]
+    #[schemars(description = "Maximum number of tasks to return (default 50)")]
+    pub limit: Option<u32>,
+    #[serde(default)]
+    #[schemars(description = "Number of tasks to skip")]
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TaskActionResponse {
+    pub task_id: Uuid,
+    pub status: TaskStatus,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct AcquireTaskResponse {
+    /// The acquired task, or null when no task is available
+    pub task: Option<Task>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TaskListResponse {
+    pub tasks: Vec<Task>,
+}
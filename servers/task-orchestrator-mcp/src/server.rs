@@ -1,28 +1,126 @@
-use std::sync::Arc;
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock};
 use rmcp::{
-    ServerHandler,
-    model::{ServerInfo, ServerCapabilities},
+    ErrorData as McpError, RoleServer, ServerHandler,
+    handler::server::{router::tool::ToolRouter, tool::Parameters, wrapper::Json},
+    handler::server::tool::ToolCallContext,
+    model::{
+        CallToolRequestParam, CallToolResult, ClientCapabilities, Implementation, InitializeRequestParam,
+        InitializeResult, ListToolsResult, LoggingLevel, PaginatedRequestParam, ProtocolVersion, ServerCapabilities,
+        ServerInfo, SetLevelRequestParam,
+    },
+    service::{NotificationContext, RequestContext},
+    tool, tool_router,
     transport::streamable_http_server::{StreamableHttpService, StreamableHttpServerConfig},
     transport::streamable_http_server::session::local::LocalSessionManager,
 };
-use serde_json::json;
 use uuid::Uuid;
 
+use crate::tools::{forward_events, ToolAvailability};
 use crate::storage::{InMemoryTaskRepository, TaskRepository, RepositoryError};
-use crate::models::{CreateTaskRequest, TaskFilter, TaskResult};
+use crate::models::{
+    AcquireTaskParams, AcquireTaskResponse, CompleteTaskParams, CompletionStatus, CreateTaskRequest, ListTasksParams,
+    Task, TaskActionResponse, TaskFilter, TaskIdParams, TaskListResponse, TaskResult, TaskStatistics,
+};
+
+/// 支持的MCP协议版本，从新到旧
+pub const SUPPORTED_PROTOCOL_VERSIONS: [ProtocolVersion; 2] =
+    [ProtocolVersion::V_2025_03_26, ProtocolVersion::V_2024_11_05];
 
+/// 客户端在 `initialize` 中声明的信息
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct ClientSession {
+    /// 协商后的协议版本
+    pub protocol_version: ProtocolVersion,
+    pub client_info: Implementation,
+    pub capabilities: ClientCapabilities,
+}
+
+/// MCP服务器，工具的输入/输出Schema由参数和结果类型派生
+#[derive(Clone)]
 pub struct TaskOrchestratorServer {
-    #[allow(dead_code)] // Field is used by MCP tools but compiler doesn't detect it
     task_repository: Arc<InMemoryTaskRepository>,
+    tool_router: ToolRouter<Self>,
+    /// 当前会话的客户端信息，`initialize` 成功后写入
+    session: Arc<OnceLock<ClientSession>>,
+    /// 工具启用状态，所有会话共享
+    tools: Arc<ToolAvailability>,
+    /// 当前会话通过 `logging/setLevel` 设置的最低通知级别
+    log_level: Arc<Mutex<LoggingLevel>>,
 }
 
 impl TaskOrchestratorServer {
     pub fn new(task_repository: Arc<InMemoryTaskRepository>) -> Self {
-        Self { task_repository }
+        let tool_router = Self::tool_router();
+        let tools = Arc::new(ToolAvailability::new(
+            tool_router.list_all().into_iter().map(|tool| tool.name.to_string()),
+        ));
+        Self {
+            task_repository,
+            tool_router,
+            session: Arc::new(OnceLock::new()),
+            tools,
+            log_level: Arc::new(Mutex::new(LoggingLevel::Info)),
+        }
+    }
+
+    /// 为新会话创建服务器，共享任务存储和工具状态，客户端信息各自独立
+    fn for_session(&self) -> Self {
+        Self {
+            session: Arc::new(OnceLock::new()),
+            log_level: Arc::new(Mutex::new(LoggingLevel::Info)),
+            ..self.clone()
+        }
+    }
+
+    /// 工具启用状态，供REST接口和配置重载切换工具
+    pub fn tool_availability(&self) -> Arc<ToolAvailability> {
+        self.tools.clone()
+    }
+
+    /// 当前会话的客户端信息，`initialize` 之前为 `None`
+    #[allow(dead_code)]
+    pub fn client_session(&self) -> Option<&ClientSession> {
+        self.session.get()
+    }
+
+    /// 记录 `initialize` 请求中的客户端信息，返回协商后的协议版本
+    ///
+    /// 只接受 [`SUPPORTED_PROTOCOL_VERSIONS`] 中的版本，其他版本按MCP规范返回
+    /// `-32602 Unsupported protocol version` 错误，`data` 中列出支持的版本和请求的版本。
+    fn negotiate(&self, request: &InitializeRequestParam) -> Result<ProtocolVersion, McpError> {
+        let requested = &request.protocol_version;
+        if !SUPPORTED_PROTOCOL_VERSIONS.contains(requested) {
+            tracing::warn!(
+                client = %request.client_info.name,
+                requested = %requested,
+                "Rejected MCP client with unsupported protocol version"
+            );
+            return Err(McpError::invalid_params(
+                "Unsupported protocol version",
+                Some(serde_json::json!({
+                    "supported": SUPPORTED_PROTOCOL_VERSIONS,
+                    "requested": requested,
+                })),
+            ));
+        }
+
+        tracing::info!(
+            client = %request.client_info.name,
+            client_version = %request.client_info.version,
+            protocol_version = %requested,
+            "MCP client initialized"
+        );
+        let _ = self.session.set(ClientSession {
+            protocol_version: requested.clone(),
+            client_info: request.client_info.clone(),
+            capabilities: request.capabilities.clone(),
+        });
+        Ok(requested.clone())
     }
 
-    #[allow(dead_code)] // Reserved for future MCP HTTP service integration
+    /// 创建MCP的Streamable HTTP服务
     pub fn create_http_service(&self) -> StreamableHttpService<Self, LocalSessionManager> {
         let config = StreamableHttpServerConfig {
             sse_keep_alive: Some(std::time::Duration::from_secs(30)),
@@ -33,178 +131,122 @@ impl TaskOrchestratorServer {
         let server = self.clone();
 
         StreamableHttpService::new(
-            move || Ok(server.clone()),
+            move || Ok(server.for_session()),
             session_manager,
             config,
         )
     }
+}
 
-    // Tool implementations - these methods are used by MCP tools
-    #[allow(dead_code)] // Used by MCP tools
-    pub async fn create_task(
+#[tool_router]
+impl TaskOrchestratorServer {
+    #[tool(description = "Create a new task")]
+    async fn create_task(
         &self,
-        work_directory: String,
-        prompt: String,
-        priority: Option<String>,
-        tags: Option<Vec<String>>,
-        max_retries: Option<u32>,
-        timeout_seconds: Option<u32>,
-    ) -> Result<serde_json::Value, String> {
-        let task_priority = match priority.as_deref() {
-            Some("low") => crate::models::TaskPriority::Low,
-            Some("medium") => crate::models::TaskPriority::Medium,
-            Some("high") => crate::models::TaskPriority::High,
-            Some("urgent") => crate::models::TaskPriority::Urgent,
-            Some(_) | None => crate::models::TaskPriority::Medium,
-        };
-
-        let request = CreateTaskRequest {
-            work_directory,
-            prompt,
-            priority: Some(task_priority),
-            tags,
-            max_retries,
-            timeout_seconds,
-        };
-
+        Parameters(request): Parameters<CreateTaskRequest>,
+    ) -> Result<Json<TaskActionResponse>, String> {
         match self.task_repository.create_task(request).await {
-            Ok(task) => Ok(json!({
-                "task_id": task.id,
-                "status": format!("{:?}", task.status),
-                "message": "Task created successfully"
+            Ok(task) => Ok(Json(TaskActionResponse {
+                task_id: task.id,
+                status: task.status,
+                message: "Task created successfully".to_string(),
             })),
             Err(e) => Err(format!("Failed to create task: {e}")),
         }
     }
 
-    #[allow(dead_code)] // Used by MCP tools
-    pub async fn get_task(&self, task_id: String) -> Result<serde_json::Value, String> {
-        let task_id = Uuid::parse_str(&task_id).map_err(|e| format!("Invalid task ID: {e}"))?;
+    #[tool(description = "Get a task by ID")]
+    async fn get_task(&self, Parameters(params): Parameters<TaskIdParams>) -> Result<Json<Task>, String> {
+        let task_id = Uuid::parse_str(&params.task_id).map_err(|e| format!("Invalid task ID: {e}"))?;
 
         match self.task_repository.get_task(task_id).await {
-            Ok(task) => Ok(serde_json::to_value(task).unwrap()),
+            Ok(task) => Ok(Json(task)),
             Err(RepositoryError::TaskNotFound(_)) => Err("Task not found".to_string()),
             Err(e) => Err(format!("Failed to get task: {e}")),
         }
     }
 
-    #[allow(dead_code)] // Used by MCP tools
-    pub async fn acquire_task(&self, worker_id: String, work_directory: String) -> Result<serde_json::Value, String> {
-        match self.task_repository.acquire_task(worker_id, work_directory).await {
-            Ok(Some(task)) => Ok(serde_json::to_value(task).unwrap()),
-            Ok(None) => Ok(json!({
-                "message": "No tasks available for acquisition",
-                "task": null
-            })),
+    #[tool(description = "Acquire the next waiting task in a working directory")]
+    async fn acquire_task(
+        &self,
+        Parameters(params): Parameters<AcquireTaskParams>,
+    ) -> Result<Json<AcquireTaskResponse>, String> {
+        match self.task_repository.acquire_task(params.worker_id, params.work_directory).await {
+            Ok(task) => Ok(Json(AcquireTaskResponse { task })),
             Err(e) => Err(format!("Failed to acquire task: {e}")),
         }
     }
 
-    #[allow(dead_code)] // Used by MCP tools
-    pub async fn complete_task(
+    #[tool(description = "Complete a running task as succeeded or failed")]
+    async fn complete_task(
         &self,
-        task_id: String,
-        status: String,
-        output: String,
-        duration_ms: Option<u64>,
-        metadata: Option<serde_json::Value>,
-    ) -> Result<serde_json::Value, String> {
-        let task_id = Uuid::parse_str(&task_id).map_err(|e| format!("Invalid task ID: {e}"))?;
-
-        let result = TaskResult {
-            status: status.clone(),
-            output: output.clone(),
-            duration_ms: duration_ms.unwrap_or(0),
-            metadata: metadata.map(|m| serde_json::from_value(m).unwrap_or_default()).unwrap_or_default(),
-        };
+        Parameters(params): Parameters<CompleteTaskParams>,
+    ) -> Result<Json<TaskActionResponse>, String> {
+        let task_id = Uuid::parse_str(&params.task_id).map_err(|e| format!("Invalid task ID: {e}"))?;
 
-        match status.as_str() {
-            "success" => {
+        match params.status {
+            CompletionStatus::Success => {
+                let result = TaskResult {
+                    status: "success".to_string(),
+                    output: params.output,
+                    duration_ms: params.duration_ms.unwrap_or(0),
+                    metadata: params.metadata.unwrap_or_default(),
+                };
                 match self.task_repository.complete_task(task_id, result).await {
-                    Ok(task) => Ok(json!({
-                        "task_id": task.id,
-                        "status": format!("{:?}", task.status),
-                        "message": "Task completed successfully"
+                    Ok(task) => Ok(Json(TaskActionResponse {
+                        task_id: task.id,
+                        status: task.status,
+                        message: "Task completed successfully".to_string(),
                     })),
                     Err(e) => Err(format!("Failed to complete task: {e}")),
                 }
             }
-            "failed" => {
-                match self.task_repository.fail_task(task_id, output).await {
-                    Ok(task) => Ok(json!({
-                        "task_id": task.id,
-                        "status": format!("{:?}", task.status),
-                        "message": "Task marked as failed"
+            CompletionStatus::Failed => {
+                match self.task_repository.fail_task(task_id, params.output).await {
+                    Ok(task) => Ok(Json(TaskActionResponse {
+                        task_id: task.id,
+                        status: task.status,
+                        message: "Task marked as failed".to_string(),
                     })),
                     Err(e) => Err(format!("Failed to mark task as failed: {e}")),
                 }
             }
-            _ => Err("Invalid status. Must be 'success' or 'failed'".to_string()),
         }
     }
 
-    #[allow(dead_code)] // Used by MCP tools
-    pub async fn list_tasks(
-        &self,
-        status: Option<String>,
-        priority: Option<String>,
-        worker_id: Option<String>,
-        limit: Option<u32>,
-        offset: Option<u32>,
-    ) -> Result<serde_json::Value, String> {
-        let mut filter = TaskFilter {
-            status: None,
-            priority: None,
-            worker_id,
-            limit: limit.unwrap_or(50),
-            offset: offset.unwrap_or(0),
+    #[tool(description = "List tasks, optionally filtered by status, priority or worker")]
+    async fn list_tasks(&self, Parameters(params): Parameters<ListTasksParams>) -> Result<Json<TaskListResponse>, String> {
+        let filter = TaskFilter {
+            status: params.status,
+            priority: params.priority,
+            worker_id: params.worker_id,
+            limit: params.limit.unwrap_or(50),
+            offset: params.offset.unwrap_or(0),
         };
 
-        if let Some(status_str) = status {
-            filter.status = match status_str.as_str() {
-                "pending" => Some(crate::models::TaskStatus::Pending),
-                "waiting" => Some(crate::models::TaskStatus::Waiting),
-                "running" => Some(crate::models::TaskStatus::Running),
-                "completed" => Some(crate::models::TaskStatus::Completed),
-                "failed" => Some(crate::models::TaskStatus::Failed),
-                "cancelled" => Some(crate::models::TaskStatus::Cancelled),
-                _ => None,
-            };
-        }
-
-        if let Some(priority_str) = priority {
-            filter.priority = match priority_str.as_str() {
-                "low" => Some(crate::models::TaskPriority::Low),
-                "medium" => Some(crate::models::TaskPriority::Medium),
-                "high" => Some(crate::models::TaskPriority::High),
-                "urgent" => Some(crate::models::TaskPriority::Urgent),
-                _ => None,
-            };
-        }
-
         match self.task_repository.list_tasks(filter).await {
-            Ok(tasks) => Ok(serde_json::to_value(tasks).unwrap()),
+            Ok(tasks) => Ok(Json(TaskListResponse { tasks })),
             Err(e) => Err(format!("Failed to list tasks: {e}")),
         }
     }
 
-    #[allow(dead_code)] // Used by MCP tools
-    pub async fn get_statistics(&self) -> Result<serde_json::Value, String> {
+    #[tool(description = "Get task statistics")]
+    async fn get_statistics(&self) -> Result<Json<TaskStatistics>, String> {
         match self.task_repository.get_statistics().await {
-            Ok(stats) => Ok(serde_json::to_value(stats).unwrap()),
+            Ok(stats) => Ok(Json(stats)),
             Err(e) => Err(format!("Failed to get statistics: {e}")),
         }
     }
 
-    #[allow(dead_code)] // Used by MCP tools
-    pub async fn retry_task(&self, task_id: String) -> Result<serde_json::Value, String> {
-        let task_id = Uuid::parse_str(&task_id).map_err(|e| format!("Invalid task ID: {e}"))?;
+    #[tool(description = "Retry a failed task")]
+    async fn retry_task(&self, Parameters(params): Parameters<TaskIdParams>) -> Result<Json<TaskActionResponse>, String> {
+        let task_id = Uuid::parse_str(&params.task_id).map_err(|e| format!("Invalid task ID: {e}"))?;
 
         match self.task_repository.retry_task(task_id).await {
-            Ok(task) => Ok(json!({
-                "task_id": task.id,
-                "status": format!("{:?}", task.status),
-                "message": "Task retry initiated successfully"
+            Ok(task) => Ok(Json(TaskActionResponse {
+                task_id: task.id,
+                status: task.status,
+                message: "Task retry initiated successfully".to_string(),
             })),
             Err(e) => Err(format!("Failed to retry task: {e}")),
         }
@@ -214,15 +256,142 @@ impl TaskOrchestratorServer {
 impl ServerHandler for TaskOrchestratorServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
-            protocol_version: rmcp::model::ProtocolVersion::default(),
+            protocol_version: ProtocolVersion::LATEST,
             capabilities: ServerCapabilities::builder()
                 .enable_tools()
+                .enable_tool_list_changed()
+                .enable_logging()
                 .build(),
-            server_info: rmcp::model::Implementation {
+            server_info: Implementation {
                 name: "task-orchestrator-mcp".to_string(),
-                version: "0.1.0".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
             },
             instructions: Some("A task orchestrator MCP server for managing and executing tasks".to_string()),
         }
     }
-}
\ No newline at end of file
+
+    async fn initialize(
+        &self,
+        request: InitializeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<InitializeResult, McpError> {
+        let protocol_version = self.negotiate(&request)?;
+        if context.peer.peer_info().is_none() {
+            context.peer.set_peer_info(request);
+        }
+        Ok(InitializeResult {
+            protocol_version,
+            ..self.get_info()
+        })
+    }
+
+    /// 客户端完成初始化后开始推送工具列表变化和服务状态
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        let log_level = self.log_level.clone();
+        tokio::spawn(forward_events(context.peer, self.tools.subscribe(), move || {
+            *log_level.lock().unwrap()
+        }));
+    }
+
+    async fn set_level(&self, request: SetLevelRequestParam, _context: RequestContext<RoleServer>) -> Result<(), McpError> {
+        *self.log_level.lock().unwrap() = request.level;
+        Ok(())
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        let tools = self
+            .tool_router
+            .list_all()
+            .into_iter()
+            .filter(|tool| self.tools.is_enabled(&tool.name))
+            .collect();
+        Ok(ListToolsResult::with_all_items(tools))
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.tools.is_enabled(&request.name) {
+            return Err(McpError::invalid_params(
+                format!("Tool is temporarily disabled: {}", request.name),
+                Some(serde_json::json!({"tool": request.name})),
+            ));
+        }
+        let tcc = ToolCallContext::new(self, request, context);
+        self.tool_router.call(tcc).await
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_schemas_are_generated_from_types() {
+        let server = TaskOrchestratorServer::new(Arc::new(InMemoryTaskRepository::new()));
+        let tools = server.tool_router.list_all();
+        let tool = |name: &str| tools.iter().find(|t| t.name == name).unwrap().clone();
+        assert_eq!(tools.len(), 7);
+
+        let create = tool("create_task");
+        assert_eq!(create.input_schema["required"], serde_json::json!(["work_directory", "prompt"]));
+        let output = create.output_schema.unwrap();
+        assert!(output["properties"]["task_id"].is_object());
+
+        // 枚举参数的取值来自类型定义
+        let complete = serde_json::to_value(tool("complete_task")).unwrap();
+        assert!(complete["inputSchema"].to_string().contains("\"success\""));
+        assert!(tool("list_tasks").output_schema.unwrap()["properties"]["tasks"].is_object());
+        assert!(tool("get_statistics").output_schema.unwrap()["properties"]["success_rate"].is_object());
+    }
+
+    #[test]
+    fn test_protocol_version_negotiation() {
+        let server = TaskOrchestratorServer::new(Arc::new(InMemoryTaskRepository::new()));
+        let request = |version: &str| -> InitializeRequestParam {
+            serde_json::from_value(serde_json::json!({
+                "protocolVersion": version,
+                "capabilities": {"roots": {"listChanged": true}},
+                "clientInfo": {"name": "test-client", "version": "1.0"}
+            }))
+            .unwrap()
+        };
+
+        let error = server.negotiate(&request("1999-01-01")).unwrap_err();
+        assert_eq!(error.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert_eq!(error.message, "Unsupported protocol version");
+        assert_eq!(
+            error.data.unwrap(),
+            serde_json::json!({"supported": ["2025-03-26", "2024-11-05"], "requested": "1999-01-01"})
+        );
+        assert!(server.client_session().is_none());
+
+        // 支持的旧版本按客户端请求的版本协商
+        assert_eq!(server.negotiate(&request("2024-11-05")).unwrap(), ProtocolVersion::V_2024_11_05);
+        let session = server.client_session().unwrap();
+        assert_eq!(session.protocol_version, ProtocolVersion::V_2024_11_05);
+        assert_eq!(session.client_info.name, "test-client");
+        assert!(session.capabilities.roots.is_some());
+
+        // 每个会话单独记录客户端信息
+        assert!(server.for_session().client_session().is_none());
+    }
+
+    #[test]
+    fn test_sessions_share_tool_availability() {
+        let server = TaskOrchestratorServer::new(Arc::new(InMemoryTaskRepository::new()));
+        let session = server.for_session();
+        assert_eq!(server.tool_availability().tools().len(), 7);
+
+        // 任一入口停用工具，所有会话都能看到，并收到列表变化通知
+        let mut events = session.tools.subscribe();
+        server.tool_availability().set_enabled("retry_task", false).unwrap();
+        assert!(!session.tools.is_enabled("retry_task"));
+        assert_eq!(events.try_recv().unwrap(), crate::tools::ServerEvent::ToolListChanged);
+    }
+}
@@ -119,8 +119,8 @@
 //! 
 //! ## 内存存储特点
 //! 
-//! - **高性能**: 基于内存的HashMap存储
-//! - **线程安全**: 使用Tokio的RwLock实现异步并发控制
+//! - **共享实现**: 任务存取和调度使用 `task-store` 的 `MemoryTaskRepository`，与其他编排服务共用
+//! - **按目录排队**: 等待中的任务按工作目录放入优先队列，获取任务不需要扫描全表
 //! - **自动清理**: 支持定期清理过期任务
 //! - **分页支持**: 支持limit和offset分页
 //! - **排序功能**: 按优先级和创建时间排序
@@ -132,6 +132,7 @@
 //! - `TaskNotFound`: 任务不存在
 //! - `InvalidStateTransition`: 非法状态转换
 //! - `TaskLocked`: 任务被锁定（预留功能）
+//! - `Storage`: 存储后端错误
 //! 
 //! ## 扩展性
 //! 
@@ -149,12 +150,10 @@
 //! - 高效的过滤和排序算法
 //! - 最小化锁的持有时间
 
-use std::sync::Arc;
-use std::collections::HashMap;
-use tokio::sync::RwLock;
-use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use task_store::{MemoryTaskRepository, StoreError, StoreResult, StoredTask, TaskPhase, TaskRepository as _};
 use thiserror::Error;
+use uuid::Uuid;
 
 use crate::models::{Task, TaskStatus, TaskFilter, CreateTaskRequest, UpdateTaskRequest, TaskResult, TaskStatistics};
 
@@ -167,6 +166,19 @@ pub enum RepositoryError {
     #[error("Task locked by another worker")]
     #[allow(dead_code)]
     TaskLocked, // Reserved for future distributed locking
+    #[error(transparent)]
+    Storage(StoreError),
+}
+
+impl RepositoryError {
+    /// 转换共享存储的错误，修改回调以冲突拒绝的都是非法状态转换
+    fn from_store(task_id: Uuid, err: StoreError) -> Self {
+        match err {
+            StoreError::NotFound(_) => RepositoryError::TaskNotFound(task_id),
+            StoreError::Conflict(_) => RepositoryError::InvalidStateTransition,
+            StoreError::Backend(_) => RepositoryError::Storage(err),
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -186,17 +198,80 @@ pub trait TaskRepository: Send + Sync {
     async fn cleanup_old_tasks(&self, older_than: DateTime<Utc>) -> Result<u64, RepositoryError>;
 }
 
-#[derive(Debug)]
+impl StoredTask for Task {
+    type Id = Uuid;
+
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn work_directory(&self) -> &str {
+        &self.work_directory
+    }
+
+    fn phase(&self) -> TaskPhase {
+        match self.status {
+            TaskStatus::Pending => TaskPhase::Held,
+            TaskStatus::Waiting => TaskPhase::Queued,
+            TaskStatus::Running => TaskPhase::Running,
+            TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled => TaskPhase::Finished,
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        self.priority as i32
+    }
+
+    fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+}
+
+/// 内存任务仓库，存取和调度使用 `task-store` 的共享实现
+#[derive(Default)]
 pub struct InMemoryTaskRepository {
-    tasks: Arc<RwLock<HashMap<Uuid, Task>>>,
+    tasks: MemoryTaskRepository<Task>,
 }
 
 impl InMemoryTaskRepository {
     pub fn new() -> Self {
         Self {
-            tasks: Arc::new(RwLock::new(HashMap::new())),
+            tasks: MemoryTaskRepository::new(),
+        }
+    }
+}
+
+/// 按更新请求修改任务，状态转换非法时返回冲突
+fn apply_update(task: &mut Task, request: &UpdateTaskRequest) -> StoreResult<()> {
+    if let Some(status) = request.status {
+        if !is_valid_state_transition(task.status, status) {
+            return Err(StoreError::Conflict(format!("{:?} -> {:?}", task.status, status)));
         }
+        task.status = status;
+        
+        match status {
+            TaskStatus::Running => task.started_at = Some(Utc::now()),
+            TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled => {
+                task.completed_at = Some(Utc::now());
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(worker_id) = &request.worker_id {
+        task.worker_id = Some(worker_id.clone());
+    }
+
+    if let Some(result) = &request.result {
+        task.result = Some(result.clone());
     }
+
+    if let Some(error_message) = &request.error_message {
+        task.error_message = Some(error_message.clone());
+    }
+
+    task.updated_at = Utc::now();
+    Ok(())
 }
 
 #[async_trait::async_trait]
@@ -205,7 +280,7 @@ impl TaskRepository for InMemoryTaskRepository {
         let task_id = Uuid::new_v4();
         let now = Utc::now();
 
-        let task = Task {
+        let mut task = Task {
             id: task_id,
             work_directory: request.work_directory,
             prompt: request.prompt,
@@ -224,71 +299,37 @@ impl TaskRepository for InMemoryTaskRepository {
             error_message: None,
         };
 
-        let mut tasks = self.tasks.write().await;
-        tasks.insert(task_id, task.clone());
-
         // Move to waiting state
-        let mut updated_task = task;
-        updated_task.status = TaskStatus::Waiting;
-        updated_task.updated_at = Utc::now();
-        tasks.insert(task_id, updated_task.clone());
+        task.status = TaskStatus::Waiting;
+        task.updated_at = Utc::now();
+        self.tasks.insert(&task).await.map_err(|e| RepositoryError::from_store(task_id, e))?;
 
-        Ok(updated_task)
+        Ok(task)
     }
 
     async fn get_task(&self, task_id: Uuid) -> Result<Task, RepositoryError> {
-        let tasks = self.tasks.read().await;
-        tasks.get(&task_id).cloned().ok_or(RepositoryError::TaskNotFound(task_id))
+        self.tasks.get(&task_id).await
+            .map_err(|e| RepositoryError::from_store(task_id, e))?
+            .ok_or(RepositoryError::TaskNotFound(task_id))
     }
 
     async fn update_task(&self, task_id: Uuid, request: UpdateTaskRequest) -> Result<Task, RepositoryError> {
-        let mut tasks = self.tasks.write().await;
-        let mut task = tasks.get(&task_id).cloned().ok_or(RepositoryError::TaskNotFound(task_id))?;
-        
-        // Update fields
-        if let Some(status) = request.status {
-            if !is_valid_state_transition(task.status, status) {
-                return Err(RepositoryError::InvalidStateTransition);
-            }
-            task.status = status;
-            
-            match status {
-                TaskStatus::Running => task.started_at = Some(Utc::now()),
-                TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled => {
-                    task.completed_at = Some(Utc::now());
-                }
-                _ => {}
-            }
-        }
-
-        if let Some(worker_id) = request.worker_id {
-            task.worker_id = Some(worker_id);
-        }
-
-        if let Some(result) = request.result {
-            task.result = Some(result);
-        }
-
-        if let Some(error_message) = request.error_message {
-            task.error_message = Some(error_message);
-        }
-
-        task.updated_at = Utc::now();
-        tasks.insert(task_id, task.clone());
-        Ok(task)
+        self.tasks.modify(&task_id, &|task| apply_update(task, &request)).await
+            .map_err(|e| RepositoryError::from_store(task_id, e))
     }
 
     #[allow(dead_code)]
     async fn delete_task(&self, task_id: Uuid) -> Result<(), RepositoryError> {
         // Reserved for future use - task deletion functionality
-        let mut tasks = self.tasks.write().await;
-        tasks.remove(&task_id).ok_or(RepositoryError::TaskNotFound(task_id))?;
-        Ok(())
+        match self.tasks.remove(&task_id).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(RepositoryError::TaskNotFound(task_id)),
+            Err(e) => Err(RepositoryError::from_store(task_id, e)),
+        }
     }
 
     async fn list_tasks(&self, filter: TaskFilter) -> Result<Vec<Task>, RepositoryError> {
-        let tasks = self.tasks.read().await;
-        let mut filtered_tasks: Vec<Task> = tasks.values().cloned().collect();
+        let mut filtered_tasks = self.tasks.scan().await.map_err(RepositoryError::Storage)?;
 
         // Apply filters
         if let Some(status) = filter.status {
@@ -317,24 +358,15 @@ impl TaskRepository for InMemoryTaskRepository {
     }
 
     async fn acquire_task(&self, worker_id: String, work_directory: String) -> Result<Option<Task>, RepositoryError> {
-        let mut tasks = self.tasks.write().await;
-        
-        // Find the highest priority waiting task that matches the work directory
-        let task_to_acquire = tasks.values_mut()
-            .filter(|task| task.status == TaskStatus::Waiting && task.work_directory == work_directory)
-            .max_by(|a, b| a.priority.cmp(&b.priority));
-
-        if let Some(task) = task_to_acquire {
-            let _task_id = task.id;
+        // 取出该目录下优先级最高的等待任务，取出和标记为执行中是同一个原子操作
+        self.tasks.claim_next(&work_directory, &|task| {
+            let now = Utc::now();
             task.status = TaskStatus::Running;
             task.worker_id = Some(worker_id.clone());
-            task.started_at = Some(Utc::now());
-            task.updated_at = Utc::now();
-            
-            Ok(Some(task.clone()))
-        } else {
-            Ok(None)
-        }
+            task.started_at = Some(now);
+            task.updated_at = now;
+            Ok(())
+        }).await.map_err(RepositoryError::Storage)
     }
 
     async fn complete_task(&self, task_id: Uuid, result: TaskResult) -> Result<Task, RepositoryError> {
@@ -398,7 +430,7 @@ impl TaskRepository for InMemoryTaskRepository {
     }
 
     async fn get_statistics(&self) -> Result<TaskStatistics, RepositoryError> {
-        let tasks = self.tasks.read().await;
+        let tasks = self.tasks.scan().await.map_err(RepositoryError::Storage)?;
         let mut total_tasks = 0;
         let mut pending_tasks = 0;
         let mut waiting_tasks = 0;
@@ -409,7 +441,7 @@ impl TaskRepository for InMemoryTaskRepository {
         let mut sum_completion_time = 0;
         let mut completion_count = 0;
 
-        for task in tasks.values() {
+        for task in &tasks {
             total_tasks += 1;
             
             match task.status {
@@ -454,15 +486,10 @@ impl TaskRepository for InMemoryTaskRepository {
     #[allow(dead_code)]
     async fn cleanup_old_tasks(&self, older_than: DateTime<Utc>) -> Result<u64, RepositoryError> {
         // Reserved for future use - cleanup old completed/failed tasks
-        let mut tasks = self.tasks.write().await;
-        let initial_count = tasks.len();
-        
-        tasks.retain(|_, task| {
+        self.tasks.retain(&|task| {
             !(task.status == TaskStatus::Completed || task.status == TaskStatus::Failed || task.status == TaskStatus::Cancelled)
                 || task.completed_at.is_some_and(|completed| completed > older_than)
-        });
-        
-        Ok((initial_count - tasks.len()) as u64)
+        }).await.map_err(RepositoryError::Storage)
     }
 }
 
@@ -0,0 +1,241 @@
+//! 运行时工具开关与会话通知
+//!
+//! 工具可以在运行时停用和恢复（配置重载、依赖故障、运维手动切换）。每次可用工具集合变化后，
+//! [`ToolAvailability`] 广播一条 [`ServerEvent::ToolListChanged`] 和一条服务状态，
+//! 每个已初始化的MCP会话把它们转成 `notifications/tools/list_changed` 和
+//! `notifications/message`（logger为 `server-status`）推送给客户端，客户端据此刷新工具缓存。
+
+use std::collections::BTreeSet;
+use std::sync::RwLock;
+
+use rmcp::model::{LoggingLevel, LoggingMessageNotificationParam};
+use rmcp::service::{Peer, RoleServer};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// 服务状态通知使用的logger名
+pub const STATUS_LOGGER: &str = "server-status";
+
+/// 广播通道容量，会话落后超过该数量时直接要求客户端刷新工具列表
+const EVENT_CAPACITY: usize = 64;
+
+/// 服务状态
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ServerStatus {
+    /// `ok`、`degraded` 或 `shutting_down`
+    pub status: String,
+    pub message: String,
+    /// 当前停用的工具
+    pub disabled_tools: Vec<String>,
+}
+
+impl ServerStatus {
+    /// 通知使用的日志级别，非 `ok` 状态为警告
+    pub fn level(&self) -> LoggingLevel {
+        if self.status == "ok" {
+            LoggingLevel::Info
+        } else {
+            LoggingLevel::Warning
+        }
+    }
+}
+
+/// 推送给会话的服务端事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerEvent {
+    /// 可用工具集合变化
+    ToolListChanged,
+    /// 服务状态变化
+    Status(ServerStatus),
+}
+
+/// 未知工具名
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown tool: {0}")]
+pub struct UnknownTool(pub String);
+
+/// 工具的启用状态，所有会话共享
+pub struct ToolAvailability {
+    known: BTreeSet<String>,
+    disabled: RwLock<BTreeSet<String>>,
+    events: broadcast::Sender<ServerEvent>,
+}
+
+impl ToolAvailability {
+    /// 使用全部已注册工具创建，初始全部启用
+    pub fn new(known: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            known: known.into_iter().collect(),
+            disabled: RwLock::new(BTreeSet::new()),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+        }
+    }
+
+    /// 工具是否启用
+    pub fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.read().unwrap().contains(name)
+    }
+
+    /// 全部工具及其启用状态
+    pub fn tools(&self) -> Vec<(String, bool)> {
+        let disabled = self.disabled.read().unwrap();
+        self.known.iter().map(|name| (name.clone(), !disabled.contains(name))).collect()
+    }
+
+    /// 启用或停用单个工具，状态有变化时通知所有会话，返回是否有变化
+    pub fn set_enabled(&self, name: &str, enabled: bool) -> Result<bool, UnknownTool> {
+        if !self.known.contains(name) {
+            return Err(UnknownTool(name.to_string()));
+        }
+        let mut disabled = self.disabled.read().unwrap().clone();
+        if enabled {
+            disabled.remove(name);
+        } else {
+            disabled.insert(name.to_string());
+        }
+        Ok(self.replace(disabled))
+    }
+
+    /// 用新的停用列表整体替换（配置重载），忽略未知工具名，返回是否有变化
+    pub fn set_disabled(&self, names: &[String]) -> bool {
+        let mut disabled = BTreeSet::new();
+        for name in names {
+            if self.known.contains(name) {
+                disabled.insert(name.clone());
+            } else {
+                tracing::warn!(tool = %name, "Ignoring unknown tool in disabled tool list");
+            }
+        }
+        self.replace(disabled)
+    }
+
+    /// 当前服务状态
+    pub fn status(&self) -> ServerStatus {
+        let disabled_tools: Vec<String> = self.disabled.read().unwrap().iter().cloned().collect();
+        if disabled_tools.is_empty() {
+            ServerStatus {
+                status: "ok".to_string(),
+                message: "All tools are available".to_string(),
+                disabled_tools,
+            }
+        } else {
+            ServerStatus {
+                status: "degraded".to_string(),
+                message: format!("Tools temporarily unavailable: {}", disabled_tools.join(", ")),
+                disabled_tools,
+            }
+        }
+    }
+
+    /// 通知所有会话服务即将关闭
+    pub fn announce_shutdown(&self) {
+        let status = ServerStatus {
+            status: "shutting_down".to_string(),
+            message: "Server is shutting down".to_string(),
+            disabled_tools: self.disabled.read().unwrap().iter().cloned().collect(),
+        };
+        let _ = self.events.send(ServerEvent::Status(status));
+    }
+
+    /// 订阅服务端事件
+    pub fn subscribe(&self) -> broadcast::Receiver<ServerEvent> {
+        self.events.subscribe()
+    }
+
+    fn replace(&self, disabled: BTreeSet<String>) -> bool {
+        {
+            let mut current = self.disabled.write().unwrap();
+            if *current == disabled {
+                return false;
+            }
+            *current = disabled;
+        }
+        let status = self.status();
+        tracing::info!(status = %status.status, disabled_tools = ?status.disabled_tools, "Tool availability changed");
+        // 没有会话订阅时发送失败，忽略即可
+        let _ = self.events.send(ServerEvent::ToolListChanged);
+        let _ = self.events.send(ServerEvent::Status(status));
+        true
+    }
+}
+
+/// 把服务端事件转发给一个会话，直到会话断开或事件源关闭
+pub async fn forward_events(
+    peer: Peer<RoleServer>,
+    mut events: broadcast::Receiver<ServerEvent>,
+    min_level: impl Fn() -> LoggingLevel,
+) {
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            // 错过了部分事件，只需让客户端重新拉取工具列表
+            Err(broadcast::error::RecvError::Lagged(_)) => ServerEvent::ToolListChanged,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let result = match event {
+            ServerEvent::ToolListChanged => peer.notify_tool_list_changed().await,
+            ServerEvent::Status(status) => {
+                if severity(status.level()) < severity(min_level()) {
+                    continue;
+                }
+                peer.notify_logging_message(LoggingMessageNotificationParam {
+                    level: status.level(),
+                    logger: Some(STATUS_LOGGER.to_string()),
+                    data: serde_json::to_value(&status).unwrap_or_default(),
+                })
+                .await
+            }
+        };
+        if let Err(e) = result {
+            tracing::debug!(error = %e, "Stopped forwarding server events to closed session");
+            break;
+        }
+    }
+}
+
+/// 日志级别的严重程度，用于按 `logging/setLevel` 过滤
+pub fn severity(level: LoggingLevel) -> u8 {
+    match level {
+        LoggingLevel::Debug => 0,
+        LoggingLevel::Info => 1,
+        LoggingLevel::Notice => 2,
+        LoggingLevel::Warning => 3,
+        LoggingLevel::Error => 4,
+        LoggingLevel::Critical => 5,
+        LoggingLevel::Alert => 6,
+        LoggingLevel::Emergency => 7,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn availability() -> ToolAvailability {
+        ToolAvailability::new(["create_task", "get_task"].map(String::from))
+    }
+
+    #[test]
+    fn test_toggle_broadcasts_changes() {
+        let tools = availability();
+        let mut events = tools.subscribe();
+
+        assert!(tools.set_enabled("create_task", false).unwrap());
+        assert!(!tools.is_enabled("create_task"));
+        assert_eq!(events.try_recv().unwrap(), ServerEvent::ToolListChanged);
+        let ServerEvent::Status(status) = events.try_recv().unwrap() else { panic!("expected status") };
+        assert_eq!(status.status, "degraded");
+        assert_eq!(status.disabled_tools, vec!["create_task"]);
+
+        // 状态没有变化时不通知
+        assert!(!tools.set_enabled("create_task", false).unwrap());
+        assert!(events.try_recv().is_err());
+        assert!(tools.set_enabled("missing", false).is_err());
+
+        // 配置重载整体替换停用列表
+        assert!(tools.set_disabled(&["get_task".to_string(), "missing".to_string()]));
+        assert_eq!(tools.tools(), vec![("create_task".to_string(), true), ("get_task".to_string(), false)]);
+        assert!(tools.set_disabled(&[]));
+        assert_eq!(tools.status().status, "ok");
+    }
+}
@@ -0,0 +1,14 @@
+-- 任务事件表（事件溯源模式下的事实来源）
+CREATE TABLE IF NOT EXISTS task_events (
+    sequence INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    snapshot TEXT,
+    occurred_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    
+    -- 约束
+    CHECK (event_type IN ('created', 'started', 'completed', 'failed', 'retried', 'cancelled', 'updated', 'deleted'))
+);
+
+-- 创建索引
+CREATE INDEX IF NOT EXISTS idx_task_events_task_id ON task_events(task_id, sequence);
@@ -0,0 +1,87 @@
+-- 任务统计计数器表（由触发器随任务写入在同一事务内维护）
+-- dimension: total / status / priority / processing
+-- processing 行记录同时具有开始和完成时间的任务数及处理时长总和（秒）
+CREATE TABLE IF NOT EXISTS task_counters (
+    dimension TEXT NOT NULL,
+    value TEXT NOT NULL DEFAULT '',
+    count INTEGER NOT NULL DEFAULT 0,
+    total_seconds REAL NOT NULL DEFAULT 0,
+
+    PRIMARY KEY (dimension, value)
+);
+
+INSERT OR IGNORE INTO task_counters (dimension, value) VALUES
+    ('total', ''),
+    ('status', 'waiting'),
+    ('status', 'working'),
+    ('status', 'completed'),
+    ('status', 'failed'),
+    ('status', 'cancelled'),
+    ('priority', 'low'),
+    ('priority', 'medium'),
+    ('priority', 'high'),
+    ('processing', '');
+
+-- 根据现有任务回填计数器
+UPDATE task_counters SET count = (SELECT COUNT(*) FROM tasks) WHERE dimension = 'total';
+UPDATE task_counters SET count = (SELECT COUNT(*) FROM tasks WHERE tasks.status = task_counters.value) WHERE dimension = 'status';
+UPDATE task_counters SET count = (SELECT COUNT(*) FROM tasks WHERE tasks.priority = task_counters.value) WHERE dimension = 'priority';
+UPDATE task_counters SET
+    count = (SELECT COUNT(*) FROM tasks
+        WHERE julianday(completed_at) IS NOT NULL AND julianday(started_at) IS NOT NULL),
+    total_seconds = (SELECT COALESCE(SUM((julianday(completed_at) - julianday(started_at)) * 86400), 0.0) FROM tasks
+        WHERE julianday(completed_at) IS NOT NULL AND julianday(started_at) IS NOT NULL)
+WHERE dimension = 'processing';
+
+-- 创建触发器：维护统计计数器
+CREATE TRIGGER IF NOT EXISTS task_counters_insert
+    AFTER INSERT ON tasks
+    FOR EACH ROW
+BEGIN
+    UPDATE task_counters SET count = count + 1
+    WHERE dimension = 'total'
+        OR (dimension = 'status' AND value = NEW.status)
+        OR (dimension = 'priority' AND value = NEW.priority);
+    UPDATE task_counters SET
+        count = count + 1,
+        total_seconds = total_seconds + (julianday(NEW.completed_at) - julianday(NEW.started_at)) * 86400
+    WHERE dimension = 'processing'
+        AND julianday(NEW.completed_at) IS NOT NULL AND julianday(NEW.started_at) IS NOT NULL;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_update
+    AFTER UPDATE OF status, priority, started_at, completed_at ON tasks
+    FOR EACH ROW
+BEGIN
+    UPDATE task_counters SET count = count - 1
+    WHERE (dimension = 'status' AND value = OLD.status)
+        OR (dimension = 'priority' AND value = OLD.priority);
+    UPDATE task_counters SET count = count + 1
+    WHERE (dimension = 'status' AND value = NEW.status)
+        OR (dimension = 'priority' AND value = NEW.priority);
+    UPDATE task_counters SET
+        count = count - 1,
+        total_seconds = total_seconds - (julianday(OLD.completed_at) - julianday(OLD.started_at)) * 86400
+    WHERE dimension = 'processing'
+        AND julianday(OLD.completed_at) IS NOT NULL AND julianday(OLD.started_at) IS NOT NULL;
+    UPDATE task_counters SET
+        count = count + 1,
+        total_seconds = total_seconds + (julianday(NEW.completed_at) - julianday(NEW.started_at)) * 86400
+    WHERE dimension = 'processing'
+        AND julianday(NEW.completed_at) IS NOT NULL AND julianday(NEW.started_at) IS NOT NULL;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_delete
+    AFTER DELETE ON tasks
+    FOR EACH ROW
+BEGIN
+    UPDATE task_counters SET count = count - 1
+    WHERE dimension = 'total'
+        OR (dimension = 'status' AND value = OLD.status)
+        OR (dimension = 'priority' AND value = OLD.priority);
+    UPDATE task_counters SET
+        count = count - 1,
+        total_seconds = total_seconds - (julianday(OLD.completed_at) - julianday(OLD.started_at)) * 86400
+    WHERE dimension = 'processing'
+        AND julianday(OLD.completed_at) IS NOT NULL AND julianday(OLD.started_at) IS NOT NULL;
+END;
@@ -0,0 +1,43 @@
+-- 任务有效期：超过 expires_at 仍未开始的等待任务由调度器取消
+ALTER TABLE tasks ADD COLUMN expires_at DATETIME;
+-- 取消原因：requested（手动取消）/ expired（超过有效期）
+ALTER TABLE tasks ADD COLUMN cancel_reason TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_status_expires_at ON tasks(status, expires_at);
+
+-- 按取消原因计数
+INSERT OR IGNORE INTO task_counters (dimension, value) VALUES
+    ('cancel_reason', 'requested'),
+    ('cancel_reason', 'expired');
+
+UPDATE task_counters SET count = (SELECT COUNT(*) FROM tasks WHERE tasks.cancel_reason = task_counters.value)
+WHERE dimension = 'cancel_reason';
+
+CREATE TRIGGER IF NOT EXISTS task_counters_cancel_reason_insert
+    AFTER INSERT ON tasks
+    FOR EACH ROW
+    WHEN NEW.cancel_reason IS NOT NULL
+BEGIN
+    UPDATE task_counters SET count = count + 1
+    WHERE dimension = 'cancel_reason' AND value = NEW.cancel_reason;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_cancel_reason_update
+    AFTER UPDATE OF cancel_reason ON tasks
+    FOR EACH ROW
+    WHEN OLD.cancel_reason IS NOT NEW.cancel_reason
+BEGIN
+    UPDATE task_counters SET count = count - 1
+    WHERE dimension = 'cancel_reason' AND value = OLD.cancel_reason;
+    UPDATE task_counters SET count = count + 1
+    WHERE dimension = 'cancel_reason' AND value = NEW.cancel_reason;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_cancel_reason_delete
+    AFTER DELETE ON tasks
+    FOR EACH ROW
+    WHEN OLD.cancel_reason IS NOT NULL
+BEGIN
+    UPDATE task_counters SET count = count - 1
+    WHERE dimension = 'cancel_reason' AND value = OLD.cancel_reason;
+END;
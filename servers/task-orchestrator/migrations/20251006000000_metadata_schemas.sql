@@ -0,0 +1,7 @@
+-- 元数据模式表（按命名空间校验任务元数据的JSON Schema）
+CREATE TABLE IF NOT EXISTS metadata_schemas (
+    namespace TEXT PRIMARY KEY,
+    schema TEXT NOT NULL,
+    strict BOOLEAN NOT NULL DEFAULT 0,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
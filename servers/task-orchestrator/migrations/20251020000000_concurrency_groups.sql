@@ -0,0 +1,4 @@
+-- 并发组：同一组内同时只有一个任务在执行，不论工作目录
+ALTER TABLE tasks ADD COLUMN concurrency_group TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_concurrency_group_status ON tasks(concurrency_group, status);
@@ -0,0 +1,43 @@
+-- 失败分类：失败任务记录结构化的失败类别（timeout / oom / tool_error / validation / upstream / unknown），用于统计与过滤
+
+ALTER TABLE tasks ADD COLUMN failure_category TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_failure_category ON tasks(failure_category);
+
+-- 按失败类别计数
+INSERT OR IGNORE INTO task_counters (dimension, value) VALUES
+    ('failure_category', 'timeout'),
+    ('failure_category', 'oom'),
+    ('failure_category', 'tool_error'),
+    ('failure_category', 'validation'),
+    ('failure_category', 'upstream'),
+    ('failure_category', 'unknown');
+
+CREATE TRIGGER IF NOT EXISTS task_counters_failure_category_insert
+    AFTER INSERT ON tasks
+    FOR EACH ROW
+    WHEN NEW.failure_category IS NOT NULL
+BEGIN
+    UPDATE task_counters SET count = count + 1
+    WHERE dimension = 'failure_category' AND value = NEW.failure_category;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_failure_category_update
+    AFTER UPDATE OF failure_category ON tasks
+    FOR EACH ROW
+    WHEN OLD.failure_category IS NOT NEW.failure_category
+BEGIN
+    UPDATE task_counters SET count = count - 1
+    WHERE dimension = 'failure_category' AND value = OLD.failure_category;
+    UPDATE task_counters SET count = count + 1
+    WHERE dimension = 'failure_category' AND value = NEW.failure_category;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_failure_category_delete
+    AFTER DELETE ON tasks
+    FOR EACH ROW
+    WHEN OLD.failure_category IS NOT NULL
+BEGIN
+    UPDATE task_counters SET count = count - 1
+    WHERE dimension = 'failure_category' AND value = OLD.failure_category;
+END;
@@ -0,0 +1,2 @@
+-- 按失败类别重试：失败后重新排队的任务在 retry_after 之前不会被获取
+ALTER TABLE tasks ADD COLUMN retry_after DATETIME;
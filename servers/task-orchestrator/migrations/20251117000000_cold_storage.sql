@@ -0,0 +1,10 @@
+-- 冷存储索引：已移入冷存储段文件的任务，按任务ID查询时从段文件读取
+CREATE TABLE IF NOT EXISTS cold_tasks (
+    task_id TEXT PRIMARY KEY,
+    segment TEXT NOT NULL,
+    status TEXT NOT NULL,
+    completed_at DATETIME,
+    archived_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_cold_tasks_segment ON cold_tasks(segment);
@@ -0,0 +1,212 @@
+-- 字段加密：密文（`enc:v1:<密钥ID>:` + base64）约为明文长度的4/3，提示词的长度约束只作用于明文行。
+-- 明文长度在加密前由领域模型校验（不超过10000个字符）。
+--
+-- SQLite 无法修改 CHECK 约束，重建 tasks 和 task_history 表。迁移在事务内执行，无法关闭外键，
+-- 删除 tasks 会级联删除评论，因此先备份历史和评论，重建后写回。
+
+CREATE TABLE tasks_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id TEXT UNIQUE NOT NULL,
+    work_directory TEXT NOT NULL,
+    prompt TEXT NOT NULL,
+    priority TEXT DEFAULT 'medium' CHECK (priority IN ('low', 'medium', 'high')),
+    tags TEXT DEFAULT '[]',
+    status TEXT DEFAULT 'waiting' CHECK (status IN ('pending_approval', 'waiting', 'working', 'completed', 'failed', 'cancelled')),
+    worker_id TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    started_at DATETIME,
+    completed_at DATETIME,
+    result TEXT,
+    error_message TEXT,
+    retry_count INTEGER DEFAULT 0,
+    max_retries INTEGER DEFAULT 3,
+    metadata TEXT DEFAULT '{}',
+    version INTEGER DEFAULT 1,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    expires_at DATETIME,
+    cancel_reason TEXT,
+    concurrency_group TEXT,
+    failure_category TEXT,
+    retry_after DATETIME,
+    
+    -- 约束
+    CHECK (work_directory != ''),
+    CHECK (prompt != ''),
+    CHECK (length(work_directory) <= 512),
+    CHECK (length(prompt) <= 10000 OR prompt LIKE 'enc:v1:%'),
+    CHECK (retry_count >= 0),
+    CHECK (max_retries >= 0),
+    CHECK (version >= 1)
+);
+
+INSERT INTO tasks_new (
+    id, task_id, work_directory, prompt, priority, tags, status, worker_id, created_at, started_at, completed_at,
+    result, error_message, retry_count, max_retries, metadata, version, updated_at, expires_at, cancel_reason, concurrency_group,
+    failure_category, retry_after
+)
+SELECT
+    id, task_id, work_directory, prompt, priority, tags, status, worker_id, created_at, started_at, completed_at,
+    result, error_message, retry_count, max_retries, metadata, version, updated_at, expires_at, cancel_reason, concurrency_group,
+    failure_category, retry_after
+FROM tasks;
+
+CREATE TABLE task_history_backup AS SELECT * FROM task_history;
+CREATE TABLE task_comments_backup AS SELECT * FROM task_comments;
+
+DROP TABLE task_history;
+DROP TABLE tasks;
+ALTER TABLE tasks_new RENAME TO tasks;
+
+CREATE TABLE task_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    worker_id TEXT,
+    changed_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    details TEXT DEFAULT '{}',
+    
+    -- 外键约束
+    FOREIGN KEY (task_id) REFERENCES tasks(task_id) ON DELETE CASCADE,
+    
+    -- 约束
+    CHECK (status IN ('pending_approval', 'waiting', 'working', 'completed', 'failed', 'cancelled'))
+);
+
+INSERT INTO task_history (id, task_id, status, worker_id, changed_at, details)
+SELECT id, task_id, status, worker_id, changed_at, details FROM task_history_backup;
+
+DELETE FROM task_comments;
+INSERT INTO task_comments (id, task_id, author, text, data, created_at)
+SELECT id, task_id, author, text, data, created_at FROM task_comments_backup;
+
+DROP TABLE task_history_backup;
+DROP TABLE task_comments_backup;
+
+-- 重建索引
+CREATE INDEX IF NOT EXISTS idx_tasks_status_priority ON tasks(status, priority, created_at);
+CREATE INDEX IF NOT EXISTS idx_tasks_work_directory ON tasks(work_directory, status);
+CREATE INDEX IF NOT EXISTS idx_tasks_created_at ON tasks(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_tasks_worker_id ON tasks(worker_id, status);
+CREATE INDEX IF NOT EXISTS idx_tasks_task_id_version ON tasks(task_id, version);
+CREATE INDEX IF NOT EXISTS idx_tasks_status_expires_at ON tasks(status, expires_at);
+CREATE INDEX IF NOT EXISTS idx_tasks_concurrency_group_status ON tasks(concurrency_group, status);
+CREATE INDEX IF NOT EXISTS idx_tasks_failure_category ON tasks(failure_category);
+CREATE INDEX IF NOT EXISTS idx_task_history_task_id ON task_history(task_id, changed_at DESC);
+
+-- 重建触发器
+CREATE TRIGGER IF NOT EXISTS update_tasks_updated_at 
+    AFTER UPDATE ON tasks
+    FOR EACH ROW
+BEGIN
+    UPDATE tasks SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_insert
+    AFTER INSERT ON tasks
+    FOR EACH ROW
+BEGIN
+    UPDATE task_counters SET count = count + 1
+    WHERE dimension = 'total'
+        OR (dimension = 'status' AND value = NEW.status)
+        OR (dimension = 'priority' AND value = NEW.priority);
+    UPDATE task_counters SET
+        count = count + 1,
+        total_seconds = total_seconds + (julianday(NEW.completed_at) - julianday(NEW.started_at)) * 86400
+    WHERE dimension = 'processing'
+        AND julianday(NEW.completed_at) IS NOT NULL AND julianday(NEW.started_at) IS NOT NULL;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_update
+    AFTER UPDATE OF status, priority, started_at, completed_at ON tasks
+    FOR EACH ROW
+BEGIN
+    UPDATE task_counters SET count = count - 1
+    WHERE (dimension = 'status' AND value = OLD.status)
+        OR (dimension = 'priority' AND value = OLD.priority);
+    UPDATE task_counters SET count = count + 1
+    WHERE (dimension = 'status' AND value = NEW.status)
+        OR (dimension = 'priority' AND value = NEW.priority);
+    UPDATE task_counters SET
+        count = count - 1,
+        total_seconds = total_seconds - (julianday(OLD.completed_at) - julianday(OLD.started_at)) * 86400
+    WHERE dimension = 'processing'
+        AND julianday(OLD.completed_at) IS NOT NULL AND julianday(OLD.started_at) IS NOT NULL;
+    UPDATE task_counters SET
+        count = count + 1,
+        total_seconds = total_seconds + (julianday(NEW.completed_at) - julianday(NEW.started_at)) * 86400
+    WHERE dimension = 'processing'
+        AND julianday(NEW.completed_at) IS NOT NULL AND julianday(NEW.started_at) IS NOT NULL;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_delete
+    AFTER DELETE ON tasks
+    FOR EACH ROW
+BEGIN
+    UPDATE task_counters SET count = count - 1
+    WHERE dimension = 'total'
+        OR (dimension = 'status' AND value = OLD.status)
+        OR (dimension = 'priority' AND value = OLD.priority);
+    UPDATE task_counters SET
+        count = count - 1,
+        total_seconds = total_seconds - (julianday(OLD.completed_at) - julianday(OLD.started_at)) * 86400
+    WHERE dimension = 'processing'
+        AND julianday(OLD.completed_at) IS NOT NULL AND julianday(OLD.started_at) IS NOT NULL;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_cancel_reason_insert
+    AFTER INSERT ON tasks
+    FOR EACH ROW
+    WHEN NEW.cancel_reason IS NOT NULL
+BEGIN
+    UPDATE task_counters SET count = count + 1
+    WHERE dimension = 'cancel_reason' AND value = NEW.cancel_reason;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_cancel_reason_update
+    AFTER UPDATE OF cancel_reason ON tasks
+    FOR EACH ROW
+    WHEN OLD.cancel_reason IS NOT NEW.cancel_reason
+BEGIN
+    UPDATE task_counters SET count = count - 1
+    WHERE dimension = 'cancel_reason' AND value = OLD.cancel_reason;
+    UPDATE task_counters SET count = count + 1
+    WHERE dimension = 'cancel_reason' AND value = NEW.cancel_reason;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_cancel_reason_delete
+    AFTER DELETE ON tasks
+    FOR EACH ROW
+    WHEN OLD.cancel_reason IS NOT NULL
+BEGIN
+    UPDATE task_counters SET count = count - 1
+    WHERE dimension = 'cancel_reason' AND value = OLD.cancel_reason;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_failure_category_insert
+    AFTER INSERT ON tasks
+    FOR EACH ROW
+    WHEN NEW.failure_category IS NOT NULL
+BEGIN
+    UPDATE task_counters SET count = count + 1
+    WHERE dimension = 'failure_category' AND value = NEW.failure_category;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_failure_category_update
+    AFTER UPDATE OF failure_category ON tasks
+    FOR EACH ROW
+    WHEN OLD.failure_category IS NOT NEW.failure_category
+BEGIN
+    UPDATE task_counters SET count = count - 1
+    WHERE dimension = 'failure_category' AND value = OLD.failure_category;
+    UPDATE task_counters SET count = count + 1
+    WHERE dimension = 'failure_category' AND value = NEW.failure_category;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_counters_failure_category_delete
+    AFTER DELETE ON tasks
+    FOR EACH ROW
+    WHEN OLD.failure_category IS NOT NULL
+BEGIN
+    UPDATE task_counters SET count = count - 1
+    WHERE dimension = 'failure_category' AND value = OLD.failure_category;
+END;
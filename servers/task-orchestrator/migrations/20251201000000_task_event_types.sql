@@ -0,0 +1,22 @@
+-- 事件类型约束补上审批通过（approved）和移入冷存储（archived）。
+--
+-- SQLite 无法修改 CHECK 约束，按原序列号重建 task_events 表。
+
+CREATE TABLE task_events_new (
+    sequence INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    snapshot TEXT,
+    occurred_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+
+    -- 约束
+    CHECK (event_type IN ('created', 'started', 'completed', 'failed', 'retried', 'cancelled', 'updated', 'deleted', 'approved', 'archived'))
+);
+
+INSERT INTO task_events_new (sequence, task_id, event_type, snapshot, occurred_at)
+SELECT sequence, task_id, event_type, snapshot, occurred_at FROM task_events;
+
+DROP TABLE task_events;
+ALTER TABLE task_events_new RENAME TO task_events;
+
+CREATE INDEX IF NOT EXISTS idx_task_events_task_id ON task_events(task_id, sequence);
@@ -0,0 +1,24 @@
+-- 评论（commented）和与任务状态无关的记录（recorded）也写入事件流，
+-- 新增 payload 列保存评论或记录内容，热备据此复制评论、流水线运行和元数据模式。
+--
+-- SQLite 无法修改 CHECK 约束，按原序列号重建 task_events 表。
+
+CREATE TABLE task_events_new (
+    sequence INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    snapshot TEXT,
+    payload TEXT,
+    occurred_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+
+    -- 约束
+    CHECK (event_type IN ('created', 'started', 'completed', 'failed', 'retried', 'cancelled', 'updated', 'deleted', 'approved', 'archived', 'commented', 'recorded'))
+);
+
+INSERT INTO task_events_new (sequence, task_id, event_type, snapshot, occurred_at)
+SELECT sequence, task_id, event_type, snapshot, occurred_at FROM task_events;
+
+DROP TABLE task_events;
+ALTER TABLE task_events_new RENAME TO task_events;
+
+CREATE INDEX IF NOT EXISTS idx_task_events_task_id ON task_events(task_id, sequence);
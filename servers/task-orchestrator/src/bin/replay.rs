@@ -0,0 +1,149 @@
+use std::env;
+
+use serde_json::Value;
+
+const USAGE: &str = "Usage: replay --id <ID> (--file <requests.jsonl> | --source <http://host:port>) --target <http://host:port> [--header 'Name: value']...
+
+Re-issues a request recorded by the orchestrator's request recording mode against a test instance.
+Masked credential headers are dropped; supply working ones with --header.";
+
+/// 重放参数
+struct Args {
+    id: u64,
+    file: Option<String>,
+    source: Option<String>,
+    target: String,
+    headers: Vec<(String, String)>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut id = None;
+    let mut file = None;
+    let mut source = None;
+    let mut target = None;
+    let mut headers = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} requires a value", arg));
+        match arg.as_str() {
+            "--id" => id = Some(value()?.parse::<u64>().map_err(|e| format!("invalid --id: {}", e))?),
+            "--file" => file = Some(value()?),
+            "--source" => source = Some(value()?),
+            "--target" => target = Some(value()?),
+            "--header" => {
+                let header = value()?;
+                let (name, value) = header
+                    .split_once(':')
+                    .ok_or_else(|| format!("invalid --header '{}', expected 'Name: value'", header))?;
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+            "-h" | "--help" => return Err(String::new()),
+            other => return Err(format!("unknown argument: {}", other)),
+        }
+    }
+
+    if file.is_some() == source.is_some() {
+        return Err("exactly one of --file or --source is required".to_string());
+    }
+    Ok(Args {
+        id: id.ok_or("--id is required")?,
+        file,
+        source,
+        target: target.ok_or("--target is required")?,
+        headers,
+    })
+}
+
+/// 从JSON Lines文件或运行中实例的录制端点查找请求
+async fn load_exchange(args: &Args, client: &reqwest::Client) -> Result<Value, Box<dyn std::error::Error>> {
+    let matches_id = |exchange: &Value| exchange["id"].as_u64() == Some(args.id);
+
+    if let Some(path) = &args.file {
+        for line in std::fs::read_to_string(path)?.lines().filter(|line| !line.trim().is_empty()) {
+            let exchange: Value = serde_json::from_str(line)?;
+            if matches_id(&exchange) {
+                return Ok(exchange);
+            }
+        }
+        return Err(format!("request {} not found in {}", args.id, path).into());
+    }
+
+    let source = args.source.as_deref().unwrap_or_default().trim_end_matches('/');
+    let response: Value = client
+        .get(format!("{}/api/v1/admin/recent-requests?limit=10000", source))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    response["data"]
+        .as_array()
+        .and_then(|exchanges| exchanges.iter().find(|exchange| matches_id(exchange)))
+        .cloned()
+        .ok_or_else(|| format!("request {} not found at {}", args.id, source).into())
+}
+
+/// 录制时被掩码或省略、重放时不能原样发送的值
+fn is_placeholder(value: &str) -> bool {
+    value.contains("[REDACTED]") || (value.starts_with('<') && value.ends_with("omitted>"))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            if !message.is_empty() {
+                eprintln!("{}\n", message);
+            }
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let client = reqwest::Client::new();
+    let exchange = load_exchange(&args, &client).await?;
+
+    let method = reqwest::Method::from_bytes(exchange["method"].as_str().unwrap_or("GET").as_bytes())?;
+    let uri = exchange["uri"].as_str().unwrap_or("/");
+    let url = format!("{}{}", args.target.trim_end_matches('/'), uri);
+    println!("Replaying request {} ({} {}) against {}", args.id, method, uri, args.target);
+
+    let mut request = client.request(method, &url);
+    if let Some(headers) = exchange["request_headers"].as_object() {
+        for (name, value) in headers {
+            let value = value.as_str().unwrap_or_default();
+            let skipped = matches!(name.as_str(), "host" | "content-length" | "connection" | "transfer-encoding");
+            if skipped || is_placeholder(value) {
+                continue;
+            }
+            if !args.headers.iter().any(|(override_name, _)| override_name.eq_ignore_ascii_case(name)) {
+                request = request.header(name.as_str(), value);
+            }
+        }
+    }
+    for (name, value) in &args.headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    if let Some(body) = exchange["request_body"].as_str() {
+        if is_placeholder(body) {
+            eprintln!("Warning: recorded request body was omitted or contains redacted values");
+        }
+        request = request.body(body.to_string());
+    }
+
+    let response = request.send().await?;
+    let status = response.status().as_u16();
+    let body = response.text().await?;
+
+    let recorded_status = exchange["status"].as_u64().unwrap_or_default();
+    println!("Recorded status: {}", recorded_status);
+    println!("Replayed status: {}", status);
+    println!("{}", body);
+    if u64::from(status) != recorded_status {
+        eprintln!("Status differs from the recorded response");
+        std::process::exit(1);
+    }
+    Ok(())
+}
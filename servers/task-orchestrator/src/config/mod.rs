@@ -1,3 +1,4 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use config::{Config, ConfigError, File, Environment as ConfigEnv};
 use std::path::PathBuf;
@@ -5,7 +6,8 @@ use crate::errors::AppError;
 use std::env;
 
 /// 数据库配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct DatabaseConfig {
     pub url: String,
     pub max_connections: u32,
@@ -19,6 +21,16 @@ pub struct DatabaseConfig {
     pub cache_size: i64,
     pub mmap_size: i64,
     pub page_size: u32,
+    #[serde(default)]
+    pub enable_event_sourcing: bool,
+    #[serde(default)]
+    pub rebuild_projection_on_startup: bool,
+    /// 获取连接超过该时长（毫秒）时记录警告
+    pub slow_acquire_threshold_ms: u64,
+    /// 连接池存活检查间隔（秒），0表示不检查
+    pub health_check_interval: u64,
+    /// 连续失败多少次后重建连接池，0表示不自动重建
+    pub max_consecutive_failures: u32,
 }
 
 impl Default for DatabaseConfig {
@@ -36,12 +48,17 @@ impl Default for DatabaseConfig {
             cache_size: -64000, // 64MB
             mmap_size: 268435456, // 256MB
             page_size: 4096,
+            enable_event_sourcing: false,
+            rebuild_projection_on_startup: false,
+            slow_acquire_threshold_ms: 100,
+            health_check_interval: 30,
+            max_consecutive_failures: 3,
         }
     }
 }
 
 /// 服务器配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
@@ -52,6 +69,18 @@ pub struct ServerConfig {
     pub enable_compression: bool,
     pub enable_request_id: bool,
     pub enable_tracing: bool,
+    /// Unix域套接字路径，设置后在TCP之外同时监听；以 `@` 开头表示Linux抽象命名空间
+    #[serde(default)]
+    pub unix_socket: Option<String>,
+    /// 套接字文件的八进制权限，如 `660`；未设置时由umask决定
+    #[serde(default)]
+    pub unix_socket_mode: Option<String>,
+    /// 开启SO_REUSEPORT，允许新进程在旧进程退出前绑定同一端口
+    #[serde(default)]
+    pub reuse_port: bool,
+    /// 关闭时等待进行中请求完成的最长秒数，0表示一直等待
+    #[serde(default)]
+    pub shutdown_drain_timeout: u64,
 }
 
 impl Default for ServerConfig {
@@ -66,12 +95,30 @@ impl Default for ServerConfig {
             enable_compression: true,
             enable_request_id: true,
             enable_tracing: true,
+            unix_socket: None,
+            unix_socket_mode: None,
+            reuse_port: false,
+            shutdown_drain_timeout: 30,
         }
     }
 }
 
+impl ServerConfig {
+    /// 解析套接字文件权限，接受 `660`、`0o660` 形式的八进制字符串
+    pub fn socket_mode(&self) -> Result<Option<u32>, AppError> {
+        let Some(mode) = &self.unix_socket_mode else {
+            return Ok(None);
+        };
+        unix_socket::parse_socket_mode(mode)
+            .map(Some)
+            .ok_or_else(|| AppError::Configuration(ConfigError::Message(format!(
+                "Invalid unix socket mode '{}': expected octal permissions such as 660", mode
+            ))))
+    }
+}
+
 /// 日志配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LoggingConfig {
     pub level: String,
     pub format: LogFormat,
@@ -81,6 +128,67 @@ pub struct LoggingConfig {
     pub enable_json: bool,
     pub enable_pretty: bool,
     pub targets: Vec<LogTarget>,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    /// 崩溃报告配置
+    #[serde(default)]
+    pub crash_report: CrashReportConfig,
+}
+
+/// 崩溃报告配置
+///
+/// 发生panic时写入包含panic信息、回溯和最近日志的报告文件。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct CrashReportConfig {
+    pub enabled: bool,
+    /// 报告文件目录
+    pub directory: PathBuf,
+    /// 报告中附带的最近日志条数
+    pub recent_log_lines: usize,
+}
+
+impl Default for CrashReportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: PathBuf::from("crash-reports"),
+            recent_log_lines: 200,
+        }
+    }
+}
+
+/// 敏感数据脱敏配置
+///
+/// 作用于结构化日志、API响应中的任务元数据和导出的生命周期事件。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    /// 需要掩码的元数据键（不区分大小写，按键名片段匹配，如 `token` 命中 `github_token`，不命中 `max_tokens`）
+    pub sensitive_keys: Vec<String>,
+    /// 长度达到该值且形似密钥的字符串会被掩码
+    pub min_secret_length: usize,
+    /// 掩码文本
+    pub mask: String,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sensitive_keys: vec![
+                "api_key".to_string(),
+                "apikey".to_string(),
+                "token".to_string(),
+                "secret".to_string(),
+                "password".to_string(),
+                "authorization".to_string(),
+            ],
+            min_secret_length: 32,
+            mask: "[REDACTED]".to_string(),
+        }
+    }
 }
 
 impl Default for LoggingConfig {
@@ -94,12 +202,14 @@ impl Default for LoggingConfig {
             enable_json: true,
             enable_pretty: false,
             targets: vec![LogTarget::Stdout],
+            redaction: RedactionConfig::default(),
+            crash_report: CrashReportConfig::default(),
         }
     }
 }
 
 /// 日志格式
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum LogFormat {
     Json,
@@ -108,7 +218,7 @@ pub enum LogFormat {
 }
 
 /// 日志目标
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum LogTarget {
     Stdout,
@@ -117,7 +227,7 @@ pub enum LogTarget {
 }
 
 /// 安全配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SecurityConfig {
     pub enable_auth: bool,
     pub api_key_required: bool,
@@ -132,6 +242,115 @@ pub struct SecurityConfig {
     pub enable_https: bool,
     pub tls_cert_path: Option<PathBuf>,
     pub tls_key_path: Option<PathBuf>,
+    #[serde(default)]
+    pub work_directory_policy: WorkDirectoryPolicyConfig,
+    #[serde(default)]
+    pub secret_scanning: SecretScanningConfig,
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+}
+
+/// 静态数据加密配置
+///
+/// 对任务表的提示词、结果和元数据列以及事件快照进行字段级 AES-256-GCM 加密。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    pub enabled: bool,
+    /// 当前用于加密的密钥ID
+    pub active_key_id: String,
+    /// 密钥ID -> base64编码的256位密钥，轮换后旧密钥保留用于解密
+    pub keys: std::collections::HashMap<String, String>,
+    /// 从该环境变量读取当前密钥（如由KMS注入），优先于 `keys` 中的同ID密钥
+    pub key_env: Option<String>,
+    /// 启动时将明文行和旧密钥加密的行重新加密为当前密钥
+    pub reencrypt_on_startup: bool,
+}
+
+impl Default for EncryptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            active_key_id: "primary".to_string(),
+            keys: std::collections::HashMap::new(),
+            key_env: None,
+            reencrypt_on_startup: false,
+        }
+    }
+}
+
+/// 检测到密钥时的处理方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretAction {
+    /// 拒绝创建任务
+    Reject,
+    /// 将命中内容替换为占位符后保存
+    Redact,
+    /// 原样保存，仅在任务元数据中记录检测结果
+    Warn,
+}
+
+/// 密钥扫描配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SecretScanningConfig {
+    pub enabled: bool,
+    pub action: SecretAction,
+    /// 自定义规则（规则名 -> 正则），与内置规则一起生效
+    pub rules: std::collections::HashMap<String, String>,
+    /// 高熵字符串阈值（比特/字符）
+    pub entropy_threshold: f64,
+    /// 参与熵检测的最小长度
+    pub min_entropy_length: usize,
+}
+
+impl Default for SecretScanningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            action: SecretAction::Redact,
+            rules: std::collections::HashMap::new(),
+            entropy_threshold: 4.5,
+            min_entropy_length: 32,
+        }
+    }
+}
+
+/// 工作目录策略违规时的处理方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    /// 拒绝创建任务
+    Reject,
+    /// 允许创建，但在任务元数据中标记违规；获取任务时只记录警告
+    Flag,
+}
+
+/// 工作目录策略配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct WorkDirectoryPolicyConfig {
+    pub enabled: bool,
+    /// 允许的路径前缀（按路径组件匹配），为空表示不限制
+    pub allowed_prefixes: Vec<String>,
+    /// 禁止的路径正则
+    pub denied_patterns: Vec<String>,
+    pub action: PolicyAction,
+    /// 工作者获取任务时检查工作目录是否存在
+    pub check_exists_on_acquire: bool,
+}
+
+impl Default for WorkDirectoryPolicyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allowed_prefixes: vec![],
+            denied_patterns: vec![],
+            action: PolicyAction::Reject,
+            check_exists_on_acquire: false,
+        }
+    }
 }
 
 impl Default for SecurityConfig {
@@ -150,12 +369,16 @@ impl Default for SecurityConfig {
             enable_https: false,
             tls_cert_path: None,
             tls_key_path: None,
+            work_directory_policy: WorkDirectoryPolicyConfig::default(),
+            secret_scanning: SecretScanningConfig::default(),
+            encryption: EncryptionConfig::default(),
         }
     }
 }
 
 /// 任务配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct TaskConfig {
     pub max_concurrent_tasks: u32,
     pub default_task_timeout: u64,
@@ -166,6 +389,16 @@ pub struct TaskConfig {
     pub enable_fair_scheduling: bool,
     pub worker_timeout: u64,
     pub heartbeat_interval: u64,
+    /// 启用缓冲式历史写入，状态转换历史按批写入数据库
+    pub enable_history_batching: bool,
+    /// 每批写入的最大历史记录数
+    pub history_batch_size: usize,
+    /// 历史缓冲区刷新间隔（毫秒）
+    pub history_flush_interval_ms: u64,
+    /// 等待任务完成接口的最长等待时间（秒）
+    pub max_wait_timeout: u64,
+    /// 新任务使用按创建时间排序的UUIDv7作为ID，关闭时使用随机的UUIDv4
+    pub time_ordered_ids: bool,
 }
 
 impl Default for TaskConfig {
@@ -180,12 +413,17 @@ impl Default for TaskConfig {
             enable_fair_scheduling: true,
             worker_timeout: 300,
             heartbeat_interval: 30,
+            enable_history_batching: false,
+            history_batch_size: 100,
+            history_flush_interval_ms: 200,
+            max_wait_timeout: 300,
+            time_ordered_ids: false,
         }
     }
 }
 
 /// 监控配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MonitoringConfig {
     pub enable_metrics: bool,
     pub metrics_endpoint: String,
@@ -196,6 +434,15 @@ pub struct MonitoringConfig {
     pub enable_tracing: bool,
     pub tracing_endpoint: Option<String>,
     pub metrics_collection_interval: u64,
+    /// 独立指标监听端口，设置后指标和健康检查端点在该端口单独提供
+    #[serde(default)]
+    pub metrics_port: Option<u16>,
+    /// Prometheus指标的标签基数限制和示例配置
+    #[serde(default)]
+    pub metric_labels: MetricLabelsConfig,
+    /// 内存自监控配置
+    #[serde(default)]
+    pub memory_watchdog: MemoryWatchdogConfig,
 }
 
 impl Default for MonitoringConfig {
@@ -210,19 +457,100 @@ impl Default for MonitoringConfig {
             enable_tracing: false,
             tracing_endpoint: None,
             metrics_collection_interval: 60,
+            metrics_port: None,
+            metric_labels: MetricLabelsConfig::default(),
+            memory_watchdog: MemoryWatchdogConfig::default(),
+        }
+    }
+}
+
+/// Prometheus指标标签配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct MetricLabelsConfig {
+    /// 允许输出的标签名，未列出的标签不会出现在指标中
+    pub allowed_labels: Vec<String>,
+    /// 每个标签最多记录的不同取值数，超出后记为 `__overflow__`
+    pub max_values_per_label: usize,
+    /// `work_directory` 标签只保留前几级路径
+    pub work_directory_depth: usize,
+    /// `work_directory` 标签输出路径哈希而不是路径本身
+    pub hash_work_directory: bool,
+    /// 直方图样本附带追踪ID示例，仅OpenMetrics格式输出
+    pub enable_exemplars: bool,
+}
+
+impl Default for MetricLabelsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_labels: ["work_directory", "priority", "reason", "method", "route", "status"]
+                .iter()
+                .map(|label| label.to_string())
+                .collect(),
+            max_values_per_label: 100,
+            work_directory_depth: 3,
+            hash_work_directory: false,
+            enable_exemplars: true,
+        }
+    }
+}
+
+/// 内存自监控配置
+///
+/// 常驻内存（RSS）超过软上限时清空进程内缓存，超过硬上限时额外拒绝大请求体，
+/// 在被OOM killer终止之前释放内存。上限为0表示不检查。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct MemoryWatchdogConfig {
+    pub enabled: bool,
+    /// 检查间隔（秒）
+    pub check_interval: u64,
+    /// 软上限（字节），超过时清空缓存
+    pub soft_limit_bytes: u64,
+    /// 硬上限（字节），超过时拒绝大请求体
+    pub hard_limit_bytes: u64,
+    /// 超过硬上限时允许的最大请求体（字节）
+    pub max_body_bytes_under_pressure: u64,
+}
+
+impl Default for MemoryWatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_interval: 10,
+            soft_limit_bytes: 512 * 1024 * 1024, // 512MB
+            hard_limit_bytes: 768 * 1024 * 1024, // 768MB
+            max_body_bytes_under_pressure: 64 * 1024, // 64KB
+        }
+    }
+}
+
+impl MemoryWatchdogConfig {
+    /// 内存自监控使用的上限
+    pub fn limits(&self) -> memory_watchdog::MemoryLimits {
+        memory_watchdog::MemoryLimits {
+            check_interval: std::time::Duration::from_secs(self.check_interval),
+            soft_limit_bytes: self.soft_limit_bytes,
+            hard_limit_bytes: self.hard_limit_bytes,
+            max_body_bytes_under_pressure: self.max_body_bytes_under_pressure,
         }
     }
 }
 
 /// 缓存配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct CacheConfig {
     pub enable_cache: bool,
     pub cache_type: CacheType,
+    /// 单个任务缓存的存活时间（秒）
     pub cache_ttl: u64,
+    /// 缓存容量上限（任务数）
     pub cache_size: u64,
     pub redis_url: Option<String>,
     pub memory_cache_size: u64,
+    /// 任务列表查询缓存的存活时间（秒）
+    pub list_cache_ttl: u64,
 }
 
 impl Default for CacheConfig {
@@ -234,12 +562,13 @@ impl Default for CacheConfig {
             cache_size: 1000,
             redis_url: None,
             memory_cache_size: 100 * 1024 * 1024, // 100MB
+            list_cache_ttl: 5,
         }
     }
 }
 
 /// 缓存类型
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum CacheType {
     Memory,
@@ -247,7 +576,7 @@ pub enum CacheType {
 }
 
 /// 外部服务配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExternalServiceConfig {
     pub enable_external_services: bool,
     pub services: std::collections::HashMap<String, ExternalService>,
@@ -263,19 +592,529 @@ impl Default for ExternalServiceConfig {
 }
 
 /// 外部服务
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct ExternalService {
     pub url: String,
+    /// 单次请求超时（秒）
     pub timeout: u64,
+    /// 失败后的最大重试次数
     pub retries: u32,
+    /// 首次重试前的等待时间（毫秒），之后每次翻倍
+    pub retry_backoff_ms: u64,
     pub api_key: Option<String>,
     pub enable_circuit_breaker: bool,
     pub circuit_breaker_threshold: u32,
+    /// 熔断器打开后多久（秒）放行试探请求
     pub circuit_breaker_timeout: u64,
 }
 
+impl Default for ExternalService {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            timeout: 30,
+            retries: 3,
+            retry_backoff_ms: 200,
+            api_key: None,
+            enable_circuit_breaker: true,
+            circuit_breaker_threshold: 5,
+            circuit_breaker_timeout: 60,
+        }
+    }
+}
+
+/// 集群配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ClusterConfig {
+    pub enable_leader_election: bool,
+    pub node_id: Option<String>,
+    pub leader_lease_ttl: u64,
+    pub leader_renew_interval: u64,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self {
+            enable_leader_election: false,
+            node_id: None,
+            leader_lease_ttl: 30,
+            leader_renew_interval: 10,
+        }
+    }
+}
+
+impl ClusterConfig {
+    /// 获取节点ID，未配置时使用主机名加随机后缀
+    pub fn resolve_node_id(&self) -> String {
+        match &self.node_id {
+            Some(node_id) if !node_id.is_empty() => node_id.clone(),
+            _ => {
+                let host = env::var("HOSTNAME").unwrap_or_else(|_| "node".to_string());
+                format!("{}-{}", host, &uuid::Uuid::new_v4().simple().to_string()[..8])
+            }
+        }
+    }
+}
+
+/// systemd 集成配置
+///
+/// 启用后，在 `Type=notify` 单元中启动时发送 `READY=1`/`STOPPING=1`，
+/// 单元配置了 `WatchdogSec` 时定期发送看门狗保活。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SystemdConfig {
+    pub enabled: bool,
+}
+
+/// HTTP请求并发配额配置
+///
+/// 按路由组（执行、写、只读）和租户（API密钥或客户端地址）限制同时处理的请求数，
+/// 排队超时或排队过深时返回 `429`。限额为0表示不限制。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct RequestQuotaConfig {
+    pub enabled: bool,
+    /// 获取、完成任务和上传产物请求的并发上限
+    pub execute_limit: usize,
+    /// 其他写请求的并发上限
+    pub write_limit: usize,
+    /// 只读请求的并发上限
+    pub read_limit: usize,
+    /// 单个租户的并发上限
+    pub per_tenant_limit: usize,
+    /// 等待许可的最长时间（毫秒）
+    pub queue_timeout_ms: u64,
+    /// 每个路由组的最大排队请求数
+    pub max_queue_depth: usize,
+}
+
+impl Default for RequestQuotaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            execute_limit: 32,
+            write_limit: 64,
+            read_limit: 256,
+            per_tenant_limit: 16,
+            queue_timeout_ms: 1000,
+            max_queue_depth: 128,
+        }
+    }
+}
+
+/// 请求录制配置（调试用）
+///
+/// 启用后记录脱敏后的API请求和响应，通过 `GET /api/v1/admin/recent-requests` 查看，
+/// 可用 `replay` 工具重放到测试实例。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct RequestRecordingConfig {
+    pub enabled: bool,
+    /// 内存中保留的最近请求数
+    pub capacity: usize,
+    /// 请求体和响应体的录制上限，超出时只记录长度
+    pub max_body_bytes: usize,
+    /// 同时追加写入的JSON Lines文件
+    pub file: Option<String>,
+}
+
+impl Default for RequestRecordingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            capacity: 200,
+            max_body_bytes: 65536,
+            file: None,
+        }
+    }
+}
+
+/// 压缩请求体配置
+///
+/// 接受 `Content-Encoding: gzip` / `deflate` 的请求体，解压时限制解压后大小和压缩比，防止压缩炸弹。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct RequestDecompressionConfig {
+    pub enabled: bool,
+    /// 解压后请求体的大小上限（字节）
+    pub max_decompressed_bytes: usize,
+    /// 解压后大小与压缩大小之比的上限，解压后不超过64KB的请求体不检查
+    pub max_ratio: usize,
+}
+
+impl Default for RequestDecompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_decompressed_bytes: 8 * 1024 * 1024,
+            max_ratio: 100,
+        }
+    }
+}
+
+/// 消息队列配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct QueueConfig {
+    pub enabled: bool,
+    pub backend: QueueBackend,
+    pub url: String,
+    pub subject_prefix: String,
+    pub ack_subject: String,
+    pub rabbitmq_vhost: String,
+    pub rabbitmq_exchange: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub poll_interval_ms: u64,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: QueueBackend::Nats,
+            url: "nats://127.0.0.1:4222".to_string(),
+            subject_prefix: "tasks".to_string(),
+            ack_subject: "tasks.ack".to_string(),
+            rabbitmq_vhost: "/".to_string(),
+            rabbitmq_exchange: "tasks".to_string(),
+            username: None,
+            password: None,
+            poll_interval_ms: 1000,
+        }
+    }
+}
+
+/// 消息队列后端
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum QueueBackend {
+    Nats,
+    RabbitMq,
+}
+
+/// 事件导出配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct EventExportConfig {
+    pub enabled: bool,
+    pub kafka_rest_url: String,
+    pub topic: String,
+    pub batch_size: usize,
+    pub flush_interval_ms: u64,
+    pub max_retries: u32,
+    pub retry_backoff_ms: u64,
+    pub buffer_size: usize,
+    pub request_timeout: u64,
+}
+
+impl Default for EventExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            kafka_rest_url: "http://127.0.0.1:8082".to_string(),
+            topic: "task-lifecycle-events".to_string(),
+            batch_size: 100,
+            flush_interval_ms: 1000,
+            max_retries: 3,
+            retry_backoff_ms: 200,
+            buffer_size: 10000,
+            request_timeout: 10,
+        }
+    }
+}
+
+/// 对象存储配置（S3兼容）
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ObjectStorageConfig {
+    pub enabled: bool,
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub path_style: bool,
+    pub artifact_prefix: String,
+    pub multipart_threshold: usize,
+    pub part_size: usize,
+    pub presign_expiry: u64,
+    /// 单个产物的最大字节数，同时作为上传接口的请求体上限
+    pub max_artifact_size: usize,
+}
+
+impl Default for ObjectStorageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "http://127.0.0.1:9000".to_string(),
+            bucket: "task-artifacts".to_string(),
+            region: "us-east-1".to_string(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            path_style: true,
+            artifact_prefix: "artifacts".to_string(),
+            multipart_threshold: 16 * 1024 * 1024, // 16MB
+            part_size: 8 * 1024 * 1024, // 8MB
+            presign_expiry: 3600,
+            max_artifact_size: 256 * 1024 * 1024, // 256MB
+        }
+    }
+}
+
+/// 维护窗口配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    pub windows: Vec<MaintenanceWindowConfig>,
+}
+
+/// 单个维护窗口
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct MaintenanceWindowConfig {
+    pub name: String,
+    /// 时间表，如 `Sat 02:00-04:00 UTC`、`Mon-Fri 23:00-01:00`、`* 03:00-03:30`（UTC）
+    pub schedule: String,
+    /// 受影响的工作目录前缀，为空时匹配全部工作目录
+    pub work_directories: Vec<String>,
+    /// 受影响的任务标签，为空时匹配全部任务
+    pub tags: Vec<String>,
+}
+
+/// 任务流水线配置：按顺序执行的任务模板
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct PipelineConfig {
+    pub description: Option<String>,
+    pub steps: Vec<PipelineStepConfig>,
+    /// 单次运行最多创建的步骤数，防止分支跳转形成死循环
+    pub max_steps: usize,
+    /// 各步骤任务的并发组，步骤可单独覆盖
+    pub concurrency_group: Option<String>,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            description: None,
+            steps: Vec::new(),
+            max_steps: 20,
+            concurrency_group: None,
+        }
+    }
+}
+
+/// 流水线步骤
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct PipelineStepConfig {
+    /// 步骤名称，为空时使用 `step-<序号>`
+    pub name: String,
+    /// 提示词模板，`{{input}}` 替换为运行时的输入，`{{previous_output}}` 替换为上一步的输出
+    pub prompt: String,
+    /// 步骤优先级，未设置时使用运行请求的优先级
+    pub priority: Option<String>,
+    /// 追加到运行请求标签之后的标签
+    pub tags: Vec<String>,
+    /// 执行条件，不成立时跳过本步骤，继续判断后面的步骤
+    pub when: Option<String>,
+    /// 本步骤成功后按顺序判断的分支，第一个成立的分支决定下一步
+    pub branches: Vec<PipelineBranchConfig>,
+    /// 本步骤任务的并发组，未设置时使用流水线的并发组
+    pub concurrency_group: Option<String>,
+    /// 本步骤任务需要人工审批后才能执行
+    pub requires_approval: bool,
+}
+
+/// 流水线分支：条件成立时跳转到指定步骤
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct PipelineBranchConfig {
+    /// 对本步骤结果求值的条件表达式
+    pub when: String,
+    /// 目标步骤名称
+    pub goto: String,
+}
+
+/// 人工审批配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ApprovalConfig {
+    /// 审批人（名称 -> API密钥），为空时不校验调用方，审批人取请求中的 `approver`
+    pub approvers: std::collections::HashMap<String, String>,
+    /// 待审批任务超过该时间（秒）未处理时自动拒绝，0 表示不自动拒绝
+    pub auto_reject_after: u64,
+}
+
+/// 失败分类规则
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FailureRuleConfig {
+    /// 失败类别：timeout / oom / tool_error / validation / upstream / unknown
+    pub category: String,
+    /// 匹配错误信息的正则
+    pub pattern: String,
+}
+
+/// 失败分类配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct FailureClassificationConfig {
+    /// 自定义规则，按顺序匹配，先于内置规则生效
+    pub rules: Vec<FailureRuleConfig>,
+}
+
+/// 单个失败类别的重试策略
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct CategoryRetryConfig {
+    /// 该类别失败时的最大重试次数，未设置时沿用任务的 `max_retries`
+    pub max_retries: Option<u32>,
+    /// 首次重试前的等待时间（秒），0 表示立即重新排队
+    pub backoff_seconds: u64,
+    /// 每次重试后等待时间的倍数
+    pub backoff_multiplier: f64,
+    /// 等待时间上限（秒）
+    pub max_backoff_seconds: u64,
+}
+
+impl Default for CategoryRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: None,
+            backoff_seconds: 0,
+            backoff_multiplier: 2.0,
+            max_backoff_seconds: 3600,
+        }
+    }
+}
+
+/// 单个优先级的SLA目标（秒），0 表示不设目标
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SlaTargetConfig {
+    /// 创建后多长时间内必须开始执行
+    pub start_within: u64,
+    /// 创建后多长时间内必须执行结束
+    pub finish_within: u64,
+}
+
+/// SLA告警规则
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SlaAlertConfig {
+    pub name: String,
+    /// 只统计该优先级，未设置时统计全部优先级
+    pub priority: Option<String>,
+    /// 统计的违约类型：start / finish / any
+    pub kind: String,
+    /// 统计窗口内的违约任务数达到该值时触发
+    pub threshold: u64,
+}
+
+impl Default for SlaAlertConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            priority: None,
+            kind: "any".to_string(),
+            threshold: 1,
+        }
+    }
+}
+
+/// SLA跟踪配置，`targets` 为空时不跟踪
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SlaConfig {
+    /// 优先级 -> SLA目标
+    pub targets: std::collections::HashMap<String, SlaTargetConfig>,
+    /// 统计窗口（秒），只统计窗口内创建的任务和仍在进行的任务
+    pub window: u64,
+    /// 已用时间达到目标的该比例时视为有违约风险
+    pub at_risk_ratio: f64,
+    pub alerts: Vec<SlaAlertConfig>,
+    /// 告警状态变化时通知的外部服务（`external_services.services` 中的名称）
+    pub alert_service: Option<String>,
+    /// 通知请求的路径
+    pub alert_path: String,
+}
+
+impl Default for SlaConfig {
+    fn default() -> Self {
+        Self {
+            targets: std::collections::HashMap::new(),
+            window: 86400,
+            at_risk_ratio: 0.8,
+            alerts: Vec::new(),
+            alert_service: None,
+            alert_path: "/alerts".to_string(),
+        }
+    }
+}
+
+/// 冷存储配置，启用后调度器把结束较久的任务移入冷存储，不再删除
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ColdStorageConfig {
+    pub enabled: bool,
+    /// 段文件目录
+    pub directory: String,
+    /// 任务结束超过该天数后移入冷存储
+    pub after_days: u32,
+    /// 每个清理周期最多移动的任务数，同时也是单个段文件的最大任务数
+    pub batch_size: u32,
+}
+
+impl Default for ColdStorageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: "./data/cold".to_string(),
+            after_days: 30,
+            batch_size: 500,
+        }
+    }
+}
+
+/// 热备配置
+///
+/// 启用后本实例作为备机启动：从主实例的事件流复制任务状态，只提供只读接口，不运行调度等后台任务；
+/// 主实例故障时通过管理接口提升为主实例。主备两端都需要开启事件溯源。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct StandbyConfig {
+    /// 以备机身份启动
+    pub enabled: bool,
+    /// 主实例的API地址，例如 `http://primary:8080`
+    pub primary_url: String,
+    /// 拉取事件的间隔（毫秒）
+    pub poll_interval_ms: u64,
+    /// 每次拉取的最大事件数
+    pub batch_size: u32,
+    /// 拉取请求超时（秒）
+    pub request_timeout: u64,
+    /// 共享令牌：设置后复制接口和提升接口要求 `Authorization: Bearer <token>`，备机拉取时携带该令牌
+    pub token: Option<String>,
+}
+
+impl Default for StandbyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            primary_url: String::new(),
+            poll_interval_ms: 1000,
+            batch_size: 500,
+            request_timeout: 10,
+            token: None,
+        }
+    }
+}
+
 /// 应用配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AppConfig {
     pub database: DatabaseConfig,
     pub server: ServerConfig,
@@ -285,12 +1124,56 @@ pub struct AppConfig {
     pub monitoring: MonitoringConfig,
     pub cache: CacheConfig,
     pub external_services: ExternalServiceConfig,
+    #[serde(default)]
+    pub cluster: ClusterConfig,
+    #[serde(default)]
+    pub queue: QueueConfig,
+    #[serde(default)]
+    pub event_export: EventExportConfig,
+    #[serde(default)]
+    pub object_storage: ObjectStorageConfig,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    #[serde(default)]
+    pub systemd: SystemdConfig,
+    #[serde(default)]
+    pub request_recording: RequestRecordingConfig,
+    #[serde(default)]
+    pub request_decompression: RequestDecompressionConfig,
+    #[serde(default)]
+    pub request_quotas: RequestQuotaConfig,
+    #[serde(default)]
+    pub approval: ApprovalConfig,
+    #[serde(default)]
+    pub failure_classification: FailureClassificationConfig,
+    /// 按失败类别的重试策略（类别 -> 策略），未配置的类别沿用任务的 `max_retries` 且不等待
+    #[serde(default)]
+    pub retry_policy: std::collections::HashMap<String, CategoryRetryConfig>,
+    #[serde(default)]
+    pub sla: SlaConfig,
+    #[serde(default)]
+    pub cold_storage: ColdStorageConfig,
+    #[serde(default)]
+    pub standby: StandbyConfig,
+    /// 任务流水线（名称 -> 定义）
+    #[serde(default)]
+    pub pipelines: std::collections::HashMap<String, PipelineConfig>,
     pub environment: Environment,
     pub debug: bool,
     pub version: String,
 }
 
 impl AppConfig {
+    /// 配置文件的JSON Schema（`--print-config-schema` 输出，`--validate-config` 按它校验）
+    pub fn json_schema() -> serde_json::Value {
+        let mut schema = schemars::schema_for!(AppConfig).to_value();
+        // 这三个字段由加载器按 APP_ENV 和编译版本提供默认值，配置文件中可以省略
+        if let Some(required) = schema.get_mut("required").and_then(|r| r.as_array_mut()) {
+            required.retain(|name| !matches!(name.as_str(), Some("environment" | "debug" | "version")));
+        }
+        schema
+    }
+
     /// 从环境变量加载配置
     pub fn from_env() -> Result<Self, ConfigError> {
         // 确定环境
@@ -302,16 +1185,27 @@ impl AppConfig {
             _ => Environment::Development,
         };
 
-        let config = Config::builder()
+        let files = Config::builder()
             .add_source(File::with_name("config/default").required(false))
             .add_source(File::with_name("config/local").required(false))
             .add_source(File::with_name("config/production").required(false))
-            .add_source(ConfigEnv::with_prefix("APP").separator("_"))
             .set_default("environment", environment.to_string())?
             .set_default("debug", matches!(environment, Environment::Development))?
             .set_default("version", env!("CARGO_PKG_VERSION"))?
             .build()?;
 
+        // 展开配置文件中的 `${ENV_VAR}`，再叠加环境变量覆盖（`APP_*_FILE` 从文件读取密钥）
+        let foreign = |e: config_schema::EnvError| ConfigError::Foreign(Box::new(e));
+        let mut base: serde_json::Value = files.try_deserialize()?;
+        config_schema::interpolate_value(&mut base).map_err(foreign)?;
+        let env = config_schema::EnvOverrides::new("APP", "_").vars(&base).map_err(foreign)?;
+
+        let config = Config::builder()
+            // 通过JSON源回填而不是 Config::try_from，后者会丢弃空表（如 `services = {}`）
+            .add_source(File::from_str(&base.to_string(), config::FileFormat::Json))
+            .add_source(ConfigEnv::with_prefix("APP").separator("_").source(Some(env)))
+            .build()?;
+
         config.try_deserialize()
     }
 
@@ -343,6 +1237,8 @@ impl AppConfig {
             ));
         }
 
+        self.server.socket_mode()?;
+
         // 验证安全配置
         if self.security.enable_auth && self.security.api_keys.is_empty() {
             return Err(AppError::Configuration(
@@ -350,6 +1246,53 @@ impl AppConfig {
             ));
         }
 
+        for (name, pattern) in &self.security.secret_scanning.rules {
+            if let Err(e) = regex::Regex::new(pattern) {
+                return Err(AppError::Configuration(
+                    ConfigError::Message(format!("Invalid secret scanning rule '{}': {}", name, e))
+                ));
+            }
+        }
+
+        let encryption = &self.security.encryption;
+        if encryption.enabled
+            && encryption.key_env.is_none()
+            && !encryption.keys.contains_key(&encryption.active_key_id)
+        {
+            return Err(AppError::Configuration(
+                ConfigError::Message(format!("Encryption key '{}' is not configured", encryption.active_key_id))
+            ));
+        }
+
+        for pattern in &self.security.work_directory_policy.denied_patterns {
+            if let Err(e) = regex::Regex::new(pattern) {
+                return Err(AppError::Configuration(
+                    ConfigError::Message(format!("Invalid denied work directory pattern '{}': {}", pattern, e))
+                ));
+            }
+        }
+
+        if let Some(service) = &self.sla.alert_service {
+            if !self.external_services.enable_external_services || !self.external_services.services.contains_key(service) {
+                return Err(AppError::Configuration(
+                    ConfigError::Message(format!("SLA alert service '{}' is not an enabled external service", service))
+                ));
+            }
+        }
+
+        if self.cold_storage.enabled {
+            if self.cold_storage.directory.is_empty() {
+                return Err(AppError::Configuration(
+                    ConfigError::Message("Cold storage directory cannot be empty".to_string())
+                ));
+            }
+            if self.cold_storage.after_days == 0 || self.cold_storage.batch_size == 0 {
+                return Err(AppError::Configuration(
+                    ConfigError::Message("Cold storage after_days and batch_size must be positive".to_string())
+                ));
+            }
+        }
+
         // 验证任务配置
         if self.task.max_concurrent_tasks == 0 {
             return Err(AppError::Configuration(
@@ -363,6 +1306,65 @@ impl AppConfig {
             ));
         }
 
+        // 验证集群配置
+        if self.cluster.enable_leader_election
+            && self.cluster.leader_renew_interval >= self.cluster.leader_lease_ttl
+        {
+            return Err(AppError::Configuration(
+                ConfigError::Message("Leader renew interval must be less than lease TTL".to_string())
+            ));
+        }
+
+        // 验证热备配置
+        if self.standby.enabled {
+            if self.standby.primary_url.is_empty() {
+                return Err(AppError::Configuration(
+                    ConfigError::Message("Standby mode requires a primary URL".to_string())
+                ));
+            }
+            if !self.database.enable_event_sourcing {
+                return Err(AppError::Configuration(
+                    ConfigError::Message("Standby mode requires event sourcing to be enabled".to_string())
+                ));
+            }
+            if self.standby.batch_size == 0 || self.standby.poll_interval_ms == 0 {
+                return Err(AppError::Configuration(
+                    ConfigError::Message("Standby batch_size and poll_interval_ms must be positive".to_string())
+                ));
+            }
+        }
+
+        // 验证队列配置
+        if self.queue.enabled && self.queue.url.is_empty() {
+            return Err(AppError::Configuration(
+                ConfigError::Message("Queue URL is required when queue distribution is enabled".to_string())
+            ));
+        }
+
+        // 验证事件导出配置
+        if self.event_export.enabled && (self.event_export.topic.is_empty() || self.event_export.batch_size == 0) {
+            return Err(AppError::Configuration(
+                ConfigError::Message("Event export requires a topic and a non-zero batch size".to_string())
+            ));
+        }
+
+        // 验证对象存储配置
+        if self.object_storage.enabled
+            && (self.object_storage.bucket.is_empty() || self.object_storage.access_key_id.is_empty())
+        {
+            return Err(AppError::Configuration(
+                ConfigError::Message("Object storage requires a bucket and credentials".to_string())
+            ));
+        }
+
+        // 验证维护窗口配置
+        for window in &self.maintenance.windows {
+            crate::services::MaintenanceWindow::from_config(window)?;
+        }
+
+        // 验证流水线配置
+        crate::services::PipelineRegistry::from_config(&self.pipelines)?;
+
         // 验证缓存配置
         if self.cache.enable_cache && self.cache.cache_type == CacheType::Redis && self.cache.redis_url.is_none() {
             return Err(AppError::Configuration(
@@ -400,7 +1402,7 @@ impl AppConfig {
 }
 
 /// 环境类型
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Environment {
     Development,
@@ -527,6 +1529,22 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_cluster_config_validation() {
+        let mut config = AppConfig::from_env().unwrap();
+        config.security.enable_auth = false;
+        config.cluster.enable_leader_election = true;
+        config.cluster.leader_lease_ttl = 10;
+        config.cluster.leader_renew_interval = 10;
+        assert!(config.validate().is_err());
+
+        config.cluster.leader_renew_interval = 3;
+        assert!(config.validate().is_ok());
+
+        config.cluster.node_id = Some("node-1".to_string());
+        assert_eq!(config.cluster.resolve_node_id(), "node-1");
+    }
+
     #[test]
     fn test_environment_detection() {
         std::env::set_var("APP_ENV", "production");
@@ -541,4 +1559,22 @@ mod tests {
         let config = AppConfig::from_env().unwrap();
         assert!(config.is_test());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_bundled_configs_match_schema() {
+        let schema = AppConfig::json_schema();
+        for file in ["config/default.toml", "config/production.toml"] {
+            let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(file);
+            let issues = config_schema::validate_file(&schema, &path).unwrap();
+            assert!(issues.is_empty(), "{}: {:?}", file, issues);
+        }
+
+        let issues = config_schema::validate_str(
+            &schema,
+            config_schema::ConfigFormat::Yaml,
+            "logging:\n  format: xml\n",
+        )
+        .unwrap();
+        assert!(issues.iter().any(|i| i.pointer == "/logging/format" && i.line == Some(2)), "{:?}", issues);
+    }
+}
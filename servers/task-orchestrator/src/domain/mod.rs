@@ -193,7 +193,7 @@ use std::collections::HashMap;
 use validator::Validate;
 
 /// 任务ID值对象
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct TaskId(Uuid);
 
 impl TaskId {
@@ -201,6 +201,18 @@ impl TaskId {
         Self(Uuid::new_v4())
     }
 
+    /// 按创建时间生成按时间排序的UUIDv7
+    ///
+    /// 高48位为创建时间的毫秒时间戳，同一毫秒内的ID顺序随机。解析时v4和v7都接受。
+    pub fn new_v7(created_at: DateTime<Utc>) -> Self {
+        let timestamp = uuid::Timestamp::from_unix(
+            uuid::NoContext,
+            created_at.timestamp().max(0) as u64,
+            created_at.timestamp_subsec_nanos(),
+        );
+        Self(Uuid::new_v7(timestamp))
+    }
+
     pub fn from_str(s: &str) -> Result<Self, TaskIdError> {
         let uuid = Uuid::from_str(s).map_err(TaskIdError::InvalidUuid)?;
         Ok(Self(uuid))
@@ -293,6 +305,9 @@ pub enum WorkDirectoryError {
     InvalidPath,
 }
 
+/// 提示词长度上限（字节）
+pub const MAX_PROMPT_LENGTH: usize = 10000;
+
 /// 任务提示值对象
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Prompt(String);
@@ -303,7 +318,7 @@ impl Prompt {
             return Err(PromptError::EmptyPrompt);
         }
 
-        if text.len() > 10000 {
+        if text.len() > MAX_PROMPT_LENGTH {
             return Err(PromptError::PromptTooLong);
         }
 
@@ -427,8 +442,10 @@ pub enum WorkerIdError {
 
 /// 任务状态枚举
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::EnumString, strum::Display)]
-#[strum(serialize_all = "lowercase")]
+#[strum(serialize_all = "snake_case")]
 pub enum TaskStatus {
+    /// 等待人工审批，审批通过前不会被获取
+    PendingApproval,
     Waiting,
     Working,
     Completed,
@@ -439,6 +456,7 @@ pub enum TaskStatus {
 impl TaskStatus {
     pub fn from_str(s: &str) -> Result<Self, TaskStatusError> {
         match s.to_lowercase().as_str() {
+            "pending_approval" => Ok(TaskStatus::PendingApproval),
             "waiting" => Ok(TaskStatus::Waiting),
             "working" => Ok(TaskStatus::Working),
             "completed" => Ok(TaskStatus::Completed),
@@ -467,6 +485,19 @@ impl TaskStatusError {
     }
 }
 
+/// 任务取消原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::EnumString, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum CancelReason {
+    /// 通过接口手动取消
+    Requested,
+    /// 超过有效期仍未开始，由调度器取消
+    Expired,
+    /// 审批被拒绝或超过审批时限
+    Rejected,
+}
+
 /// 任务优先级枚举
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::EnumString, strum::Display)]
 #[strum(serialize_all = "lowercase")]
@@ -511,6 +542,25 @@ impl TaskPriorityError {
     }
 }
 
+/// 任务失败分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, strum::EnumString, strum::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum FailureCategory {
+    /// 执行超时
+    Timeout,
+    /// 内存不足
+    Oom,
+    /// 执行器调用的工具或命令出错
+    ToolError,
+    /// 输入或输出校验失败
+    Validation,
+    /// 上游服务不可用或限流
+    Upstream,
+    /// 无法归类
+    Unknown,
+}
+
 /// 任务结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskResult {
@@ -520,6 +570,9 @@ pub struct TaskResult {
     pub details: HashMap<String, serde_json::Value>,
     pub duration: Option<u64>, // 毫秒
     pub metadata: HashMap<String, serde_json::Value>,
+    /// 失败分类，执行器可以在失败结果中直接给出
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_category: Option<FailureCategory>,
 }
 
 impl TaskResult {
@@ -531,6 +584,7 @@ impl TaskResult {
             details: HashMap::new(),
             duration: None,
             metadata: HashMap::new(),
+            failure_category: None,
         }
     }
 
@@ -542,6 +596,7 @@ impl TaskResult {
             details: HashMap::new(),
             duration: None,
             metadata: HashMap::new(),
+            failure_category: None,
         }
     }
 
@@ -596,6 +651,179 @@ impl TaskHistory {
         self.details.insert(key, value);
         self
     }
+
+    /// 记录一次执行的序号（从1开始）和结果
+    pub fn with_attempt(self, attempt: u32, result: &TaskResult) -> Self {
+        self.with_detail(ATTEMPT_DETAIL_KEY.to_string(), serde_json::json!(attempt))
+            .with_detail(RESULT_DETAIL_KEY.to_string(), serde_json::json!(result))
+    }
+
+    /// 解析执行记录，非执行结束产生的历史返回 `None`
+    pub fn attempt(&self) -> Option<TaskAttempt> {
+        let attempt = self.details.get(ATTEMPT_DETAIL_KEY)?.as_u64()? as u32;
+        let result = serde_json::from_value(self.details.get(RESULT_DETAIL_KEY)?.clone()).ok()?;
+        Some(TaskAttempt {
+            attempt,
+            status: self.status,
+            worker_id: self.worker_id.clone(),
+            finished_at: self.changed_at,
+            result,
+        })
+    }
+}
+
+/// 任务历史详情中记录执行序号的键
+pub const ATTEMPT_DETAIL_KEY: &str = "attempt";
+
+/// 任务历史详情中记录执行结果的键
+pub const RESULT_DETAIL_KEY: &str = "result";
+
+/// 任务评论
+///
+/// 人工或工作节点附加在任务上的带时间戳的说明，可携带任意结构化数据。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskComment {
+    pub id: u64,
+    pub task_id: TaskId,
+    pub author: String,
+    pub text: String,
+    pub data: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaskComment {
+    pub fn new(task_id: TaskId, author: String, text: String) -> Self {
+        Self {
+            id: 0,
+            task_id,
+            author,
+            text,
+            data: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn with_data(mut self, data: serde_json::Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+/// 任务元数据模式
+///
+/// 按命名空间（工作目录前缀）注册的JSON Schema，创建和更新任务时用它校验元数据。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetadataSchema {
+    pub namespace: String,
+    pub schema: serde_json::Value,
+    /// 严格模式下拒绝模式中未声明的键
+    pub strict: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MetadataSchema {
+    pub fn new(namespace: String, schema: serde_json::Value, strict: bool) -> Self {
+        Self {
+            namespace,
+            schema,
+            strict,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// 流水线运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::EnumString, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum PipelineRunStatus {
+    /// 当前步骤尚未结束
+    Running,
+    /// 没有需要执行的后续步骤
+    Completed,
+    /// 某一步失败、取消或以失败结果完成
+    Stopped,
+    /// 执行步数达到流水线的上限（循环保护）
+    Aborted,
+}
+
+/// 流水线运行中已创建的一步
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineRunStep {
+    pub name: String,
+    pub task_id: TaskId,
+    pub status: TaskStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 流水线运行记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineRun {
+    pub run_id: String,
+    pub pipeline: String,
+    pub status: PipelineRunStatus,
+    /// 按创建顺序排列的步骤，分支跳转时同一步骤可能出现多次
+    pub steps: Vec<PipelineRunStep>,
+    /// 条件不成立而跳过的步骤名称
+    pub skipped: Vec<String>,
+    /// 停止或中止的原因
+    pub message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PipelineRun {
+    pub fn new(run_id: String, pipeline: String) -> Self {
+        let now = Utc::now();
+        Self {
+            run_id,
+            pipeline,
+            status: PipelineRunStatus::Running,
+            steps: Vec::new(),
+            skipped: Vec::new(),
+            message: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// 记录新创建的步骤任务
+    pub fn record_step(&mut self, name: String, task: &Task) {
+        self.steps.push(PipelineRunStep {
+            name,
+            task_id: task.id,
+            status: task.status,
+            created_at: task.created_at,
+        });
+        self.updated_at = Utc::now();
+    }
+
+    /// 同步步骤任务的状态
+    pub fn update_step(&mut self, task: &Task) {
+        if let Some(step) = self.steps.iter_mut().rev().find(|step| step.task_id == task.id) {
+            step.status = task.status;
+            self.updated_at = Utc::now();
+        }
+    }
+
+    /// 结束运行
+    pub fn finish(&mut self, status: PipelineRunStatus, message: Option<String>) {
+        self.status = status;
+        self.message = message;
+        self.updated_at = Utc::now();
+    }
+}
+
+/// 单次执行记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskAttempt {
+    /// 执行序号，从1开始
+    pub attempt: u32,
+    /// 执行结束后的任务状态（失败后重新排队时为 `waiting`）
+    pub status: TaskStatus,
+    pub worker_id: Option<WorkerId>,
+    pub finished_at: DateTime<Utc>,
+    pub result: TaskResult,
 }
 
 /// 任务聚合根
@@ -617,6 +845,30 @@ pub struct Task {
     pub max_retries: u32,
     pub metadata: HashMap<String, serde_json::Value>,
     pub version: u32,
+    /// 有效期，超过后仍在等待的任务会被取消
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 取消原因，仅已取消的任务有值
+    #[serde(default)]
+    pub cancel_reason: Option<CancelReason>,
+    /// 并发组，同一组内同时只有一个任务在执行
+    #[serde(default)]
+    pub concurrency_group: Option<String>,
+    /// 失败分类，仅最终失败或以失败结果完成的任务有值
+    #[serde(default)]
+    pub failure_category: Option<FailureCategory>,
+    /// 失败后重新排队的任务在该时间之前不会被获取
+    #[serde(default)]
+    pub retry_after: Option<DateTime<Utc>>,
+}
+
+/// 执行失败后的重试决定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryDecision {
+    /// 重新排队，设置 `not_before` 时在该时间之前不会被获取
+    Retry { not_before: Option<DateTime<Utc>> },
+    /// 不再重试，标记为失败
+    GiveUp,
 }
 
 impl Task {
@@ -644,6 +896,11 @@ impl Task {
             max_retries: 3,
             metadata: HashMap::new(),
             version: 1,
+            expires_at: None,
+            cancel_reason: None,
+            concurrency_group: None,
+            failure_category: None,
+            retry_after: None,
         }
     }
 
@@ -659,6 +916,7 @@ impl Task {
         self.status = TaskStatus::Working;
         self.worker_id = Some(worker_id);
         self.started_at = Some(Utc::now());
+        self.retry_after = None;
         self.version += 1;
 
         Ok(())
@@ -674,6 +932,7 @@ impl Task {
         }
 
         self.status = TaskStatus::Completed;
+        self.failure_category = result.failure_category.filter(|_| result.status == TaskResultStatus::Failed);
         self.result = Some(result);
         self.completed_at = Some(Utc::now());
         self.error_message = None;
@@ -684,6 +943,16 @@ impl Task {
 
     /// 任务失败
     pub fn fail(&mut self, error: String) -> Result<(), TaskError> {
+        let decision = if self.retry_count < self.max_retries {
+            RetryDecision::Retry { not_before: None }
+        } else {
+            RetryDecision::GiveUp
+        };
+        self.fail_with(error, decision)
+    }
+
+    /// 按给定的重试决定处理失败
+    pub fn fail_with(&mut self, error: String, decision: RetryDecision) -> Result<(), TaskError> {
         if self.status != TaskStatus::Working {
             return Err(TaskError::InvalidStatusTransition {
                 from: self.status,
@@ -691,19 +960,39 @@ impl Task {
             });
         }
 
-        if self.retry_count < self.max_retries {
-            // 重试任务
-            self.status = TaskStatus::Waiting;
-            self.worker_id = None;
-            self.started_at = None;
-            self.retry_count += 1;
-        } else {
-            // 达到最大重试次数，标记为失败
-            self.status = TaskStatus::Failed;
-            self.completed_at = Some(Utc::now());
-            self.error_message = Some(error);
+        match decision {
+            RetryDecision::Retry { not_before } => {
+                // 重试任务
+                self.status = TaskStatus::Waiting;
+                self.worker_id = None;
+                self.started_at = None;
+                self.retry_after = not_before;
+                self.retry_count += 1;
+            }
+            RetryDecision::GiveUp => {
+                // 不再重试，标记为失败
+                self.status = TaskStatus::Failed;
+                self.completed_at = Some(Utc::now());
+                self.error_message = Some(error);
+            }
+        }
+
+        self.version += 1;
+        Ok(())
+    }
+
+    /// 释放已获取的任务，使其重新等待（不计入重试次数）
+    pub fn release(&mut self) -> Result<(), TaskError> {
+        if self.status != TaskStatus::Working {
+            return Err(TaskError::InvalidStatusTransition {
+                from: self.status,
+                to: TaskStatus::Waiting,
+            });
         }
 
+        self.status = TaskStatus::Waiting;
+        self.worker_id = None;
+        self.started_at = None;
         self.version += 1;
         Ok(())
     }
@@ -720,11 +1009,134 @@ impl Task {
         self.status = TaskStatus::Cancelled;
         self.completed_at = Some(Utc::now());
         self.error_message = reason;
+        self.cancel_reason = Some(CancelReason::Requested);
+        self.version += 1;
+
+        Ok(())
+    }
+
+    /// 等待任务超过有效期，以 `Expired` 原因取消
+    pub fn expire(&mut self) -> Result<(), TaskError> {
+        if self.status != TaskStatus::Waiting {
+            return Err(TaskError::InvalidStatusTransition {
+                from: self.status,
+                to: TaskStatus::Cancelled,
+            });
+        }
+
+        let expires_at = self.expires_at.unwrap_or_else(Utc::now);
+        self.status = TaskStatus::Cancelled;
+        self.completed_at = Some(Utc::now());
+        self.error_message = Some(format!("Task expired at {} before it was started", expires_at.to_rfc3339()));
+        self.cancel_reason = Some(CancelReason::Expired);
+        self.version += 1;
+
+        Ok(())
+    }
+
+    /// 审批通过，进入等待状态
+    pub fn approve(&mut self) -> Result<(), TaskError> {
+        if self.status != TaskStatus::PendingApproval {
+            return Err(TaskError::InvalidStatusTransition {
+                from: self.status,
+                to: TaskStatus::Waiting,
+            });
+        }
+
+        self.status = TaskStatus::Waiting;
         self.version += 1;
+        Ok(())
+    }
 
+    /// 审批被拒绝，以 `Rejected` 原因取消
+    pub fn reject(&mut self, reason: String) -> Result<(), TaskError> {
+        if self.status != TaskStatus::PendingApproval {
+            return Err(TaskError::InvalidStatusTransition {
+                from: self.status,
+                to: TaskStatus::Cancelled,
+            });
+        }
+
+        self.status = TaskStatus::Cancelled;
+        self.completed_at = Some(Utc::now());
+        self.error_message = Some(reason);
+        self.cancel_reason = Some(CancelReason::Rejected);
+        self.version += 1;
         Ok(())
     }
 
+    /// 是否为已超过有效期的等待任务
+    pub fn is_past_expiry(&self, now: DateTime<Utc>) -> bool {
+        self.status == TaskStatus::Waiting && self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// 更新任务字段（仅限非终态任务），返回实际发生的变更
+    pub fn update_fields(
+        &mut self,
+        priority: Option<TaskPriority>,
+        tags: Option<Vec<TaskTag>>,
+        metadata: Option<HashMap<String, serde_json::Value>>,
+        max_retries: Option<u32>,
+    ) -> Result<Vec<TaskFieldChange>, TaskError> {
+        if self.status.is_terminal() {
+            return Err(TaskError::NotModifiable(self.status));
+        }
+
+        if let Some(max_retries) = max_retries {
+            if max_retries < self.retry_count {
+                return Err(TaskError::MaxRetriesExceeded {
+                    retry_count: self.retry_count,
+                    max_retries,
+                });
+            }
+        }
+
+        let mut changes = Vec::new();
+        let mut record = |field: &str, old_value: serde_json::Value, new_value: serde_json::Value| {
+            if old_value != new_value {
+                changes.push(TaskFieldChange {
+                    field: field.to_string(),
+                    old_value,
+                    new_value,
+                });
+            }
+        };
+
+        if let Some(priority) = priority {
+            record("priority", serde_json::json!(self.priority.to_string()), serde_json::json!(priority.to_string()));
+            self.priority = priority;
+        }
+
+        if let Some(tags) = tags {
+            let as_json = |tags: &[TaskTag]| serde_json::json!(tags.iter().map(|t| t.as_str()).collect::<Vec<_>>());
+            record("tags", as_json(&self.tags), as_json(&tags));
+            self.tags = tags;
+        }
+
+        if let Some(patch) = metadata {
+            for (key, value) in patch {
+                let old_value = self.metadata.get(&key).cloned().unwrap_or(serde_json::Value::Null);
+                record(&format!("metadata.{}", key), old_value, value.clone());
+                if value.is_null() {
+                    self.metadata.remove(&key);
+                } else {
+                    self.metadata.insert(key, value);
+                }
+            }
+        }
+
+        if let Some(max_retries) = max_retries {
+            record("max_retries", serde_json::json!(self.max_retries), serde_json::json!(max_retries));
+            self.max_retries = max_retries;
+        }
+
+        if !changes.is_empty() {
+            self.version += 1;
+        }
+
+        Ok(changes)
+    }
+
     /// 重试任务
     pub fn retry(&mut self) -> Result<(), TaskError> {
         if self.status != TaskStatus::Failed {
@@ -746,6 +1158,7 @@ impl Task {
         self.started_at = None;
         self.completed_at = None;
         self.error_message = None;
+        self.failure_category = None;
         self.retry_count += 1;
         self.version += 1;
 
@@ -782,6 +1195,168 @@ impl Task {
     }
 }
 
+/// 任务事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::EnumString, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum TaskEventType {
+    Created,
+    Started,
+    Completed,
+    Failed,
+    Retried,
+    Cancelled,
+    Updated,
+    Deleted,
+    /// 审批通过，任务进入等待队列
+    Approved,
+    /// 任务移入冷存储，不再保留在投影中
+    Archived,
+    /// 任务新增评论，不改变任务状态
+    Commented,
+    /// 与任务状态无关的记录（流水线运行、元数据模式），`task_id` 为空UUID
+    Recorded,
+}
+
+/// 记录事件携带的内容
+///
+/// 这些数据不属于任务状态，但同样要随事件流复制到热备。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+pub enum EventRecord {
+    PipelineRun(PipelineRun),
+    MetadataSchema(MetadataSchema),
+    /// 删除的元数据模式命名空间
+    MetadataSchemaDeleted(String),
+}
+
+/// 任务事件
+///
+/// 事件溯源模式下，每次状态变更都会追加一条事件，事件携带变更后的任务快照，
+/// 当前任务状态由事件按序列号重放得到。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskEvent {
+    pub sequence: u64,
+    pub task_id: TaskId,
+    pub event_type: TaskEventType,
+    pub snapshot: Option<Task>,
+    pub occurred_at: DateTime<Utc>,
+    /// 评论事件携带的评论内容
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<TaskComment>,
+    /// 记录事件携带的内容
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record: Option<EventRecord>,
+}
+
+impl TaskEvent {
+    pub fn new(event_type: TaskEventType, task: &Task) -> Self {
+        Self {
+            sequence: 0,
+            task_id: task.id,
+            event_type,
+            snapshot: Some(task.clone()),
+            occurred_at: Utc::now(),
+            comment: None,
+            record: None,
+        }
+    }
+
+    /// 任务新增评论事件
+    pub fn commented(task: &Task, comment: TaskComment) -> Self {
+        Self {
+            comment: Some(comment),
+            ..Self::new(TaskEventType::Commented, task)
+        }
+    }
+
+    /// 写入事件存储的评论事件，不携带任务快照
+    pub fn comment_added(comment: TaskComment) -> Self {
+        let task_id = comment.task_id;
+        Self {
+            event_type: TaskEventType::Commented,
+            comment: Some(comment),
+            ..Self::deleted(task_id)
+        }
+    }
+
+    /// 与任务状态无关的记录事件
+    pub fn recorded(record: EventRecord) -> Self {
+        Self {
+            event_type: TaskEventType::Recorded,
+            record: Some(record),
+            ..Self::deleted(TaskId(Uuid::nil()))
+        }
+    }
+
+    /// 根据前后状态推导事件类型
+    pub fn from_transition(previous: Option<&Task>, current: &Task) -> Self {
+        let event_type = match (previous.map(|t| t.status), current.status) {
+            (None, _) => TaskEventType::Created,
+            (Some(from), to) if from == to => TaskEventType::Updated,
+            (_, TaskStatus::Working) => TaskEventType::Started,
+            (_, TaskStatus::Completed) => TaskEventType::Completed,
+            (_, TaskStatus::Failed) => TaskEventType::Failed,
+            (_, TaskStatus::Cancelled) => TaskEventType::Cancelled,
+            (Some(TaskStatus::PendingApproval), TaskStatus::Waiting) => TaskEventType::Approved,
+            (_, TaskStatus::Waiting) => TaskEventType::Retried,
+            (_, TaskStatus::PendingApproval) => TaskEventType::Updated,
+        };
+        Self::new(event_type, current)
+    }
+
+    pub fn deleted(task_id: TaskId) -> Self {
+        Self {
+            sequence: 0,
+            task_id,
+            event_type: TaskEventType::Deleted,
+            snapshot: None,
+            occurred_at: Utc::now(),
+            comment: None,
+            record: None,
+        }
+    }
+
+    /// 任务移入冷存储事件，重放时与删除一样从投影状态中移除
+    pub fn archived(task_id: TaskId) -> Self {
+        Self {
+            event_type: TaskEventType::Archived,
+            ..Self::deleted(task_id)
+        }
+    }
+
+    /// 转换为任务历史记录
+    pub fn to_history(&self) -> Option<TaskHistory> {
+        let snapshot = self.snapshot.as_ref()?;
+        Some(TaskHistory {
+            id: self.sequence,
+            task_id: self.task_id,
+            status: snapshot.status,
+            worker_id: snapshot.worker_id.clone(),
+            changed_at: self.occurred_at,
+            details: HashMap::from([(
+                "event".to_string(),
+                serde_json::Value::String(self.event_type.to_string()),
+            )]),
+        })
+    }
+
+    /// 将事件应用到投影状态
+    pub fn apply(&self, state: &mut HashMap<TaskId, Task>) {
+        if matches!(self.event_type, TaskEventType::Commented | TaskEventType::Recorded) {
+            return;
+        }
+        match &self.snapshot {
+            Some(task) if !matches!(self.event_type, TaskEventType::Deleted | TaskEventType::Archived) => {
+                state.insert(self.task_id, task.clone());
+            }
+            _ => {
+                state.remove(&self.task_id);
+            }
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum TaskError {
     #[error("Invalid status transition from {from} to {to}")]
@@ -800,6 +1375,8 @@ pub enum TaskError {
     AlreadyAcquired,
     #[error("Concurrency conflict")]
     ConcurrencyConflict,
+    #[error("Task in {0} state cannot be modified")]
+    NotModifiable(TaskStatus),
 }
 
 /// 验证任务创建请求
@@ -813,6 +1390,14 @@ pub struct CreateTaskRequest {
     pub priority: Option<TaskPriority>,
     #[validate(custom(function = "validate_tags"))]
     pub tags: Option<Vec<String>>,
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
+    /// 有效期，必须晚于当前时间
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 并发组
+    #[validate(custom(function = "validate_concurrency_group"))]
+    pub concurrency_group: Option<String>,
+    /// 需要人工审批，审批通过前任务处于 `PendingApproval` 状态
+    pub requires_approval: bool,
 }
 
 fn validate_priority(_priority: &TaskPriority) -> Result<(), validator::ValidationError> {
@@ -831,6 +1416,17 @@ pub fn validate_tags(tags: &Vec<String>) -> Result<(), validator::ValidationErro
     Ok(())
 }
 
+/// 并发组名称：1-64个字母、数字、`_`、`-`、`.` 或 `:`
+pub fn validate_concurrency_group(group: &str) -> Result<(), validator::ValidationError> {
+    if group.is_empty() || group.len() > 64 {
+        return Err(validator::ValidationError::new("invalid_concurrency_group_length"));
+    }
+    if !group.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')) {
+        return Err(validator::ValidationError::new("invalid_concurrency_group_format"));
+    }
+    Ok(())
+}
+
 /// 验证任务完成请求
 #[derive(Debug, Validate)]
 pub struct CompleteTaskRequest {
@@ -839,6 +1435,46 @@ pub struct CompleteTaskRequest {
     pub result: Option<TaskResult>,
 }
 
+/// 验证任务字段更新请求
+#[derive(Debug, Default, Validate)]
+pub struct UpdateTaskRequest {
+    pub priority: Option<TaskPriority>,
+    #[validate(custom(function = "validate_tags"))]
+    pub tags: Option<Vec<String>>,
+    /// 元数据合并补丁，值为 null 时删除对应键
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
+    #[validate(range(max = 100))]
+    pub max_retries: Option<u32>,
+}
+
+/// 验证任务克隆请求，未设置的字段沿用源任务
+#[derive(Debug, Default, Validate)]
+pub struct CloneTaskRequest {
+    #[validate(length(min = 1, max = 512))]
+    pub work_directory: Option<String>,
+    #[validate(length(min = 1, max = 10000))]
+    pub prompt: Option<String>,
+    pub priority: Option<TaskPriority>,
+    #[validate(custom(function = "validate_tags"))]
+    pub tags: Option<Vec<String>>,
+    /// 元数据合并补丁，值为 null 时删除对应键
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
+    /// 有效期，源任务的有效期不会被复制
+    pub expires_at: Option<DateTime<Utc>>,
+    #[validate(custom(function = "validate_concurrency_group"))]
+    pub concurrency_group: Option<String>,
+    /// 需要人工审批；源任务需要审批时克隆任务总是需要审批
+    pub requires_approval: bool,
+}
+
+/// 任务字段变更记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskFieldChange {
+    pub field: String,
+    pub old_value: serde_json::Value,
+    pub new_value: serde_json::Value,
+}
+
 /// 验证任务获取请求
 #[derive(Debug, Validate)]
 pub struct AcquireTaskRequest {
@@ -846,6 +1482,9 @@ pub struct AcquireTaskRequest {
     pub work_path: String,
     #[validate(length(min = 1, max = 100))]
     pub worker_id: String,
+    /// 节点能力标签，只获取标签全部包含在其中的任务；为空时不按标签过滤
+    #[validate(custom(function = "validate_tags"))]
+    pub capabilities: Option<Vec<String>>,
 }
 
 /// 分页信息
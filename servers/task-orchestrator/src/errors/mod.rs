@@ -1,13 +1,18 @@
 use thiserror::Error;
 use axum::{
+    extract::Request,
+    middleware::Next,
     response::{IntoResponse, Response},
-    http::StatusCode,
-    Json,
+    http::{header, HeaderValue, StatusCode},
 };
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
+use object_storage::ObjectStoreError;
+use task_store::StoreError;
 
-use crate::domain::{TaskId, TaskStatus, TaskPriority, TaskIdError, TaskTagError, WorkerIdError, WorkDirectoryError, PromptError};
+use crate::domain::{TaskId, TaskStatus, TaskIdError, TaskTagError, WorkerIdError, WorkDirectoryError, PromptError, TaskError};
+use crate::utils::i18n::{self, Locale};
+use crate::utils::timestamp::{self, ApiTimestamp, TimestampFormat, TimestampStyle};
 
 /// 应用错误类型
 #[derive(Debug, Error)]
@@ -17,9 +22,21 @@ pub enum AppError {
     
     #[error("Task not found: {0}")]
     TaskNotFound(TaskId),
+
+    #[error("Pipeline not found: {0}")]
+    PipelineNotFound(String),
+
+    #[error("Pipeline run not found: {0}")]
+    PipelineRunNotFound(String),
+
+    #[error("Artifact not found: {0}")]
+    ArtifactNotFound(String),
     
     #[error("Task already acquired by another worker")]
     TaskAlreadyAcquired,
+
+    #[error("Concurrency group is busy: {0}")]
+    ConcurrencyGroupBusy(String),
     
     #[error("Concurrency conflict")]
     ConcurrencyConflict,
@@ -41,6 +58,36 @@ pub enum AppError {
     
     #[error("Service unavailable: {0}")]
     ServiceUnavailable(String),
+
+    #[error("Paused for maintenance window '{window}' until {resumes_at}")]
+    Maintenance {
+        window: String,
+        resumes_at: chrono::DateTime<chrono::Utc>,
+    },
+
+    #[error("Task queue is paused for {0}")]
+    QueuePaused(String),
+
+    #[error("Task queue is draining, new tasks are not accepted")]
+    QueueDraining,
+
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(#[from] crate::models::FilterParseError),
+
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Unsupported content encoding: {0}")]
+    UnsupportedContentEncoding(String),
+
+    #[error("Invalid compressed request body: {0}")]
+    InvalidContentEncoding(String),
+
+    #[error("Metadata does not match the schema for namespace '{namespace}': {}", errors.join("; "))]
+    MetadataSchemaViolation {
+        namespace: String,
+        errors: Vec<String>,
+    },
     
     #[error("Internal server error: {0}")]
     Internal(String),
@@ -180,6 +227,7 @@ pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<ApiError>,
+    #[serde(serialize_with = "timestamp::serialize_utc")]
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
@@ -203,101 +251,310 @@ impl<T> ApiResponse<T> {
     }
 }
 
+impl AppError {
+    /// 按语言生成错误消息
+    pub fn localized_message(&self, locale: Locale) -> String {
+        let t = |key: &str, args: &[String]| i18n::translate(locale, key, args);
+        match self {
+            AppError::Validation(err) => err.localized_message(locale),
+            AppError::TaskNotFound(task_id) => t("error.task_not_found", &[task_id.to_string()]),
+            AppError::PipelineNotFound(name) => t("error.pipeline_not_found", std::slice::from_ref(name)),
+            AppError::PipelineRunNotFound(run_id) => t("error.pipeline_run_not_found", std::slice::from_ref(run_id)),
+            AppError::ArtifactNotFound(key) => t("error.artifact_not_found", std::slice::from_ref(key)),
+            AppError::TaskAlreadyAcquired => t("error.task_already_acquired", &[]),
+            AppError::ConcurrencyGroupBusy(group) => t("error.concurrency_group_busy", std::slice::from_ref(group)),
+            AppError::ConcurrencyConflict => t("error.concurrency_conflict", &[]),
+            AppError::Database(err) => t("error.database", &[err.to_string()]),
+            AppError::Configuration(err) => t("error.configuration", &[err.to_string()]),
+            AppError::Authentication(err) => t("error.authentication", &[err.clone()]),
+            AppError::Authorization(err) => t("error.authorization", &[err.clone()]),
+            AppError::RateLimitExceeded => t("error.rate_limit_exceeded", &[]),
+            AppError::ServiceUnavailable(err) => t("error.service_unavailable", &[err.clone()]),
+            AppError::Maintenance { window, resumes_at } => {
+                t("error.maintenance", &[window.clone(), resumes_at.to_rfc3339()])
+            }
+            AppError::QueuePaused(scope) => t("error.queue_paused", std::slice::from_ref(scope)),
+            AppError::QueueDraining => t("error.queue_draining", &[]),
+            AppError::InvalidFilter(err) => {
+                t("error.invalid_filter", &[err.position.to_string(), err.token.clone(), err.message.clone()])
+            }
+            AppError::PayloadTooLarge(err) => t("error.payload_too_large", std::slice::from_ref(err)),
+            AppError::UnsupportedContentEncoding(encoding) => {
+                t("error.unsupported_content_encoding", std::slice::from_ref(encoding))
+            }
+            AppError::InvalidContentEncoding(err) => t("error.invalid_content_encoding", std::slice::from_ref(err)),
+            AppError::MetadataSchemaViolation { namespace, errors } => {
+                t("error.metadata_schema_violation", &[namespace.clone(), errors.join("; ")])
+            }
+            AppError::Internal(err) => t("error.internal", &[err.clone()]),
+            AppError::InvalidTaskId(err) => t("error.invalid_task_id", &[err.to_string()]),
+            AppError::DateParseError(err) => t("error.date_parse", &[err.to_string()]),
+            AppError::Anyhow(err) => t("error.anyhow", &[err.to_string()]),
+            AppError::TaskStatus(err) => t("error.task_status", &[err.to_string()]),
+            AppError::TaskPriority(err) => t("error.task_priority", &[err.to_string()]),
+            AppError::Task(err) => {
+                let detail = match err {
+                    TaskError::InvalidStatusTransition { from, to } => {
+                        t("task.invalid_status_transition", &[from.to_string(), to.to_string()])
+                    }
+                    TaskError::MaxRetriesExceeded { retry_count, max_retries } => {
+                        t("task.max_retries_exceeded", &[retry_count.to_string(), max_retries.to_string()])
+                    }
+                    TaskError::NotFound(task_id) => t("task.not_found", &[task_id.to_string()]),
+                    TaskError::AlreadyAcquired => t("task.already_acquired", &[]),
+                    TaskError::ConcurrencyConflict => t("task.concurrency_conflict", &[]),
+                    TaskError::NotModifiable(status) => t("task.not_modifiable", &[status.to_string()]),
+                };
+                t("error.task", &[detail])
+            }
+            AppError::TaskTag(err) => {
+                let key = match err {
+                    TaskTagError::EmptyTag => "tag.empty",
+                    TaskTagError::TagTooLong => "tag.too_long",
+                    TaskTagError::InvalidTagFormat => "tag.invalid_format",
+                };
+                t("error.task_tag", &[t(key, &[])])
+            }
+            AppError::WorkerId(err) => {
+                let key = match err {
+                    WorkerIdError::EmptyWorkerId => "worker_id.empty",
+                    WorkerIdError::WorkerIdTooLong => "worker_id.too_long",
+                };
+                t("error.worker_id", &[t(key, &[])])
+            }
+            AppError::WorkDirectory(err) => {
+                let key = match err {
+                    WorkDirectoryError::EmptyPath => "work_directory.empty",
+                    WorkDirectoryError::PathTooLong => "work_directory.too_long",
+                    WorkDirectoryError::NotAbsolutePath => "work_directory.not_absolute",
+                    WorkDirectoryError::InvalidPath => "work_directory.invalid",
+                };
+                t("error.work_directory", &[t(key, &[])])
+            }
+            AppError::Prompt(err) => {
+                let key = match err {
+                    PromptError::EmptyPrompt => "prompt.empty",
+                    PromptError::PromptTooLong => "prompt.too_long",
+                };
+                t("error.prompt", &[t(key, &[])])
+            }
+            AppError::Migration(err) => t("error.migration", &[err.to_string()]),
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let locale = current_request_context().map(|ctx| ctx.locale).unwrap_or_default();
+        let message = self.localized_message(locale);
+
         let (status, api_error) = match self {
-            AppError::Validation(err) => (
-                StatusCode::BAD_REQUEST,
-                ApiError::validation(err.to_string()),
-            ),
-            AppError::TaskNotFound(task_id) => (
-                StatusCode::NOT_FOUND,
-                ApiError::not_found(format!("Task not found: {}", task_id)),
-            ),
-            AppError::TaskAlreadyAcquired => (
-                StatusCode::CONFLICT,
-                ApiError::conflict("Task already acquired by another worker".to_string()),
-            ),
-            AppError::ConcurrencyConflict => (
-                StatusCode::CONFLICT,
-                ApiError::conflict("Concurrency conflict".to_string()),
-            ),
-            AppError::Database(err) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ApiError::internal_error(format!("Database error: {}", err)),
-            ),
-            AppError::Configuration(err) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ApiError::internal_error(format!("Configuration error: {}", err)),
-            ),
-            AppError::Authentication(err) => (
-                StatusCode::UNAUTHORIZED,
-                ApiError::unauthorized(err),
-            ),
-            AppError::Authorization(err) => (
-                StatusCode::FORBIDDEN,
-                ApiError::forbidden(err),
-            ),
-            AppError::RateLimitExceeded => (
-                StatusCode::TOO_MANY_REQUESTS,
-                ApiError::rate_limit_exceeded(),
-            ),
-            AppError::ServiceUnavailable(err) => (
-                StatusCode::SERVICE_UNAVAILABLE,
-                ApiError::service_unavailable(err),
-            ),
-            AppError::Internal(err) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ApiError::internal_error(err),
-            ),
-            AppError::InvalidTaskId(err) => (
-                StatusCode::BAD_REQUEST,
-                ApiError::validation(format!("Invalid task ID: {}", err)),
-            ),
-            AppError::DateParseError(err) => (
-                StatusCode::BAD_REQUEST,
-                ApiError::validation(format!("Date parsing error: {}", err)),
-            ),
-            AppError::Anyhow(err) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ApiError::internal_error(format!("Internal error: {}", err)),
-            ),
-            AppError::TaskStatus(err) => (
-                StatusCode::BAD_REQUEST,
-                ApiError::validation(format!("Invalid task status: {}", err)),
-            ),
-            AppError::TaskPriority(err) => (
-                StatusCode::BAD_REQUEST,
-                ApiError::validation(format!("Invalid task priority: {}", err)),
-            ),
-            AppError::Task(err) => (
-                StatusCode::BAD_REQUEST,
-                ApiError::validation(format!("Task error: {}", err)),
-            ),
-            AppError::TaskTag(err) => (
-                StatusCode::BAD_REQUEST,
-                ApiError::validation(format!("Task tag error: {}", err)),
-            ),
-            AppError::WorkerId(err) => (
-                StatusCode::BAD_REQUEST,
-                ApiError::validation(format!("Worker ID error: {}", err)),
-            ),
-            AppError::WorkDirectory(err) => (
-                StatusCode::BAD_REQUEST,
-                ApiError::validation(format!("Work directory error: {}", err)),
-            ),
-            AppError::Prompt(err) => (
-                StatusCode::BAD_REQUEST,
-                ApiError::validation(format!("Prompt error: {}", err)),
-            ),
-            AppError::Migration(err) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ApiError::internal_error(format!("Database migration error: {}", err)),
+            AppError::Validation(_)
+            | AppError::InvalidTaskId(_)
+            | AppError::DateParseError(_)
+            | AppError::TaskStatus(_)
+            | AppError::TaskPriority(_)
+            | AppError::Task(_)
+            | AppError::TaskTag(_)
+            | AppError::WorkerId(_)
+            | AppError::WorkDirectory(_)
+            | AppError::Prompt(_) => (StatusCode::BAD_REQUEST, ApiError::validation(message)),
+            AppError::TaskNotFound(_)
+            | AppError::PipelineNotFound(_)
+            | AppError::PipelineRunNotFound(_)
+            | AppError::ArtifactNotFound(_) => {
+                (StatusCode::NOT_FOUND, ApiError::not_found(message))
+            }
+            AppError::TaskAlreadyAcquired | AppError::ConcurrencyGroupBusy(_) | AppError::ConcurrencyConflict => {
+                (StatusCode::CONFLICT, ApiError::conflict(message))
+            }
+            AppError::Authentication(_) => (StatusCode::UNAUTHORIZED, ApiError::unauthorized(message)),
+            AppError::Authorization(_) => (StatusCode::FORBIDDEN, ApiError::forbidden(message)),
+            AppError::RateLimitExceeded => (StatusCode::TOO_MANY_REQUESTS, ApiError::new("RATE_LIMIT_EXCEEDED".to_string(), message)),
+            AppError::ServiceUnavailable(_) => (StatusCode::SERVICE_UNAVAILABLE, ApiError::service_unavailable(message)),
+            AppError::Maintenance { window, resumes_at } => {
+                let details = HashMap::from([
+                    ("window".to_string(), serde_json::json!(window)),
+                    ("resumes_at".to_string(), serde_json::json!(resumes_at)),
+                ]);
+                let error = ApiError::new("MAINTENANCE".to_string(), message).with_details(details);
+                (StatusCode::SERVICE_UNAVAILABLE, error)
+            }
+            AppError::QueuePaused(scope) => {
+                let details = HashMap::from([("scope".to_string(), serde_json::json!(scope))]);
+                let error = ApiError::new("QUEUE_PAUSED".to_string(), message).with_details(details);
+                (StatusCode::SERVICE_UNAVAILABLE, error)
+            }
+            AppError::QueueDraining => {
+                (StatusCode::SERVICE_UNAVAILABLE, ApiError::new("QUEUE_DRAINING".to_string(), message))
+            }
+            AppError::InvalidFilter(err) => {
+                let details = HashMap::from([
+                    ("position".to_string(), serde_json::json!(err.position)),
+                    ("token".to_string(), serde_json::json!(err.token)),
+                ]);
+                let error = ApiError::new("INVALID_FILTER".to_string(), message).with_details(details);
+                (StatusCode::BAD_REQUEST, error)
+            }
+            AppError::PayloadTooLarge(_) => {
+                (StatusCode::PAYLOAD_TOO_LARGE, ApiError::new("PAYLOAD_TOO_LARGE".to_string(), message))
+            }
+            AppError::UnsupportedContentEncoding(_) => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                ApiError::new("UNSUPPORTED_CONTENT_ENCODING".to_string(), message),
             ),
+            AppError::InvalidContentEncoding(_) => {
+                (StatusCode::BAD_REQUEST, ApiError::new("INVALID_CONTENT_ENCODING".to_string(), message))
+            }
+            AppError::MetadataSchemaViolation { namespace, errors } => {
+                let details = HashMap::from([
+                    ("namespace".to_string(), serde_json::json!(namespace)),
+                    ("errors".to_string(), serde_json::json!(errors)),
+                ]);
+                let error = ApiError::new("METADATA_SCHEMA_VIOLATION".to_string(), message).with_details(details);
+                (StatusCode::UNPROCESSABLE_ENTITY, error)
+            }
+            AppError::Database(_)
+            | AppError::Configuration(_)
+            | AppError::Internal(_)
+            | AppError::Anyhow(_)
+            | AppError::Migration(_) => (StatusCode::INTERNAL_SERVER_ERROR, ApiError::internal_error(message)),
         };
 
-        let response = ApiResponse::<()>::error(api_error);
-        (status, Json(response)).into_response()
+        problem_from_api_error(status, api_error).into_response()
+    }
+}
+
+pub use problem_details::{CORRELATION_ID_HEADER, TRACE_ID_HEADER};
+
+/// 请求上下文（追踪ID、请求路径和协商的语言与时间格式）
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub trace_id: String,
+    /// 客户端通过 `x-correlation-id` 传入的关联ID
+    pub correlation_id: Option<String>,
+    pub instance: String,
+    pub locale: Locale,
+    /// 未协商时为 `None`，响应时间字段保持原有格式
+    pub timestamp_format: Option<TimestampFormat>,
+}
+
+tokio::task_local! {
+    pub(crate) static REQUEST_CONTEXT: RequestContext;
+}
+
+/// 获取当前请求的上下文
+pub fn current_request_context() -> Option<RequestContext> {
+    REQUEST_CONTEXT.try_with(|ctx| ctx.clone()).ok()
+}
+
+/// 从 `lang` 查询参数或 `Accept-Language` 请求头协商语言
+fn negotiate_locale(request: &Request) -> Locale {
+    let from_query = request.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "lang")
+            .and_then(|(_, value)| Locale::from_tag(value))
+    });
+
+    from_query
+        .or_else(|| {
+            request
+                .headers()
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(Locale::from_accept_language)
+        })
+        .unwrap_or_default()
+}
+
+/// 解码后的查询参数值
+fn query_param(request: &Request, name: &str) -> Option<String> {
+    let query = request.uri().query()?;
+    url::form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+/// 从查询参数或请求头协商响应时间格式，查询参数优先
+fn negotiate_timestamp_format(request: &Request) -> Option<TimestampFormat> {
+    let header = |name: &str| request.headers().get(name).and_then(|v| v.to_str().ok());
+    let style = query_param(request, timestamp::TIME_FORMAT_PARAM)
+        .and_then(|value| TimestampStyle::parse(&value))
+        .or_else(|| header(timestamp::TIME_FORMAT_HEADER).and_then(TimestampStyle::parse));
+    // 未编码的 `+08:00` 在查询参数中会被解码为空格开头
+    let offset = query_param(request, timestamp::TIMEZONE_PARAM)
+        .and_then(|value| timestamp::parse_offset(&value.replacen(' ', "+", 1)))
+        .or_else(|| header(timestamp::TIMEZONE_HEADER).and_then(timestamp::parse_offset));
+    TimestampFormat::negotiate(style, offset)
+}
+
+/// 追踪ID中间件
+///
+/// 沿用调用方传入的 `x-trace-id`（或 `x-request-id`），否则生成新的追踪ID；
+/// 错误响应体和响应头都会携带该追踪ID。调用方传入的 `x-correlation-id` 原样返回，
+/// 并与追踪ID一起记录在本次请求的日志中。同时协商本次请求的错误消息语言和响应时间格式。
+pub async fn trace_id_middleware(request: Request, next: Next) -> Response {
+    let trace_id = problem_details::incoming_trace_id(request.headers())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let correlation_id = request
+        .headers()
+        .get(CORRELATION_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(problem_details::parse_correlation_id);
+
+    let span = tracing::info_span!(
+        "request",
+        trace_id = %trace_id,
+        correlation_id = correlation_id.as_deref().unwrap_or("-"),
+    );
+    let context = RequestContext {
+        trace_id: trace_id.clone(),
+        correlation_id: correlation_id.clone(),
+        instance: request.uri().path().to_string(),
+        locale: negotiate_locale(&request),
+        timestamp_format: negotiate_timestamp_format(&request),
+    };
+
+    let mut response = REQUEST_CONTEXT
+        .scope(context, tracing::Instrument::instrument(next.run(request), span))
+        .await;
+    if let Ok(value) = HeaderValue::from_str(&trace_id) {
+        response.headers_mut().insert(TRACE_ID_HEADER, value);
+    }
+    if let Some(value) = correlation_id.and_then(|id| HeaderValue::from_str(&id).ok()) {
+        response.headers_mut().insert(CORRELATION_ID_HEADER, value);
+    }
+    response
+}
+
+/// RFC 7807 问题详情，时间戳按请求协商的格式输出
+pub type ProblemDetails = problem_details::ProblemDetails<ApiTimestamp>;
+
+/// 按当前请求上下文创建问题详情：沿用追踪ID和关联ID，标题按协商的语言本地化
+pub fn request_problem(status: StatusCode, code: &str, detail: String) -> ProblemDetails {
+    let problem = ProblemDetails::new(status, code, detail);
+    let Some(context) = current_request_context() else {
+        return problem;
+    };
+    let title = i18n::lookup(context.locale, &format!("title.{}", code))
+        .or_else(|| status.canonical_reason())
+        .unwrap_or("Error");
+    problem
+        .with_title(title)
+        .with_instance(context.instance)
+        .with_trace_id(context.trace_id)
+        .with_correlation_id(context.correlation_id)
+        .with_language(context.locale.tag())
+}
+
+/// 由API错误创建问题详情
+fn problem_from_api_error(status: StatusCode, error: ApiError) -> ProblemDetails {
+    let problem = request_problem(status, &error.code, error.message);
+    match error.details {
+        Some(details) => problem.with_details(serde_json::Value::Object(details.into_iter().collect())),
+        None => problem,
     }
 }
 
@@ -330,6 +587,27 @@ pub struct ValidationErrorDetail {
 }
 
 impl ValidationError {
+    /// 按语言生成校验错误消息
+    pub fn localized_message(&self, locale: Locale) -> String {
+        let (key, args) = match self {
+            ValidationError::InvalidWorkDirectory(v) => ("validation.invalid_work_directory", vec![v.clone()]),
+            ValidationError::InvalidPrompt(v) => ("validation.invalid_prompt", vec![v.clone()]),
+            ValidationError::InvalidPriority(v) => ("validation.invalid_priority", vec![v.clone()]),
+            ValidationError::InvalidTags(v) => ("validation.invalid_tags", vec![v.clone()]),
+            ValidationError::InvalidWorkerId(v) => ("validation.invalid_worker_id", vec![v.clone()]),
+            ValidationError::InvalidValidation(v) => ("validation.invalid_validation", vec![v.clone()]),
+            ValidationError::MissingField(v) => ("validation.missing_field", vec![v.clone()]),
+            ValidationError::FieldTooLong(v) => ("validation.field_too_long", vec![v.clone()]),
+            ValidationError::InvalidStatusTransition { from, to } => {
+                ("validation.invalid_status_transition", vec![from.to_string(), to.to_string()])
+            }
+            ValidationError::MaxRetriesExceeded { current, max } => {
+                ("validation.max_retries_exceeded", vec![current.to_string(), max.to_string()])
+            }
+        };
+        i18n::translate(locale, key, &args)
+    }
+
     pub fn invalid_validation(messages: String) -> Self {
         ValidationError::InvalidValidation(messages)
     }
@@ -441,6 +719,31 @@ impl From<QueueError> for AppError {
     }
 }
 
+impl From<StoreError> for AppError {
+    fn from(err: StoreError) -> Self {
+        match err {
+            StoreError::Conflict(_) => AppError::ConcurrencyConflict,
+            StoreError::NotFound(_) | StoreError::Backend(_) => AppError::Internal(err.to_string()),
+        }
+    }
+}
+
+impl From<ObjectStoreError> for AppError {
+    fn from(err: ObjectStoreError) -> Self {
+        match err {
+            ObjectStoreError::Request(_) | ObjectStoreError::Status { .. } => AppError::ServiceUnavailable(err.to_string()),
+            ObjectStoreError::Config(_) | ObjectStoreError::Protocol(_) => AppError::Internal(err.to_string()),
+        }
+    }
+}
+
+/// 存储后端（如SQLite锁管理器）内部的应用错误统一转为后端错误
+impl From<AppError> for StoreError {
+    fn from(err: AppError) -> Self {
+        StoreError::backend(err)
+    }
+}
+
 /// 并发控制错误
 #[derive(Debug, Error)]
 pub enum ConcurrencyError {
@@ -474,4 +777,98 @@ impl From<ConcurrencyError> for AppError {
             }
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use problem_details::{ERROR_DOCS_BASE_URL, PROBLEM_JSON_CONTENT_TYPE};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_error_rendered_as_problem_json() {
+        let app = Router::new()
+            .route("/api/v1/tasks/:id", get(|| async { Err::<(), _>(AppError::TaskNotFound(TaskId::default())) }))
+            .layer(axum::middleware::from_fn(trace_id_middleware));
+
+        let request = axum::http::Request::builder()
+            .uri("/api/v1/tasks/abc")
+            .header(TRACE_ID_HEADER, "trace-123")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON_CONTENT_TYPE);
+        assert_eq!(response.headers()[TRACE_ID_HEADER], "trace-123");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["status"], 404);
+        assert_eq!(problem["code"], "NOT_FOUND");
+        assert_eq!(problem["title"], "Not Found");
+        assert_eq!(problem["trace_id"], "trace-123");
+        assert_eq!(problem["instance"], "/api/v1/tasks/abc");
+        assert_eq!(problem["type"], format!("{}#not_found", ERROR_DOCS_BASE_URL));
+        assert!(problem.get("correlation_id").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_correlation_id_echoed() {
+        let app = Router::new()
+            .route("/api/v1/tasks/:id", get(|| async { Err::<(), _>(AppError::TaskNotFound(TaskId::default())) }))
+            .route("/health", get(|| async { current_request_context().and_then(|ctx| ctx.correlation_id).unwrap_or_default() }))
+            .layer(axum::middleware::from_fn(trace_id_middleware));
+        let request = |uri: &str, correlation_id: &str| {
+            axum::http::Request::builder()
+                .uri(uri)
+                .header(CORRELATION_ID_HEADER, correlation_id)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(request("/health", "order-42")).await.unwrap();
+        assert_eq!(response.headers()[CORRELATION_ID_HEADER], "order-42");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"order-42");
+
+        let response = app.clone().oneshot(request("/api/v1/tasks/abc", "order-42")).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["correlation_id"], "order-42");
+
+        // 不合法的关联ID被忽略
+        let response = app.oneshot(request("/health", "has space")).await.unwrap();
+        assert!(response.headers().get(CORRELATION_ID_HEADER).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_problem_localized_by_accept_language() {
+        let app = Router::new()
+            .route("/api/v1/tasks", get(|| async { Err::<(), _>(AppError::WorkDirectory(WorkDirectoryError::NotAbsolutePath)) }))
+            .layer(axum::middleware::from_fn(trace_id_middleware));
+
+        let request = axum::http::Request::builder()
+            .uri("/api/v1/tasks")
+            .header(header::ACCEPT_LANGUAGE, "zh-CN,zh;q=0.9,en;q=0.8")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_LANGUAGE], "zh-CN");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["title"], "参数校验失败");
+        assert_eq!(problem["detail"], "工作目录错误：工作目录必须是绝对路径");
+
+        // 查询参数优先于请求头
+        let request = axum::http::Request::builder()
+            .uri("/api/v1/tasks?lang=en")
+            .header(header::ACCEPT_LANGUAGE, "zh-CN")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["detail"], "Work directory error: Work directory must be an absolute path");
+    }
+}
@@ -0,0 +1,92 @@
+//! # 压缩请求体
+//!
+//! 请求体较大（如元数据、执行结果或流水线定义较多）时客户端可以用 `Content-Encoding: gzip` 或 `deflate` 压缩请求体，
+//! 中间件在进入处理器之前用 `request-decompression` 解压，并去掉 `Content-Encoding` 头。解压时同时限制解压后大小和压缩比，超过任一限制立即停止解压并返回 `413`，
+//! 避免压缩炸弹占满内存。
+//!
+//! 产物上传（`PUT .../artifacts/:name`）的请求体按原样保存，不解压。`deflate` 同时接受带zlib头
+//! （RFC 9110）和不带头的原始deflate数据。
+
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use request_decompression::{decompress_request, DecompressError, Decompressor};
+
+use super::ApiState;
+use crate::config::RequestDecompressionConfig;
+use crate::errors::AppError;
+
+/// 按配置创建请求体解压器
+pub fn request_decompressor(config: &RequestDecompressionConfig) -> Decompressor {
+    Decompressor::new(config.max_decompressed_bytes, config.max_ratio)
+}
+
+/// 解压中间件
+pub async fn decompression_middleware(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    match decompress(state.request_decompressor.as_ref(), request).await {
+        Ok(request) => next.run(request).await,
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn decompress(decompressor: Option<&Decompressor>, request: Request) -> Result<Request, AppError> {
+    if request.method() == Method::PUT && request.uri().path().contains("/artifacts/") {
+        return Ok(request);
+    }
+    decompress_request(decompressor, request).await.map_err(|e| match e {
+        DecompressError::Unsupported(encoding) => AppError::UnsupportedContentEncoding(encoding),
+        DecompressError::Corrupt(message) => AppError::InvalidContentEncoding(message),
+        DecompressError::TooLarge(message) => AppError::PayloadTooLarge(message),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::header;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn request(method: Method, uri: &str, body: Vec<u8>) -> Request {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_decompress_maps_errors_and_skips_artifacts() {
+        let decompressor = request_decompressor(&RequestDecompressionConfig {
+            enabled: true,
+            max_decompressed_bytes: 1024,
+            max_ratio: 10,
+        });
+        let body = br#"{"prompt":"hello"}"#;
+        let decompressed = decompress(Some(&decompressor), request(Method::POST, "/api/v1/tasks", gzip(body))).await.unwrap();
+        assert_eq!(&axum::body::to_bytes(decompressed.into_body(), 1024).await.unwrap()[..], body);
+
+        let result = decompress(Some(&decompressor), request(Method::POST, "/api/v1/tasks", b"not gzip".to_vec())).await;
+        assert!(matches!(result, Err(AppError::InvalidContentEncoding(_))));
+        let result = decompress(Some(&decompressor), request(Method::POST, "/api/v1/tasks", gzip(&[b'a'; 4096]))).await;
+        assert!(matches!(result, Err(AppError::PayloadTooLarge(_))));
+
+        // 未启用时拒绝压缩请求体，产物上传不解压
+        let result = decompress(None, request(Method::POST, "/api/v1/tasks", gzip(body))).await;
+        assert!(matches!(result, Err(AppError::UnsupportedContentEncoding(_))));
+        let upload = decompress(None, request(Method::PUT, "/api/v1/tasks/1/artifacts/log.gz", gzip(body))).await.unwrap();
+        assert_eq!(upload.headers()[header::CONTENT_ENCODING], "gzip");
+    }
+}
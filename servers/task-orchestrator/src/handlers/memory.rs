@@ -0,0 +1,61 @@
+//! # 内存压力下的请求体限制
+//!
+//! 常驻内存超过硬上限时，拒绝声明长度超过上限的请求体以及长度未知的分块请求体，返回 `503` 和 `Retry-After`，
+//! 在读取请求体之前就拒绝，避免继续分配内存。
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::ApiState;
+use crate::errors::AppError;
+
+/// 请求体声明的长度，分块传输时长度未知，视为无限大
+fn declared_body_length(headers: &HeaderMap) -> Option<u64> {
+    if let Some(length) = headers.get(header::CONTENT_LENGTH) {
+        return length.to_str().ok()?.parse().ok();
+    }
+    headers.contains_key(header::TRANSFER_ENCODING).then_some(u64::MAX)
+}
+
+/// 内存压力下拒绝大请求体的中间件
+pub async fn memory_pressure_middleware(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let Some(watchdog) = &state.memory_watchdog else {
+        return next.run(request).await;
+    };
+    let Some(length) = declared_body_length(request.headers()) else {
+        return next.run(request).await;
+    };
+    if !watchdog.should_reject_body(length) {
+        return next.run(request).await;
+    }
+
+    tracing::warn!(
+        rss_bytes = watchdog.rss_bytes(),
+        path = %request.uri().path(),
+        "Rejected request body under memory pressure"
+    );
+    let mut response =
+        AppError::ServiceUnavailable("Server is under memory pressure, large request bodies are rejected".to_string())
+            .into_response();
+    response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from_static("30"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_declared_body_length() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(declared_body_length(&headers), None);
+        headers.insert(header::TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
+        assert_eq!(declared_body_length(&headers), Some(u64::MAX));
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from_static("42"));
+        assert_eq!(declared_body_length(&headers), Some(42));
+    }
+}
@@ -5,21 +5,54 @@ use axum::{
     Router,
 };
 use serde::{Deserialize, Serialize};
+use tower_http::catch_panic::CatchPanicLayer;
 use std::sync::Arc;
 use validator::Validate;
 
+use crate::config::MonitoringConfig;
 use crate::domain::{TaskId, TaskStatus, TaskPriority};
-use crate::services::TaskService;
-use crate::domain::{CreateTaskRequest, CompleteTaskRequest, AcquireTaskRequest};
-use crate::models::TaskFilter;
-use crate::errors::{AppError, AppResult, ApiResponse};
+use crate::services::{TaskService, LeaderElector, ClusterStatus, ArtifactStore, QueueControlStatus, StandbyReplicator, StandbyStatus};
+use crate::infrastructure::{EventStore, REPLICATION_EVENTS_PATH};
+use crate::domain::{CreateTaskRequest, CompleteTaskRequest, AcquireTaskRequest, UpdateTaskRequest, CloneTaskRequest};
+use crate::models::{FilterExpr, TaskFilter, TaskSort};
+use crate::errors::{AppError, AppResult, ApiResponse, ProblemDetails, request_problem, trace_id_middleware, current_request_context};
+use crate::utils::i18n;
+use crate::utils::timestamp::ApiTimestamp;
 use crate::utils::logging::StructuredLogger;
+use crate::utils::{MetricsCollector, Redactor};
+use memory_watchdog::MemoryWatchdog;
+
+pub mod v2;
+pub mod recording;
+pub mod quota;
+pub mod decompression;
+pub mod memory;
+pub mod prometheus;
+pub mod standby;
 
 /// API处理器状态
 #[derive(Clone)]
 pub struct ApiState {
     pub task_service: Arc<TaskService>,
     pub logger: StructuredLogger,
+    pub leader_elector: Option<Arc<LeaderElector>>,
+    pub artifact_store: Option<Arc<ArtifactStore>>,
+    pub request_recorder: Option<Arc<recording::RequestRecorder>>,
+    pub request_quotas: Option<Arc<crate::utils::concurrency::RequestQuotas>>,
+    /// 压缩请求体解压器，未启用时拒绝带 `Content-Encoding` 的请求体
+    pub request_decompressor: Option<request_decompression::Decompressor>,
+    /// Prometheus指标收集器，未设置时不记录请求耗时
+    pub metrics: Option<Arc<MetricsCollector>>,
+    /// Prometheus端点路径，未设置时不在API监听器上提供
+    pub prometheus_endpoint: Option<String>,
+    /// 内存自监控，内存超过硬上限时拒绝大请求体
+    pub memory_watchdog: Option<Arc<MemoryWatchdog>>,
+    /// 事件日志，开启事件溯源时通过复制接口提供给备机
+    pub event_store: Option<Arc<dyn EventStore>>,
+    /// 热备复制器，以备机身份启动时设置
+    pub standby: Option<Arc<StandbyReplicator>>,
+    /// 复制接口和提升接口的共享令牌
+    pub replication_token: Option<String>,
 }
 
 /// 任务创建请求
@@ -36,6 +69,23 @@ pub struct ApiCreateTaskRequest {
     
     #[validate(custom(function = "crate::domain::validate_tags"))]
     pub tags: Option<Vec<String>>,
+
+    pub metadata: Option<std::collections::HashMap<String, serde_json::Value>>,
+
+    /// 有效期（RFC3339），超过后仍未开始的任务会被取消，与 `ttl_seconds` 二选一
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// 有效期（自创建起的秒数）
+    #[validate(range(min = 1))]
+    pub ttl_seconds: Option<u64>,
+
+    /// 并发组，同一组内同时只有一个任务在执行
+    #[validate(custom(function = "crate::domain::validate_concurrency_group"))]
+    pub concurrency_group: Option<String>,
+
+    /// 需要人工审批，审批通过前不会被获取
+    #[serde(default)]
+    pub requires_approval: bool,
 }
 
 /// 任务创建响应
@@ -46,7 +96,69 @@ pub struct ApiCreateTaskResponse {
     pub priority: String,
     pub work_directory: String,
     pub tags: Vec<String>,
-    pub created_at: String,
+    pub created_at: ApiTimestamp,
+}
+
+impl From<&crate::domain::Task> for ApiCreateTaskResponse {
+    fn from(task: &crate::domain::Task) -> Self {
+        Self {
+            task_id: task.id.to_string(),
+            status: task.status.to_string(),
+            priority: task.priority.to_string(),
+            work_directory: task.work_directory.to_string(),
+            tags: task.tags.iter().map(|t| t.to_string()).collect(),
+            created_at: task.created_at.into(),
+        }
+    }
+}
+
+/// 流水线运行请求
+#[derive(Debug, Deserialize, Validate)]
+pub struct ApiRunPipelineRequest {
+    #[validate(length(min = 1, max = 512))]
+    pub work_directory: String,
+
+    /// 填入步骤提示词中 `{{input}}` 的输入
+    #[validate(length(max = 10000))]
+    pub input: Option<String>,
+
+    #[validate(custom(function = "validate_priority_string"))]
+    pub priority: Option<String>,
+
+    #[validate(custom(function = "crate::domain::validate_tags"))]
+    pub tags: Option<Vec<String>>,
+
+    pub metadata: Option<std::collections::HashMap<String, serde_json::Value>>,
+}
+
+/// 流水线运行响应
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiPipelineRunResponse {
+    pub run_id: String,
+    pub pipeline: String,
+    pub steps: usize,
+    pub status: crate::domain::PipelineRunStatus,
+    /// 条件不成立而跳过的步骤
+    pub skipped: Vec<String>,
+    /// 第一步的任务，所有步骤都被跳过时为空
+    pub task: Option<ApiCreateTaskResponse>,
+}
+
+/// 任务创建查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct ApiCreateTaskQuery {
+    /// 只执行校验和策略检查，不创建任务
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// 任务创建模拟响应
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiTaskSimulation {
+    pub dry_run: bool,
+    pub task: ApiTaskDetail,
+    pub warnings: Vec<String>,
+    pub matching_workers: Vec<String>,
 }
 
 /// 任务获取请求
@@ -54,6 +166,8 @@ pub struct ApiCreateTaskResponse {
 pub struct ApiGetTaskRequest {
     pub work_path: String,
     pub worker_id: String,
+    /// 逗号分隔的节点能力标签
+    pub capabilities: Option<String>,
 }
 
 /// 任务获取响应
@@ -84,6 +198,37 @@ pub struct ApiTaskResult {
     pub details: serde_json::Value,
     #[serde(default)]
     pub duration: Option<u64>,
+    /// 失败类别：timeout / oom / tool_error / validation / upstream / unknown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_category: Option<String>,
+}
+
+/// 单次执行记录
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiTaskAttempt {
+    pub attempt: u32,
+    pub status: String,
+    pub worker_id: Option<String>,
+    pub finished_at: ApiTimestamp,
+    pub result: ApiTaskResult,
+}
+
+/// 执行差异查询参数
+#[derive(Debug, Deserialize)]
+pub struct ApiAttemptDiffQuery {
+    pub from: u32,
+    pub to: u32,
+}
+
+/// 容量预测查询参数
+#[derive(Debug, Deserialize)]
+pub struct ApiCapacityForecastQuery {
+    /// 统计窗口（秒）
+    pub window: Option<u64>,
+    /// 覆盖当前工作节点数
+    pub workers: Option<u32>,
+    /// 模拟增加的工作节点数，逗号分隔
+    pub add: Option<String>,
 }
 
 /// 任务详情响应
@@ -96,14 +241,66 @@ pub struct ApiTaskDetail {
     pub tags: Vec<String>,
     pub status: String,
     pub worker_id: Option<String>,
-    pub created_at: String,
-    pub started_at: Option<String>,
-    pub completed_at: Option<String>,
+    pub created_at: ApiTimestamp,
+    pub started_at: Option<ApiTimestamp>,
+    pub completed_at: Option<ApiTimestamp>,
     pub result: Option<ApiTaskResult>,
     pub error_message: Option<String>,
     pub retry_count: u32,
     pub max_retries: u32,
     pub metadata: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<ApiTimestamp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancel_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_category: Option<String>,
+    /// 失败后退避中的任务在该时间之前不会被获取
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<ApiTimestamp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency_group: Option<String>,
+    /// 仅任务详情接口返回
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<ApiTaskComment>,
+}
+
+/// 等待任务完成查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct ApiWaitTaskQuery {
+    /// 最长等待时间（秒），默认30秒，不超过配置的 `max_wait_timeout`
+    pub timeout: Option<u64>,
+}
+
+/// 等待任务完成响应
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiWaitTaskResponse {
+    /// 任务是否已进入终态，为 `false` 表示等待超时
+    pub completed: bool,
+    pub task: ApiTaskDetail,
+}
+
+/// 添加任务评论请求
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ApiAddCommentRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub author: String,
+
+    #[validate(length(min = 1, max = 10000))]
+    pub text: String,
+
+    pub data: Option<serde_json::Value>,
+}
+
+/// 任务评论
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiTaskComment {
+    pub id: u64,
+    pub author: String,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    pub created_at: ApiTimestamp,
 }
 
 /// 任务列表查询参数
@@ -115,8 +312,17 @@ pub struct ApiTaskListQuery {
     pub tags: Option<String>,
     pub created_after: Option<String>,
     pub created_before: Option<String>,
+    /// 取消原因：requested / expired / rejected
+    pub cancel_reason: Option<String>,
+    /// 失败类别：timeout / oom / tool_error / validation / upstream / unknown
+    pub failure_category: Option<String>,
+    /// 组合过滤表达式，语法见 [`crate::models::filter_expr`]
+    pub filter: Option<String>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    /// 多键排序，如 `priority:desc,created_at:asc`
+    pub sort: Option<String>,
+    /// 单字段排序（旧参数），指定 `sort` 时忽略
     pub sort_by: Option<String>,
     pub sort_order: Option<String>,
 }
@@ -137,6 +343,53 @@ pub struct ApiPagination {
     pub has_more: bool,
 }
 
+/// 任务字段更新请求
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ApiUpdateTaskRequest {
+    #[validate(custom(function = "validate_priority_string"))]
+    pub priority: Option<String>,
+
+    #[validate(custom(function = "crate::domain::validate_tags"))]
+    pub tags: Option<Vec<String>>,
+
+    /// 元数据合并补丁，值为 null 时删除对应键
+    pub metadata: Option<std::collections::HashMap<String, serde_json::Value>>,
+
+    #[validate(range(max = 100))]
+    pub max_retries: Option<u32>,
+}
+
+/// 任务克隆请求，未设置的字段沿用源任务
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct ApiCloneTaskRequest {
+    #[validate(length(min = 1, max = 512))]
+    pub work_directory: Option<String>,
+
+    #[validate(length(min = 1, max = 10000))]
+    pub prompt: Option<String>,
+
+    #[validate(custom(function = "validate_priority_string"))]
+    pub priority: Option<String>,
+
+    #[validate(custom(function = "crate::domain::validate_tags"))]
+    pub tags: Option<Vec<String>>,
+
+    /// 元数据合并补丁，值为 null 时删除对应键
+    pub metadata: Option<std::collections::HashMap<String, serde_json::Value>>,
+
+    /// 有效期（RFC3339），与 `ttl_seconds` 二选一，源任务的有效期不会被复制
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+
+    #[validate(range(min = 1))]
+    pub ttl_seconds: Option<u64>,
+
+    #[validate(custom(function = "crate::domain::validate_concurrency_group"))]
+    pub concurrency_group: Option<String>,
+
+    #[serde(default)]
+    pub requires_approval: bool,
+}
+
 /// 任务取消请求
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiCancelTaskRequest {
@@ -148,10 +401,28 @@ pub struct ApiCancelTaskRequest {
 pub struct ApiCancelTaskResponse {
     pub task_id: String,
     pub status: String,
-    pub cancelled_at: String,
+    pub cancelled_at: ApiTimestamp,
     pub reason: Option<String>,
 }
 
+/// 任务审批请求，审批与拒绝共用
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ApiApprovalRequest {
+    /// 审批人，仅在未配置审批人密钥时使用
+    pub approver: Option<String>,
+    /// 审批意见或拒绝原因
+    pub comment: Option<String>,
+}
+
+/// 任务审批响应
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiApprovalResponse {
+    pub task_id: String,
+    pub status: String,
+    pub approver: String,
+    pub comment: Option<String>,
+}
+
 /// 任务重试响应
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiRetryTaskResponse {
@@ -159,18 +430,31 @@ pub struct ApiRetryTaskResponse {
     pub status: String,
     pub retry_count: u32,
     pub max_retries: u32,
-    pub last_retry_at: String,
+    pub last_retry_at: ApiTimestamp,
 }
 
 /// 健康检查响应
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HealthCheckResponse {
     pub status: String,
-    pub timestamp: String,
+    pub timestamp: ApiTimestamp,
     pub version: String,
     pub uptime: String,
     pub components: serde_json::Value,
     pub metrics: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cluster: Option<ClusterStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub standby: Option<StandbyStatus>,
+}
+
+/// 任务产物响应
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiArtifactResponse {
+    pub task_id: String,
+    pub name: String,
+    pub key: String,
+    pub download_url: String,
 }
 
 /// 统计信息响应
@@ -179,16 +463,138 @@ pub struct StatisticsResponse {
     pub overview: serde_json::Value,
     pub status_distribution: serde_json::Value,
     pub priority_distribution: serde_json::Value,
+    pub failure_distribution: serde_json::Value,
     pub performance_metrics: serde_json::Value,
     pub time_series: Vec<serde_json::Value>,
 }
 
+/// 队列暂停/恢复请求，未指定工作目录时作用于全局
+#[derive(Debug, Default, Deserialize)]
+pub struct ApiQueueControlRequest {
+    pub work_directory: Option<String>,
+}
+
+/// 注册元数据模式请求
+#[derive(Debug, Deserialize)]
+pub struct ApiMetadataSchemaRequest {
+    /// 命名空间（工作目录前缀）
+    pub namespace: String,
+    pub schema: serde_json::Value,
+    /// 拒绝模式中未声明的键
+    #[serde(default)]
+    pub strict: bool,
+}
+
+/// 元数据模式命名空间查询参数
+#[derive(Debug, Deserialize)]
+pub struct ApiMetadataSchemaQuery {
+    pub namespace: String,
+}
+
+/// 队列控制状态响应
+#[derive(Debug, Serialize)]
+pub struct ApiQueueStatus {
+    #[serde(flatten)]
+    pub control: QueueControlStatus,
+    pub waiting_tasks: u64,
+    pub working_tasks: u64,
+    /// 排空模式下已没有等待或执行中的任务
+    pub drained: bool,
+}
+
 
 /// 创建任务处理器
 pub async fn create_task_handler(
     State(state): State<ApiState>,
+    Query(params): Query<ApiCreateTaskQuery>,
     Json(request): Json<ApiCreateTaskRequest>,
-) -> Result<impl IntoResponse, AppError> {
+) -> Result<axum::response::Response, AppError> {
+    let create_request = create_request(request)?;
+
+    if params.dry_run {
+        let simulation = simulate_task(&state, create_request).await?;
+        return Ok(Json(ApiResponse::success(simulation)).into_response());
+    }
+
+    // 创建任务
+    let task = create_task(&state, create_request).await?;
+
+    // 构建响应
+    let response = ApiCreateTaskResponse::from(&task);
+
+    Ok(Json(ApiResponse::success(response)).into_response())
+}
+
+/// 列出流水线定义
+pub async fn list_pipelines_handler(
+    State(state): State<ApiState>,
+) -> Json<ApiResponse<Vec<crate::services::PipelineSummary>>> {
+    Json(ApiResponse::success(state.task_service.pipelines().summaries()))
+}
+
+/// 运行流水线处理器，创建第一步的任务
+pub async fn run_pipeline_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    Json(request): Json<ApiRunPipelineRequest>,
+) -> Result<Json<ApiResponse<ApiPipelineRunResponse>>, AppError> {
+    request.validate().map_err(|e| {
+        AppError::Validation(crate::errors::ValidationError::invalid_validation(e.to_string()))
+    })?;
+    let priority = request
+        .priority
+        .as_deref()
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            TaskPriority::from_str(p)
+                .map_err(|_| AppError::Validation(crate::errors::ValidationError::invalid_priority(p.to_string())))
+        })
+        .transpose()?;
+
+    let start = state
+        .task_service
+        .run_pipeline(
+            &name,
+            crate::services::PipelineRunRequest {
+                work_directory: request.work_directory,
+                input: request.input,
+                priority,
+                tags: request.tags.unwrap_or_default(),
+                metadata: request.metadata.unwrap_or_default(),
+            },
+        )
+        .await?;
+    if let Some(task) = &start.task {
+        state.logger.log_task_created(
+            &task.id.to_string(),
+            task.work_directory.as_str(),
+            &task.priority.to_string(),
+            task.tags.len(),
+            &task.status.to_string(),
+        );
+    }
+
+    Ok(Json(ApiResponse::success(ApiPipelineRunResponse {
+        task: start.task.as_ref().map(ApiCreateTaskResponse::from),
+        steps: state.task_service.pipelines().get(&name).map_or(0, |p| p.steps.len()),
+        run_id: start.run.run_id,
+        pipeline: start.run.pipeline,
+        status: start.run.status,
+        skipped: start.run.skipped,
+    })))
+}
+
+/// 查询流水线运行状态处理器
+pub async fn get_pipeline_run_handler(
+    State(state): State<ApiState>,
+    Path(run_id): Path<String>,
+) -> Result<Json<ApiResponse<crate::domain::PipelineRun>>, AppError> {
+    let run = state.task_service.get_pipeline_run(&run_id).await?;
+    Ok(Json(ApiResponse::success(run)))
+}
+
+/// 验证创建请求并转换为领域请求
+fn create_request(request: ApiCreateTaskRequest) -> AppResult<CreateTaskRequest> {
     // 验证请求
     request.validate().map_err(|e| {
         AppError::Validation(crate::errors::ValidationError::invalid_validation(e.to_string()))
@@ -209,18 +615,68 @@ pub async fn create_task_handler(
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| AppError::Validation(crate::errors::ValidationError::invalid_tags(e.to_string())))?;
 
-    // 创建任务请求
-    let create_request = CreateTaskRequest {
+    // 转换有效期
+    let expires_at = expiry(request.expires_at, request.ttl_seconds)?;
+
+    Ok(CreateTaskRequest {
         work_directory: request.work_directory,
         prompt: request.prompt,
         priority: Some(priority),
         tags: Some(tags.into_iter().map(|t| t.to_string()).collect()),
-    };
+        metadata: request.metadata,
+        expires_at,
+        concurrency_group: request.concurrency_group,
+        requires_approval: request.requires_approval,
+    })
+}
 
-    // 创建任务
-    let task = state.task_service.create_task(create_request).await?;
+/// 创建任务并记录日志
+/// 根据 `expires_at` 或 `ttl_seconds` 计算有效期，二者只能指定一个
+fn expiry(
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    ttl_seconds: Option<u64>,
+) -> AppResult<Option<chrono::DateTime<chrono::Utc>>> {
+    match (expires_at, ttl_seconds) {
+        (Some(_), Some(_)) => Err(AppError::Validation(crate::errors::ValidationError::invalid_validation(
+            "expires_at and ttl_seconds are mutually exclusive".to_string(),
+        ))),
+        (Some(expires_at), None) => Ok(Some(expires_at)),
+        (None, Some(ttl_seconds)) => {
+            let ttl = i64::try_from(ttl_seconds).ok().and_then(chrono::Duration::try_seconds).ok_or_else(|| {
+                AppError::Validation(crate::errors::ValidationError::invalid_validation("ttl_seconds is too large".to_string()))
+            })?;
+            Ok(Some(chrono::Utc::now() + ttl))
+        }
+        (None, None) => Ok(None),
+    }
+}
+
+/// 克隆任务处理器
+pub async fn clone_task_handler(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+    Json(request): Json<ApiCloneTaskRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    request.validate().map_err(|e| {
+        AppError::Validation(crate::errors::ValidationError::invalid_validation(e.to_string()))
+    })?;
+    let priority = request.priority
+        .as_deref()
+        .map(|p| TaskPriority::from_str(p).map_err(|_| AppError::Validation(crate::errors::ValidationError::invalid_priority(p.to_string()))))
+        .transpose()?;
+
+    let task = state.task_service.clone_task(&task_id, CloneTaskRequest {
+        work_directory: request.work_directory,
+        prompt: request.prompt,
+        priority,
+        tags: request.tags,
+        metadata: request.metadata,
+        expires_at: expiry(request.expires_at, request.ttl_seconds)?,
+        concurrency_group: request.concurrency_group,
+        requires_approval: request.requires_approval,
+    }).await?;
 
-    // 记录日志
     state.logger.log_task_created(
         &task.id.to_string(),
         task.work_directory.as_str(),
@@ -228,18 +684,31 @@ pub async fn create_task_handler(
         task.tags.len(),
         &task.status.to_string(),
     );
+    Ok(Json(ApiResponse::success(ApiCreateTaskResponse::from(&task))))
+}
 
-    // 构建响应
-    let response = ApiCreateTaskResponse {
-        task_id: task.id.to_string(),
-        status: task.status.to_string(),
-        priority: task.priority.to_string(),
-        work_directory: task.work_directory.to_string(),
-        tags: task.tags.iter().map(|t| t.to_string()).collect(),
-        created_at: task.created_at.to_rfc3339(),
-    };
+async fn create_task(state: &ApiState, request: CreateTaskRequest) -> AppResult<crate::domain::Task> {
+    let task = state.task_service.create_task(request).await?;
 
-    Ok(Json(ApiResponse::success(response)))
+    state.logger.log_task_created(
+        &task.id.to_string(),
+        task.work_directory.as_str(),
+        &task.priority.to_string(),
+        task.tags.len(),
+        &task.status.to_string(),
+    );
+    Ok(task)
+}
+
+/// 模拟创建任务
+async fn simulate_task(state: &ApiState, request: CreateTaskRequest) -> AppResult<ApiTaskSimulation> {
+    let simulation = state.task_service.simulate_task(request).await?;
+    Ok(ApiTaskSimulation {
+        dry_run: true,
+        task: task_detail(simulation.task, state.logger.redactor()),
+        warnings: simulation.warnings,
+        matching_workers: simulation.matching_workers,
+    })
 }
 
 /// 获取下一个任务处理器
@@ -247,6 +716,24 @@ pub async fn get_next_task_handler(
     State(state): State<ApiState>,
     Query(params): Query<ApiGetTaskRequest>,
 ) -> Result<impl IntoResponse, AppError> {
+    match acquire_task(&state, params).await? {
+        Some(task) => {
+            let response = ApiGetTaskResponse {
+                task_id: task.id.to_string(),
+                prompt: task.prompt.to_string(),
+                work_directory: task.work_directory.to_string(),
+                priority: task.priority.to_string(),
+                tags: task.tags.iter().map(|t| t.to_string()).collect(),
+            };
+
+            Ok(Json(ApiResponse::success(response)))
+        }
+        None => Ok(Json(ApiResponse::success(ApiGetTaskResponse::default()))),
+    }
+}
+
+/// 验证获取请求、获取下一个任务并记录日志
+async fn acquire_task(state: &ApiState, params: ApiGetTaskRequest) -> AppResult<Option<crate::domain::Task>> {
     // 验证请求
     if params.work_path.is_empty() {
         return Err(AppError::Validation(crate::errors::ValidationError::missing_field("work_path".to_string())));
@@ -259,34 +746,29 @@ pub async fn get_next_task_handler(
     let acquire_request = AcquireTaskRequest {
         work_path: params.work_path,
         worker_id: params.worker_id,
+        capabilities: params.capabilities.map(|capabilities| {
+            capabilities
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        }),
     };
 
     // 获取任务
     let task = state.task_service.acquire_task(acquire_request).await?;
 
-    match task {
-        Some(task) => {
-            // 记录日志
-            state.logger.log_task_acquired(
-                &task.id.to_string(),
-                &task.worker_id.as_ref().unwrap().to_string(),
-                task.work_directory.as_str(),
-                &task.priority.to_string(),
-                0, // 获取时间
-            );
-
-            let response = ApiGetTaskResponse {
-                task_id: task.id.to_string(),
-                prompt: task.prompt.to_string(),
-                work_directory: task.work_directory.to_string(),
-                priority: task.priority.to_string(),
-                tags: task.tags.iter().map(|t| t.to_string()).collect(),
-            };
-
-            Ok(Json(ApiResponse::success(response)))
-        }
-        None => Ok(Json(ApiResponse::success(ApiGetTaskResponse::default()))),
+    if let Some(task) = &task {
+        // 记录日志
+        state.logger.log_task_acquired(
+            &task.id.to_string(),
+            &task.worker_id.as_ref().unwrap().to_string(),
+            task.work_directory.as_str(),
+            &task.priority.to_string(),
+            0, // 获取时间
+        );
     }
+    Ok(task)
 }
 
 /// 完成任务处理器
@@ -295,15 +777,32 @@ pub async fn complete_task_handler(
     Path(task_id): Path<String>,
     Json(request): Json<ApiCompleteTaskRequest>,
 ) -> Result<impl IntoResponse, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    let task = complete_task(&state, &task_id, request).await?;
+
+    let response = serde_json::json!({
+        "task_id": task.id.to_string(),
+        "status": task.status.to_string(),
+        "completed_at": ApiTimestamp(task.completed_at.unwrap()),
+        "worker_id": task.worker_id.as_ref().map(|w| w.to_string()),
+    });
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// 验证完成请求、完成任务并记录日志
+async fn complete_task(
+    state: &ApiState,
+    task_id: &TaskId,
+    request: ApiCompleteTaskRequest,
+) -> AppResult<crate::domain::Task> {
     // 验证请求
     request.validate().map_err(|e| {
         AppError::Validation(crate::errors::ValidationError::invalid_validation(e.to_string()))
     })?;
 
-    let task_id = TaskId::from_str(&task_id)?;
-
     // 转换任务结果
-    let result = request.result.map(|r| {
+    let result = request.result.map(|r| -> AppResult<_> {
         let status = match r.status.as_str() {
             "success" => crate::domain::TaskResultStatus::Success,
             "failed" => crate::domain::TaskResultStatus::Failed,
@@ -317,8 +816,9 @@ pub async fn complete_task_handler(
             .map(|obj| obj.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
             .unwrap_or_default();
         task_result.duration = r.duration;
-        task_result
-    });
+        task_result.failure_category = r.failure_category.as_deref().map(failure_category).transpose()?;
+        Ok(task_result)
+    }).transpose()?;
 
     let complete_request = CompleteTaskRequest {
         original_prompt: request.original_prompt,
@@ -326,7 +826,7 @@ pub async fn complete_task_handler(
     };
 
     // 完成任务
-    let task = state.task_service.complete_task(&task_id, complete_request).await?;
+    let task = state.task_service.complete_task(task_id, complete_request).await?;
 
     // 记录日志
     if let Some(worker_id) = &task.worker_id {
@@ -344,14 +844,7 @@ pub async fn complete_task_handler(
         );
     }
 
-    let response = serde_json::json!({
-        "task_id": task.id.to_string(),
-        "status": task.status.to_string(),
-        "completed_at": task.completed_at.unwrap().to_rfc3339(),
-        "worker_id": task.worker_id.as_ref().map(|w| w.to_string()),
-    });
-
-    Ok(Json(ApiResponse::success(response)))
+    Ok(task)
 }
 
 /// 获取任务详情处理器
@@ -360,20 +853,160 @@ pub async fn get_task_handler(
     Path(task_id): Path<String>,
 ) -> Result<impl IntoResponse, AppError> {
     let task_id = TaskId::from_str(&task_id)?;
-    let task = state.task_service.get_task(&task_id).await?;
+    Ok(Json(ApiResponse::success(load_task_detail(&state, &task_id).await?)))
+}
+
+/// 等待任务完成处理器（长轮询）
+pub async fn wait_task_handler(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+    Query(params): Query<ApiWaitTaskQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    let task = state.task_service.wait_for_completion(&task_id, params.timeout).await?;
+
+    let response = ApiWaitTaskResponse {
+        completed: task.status.is_terminal(),
+        task: task_detail(task, state.logger.redactor()),
+    };
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// 获取包含评论的任务详情
+async fn load_task_detail(state: &ApiState, task_id: &TaskId) -> AppResult<ApiTaskDetail> {
+    let task = state.task_service.get_task(task_id).await?;
+    let comments = state.task_service.get_task_comments(task_id).await?;
+    let redactor = state.logger.redactor();
+
+    let mut detail = task_detail(task, redactor);
+    detail.comments = comments.into_iter().map(|c| task_comment(c, redactor)).collect();
+    Ok(detail)
+}
+
+/// 添加任务评论处理器
+pub async fn add_task_comment_handler(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+    Json(request): Json<ApiAddCommentRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    request.validate().map_err(|e| {
+        AppError::Validation(crate::errors::ValidationError::invalid_validation(e.to_string()))
+    })?;
+
+    let task_id = TaskId::from_str(&task_id)?;
+    let comment = state
+        .task_service
+        .add_comment(&task_id, request.author, request.text, request.data)
+        .await?;
+
+    Ok(Json(ApiResponse::success(task_comment(comment, state.logger.redactor()))))
+}
+
+/// 任务执行记录处理器
+pub async fn list_task_attempts_handler(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    Ok(Json(ApiResponse::success(task_attempts(&state, &task_id).await?)))
+}
+
+/// 获取任务执行记录
+async fn task_attempts(state: &ApiState, task_id: &TaskId) -> AppResult<Vec<ApiTaskAttempt>> {
+    let attempts = state.task_service.get_task_attempts(task_id).await?;
+    let redactor = state.logger.redactor();
+
+    Ok(attempts
+        .into_iter()
+        .map(|attempt| ApiTaskAttempt {
+            attempt: attempt.attempt,
+            status: attempt.status.to_string(),
+            worker_id: attempt.worker_id.map(|w| w.to_string()),
+            finished_at: attempt.finished_at.into(),
+            result: task_result(&attempt.result, redactor),
+        })
+        .collect())
+}
+
+/// 执行输出差异处理器，`Accept: text/x-diff` 时直接返回统一格式文本差异
+pub async fn diff_task_attempts_handler(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+    Query(query): Query<ApiAttemptDiffQuery>,
+    headers: axum::http::HeaderMap,
+) -> Result<axum::response::Response, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    let diff = state.task_service.diff_task_attempts(&task_id, query.from, query.to).await?;
+
+    let wants_text = headers
+        .get(axum::http::header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/x-diff") || accept.contains("text/plain"));
+    if wants_text {
+        return Ok(([(axum::http::header::CONTENT_TYPE, "text/x-diff; charset=utf-8")], diff.unified).into_response());
+    }
+    Ok(Json(ApiResponse::success(diff)).into_response())
+}
+
+/// 更新任务字段处理器
+pub async fn update_task_handler(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+    Json(request): Json<ApiUpdateTaskRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    let task = state.task_service.update_task_fields(&task_id, update_request(request)?).await?;
+
+    Ok(Json(ApiResponse::success(task_detail(task, state.logger.redactor()))))
+}
+
+/// 验证字段更新请求并转换为领域请求
+fn update_request(request: ApiUpdateTaskRequest) -> AppResult<UpdateTaskRequest> {
+    request.validate().map_err(|e| {
+        AppError::Validation(crate::errors::ValidationError::invalid_validation(e.to_string()))
+    })?;
+
+    let priority = request.priority
+        .as_deref()
+        .map(|p| TaskPriority::from_str(p).map_err(|_| AppError::Validation(crate::errors::ValidationError::invalid_priority(p.to_string()))))
+        .transpose()?;
+
+    Ok(UpdateTaskRequest {
+        priority,
+        tags: request.tags,
+        metadata: request.metadata,
+        max_retries: request.max_retries,
+    })
+}
+
+/// 解析失败类别参数
+fn failure_category(value: &str) -> AppResult<crate::domain::FailureCategory> {
+    value.to_lowercase().parse().map_err(|_| {
+        AppError::Validation(crate::errors::ValidationError::invalid_validation(
+            format!("Invalid failure category: {}", value),
+        ))
+    })
+}
 
-    let result = task.result.as_ref().map(|r| ApiTaskResult {
-        status: match r.status {
+/// 将任务转换为详情响应，元数据中的敏感字段会被掩码
+fn task_result(result: &crate::domain::TaskResult, redactor: &Redactor) -> ApiTaskResult {
+    ApiTaskResult {
+        status: match result.status {
             crate::domain::TaskResultStatus::Success => "success".to_string(),
             crate::domain::TaskResultStatus::Failed => "failed".to_string(),
         },
-        output: r.output.clone(),
-        error: r.error.clone(),
-        details: serde_json::Value::Object(r.details.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
-        duration: r.duration,
-    });
+        output: result.output.clone(),
+        error: result.error.clone(),
+        details: serde_json::Value::Object(redactor.redact_metadata(&result.details).into_iter().collect()),
+        duration: result.duration,
+        failure_category: result.failure_category.map(|c| c.to_string()),
+    }
+}
+
+fn task_detail(task: crate::domain::Task, redactor: &Redactor) -> ApiTaskDetail {
+    let result = task.result.as_ref().map(|r| task_result(r, redactor));
 
-    let response = ApiTaskDetail {
+    ApiTaskDetail {
         task_id: task.id.to_string(),
         work_directory: task.work_directory.to_string(),
         prompt: task.prompt.to_string(),
@@ -381,17 +1014,31 @@ pub async fn get_task_handler(
         tags: task.tags.iter().map(|t| t.to_string()).collect(),
         status: task.status.to_string(),
         worker_id: task.worker_id.map(|w| w.to_string()),
-        created_at: task.created_at.to_rfc3339(),
-        started_at: task.started_at.map(|t| t.to_rfc3339()),
-        completed_at: task.completed_at.map(|t| t.to_rfc3339()),
+        created_at: task.created_at.into(),
+        started_at: task.started_at.map(ApiTimestamp),
+        completed_at: task.completed_at.map(ApiTimestamp),
         result,
         error_message: task.error_message,
         retry_count: task.retry_count,
         max_retries: task.max_retries,
-        metadata: serde_json::Value::Object(task.metadata.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
-    };
+        metadata: serde_json::Value::Object(redactor.redact_metadata(&task.metadata).into_iter().collect()),
+        expires_at: task.expires_at.map(ApiTimestamp),
+        cancel_reason: task.cancel_reason.map(|r| r.to_string()),
+        failure_category: task.failure_category.map(|c| c.to_string()),
+        retry_after: task.retry_after.map(ApiTimestamp),
+        concurrency_group: task.concurrency_group,
+        comments: Vec::new(),
+    }
+}
 
-    Ok(Json(ApiResponse::success(response)))
+fn task_comment(comment: crate::domain::TaskComment, redactor: &Redactor) -> ApiTaskComment {
+    ApiTaskComment {
+        id: comment.id,
+        author: comment.author,
+        text: redactor.redact_text(&comment.text).into_owned(),
+        data: comment.data.as_ref().map(|data| redactor.redact_value(data)),
+        created_at: comment.created_at.into(),
+    }
 }
 
 /// 获取任务列表处理器
@@ -426,6 +1073,23 @@ pub async fn list_tasks_handler(
         filter = filter.with_created_before(chrono::DateTime::parse_from_rfc3339(created_before)?.with_timezone(&chrono::Utc).into());
     }
 
+    if let Some(cancel_reason) = &params.cancel_reason {
+        let cancel_reason = cancel_reason.parse().map_err(|_| {
+            AppError::Validation(crate::errors::ValidationError::invalid_validation(
+                format!("Invalid cancel reason: {}", cancel_reason),
+            ))
+        })?;
+        filter = filter.with_cancel_reason(cancel_reason);
+    }
+
+    if let Some(category) = &params.failure_category {
+        filter = filter.with_failure_category(failure_category(category)?);
+    }
+
+    if let Some(expression) = &params.filter {
+        filter = filter.with_expression(FilterExpr::parse(expression)?);
+    }
+
     if let Some(limit) = params.limit {
         filter = filter.with_limit(limit);
     }
@@ -434,48 +1098,20 @@ pub async fn list_tasks_handler(
         filter = filter.with_offset(offset);
     }
 
-    if let Some(sort_by) = &params.sort_by {
-        filter = filter.with_sort_by(sort_by.clone());
-    }
-
-    if let Some(sort_order) = &params.sort_order {
-        filter = filter.with_sort_order(sort_order.clone());
+    if let Some(sort) = &params.sort {
+        filter = filter.with_sort(TaskSort::parse(sort)?);
+    } else if let Some(sort_by) = &params.sort_by {
+        filter = filter.with_sort(TaskSort::from_legacy(sort_by, params.sort_order.as_deref())?);
     }
 
     // 获取任务列表
     let (tasks, total) = state.task_service.list_tasks(filter).await?;
 
     // 转换任务详情
-    let task_details = tasks.into_iter().map(|task| {
-        let result = task.result.as_ref().map(|r| ApiTaskResult {
-            status: match r.status {
-                crate::domain::TaskResultStatus::Success => "success".to_string(),
-                crate::domain::TaskResultStatus::Failed => "failed".to_string(),
-            },
-            output: r.output.clone(),
-            error: r.error.clone(),
-            details: serde_json::Value::Object(r.details.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
-            duration: r.duration,
-        });
-
-        ApiTaskDetail {
-            task_id: task.id.to_string(),
-            work_directory: task.work_directory.to_string(),
-            prompt: task.prompt.to_string(),
-            priority: task.priority.to_string(),
-            tags: task.tags.iter().map(|t| t.to_string()).collect(),
-            status: task.status.to_string(),
-            worker_id: task.worker_id.map(|w| w.to_string()),
-            created_at: task.created_at.to_rfc3339(),
-            started_at: task.started_at.map(|t| t.to_rfc3339()),
-            completed_at: task.completed_at.map(|t| t.to_rfc3339()),
-            result,
-            error_message: task.error_message,
-            retry_count: task.retry_count,
-            max_retries: task.max_retries,
-            metadata: serde_json::Value::Object(task.metadata.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
-        }
-    }).collect();
+    let task_details = tasks
+        .into_iter()
+        .map(|task| task_detail(task, state.logger.redactor()))
+        .collect();
 
     let limit = params.limit.unwrap_or(100) as u64;
     let offset = params.offset.unwrap_or(0) as u64;
@@ -500,26 +1136,72 @@ pub async fn cancel_task_handler(
     Json(request): Json<ApiCancelTaskRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     let task_id = TaskId::from_str(&task_id)?;
-    let reason = request.reason.clone();
-    let task = state.task_service.cancel_task(&task_id, reason.clone()).await?;
-
-    // 记录日志
-    state.logger.log_task_cancelled(
-        &task.id.to_string(),
-        reason.as_deref(),
-        None,
-    );
+    let task = cancel_task(&state, &task_id, request.reason).await?;
 
     let response = ApiCancelTaskResponse {
         task_id: task.id.to_string(),
         status: task.status.to_string(),
-        cancelled_at: task.completed_at.unwrap().to_rfc3339(),
+        cancelled_at: task.completed_at.unwrap().into(),
         reason: task.error_message,
     };
 
     Ok(Json(ApiResponse::success(response)))
 }
 
+/// 审批通过处理器
+pub async fn approve_task_handler(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+    headers: axum::http::HeaderMap,
+    Json(request): Json<ApiApprovalRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    let approver = state
+        .task_service
+        .identify_approver(quota::api_key(&headers), request.approver.as_deref())?;
+    let task = state.task_service.approve_task(&task_id, approver.clone(), request.comment.clone()).await?;
+
+    Ok(Json(ApiResponse::success(ApiApprovalResponse {
+        task_id: task.id.to_string(),
+        status: task.status.to_string(),
+        approver,
+        comment: request.comment,
+    })))
+}
+
+/// 审批拒绝处理器
+pub async fn reject_task_handler(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+    headers: axum::http::HeaderMap,
+    Json(request): Json<ApiApprovalRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    let approver = state
+        .task_service
+        .identify_approver(quota::api_key(&headers), request.approver.as_deref())?;
+    let task = state.task_service.reject_task(&task_id, approver.clone(), request.comment.clone()).await?;
+
+    Ok(Json(ApiResponse::success(ApiApprovalResponse {
+        task_id: task.id.to_string(),
+        status: task.status.to_string(),
+        approver,
+        comment: request.comment,
+    })))
+}
+
+/// 取消任务并记录日志
+async fn cancel_task(state: &ApiState, task_id: &TaskId, reason: Option<String>) -> AppResult<crate::domain::Task> {
+    let task = state.task_service.cancel_task(task_id, reason.clone()).await?;
+
+    state.logger.log_task_cancelled(
+        &task.id.to_string(),
+        reason.as_deref(),
+        None,
+    );
+    Ok(task)
+}
+
 /// 重试任务处理器
 pub async fn retry_task_handler(
     State(state): State<ApiState>,
@@ -533,7 +1215,7 @@ pub async fn retry_task_handler(
         status: task.status.to_string(),
         retry_count: task.retry_count,
         max_retries: task.max_retries,
-        last_retry_at: chrono::Utc::now().to_rfc3339(),
+        last_retry_at: chrono::Utc::now().into(),
     };
 
     Ok(Json(ApiResponse::success(response)))
@@ -541,20 +1223,27 @@ pub async fn retry_task_handler(
 
 /// 健康检查处理器
 pub async fn health_check_handler(
-    State(_state): State<ApiState>,
+    State(state): State<ApiState>,
 ) -> Result<impl IntoResponse, AppError> {
     use crate::utils::HealthChecker;
     
     let health_checker = HealthChecker::new();
     let health_status = health_checker.check_health().await;
 
+    let cluster = match &state.leader_elector {
+        Some(elector) => Some(elector.status().await),
+        None => None,
+    };
+
     let response = HealthCheckResponse {
         status: health_status.status,
-        timestamp: health_status.timestamp.to_rfc3339(),
+        timestamp: health_status.timestamp.into(),
         version: health_status.version,
         uptime: format!("{:?}", std::time::SystemTime::UNIX_EPOCH.elapsed().unwrap()),
         components: serde_json::to_value(health_status.components).unwrap(),
         metrics: serde_json::to_value(health_status.metrics).unwrap(),
+        cluster,
+        standby: state.standby.as_ref().map(|standby| standby.status()),
     };
 
     Ok(Json(response))
@@ -564,18 +1253,67 @@ pub async fn health_check_handler(
 pub async fn get_statistics_handler(
     State(state): State<ApiState>,
 ) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(ApiResponse::success(statistics(&state).await?)))
+}
+
+/// 获取SLA报告处理器
+pub async fn get_sla_handler(
+    State(state): State<ApiState>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(ApiResponse::success(state.task_service.sla_report().await?)))
+}
+
+/// 容量预测默认统计窗口（秒）
+const CAPACITY_DEFAULT_WINDOW: u64 = 3600;
+/// 容量预测最大统计窗口（秒）
+const CAPACITY_MAX_WINDOW: u64 = 7 * 86400;
+/// 最多模拟的扩容方案数
+const CAPACITY_MAX_SCENARIOS: usize = 10;
+
+/// 容量预测处理器
+pub async fn capacity_forecast_handler(
+    State(state): State<ApiState>,
+    Query(query): Query<ApiCapacityForecastQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let invalid = |message: String| AppError::Validation(crate::errors::ValidationError::invalid_validation(message));
+
+    let window = query.window.unwrap_or(CAPACITY_DEFAULT_WINDOW);
+    if window == 0 || window > CAPACITY_MAX_WINDOW {
+        return Err(invalid(format!("window must be between 1 and {} seconds", CAPACITY_MAX_WINDOW)));
+    }
+    let added = match query.add.as_deref() {
+        None => vec![1, 2, 5],
+        Some(list) => list
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<u32>().map_err(|_| invalid(format!("invalid worker count '{}' in add", s))))
+            .collect::<AppResult<Vec<_>>>()?,
+    };
+    if added.len() > CAPACITY_MAX_SCENARIOS {
+        return Err(invalid(format!("at most {} scenarios can be simulated", CAPACITY_MAX_SCENARIOS)));
+    }
+
+    let forecast = state.task_service.capacity_forecast(window, query.workers, &added).await?;
+    Ok(Json(ApiResponse::success(forecast)))
+}
+
+/// 汇总任务统计和各组件指标
+async fn statistics(state: &ApiState) -> AppResult<StatisticsResponse> {
     let stats = state.task_service.get_statistics().await?;
 
-    let response = StatisticsResponse {
+    Ok(StatisticsResponse {
         overview: serde_json::json!({
             "total_tasks": stats.total_tasks,
             "completed_tasks": stats.completed_tasks,
             "failed_tasks": stats.failed_tasks,
             "cancelled_tasks": stats.cancelled_tasks,
+            "expired_tasks": stats.expired_tasks,
             "active_tasks": stats.active_tasks,
             "success_rate": stats.success_rate
         }),
         status_distribution: serde_json::json!({
+            "pending_approval": stats.pending_approval_tasks,
             "waiting": stats.waiting_tasks,
             "working": stats.working_tasks,
             "completed": stats.completed_tasks,
@@ -583,15 +1321,152 @@ pub async fn get_statistics_handler(
             "cancelled": stats.cancelled_tasks
         }),
         priority_distribution: serde_json::json!({
-            "low": 0, // 需要从数据库获取
-            "medium": 0,
-            "high": 0
+            "low": stats.low_priority_tasks,
+            "medium": stats.medium_priority_tasks,
+            "high": stats.high_priority_tasks
         }),
+        failure_distribution: serde_json::json!(stats.failure_categories),
         performance_metrics: serde_json::json!({
             "avg_processing_time": stats.avg_processing_time,
-            "tasks_per_hour": stats.tasks_per_hour
+            "tasks_per_hour": stats.tasks_per_hour,
+            "completion_waiters": state.task_service.completion_waiters(),
+            "event_export": state.task_service.exporter_stats(),
+            "secret_scanning": state.task_service.secret_scanner_stats(),
+            "task_cache": state.task_service.task_cache_stats(),
+            "history_writer": state.task_service.history_writer_stats(),
+            "database_pool": state.task_service.database_pool_stats(),
+            "external_services": state.task_service.service_client_stats().await,
+            "routing": state.task_service.routing_stats().await?,
+            "request_quotas": state.request_quotas.as_ref().map(|quotas| quotas.stats()),
+            "memory": state.memory_watchdog.as_ref().map(|watchdog| watchdog.stats()),
+            "queue_control": state.task_service.queue_control().status()
         }),
         time_series: vec![],
+    })
+}
+
+/// 当前队列控制状态和剩余任务数
+async fn queue_status(state: &ApiState, control: QueueControlStatus) -> AppResult<ApiQueueStatus> {
+    let stats = state.task_service.get_statistics().await?;
+    Ok(ApiQueueStatus {
+        drained: control.draining && stats.waiting_tasks == 0 && stats.working_tasks == 0,
+        control,
+        waiting_tasks: stats.waiting_tasks,
+        working_tasks: stats.working_tasks,
+    })
+}
+
+/// 查看队列控制状态处理器
+pub async fn get_queue_status_handler(State(state): State<ApiState>) -> Result<impl IntoResponse, AppError> {
+    let control = state.task_service.queue_control().status();
+    Ok(Json(ApiResponse::success(queue_status(&state, control).await?)))
+}
+
+/// 暂停分发任务处理器
+pub async fn pause_queue_handler(
+    State(state): State<ApiState>,
+    request: Option<Json<ApiQueueControlRequest>>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(request) = request.unwrap_or_default();
+    let control = state.task_service.queue_control().pause(request.work_directory.as_deref());
+    Ok(Json(ApiResponse::success(queue_status(&state, control).await?)))
+}
+
+/// 恢复分发任务处理器，全局恢复时同时退出排空模式
+pub async fn resume_queue_handler(
+    State(state): State<ApiState>,
+    request: Option<Json<ApiQueueControlRequest>>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(request) = request.unwrap_or_default();
+    let control = state.task_service.queue_control().resume(request.work_directory.as_deref());
+    Ok(Json(ApiResponse::success(queue_status(&state, control).await?)))
+}
+
+/// 进入排空模式处理器
+pub async fn drain_queue_handler(State(state): State<ApiState>) -> Result<impl IntoResponse, AppError> {
+    let control = state.task_service.queue_control().drain();
+    Ok(Json(ApiResponse::success(queue_status(&state, control).await?)))
+}
+
+/// 列出元数据模式处理器
+pub async fn list_metadata_schemas_handler(State(state): State<ApiState>) -> Result<impl IntoResponse, AppError> {
+    let schemas = state.task_service.metadata_schemas().list();
+    Ok(Json(ApiResponse::success(schemas)))
+}
+
+/// 注册或替换元数据模式处理器
+pub async fn put_metadata_schema_handler(
+    State(state): State<ApiState>,
+    Json(request): Json<ApiMetadataSchemaRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let schema = state
+        .task_service
+        .register_metadata_schema(request.namespace, request.schema, request.strict)
+        .await?;
+    Ok(Json(ApiResponse::success(schema)))
+}
+
+/// 删除元数据模式处理器
+pub async fn delete_metadata_schema_handler(
+    State(state): State<ApiState>,
+    Query(query): Query<ApiMetadataSchemaQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let deleted = state.task_service.delete_metadata_schema(&query.namespace).await?;
+    Ok(Json(ApiResponse::success(serde_json::json!({
+        "namespace": query.namespace,
+        "deleted": deleted,
+    }))))
+}
+
+/// 获取产物存储，未启用时返回服务不可用
+fn artifact_store(state: &ApiState) -> AppResult<&Arc<ArtifactStore>> {
+    state.artifact_store.as_ref().ok_or_else(|| {
+        AppError::ServiceUnavailable("Artifact storage is not enabled".to_string())
+    })
+}
+
+/// 上传任务产物处理器
+pub async fn upload_artifact_handler(
+    State(state): State<ApiState>,
+    Path((task_id, name)): Path<(String, String)>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<impl IntoResponse, AppError> {
+    let store = artifact_store(&state)?;
+    let task_id = TaskId::from_str(&task_id)?;
+    state.task_service.get_task(&task_id).await?;
+
+    let content_type = headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/octet-stream");
+
+    let key = store.upload(&task_id, &name, body.to_vec(), content_type).await?;
+    let response = ApiArtifactResponse {
+        task_id: task_id.to_string(),
+        download_url: store.download_url(&task_id, &name)?,
+        name,
+        key,
+    };
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// 获取任务产物下载地址处理器
+pub async fn get_artifact_handler(
+    State(state): State<ApiState>,
+    Path((task_id, name)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let store = artifact_store(&state)?;
+    let task_id = TaskId::from_str(&task_id)?;
+    state.task_service.get_task(&task_id).await?;
+
+    let (key, download_url) = store.existing_download_url(&task_id, &name).await?;
+    let response = ApiArtifactResponse {
+        task_id: task_id.to_string(),
+        key,
+        download_url,
+        name,
     };
 
     Ok(Json(ApiResponse::success(response)))
@@ -611,16 +1486,93 @@ fn validate_priority_string(priority: &str) -> Result<(), validator::ValidationE
 
 /// 创建API路由
 pub fn create_routes(state: ApiState) -> Router {
-    Router::new()
+    // 产物上传整体读入内存，请求体上限取自产物存储配置而不是axum默认的2MB
+    let mut artifact_routes = axum::routing::put(upload_artifact_handler).get(get_artifact_handler);
+    if let Some(store) = &state.artifact_store {
+        artifact_routes = artifact_routes.layer(axum::extract::DefaultBodyLimit::max(store.max_size()));
+    }
+
+    let mut router = Router::new();
+    if let Some(endpoint) = &state.prometheus_endpoint {
+        router = router.route(endpoint, get(prometheus::prometheus_handler));
+    }
+    router
         // 任务管理
         .route("/api/v1/tasks", post(create_task_handler).get(list_tasks_handler))
         .route("/api/v1/tasks/next", get(get_next_task_handler))
-        .route("/api/v1/tasks/:task_id", get(get_task_handler))
+        .route("/api/v1/tasks/:task_id", get(get_task_handler).patch(update_task_handler))
         .route("/api/v1/tasks/:task_id/complete", post(complete_task_handler))
         .route("/api/v1/tasks/:task_id/cancel", post(cancel_task_handler))
+        .route("/api/v1/tasks/:task_id/approve", post(approve_task_handler))
+        .route("/api/v1/tasks/:task_id/reject", post(reject_task_handler))
         .route("/api/v1/tasks/:task_id/retry", post(retry_task_handler))
+        .route("/api/v1/tasks/:task_id/clone", post(clone_task_handler))
+        .route("/api/v1/tasks/:task_id/wait", get(wait_task_handler))
+        .route("/api/v1/tasks/:task_id/comments", post(add_task_comment_handler))
+        .route("/api/v1/tasks/:task_id/attempts", get(list_task_attempts_handler))
+        .route("/api/v1/tasks/:task_id/attempts/diff", get(diff_task_attempts_handler))
+        .route("/api/v1/pipelines", get(list_pipelines_handler))
+        .route("/api/v1/pipelines/:name/run", post(run_pipeline_handler))
+        .route("/api/v1/pipeline-runs/:run_id", get(get_pipeline_run_handler))
+        .route("/api/v1/tasks/:task_id/artifacts/:name", artifact_routes)
         // 系统管理
         .route("/health", get(health_check_handler))
         .route("/api/v1/statistics", get(get_statistics_handler))
+        .route("/api/v1/sla", get(get_sla_handler))
+        .route("/api/v1/capacity/forecast", get(capacity_forecast_handler))
+        .route(recording::RECENT_REQUESTS_PATH, get(recording::recent_requests_handler))
+        .route("/api/v1/admin/queue", get(get_queue_status_handler))
+        .route("/api/v1/admin/queue/pause", post(pause_queue_handler))
+        .route("/api/v1/admin/queue/resume", post(resume_queue_handler))
+        .route("/api/v1/admin/queue/drain", post(drain_queue_handler))
+        .route("/api/v1/admin/standby", get(standby::standby_status_handler))
+        .route(standby::PROMOTE_PATH, post(standby::promote_handler))
+        .route(REPLICATION_EVENTS_PATH, get(standby::replication_events_handler))
+        .route(
+            "/api/v1/admin/metadata-schemas",
+            get(list_metadata_schemas_handler)
+                .put(put_metadata_schema_handler)
+                .delete(delete_metadata_schema_handler),
+        )
+        .nest("/api/v2", v2::routes())
+        .fallback(not_found_handler)
+        .layer(axum::middleware::from_fn_with_state(state.clone(), standby::standby_middleware))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), quota::quota_middleware))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), recording::recording_middleware))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), decompression::decompression_middleware))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), memory::memory_pressure_middleware))
+        .layer(axum::middleware::from_fn(v2::version_negotiation_middleware))
+        .layer(CatchPanicLayer::custom(crate::utils::crash::panic_response))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), prometheus::metrics_middleware))
+        .layer(axum::middleware::from_fn(trace_id_middleware))
+        .with_state(state)
+}
+
+/// 创建独立指标监听器的路由，仅提供指标和健康检查端点
+pub fn create_metrics_routes(state: ApiState, monitoring: &MonitoringConfig) -> Router {
+    let mut router = Router::new();
+    if monitoring.enable_health_check {
+        router = router.route(&monitoring.health_check_endpoint, get(health_check_handler));
+    }
+    if monitoring.enable_metrics {
+        router = router.route(&monitoring.metrics_endpoint, get(get_statistics_handler));
+    }
+    if monitoring.enable_prometheus {
+        router = router.route(&monitoring.prometheus_endpoint, get(prometheus::prometheus_handler));
+    }
+    router
+        .fallback(not_found_handler)
+        .layer(CatchPanicLayer::custom(crate::utils::crash::panic_response))
+        .layer(axum::middleware::from_fn(trace_id_middleware))
         .with_state(state)
+}
+
+/// 未匹配路由处理器
+async fn not_found_handler(uri: axum::http::Uri) -> ProblemDetails {
+    let locale = current_request_context().map(|ctx| ctx.locale).unwrap_or_default();
+    request_problem(
+        axum::http::StatusCode::NOT_FOUND,
+        "NOT_FOUND",
+        i18n::translate(locale, "error.route_not_found", &[uri.path().to_string()]),
+    )
 }
\ No newline at end of file
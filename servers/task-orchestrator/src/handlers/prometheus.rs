@@ -0,0 +1,61 @@
+//! # Prometheus端点
+//!
+//! 记录每个请求的耗时，并按 `Accept` 请求头以Prometheus文本格式或OpenMetrics格式导出指标。
+//! `route` 标签使用匹配到的路由模板（如 `/api/v1/tasks/:task_id`），未匹配的请求统一记为 `unmatched`。
+
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::ApiState;
+use crate::errors::current_request_context;
+use crate::utils::metrics::{accepts_openmetrics, OPENMETRICS_CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE};
+
+/// 未匹配路由的 `route` 标签值
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// 记录请求耗时的中间件，需位于 `trace_id_middleware` 之内以取得追踪ID
+pub async fn metrics_middleware(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let Some(metrics) = state.metrics.clone() else {
+        return next.run(request).await;
+    };
+
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| UNMATCHED_ROUTE.to_string(), |path| path.as_str().to_string());
+    let started = Instant::now();
+    let response = next.run(request).await;
+
+    let trace_id = current_request_context().map(|ctx| ctx.trace_id);
+    metrics.record_http_request(
+        &method,
+        &route,
+        response.status().as_u16(),
+        started.elapsed().as_secs_f64(),
+        trace_id.as_deref(),
+    );
+    response
+}
+
+/// 导出Prometheus指标
+pub async fn prometheus_handler(State(state): State<ApiState>, request: Request) -> Response {
+    let Some(metrics) = &state.metrics else {
+        return axum::http::StatusCode::NOT_FOUND.into_response();
+    };
+    // 缓存计数只在本进程内累计，导出时同步
+    if let Some(stats) = state.task_service.task_cache_stats() {
+        metrics.set_task_cache_stats(&stats);
+    }
+
+    let accept = request.headers().get(header::ACCEPT).and_then(|v| v.to_str().ok());
+    let openmetrics = accepts_openmetrics(accept);
+    let content_type = if openmetrics { OPENMETRICS_CONTENT_TYPE } else { PROMETHEUS_CONTENT_TYPE };
+    ([(header::CONTENT_TYPE, content_type)], metrics.render(openmetrics)).into_response()
+}
@@ -0,0 +1,138 @@
+//! # 请求并发配额
+//!
+//! 把 [`RequestQuotas`](crate::utils::concurrency::RequestQuotas) 接入HTTP处理：按路由把请求分到执行、写、只读三组，
+//! 按API密钥（未提供时按对端地址）区分租户，取得许可后才进入处理器，拿不到许可时返回 `429` 和 `Retry-After`。
+
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, HeaderValue, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+use super::ApiState;
+use crate::utils::concurrency::RouteGroup;
+
+/// 不受配额限制的路径，保证过载时探活仍然可用
+const EXEMPT_PATHS: &[&str] = &["/health"];
+
+/// 按方法和路径划分路由组
+pub fn classify(method: &Method, path: &str) -> RouteGroup {
+    let path = path.trim_end_matches('/');
+    let executes = path.ends_with("/tasks/next")
+        || (method == Method::POST && path.ends_with("/complete"))
+        || (method == Method::PUT && path.contains("/artifacts/"));
+    if executes {
+        RouteGroup::Execute
+    } else if method == Method::GET || method == Method::HEAD || method == Method::OPTIONS {
+        RouteGroup::Read
+    } else {
+        RouteGroup::Write
+    }
+}
+
+/// 请求携带的API密钥，依次读取 `X-Api-Key` 和 `Authorization: Bearer`
+pub fn api_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("x-api-key")
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "))
+        })
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+/// 租户标识
+///
+/// 使用 `X-Api-Key` 或 `Authorization: Bearer` 中密钥的哈希前缀，避免在内存和统计中保留明文密钥；
+/// 未提供密钥时使用对端IP，Unix域套接字上的请求归为 `local`。
+pub fn tenant_key(headers: &HeaderMap, peer: Option<SocketAddr>) -> String {
+    match (api_key(headers), peer) {
+        (Some(key), _) => format!("key:{}", &hex::encode(Sha256::digest(key.as_bytes()))[..16]),
+        (None, Some(peer)) => format!("ip:{}", peer.ip()),
+        (None, None) => "local".to_string(),
+    }
+}
+
+/// 并发配额中间件，未启用配额时直接放行
+pub async fn quota_middleware(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let Some(quotas) = state.request_quotas.clone() else {
+        return next.run(request).await;
+    };
+    if EXEMPT_PATHS.contains(&request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let group = classify(request.method(), request.uri().path());
+    let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0);
+    let tenant = tenant_key(request.headers(), peer);
+
+    match quotas.acquire(group, &tenant).await {
+        Ok(_permit) => next.run(request).await,
+        Err(e) => {
+            tracing::warn!("Request quota exhausted for {} requests from {}: {}", group.as_str(), tenant, e);
+            let mut response = e.into_response();
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from_static("1"));
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RequestQuotaConfig;
+    use crate::errors::AppError;
+    use crate::utils::concurrency::RequestQuotas;
+
+    #[tokio::test]
+    async fn test_route_groups_and_tenant_quotas() {
+        assert_eq!(classify(&Method::GET, "/api/v1/tasks/next"), RouteGroup::Execute);
+        assert_eq!(classify(&Method::POST, "/api/v2/tasks/abc/complete"), RouteGroup::Execute);
+        assert_eq!(classify(&Method::PUT, "/api/v1/tasks/abc/artifacts/log.txt"), RouteGroup::Execute);
+        assert_eq!(classify(&Method::GET, "/api/v1/tasks/abc"), RouteGroup::Read);
+        assert_eq!(classify(&Method::POST, "/api/v1/tasks"), RouteGroup::Write);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        let tenant = tenant_key(&headers, Some("10.0.0.1:5000".parse().unwrap()));
+        assert!(tenant.starts_with("key:") && !tenant.contains("secret"));
+        assert_eq!(tenant_key(&HeaderMap::new(), Some("10.0.0.1:5000".parse().unwrap())), "ip:10.0.0.1");
+        assert_eq!(tenant_key(&HeaderMap::new(), None), "local");
+
+        let quotas = RequestQuotas::new(&RequestQuotaConfig {
+            enabled: true,
+            execute_limit: 2,
+            per_tenant_limit: 1,
+            queue_timeout_ms: 50,
+            ..Default::default()
+        });
+
+        // 同一租户的第二个请求在租户信号量上排队超时，不影响其他租户
+        let noisy = quotas.acquire(RouteGroup::Execute, "noisy").await.unwrap();
+        assert!(matches!(quotas.acquire(RouteGroup::Execute, "noisy").await, Err(AppError::RateLimitExceeded)));
+        let other = quotas.acquire(RouteGroup::Execute, "other").await.unwrap();
+
+        // 路由组槽位已满
+        assert!(matches!(quotas.acquire(RouteGroup::Execute, "third").await, Err(AppError::RateLimitExceeded)));
+        assert!(quotas.acquire(RouteGroup::Read, "third").await.is_ok());
+
+        let stats = quotas.stats();
+        assert_eq!(stats.groups["execute"].in_flight, 2);
+        assert_eq!(stats.groups["execute"].rejected, 1);
+        assert_eq!(stats.groups["execute"].queued, 0);
+        assert_eq!(stats.tenant_rejections, 1);
+        assert_eq!(stats.active_tenants, 2);
+
+        drop((noisy, other));
+        assert_eq!(quotas.stats().groups["execute"].in_flight, 0);
+        assert!(quotas.acquire(RouteGroup::Execute, "noisy").await.is_ok());
+    }
+}
@@ -0,0 +1,266 @@
+//! # 请求录制
+//!
+//! 调试模式下记录脱敏后的API请求和响应，保存在内存环形缓冲区中，可选追加写入JSON Lines文件。
+//! 通过 `GET /api/v1/admin/recent-requests` 查看最近的请求，并用 `replay` 工具把录制的请求
+//! 重新发送到测试实例，便于复现只在特定工作节点上出现的问题。
+//!
+//! 认证类请求头只保留掩码，请求体和响应体按日志脱敏规则处理；超过大小上限或长度未知的
+//! 请求体（如产物上传）不缓冲，只记录长度。
+
+use std::collections::{BTreeMap, VecDeque};
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use axum::{
+    body::{Body, HttpBody},
+    extract::{Query, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+
+use super::ApiState;
+use crate::config::RequestRecordingConfig;
+use crate::errors::{current_request_context, ApiResponse, AppError, AppResult};
+use crate::utils::Redactor;
+
+/// 查看录制记录的端点，本身不被录制
+pub const RECENT_REQUESTS_PATH: &str = "/api/v1/admin/recent-requests";
+
+/// 无论脱敏配置如何都只记录掩码的请求头
+const MASKED_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+];
+
+/// 一次录制的请求和响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedExchange {
+    pub id: u64,
+    pub trace_id: Option<String>,
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+    pub method: String,
+    /// 路径和查询字符串
+    pub uri: String,
+    pub request_headers: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<String>,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_body: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// 请求录制器
+pub struct RequestRecorder {
+    capacity: usize,
+    max_body_bytes: usize,
+    redactor: Redactor,
+    next_id: AtomicU64,
+    recent: Mutex<VecDeque<RecordedExchange>>,
+    file: Option<Mutex<std::fs::File>>,
+}
+
+impl RequestRecorder {
+    /// 根据配置创建录制器，配置了文件时以追加方式打开
+    pub fn from_config(config: &RequestRecordingConfig, redactor: Redactor) -> AppResult<Self> {
+        let file = match &config.file {
+            Some(path) => {
+                let file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| AppError::Internal(format!("Failed to open request recording file {}: {}", path, e)))?;
+                Some(Mutex::new(file))
+            }
+            None => None,
+        };
+        Ok(Self {
+            capacity: config.capacity.max(1),
+            max_body_bytes: config.max_body_bytes,
+            redactor,
+            next_id: AtomicU64::new(1),
+            recent: Mutex::new(VecDeque::new()),
+            file,
+        })
+    }
+
+    /// 最近的录制记录，按时间倒序
+    pub fn recent(&self, limit: usize) -> Vec<RecordedExchange> {
+        self.recent.lock().unwrap().iter().rev().take(limit).cloned().collect()
+    }
+
+    fn record(&self, mut exchange: RecordedExchange) {
+        exchange.id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        if let Some(file) = &self.file {
+            match serde_json::to_string(&exchange) {
+                Ok(line) => {
+                    if let Err(e) = writeln!(file.lock().unwrap(), "{}", line) {
+                        tracing::warn!("Failed to write recorded request: {}", e);
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to serialize recorded request: {}", e),
+            }
+        }
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() >= self.capacity {
+            recent.pop_front();
+        }
+        recent.push_back(exchange);
+    }
+
+    fn sanitize_headers(&self, headers: &HeaderMap) -> BTreeMap<String, String> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let name = name.as_str().to_string();
+                let value = if MASKED_HEADERS.contains(&name.as_str()) || self.redactor.is_sensitive_key(&name) {
+                    "[REDACTED]".to_string()
+                } else {
+                    self.redactor.redact_text(&String::from_utf8_lossy(value.as_bytes())).into_owned()
+                };
+                (name, value)
+            })
+            .collect()
+    }
+
+    fn sanitize_body(&self, bytes: &[u8]) -> Option<String> {
+        if bytes.is_empty() {
+            return None;
+        }
+        let text = match serde_json::from_slice::<serde_json::Value>(bytes) {
+            Ok(value) => self.redactor.redact_value(&value).to_string(),
+            Err(_) => self.redactor.redact_text(&String::from_utf8_lossy(bytes)).into_owned(),
+        };
+        Some(text)
+    }
+
+    /// 长度已知且不超过上限时读出整个body，否则原样返回并只记录长度
+    async fn capture(&self, body: Body) -> (Body, Option<String>) {
+        let size = body.size_hint().exact();
+        match size {
+            Some(size) if size as usize <= self.max_body_bytes => {
+                match axum::body::to_bytes(body, self.max_body_bytes).await {
+                    Ok(bytes) => {
+                        let recorded = self.sanitize_body(&bytes);
+                        (Body::from(bytes), recorded)
+                    }
+                    Err(e) => (Body::empty(), Some(format!("<failed to read body: {}>", e))),
+                }
+            }
+            Some(size) => (body, Some(format!("<{} bytes omitted>", size))),
+            None => (body, Some("<streaming body omitted>".to_string())),
+        }
+    }
+}
+
+/// 录制中间件，未启用录制时直接放行
+pub async fn recording_middleware(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let Some(recorder) = state.request_recorder.clone() else {
+        return next.run(request).await;
+    };
+    if request.uri().path() == RECENT_REQUESTS_PATH {
+        return next.run(request).await;
+    }
+
+    let started = Instant::now();
+    let (parts, body) = request.into_parts();
+    let (body, request_body) = recorder.capture(body).await;
+    let method = parts.method.to_string();
+    let uri = parts.uri.path_and_query().map(|pq| pq.to_string()).unwrap_or_default();
+    let request_headers = recorder.sanitize_headers(&parts.headers);
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    let (parts, body) = response.into_parts();
+    let (body, response_body) = recorder.capture(body).await;
+    recorder.record(RecordedExchange {
+        id: 0,
+        trace_id: current_request_context().map(|ctx| ctx.trace_id),
+        recorded_at: chrono::Utc::now(),
+        method,
+        uri: recorder.redactor.redact_text(&uri).into_owned(),
+        request_headers,
+        request_body,
+        status: parts.status.as_u16(),
+        response_body,
+        duration_ms: started.elapsed().as_millis() as u64,
+    });
+    Response::from_parts(parts, body)
+}
+
+/// 查看录制记录的查询参数
+#[derive(Debug, Deserialize)]
+pub struct RecentRequestsQuery {
+    pub limit: Option<usize>,
+}
+
+/// 最近录制的请求处理器
+pub async fn recent_requests_handler(
+    State(state): State<ApiState>,
+    Query(query): Query<RecentRequestsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let recorder = state.request_recorder.as_ref().ok_or_else(|| {
+        AppError::ServiceUnavailable("Request recording is not enabled".to_string())
+    })?;
+    Ok(Json(ApiResponse::success(recorder.recent(query.limit.unwrap_or(50)))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[tokio::test]
+    async fn test_recorder_sanitizes_and_rotates() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("requests.jsonl");
+        let config = RequestRecordingConfig {
+            enabled: true,
+            capacity: 2,
+            max_body_bytes: 64,
+            file: Some(file.display().to_string()),
+        };
+        let recorder = RequestRecorder::from_config(&config, Redactor::default()).unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_static("Bearer abc"));
+        headers.insert("x-worker-id", HeaderValue::from_static("worker-1"));
+        let headers = recorder.sanitize_headers(&headers);
+        assert_eq!(headers["authorization"], "[REDACTED]");
+        assert_eq!(headers["x-worker-id"], "worker-1");
+
+        let (body, recorded) = recorder.capture(Body::from(r#"{"prompt":"hi","api_key":"k"}"#)).await;
+        assert_eq!(recorded.unwrap(), r#"{"api_key":"[REDACTED]","prompt":"hi"}"#);
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        assert_eq!(&bytes[..], br#"{"prompt":"hi","api_key":"k"}"#);
+
+        let (_, recorded) = recorder.capture(Body::from(vec![b'x'; 100])).await;
+        assert_eq!(recorded.unwrap(), "<100 bytes omitted>");
+
+        for path in ["/a", "/b", "/c"] {
+            recorder.record(RecordedExchange {
+                id: 0,
+                trace_id: None,
+                recorded_at: chrono::Utc::now(),
+                method: "GET".to_string(),
+                uri: path.to_string(),
+                request_headers: BTreeMap::new(),
+                request_body: None,
+                status: 200,
+                response_body: None,
+                duration_ms: 1,
+            });
+        }
+        let recent = recorder.recent(10);
+        assert_eq!(recent.iter().map(|e| e.uri.as_str()).collect::<Vec<_>>(), vec!["/c", "/b"]);
+        assert_eq!(recent[0].id, 3);
+        assert_eq!(std::fs::read_to_string(&file).unwrap().lines().count(), 3);
+    }
+}
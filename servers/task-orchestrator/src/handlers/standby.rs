@@ -0,0 +1,139 @@
+//! # 热备接口
+//!
+//! - 主实例：`GET /api/v1/replication/events?after=N&limit=M` 按序列号分页提供事件流，供备机复制
+//! - 备机：`GET /api/v1/admin/standby` 查看复制状态，`POST /api/v1/admin/standby/promote` 提升为主实例
+//!
+//! 备机角色期间，写请求和获取任务请求返回 `503`。配置了 `standby.token` 时，复制接口和提升接口
+//! 要求 `Authorization: Bearer <token>`。
+
+use axum::{
+    extract::{Query, Request, State},
+    http::{header, HeaderMap, Method},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use serde::Deserialize;
+
+use super::ApiState;
+use crate::errors::{AppError, AppResult, ApiResponse};
+use crate::infrastructure::ReplicationBatch;
+
+/// 提升接口路径，备机角色期间唯一放行的写请求
+pub const PROMOTE_PATH: &str = "/api/v1/admin/standby/promote";
+
+/// 复制接口单次返回的最大事件数
+const MAX_REPLICATION_LIMIT: u32 = 5000;
+
+/// 复制事件查询参数
+#[derive(Debug, Deserialize)]
+pub struct ReplicationQuery {
+    #[serde(default)]
+    pub after: u64,
+    pub limit: Option<u32>,
+}
+
+/// 请求是否会修改任务状态（获取任务虽是GET请求，也会把任务分配给工作者）
+fn is_write_request(method: &Method, path: &str) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) || path.ends_with("/tasks/next")
+}
+
+/// 校验共享令牌，未配置令牌时不校验
+fn authorize(headers: &HeaderMap, token: Option<&str>) -> AppResult<()> {
+    let Some(token) = token else {
+        return Ok(());
+    };
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    // 逐字节比较全部内容，避免按前缀长短泄露时间差
+    let matches = provided.len() == token.len()
+        && provided.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0;
+    if matches {
+        Ok(())
+    } else {
+        Err(AppError::Authentication("Invalid or missing standby token".to_string()))
+    }
+}
+
+/// 备机角色期间拒绝写请求的中间件
+pub async fn standby_middleware(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let Some(standby) = &state.standby else {
+        return next.run(request).await;
+    };
+    let path = request.uri().path();
+    if !standby.is_standby() || path == PROMOTE_PATH || !is_write_request(request.method(), path) {
+        return next.run(request).await;
+    }
+
+    AppError::ServiceUnavailable(format!(
+        "This node is a read-only standby of {}; send writes to the primary or promote this node",
+        standby.status().primary_url
+    ))
+    .into_response()
+}
+
+/// 复制事件处理器（主实例）
+pub async fn replication_events_handler(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Query(query): Query<ReplicationQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    authorize(&headers, state.replication_token.as_deref())?;
+    let event_store = state.event_store.as_ref().ok_or_else(|| {
+        AppError::ServiceUnavailable("Replication requires database.enable_event_sourcing".to_string())
+    })?;
+
+    let limit = query.limit.unwrap_or(500).clamp(1, MAX_REPLICATION_LIMIT);
+    // 先读最新序列号，保证返回的序列号不小于本批事件
+    let last_sequence = event_store.last_sequence().await?;
+    let events = event_store.load_events_page(query.after, limit).await?;
+    let last_sequence = events.last().map_or(last_sequence, |event| event.sequence.max(last_sequence));
+
+    Ok(Json(ApiResponse::success(ReplicationBatch { events, last_sequence })))
+}
+
+/// 热备状态处理器
+pub async fn standby_status_handler(State(state): State<ApiState>) -> Result<impl IntoResponse, AppError> {
+    let standby = state
+        .standby
+        .as_ref()
+        .ok_or_else(|| AppError::ServiceUnavailable("Standby mode is not configured".to_string()))?;
+    Ok(Json(ApiResponse::success(standby.status())))
+}
+
+/// 提升为主实例处理器
+pub async fn promote_handler(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    authorize(&headers, state.replication_token.as_deref())?;
+    let standby = state
+        .standby
+        .as_ref()
+        .ok_or_else(|| AppError::ServiceUnavailable("Standby mode is not configured".to_string()))?;
+    state.logger.log_info("Promoting standby to primary", None);
+    Ok(Json(ApiResponse::success(standby.promote().await)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_write_requests_and_token() {
+        assert!(is_write_request(&Method::POST, "/api/v1/tasks"));
+        assert!(is_write_request(&Method::GET, "/api/v2/tasks/next"));
+        assert!(!is_write_request(&Method::GET, "/api/v1/tasks/abc/wait"));
+
+        let mut headers = HeaderMap::new();
+        assert!(authorize(&headers, None).is_ok());
+        assert!(authorize(&headers, Some("secret")).is_err());
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer secreT"));
+        assert!(authorize(&headers, Some("secret")).is_err());
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        assert!(authorize(&headers, Some("secret")).is_ok());
+    }
+}
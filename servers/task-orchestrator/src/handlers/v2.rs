@@ -0,0 +1,386 @@
+//! # API v2
+//!
+//! 与v1相比的不兼容调整：
+//!
+//! - 成功响应统一为 `{"data": ...}` 信封，列表额外携带 `page`，不再包含 `success`/`error`/`timestamp`
+//! - 错误统一返回 `application/problem+json`
+//! - 列表使用不透明游标分页（`cursor` + `limit`），取代 `offset`
+//! - 创建返回 `201 Created` 和 `Location`，没有可获取的任务时返回 `204 No Content`
+//! - 完成、取消、重试和更新统一返回完整的任务详情
+//!
+//! 版本通过路径（`/api/v1`、`/api/v2`）选择，也可在 `Accept` 中声明
+//! `application/vnd.task-orchestrator.v2+json`，声明的版本与路径不符时返回406。
+
+use axum::{
+    extract::{Path, Query, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Router,
+};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ApiAddCommentRequest, ApiCancelTaskRequest, ApiCompleteTaskRequest, ApiCreateTaskQuery,
+    ApiCreateTaskRequest, ApiGetTaskRequest, ApiState, ApiUpdateTaskRequest,
+};
+use crate::domain::{TaskId, TaskPriority, TaskStatus};
+use crate::errors::{AppError, AppResult, request_problem};
+use crate::models::{FilterExpr, TaskFilter, TaskSort};
+use crate::utils::i18n;
+
+/// 已提供的API版本
+pub const SUPPORTED_VERSIONS: &[u32] = &[1, 2];
+
+/// 响应中标明API版本的头
+pub const API_VERSION_HEADER: &str = "api-version";
+
+/// 版本化媒体类型前缀，完整形式为 `application/vnd.task-orchestrator.v{N}+json`
+const VENDOR_MEDIA_TYPE_PREFIX: &str = "application/vnd.task-orchestrator.v";
+
+/// 列表默认每页条数
+const DEFAULT_PAGE_LIMIT: u64 = 50;
+
+/// 列表每页条数上限
+const MAX_PAGE_LIMIT: u64 = 500;
+
+/// v2响应信封
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    pub data: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<Page>,
+}
+
+impl<T> Envelope<T> {
+    pub fn new(data: T) -> Self {
+        Self { data, page: None }
+    }
+}
+
+/// 游标分页信息
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Page {
+    pub limit: u64,
+    pub total: u64,
+    /// 下一页游标，最后一页为空
+    pub next_cursor: Option<String>,
+}
+
+/// 任务列表查询参数
+#[derive(Debug, Deserialize)]
+pub struct ListTasksQuery {
+    pub status: Option<String>,
+    pub work_directory: Option<String>,
+    pub priority: Option<String>,
+    pub tags: Option<String>,
+    /// 组合过滤表达式，语法见 [`crate::models::filter_expr`]
+    pub filter: Option<String>,
+    /// 多键排序，如 `priority:desc,created_at:asc`
+    pub sort: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<u64>,
+}
+
+/// 创建v2路由，挂载在 `/api/v2` 下
+pub fn routes() -> Router<ApiState> {
+    Router::new()
+        .route("/tasks", post(create_task).get(list_tasks))
+        .route("/tasks/next", get(next_task))
+        .route("/tasks/:task_id", get(get_task).patch(update_task))
+        .route("/tasks/:task_id/complete", post(complete_task))
+        .route("/tasks/:task_id/cancel", post(cancel_task))
+        .route("/tasks/:task_id/retry", post(retry_task))
+        .route("/tasks/:task_id/comments", post(add_comment).get(list_comments))
+        .route("/tasks/:task_id/attempts", get(list_attempts))
+        .route("/statistics", get(statistics))
+}
+
+/// API版本协商中间件
+///
+/// `Accept` 中声明的版本不受支持或与请求路径的版本不符时返回406；
+/// 版本化路径的响应带 `api-version` 头，v1响应额外带 `Deprecation` 和指向v2的 `Link` 头。
+pub async fn version_negotiation_middleware(request: Request, next: Next) -> Response {
+    let path_version = path_version(request.uri().path());
+    let accepted = request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .map(accepted_versions)
+        .unwrap_or_default();
+
+    if !accepted.is_empty() {
+        let acceptable = match path_version {
+            Some(version) => accepted.contains(&version),
+            None => accepted.iter().any(|v| SUPPORTED_VERSIONS.contains(v)),
+        };
+        if !acceptable {
+            let locale = crate::errors::current_request_context().map(|ctx| ctx.locale).unwrap_or_default();
+            let requested = accepted.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ");
+            let supported = SUPPORTED_VERSIONS.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ");
+            return request_problem(
+                StatusCode::NOT_ACCEPTABLE,
+                "NOT_ACCEPTABLE",
+                i18n::translate(locale, "error.unsupported_api_version", &[requested, supported]),
+            )
+            .into_response();
+        }
+    }
+
+    let mut response = next.run(request).await;
+    if let Some(version) = path_version {
+        let headers = response.headers_mut();
+        headers.insert(API_VERSION_HEADER, HeaderValue::from(version));
+        if version == 1 {
+            headers.insert("deprecation", HeaderValue::from_static("true"));
+            headers.insert(header::LINK, HeaderValue::from_static("</api/v2>; rel=\"successor-version\""));
+        }
+    }
+    response
+}
+
+/// 从路径中解析 `/api/v{N}/` 版本号
+fn path_version(path: &str) -> Option<u32> {
+    let rest = path.strip_prefix("/api/v")?;
+    let digits = rest.split('/').next()?;
+    digits.parse().ok()
+}
+
+/// 解析 `Accept` 中声明的版本化媒体类型
+fn accepted_versions(accept: &str) -> Vec<u32> {
+    accept
+        .split(',')
+        .filter_map(|range| {
+            let media_type = range.split(';').next()?.trim().to_ascii_lowercase();
+            media_type
+                .strip_prefix(VENDOR_MEDIA_TYPE_PREFIX)?
+                .strip_suffix("+json")?
+                .parse()
+                .ok()
+        })
+        .collect()
+}
+
+/// 编码分页游标
+///
+/// 游标对调用方不透明，目前记录下一页的起始位置。
+fn encode_cursor(offset: u64) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(format!("offset:{}", offset))
+}
+
+/// 解码分页游标
+fn decode_cursor(cursor: &str) -> AppResult<u64> {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .and_then(|value| value.strip_prefix("offset:")?.parse().ok())
+        .ok_or_else(|| {
+            AppError::Validation(crate::errors::ValidationError::invalid_validation(format!(
+                "Invalid cursor: {}",
+                cursor
+            )))
+        })
+}
+
+async fn create_task(
+    State(state): State<ApiState>,
+    Query(params): Query<ApiCreateTaskQuery>,
+    Json(request): Json<ApiCreateTaskRequest>,
+) -> Result<Response, AppError> {
+    let create_request = super::create_request(request)?;
+
+    if params.dry_run {
+        let simulation = super::simulate_task(&state, create_request).await?;
+        return Ok(Json(Envelope::new(simulation)).into_response());
+    }
+
+    let task = super::create_task(&state, create_request).await?;
+    let location = format!("/api/v2/tasks/{}", task.id);
+    let detail = super::task_detail(task, state.logger.redactor());
+
+    let mut response = (StatusCode::CREATED, Json(Envelope::new(detail))).into_response();
+    if let Ok(value) = HeaderValue::from_str(&location) {
+        response.headers_mut().insert(header::LOCATION, value);
+    }
+    Ok(response)
+}
+
+async fn list_tasks(
+    State(state): State<ApiState>,
+    Query(params): Query<ListTasksQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT);
+    let offset = params.cursor.as_deref().map(decode_cursor).transpose()?.unwrap_or(0);
+
+    let mut filter = TaskFilter::new()
+        .with_limit(limit as i64)
+        .with_offset(offset as i64);
+    if let Some(status) = &params.status {
+        filter = filter.with_status(TaskStatus::from_str(status)?);
+    }
+    if let Some(work_directory) = &params.work_directory {
+        filter = filter.with_work_directory(work_directory.clone());
+    }
+    if let Some(priority) = &params.priority {
+        filter = filter.with_priority(TaskPriority::from_str(priority)?);
+    }
+    if let Some(tags) = &params.tags {
+        filter = filter.with_tags(tags.split(',').map(|s| s.trim().to_string()).collect());
+    }
+    if let Some(expression) = &params.filter {
+        filter = filter.with_expression(FilterExpr::parse(expression)?);
+    }
+    if let Some(sort) = &params.sort {
+        filter = filter.with_sort(TaskSort::parse(sort)?);
+    }
+
+    let (tasks, total) = state.task_service.list_tasks(filter).await?;
+    let next_offset = offset + tasks.len() as u64;
+    let tasks: Vec<_> = tasks
+        .into_iter()
+        .map(|task| super::task_detail(task, state.logger.redactor()))
+        .collect();
+
+    Ok(Json(Envelope {
+        data: tasks,
+        page: Some(Page {
+            limit,
+            total,
+            next_cursor: (next_offset < total).then(|| encode_cursor(next_offset)),
+        }),
+    }))
+}
+
+async fn next_task(
+    State(state): State<ApiState>,
+    Query(params): Query<ApiGetTaskRequest>,
+) -> Result<Response, AppError> {
+    match super::acquire_task(&state, params).await? {
+        Some(task) => Ok(Json(Envelope::new(super::task_detail(task, state.logger.redactor()))).into_response()),
+        None => Ok(StatusCode::NO_CONTENT.into_response()),
+    }
+}
+
+async fn get_task(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    Ok(Json(Envelope::new(super::load_task_detail(&state, &task_id).await?)))
+}
+
+async fn update_task(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+    Json(request): Json<ApiUpdateTaskRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    let task = state.task_service.update_task_fields(&task_id, super::update_request(request)?).await?;
+    Ok(Json(Envelope::new(super::task_detail(task, state.logger.redactor()))))
+}
+
+async fn complete_task(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+    Json(request): Json<ApiCompleteTaskRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    let task = super::complete_task(&state, &task_id, request).await?;
+    Ok(Json(Envelope::new(super::task_detail(task, state.logger.redactor()))))
+}
+
+async fn cancel_task(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+    Json(request): Json<ApiCancelTaskRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    let task = super::cancel_task(&state, &task_id, request.reason).await?;
+    Ok(Json(Envelope::new(super::task_detail(task, state.logger.redactor()))))
+}
+
+async fn retry_task(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    let task = state.task_service.retry_task(&task_id).await?;
+    Ok(Json(Envelope::new(super::task_detail(task, state.logger.redactor()))))
+}
+
+async fn add_comment(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+    Json(request): Json<ApiAddCommentRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    use validator::Validate;
+
+    request.validate().map_err(|e| {
+        AppError::Validation(crate::errors::ValidationError::invalid_validation(e.to_string()))
+    })?;
+
+    let task_id = TaskId::from_str(&task_id)?;
+    let comment = state
+        .task_service
+        .add_comment(&task_id, request.author, request.text, request.data)
+        .await?;
+    Ok((
+        StatusCode::CREATED,
+        Json(Envelope::new(super::task_comment(comment, state.logger.redactor()))),
+    ))
+}
+
+async fn list_comments(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    // 任务不存在时返回404，而不是空列表
+    state.task_service.get_task(&task_id).await?;
+    let redactor = state.logger.redactor();
+    let comments: Vec<_> = state
+        .task_service
+        .get_task_comments(&task_id)
+        .await?
+        .into_iter()
+        .map(|comment| super::task_comment(comment, redactor))
+        .collect();
+    Ok(Json(Envelope::new(comments)))
+}
+
+async fn list_attempts(
+    State(state): State<ApiState>,
+    Path(task_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let task_id = TaskId::from_str(&task_id)?;
+    Ok(Json(Envelope::new(super::task_attempts(&state, &task_id).await?)))
+}
+
+async fn statistics(State(state): State<ApiState>) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(Envelope::new(super::statistics(&state).await?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_negotiation_and_cursor() {
+        assert_eq!(path_version("/api/v1/tasks"), Some(1));
+        assert_eq!(path_version("/api/v2"), Some(2));
+        assert_eq!(path_version("/health"), None);
+
+        assert_eq!(
+            accepted_versions("application/json, application/vnd.task-orchestrator.v2+json; q=0.9"),
+            vec![2]
+        );
+        assert!(accepted_versions("*/*").is_empty());
+
+        let cursor = encode_cursor(150);
+        assert_eq!(decode_cursor(&cursor).unwrap(), 150);
+        assert!(decode_cursor("not-a-cursor").is_err());
+    }
+}
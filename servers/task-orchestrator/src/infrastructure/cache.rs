@@ -0,0 +1,311 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use moka::future::Cache;
+use serde::{Deserialize, Serialize};
+
+use crate::config::CacheConfig;
+use crate::domain::{MetadataSchema, PipelineRun, Task, TaskComment, TaskHistory, TaskId};
+use crate::errors::AppResult;
+use crate::models::{LoadSample, SlaSample, TaskFilter, TaskStatistics};
+use super::database::TaskRepository;
+
+/// 缓存统计快照
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CacheStats {
+    pub task_hits: u64,
+    pub task_misses: u64,
+    pub list_hits: u64,
+    pub list_misses: u64,
+    pub invalidations: u64,
+    pub hit_rate: f64,
+}
+
+#[derive(Default)]
+struct CacheCounters {
+    task_hits: AtomicU64,
+    task_misses: AtomicU64,
+    list_hits: AtomicU64,
+    list_misses: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+/// 只读直通缓存任务仓库
+///
+/// 缓存 `get_task` 和 `list_tasks` 的结果，所有写操作先写入内层仓库再使缓存失效。
+/// 写操作会递增代数，读取期间代数变化时不回填缓存，避免并发写入后缓存旧值。
+/// 缓存只在本进程内有效，多实例部署时其他实例的写入要等TTL过期后才可见。
+pub struct CachedTaskRepository {
+    inner: Arc<dyn TaskRepository>,
+    tasks: Cache<TaskId, Task>,
+    lists: Cache<String, (Vec<Task>, u64)>,
+    generation: AtomicU64,
+    counters: CacheCounters,
+}
+
+impl CachedTaskRepository {
+    /// 根据缓存配置包装内层仓库
+    pub fn new(inner: Arc<dyn TaskRepository>, config: &CacheConfig) -> Self {
+        let tasks = Cache::builder()
+            .max_capacity(config.cache_size)
+            .time_to_live(Duration::from_secs(config.cache_ttl.max(1)))
+            .build();
+        // 列表缓存按包含的任务数计权，与单任务缓存共用容量上限
+        let lists = Cache::builder()
+            .max_capacity(config.cache_size)
+            .weigher(|_: &String, (tasks, _): &(Vec<Task>, u64)| tasks.len().saturating_add(1).min(u32::MAX as usize) as u32)
+            .time_to_live(Duration::from_secs(config.list_cache_ttl.max(1)))
+            .build();
+
+        Self {
+            inner,
+            tasks,
+            lists,
+            generation: AtomicU64::new(0),
+            counters: CacheCounters::default(),
+        }
+    }
+
+    /// 获取统计快照
+    pub fn stats(&self) -> CacheStats {
+        let task_hits = self.counters.task_hits.load(Ordering::Relaxed);
+        let task_misses = self.counters.task_misses.load(Ordering::Relaxed);
+        let list_hits = self.counters.list_hits.load(Ordering::Relaxed);
+        let list_misses = self.counters.list_misses.load(Ordering::Relaxed);
+        let lookups = task_hits + task_misses + list_hits + list_misses;
+
+        CacheStats {
+            task_hits,
+            task_misses,
+            list_hits,
+            list_misses,
+            invalidations: self.counters.invalidations.load(Ordering::Relaxed),
+            hit_rate: if lookups > 0 {
+                (task_hits + list_hits) as f64 / lookups as f64
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// 清空全部缓存，内存压力下由内存自监控调用
+    pub fn evict_all(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.tasks.invalidate_all();
+        self.lists.invalidate_all();
+    }
+
+    /// 写操作完成后使单个任务和全部列表缓存失效
+    async fn invalidate(&self, task_id: Option<&TaskId>) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        match task_id {
+            Some(task_id) => self.tasks.invalidate(task_id).await,
+            None => self.tasks.invalidate_all(),
+        }
+        self.lists.invalidate_all();
+        self.counters.invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 过滤条件的缓存键
+    fn list_key(filter: &TaskFilter) -> String {
+        format!("{:?}", filter)
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskRepository for CachedTaskRepository {
+    async fn create_task(&self, task: &Task) -> AppResult<TaskId> {
+        let task_id = self.inner.create_task(task).await?;
+        self.invalidate(Some(&task_id)).await;
+        Ok(task_id)
+    }
+
+    async fn get_task(&self, task_id: &TaskId) -> AppResult<Option<Task>> {
+        if let Some(task) = self.tasks.get(task_id).await {
+            self.counters.task_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(Some(task));
+        }
+        self.counters.task_misses.fetch_add(1, Ordering::Relaxed);
+
+        let generation = self.generation.load(Ordering::SeqCst);
+        let task = self.inner.get_task(task_id).await?;
+        if let Some(task) = &task {
+            if self.generation.load(Ordering::SeqCst) == generation {
+                self.tasks.insert(*task_id, task.clone()).await;
+                // 回填与写入交错时撤销回填
+                if self.generation.load(Ordering::SeqCst) != generation {
+                    self.tasks.invalidate(task_id).await;
+                }
+            }
+        }
+        Ok(task)
+    }
+
+    async fn update_task(&self, task: &Task) -> AppResult<()> {
+        let result = self.inner.update_task(task).await;
+        self.invalidate(Some(&task.id)).await;
+        result
+    }
+
+    async fn delete_task(&self, task_id: &TaskId) -> AppResult<()> {
+        let result = self.inner.delete_task(task_id).await;
+        self.invalidate(Some(task_id)).await;
+        result
+    }
+
+    async fn get_next_task(
+        &self,
+        work_directory: &str,
+        worker_id: &str,
+        capabilities: Option<&[String]>,
+    ) -> AppResult<Option<Task>> {
+        // 获取任务会修改任务状态
+        let task = self.inner.get_next_task(work_directory, worker_id, capabilities).await?;
+        if let Some(task) = &task {
+            self.invalidate(Some(&task.id)).await;
+        }
+        Ok(task)
+    }
+
+    async fn list_tasks(&self, filter: &TaskFilter) -> AppResult<(Vec<Task>, u64)> {
+        let key = Self::list_key(filter);
+        if let Some(page) = self.lists.get(&key).await {
+            self.counters.list_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(page);
+        }
+        self.counters.list_misses.fetch_add(1, Ordering::Relaxed);
+
+        let generation = self.generation.load(Ordering::SeqCst);
+        let page = self.inner.list_tasks(filter).await?;
+        if self.generation.load(Ordering::SeqCst) == generation {
+            self.lists.insert(key.clone(), page.clone()).await;
+            if self.generation.load(Ordering::SeqCst) != generation {
+                self.lists.invalidate(&key).await;
+            }
+        }
+        Ok(page)
+    }
+
+    async fn get_statistics(&self) -> AppResult<TaskStatistics> {
+        self.inner.get_statistics().await
+    }
+
+    async fn waiting_tag_sets(&self) -> AppResult<Vec<(Vec<String>, u64)>> {
+        self.inner.waiting_tag_sets().await
+    }
+
+    async fn reconcile_statistics(&self) -> AppResult<u64> {
+        self.inner.reconcile_statistics().await
+    }
+
+    async fn sla_samples(&self, created_after: DateTime<Utc>) -> AppResult<Vec<SlaSample>> {
+        self.inner.sla_samples(created_after).await
+    }
+
+    async fn load_sample(&self, since: DateTime<Utc>) -> AppResult<LoadSample> {
+        self.inner.load_sample(since).await
+    }
+
+    async fn archive_tasks(&self, older_than: DateTime<Utc>, limit: u32) -> AppResult<Vec<TaskId>> {
+        let result = self.inner.archive_tasks(older_than, limit).await;
+        self.invalidate(None).await;
+        result
+    }
+
+    async fn create_task_history(&self, history: &TaskHistory) -> AppResult<u64> {
+        self.inner.create_task_history(history).await
+    }
+
+    async fn create_task_histories(&self, histories: &[TaskHistory]) -> AppResult<u64> {
+        self.inner.create_task_histories(histories).await
+    }
+
+    async fn get_task_history(&self, task_id: &TaskId) -> AppResult<Vec<TaskHistory>> {
+        self.inner.get_task_history(task_id).await
+    }
+
+    async fn add_task_comment(&self, comment: &TaskComment) -> AppResult<u64> {
+        self.inner.add_task_comment(comment).await
+    }
+
+    async fn get_task_comments(&self, task_id: &TaskId) -> AppResult<Vec<TaskComment>> {
+        self.inner.get_task_comments(task_id).await
+    }
+
+    async fn save_metadata_schema(&self, schema: &MetadataSchema) -> AppResult<()> {
+        self.inner.save_metadata_schema(schema).await
+    }
+
+    async fn delete_metadata_schema(&self, namespace: &str) -> AppResult<bool> {
+        self.inner.delete_metadata_schema(namespace).await
+    }
+
+    async fn list_metadata_schemas(&self) -> AppResult<Vec<MetadataSchema>> {
+        self.inner.list_metadata_schemas().await
+    }
+
+    async fn save_pipeline_run(&self, run: &PipelineRun) -> AppResult<()> {
+        self.inner.save_pipeline_run(run).await
+    }
+
+    async fn get_pipeline_run(&self, run_id: &str) -> AppResult<Option<PipelineRun>> {
+        self.inner.get_pipeline_run(run_id).await
+    }
+
+    async fn cleanup_expired_tasks(&self, older_than: DateTime<Utc>) -> AppResult<u64> {
+        let result = self.inner.cleanup_expired_tasks(older_than).await;
+        self.invalidate(None).await;
+        result
+    }
+
+    async fn retry_failed_tasks(&self, max_retries: u32) -> AppResult<Vec<TaskId>> {
+        let result = self.inner.retry_failed_tasks(max_retries).await;
+        self.invalidate(None).await;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Prompt, TaskPriority, TaskStatus, WorkDirectory, WorkerId};
+    use crate::infrastructure::SqliteTaskRepository;
+
+    #[tokio::test]
+    async fn test_read_through_and_invalidation() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let inner: Arc<dyn TaskRepository> = Arc::new(SqliteTaskRepository::with_pool(pool).await.unwrap());
+        let repo = CachedTaskRepository::new(inner, &CacheConfig::default());
+
+        let mut task = Task::new(
+            WorkDirectory::new("/tmp/cache".to_string()).unwrap(),
+            Prompt::new("cached".to_string()).unwrap(),
+            TaskPriority::Medium,
+            vec![],
+        );
+        repo.create_task(&task).await.unwrap();
+
+        let filter = TaskFilter::new();
+        assert!(repo.get_task(&task.id).await.unwrap().is_some());
+        assert!(repo.get_task(&task.id).await.unwrap().is_some());
+        assert_eq!(repo.list_tasks(&filter).await.unwrap().1, 1);
+        assert_eq!(repo.list_tasks(&filter).await.unwrap().1, 1);
+
+        let stats = repo.stats();
+        assert_eq!((stats.task_hits, stats.task_misses), (1, 1));
+        assert_eq!((stats.list_hits, stats.list_misses), (1, 1));
+        assert_eq!(stats.hit_rate, 0.5);
+
+        // 写入后读取到最新状态
+        task.start(WorkerId::new("worker-1".to_string()).unwrap()).unwrap();
+        repo.update_task(&task).await.unwrap();
+        assert_eq!(repo.get_task(&task.id).await.unwrap().unwrap().status, TaskStatus::Working);
+        assert_eq!(repo.list_tasks(&filter).await.unwrap().0[0].status, TaskStatus::Working);
+    }
+}
@@ -0,0 +1,164 @@
+//! 冷存储
+//!
+//! 已结束较久的任务从热数据库移出，连同历史和评论按批写入gzip压缩的JSON Lines段文件，
+//! 热数据库的 `cold_tasks` 表只保留任务ID到段文件的索引，按ID查询时透明地从段文件读取。
+//! 段文件中的记录与数据库行相同，启用字段加密时敏感列保持加密。段文件写入后不再修改。
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{AppError, AppResult};
+use crate::models::{TaskCommentRecord, TaskHistoryRecord, TaskRecord};
+
+/// 段文件扩展名
+const SEGMENT_SUFFIX: &str = ".jsonl.gz";
+
+/// 冷存储中的一个任务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColdTaskEntry {
+    pub task: TaskRecord,
+    #[serde(default)]
+    pub history: Vec<TaskHistoryRecord>,
+    #[serde(default)]
+    pub comments: Vec<TaskCommentRecord>,
+}
+
+/// 冷存储段文件目录
+#[derive(Debug, Clone)]
+pub struct ColdStorage {
+    directory: PathBuf,
+}
+
+impl ColdStorage {
+    /// 使用指定目录，不存在时创建
+    pub fn new(directory: impl Into<PathBuf>) -> AppResult<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory).map_err(|e| {
+            AppError::Internal(format!("Failed to create cold storage directory {}: {}", directory.display(), e))
+        })?;
+        Ok(Self { directory })
+    }
+
+    /// 写入一个新的段文件，返回段文件名
+    ///
+    /// 先写入临时文件并同步到磁盘，再重命名为正式文件名，读取方不会看到写了一半的段。
+    pub async fn write_segment(&self, entries: Vec<ColdTaskEntry>) -> AppResult<String> {
+        let name = format!(
+            "tasks-{}-{}{}",
+            chrono::Utc::now().format("%Y%m%d%H%M%S"),
+            uuid::Uuid::new_v4().simple(),
+            SEGMENT_SUFFIX
+        );
+        let path = self.directory.join(&name);
+        tokio::task::spawn_blocking(move || write_segment_file(&path, &entries))
+            .await
+            .map_err(|e| AppError::Internal(format!("Cold storage writer panicked: {}", e)))??;
+        Ok(name)
+    }
+
+    /// 从段文件中读取指定任务
+    pub async fn read_task(&self, segment: &str, task_id: &str) -> AppResult<Option<ColdTaskEntry>> {
+        let path = self.segment_path(segment)?;
+        let task_id = task_id.to_string();
+        tokio::task::spawn_blocking(move || find_in_segment(&path, &task_id))
+            .await
+            .map_err(|e| AppError::Internal(format!("Cold storage reader panicked: {}", e)))?
+    }
+
+    /// 段文件路径，拒绝索引中不是本模块生成的文件名
+    fn segment_path(&self, segment: &str) -> AppResult<PathBuf> {
+        if !segment.ends_with(SEGMENT_SUFFIX) || segment.contains(['/', '\\']) || segment.starts_with('.') {
+            return Err(AppError::Internal(format!("Invalid cold storage segment name '{}'", segment)));
+        }
+        Ok(self.directory.join(segment))
+    }
+}
+
+fn io_error(path: &Path, e: impl std::fmt::Display) -> AppError {
+    AppError::Internal(format!("Cold storage segment {}: {}", path.display(), e))
+}
+
+fn write_segment_file(path: &Path, entries: &[ColdTaskEntry]) -> AppResult<()> {
+    let tmp = path.with_extension("tmp");
+    let file = std::fs::File::create(&tmp).map_err(|e| io_error(&tmp, e))?;
+    let mut encoder = GzEncoder::new(std::io::BufWriter::new(file), Compression::default());
+    for entry in entries {
+        serde_json::to_writer(&mut encoder, entry).map_err(|e| io_error(&tmp, e))?;
+        encoder.write_all(b"\n").map_err(|e| io_error(&tmp, e))?;
+    }
+    let file = encoder
+        .finish()
+        .and_then(|writer| writer.into_inner().map_err(|e| e.into_error()))
+        .map_err(|e| io_error(&tmp, e))?;
+    file.sync_all().map_err(|e| io_error(&tmp, e))?;
+    std::fs::rename(&tmp, path).map_err(|e| io_error(path, e))
+}
+
+fn find_in_segment(path: &Path, task_id: &str) -> AppResult<Option<ColdTaskEntry>> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(AppError::Internal(format!("Cold storage segment {} is missing", path.display())));
+        }
+        Err(e) => return Err(io_error(path, e)),
+    };
+    // 先按任务ID子串过滤，只解析可能匹配的行
+    let needle = format!("\"task_id\":\"{}\"", task_id);
+    for line in BufReader::new(GzDecoder::new(file)).lines() {
+        let line = line.map_err(|e| io_error(path, e))?;
+        if !line.contains(&needle) {
+            continue;
+        }
+        let entry: ColdTaskEntry = serde_json::from_str(&line).map_err(|e| io_error(path, e))?;
+        if entry.task.task_id == task_id {
+            return Ok(Some(entry));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Prompt, Task, TaskPriority, WorkDirectory};
+
+    fn entry(prompt: &str) -> ColdTaskEntry {
+        let task = Task::new(
+            WorkDirectory::new("/repo".to_string()).unwrap(),
+            Prompt::new(prompt.to_string()).unwrap(),
+            TaskPriority::Low,
+            vec![],
+        );
+        ColdTaskEntry { task: TaskRecord::from_domain(&task).unwrap(), history: vec![], comments: vec![] }
+    }
+
+    #[tokio::test]
+    async fn test_segment_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let directory = dir.path().join("cold");
+        let storage = ColdStorage::new(&directory).unwrap();
+        let entries = vec![entry("first"), entry("second")];
+        let second_id = entries[1].task.task_id.clone();
+
+        let segment = storage.write_segment(entries).await.unwrap();
+        assert!(segment.ends_with(SEGMENT_SUFFIX));
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 1);
+
+        let found = storage.read_task(&segment, &second_id).await.unwrap().unwrap();
+        assert_eq!(found.task.prompt, "second");
+        assert!(storage.read_task(&segment, "missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_foreign_segment_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = ColdStorage::new(dir.path()).unwrap();
+        assert!(storage.read_task("../tasks.db", "id").await.is_err());
+        assert!(storage.read_task("other.jsonl.gz", "id").await.is_err());
+    }
+}
@@ -1,12 +1,18 @@
-use sqlx::{Sqlite, Pool, sqlite::SqliteConnectOptions};
+use sqlx::{Sqlite, SqliteConnection, Pool, sqlite::SqliteConnectOptions};
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
 
-use crate::domain::{Task, TaskId, TaskHistory};
-use crate::models::{TaskRecord, TaskHistoryRecord, TaskFilter, TaskStatistics, LockRecord};
+use crate::domain::{Task, TaskId, TaskStatus, TaskHistory, TaskComment, MetadataSchema, PipelineRun};
+use crate::models::{TaskRecord, TaskHistoryRecord, TaskCommentRecord, MetadataSchemaRecord, PipelineRunRecord, TaskFilter, TaskStatistics, SlaSample, LoadSample, LockRecord};
 use crate::errors::{AppError, AppResult};
+use task_store::{StoreError, StoreResult, StoredTask, TaskChange, TaskPhase, TaskPredicate};
 use crate::config::DatabaseConfig;
+use super::encryption::FieldCipher;
+use super::cold_storage::{ColdStorage, ColdTaskEntry};
+use super::pool::ManagedPool;
+use crate::utils::clock::{system_clock, Clock, Deadline};
 
 /// 任务仓库特征
 #[async_trait::async_trait]
@@ -24,7 +30,14 @@ pub trait TaskRepository: Send + Sync {
     async fn delete_task(&self, task_id: &TaskId) -> AppResult<()>;
     
     /// 获取下一个待处理任务
-    async fn get_next_task(&self, work_directory: &str, worker_id: &str) -> AppResult<Option<Task>>;
+    ///
+    /// 指定 `capabilities` 时只返回标签全部包含在其中的任务，`None` 表示不按标签过滤。
+    async fn get_next_task(
+        &self,
+        work_directory: &str,
+        worker_id: &str,
+        capabilities: Option<&[String]>,
+    ) -> AppResult<Option<Task>>;
     
     /// 查询任务列表
     async fn list_tasks(&self, filter: &TaskFilter) -> AppResult<(Vec<Task>, u64)>;
@@ -32,131 +45,436 @@ pub trait TaskRepository: Send + Sync {
     /// 获取任务统计
     async fn get_statistics(&self) -> AppResult<TaskStatistics>;
     
+    /// 按标签组合统计等待中的任务数，返回 (标签, 任务数)
+    async fn waiting_tag_sets(&self) -> AppResult<Vec<(Vec<String>, u64)>> {
+        Ok(Vec::new())
+    }
+    
+    /// 校验统计计数器与任务表是否一致，不一致时修复，返回修复的计数器数量
+    async fn reconcile_statistics(&self) -> AppResult<u64> {
+        Ok(0)
+    }
+    
+    /// 获取 `created_after` 之后创建的任务和仍在等待或执行的任务的时间点，用于SLA计算
+    async fn sla_samples(&self, _created_after: DateTime<Utc>) -> AppResult<Vec<SlaSample>> {
+        Ok(Vec::new())
+    }
+    
+    /// 统计 `since` 之后的任务到达数和执行耗时，用于容量预测
+    async fn load_sample(&self, _since: DateTime<Utc>) -> AppResult<LoadSample> {
+        Ok(LoadSample::default())
+    }
+    
+    /// 将 `older_than` 之前结束的任务移入冷存储，每次最多 `limit` 个，返回移动的任务ID
+    async fn archive_tasks(&self, _older_than: DateTime<Utc>, _limit: u32) -> AppResult<Vec<TaskId>> {
+        Ok(Vec::new())
+    }
+    
     /// 创建任务历史
     async fn create_task_history(&self, history: &TaskHistory) -> AppResult<u64>;
     
+    /// 批量创建任务历史，返回写入条数
+    async fn create_task_histories(&self, histories: &[TaskHistory]) -> AppResult<u64> {
+        for history in histories {
+            self.create_task_history(history).await?;
+        }
+        Ok(histories.len() as u64)
+    }
+    
     /// 获取任务历史
     async fn get_task_history(&self, task_id: &TaskId) -> AppResult<Vec<TaskHistory>>;
     
-    /// 清理过期任务
-    async fn cleanup_expired_tasks(&self, older_than: DateTime<Utc>) -> AppResult<u64>;
+    /// 添加任务评论，返回评论ID
+    async fn add_task_comment(&self, comment: &TaskComment) -> AppResult<u64>;
     
-    /// 重试失败任务
-    async fn retry_failed_tasks(&self, max_retries: u32) -> AppResult<u64>;
-}
-
-/// 锁管理器特征
-#[async_trait::async_trait]
-pub trait LockManager: Send + Sync {
-    /// 尝试获取锁
-    async fn try_acquire(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> AppResult<bool>;
+    /// 获取任务评论，按添加顺序排列
+    async fn get_task_comments(&self, task_id: &TaskId) -> AppResult<Vec<TaskComment>>;
+    
+    /// 保存元数据模式，同一命名空间已存在时覆盖
+    async fn save_metadata_schema(&self, _schema: &MetadataSchema) -> AppResult<()> {
+        Ok(())
+    }
+    
+    /// 删除元数据模式，返回是否存在
+    async fn delete_metadata_schema(&self, _namespace: &str) -> AppResult<bool> {
+        Ok(false)
+    }
+    
+    /// 获取所有元数据模式
+    async fn list_metadata_schemas(&self) -> AppResult<Vec<MetadataSchema>> {
+        Ok(Vec::new())
+    }
+    
+    /// 保存流水线运行记录，同一运行ID已存在时覆盖
+    async fn save_pipeline_run(&self, _run: &PipelineRun) -> AppResult<()> {
+        Ok(())
+    }
     
-    /// 释放锁
-    async fn release(&self, resource_id: &str, owner_id: &str) -> AppResult<bool>;
+    /// 获取流水线运行记录
+    async fn get_pipeline_run(&self, _run_id: &str) -> AppResult<Option<PipelineRun>> {
+        Ok(None)
+    }
     
-    /// 检查锁是否存在
-    async fn check_lock(&self, resource_id: &str) -> AppResult<Option<String>>;
+    /// 清理过期任务
+    async fn cleanup_expired_tasks(&self, older_than: DateTime<Utc>) -> AppResult<u64>;
     
-    /// 清理过期锁
-    async fn cleanup_expired_locks(&self) -> AppResult<u64>;
+    /// 重试失败任务，返回重新排队的任务ID
+    async fn retry_failed_tasks(&self, max_retries: u32) -> AppResult<Vec<TaskId>>;
 }
 
+/// 锁管理器特征，定义在各编排服务共用的 `task-store` 中
+pub use task_store::LockManager;
+
 /// 锁管理器盒装trait，用于动态分发
 pub type DynLockManager = Arc<dyn LockManager>;
 
+/// 单条批量插入语句包含的历史记录数，避免超过SQLite的参数数量上限
+const HISTORY_INSERT_CHUNK: usize = 100;
+
+/// 已写入段文件、等待从热表移出的任务
+pub(crate) struct ColdMove {
+    pub(crate) task_id: String,
+    version: i32,
+    status: String,
+    completed_at: Option<DateTime<Utc>>,
+}
+
 /// SQLite任务仓库实现
 pub struct SqliteTaskRepository {
-    pool: Pool<Sqlite>,
+    pool: Arc<ManagedPool>,
+    cipher: Option<Arc<FieldCipher>>,
+    cold_storage: Option<Arc<ColdStorage>>,
 }
 
 impl SqliteTaskRepository {
     /// 创建新的仓库实例
     pub async fn new(config: &DatabaseConfig) -> AppResult<Self> {
         let pool = Self::create_pool(config).await?;
-        
-        // 运行数据库迁移
-        Self::run_migrations(&pool).await?;
-        
-        Ok(Self { pool })
+        Self::with_pool(pool).await
     }
     
     /// 使用现有的连接池创建仓库实例
     pub async fn with_pool(pool: Pool<Sqlite>) -> AppResult<Self> {
+        Self::with_managed_pool(Arc::new(ManagedPool::new(pool))).await
+    }
+    
+    /// 使用带健康监控的共享连接池创建仓库实例
+    pub async fn with_managed_pool(pool: Arc<ManagedPool>) -> AppResult<Self> {
         // 运行数据库迁移
-        Self::run_migrations(&pool).await?;
+        Self::run_migrations(&pool.get()).await?;
         
-        Ok(Self { pool })
+        Ok(Self { pool, cipher: None, cold_storage: None })
     }
     
-    /// 创建数据库连接池
-    async fn create_pool(config: &DatabaseConfig) -> AppResult<Pool<Sqlite>> {
-        let mut options = SqliteConnectOptions::from_str(&config.url)?;
+    /// 设置字段加密器，提示词、结果和元数据列将加密存储
+    pub fn with_cipher(mut self, cipher: Arc<FieldCipher>) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+    
+    /// 设置冷存储，按ID查询时热表中没有的任务从冷存储读取
+    pub fn with_cold_storage(mut self, cold_storage: Arc<ColdStorage>) -> Self {
+        self.cold_storage = Some(cold_storage);
+        self
+    }
+    
+    /// 从冷存储读取任务，任务不在冷存储索引中时返回 `None`
+    async fn cold_entry(&self, task_id: &TaskId) -> AppResult<Option<ColdTaskEntry>> {
+        let Some(cold_storage) = &self.cold_storage else {
+            return Ok(None);
+        };
+        let segment = sqlx::query_scalar::<_, String>("SELECT segment FROM cold_tasks WHERE task_id = ?")
+            .bind(task_id.to_string())
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?;
+        match segment {
+            Some(segment) => cold_storage.read_task(&segment, &task_id.to_string()).await,
+            None => Ok(None),
+        }
+    }
+    
+    /// 把结束时间早于 `older_than` 的至多 `limit` 个任务连同历史和评论写入新的段文件
+    ///
+    /// 返回段文件名和待从热表移出的任务；未配置冷存储或没有符合条件的任务时返回 `None`。
+    pub(crate) async fn write_cold_segment(
+        &self,
+        older_than: DateTime<Utc>,
+        limit: u32,
+    ) -> AppResult<Option<(String, Vec<ColdMove>)>> {
+        let Some(cold_storage) = &self.cold_storage else {
+            return Ok(None);
+        };
         
-        // 配置SQLite选项
-        options = options
-            .create_if_missing(true)
-            .journal_mode(if config.enable_wal_mode {
-                sqlx::sqlite::SqliteJournalMode::Wal
-            } else {
-                sqlx::sqlite::SqliteJournalMode::Delete
-            })
-            .synchronous(sqlx::sqlite::SqliteSynchronous::Full)
-            .busy_timeout(std::time::Duration::from_secs(config.busy_timeout));
+        let tasks = sqlx::query_as::<_, TaskRecord>(
+            "SELECT * FROM tasks
+             WHERE status IN ('completed', 'failed', 'cancelled') AND completed_at < ?
+             ORDER BY completed_at
+             LIMIT ?"
+        )
+        .bind(older_than)
+        .bind(limit)
+        .fetch_all(&mut *self.pool.acquire().await?)
+        .await?;
+        if tasks.is_empty() {
+            return Ok(None);
+        }
         
-        // 设置PRAGMA
-        if config.enable_foreign_keys {
-            options = options.pragma("foreign_keys", "on");
+        let mut entries = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let history = sqlx::query_as::<_, TaskHistoryRecord>(
+                "SELECT * FROM task_history WHERE task_id = ? ORDER BY changed_at DESC"
+            )
+            .bind(&task.task_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?;
+            let comments = sqlx::query_as::<_, TaskCommentRecord>(
+                "SELECT * FROM task_comments WHERE task_id = ? ORDER BY id"
+            )
+            .bind(&task.task_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?;
+            entries.push(ColdTaskEntry { task, history, comments });
         }
         
-        options = options
-            .pragma("temp_store", "memory")
-            .pragma("mmap_size", config.mmap_size.to_string())
-            .pragma("cache_size", config.cache_size.to_string())
-            .pragma("page_size", config.page_size.to_string());
+        let moves = entries
+            .iter()
+            .map(|e| ColdMove {
+                task_id: e.task.task_id.clone(),
+                version: e.task.version,
+                status: e.task.status.clone(),
+                completed_at: e.task.completed_at,
+            })
+            .collect();
+        let segment = cold_storage.write_segment(entries).await?;
+        Ok(Some((segment, moves)))
+    }
+    
+    /// 在给定连接上删除已写入段文件的任务的热数据并记录冷存储索引，返回是否移出
+    ///
+    /// 段文件写入期间被修改（如重试）的任务版本号或状态已变化，保留在热表中；
+    /// 段文件里的旧副本没有索引指向，不会被读取。
+    pub(crate) async fn move_to_cold_in(
+        &self,
+        conn: &mut SqliteConnection,
+        segment: &str,
+        moved: &ColdMove,
+    ) -> AppResult<bool> {
+        let deleted = sqlx::query(
+            "DELETE FROM tasks
+             WHERE task_id = ? AND version = ? AND status IN ('completed', 'failed', 'cancelled')"
+        )
+        .bind(&moved.task_id)
+        .bind(moved.version)
+        .execute(&mut *conn)
+        .await?;
+        if deleted.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query("DELETE FROM task_history WHERE task_id = ?").bind(&moved.task_id).execute(&mut *conn).await?;
+        sqlx::query("DELETE FROM task_comments WHERE task_id = ?").bind(&moved.task_id).execute(&mut *conn).await?;
+        sqlx::query(
+            "INSERT OR REPLACE INTO cold_tasks (task_id, segment, status, completed_at) VALUES (?, ?, ?, ?)"
+        )
+        .bind(&moved.task_id)
+        .bind(segment)
+        .bind(&moved.status)
+        .bind(moved.completed_at)
+        .execute(&mut *conn)
+        .await?;
+        Ok(true)
+    }
+    
+    /// 解密评论记录并转换为领域模型
+    fn open_comment(&self, mut record: TaskCommentRecord) -> AppResult<TaskComment> {
+        if let Some(cipher) = &self.cipher {
+            record.text = cipher.decrypt(&record.text)?;
+            record.data = record.data.as_deref().map(|v| cipher.decrypt(v)).transpose()?;
+        }
+        record.to_domain().map_err(|e| AppError::Internal(e.to_string()))
+    }
+    
+    /// 加密记录中的敏感列
+    fn seal(&self, mut record: TaskRecord) -> AppResult<TaskRecord> {
+        if let Some(cipher) = &self.cipher {
+            record.prompt = cipher.encrypt(&record.prompt)?;
+            record.result = record.result.as_deref().map(|v| cipher.encrypt(v)).transpose()?;
+            record.metadata = record.metadata.as_deref().map(|v| cipher.encrypt(v)).transpose()?;
+        }
+        Ok(record)
+    }
+    
+    /// 解密记录中的敏感列并转换为领域模型
+    fn open(&self, mut record: TaskRecord) -> AppResult<Task> {
+        if let Some(cipher) = &self.cipher {
+            record.prompt = cipher.decrypt(&record.prompt)?;
+            record.result = record.result.as_deref().map(|v| cipher.decrypt(v)).transpose()?;
+            record.metadata = record.metadata.as_deref().map(|v| cipher.decrypt(v)).transpose()?;
+        }
+        Ok(record.to_domain()?)
+    }
+    
+    /// 将明文行和旧密钥加密的行重新加密为当前密钥，返回更新的行数
+    ///
+    /// 按版本号条件更新，期间被并发修改的行会跳过，可重复执行。
+    pub async fn reencrypt_existing(&self) -> AppResult<u64> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(0);
+        };
         
-        // 创建连接池
-        let pool = sqlx::sqlite::SqlitePoolOptions::new()
-            .max_connections(config.max_connections)
-            .min_connections(config.min_connections)
-            .acquire_timeout(std::time::Duration::from_secs(config.connection_timeout))
-            .idle_timeout(std::time::Duration::from_secs(config.idle_timeout))
-            .max_lifetime(std::time::Duration::from_secs(config.max_lifetime))
-            .connect_with(options)
+        let rows = sqlx::query_as::<_, (String, String, Option<String>, Option<String>, i32)>(
+            "SELECT task_id, prompt, result, metadata, version FROM tasks"
+        )
+        .fetch_all(&mut *self.pool.acquire().await?)
+        .await?;
+        
+        let mut updated = 0;
+        for (task_id, prompt, result, metadata, version) in rows {
+            let new_prompt = cipher.reencrypt(&prompt)?;
+            let new_result = result.as_deref().map(|v| cipher.reencrypt(v)).transpose()?.flatten();
+            let new_metadata = metadata.as_deref().map(|v| cipher.reencrypt(v)).transpose()?.flatten();
+            if new_prompt.is_none() && new_result.is_none() && new_metadata.is_none() {
+                continue;
+            }
+            
+            let affected = sqlx::query(
+                "UPDATE tasks SET prompt = ?, result = ?, metadata = ? WHERE task_id = ? AND version = ?"
+            )
+            .bind(new_prompt.unwrap_or(prompt))
+            .bind(new_result.or(result))
+            .bind(new_metadata.or(metadata))
+            .bind(&task_id)
+            .bind(version)
+            .execute(&mut *self.pool.acquire().await?)
             .await?;
+            updated += affected.rows_affected();
+        }
         
-        Ok(pool)
+        let comments = sqlx::query_as::<_, (i64, String, Option<String>)>(
+            "SELECT id, text, data FROM task_comments"
+        )
+        .fetch_all(&mut *self.pool.acquire().await?)
+        .await?;
+        
+        for (id, text, data) in comments {
+            let new_text = cipher.reencrypt(&text)?;
+            let new_data = data.as_deref().map(|v| cipher.reencrypt(v)).transpose()?.flatten();
+            if new_text.is_none() && new_data.is_none() {
+                continue;
+            }
+            
+            // 评论写入后不再修改，无需版本条件
+            let affected = sqlx::query("UPDATE task_comments SET text = ?, data = ? WHERE id = ?")
+                .bind(new_text.unwrap_or(text))
+                .bind(new_data.or(data))
+                .bind(id)
+                .execute(&mut *self.pool.acquire().await?)
+                .await?;
+            updated += affected.rows_affected();
+        }
+        
+        Ok(updated)
     }
     
-    /// 运行数据库迁移
-    async fn run_migrations(pool: &Pool<Sqlite>) -> AppResult<()> {
-        sqlx::migrate!("./migrations").run(pool).await?;
+    /// 在给定连接（可以是事务）上插入评论，返回评论ID；评论ID非零时（从主实例复制的评论）沿用原ID
+    pub(crate) async fn add_task_comment_in(&self, conn: &mut SqliteConnection, comment: &TaskComment) -> AppResult<u64> {
+        let mut record = TaskCommentRecord::from_domain(comment)?;
+        if let Some(cipher) = &self.cipher {
+            record.text = cipher.encrypt(&record.text)?;
+            record.data = record.data.as_deref().map(|v| cipher.encrypt(v)).transpose()?;
+        }
+        
+        let result = sqlx::query(
+            r#"
+            INSERT INTO task_comments (id, task_id, author, text, data, created_at)
+            VALUES (NULLIF(?, 0), ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(record.id)
+        .bind(&record.task_id)
+        .bind(&record.author)
+        .bind(&record.text)
+        .bind(&record.data)
+        .bind(record.created_at)
+        .execute(&mut *conn)
+        .await?;
+        
+        Ok(result.last_insert_rowid() as u64)
+    }
+    
+    /// 在给定连接上保存元数据模式
+    pub(crate) async fn save_metadata_schema_in(&self, conn: &mut SqliteConnection, schema: &MetadataSchema) -> AppResult<()> {
+        let record = MetadataSchemaRecord::from_domain(schema)?;
+        sqlx::query(
+            r#"
+            INSERT INTO metadata_schemas (namespace, schema, strict, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(namespace) DO UPDATE SET
+                schema = excluded.schema,
+                strict = excluded.strict,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&record.namespace)
+        .bind(&record.schema)
+        .bind(record.strict)
+        .bind(record.updated_at)
+        .execute(&mut *conn)
+        .await?;
+        
         Ok(())
     }
     
-    /// 在事务中执行操作
-    async fn execute_in_transaction<F, T>(&self, operation: F) -> AppResult<T>
-    where
-        F: for<'a> FnOnce(&'a mut sqlx::sqlite::SqliteConnection) -> AppResult<T> + Send,
-        T: Send,
-    {
-        let mut conn = self.pool.begin().await?;
-        let result = operation(&mut conn)?;
-        conn.commit().await?;
-        Ok(result)
+    /// 在给定连接上删除元数据模式，返回是否存在
+    pub(crate) async fn delete_metadata_schema_in(&self, conn: &mut SqliteConnection, namespace: &str) -> AppResult<bool> {
+        let result = sqlx::query("DELETE FROM metadata_schemas WHERE namespace = ?")
+            .bind(namespace)
+            .execute(&mut *conn)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
     }
-}
-
-#[async_trait::async_trait]
-impl TaskRepository for SqliteTaskRepository {
-    async fn create_task(&self, task: &Task) -> AppResult<TaskId> {
-        let task_record = TaskRecord::from_domain(task)?;
+    
+    /// 在给定连接上保存流水线运行记录
+    pub(crate) async fn save_pipeline_run_in(&self, conn: &mut SqliteConnection, run: &PipelineRun) -> AppResult<()> {
+        let record = PipelineRunRecord::from_domain(run)?;
+        sqlx::query(
+            r#"
+            INSERT INTO pipeline_runs (run_id, pipeline, status, steps, skipped, message, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(run_id) DO UPDATE SET
+                status = excluded.status,
+                steps = excluded.steps,
+                skipped = excluded.skipped,
+                message = excluded.message,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&record.run_id)
+        .bind(&record.pipeline)
+        .bind(&record.status)
+        .bind(&record.steps)
+        .bind(&record.skipped)
+        .bind(&record.message)
+        .bind(record.created_at)
+        .bind(record.updated_at)
+        .execute(&mut *conn)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// 在给定连接（可以是事务）上插入任务
+    pub(crate) async fn insert_task_in(&self, conn: &mut SqliteConnection, task: &Task) -> AppResult<()> {
+        let task_record = self.seal(TaskRecord::from_domain(task)?)?;
         
         let result = sqlx::query(
             r#"
             INSERT INTO tasks (task_id, work_directory, prompt, priority, tags, status, 
                               worker_id, created_at, started_at, completed_at, result, 
-                              error_message, retry_count, max_retries, metadata, version)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                              error_message, retry_count, max_retries, metadata, version,
+                              expires_at, cancel_reason, concurrency_group, failure_category,
+                              retry_after)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&task_record.task_id)
@@ -175,32 +493,33 @@ impl TaskRepository for SqliteTaskRepository {
         .bind(&task_record.max_retries)
         .bind(&task_record.metadata)
         .bind(&task_record.version)
-        .execute(&self.pool)
+        .bind(task_record.expires_at)
+        .bind(&task_record.cancel_reason)
+        .bind(&task_record.concurrency_group)
+        .bind(&task_record.failure_category)
+        .bind(task_record.retry_after)
+        .execute(&mut *conn)
         .await?;
         
         if result.rows_affected() == 0 {
             return Err(AppError::Internal("Failed to create task".to_string()));
         }
         
-        Ok(task.id)
+        Ok(())
     }
     
-    async fn get_task(&self, task_id: &TaskId) -> AppResult<Option<Task>> {
-        let record = sqlx::query_as::<_, TaskRecord>(
-            "SELECT * FROM tasks WHERE task_id = ?"
-        )
-        .bind(task_id.to_string())
-        .fetch_optional(&self.pool)
-        .await?;
-        
-        match record {
-            Some(record) => Ok(Some(record.to_domain()?)),
-            None => Ok(None),
-        }
+    /// 在给定连接上读取热表中的任务，不查询冷存储
+    pub(crate) async fn get_hot_task_in(&self, conn: &mut SqliteConnection, task_id: &TaskId) -> AppResult<Option<Task>> {
+        let record = sqlx::query_as::<_, TaskRecord>("SELECT * FROM tasks WHERE task_id = ?")
+            .bind(task_id.to_string())
+            .fetch_optional(&mut *conn)
+            .await?;
+        record.map(|record| self.open(record)).transpose()
     }
     
-    async fn update_task(&self, task: &Task) -> AppResult<()> {
-        let task_record = TaskRecord::from_domain(task)?;
+    /// 在给定连接上按乐观锁更新任务，版本不匹配时返回并发冲突
+    pub(crate) async fn update_task_in(&self, conn: &mut SqliteConnection, task: &Task) -> AppResult<()> {
+        let task_record = self.seal(TaskRecord::from_domain(task)?)?;
         
         let result = sqlx::query(
             r#"
@@ -208,6 +527,8 @@ impl TaskRepository for SqliteTaskRepository {
             SET work_directory = ?, prompt = ?, priority = ?, tags = ?, status = ?,
                 worker_id = ?, started_at = ?, completed_at = ?, result = ?, 
                 error_message = ?, retry_count = ?, max_retries = ?, metadata = ?, 
+                expires_at = ?, cancel_reason = ?, concurrency_group = ?, failure_category = ?,
+                retry_after = ?,
                 version = version + 1, updated_at = CURRENT_TIMESTAMP
             WHERE task_id = ? AND version = ?
             "#,
@@ -225,9 +546,14 @@ impl TaskRepository for SqliteTaskRepository {
         .bind(&task_record.retry_count)
         .bind(&task_record.max_retries)
         .bind(&task_record.metadata)
+        .bind(task_record.expires_at)
+        .bind(&task_record.cancel_reason)
+        .bind(&task_record.concurrency_group)
+        .bind(&task_record.failure_category)
+        .bind(task_record.retry_after)
         .bind(&task_record.task_id)
         .bind(task_record.version - 1)
-        .execute(&self.pool)
+        .execute(&mut *conn)
         .await?;
         
         if result.rows_affected() == 0 {
@@ -237,12 +563,13 @@ impl TaskRepository for SqliteTaskRepository {
         Ok(())
     }
     
-    async fn delete_task(&self, task_id: &TaskId) -> AppResult<()> {
+    /// 在给定连接上删除任务
+    pub(crate) async fn delete_task_in(&self, conn: &mut SqliteConnection, task_id: &TaskId) -> AppResult<()> {
         let result = sqlx::query(
             "DELETE FROM tasks WHERE task_id = ?"
         )
         .bind(task_id.to_string())
-        .execute(&self.pool)
+        .execute(&mut *conn)
         .await?;
         
         if result.rows_affected() == 0 {
@@ -252,30 +579,155 @@ impl TaskRepository for SqliteTaskRepository {
         Ok(())
     }
     
-    async fn get_next_task(&self, work_directory: &str, worker_id: &str) -> AppResult<Option<Task>> {
+    /// 在给定连接上查找下一个可获取的任务，只读取不修改
+    pub(crate) async fn next_task_in(
+        &self,
+        conn: &mut SqliteConnection,
+        work_directory: &str,
+        capabilities: Option<&[String]>,
+    ) -> AppResult<Option<Task>> {
+        self.next_task_record_in(conn, work_directory, capabilities)
+            .await?
+            .map(|record| self.open(record))
+            .transpose()
+    }
+    
+    async fn next_task_record_in(
+        &self,
+        conn: &mut SqliteConnection,
+        work_directory: &str,
+        capabilities: Option<&[String]>,
+    ) -> AppResult<Option<TaskRecord>> {
+        let capabilities = capabilities
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        
+        // 任务的每个标签都必须出现在节点能力中；已超过有效期的任务留待调度器取消；
+        // 失败后退避中的任务到期前跳过；并发组内已有任务在执行时跳过该组的任务；
+        // 优先级列是文本，按权重而不是字典序排序
         let record = sqlx::query_as::<_, TaskRecord>(
             "SELECT * FROM tasks 
              WHERE work_directory = ? AND status = 'waiting' 
-             ORDER BY priority DESC, created_at ASC 
+               AND (expires_at IS NULL OR julianday(expires_at) > julianday('now'))
+               AND (retry_after IS NULL OR julianday(retry_after) <= julianday('now'))
+               AND (? IS NULL OR NOT EXISTS (
+                   SELECT 1 FROM json_each(COALESCE(tasks.tags, '[]')) AS required
+                   WHERE required.value NOT IN (SELECT value FROM json_each(?))
+               ))
+               AND (concurrency_group IS NULL OR NOT EXISTS (
+                   SELECT 1 FROM tasks AS running
+                   WHERE running.concurrency_group = tasks.concurrency_group AND running.status = 'working'
+               ))
+             ORDER BY CASE priority WHEN 'high' THEN 3 WHEN 'medium' THEN 2 ELSE 1 END DESC, created_at ASC 
              LIMIT 1"
         )
         .bind(work_directory)
-        .fetch_optional(&self.pool)
+        .bind(&capabilities)
+        .bind(&capabilities)
+        .fetch_optional(&mut *conn)
+        .await?;
+        
+        Ok(record)
+    }
+    
+    /// 创建数据库连接池
+    async fn create_pool(config: &DatabaseConfig) -> AppResult<Pool<Sqlite>> {
+        let mut options = SqliteConnectOptions::from_str(&config.url)?;
+        
+        // 配置SQLite选项
+        options = options
+            .create_if_missing(true)
+            .journal_mode(if config.enable_wal_mode {
+                sqlx::sqlite::SqliteJournalMode::Wal
+            } else {
+                sqlx::sqlite::SqliteJournalMode::Delete
+            })
+            .synchronous(sqlx::sqlite::SqliteSynchronous::Full)
+            .busy_timeout(std::time::Duration::from_secs(config.busy_timeout));
+        
+        // 设置PRAGMA
+        if config.enable_foreign_keys {
+            options = options.pragma("foreign_keys", "on");
+        }
+        
+        options = options
+            .pragma("temp_store", "memory")
+            .pragma("mmap_size", config.mmap_size.to_string())
+            .pragma("cache_size", config.cache_size.to_string())
+            .pragma("page_size", config.page_size.to_string());
+        
+        // 创建连接池
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .min_connections(config.min_connections)
+            .acquire_timeout(std::time::Duration::from_secs(config.connection_timeout))
+            .idle_timeout(std::time::Duration::from_secs(config.idle_timeout))
+            .max_lifetime(std::time::Duration::from_secs(config.max_lifetime))
+            .connect_with(options)
+            .await?;
+        
+        Ok(pool)
+    }
+    
+    /// 运行数据库迁移
+    async fn run_migrations(pool: &Pool<Sqlite>) -> AppResult<()> {
+        sqlx::migrate!("./migrations").run(pool).await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskRepository for SqliteTaskRepository {
+    async fn create_task(&self, task: &Task) -> AppResult<TaskId> {
+        self.insert_task_in(&mut *self.pool.acquire().await?, task).await?;
+        Ok(task.id)
+    }
+    
+    async fn get_task(&self, task_id: &TaskId) -> AppResult<Option<Task>> {
+        let record = sqlx::query_as::<_, TaskRecord>(
+            "SELECT * FROM tasks WHERE task_id = ?"
+        )
+        .bind(task_id.to_string())
+        .fetch_optional(&mut *self.pool.acquire().await?)
         .await?;
         
+        match record {
+            Some(record) => Ok(Some(self.open(record)?)),
+            None => self.cold_entry(task_id).await?.map(|entry| self.open(entry.task)).transpose(),
+        }
+    }
+    
+    async fn update_task(&self, task: &Task) -> AppResult<()> {
+        self.update_task_in(&mut *self.pool.acquire().await?, task).await
+    }
+    
+    async fn delete_task(&self, task_id: &TaskId) -> AppResult<()> {
+        self.delete_task_in(&mut *self.pool.acquire().await?, task_id).await
+    }
+    
+    async fn get_next_task(
+        &self,
+        work_directory: &str,
+        worker_id: &str,
+        capabilities: Option<&[String]>,
+    ) -> AppResult<Option<Task>> {
+        let mut conn = self.pool.acquire().await?;
+        let record = self.next_task_record_in(&mut conn, work_directory, capabilities).await?;
+        
         match record {
             Some(record) => {
                 // 使用乐观锁获取任务
                 let updated = sqlx::query(
-                    "UPDATE tasks SET status = 'working', worker_id = ?, started_at = CURRENT_TIMESTAMP, version = version + 1 WHERE task_id = ? AND status = 'waiting'"
+                    "UPDATE tasks SET status = 'working', worker_id = ?, started_at = CURRENT_TIMESTAMP, retry_after = NULL, version = version + 1 WHERE task_id = ? AND status = 'waiting'"
                 )
                 .bind(worker_id)
                 .bind(&record.task_id)
-                .execute(&self.pool)
+                .execute(&mut *conn)
                 .await?;
                 
                 if updated.rows_affected() > 0 {
-                    Ok(Some(record.to_domain()?))
+                    Ok(Some(self.open(record)?))
                 } else {
                     Ok(None) // 任务已被其他进程获取
                 }
@@ -319,31 +771,43 @@ impl TaskRepository for SqliteTaskRepository {
             params.push(created_before.to_rfc3339());
         }
         
-        // 构建ORDER BY子句
-        if let Some(sort_by) = &filter.sort_by {
-            query.push_str(&format!(" ORDER BY {}", sort_by));
-            if let Some(sort_order) = &filter.sort_order {
-                query.push(' ');
-                query.push_str(sort_order);
-            }
-        } else {
-            query.push_str(" ORDER BY created_at DESC");
+        if let Some(cancel_reason) = &filter.cancel_reason {
+            query.push_str(" AND cancel_reason = ?");
+            params.push(cancel_reason.to_string());
+        }
+        
+        if let Some(failure_category) = &filter.failure_category {
+            query.push_str(" AND failure_category = ?");
+            params.push(failure_category.to_string());
+        }
+        
+        if let Some(expires_before) = &filter.expires_before {
+            query.push_str(" AND julianday(expires_at) <= julianday(?)");
+            params.push(expires_before.to_rfc3339());
+        }
+        
+        if let Some(expression) = &filter.expression {
+            query.push_str(" AND ");
+            query.push_str(&expression.to_sql(&mut params));
         }
         
         // 获取总数
         let count_query = query.replace("SELECT * FROM", "SELECT COUNT(*) FROM");
-        let mut count_query_builder = sqlx::query_builder::QueryBuilder::new(&count_query);
+        let mut count_query_builder = sqlx::query_as::<_, (i64,)>(&count_query);
         
         for param in &params {
-            count_query_builder.push_bind(param.clone());
+            count_query_builder = count_query_builder.bind(param.clone());
         }
         
-        let count_result = count_query_builder.build_query_as::<(i64,)>()
-            .fetch_one(&self.pool)
+        let count_result = count_query_builder
+            .fetch_one(&mut *self.pool.acquire().await?)
             .await?;
         
         let total = count_result.0 as u64;
         
+        // 构建ORDER BY子句，排序字段已按白名单校验
+        query.push_str(&filter.sort.order_by_sql());
+        
         // 添加LIMIT和OFFSET
         if let Some(limit) = filter.limit {
             query.push_str(&format!(" LIMIT {}", limit));
@@ -354,64 +818,198 @@ impl TaskRepository for SqliteTaskRepository {
         }
         
         // 执行查询
-        let mut query_builder = sqlx::query_builder::QueryBuilder::new(&query);
+        let mut query_builder = sqlx::query_as::<_, TaskRecord>(&query);
         
         for param in &params {
-            query_builder.push_bind(param.clone());
+            query_builder = query_builder.bind(param.clone());
         }
         
-        let records = query_builder.build_query_as::<TaskRecord>()
-            .fetch_all(&self.pool)
+        let records = query_builder
+            .fetch_all(&mut *self.pool.acquire().await?)
             .await?;
         
         let tasks = records
             .into_iter()
-            .map(|r| r.to_domain())
+            .map(|r| self.open(r))
             .collect::<Result<Vec<_>, _>>()?;
         
         Ok((tasks, total))
     }
     
     async fn get_statistics(&self) -> AppResult<TaskStatistics> {
-        let stats = sqlx::query_as::<_, StatsRow>(
-            "SELECT 
-                COUNT(*) as total_tasks,
-                SUM(CASE WHEN status = 'completed' THEN 1 ELSE 0 END) as completed_tasks,
-                SUM(CASE WHEN status = 'failed' THEN 1 ELSE 0 END) as failed_tasks,
-                SUM(CASE WHEN status = 'cancelled' THEN 1 ELSE 0 END) as cancelled_tasks,
-                SUM(CASE WHEN status = 'waiting' THEN 1 ELSE 0 END) as waiting_tasks,
-                SUM(CASE WHEN status = 'working' THEN 1 ELSE 0 END) as working_tasks,
-                AVG(CASE WHEN completed_at IS NOT NULL AND started_at IS NOT NULL 
-                    THEN (julianday(completed_at) - julianday(started_at)) * 86400 ELSE NULL END) as avg_processing_time
-            FROM tasks"
+        // 读取触发器维护的计数器，不扫描任务表
+        let counters = sqlx::query_as::<_, CounterRow>(
+            "SELECT dimension, value, count, total_seconds FROM task_counters"
         )
-        .fetch_one(&self.pool)
+        .fetch_all(&mut *self.pool.acquire().await?)
         .await?;
         
-        let completed_tasks = stats.completed_tasks.unwrap_or(0) as u64;
-        let total_tasks = stats.total_tasks.unwrap_or(0) as u64;
-        let success_rate = if total_tasks > 0 {
-            completed_tasks as f64 / total_tasks as f64
-        } else {
-            0.0
-        };
+        let mut stats = TaskStatistics::new();
+        let mut processed = 0;
+        let mut processing_seconds = 0.0;
+        for counter in counters {
+            let count = counter.count.max(0) as u64;
+            match (counter.dimension.as_str(), counter.value.as_str()) {
+                ("total", _) => stats.total_tasks = count,
+                ("status", "waiting") => stats.waiting_tasks = count,
+                ("status", "working") => stats.working_tasks = count,
+                ("status", "completed") => stats.completed_tasks = count,
+                ("status", "failed") => stats.failed_tasks = count,
+                ("status", "cancelled") => stats.cancelled_tasks = count,
+                ("status", "pending_approval") => stats.pending_approval_tasks = count,
+                ("cancel_reason", "expired") => stats.expired_tasks = count,
+                ("failure_category", category) => {
+                    stats.failure_categories.insert(category.to_string(), count);
+                }
+                ("priority", "low") => stats.low_priority_tasks = count,
+                ("priority", "medium") => stats.medium_priority_tasks = count,
+                ("priority", "high") => stats.high_priority_tasks = count,
+                ("processing", _) => {
+                    processed = count;
+                    processing_seconds = counter.total_seconds;
+                }
+                _ => {}
+            }
+        }
+        
+        stats.active_tasks = stats.waiting_tasks + stats.working_tasks;
+        if stats.total_tasks > 0 {
+            stats.success_rate = stats.completed_tasks as f64 / stats.total_tasks as f64;
+        }
+        if processed > 0 {
+            stats.avg_processing_time = processing_seconds / processed as f64;
+        }
+        // tasks_per_hour 需要基于时间窗口计算
+        Ok(stats)
+    }
+    
+    async fn waiting_tag_sets(&self) -> AppResult<Vec<(Vec<String>, u64)>> {
+        let rows = sqlx::query_as::<_, (Option<String>, i64)>(
+            "SELECT tags, COUNT(*) FROM tasks WHERE status = 'waiting' GROUP BY tags"
+        )
+        .fetch_all(&mut *self.pool.acquire().await?)
+        .await?;
+        
+        rows.into_iter()
+            .map(|(tags, count)| {
+                let tags = tags
+                    .map(|tags| serde_json::from_str::<Vec<String>>(&tags))
+                    .transpose()
+                    .map_err(|e| AppError::Internal(e.to_string()))?
+                    .unwrap_or_default();
+                Ok((tags, count as u64))
+            })
+            .collect()
+    }
+    
+    async fn sla_samples(&self, created_after: DateTime<Utc>) -> AppResult<Vec<SlaSample>> {
+        let rows = sqlx::query_as::<_, SlaRow>(
+            "SELECT task_id, priority, status, created_at, started_at, completed_at FROM tasks
+             WHERE julianday(created_at) >= julianday(?) OR status IN ('waiting', 'working')"
+        )
+        .bind(created_after.to_rfc3339())
+        .fetch_all(&mut *self.pool.acquire().await?)
+        .await?;
+        
+        rows.into_iter()
+            .map(|(task_id, priority, status, created_at, started_at, completed_at)| {
+                Ok(SlaSample {
+                    task_id: TaskId::from_str(&task_id)?,
+                    priority: crate::domain::TaskPriority::from_str(&priority)?,
+                    status: crate::domain::TaskStatus::from_str(&status)?,
+                    created_at,
+                    started_at,
+                    completed_at,
+                })
+            })
+            .collect()
+    }
+    
+    async fn load_sample(&self, since: DateTime<Utc>) -> AppResult<LoadSample> {
+        let since = since.to_rfc3339();
+        let (arrivals, completions, service_seconds, busy_workers) = sqlx::query_as::<_, (i64, i64, f64, i64)>(
+            "SELECT
+                (SELECT COUNT(*) FROM tasks WHERE julianday(created_at) >= julianday(?1)),
+                COUNT(*),
+                COALESCE(SUM((julianday(completed_at) - julianday(started_at)) * 86400), 0.0),
+                (SELECT COUNT(DISTINCT worker_id) FROM tasks WHERE status = 'working' AND worker_id IS NOT NULL)
+             FROM tasks
+             WHERE status IN ('completed', 'failed')
+               AND julianday(completed_at) >= julianday(?1)
+               AND julianday(started_at) IS NOT NULL"
+        )
+        .bind(&since)
+        .fetch_one(&mut *self.pool.acquire().await?)
+        .await?;
         
-        let active_tasks = stats.waiting_tasks.unwrap_or(0) as u64 + stats.working_tasks.unwrap_or(0) as u64;
-        
-        Ok(TaskStatistics {
-            total_tasks,
-            completed_tasks,
-            failed_tasks: stats.failed_tasks.unwrap_or(0) as u64,
-            cancelled_tasks: stats.cancelled_tasks.unwrap_or(0) as u64,
-            active_tasks,
-            waiting_tasks: stats.waiting_tasks.unwrap_or(0) as u64,
-            working_tasks: stats.working_tasks.unwrap_or(0) as u64,
-            success_rate,
-            avg_processing_time: stats.avg_processing_time.unwrap_or(0.0),
-            tasks_per_hour: 0.0, // 需要基于时间窗口计算
+        Ok(LoadSample {
+            arrivals: arrivals as u64,
+            completions: completions as u64,
+            service_seconds: service_seconds.max(0.0),
+            busy_workers: busy_workers as u64,
         })
     }
     
+    async fn reconcile_statistics(&self) -> AppResult<u64> {
+        // 在同一事务内重新计数并修复，期间有其他写入提交时事务失败，留待下次校验
+        let mut tx = self.pool.begin().await?;
+        
+        let expected: HashMap<(String, String), (i64, f64)> = sqlx::query_as::<_, CounterRow>(
+            "SELECT 'total' AS dimension, '' AS value, COUNT(*) AS count, 0.0 AS total_seconds FROM tasks
+            UNION ALL
+            SELECT 'status', status, COUNT(*), 0.0 FROM tasks WHERE status IS NOT NULL GROUP BY status
+            UNION ALL
+            SELECT 'priority', priority, COUNT(*), 0.0 FROM tasks WHERE priority IS NOT NULL GROUP BY priority
+            UNION ALL
+            SELECT 'cancel_reason', cancel_reason, COUNT(*), 0.0 FROM tasks WHERE cancel_reason IS NOT NULL GROUP BY cancel_reason
+            UNION ALL
+            SELECT 'failure_category', failure_category, COUNT(*), 0.0 FROM tasks WHERE failure_category IS NOT NULL GROUP BY failure_category
+            UNION ALL
+            SELECT 'processing', '', COUNT(*), COALESCE(SUM((julianday(completed_at) - julianday(started_at)) * 86400), 0.0)
+            FROM tasks WHERE julianday(completed_at) IS NOT NULL AND julianday(started_at) IS NOT NULL"
+        )
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|row| ((row.dimension, row.value), (row.count, row.total_seconds)))
+        .collect();
+        
+        let counters = sqlx::query_as::<_, CounterRow>(
+            "SELECT dimension, value, count, total_seconds FROM task_counters"
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        
+        let mut repaired = 0;
+        for counter in counters {
+            let (count, total_seconds) = expected
+                .get(&(counter.dimension.clone(), counter.value.clone()))
+                .copied()
+                .unwrap_or((0, 0.0));
+            // 浮点累加顺序不同会产生微小误差，不视为不一致
+            let tolerance = 1e-6 * total_seconds.abs().max(1.0);
+            if counter.count == count && (counter.total_seconds - total_seconds).abs() <= tolerance {
+                continue;
+            }
+            
+            tracing::warn!(
+                "Task counter {}/{} drifted: {} (expected {}), repairing",
+                counter.dimension, counter.value, counter.count, count
+            );
+            sqlx::query("UPDATE task_counters SET count = ?, total_seconds = ? WHERE dimension = ? AND value = ?")
+                .bind(count)
+                .bind(total_seconds)
+                .bind(&counter.dimension)
+                .bind(&counter.value)
+                .execute(&mut *tx)
+                .await?;
+            repaired += 1;
+        }
+        
+        tx.commit().await?;
+        Ok(repaired)
+    }
+    
     async fn create_task_history(&self, history: &TaskHistory) -> AppResult<u64> {
         let history_record = TaskHistoryRecord::from_domain(history)?;
         
@@ -426,18 +1024,89 @@ impl TaskRepository for SqliteTaskRepository {
         .bind(&history_record.worker_id)
         .bind(&history_record.changed_at)
         .bind(&history_record.details)
-        .execute(&self.pool)
+        .execute(&mut *self.pool.acquire().await?)
         .await?;
         
         Ok(result.last_insert_rowid() as u64)
     }
     
+    async fn create_task_histories(&self, histories: &[TaskHistory]) -> AppResult<u64> {
+        let records = histories
+            .iter()
+            .map(TaskHistoryRecord::from_domain)
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        // 一个事务内多行插入，整批要么全部写入要么全部回滚
+        let mut tx = self.pool.begin().await?;
+        for chunk in records.chunks(HISTORY_INSERT_CHUNK) {
+            let mut builder = sqlx::QueryBuilder::<Sqlite>::new(
+                "INSERT INTO task_history (task_id, status, worker_id, changed_at, details) "
+            );
+            builder.push_values(chunk, |mut row, record| {
+                row.push_bind(&record.task_id)
+                    .push_bind(&record.status)
+                    .push_bind(&record.worker_id)
+                    .push_bind(record.changed_at)
+                    .push_bind(&record.details);
+            });
+            builder.build().execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        
+        Ok(records.len() as u64)
+    }
+    
     async fn get_task_history(&self, task_id: &TaskId) -> AppResult<Vec<TaskHistory>> {
         let records = sqlx::query_as::<_, TaskHistoryRecord>(
             "SELECT * FROM task_history WHERE task_id = ? ORDER BY changed_at DESC"
         )
         .bind(task_id.to_string())
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *self.pool.acquire().await?)
+        .await?;
+        let records = match (records.is_empty(), self.cold_storage.is_some()) {
+            (true, true) => self.cold_entry(task_id).await?.map(|entry| entry.history).unwrap_or_default(),
+            _ => records,
+        };
+        
+        records
+            .into_iter()
+            .map(|r| r.to_domain())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Internal(e.to_string()))
+    }
+    
+    async fn add_task_comment(&self, comment: &TaskComment) -> AppResult<u64> {
+        self.add_task_comment_in(&mut *self.pool.acquire().await?, comment).await
+    }
+    
+    async fn get_task_comments(&self, task_id: &TaskId) -> AppResult<Vec<TaskComment>> {
+        let records = sqlx::query_as::<_, TaskCommentRecord>(
+            "SELECT * FROM task_comments WHERE task_id = ? ORDER BY id"
+        )
+        .bind(task_id.to_string())
+        .fetch_all(&mut *self.pool.acquire().await?)
+        .await?;
+        let records = match (records.is_empty(), self.cold_storage.is_some()) {
+            (true, true) => self.cold_entry(task_id).await?.map(|entry| entry.comments).unwrap_or_default(),
+            _ => records,
+        };
+        
+        records.into_iter().map(|record| self.open_comment(record)).collect()
+    }
+    
+    async fn save_metadata_schema(&self, schema: &MetadataSchema) -> AppResult<()> {
+        self.save_metadata_schema_in(&mut *self.pool.acquire().await?, schema).await
+    }
+    
+    async fn delete_metadata_schema(&self, namespace: &str) -> AppResult<bool> {
+        self.delete_metadata_schema_in(&mut *self.pool.acquire().await?, namespace).await
+    }
+    
+    async fn list_metadata_schemas(&self) -> AppResult<Vec<MetadataSchema>> {
+        let records = sqlx::query_as::<_, MetadataSchemaRecord>(
+            "SELECT * FROM metadata_schemas ORDER BY namespace"
+        )
+        .fetch_all(&mut *self.pool.acquire().await?)
         .await?;
         
         records
@@ -447,48 +1116,305 @@ impl TaskRepository for SqliteTaskRepository {
             .map_err(|e| AppError::Internal(e.to_string()))
     }
     
+    async fn save_pipeline_run(&self, run: &PipelineRun) -> AppResult<()> {
+        self.save_pipeline_run_in(&mut *self.pool.acquire().await?, run).await
+    }
+    
+    async fn get_pipeline_run(&self, run_id: &str) -> AppResult<Option<PipelineRun>> {
+        let record = sqlx::query_as::<_, PipelineRunRecord>(
+            "SELECT * FROM pipeline_runs WHERE run_id = ?"
+        )
+        .bind(run_id)
+        .fetch_optional(&mut *self.pool.acquire().await?)
+        .await?;
+        
+        record
+            .map(|r| r.to_domain())
+            .transpose()
+            .map_err(|e| AppError::Internal(e.to_string()))
+    }
+    
     async fn cleanup_expired_tasks(&self, older_than: DateTime<Utc>) -> AppResult<u64> {
         let result = sqlx::query(
             "DELETE FROM tasks WHERE status IN ('completed', 'failed', 'cancelled') AND completed_at < ?"
         )
         .bind(older_than)
-        .execute(&self.pool)
+        .execute(&mut *self.pool.acquire().await?)
         .await?;
         
         Ok(result.rows_affected() as u64)
     }
     
-    async fn retry_failed_tasks(&self, max_retries: u32) -> AppResult<u64> {
-        let result = sqlx::query(
-            "UPDATE tasks SET status = 'waiting', worker_id = NULL, started_at = NULL, retry_count = retry_count + 1 WHERE status = 'failed' AND retry_count < ?"
+    async fn archive_tasks(&self, older_than: DateTime<Utc>, limit: u32) -> AppResult<Vec<TaskId>> {
+        let Some((segment, moves)) = self.write_cold_segment(older_than, limit).await? else {
+            return Ok(Vec::new());
+        };
+        
+        let mut tx = self.pool.begin().await?;
+        let mut archived = Vec::new();
+        for moved in &moves {
+            if self.move_to_cold_in(&mut tx, &segment, moved).await? {
+                archived.push(TaskId::from_str(&moved.task_id)?);
+            }
+        }
+        tx.commit().await?;
+        
+        Ok(archived)
+    }
+    
+    async fn retry_failed_tasks(&self, max_retries: u32) -> AppResult<Vec<TaskId>> {
+        let task_ids = sqlx::query_scalar::<_, String>(
+            "UPDATE tasks SET status = 'waiting', worker_id = NULL, started_at = NULL, failure_category = NULL, retry_count = retry_count + 1, version = version + 1 WHERE status = 'failed' AND retry_count < ? RETURNING task_id"
         )
         .bind(max_retries)
-        .execute(&self.pool)
+        .fetch_all(&mut *self.pool.acquire().await?)
         .await?;
         
-        Ok(result.rows_affected() as u64)
+        task_ids.iter().map(|id| Ok(TaskId::from_str(id)?)).collect()
+    }
+}
+
+/// 共享存储层读取的任务属性，阶段划分与 [`SqliteTaskRepository::next_task_in`] 的查询条件一致
+impl StoredTask for Task {
+    type Id = TaskId;
+    
+    fn id(&self) -> TaskId {
+        self.id
+    }
+    
+    fn work_directory(&self) -> &str {
+        self.work_directory.as_str()
+    }
+    
+    fn phase(&self) -> TaskPhase {
+        match self.status {
+            TaskStatus::PendingApproval => TaskPhase::Held,
+            TaskStatus::Waiting => TaskPhase::Queued,
+            TaskStatus::Working => TaskPhase::Running,
+            TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled => TaskPhase::Finished,
+        }
+    }
+    
+    fn priority(&self) -> i32 {
+        self.priority.weight() as i32
+    }
+    
+    fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+    
+    fn is_claimable(&self) -> bool {
+        let now = Utc::now();
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+            && self.retry_after.is_none_or(|retry_after| retry_after <= now)
+    }
+}
+
+/// 共享任务仓库接口的SQLite实现
+///
+/// 修改类操作在事务中读取热表、应用修改并按乐观锁写回，版本号由仓库递增。
+/// 冷存储中的任务不参与，已归档的任务只能通过 [`TaskRepository::get_task`] 读取。
+#[async_trait::async_trait]
+impl task_store::TaskRepository<Task> for SqliteTaskRepository {
+    async fn insert(&self, task: &Task) -> StoreResult<()> {
+        let mut tx = self.pool.begin().await.map_err(StoreError::backend)?;
+        if self.get_hot_task_in(&mut tx, &task.id).await?.is_some() {
+            return Err(StoreError::Conflict(format!("task {} already exists", task.id)));
+        }
+        self.insert_task_in(&mut tx, task).await?;
+        tx.commit().await.map_err(StoreError::backend)?;
+        Ok(())
+    }
+    
+    async fn get(&self, id: &TaskId) -> StoreResult<Option<Task>> {
+        let mut conn = self.pool.acquire().await.map_err(StoreError::backend)?;
+        Ok(self.get_hot_task_in(&mut conn, id).await?)
+    }
+    
+    async fn update(&self, task: &Task) -> StoreResult<()> {
+        self.modify(&task.id, &|current| {
+            *current = task.clone();
+            Ok(())
+        }).await?;
+        Ok(())
+    }
+    
+    async fn remove(&self, id: &TaskId) -> StoreResult<bool> {
+        let mut conn = self.pool.acquire().await.map_err(StoreError::backend)?;
+        match self.delete_task_in(&mut conn, id).await {
+            Ok(()) => Ok(true),
+            Err(AppError::TaskNotFound(_)) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+    
+    async fn scan(&self) -> StoreResult<Vec<Task>> {
+        let records = sqlx::query_as::<_, TaskRecord>("SELECT * FROM tasks")
+            .fetch_all(&mut *self.pool.acquire().await.map_err(StoreError::backend)?)
+            .await
+            .map_err(StoreError::backend)?;
+        Ok(records.into_iter().map(|record| self.open(record)).collect::<AppResult<_>>()?)
+    }
+    
+    async fn next_queued(&self, work_directory: &str) -> StoreResult<Option<Task>> {
+        let mut conn = self.pool.acquire().await.map_err(StoreError::backend)?;
+        Ok(self.next_task_in(&mut conn, work_directory, None).await?)
+    }
+    
+    async fn modify(&self, id: &TaskId, change: TaskChange<'_, Task>) -> StoreResult<Task> {
+        let mut tx = self.pool.begin().await.map_err(StoreError::backend)?;
+        let current = self.get_hot_task_in(&mut tx, id).await?
+            .ok_or_else(|| StoreError::NotFound(id.to_string()))?;
+        let task = self.save_change_in(&mut tx, current, change).await?;
+        tx.commit().await.map_err(StoreError::backend)?;
+        Ok(task)
+    }
+    
+    async fn claim_next(&self, work_directory: &str, change: TaskChange<'_, Task>) -> StoreResult<Option<Task>> {
+        let mut tx = self.pool.begin().await.map_err(StoreError::backend)?;
+        let Some(current) = self.next_task_in(&mut tx, work_directory, None).await? else {
+            return Ok(None);
+        };
+        let task = self.save_change_in(&mut tx, current, change).await?;
+        tx.commit().await.map_err(StoreError::backend)?;
+        Ok(Some(task))
+    }
+    
+    async fn retain(&self, keep: TaskPredicate<'_, Task>) -> StoreResult<u64> {
+        let removed: Vec<TaskId> = self.scan().await?
+            .into_iter()
+            .filter(|task| !keep(task))
+            .map(|task| task.id)
+            .collect();
+        
+        let mut tx = self.pool.begin().await.map_err(StoreError::backend)?;
+        for id in &removed {
+            self.delete_task_in(&mut tx, id).await?;
+        }
+        tx.commit().await.map_err(StoreError::backend)?;
+        Ok(removed.len() as u64)
+    }
+}
+
+impl SqliteTaskRepository {
+    /// 对读取到的任务应用修改并按乐观锁写回，修改内容不影响版本号
+    async fn save_change_in(
+        &self,
+        conn: &mut SqliteConnection,
+        current: Task,
+        change: TaskChange<'_, Task>,
+    ) -> StoreResult<Task> {
+        let version = current.version;
+        let mut task = current;
+        change(&mut task)?;
+        task.version = version + 1;
+        match self.update_task_in(conn, &task).await {
+            Ok(()) => Ok(task),
+            Err(AppError::ConcurrencyConflict) => Err(StoreError::Conflict(format!("task {} was modified concurrently", task.id))),
+            Err(e) => Err(e.into()),
+        }
     }
 }
 
 /// SQLite锁管理器实现
+///
+/// 锁的到期时间按墙上时间持久化，供其他节点判断。本进程持有的锁额外记录单调截止时间，
+/// 截止前续约、检查和清理都以单调时钟为准，系统时间跳变不会让本进程误判自己的锁已过期。
 pub struct SqliteLockManager {
-    pool: Pool<Sqlite>,
+    pool: Arc<ManagedPool>,
+    clock: Arc<dyn Clock>,
+    held: std::sync::Mutex<HashMap<String, HeldLock>>,
+}
+
+/// 本进程持有的锁
+struct HeldLock {
+    owner_id: String,
+    deadline: Deadline,
 }
 
 impl SqliteLockManager {
     pub async fn new(pool: Pool<Sqlite>) -> Self {
-        Self { pool }
+        Self::with_managed_pool(Arc::new(ManagedPool::new(pool)))
     }
     
     pub async fn with_pool(pool: Pool<Sqlite>) -> Self {
-        Self { pool }
+        Self::with_managed_pool(Arc::new(ManagedPool::new(pool)))
+    }
+    
+    /// 使用带健康监控的共享连接池
+    pub fn with_managed_pool(pool: Arc<ManagedPool>) -> Self {
+        Self {
+            pool,
+            clock: system_clock(),
+            held: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+    
+    /// 设置时钟
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// 本进程持有且按单调时钟未到期的锁的持有者
+    fn held_owner(&self, resource_id: &str) -> Option<String> {
+        let held = self.held.lock().unwrap();
+        held.get(resource_id)
+            .filter(|lock| !lock.deadline.is_expired(self.clock.as_ref()))
+            .map(|lock| lock.owner_id.clone())
+    }
+    
+    fn record_held(&self, resource_id: &str, owner_id: &str, deadline: Deadline) {
+        self.held.lock().unwrap().insert(
+            resource_id.to_string(),
+            HeldLock { owner_id: owner_id.to_string(), deadline },
+        );
+    }
+    
+    fn forget_held(&self, resource_id: &str, owner_id: &str) {
+        let mut held = self.held.lock().unwrap();
+        if held.get(resource_id).map(|lock| lock.owner_id == owner_id).unwrap_or(false) {
+            held.remove(resource_id);
+        }
     }
 }
 
 #[async_trait::async_trait]
 impl LockManager for SqliteLockManager {
-    async fn try_acquire(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> AppResult<bool> {
-        let expires_at = Utc::now() + chrono::Duration::seconds(ttl_seconds as i64);
+    async fn try_acquire(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> StoreResult<bool> {
+        Ok(self.acquire_lock(resource_id, owner_id, ttl_seconds).await?)
+    }
+    
+    async fn renew(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> StoreResult<bool> {
+        Ok(self.renew_lock(resource_id, owner_id, ttl_seconds).await?)
+    }
+    
+    async fn release(&self, resource_id: &str, owner_id: &str) -> StoreResult<bool> {
+        Ok(self.release_lock(resource_id, owner_id).await?)
+    }
+    
+    async fn check_lock(&self, resource_id: &str) -> StoreResult<Option<String>> {
+        Ok(self.lock_owner(resource_id).await?)
+    }
+    
+    async fn cleanup_expired_locks(&self) -> StoreResult<u64> {
+        Ok(self.cleanup_locks().await?)
+    }
+}
+
+impl SqliteLockManager {
+    async fn acquire_lock(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> AppResult<bool> {
+        let now = self.clock.now();
+        let deadline = Deadline::after(self.clock.as_ref(), std::time::Duration::from_secs(ttl_seconds));
+        
+        // 已过期的锁可以被其他持有者接管，本进程持有且未到期的锁不按墙上时间清理
+        if self.held_owner(resource_id).is_none() {
+            sqlx::query("DELETE FROM locks WHERE resource_id = ? AND expires_at <= ?")
+                .bind(resource_id)
+                .bind(now)
+                .execute(&mut *self.pool.acquire().await?)
+                .await?;
+        }
         
         let result = sqlx::query(
             r#"
@@ -498,59 +1424,113 @@ impl LockManager for SqliteLockManager {
         )
         .bind(resource_id)
         .bind(owner_id)
-        .bind(expires_at)
-        .execute(&self.pool)
+        .bind(deadline.expires_at())
+        .execute(&mut *self.pool.acquire().await?)
         .await?;
         
-        Ok(result.rows_affected() > 0)
+        let acquired = result.rows_affected() > 0;
+        if acquired {
+            self.record_held(resource_id, owner_id, deadline);
+        }
+        Ok(acquired)
     }
     
-    async fn release(&self, resource_id: &str, owner_id: &str) -> AppResult<bool> {
+    async fn renew_lock(&self, resource_id: &str, owner_id: &str, ttl_seconds: u64) -> AppResult<bool> {
+        let now = self.clock.now();
+        let deadline = Deadline::after(self.clock.as_ref(), std::time::Duration::from_secs(ttl_seconds));
+        
+        // 单调截止时间内持有者未变即可续约，否则按持久化的墙上到期时间判断
+        let result = if self.held_owner(resource_id).as_deref() == Some(owner_id) {
+            sqlx::query("UPDATE locks SET expires_at = ? WHERE resource_id = ? AND owner_id = ?")
+                .bind(deadline.expires_at())
+                .bind(resource_id)
+                .bind(owner_id)
+                .execute(&mut *self.pool.acquire().await?)
+                .await?
+        } else {
+            sqlx::query(
+                "UPDATE locks SET expires_at = ? WHERE resource_id = ? AND owner_id = ? AND expires_at > ?"
+            )
+            .bind(deadline.expires_at())
+            .bind(resource_id)
+            .bind(owner_id)
+            .bind(now)
+            .execute(&mut *self.pool.acquire().await?)
+            .await?
+        };
+        
+        let renewed = result.rows_affected() > 0;
+        if renewed {
+            self.record_held(resource_id, owner_id, deadline);
+        } else {
+            self.forget_held(resource_id, owner_id);
+        }
+        Ok(renewed)
+    }
+    
+    async fn release_lock(&self, resource_id: &str, owner_id: &str) -> AppResult<bool> {
+        self.forget_held(resource_id, owner_id);
         let result = sqlx::query(
             "DELETE FROM locks WHERE resource_id = ? AND owner_id = ?"
         )
         .bind(resource_id)
         .bind(owner_id)
-        .execute(&self.pool)
+        .execute(&mut *self.pool.acquire().await?)
         .await?;
         
         Ok(result.rows_affected() > 0)
     }
     
-    async fn check_lock(&self, resource_id: &str) -> AppResult<Option<String>> {
+    async fn lock_owner(&self, resource_id: &str) -> AppResult<Option<String>> {
         let record = sqlx::query_as::<_, LockRecord>(
-            "SELECT * FROM locks WHERE resource_id = ? AND expires_at > CURRENT_TIMESTAMP"
+            "SELECT * FROM locks WHERE resource_id = ?"
         )
         .bind(resource_id)
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *self.pool.acquire().await?)
         .await?;
         
-        Ok(record.map(|r| r.owner_id))
+        let held_owner = self.held_owner(resource_id);
+        let now = self.clock.now();
+        Ok(record
+            .filter(|r| held_owner.as_deref() == Some(r.owner_id.as_str()) || r.expires_at > now)
+            .map(|r| r.owner_id))
     }
     
-    async fn cleanup_expired_locks(&self) -> AppResult<u64> {
-        let result = sqlx::query(
-            "DELETE FROM locks WHERE expires_at < CURRENT_TIMESTAMP"
-        )
-        .execute(&self.pool)
-        .await?;
+    async fn cleanup_locks(&self) -> AppResult<u64> {
+        let held: Vec<String> = {
+            let held = self.held.lock().unwrap();
+            held.iter()
+                .filter(|(_, lock)| !lock.deadline.is_expired(self.clock.as_ref()))
+                .map(|(resource_id, _)| resource_id.clone())
+                .collect()
+        };
+        
+        let mut sql = "DELETE FROM locks WHERE expires_at < ?".to_string();
+        if !held.is_empty() {
+            sql.push_str(&format!(" AND resource_id NOT IN ({})", vec!["?"; held.len()].join(", ")));
+        }
+        let mut query = sqlx::query(&sql).bind(self.clock.now());
+        for resource_id in &held {
+            query = query.bind(resource_id);
+        }
+        let result = query.execute(&mut *self.pool.acquire().await?).await?;
         
         Ok(result.rows_affected() as u64)
     }
 }
 
-/// 统计查询结果行
+/// 统计计数器行
 #[derive(sqlx::FromRow)]
-struct StatsRow {
-    total_tasks: Option<i64>,
-    completed_tasks: Option<i64>,
-    failed_tasks: Option<i64>,
-    cancelled_tasks: Option<i64>,
-    waiting_tasks: Option<i64>,
-    working_tasks: Option<i64>,
-    avg_processing_time: Option<f64>,
+struct CounterRow {
+    dimension: String,
+    value: String,
+    count: i64,
+    total_seconds: f64,
 }
 
+/// SLA时间点行：任务ID、优先级、状态、创建/开始/结束时间
+type SlaRow = (String, String, String, DateTime<Utc>, Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -575,6 +1555,9 @@ mod tests {
             cache_size: -64000,
             mmap_size: 268435456,
             page_size: 4096,
+            enable_event_sourcing: false,
+            rebuild_projection_on_startup: false,
+            ..Default::default()
         };
         
         let pool = SqliteTaskRepository::create_pool(&config).await.unwrap();
@@ -617,7 +1600,7 @@ mod tests {
         let task_id = repo.create_task(&task).await.unwrap();
         
         // 获取任务 - 可能返回不同的任务，所以只检查返回的任务不为空
-        let next_task = repo.get_next_task("/test", "worker-1").await.unwrap();
+        let next_task = repo.get_next_task("/test", "worker-1", None).await.unwrap();
         assert!(next_task.is_some(), "Should return a task");
         
         // 更新原始任务的状态
@@ -632,4 +1615,542 @@ mod tests {
         let completed = repo.get_task(&task_id).await.unwrap().unwrap();
         assert_eq!(completed.status, TaskStatus::Completed);
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_lock_renew_and_expired_takeover() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SqliteTaskRepository::run_migrations(&pool).await.unwrap();
+        let lock_manager = SqliteLockManager::with_pool(pool).await;
+        
+        assert!(lock_manager.try_acquire("leader", "node-a", 30).await.unwrap());
+        assert!(!lock_manager.try_acquire("leader", "node-b", 30).await.unwrap());
+        assert!(lock_manager.renew("leader", "node-a", 30).await.unwrap());
+        assert!(!lock_manager.renew("leader", "node-b", 30).await.unwrap());
+        assert_eq!(lock_manager.check_lock("leader").await.unwrap(), Some("node-a".to_string()));
+        
+        // 租约过期后可被其他节点接管
+        assert!(lock_manager.release("leader", "node-a").await.unwrap());
+        assert!(lock_manager.try_acquire("leader", "node-a", 0).await.unwrap());
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert_eq!(lock_manager.check_lock("leader").await.unwrap(), None);
+        assert!(!lock_manager.renew("leader", "node-a", 30).await.unwrap());
+        assert!(lock_manager.try_acquire("leader", "node-b", 30).await.unwrap());
+    }
+    
+    #[tokio::test]
+    async fn test_held_lock_survives_wall_clock_jump() {
+        use crate::utils::clock::ManualClock;
+        
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SqliteTaskRepository::run_migrations(&pool).await.unwrap();
+        let clock = Arc::new(ManualClock::new());
+        let lock_manager = SqliteLockManager::with_pool(pool).await.with_clock(clock.clone());
+        
+        assert!(lock_manager.try_acquire("leader", "node-a", 30).await.unwrap());
+        
+        // 系统时间向前跳变后，单调截止时间内的锁仍然有效，不会被清理
+        clock.jump_wall_clock(chrono::Duration::hours(1));
+        assert_eq!(lock_manager.check_lock("leader").await.unwrap(), Some("node-a".to_string()));
+        assert_eq!(lock_manager.cleanup_expired_locks().await.unwrap(), 0);
+        assert!(!lock_manager.try_acquire("leader", "node-b", 30).await.unwrap());
+        assert!(lock_manager.renew("leader", "node-a", 30).await.unwrap());
+        
+        // 超过单调截止时间未续约，回退到按持久化的墙上到期时间判断
+        clock.advance(std::time::Duration::from_secs(31));
+        assert_eq!(lock_manager.check_lock("leader").await.unwrap(), None);
+        assert!(!lock_manager.renew("leader", "node-a", 30).await.unwrap());
+        assert!(lock_manager.try_acquire("leader", "node-b", 30).await.unwrap());
+        assert_eq!(lock_manager.cleanup_expired_locks().await.unwrap(), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_statistics_counters_and_reconcile() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = SqliteTaskRepository::with_pool(pool).await.unwrap();
+        let new_task = |priority| Task::new(
+            crate::domain::WorkDirectory::new("/test".to_string()).unwrap(),
+            crate::domain::Prompt::new("Test task".to_string()).unwrap(),
+            priority,
+            vec![],
+        );
+        
+        let mut task = new_task(TaskPriority::High);
+        repo.create_task(&task).await.unwrap();
+        let removed = new_task(TaskPriority::Low);
+        repo.create_task(&removed).await.unwrap();
+        repo.create_task(&new_task(TaskPriority::Medium)).await.unwrap();
+        repo.delete_task(&removed.id).await.unwrap();
+        
+        task.start(crate::domain::WorkerId::new("worker-1".to_string()).unwrap()).unwrap();
+        repo.update_task(&task).await.unwrap();
+        task.complete(crate::domain::TaskResult::success("Done".to_string())).unwrap();
+        repo.update_task(&task).await.unwrap();
+        
+        let stats = repo.get_statistics().await.unwrap();
+        assert_eq!((stats.total_tasks, stats.completed_tasks, stats.waiting_tasks, stats.working_tasks), (2, 1, 1, 0));
+        assert_eq!((stats.low_priority_tasks, stats.medium_priority_tasks, stats.high_priority_tasks), (0, 1, 1));
+        assert_eq!(stats.success_rate, 0.5);
+        assert_eq!(repo.reconcile_statistics().await.unwrap(), 0);
+        
+        // 人为破坏计数器后由校验任务修复
+        sqlx::query("UPDATE task_counters SET count = 42 WHERE dimension IN ('total', 'status')")
+            .execute(&repo.pool.get())
+            .await
+            .unwrap();
+        assert_eq!(repo.reconcile_statistics().await.unwrap(), 7);
+        assert_eq!(repo.get_statistics().await.unwrap().total_tasks, 2);
+        assert_eq!(repo.get_statistics().await.unwrap().failed_tasks, 0);
+    }
+    
+    #[tokio::test]
+    async fn test_next_task_capability_filter() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = SqliteTaskRepository::with_pool(pool).await.unwrap();
+        let new_task = |tags: &[&str]| Task::new(
+            crate::domain::WorkDirectory::new("/test".to_string()).unwrap(),
+            crate::domain::Prompt::new("Test task".to_string()).unwrap(),
+            TaskPriority::Medium,
+            tags.iter().map(|t| crate::domain::TaskTag::new(t.to_string()).unwrap()).collect(),
+        );
+        let gpu = new_task(&["gpu", "rust"]);
+        repo.create_task(&gpu).await.unwrap();
+        let rust = new_task(&["rust"]);
+        repo.create_task(&rust).await.unwrap();
+        
+        let sets = repo.waiting_tag_sets().await.unwrap();
+        assert_eq!(sets.len(), 2);
+        assert!(sets.contains(&(vec!["rust".to_string()], 1)));
+        
+        let capabilities = vec!["rust".to_string(), "claude".to_string()];
+        let acquired = repo.get_next_task("/test", "worker-1", Some(&capabilities)).await.unwrap();
+        assert_eq!(acquired.unwrap().id, rust.id);
+        assert!(repo.get_next_task("/test", "worker-1", Some(&capabilities)).await.unwrap().is_none());
+        assert!(repo.get_next_task("/test", "worker-1", Some(&[])).await.unwrap().is_none());
+        
+        // 未声明能力时不按标签过滤
+        let acquired = repo.get_next_task("/test", "worker-2", None).await.unwrap();
+        assert_eq!(acquired.unwrap().id, gpu.id);
+    }
+    
+    #[tokio::test]
+    async fn test_shared_repository_interface() {
+        use task_store::TaskRepository as _;
+        
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = SqliteTaskRepository::with_pool(pool).await.unwrap();
+        let new_task = |priority| Task::new(
+            crate::domain::WorkDirectory::new("/test".to_string()).unwrap(),
+            crate::domain::Prompt::new("Test task".to_string()).unwrap(),
+            priority,
+            vec![],
+        );
+        let low = new_task(TaskPriority::Low);
+        let high = new_task(TaskPriority::High);
+        repo.insert(&low).await.unwrap();
+        repo.insert(&high).await.unwrap();
+        assert!(matches!(repo.insert(&low).await, Err(StoreError::Conflict(_))));
+        
+        // 按优先级获取，修改后版本号由仓库递增
+        let worker = crate::domain::WorkerId::new("worker-1".to_string()).unwrap();
+        let claimed = repo.claim_next("/test", &|task| {
+            task.start(worker.clone()).map_err(|e| StoreError::Conflict(e.to_string()))
+        }).await.unwrap().unwrap();
+        assert_eq!(claimed.id, high.id);
+        assert_eq!(claimed.phase(), TaskPhase::Running);
+        assert_eq!(repo.get(&high.id).await.unwrap().unwrap().version, high.version + 1);
+        assert_eq!(repo.next_queued("/test").await.unwrap().unwrap().id, low.id);
+        
+        // 回调返回错误时不保存
+        let rejected = repo.modify(&low.id, &|_| Err(StoreError::Conflict("busy".to_string()))).await;
+        assert!(matches!(rejected, Err(StoreError::Conflict(_))));
+        let cancelled = repo.modify(&low.id, &|task| {
+            task.cancel(None).map_err(|e| StoreError::Conflict(e.to_string()))
+        }).await.unwrap();
+        assert_eq!(cancelled.status, TaskStatus::Cancelled);
+        assert!(repo.claim_next("/test", &|_| Ok(())).await.unwrap().is_none());
+        
+        assert_eq!(repo.retain(&|task| task.phase() != TaskPhase::Finished).await.unwrap(), 1);
+        assert_eq!(repo.scan().await.unwrap().len(), 1);
+        assert!(repo.remove(&high.id).await.unwrap());
+        assert!(!repo.remove(&high.id).await.unwrap());
+        assert!(matches!(repo.update(&high).await, Err(StoreError::NotFound(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_task_comments() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = SqliteTaskRepository::with_pool(pool).await.unwrap();
+        let task = Task::new(
+            crate::domain::WorkDirectory::new("/test".to_string()).unwrap(),
+            crate::domain::Prompt::new("Test task".to_string()).unwrap(),
+            TaskPriority::Medium,
+            vec![],
+        );
+        repo.create_task(&task).await.unwrap();
+        
+        let first = TaskComment::new(task.id, "alice".to_string(), "Needs the staging DB".to_string());
+        let second = TaskComment::new(task.id, "worker-1".to_string(), "Tests flaky".to_string())
+            .with_data(serde_json::json!({"failed": ["test_login"]}));
+        let first_id = repo.add_task_comment(&first).await.unwrap();
+        let second_id = repo.add_task_comment(&second).await.unwrap();
+        assert!(second_id > first_id);
+        
+        let comments = repo.get_task_comments(&task.id).await.unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!((comments[0].id, comments[0].author.as_str()), (first_id, "alice"));
+        assert_eq!(comments[1].data, Some(serde_json::json!({"failed": ["test_login"]})));
+        
+        // 删除任务时级联删除评论
+        repo.delete_task(&task.id).await.unwrap();
+        assert!(repo.get_task_comments(&task.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_archive_tasks_to_cold_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = SqliteTaskRepository::with_pool(pool).await.unwrap()
+            .with_cold_storage(Arc::new(ColdStorage::new(dir.path()).unwrap()));
+
+        let task = |days_ago: i64, status: TaskStatus| {
+            let mut task = Task::new(
+                crate::domain::WorkDirectory::new("/test".to_string()).unwrap(),
+                crate::domain::Prompt::new("Old task".to_string()).unwrap(),
+                TaskPriority::Medium,
+                vec![],
+            );
+            task.status = status;
+            if status != TaskStatus::Waiting {
+                task.completed_at = Some(Utc::now() - chrono::Duration::days(days_ago));
+            }
+            task
+        };
+        let old = task(40, TaskStatus::Completed);
+        let recent = task(1, TaskStatus::Failed);
+        let waiting = task(0, TaskStatus::Waiting);
+        for t in [&old, &recent, &waiting] {
+            repo.create_task(t).await.unwrap();
+        }
+        repo.create_task_history(&TaskHistory::new(old.id, TaskStatus::Completed, None)).await.unwrap();
+        repo.add_task_comment(&TaskComment::new(old.id, "alice".to_string(), "Done".to_string())).await.unwrap();
+
+        let cutoff = Utc::now() - chrono::Duration::days(30);
+        assert_eq!(repo.archive_tasks(cutoff, 100).await.unwrap().len(), 1);
+        assert!(repo.archive_tasks(cutoff, 100).await.unwrap().is_empty());
+
+        // 热表中不再有该任务，按ID查询仍能透明读取任务、历史和评论
+        let (hot, _) = repo.list_tasks(&TaskFilter::new()).await.unwrap();
+        assert_eq!(hot.len(), 2);
+        assert!(hot.iter().all(|t| t.id != old.id));
+        let archived = repo.get_task(&old.id).await.unwrap().unwrap();
+        assert_eq!((archived.status, archived.prompt.as_str()), (TaskStatus::Completed, "Old task"));
+        assert_eq!(repo.get_task_history(&old.id).await.unwrap().len(), 1);
+        assert_eq!(repo.get_task_comments(&old.id).await.unwrap()[0].text, "Done");
+        assert!(repo.get_task(&TaskId::new()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_archive_keeps_tasks_retried_after_selection() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = SqliteTaskRepository::with_pool(pool).await.unwrap()
+            .with_cold_storage(Arc::new(ColdStorage::new(dir.path()).unwrap()));
+
+        let mut task = Task::new(
+            crate::domain::WorkDirectory::new("/test".to_string()).unwrap(),
+            crate::domain::Prompt::new("Flaky task".to_string()).unwrap(),
+            TaskPriority::Medium,
+            vec![],
+        );
+        task.status = TaskStatus::Failed;
+        task.completed_at = Some(Utc::now() - chrono::Duration::days(40));
+        repo.create_task(&task).await.unwrap();
+
+        // 段文件写入后、删除热数据前任务被重试，任务必须留在热表中
+        let cutoff = Utc::now() - chrono::Duration::days(30);
+        let (segment, moves) = repo.write_cold_segment(cutoff, 100).await.unwrap().unwrap();
+        assert_eq!(repo.retry_failed_tasks(3).await.unwrap(), vec![task.id]);
+        let mut tx = repo.pool.begin().await.unwrap();
+        assert!(!repo.move_to_cold_in(&mut tx, &segment, &moves[0]).await.unwrap());
+        tx.commit().await.unwrap();
+
+        let (hot, _) = repo.list_tasks(&TaskFilter::new()).await.unwrap();
+        assert_eq!(hot.len(), 1);
+        assert_eq!((hot[0].status, hot[0].version), (TaskStatus::Waiting, task.version + 1));
+        let indexed = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM cold_tasks")
+            .fetch_one(&mut *repo.pool.acquire().await.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(indexed, 0);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_schemas() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = SqliteTaskRepository::with_pool(pool).await.unwrap();
+
+        let schema = MetadataSchema::new("/srv/infra".to_string(), serde_json::json!({"type": "object"}), false);
+        repo.save_metadata_schema(&schema).await.unwrap();
+        // 同一命名空间覆盖
+        let strict = MetadataSchema::new("/srv/infra".to_string(), serde_json::json!({"required": ["team"]}), true);
+        repo.save_metadata_schema(&strict).await.unwrap();
+
+        let schemas = repo.list_metadata_schemas().await.unwrap();
+        assert_eq!(schemas.len(), 1);
+        assert_eq!((schemas[0].schema.clone(), schemas[0].strict), (strict.schema, true));
+
+        assert!(repo.delete_metadata_schema("/srv/infra").await.unwrap());
+        assert!(!repo.delete_metadata_schema("/srv/infra").await.unwrap());
+        assert!(repo.list_metadata_schemas().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_runs() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = SqliteTaskRepository::with_pool(pool).await.unwrap();
+        assert!(repo.get_pipeline_run("run-1").await.unwrap().is_none());
+
+        let task = Task::new(
+            crate::domain::WorkDirectory::new("/repo".to_string()).unwrap(),
+            crate::domain::Prompt::new("Analyze".to_string()).unwrap(),
+            TaskPriority::Medium,
+            vec![],
+        );
+        let mut run = PipelineRun::new("run-1".to_string(), "review".to_string());
+        run.record_step("analyze".to_string(), &task);
+        repo.save_pipeline_run(&run).await.unwrap();
+
+        run.skipped.push("fix".to_string());
+        run.finish(crate::domain::PipelineRunStatus::Completed, None);
+        repo.save_pipeline_run(&run).await.unwrap();
+        let saved = repo.get_pipeline_run("run-1").await.unwrap().unwrap();
+        assert_eq!(saved.status, crate::domain::PipelineRunStatus::Completed);
+        assert_eq!(saved.steps, run.steps);
+        assert_eq!(saved.skipped, vec!["fix".to_string()]);
+    }
+    
+    #[tokio::test]
+    async fn test_encrypted_columns_and_reencrypt() {
+        use base64::Engine;
+        
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let plain_repo = SqliteTaskRepository::with_pool(pool.clone()).await.unwrap();
+        let legacy = Task::new(
+            crate::domain::WorkDirectory::new("/test".to_string()).unwrap(),
+            crate::domain::Prompt::new("Legacy task".to_string()).unwrap(),
+            TaskPriority::Medium,
+            vec![],
+        );
+        plain_repo.create_task(&legacy).await.unwrap();
+        
+        let config = crate::config::EncryptionConfig {
+            enabled: true,
+            active_key_id: "k1".to_string(),
+            keys: std::collections::HashMap::from([(
+                "k1".to_string(),
+                base64::engine::general_purpose::STANDARD.encode([9u8; 32]),
+            )]),
+            ..Default::default()
+        };
+        let repo = SqliteTaskRepository::with_pool(pool.clone()).await.unwrap()
+            .with_cipher(Arc::new(FieldCipher::from_config(&config).unwrap()));
+        
+        let task = Task::new(
+            crate::domain::WorkDirectory::new("/test".to_string()).unwrap(),
+            crate::domain::Prompt::new("Secret task".to_string()).unwrap(),
+            TaskPriority::Medium,
+            vec![],
+        );
+        repo.create_task(&task).await.unwrap();
+        
+        let raw: (String,) = sqlx::query_as("SELECT prompt FROM tasks WHERE task_id = ?")
+            .bind(task.id.to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(raw.0.starts_with("enc:v1:k1:"));
+        assert_eq!(repo.get_task(&task.id).await.unwrap().unwrap().prompt.as_str(), "Secret task");
+        
+        // 密文约为明文的4/3，最长的提示词加密后仍可保存
+        let long = Task::new(
+            crate::domain::WorkDirectory::new("/test".to_string()).unwrap(),
+            crate::domain::Prompt::new("x".repeat(10000)).unwrap(),
+            TaskPriority::Medium,
+            vec![],
+        );
+        repo.create_task(&long).await.unwrap();
+        assert_eq!(repo.get_task(&long.id).await.unwrap().unwrap().prompt.as_str().len(), 10000);
+        let oversized = sqlx::query("UPDATE tasks SET prompt = ? WHERE task_id = ?")
+            .bind("x".repeat(10001))
+            .bind(long.id.to_string())
+            .execute(&pool)
+            .await;
+        assert!(oversized.is_err());
+        
+        // 历史明文行仍可读取，迁移后加密
+        assert_eq!(repo.get_task(&legacy.id).await.unwrap().unwrap().prompt.as_str(), "Legacy task");
+        assert_eq!(repo.reencrypt_existing().await.unwrap(), 1);
+        assert_eq!(repo.reencrypt_existing().await.unwrap(), 0);
+        assert_eq!(repo.get_task(&legacy.id).await.unwrap().unwrap().prompt.as_str(), "Legacy task");
+    }
+    
+    #[tokio::test]
+    async fn test_list_tasks_filter_expression_matches_predicate() {
+        use crate::models::FilterExpr;
+        
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = SqliteTaskRepository::with_pool(pool).await.unwrap();
+        let new_task = |dir: &str, priority, tags: &[&str]| Task::new(
+            crate::domain::WorkDirectory::new(dir.to_string()).unwrap(),
+            crate::domain::Prompt::new("Test task".to_string()).unwrap(),
+            priority,
+            tags.iter().map(|t| crate::domain::TaskTag::new(t.to_string()).unwrap()).collect(),
+        );
+        
+        let mut tasks = vec![
+            new_task("/srv/api", TaskPriority::High, &["urgent"]),
+            new_task("/srv/api/v2", TaskPriority::Low, &["urgent", "rust"]),
+            new_task("/srv/apix", TaskPriority::Medium, &[]),
+            new_task("/srv/web", TaskPriority::Low, &["rust"]),
+        ];
+        tasks[0].created_at = "2025-01-01T08:00:00Z".parse().unwrap();
+        tasks[1].start(crate::domain::WorkerId::new("worker-1".to_string()).unwrap()).unwrap();
+        tasks[1].fail("boom".to_string()).unwrap();
+        tasks[1].retry_count = 2;
+        tasks[2].cancel(None).unwrap();
+        for task in &tasks {
+            repo.create_task(task).await.unwrap();
+        }
+        
+        for input in [
+            "status:failed AND (tag:urgent OR priority:high)",
+            "tag:urgent OR priority>=medium",
+            "NOT tag:rust",
+            "tag!=urgent",
+            "dir:/srv/api",
+            "dir!=/srv/api/",
+            "worker:worker-1",
+            "worker!=worker-1",
+            "cancel_reason:requested",
+            "cancel_reason!=requested",
+            "created:2025-01-01",
+            "created!=2025-01-01",
+            "created>2025-01-01T12:00:00Z",
+            "started<2100-01-01 OR completed>=2000-01-01",
+            "started!=2100-01-01",
+            "retries>1",
+            "priority<high status!=waiting",
+        ] {
+            let expr = FilterExpr::parse(input).unwrap();
+            let (listed, total) = repo.list_tasks(&TaskFilter::new().with_expression(expr.clone())).await.unwrap();
+            let mut listed: Vec<_> = listed.into_iter().map(|t| t.id).collect();
+            let mut expected: Vec<_> = tasks.iter().filter(|t| expr.matches(t)).map(|t| t.id).collect();
+            listed.sort_by_key(|id| id.to_string());
+            expected.sort_by_key(|id| id.to_string());
+            assert_eq!(listed, expected, "{}", input);
+            assert_eq!(total as usize, expected.len(), "{}", input);
+        }
+        
+        let (listed, _) = repo.list_tasks(&TaskFilter::new().with_expression(FilterExpr::parse("dir:/srv/api").unwrap())).await.unwrap();
+        assert_eq!(listed.len(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_list_tasks_sort_is_stable_and_matches_compare() {
+        use crate::models::TaskSort;
+        
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = SqliteTaskRepository::with_pool(pool).await.unwrap();
+        let created_at: chrono::DateTime<Utc> = "2025-01-01T08:00:00Z".parse().unwrap();
+        let mut tasks = Vec::new();
+        for (i, priority) in [TaskPriority::Low, TaskPriority::High, TaskPriority::Medium, TaskPriority::High, TaskPriority::Low, TaskPriority::High]
+            .into_iter()
+            .enumerate()
+        {
+            let mut task = Task::new(
+                crate::domain::WorkDirectory::new(format!("/srv/{}", i % 2)).unwrap(),
+                crate::domain::Prompt::new("Test task".to_string()).unwrap(),
+                priority,
+                vec![],
+            );
+            // 一半任务的创建时间相同，依赖 task_id 决定顺序
+            task.created_at = created_at + chrono::Duration::milliseconds((i % 3) as i64 * 1500);
+            if i % 2 == 0 {
+                task.start(crate::domain::WorkerId::new("worker-1".to_string()).unwrap()).unwrap();
+            }
+            repo.create_task(&task).await.unwrap();
+            tasks.push(task);
+        }
+        
+        for input in ["priority:desc,created_at:asc", "created_at", "started_at:desc,work_directory", "status,retry_count:desc", "task_id:desc"] {
+            let sort = TaskSort::parse(input).unwrap();
+            let mut expected = tasks.clone();
+            sort.sort(&mut expected);
+            let expected: Vec<_> = expected.into_iter().map(|t| t.id).collect();
+            
+            let filter = TaskFilter::new().with_sort(sort);
+            let (listed, _) = repo.list_tasks(&filter).await.unwrap();
+            assert_eq!(listed.into_iter().map(|t| t.id).collect::<Vec<_>>(), expected, "{}", input);
+            
+            // 分页拼接结果与整页一致
+            let mut paged = Vec::new();
+            for offset in (0..tasks.len()).step_by(4) {
+                let (page, _) = repo.list_tasks(&filter.clone().with_limit(4).with_offset(offset as i64)).await.unwrap();
+                paged.extend(page.into_iter().map(|t| t.id));
+            }
+            assert_eq!(paged, expected, "{}", input);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::config::EncryptionConfig;
+use crate::errors::{AppError, AppResult};
+
+/// 密文前缀，不带前缀的值视为历史明文
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// AES-GCM 随机数长度
+const NONCE_LEN: usize = 12;
+
+/// 字段加密器
+///
+/// 密文格式为 `enc:v1:<密钥ID>:<base64(nonce || ciphertext)>`。加密始终使用当前密钥，
+/// 解密按密文中的密钥ID选择密钥，因此轮换后旧数据仍可读取。
+pub struct FieldCipher {
+    active_key_id: String,
+    keys: HashMap<String, Aes256Gcm>,
+}
+
+impl FieldCipher {
+    /// 根据配置构建加密器
+    pub fn from_config(config: &EncryptionConfig) -> AppResult<Self> {
+        let mut encoded_keys = config.keys.clone();
+        if let Some(var) = &config.key_env {
+            let key = std::env::var(var).map_err(|_| {
+                AppError::Internal(format!("Encryption key environment variable {} is not set", var))
+            })?;
+            encoded_keys.insert(config.active_key_id.clone(), key);
+        }
+
+        let mut keys = HashMap::new();
+        for (key_id, encoded) in &encoded_keys {
+            if key_id.contains(':') {
+                return Err(AppError::Internal(format!("Invalid encryption key id '{}'", key_id)));
+            }
+            let bytes = STANDARD.decode(encoded.trim()).map_err(|e| {
+                AppError::Internal(format!("Invalid encryption key '{}': {}", key_id, e))
+            })?;
+            if bytes.len() != 32 {
+                return Err(AppError::Internal(format!(
+                    "Encryption key '{}' must be 32 bytes, got {}", key_id, bytes.len()
+                )));
+            }
+            keys.insert(key_id.clone(), Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes)));
+        }
+
+        if !keys.contains_key(&config.active_key_id) {
+            return Err(AppError::Internal(format!(
+                "Encryption key '{}' is not configured", config.active_key_id
+            )));
+        }
+
+        Ok(Self {
+            active_key_id: config.active_key_id.clone(),
+            keys,
+        })
+    }
+
+    /// 使用当前密钥加密
+    pub fn encrypt(&self, plaintext: &str) -> AppResult<String> {
+        let cipher = &self.keys[&self.active_key_id];
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| AppError::Internal("Failed to encrypt field".to_string()))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}:{}", ENCRYPTED_PREFIX, self.active_key_id, STANDARD.encode(payload)))
+    }
+
+    /// 解密字段，历史明文原样返回
+    pub fn decrypt(&self, value: &str) -> AppResult<String> {
+        let Some(rest) = value.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let (key_id, encoded) = rest
+            .split_once(':')
+            .ok_or_else(|| AppError::Internal("Malformed encrypted field".to_string()))?;
+        let cipher = self.keys.get(key_id).ok_or_else(|| {
+            AppError::Internal(format!("Unknown encryption key '{}'", key_id))
+        })?;
+
+        let payload = STANDARD
+            .decode(encoded)
+            .map_err(|_| AppError::Internal("Malformed encrypted field".to_string()))?;
+        if payload.len() < NONCE_LEN {
+            return Err(AppError::Internal("Malformed encrypted field".to_string()));
+        }
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| AppError::Internal(format!("Failed to decrypt field with key '{}'", key_id)))?;
+
+        String::from_utf8(plaintext).map_err(|e| AppError::Internal(e.to_string()))
+    }
+
+    /// 字段是否已使用当前密钥加密
+    pub fn is_current(&self, value: &str) -> bool {
+        value
+            .strip_prefix(ENCRYPTED_PREFIX)
+            .and_then(|rest| rest.split_once(':'))
+            .is_some_and(|(key_id, _)| key_id == self.active_key_id)
+    }
+
+    /// 解密后使用当前密钥重新加密，已是当前密钥的字段返回 `None`
+    pub fn reencrypt(&self, value: &str) -> AppResult<Option<String>> {
+        if self.is_current(value) {
+            return Ok(None);
+        }
+        self.decrypt(value).and_then(|plaintext| self.encrypt(&plaintext)).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(active: &str, keys: &[(&str, [u8; 32])]) -> EncryptionConfig {
+        EncryptionConfig {
+            enabled: true,
+            active_key_id: active.to_string(),
+            keys: keys.iter().map(|(id, key)| (id.to_string(), STANDARD.encode(key))).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_and_plaintext_passthrough() {
+        let cipher = FieldCipher::from_config(&config("k1", &[("k1", [7; 32])])).unwrap();
+        let sealed = cipher.encrypt("Refactor the parser").unwrap();
+
+        assert!(sealed.starts_with("enc:v1:k1:"));
+        assert_ne!(sealed, cipher.encrypt("Refactor the parser").unwrap());
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "Refactor the parser");
+        assert_eq!(cipher.decrypt("legacy plaintext").unwrap(), "legacy plaintext");
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let old = FieldCipher::from_config(&config("k1", &[("k1", [1; 32])])).unwrap();
+        let sealed = old.encrypt("result output").unwrap();
+
+        let rotated = FieldCipher::from_config(&config("k2", &[("k1", [1; 32]), ("k2", [2; 32])])).unwrap();
+        assert!(!rotated.is_current(&sealed));
+        assert_eq!(rotated.decrypt(&sealed).unwrap(), "result output");
+
+        let resealed = rotated.reencrypt(&sealed).unwrap().unwrap();
+        assert!(resealed.starts_with("enc:v1:k2:"));
+        assert!(rotated.reencrypt(&resealed).unwrap().is_none());
+        assert!(old.decrypt(&resealed).is_err());
+    }
+}
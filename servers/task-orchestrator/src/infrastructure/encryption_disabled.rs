@@ -0,0 +1,33 @@
+//! 字段加密（未启用）
+//!
+//! 未启用 `encryption` feature 时使用的占位实现：配置启用加密时启动失败，
+//! 避免在期望加密的部署中把敏感字段以明文写入数据库。
+
+use crate::config::EncryptionConfig;
+use crate::errors::{AppError, AppResult};
+
+/// 字段加密器占位，不存在实例
+pub struct FieldCipher {
+    never: std::convert::Infallible,
+}
+
+impl FieldCipher {
+    /// 始终返回配置错误
+    pub fn from_config(_config: &EncryptionConfig) -> AppResult<Self> {
+        Err(AppError::Configuration(config::ConfigError::Message(
+            "security.encryption.enabled requires building with the `encryption` feature".to_string(),
+        )))
+    }
+
+    pub fn encrypt(&self, _plaintext: &str) -> AppResult<String> {
+        match self.never {}
+    }
+
+    pub fn decrypt(&self, _value: &str) -> AppResult<String> {
+        match self.never {}
+    }
+
+    pub fn reencrypt(&self, _value: &str) -> AppResult<Option<String>> {
+        match self.never {}
+    }
+}
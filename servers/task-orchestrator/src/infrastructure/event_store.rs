@@ -0,0 +1,686 @@
+use sqlx::{Sqlite, SqliteConnection, Transaction};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::domain::{Task, TaskId, TaskHistory, TaskComment, MetadataSchema, PipelineRun, TaskStatus, TaskEvent, TaskEventType, EventRecord, WorkerId};
+use crate::models::{LoadSample, SlaSample, TaskEventRecord, TaskFilter, TaskStatistics};
+use crate::errors::{AppError, AppResult};
+use super::database::{SqliteTaskRepository, TaskRepository};
+use super::encryption::FieldCipher;
+use super::pool::ManagedPool;
+
+/// 事件存储特征
+///
+/// 只读接口；事件由 [`EventSourcedTaskRepository`] 与投影在同一事务中写入。
+#[async_trait::async_trait]
+pub trait EventStore: Send + Sync {
+    /// 获取单个任务的事件流
+    async fn load_task_events(&self, task_id: &TaskId) -> AppResult<Vec<TaskEvent>>;
+
+    /// 获取指定序列号之后的全部事件
+    async fn load_events_after(&self, sequence: u64) -> AppResult<Vec<TaskEvent>>;
+
+    /// 获取指定序列号之后的至多 `limit` 条事件（复制接口分页拉取）
+    async fn load_events_page(&self, sequence: u64, limit: u32) -> AppResult<Vec<TaskEvent>>;
+
+    /// 最新事件的序列号，没有事件时为0
+    async fn last_sequence(&self) -> AppResult<u64>;
+}
+
+/// SQLite事件存储实现
+pub struct SqliteEventStore {
+    pool: Arc<ManagedPool>,
+    cipher: Option<Arc<FieldCipher>>,
+}
+
+impl SqliteEventStore {
+    /// 使用带健康监控的共享连接池
+    pub fn with_managed_pool(pool: Arc<ManagedPool>) -> Self {
+        Self { pool, cipher: None }
+    }
+
+    /// 设置字段加密器，事件快照将加密存储
+    pub fn with_cipher(mut self, cipher: Arc<FieldCipher>) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// 开始写事务，返回前已持有数据库写锁
+    ///
+    /// sqlx 0.7 只能发出延迟的 `BEGIN`，事务先读后写时可能因快照过期直接得到 `SQLITE_BUSY`，
+    /// 不会等待 busy_timeout。开始后立即执行一次空写入提前获取写锁，之后的读取都在写锁下进行。
+    pub(crate) async fn begin_write(&self) -> AppResult<Transaction<'static, Sqlite>> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("UPDATE task_events SET sequence = sequence WHERE 0")
+            .execute(&mut *tx)
+            .await?;
+        Ok(tx)
+    }
+
+    /// 加密事件快照和载荷，转换为待写入的记录
+    fn seal(&self, event: &TaskEvent) -> AppResult<TaskEventRecord> {
+        let mut record = TaskEventRecord::from_domain(event)?;
+        if let Some(cipher) = &self.cipher {
+            record.snapshot = record.snapshot.as_deref().map(|v| cipher.encrypt(v)).transpose()?;
+            record.payload = record.payload.as_deref().map(|v| cipher.encrypt(v)).transpose()?;
+        }
+        Ok(record)
+    }
+
+    /// 在给定连接（可以是事务）上追加事件，返回事件序列号
+    pub(crate) async fn append_in(&self, conn: &mut SqliteConnection, event: &TaskEvent) -> AppResult<u64> {
+        let record = self.seal(event)?;
+        let result = sqlx::query(
+            "INSERT INTO task_events (task_id, event_type, snapshot, payload, occurred_at) VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(&record.task_id)
+        .bind(&record.event_type)
+        .bind(&record.snapshot)
+        .bind(&record.payload)
+        .bind(record.occurred_at)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(result.last_insert_rowid() as u64)
+    }
+
+    /// 在给定连接上按原序列号写入从主实例复制的事件，序列号已存在时忽略并返回false
+    pub(crate) async fn append_replicated_in(&self, conn: &mut SqliteConnection, event: &TaskEvent) -> AppResult<bool> {
+        let record = self.seal(event)?;
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO task_events (sequence, task_id, event_type, snapshot, payload, occurred_at) VALUES (?, ?, ?, ?, ?, ?)"
+        )
+        .bind(record.sequence)
+        .bind(&record.task_id)
+        .bind(&record.event_type)
+        .bind(&record.snapshot)
+        .bind(&record.payload)
+        .bind(record.occurred_at)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 解密事件快照和载荷并转换为领域事件
+    fn open(&self, mut record: TaskEventRecord) -> AppResult<TaskEvent> {
+        if let Some(cipher) = &self.cipher {
+            record.snapshot = record.snapshot.as_deref().map(|v| cipher.decrypt(v)).transpose()?;
+            record.payload = record.payload.as_deref().map(|v| cipher.decrypt(v)).transpose()?;
+        }
+        record.to_domain().map_err(|e| AppError::Internal(e.to_string()))
+    }
+
+    /// 将明文和旧密钥加密的快照、载荷重新加密为当前密钥，返回更新的行数
+    pub async fn reencrypt_existing(&self) -> AppResult<u64> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(0);
+        };
+
+        let rows = sqlx::query_as::<_, (i64, Option<String>, Option<String>)>(
+            "SELECT sequence, snapshot, payload FROM task_events WHERE snapshot IS NOT NULL OR payload IS NOT NULL"
+        )
+        .fetch_all(&mut *self.pool.acquire().await?)
+        .await?;
+
+        let mut updated = 0;
+        for (sequence, snapshot, payload) in rows {
+            let sealed_snapshot = snapshot.as_deref().map(|v| cipher.reencrypt(v)).transpose()?.flatten();
+            let sealed_payload = payload.as_deref().map(|v| cipher.reencrypt(v)).transpose()?.flatten();
+            if sealed_snapshot.is_none() && sealed_payload.is_none() {
+                continue;
+            }
+            sqlx::query("UPDATE task_events SET snapshot = ?, payload = ? WHERE sequence = ?")
+                .bind(sealed_snapshot.or(snapshot))
+                .bind(sealed_payload.or(payload))
+                .bind(sequence)
+                .execute(&mut *self.pool.acquire().await?)
+                .await?;
+            updated += 1;
+        }
+
+        Ok(updated)
+    }
+}
+
+#[async_trait::async_trait]
+impl EventStore for SqliteEventStore {
+    async fn load_task_events(&self, task_id: &TaskId) -> AppResult<Vec<TaskEvent>> {
+        let records = sqlx::query_as::<_, TaskEventRecord>(
+            "SELECT * FROM task_events WHERE task_id = ? ORDER BY sequence ASC"
+        )
+        .bind(task_id.to_string())
+        .fetch_all(&mut *self.pool.acquire().await?)
+        .await?;
+
+        records
+            .into_iter()
+            .map(|r| self.open(r))
+            .collect()
+    }
+
+    async fn load_events_after(&self, sequence: u64) -> AppResult<Vec<TaskEvent>> {
+        let records = sqlx::query_as::<_, TaskEventRecord>(
+            "SELECT * FROM task_events WHERE sequence > ? ORDER BY sequence ASC"
+        )
+        .bind(sequence as i64)
+        .fetch_all(&mut *self.pool.acquire().await?)
+        .await?;
+
+        records
+            .into_iter()
+            .map(|r| self.open(r))
+            .collect()
+    }
+
+    async fn load_events_page(&self, sequence: u64, limit: u32) -> AppResult<Vec<TaskEvent>> {
+        let records = sqlx::query_as::<_, TaskEventRecord>(
+            "SELECT * FROM task_events WHERE sequence > ? ORDER BY sequence ASC LIMIT ?"
+        )
+        .bind(sequence as i64)
+        .bind(limit as i64)
+        .fetch_all(&mut *self.pool.acquire().await?)
+        .await?;
+
+        records
+            .into_iter()
+            .map(|r| self.open(r))
+            .collect()
+    }
+
+    async fn last_sequence(&self) -> AppResult<u64> {
+        let (sequence,) = sqlx::query_as::<_, (i64,)>("SELECT COALESCE(MAX(sequence), 0) FROM task_events")
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?;
+        Ok(sequence as u64)
+    }
+}
+
+/// 事件溯源任务仓库
+///
+/// 每个写操作在同一个事务中先追加事件、再更新投影，任一步失败整体回滚，
+/// 投影中不会出现事件流无法解释的状态。事件存储和投影仓库必须共用同一个连接池。
+/// 读操作直接走投影；任务历史由事件流派生，不再单独写入历史表。
+pub struct EventSourcedTaskRepository {
+    event_store: Arc<SqliteEventStore>,
+    projection: Arc<SqliteTaskRepository>,
+}
+
+impl EventSourcedTaskRepository {
+    pub fn new(event_store: Arc<SqliteEventStore>, projection: Arc<SqliteTaskRepository>) -> Self {
+        Self {
+            event_store,
+            projection,
+        }
+    }
+
+    /// 重放全部事件，重建投影中的任务状态，返回修改的任务数
+    ///
+    /// 投影中没有事件对应的行（包括已删除的任务）会被删除。
+    pub async fn rebuild_projection(&self) -> AppResult<u64> {
+        let events = self.event_store.load_events_after(0).await?;
+        let mut state: HashMap<TaskId, Task> = HashMap::new();
+        for event in &events {
+            event.apply(&mut state);
+        }
+
+        let mut tx = self.event_store.begin_write().await?;
+        // 逐行删除而不是清空任务表，清空会级联删除评论等关联数据
+        let existing = sqlx::query_scalar::<_, String>("SELECT task_id FROM tasks")
+            .fetch_all(&mut *tx)
+            .await?;
+        let mut rebuilt = 0;
+        for task_id in existing.iter().filter_map(|id| TaskId::from_str(id).ok()) {
+            if !state.contains_key(&task_id) {
+                self.projection.delete_task_in(&mut tx, &task_id).await?;
+                rebuilt += 1;
+            }
+        }
+
+        let mut synced = HashSet::new();
+        for event in &events {
+            if !synced.insert(event.task_id) {
+                continue;
+            }
+            if let Some(task) = state.get(&event.task_id) {
+                if self.sync_projection_in(&mut tx, &event.task_id, Some(task)).await? {
+                    rebuilt += 1;
+                }
+            }
+        }
+        tx.commit().await?;
+
+        Ok(rebuilt)
+    }
+
+    /// 写入从主实例复制的事件并更新投影，返回实际写入的事件数（已存在的序列号跳过）
+    pub async fn apply_replicated(&self, events: &[TaskEvent]) -> AppResult<u64> {
+        let mut applied = 0;
+        for event in events {
+            let mut tx = self.event_store.begin_write().await?;
+            if !self.event_store.append_replicated_in(&mut tx, event).await? {
+                continue;
+            }
+            match (&event.comment, &event.record) {
+                (Some(comment), _) => {
+                    self.projection.add_task_comment_in(&mut tx, comment).await?;
+                }
+                (None, Some(record)) => self.apply_record_in(&mut tx, record).await?,
+                (None, None) => {
+                    let mut state = HashMap::new();
+                    event.apply(&mut state);
+                    self.sync_projection_in(&mut tx, &event.task_id, state.get(&event.task_id)).await?;
+                }
+            }
+            tx.commit().await?;
+            applied += 1;
+        }
+        Ok(applied)
+    }
+
+    /// 本地事件日志中最新事件的序列号
+    pub async fn last_sequence(&self) -> AppResult<u64> {
+        self.event_store.last_sequence().await
+    }
+
+    /// 加载已归档任务的事件，任务未归档时返回 `None`
+    ///
+    /// 段文件只在执行归档的实例本地，热备（包括提升后）从复制来的事件日志读取已归档的任务。
+    async fn archived_events(&self, task_id: &TaskId) -> AppResult<Option<Vec<TaskEvent>>> {
+        let events = self.event_store.load_task_events(task_id).await?;
+        let last_state = events
+            .iter()
+            .rev()
+            .find(|e| !matches!(e.event_type, TaskEventType::Commented | TaskEventType::Recorded));
+        if last_state.map(|e| e.event_type) != Some(TaskEventType::Archived) {
+            return Ok(None);
+        }
+        Ok(Some(events))
+    }
+
+    /// 把记录事件写入投影
+    async fn apply_record_in(&self, conn: &mut SqliteConnection, record: &EventRecord) -> AppResult<()> {
+        match record {
+            EventRecord::PipelineRun(run) => self.projection.save_pipeline_run_in(conn, run).await,
+            EventRecord::MetadataSchema(schema) => self.projection.save_metadata_schema_in(conn, schema).await,
+            EventRecord::MetadataSchemaDeleted(namespace) => {
+                self.projection.delete_metadata_schema_in(conn, namespace).await.map(|_| ())
+            }
+        }
+    }
+
+    /// 让投影中的任务与事件重放得到的状态一致，返回是否有修改
+    async fn sync_projection_in(
+        &self,
+        conn: &mut SqliteConnection,
+        task_id: &TaskId,
+        expected: Option<&Task>,
+    ) -> AppResult<bool> {
+        let current = self.projection.get_hot_task_in(conn, task_id).await?;
+        match (expected, current) {
+            (Some(task), None) => {
+                self.projection.insert_task_in(conn, task).await?;
+            }
+            (Some(task), Some(current)) if !same_state(task, &current) => {
+                // 投影仓库使用乐观锁，按投影当前版本写入事件快照
+                let mut task = task.clone();
+                task.version = current.version + 1;
+                self.projection.update_task_in(conn, &task).await?;
+            }
+            (None, Some(_)) => {
+                self.projection.delete_task_in(conn, task_id).await?;
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// 比较两个任务状态是否一致（忽略投影版本号）
+fn same_state(expected: &Task, current: &Task) -> bool {
+    let mut expected = expected.clone();
+    expected.version = current.version;
+    match (serde_json::to_value(&expected), serde_json::to_value(current)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskRepository for EventSourcedTaskRepository {
+    async fn create_task(&self, task: &Task) -> AppResult<TaskId> {
+        let mut tx = self.event_store.begin_write().await?;
+        self.event_store.append_in(&mut tx, &TaskEvent::from_transition(None, task)).await?;
+        self.projection.insert_task_in(&mut tx, task).await?;
+        tx.commit().await?;
+        Ok(task.id)
+    }
+
+    async fn get_task(&self, task_id: &TaskId) -> AppResult<Option<Task>> {
+        if let Some(task) = self.projection.get_task(task_id).await? {
+            return Ok(Some(task));
+        }
+        let Some(events) = self.archived_events(task_id).await? else {
+            return Ok(None);
+        };
+        Ok(events.into_iter().rev().find_map(|e| e.snapshot))
+    }
+
+    async fn update_task(&self, task: &Task) -> AppResult<()> {
+        let mut tx = self.event_store.begin_write().await?;
+        let previous = self.projection.get_hot_task_in(&mut tx, &task.id).await?;
+        self.event_store.append_in(&mut tx, &TaskEvent::from_transition(previous.as_ref(), task)).await?;
+        // 版本冲突时事务回滚，事件随之撤销
+        self.projection.update_task_in(&mut tx, task).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_task(&self, task_id: &TaskId) -> AppResult<()> {
+        let mut tx = self.event_store.begin_write().await?;
+        self.event_store.append_in(&mut tx, &TaskEvent::deleted(*task_id)).await?;
+        self.projection.delete_task_in(&mut tx, task_id).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_next_task(
+        &self,
+        work_directory: &str,
+        worker_id: &str,
+        capabilities: Option<&[String]>,
+    ) -> AppResult<Option<Task>> {
+        let mut tx = self.event_store.begin_write().await?;
+        let Some(acquired) = self.projection.next_task_in(&mut tx, work_directory, capabilities).await? else {
+            return Ok(None);
+        };
+
+        // 在写锁下选中任务，不会被其他实例抢先；与投影仓库一样返回获取前的任务
+        let mut started = acquired.clone();
+        started.start(WorkerId::new(worker_id.to_string())?)?;
+        self.event_store.append_in(&mut tx, &TaskEvent::from_transition(Some(&acquired), &started)).await?;
+        self.projection.update_task_in(&mut tx, &started).await?;
+        tx.commit().await?;
+        Ok(Some(acquired))
+    }
+
+    async fn list_tasks(&self, filter: &TaskFilter) -> AppResult<(Vec<Task>, u64)> {
+        self.projection.list_tasks(filter).await
+    }
+
+    async fn get_statistics(&self) -> AppResult<TaskStatistics> {
+        self.projection.get_statistics().await
+    }
+
+    async fn waiting_tag_sets(&self) -> AppResult<Vec<(Vec<String>, u64)>> {
+        self.projection.waiting_tag_sets().await
+    }
+
+    async fn reconcile_statistics(&self) -> AppResult<u64> {
+        self.projection.reconcile_statistics().await
+    }
+
+    async fn sla_samples(&self, created_after: DateTime<Utc>) -> AppResult<Vec<SlaSample>> {
+        self.projection.sla_samples(created_after).await
+    }
+
+    async fn load_sample(&self, since: DateTime<Utc>) -> AppResult<LoadSample> {
+        self.projection.load_sample(since).await
+    }
+
+    async fn archive_tasks(&self, older_than: DateTime<Utc>, limit: u32) -> AppResult<Vec<TaskId>> {
+        let Some((segment, moves)) = self.projection.write_cold_segment(older_than, limit).await? else {
+            return Ok(Vec::new());
+        };
+
+        // 移出热表的同时追加归档事件，重建投影时不会把已归档的任务恢复到热表
+        let mut tx = self.event_store.begin_write().await?;
+        let mut archived = Vec::new();
+        for moved in &moves {
+            if self.projection.move_to_cold_in(&mut tx, &segment, moved).await? {
+                let task_id = TaskId::from_str(&moved.task_id)?;
+                self.event_store.append_in(&mut tx, &TaskEvent::archived(task_id)).await?;
+                archived.push(task_id);
+            }
+        }
+        tx.commit().await?;
+
+        Ok(archived)
+    }
+
+    async fn create_task_history(&self, _history: &TaskHistory) -> AppResult<u64> {
+        // 历史由事件流派生，无需单独写入
+        Ok(0)
+    }
+
+    async fn get_task_history(&self, task_id: &TaskId) -> AppResult<Vec<TaskHistory>> {
+        let events = self.event_store.load_task_events(task_id).await?;
+        Ok(events.iter().rev().filter_map(|e| e.to_history()).collect())
+    }
+
+    async fn add_task_comment(&self, comment: &TaskComment) -> AppResult<u64> {
+        // 评论不影响任务状态，但要随事件流复制到热备，事件携带投影分配的评论ID
+        let mut tx = self.event_store.begin_write().await?;
+        let id = self.projection.add_task_comment_in(&mut tx, comment).await?;
+        let comment = TaskComment { id, ..comment.clone() };
+        self.event_store.append_in(&mut tx, &TaskEvent::comment_added(comment)).await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    async fn get_task_comments(&self, task_id: &TaskId) -> AppResult<Vec<TaskComment>> {
+        let comments = self.projection.get_task_comments(task_id).await?;
+        if !comments.is_empty() {
+            return Ok(comments);
+        }
+        let Some(events) = self.archived_events(task_id).await? else {
+            return Ok(comments);
+        };
+        Ok(events.into_iter().filter_map(|e| e.comment).collect())
+    }
+
+    async fn save_metadata_schema(&self, schema: &MetadataSchema) -> AppResult<()> {
+        // 元数据模式属于配置，不改变任务状态，以记录事件复制到热备
+        let record = EventRecord::MetadataSchema(schema.clone());
+        let mut tx = self.event_store.begin_write().await?;
+        self.apply_record_in(&mut tx, &record).await?;
+        self.event_store.append_in(&mut tx, &TaskEvent::recorded(record)).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_metadata_schema(&self, namespace: &str) -> AppResult<bool> {
+        let mut tx = self.event_store.begin_write().await?;
+        let deleted = self.projection.delete_metadata_schema_in(&mut tx, namespace).await?;
+        if deleted {
+            let record = EventRecord::MetadataSchemaDeleted(namespace.to_string());
+            self.event_store.append_in(&mut tx, &TaskEvent::recorded(record)).await?;
+        }
+        tx.commit().await?;
+        Ok(deleted)
+    }
+
+    async fn list_metadata_schemas(&self) -> AppResult<Vec<MetadataSchema>> {
+        self.projection.list_metadata_schemas().await
+    }
+
+    async fn save_pipeline_run(&self, run: &PipelineRun) -> AppResult<()> {
+        let record = EventRecord::PipelineRun(run.clone());
+        let mut tx = self.event_store.begin_write().await?;
+        self.apply_record_in(&mut tx, &record).await?;
+        self.event_store.append_in(&mut tx, &TaskEvent::recorded(record)).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_pipeline_run(&self, run_id: &str) -> AppResult<Option<PipelineRun>> {
+        self.projection.get_pipeline_run(run_id).await
+    }
+
+    async fn cleanup_expired_tasks(&self, older_than: DateTime<Utc>) -> AppResult<u64> {
+        let mut cleaned = 0;
+        for status in [TaskStatus::Completed, TaskStatus::Failed, TaskStatus::Cancelled] {
+            let (tasks, _) = self.projection.list_tasks(&TaskFilter::new().with_status(status)).await?;
+            for task in tasks {
+                if task.completed_at.map(|t| t < older_than).unwrap_or(false) {
+                    self.delete_task(&task.id).await?;
+                    cleaned += 1;
+                }
+            }
+        }
+        Ok(cleaned)
+    }
+
+    async fn retry_failed_tasks(&self, max_retries: u32) -> AppResult<Vec<TaskId>> {
+        let (tasks, _) = self.projection.list_tasks(&TaskFilter::new().with_status(TaskStatus::Failed)).await?;
+        let mut retried = Vec::new();
+        for mut task in tasks {
+            if task.retry_count >= max_retries {
+                continue;
+            }
+            task.max_retries = task.max_retries.max(max_retries);
+            if task.retry().is_ok() {
+                self.update_task(&task).await?;
+                retried.push(task.id);
+            }
+        }
+        Ok(retried)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{TaskPriority, WorkDirectory, Prompt, WorkerId, TaskResult};
+    use crate::infrastructure::SqliteTaskRepository;
+
+    async fn create_repositories() -> (EventSourcedTaskRepository, Arc<SqliteEventStore>, Arc<dyn TaskRepository>) {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let pool = Arc::new(ManagedPool::new(pool));
+        let projection = Arc::new(SqliteTaskRepository::with_managed_pool(pool.clone()).await.unwrap());
+        let event_store = Arc::new(SqliteEventStore::with_managed_pool(pool));
+        let repo = EventSourcedTaskRepository::new(event_store.clone(), projection.clone());
+        (repo, event_store, projection)
+    }
+
+    fn new_task() -> Task {
+        Task::new(
+            WorkDirectory::new("/test".to_string()).unwrap(),
+            Prompt::new("Test task".to_string()).unwrap(),
+            TaskPriority::Medium,
+            vec![],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_transitions_are_appended_as_events() {
+        let (repo, event_store, _) = create_repositories().await;
+        let mut task = new_task();
+        repo.create_task(&task).await.unwrap();
+
+        task.start(WorkerId::new("worker-1".to_string()).unwrap()).unwrap();
+        repo.update_task(&task).await.unwrap();
+        task.complete(TaskResult::success("Done".to_string())).unwrap();
+        repo.update_task(&task).await.unwrap();
+
+        let events = event_store.load_task_events(&task.id).await.unwrap();
+        let types: Vec<_> = events.iter().map(|e| e.event_type).collect();
+        assert_eq!(types, vec![TaskEventType::Created, TaskEventType::Started, TaskEventType::Completed]);
+
+        let history = repo.get_task_history(&task.id).await.unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].status, TaskStatus::Completed);
+
+        let projected = repo.get_task(&task.id).await.unwrap().unwrap();
+        assert_eq!(projected.status, TaskStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_projection_from_events() {
+        let (repo, event_store, projection) = create_repositories().await;
+        let mut task = new_task();
+        let created = task.clone();
+        task.start(WorkerId::new("worker-1".to_string()).unwrap()).unwrap();
+        let mut tx = event_store.begin_write().await.unwrap();
+        event_store.append_in(&mut tx, &TaskEvent::from_transition(None, &created)).await.unwrap();
+        event_store.append_in(&mut tx, &TaskEvent::from_transition(Some(&created), &task)).await.unwrap();
+        tx.commit().await.unwrap();
+
+        // 没有事件对应的投影行（例如已删除任务的残留）在重建时删除
+        let orphan = new_task();
+        projection.create_task(&orphan).await.unwrap();
+
+        assert!(projection.get_task(&task.id).await.unwrap().is_none());
+        assert_eq!(repo.rebuild_projection().await.unwrap(), 2);
+
+        let rebuilt = projection.get_task(&task.id).await.unwrap().unwrap();
+        assert_eq!(rebuilt.status, TaskStatus::Working);
+        assert!(projection.get_task(&orphan.id).await.unwrap().is_none());
+        assert_eq!(repo.rebuild_projection().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_archived_tasks_stay_out_of_rebuilt_projection() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let pool = Arc::new(ManagedPool::new(pool));
+        let projection = SqliteTaskRepository::with_managed_pool(pool.clone()).await.unwrap()
+            .with_cold_storage(Arc::new(crate::infrastructure::ColdStorage::new(dir.path()).unwrap()));
+        let event_store = Arc::new(SqliteEventStore::with_managed_pool(pool));
+        let repo = EventSourcedTaskRepository::new(event_store.clone(), Arc::new(projection));
+
+        let mut task = new_task();
+        repo.create_task(&task).await.unwrap();
+        task.start(WorkerId::new("worker-1".to_string()).unwrap()).unwrap();
+        repo.update_task(&task).await.unwrap();
+        task.complete(TaskResult::success("Done".to_string())).unwrap();
+        task.completed_at = Some(Utc::now() - chrono::Duration::days(40));
+        repo.update_task(&task).await.unwrap();
+
+        let cutoff = Utc::now() - chrono::Duration::days(30);
+        assert_eq!(repo.archive_tasks(cutoff, 100).await.unwrap().len(), 1);
+        let events = event_store.load_task_events(&task.id).await.unwrap();
+        assert_eq!(events.last().unwrap().event_type, TaskEventType::Archived);
+
+        // 重放事件不会把已归档的任务恢复到热表，按ID仍从冷存储读取
+        assert_eq!(repo.rebuild_projection().await.unwrap(), 0);
+        let (hot, _) = repo.list_tasks(&TaskFilter::new()).await.unwrap();
+        assert!(hot.is_empty());
+        let archived = repo.get_task(&task.id).await.unwrap().unwrap();
+        assert_eq!(archived.status, TaskStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_failed_write_appends_no_event() {
+        let (repo, event_store, _) = create_repositories().await;
+        let task = new_task();
+        repo.create_task(&task).await.unwrap();
+
+        // 版本冲突的更新整体回滚，不留下事件
+        let mut stale = task.clone();
+        stale.version += 5;
+        assert!(matches!(repo.update_task(&stale).await, Err(AppError::ConcurrencyConflict)));
+        assert!(repo.delete_task(&TaskId::new()).await.is_err());
+        assert_eq!(event_store.last_sequence().await.unwrap(), 1);
+
+        // 获取任务时事件和投影一起写入
+        let acquired = repo.get_next_task("/test", "worker-1", None).await.unwrap().unwrap();
+        assert_eq!(acquired.id, task.id);
+        let events = event_store.load_task_events(&task.id).await.unwrap();
+        assert_eq!(events.last().unwrap().event_type, TaskEventType::Started);
+        let projected = repo.get_task(&task.id).await.unwrap().unwrap();
+        assert_eq!(projected.status, TaskStatus::Working);
+        assert_eq!(projected.version, events.last().unwrap().snapshot.as_ref().unwrap().version);
+
+        assert!(repo.get_next_task("/test", "worker-2", None).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ExternalService, ExternalServiceConfig};
+use crate::errors::{current_request_context, AppError, AppResult, CORRELATION_ID_HEADER, TRACE_ID_HEADER};
+use crate::utils::concurrency::CircuitBreakerState;
+use crate::utils::CircuitBreaker;
+
+/// 重试等待时间上限
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(30);
+
+/// 外部服务客户端统计快照
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServiceClientStats {
+    pub requests: u64,
+    pub retries: u64,
+    pub failures: u64,
+    /// 熔断器打开时被直接拒绝的请求数
+    pub rejected: u64,
+    /// closed / open / half_open，未启用熔断器时为空
+    pub circuit: Option<String>,
+}
+
+#[derive(Default)]
+struct ClientCounters {
+    requests: AtomicU64,
+    retries: AtomicU64,
+    failures: AtomicU64,
+    rejected: AtomicU64,
+}
+
+/// 外部服务HTTP客户端
+///
+/// 按 `[external_services.services.<name>]` 配置设置请求超时和API密钥。
+/// 连接错误、超时、429和5xx响应按指数退避重试，其他4xx直接返回；
+/// 在请求处理中发出的请求携带当前请求的 `x-trace-id` 和 `x-correlation-id`；
+/// 每次失败的尝试计入熔断器，熔断器打开期间请求不再发出。
+pub struct ServiceClient {
+    name: String,
+    base_url: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+    retries: u32,
+    retry_backoff: Duration,
+    breaker: Option<CircuitBreaker>,
+    counters: ClientCounters,
+}
+
+impl ServiceClient {
+    /// 根据服务配置创建客户端
+    pub fn from_config(name: &str, config: &ExternalService) -> AppResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout.max(1)))
+            .build()
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            base_url: config.url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone(),
+            client,
+            retries: config.retries,
+            retry_backoff: Duration::from_millis(config.retry_backoff_ms),
+            breaker: config.enable_circuit_breaker.then(|| {
+                CircuitBreaker::new(
+                    config.circuit_breaker_threshold.max(1),
+                    Duration::from_secs(config.circuit_breaker_timeout),
+                )
+            }),
+            counters: ClientCounters::default(),
+        })
+    }
+
+    /// 服务名称
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 拼接服务地址
+    pub fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path.trim_start_matches('/'))
+    }
+
+    /// 发送请求，`build` 每次尝试都会重新调用以构造请求
+    pub async fn send<F>(&self, build: F) -> AppResult<reqwest::Response>
+    where
+        F: Fn(&reqwest::Client) -> reqwest::RequestBuilder,
+    {
+        self.counters.requests.fetch_add(1, Ordering::Relaxed);
+        let mut attempt = 0;
+
+        loop {
+            if let Some(breaker) = &self.breaker {
+                if !breaker.allow_request().await {
+                    self.counters.rejected.fetch_add(1, Ordering::Relaxed);
+                    return Err(AppError::ServiceUnavailable(format!(
+                        "Circuit breaker for external service '{}' is open",
+                        self.name
+                    )));
+                }
+            }
+
+            let mut request = build(&self.client);
+            if let Some(api_key) = &self.api_key {
+                request = request.bearer_auth(api_key);
+            }
+            // 在请求处理中调用外部服务时传递追踪ID和关联ID，便于跨服务排查
+            if let Some(context) = current_request_context() {
+                request = request.header(TRACE_ID_HEADER, &context.trace_id);
+                if let Some(correlation_id) = &context.correlation_id {
+                    request = request.header(CORRELATION_ID_HEADER, correlation_id);
+                }
+            }
+
+            let (error, retryable) = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    if let Some(breaker) = &self.breaker {
+                        breaker.record_success().await;
+                    }
+                    return Ok(response);
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let retryable = status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+                    let error = AppError::ServiceUnavailable(format!(
+                        "External service '{}' returned status {}",
+                        self.name, status
+                    ));
+                    // 客户端错误说明请求本身有问题，不代表服务故障
+                    if !retryable {
+                        self.counters.failures.fetch_add(1, Ordering::Relaxed);
+                        return Err(error);
+                    }
+                    (error, true)
+                }
+                Err(e) => {
+                    let retryable = e.is_timeout() || e.is_connect() || e.is_request();
+                    (
+                        AppError::ServiceUnavailable(format!("External service '{}' request failed: {}", self.name, e)),
+                        retryable,
+                    )
+                }
+            };
+
+            if let Some(breaker) = &self.breaker {
+                breaker.record_failure().await;
+            }
+            if !retryable || attempt >= self.retries {
+                self.counters.failures.fetch_add(1, Ordering::Relaxed);
+                return Err(error);
+            }
+
+            let backoff = self
+                .retry_backoff
+                .saturating_mul(2u32.saturating_pow(attempt))
+                .min(MAX_RETRY_BACKOFF);
+            tracing::warn!(
+                "{}; retrying in {:?} ({}/{})",
+                error, backoff, attempt + 1, self.retries
+            );
+            self.counters.retries.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    /// 获取统计快照
+    pub async fn stats(&self) -> ServiceClientStats {
+        let circuit = match &self.breaker {
+            Some(breaker) => Some(match breaker.get_state().await {
+                CircuitBreakerState::Closed { .. } => "closed",
+                CircuitBreakerState::Open { .. } => "open",
+                CircuitBreakerState::HalfOpen => "half_open",
+            }
+            .to_string()),
+            None => None,
+        };
+        ServiceClientStats {
+            requests: self.counters.requests.load(Ordering::Relaxed),
+            retries: self.counters.retries.load(Ordering::Relaxed),
+            failures: self.counters.failures.load(Ordering::Relaxed),
+            rejected: self.counters.rejected.load(Ordering::Relaxed),
+            circuit,
+        }
+    }
+}
+
+/// 按名称索引的外部服务客户端
+#[derive(Default)]
+pub struct ServiceClients {
+    clients: HashMap<String, Arc<ServiceClient>>,
+}
+
+impl ServiceClients {
+    /// 为配置中的每个服务创建客户端
+    pub fn from_config(config: &ExternalServiceConfig) -> AppResult<Self> {
+        let clients = config
+            .services
+            .iter()
+            .map(|(name, service)| Ok((name.clone(), Arc::new(ServiceClient::from_config(name, service)?))))
+            .collect::<AppResult<HashMap<_, _>>>()?;
+        Ok(Self { clients })
+    }
+
+    /// 获取指定服务的客户端
+    pub fn get(&self, name: &str) -> Option<Arc<ServiceClient>> {
+        self.clients.get(name).cloned()
+    }
+
+    /// 获取全部服务的统计快照
+    pub async fn stats(&self) -> HashMap<String, ServiceClientStats> {
+        let mut stats = HashMap::with_capacity(self.clients.len());
+        for (name, client) in &self.clients {
+            stats.insert(name.clone(), client.stats().await);
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use std::sync::atomic::AtomicU32;
+
+    async fn get_json(client: &ServiceClient, path: &str) -> AppResult<serde_json::Value> {
+        let url = client.url(path);
+        let response = client.send(|http| http.get(&url)).await?;
+        Ok(response.json().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_retries_and_circuit_breaker() {
+        // 前两次请求返回503，之后成功
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let app = axum::Router::new()
+            .route("/flaky", get(move || {
+                let counter = counter.clone();
+                async move {
+                    match counter.fetch_add(1, Ordering::SeqCst) {
+                        0 | 1 => (StatusCode::SERVICE_UNAVAILABLE, "{}"),
+                        _ => (StatusCode::OK, r#"{"ok":true}"#),
+                    }
+                }
+            }))
+            .route("/missing", get(|| async { StatusCode::NOT_FOUND }))
+            .route("/down", get(|| async { StatusCode::BAD_GATEWAY }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = ServiceClient::from_config("mock", &ExternalService {
+            url,
+            retries: 2,
+            retry_backoff_ms: 1,
+            circuit_breaker_threshold: 3,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let body = get_json(&client, "/flaky").await.unwrap();
+        assert_eq!(body["ok"], true);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // 4xx不重试
+        assert!(get_json(&client, "missing").await.is_err());
+
+        // 连续失败打开熔断器后不再发出请求
+        assert!(get_json(&client, "down").await.is_err());
+        assert!(get_json(&client, "flaky").await.is_err());
+
+        let stats = client.stats().await;
+        assert_eq!((stats.requests, stats.retries, stats.failures, stats.rejected), (4, 4, 2, 1));
+        assert_eq!(stats.circuit.as_deref(), Some("open"));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_propagates_correlation_id() {
+        let app = axum::Router::new().route("/echo", get(|headers: axum::http::HeaderMap| async move {
+            let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+            axum::Json(serde_json::json!({
+                "trace_id": header(TRACE_ID_HEADER),
+                "correlation_id": header(CORRELATION_ID_HEADER),
+            }))
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let client = ServiceClient::from_config("mock", &ExternalService { url, ..Default::default() }).unwrap();
+
+        let body = get_json(&client, "/echo").await.unwrap();
+        assert!(body["correlation_id"].is_null());
+
+        let context = crate::errors::RequestContext {
+            trace_id: "trace-1".to_string(),
+            correlation_id: Some("order-42".to_string()),
+            instance: "/api/v1/tasks".to_string(),
+            locale: Default::default(),
+            timestamp_format: None,
+        };
+        let body = crate::errors::REQUEST_CONTEXT
+            .scope(context, get_json(&client, "/echo"))
+            .await
+            .unwrap();
+        assert_eq!(body, serde_json::json!({"trace_id": "trace-1", "correlation_id": "order-42"}));
+    }
+}
@@ -0,0 +1,58 @@
+use crate::errors::{AppError, AppResult};
+
+/// 事件批量发布特征
+#[async_trait::async_trait]
+pub trait EventBatchPublisher: Send + Sync {
+    /// 发布一批记录到指定主题
+    async fn publish_batch(&self, topic: &str, records: &[(String, serde_json::Value)]) -> AppResult<()>;
+}
+
+/// 基于Kafka REST Proxy (v2) 的发布器
+pub struct KafkaRestPublisher {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl KafkaRestPublisher {
+    pub fn new(base_url: String, timeout: std::time::Duration) -> AppResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl EventBatchPublisher for KafkaRestPublisher {
+    async fn publish_batch(&self, topic: &str, records: &[(String, serde_json::Value)]) -> AppResult<()> {
+        let body = serde_json::json!({
+            "records": records
+                .iter()
+                .map(|(key, value)| serde_json::json!({ "key": key, "value": value }))
+                .collect::<Vec<_>>(),
+        });
+
+        let response = self.client
+            .post(format!("{}/topics/{}", self.base_url, topic))
+            .header("Content-Type", "application/vnd.kafka.json.v2+json")
+            .header("Accept", "application/vnd.kafka.v2+json")
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(format!("Kafka REST request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ServiceUnavailable(format!(
+                "Kafka REST proxy returned status {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
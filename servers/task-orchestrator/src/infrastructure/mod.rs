@@ -1,3 +1,23 @@
 pub mod database;
+pub mod event_store;
+pub mod queue;
+pub mod kafka;
+#[cfg_attr(not(feature = "encryption"), path = "encryption_disabled.rs")]
+pub mod encryption;
+pub mod cache;
+pub mod pool;
+pub mod http_client;
+pub mod cold_storage;
+pub mod replication;
 
-pub use database::{TaskRepository, LockManager, SqliteTaskRepository, SqliteLockManager};
\ No newline at end of file
+pub use database::{TaskRepository, LockManager, SqliteTaskRepository, SqliteLockManager};
+pub use event_store::{EventStore, SqliteEventStore, EventSourcedTaskRepository};
+pub use queue::{MessageQueue, QueueMessage, NatsQueue, RabbitMqQueue};
+pub use kafka::{EventBatchPublisher, KafkaRestPublisher};
+pub use object_storage::{ObjectStore, S3ObjectStore, S3Settings};
+pub use encryption::FieldCipher;
+pub use cache::{CachedTaskRepository, CacheStats};
+pub use pool::{ManagedPool, PoolStats};
+pub use http_client::{ServiceClient, ServiceClients, ServiceClientStats};
+pub use cold_storage::ColdStorage;
+pub use replication::{ReplicationBatch, ReplicationSource, HttpReplicationSource, REPLICATION_EVENTS_PATH};
@@ -0,0 +1,256 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock, Weak};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use sqlx::pool::PoolConnection;
+use sqlx::{Pool, Sqlite, Transaction};
+use tokio::task::JoinHandle;
+
+/// 两次重建连接池之间的最短间隔，避免数据库持续不可用时反复重建
+const REBUILD_BACKOFF: Duration = Duration::from_secs(5);
+
+/// 连接池统计快照
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: usize,
+    pub in_use: u32,
+    /// 正在等待连接的请求数
+    pub waiting: u64,
+    pub acquires: u64,
+    pub slow_acquires: u64,
+    pub acquire_failures: u64,
+    pub failed_health_checks: u64,
+    pub consecutive_failures: u32,
+    pub rebuilds: u64,
+}
+
+#[derive(Default)]
+struct PoolCounters {
+    waiting: AtomicU64,
+    acquires: AtomicU64,
+    slow_acquires: AtomicU64,
+    acquire_failures: AtomicU64,
+    failed_health_checks: AtomicU64,
+    consecutive_failures: AtomicU32,
+    rebuilds: AtomicU64,
+}
+
+/// 带健康监控的SQLite连接池
+///
+/// 所有获取连接的操作都经过此处统计等待数和耗时，超过阈值的获取记录警告日志。
+/// 连续失败达到上限后使用原有的连接选项重建连接池并重试一次，
+/// 旧连接池在借出的连接归还后关闭。
+pub struct ManagedPool {
+    current: RwLock<Pool<Sqlite>>,
+    slow_acquire_threshold: Duration,
+    max_consecutive_failures: u32,
+    rebuilding: AtomicBool,
+    last_rebuild: RwLock<Option<Instant>>,
+    counters: PoolCounters,
+}
+
+impl ManagedPool {
+    /// 包装已创建的连接池
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self {
+            current: RwLock::new(pool),
+            slow_acquire_threshold: Duration::from_millis(100),
+            max_consecutive_failures: 3,
+            rebuilding: AtomicBool::new(false),
+            last_rebuild: RwLock::new(None),
+            counters: PoolCounters::default(),
+        }
+    }
+
+    /// 设置慢获取告警阈值
+    pub fn with_slow_acquire_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_acquire_threshold = threshold;
+        self
+    }
+
+    /// 设置触发重建的连续失败次数，0表示不自动重建
+    pub fn with_max_consecutive_failures(mut self, max_consecutive_failures: u32) -> Self {
+        self.max_consecutive_failures = max_consecutive_failures;
+        self
+    }
+
+    /// 当前使用的连接池
+    pub fn get(&self) -> Pool<Sqlite> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// 获取连接
+    pub async fn acquire(&self) -> Result<PoolConnection<Sqlite>, sqlx::Error> {
+        let result = self.instrumented(|pool| async move { pool.acquire().await }).await;
+        match result {
+            Err(e) if self.try_rebuild().await => {
+                tracing::warn!("Retrying connection acquire on rebuilt pool after: {}", e);
+                self.instrumented(|pool| async move { pool.acquire().await }).await
+            }
+            result => result,
+        }
+    }
+
+    /// 开始事务
+    pub async fn begin(&self) -> Result<Transaction<'static, Sqlite>, sqlx::Error> {
+        let result = self.instrumented(|pool| async move { pool.begin().await }).await;
+        match result {
+            Err(e) if self.try_rebuild().await => {
+                tracing::warn!("Retrying transaction begin on rebuilt pool after: {}", e);
+                self.instrumented(|pool| async move { pool.begin().await }).await
+            }
+            result => result,
+        }
+    }
+
+    /// 执行一次存活查询
+    pub async fn check(&self) -> Result<(), sqlx::Error> {
+        let result = async {
+            let mut conn = self.acquire().await?;
+            sqlx::query("SELECT 1").execute(&mut *conn).await?;
+            Ok(())
+        }
+        .await;
+
+        if let Err(e) = &result {
+            self.counters.failed_health_checks.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!("Database health check failed: {}", e);
+        }
+        result
+    }
+
+    /// 启动后台存活检查，连接池释放后自动退出
+    pub fn start_health_check(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let pool: Weak<Self> = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(pool) = pool.upgrade() else { break };
+                let _ = pool.check().await;
+            }
+        })
+    }
+
+    /// 获取统计快照
+    pub fn stats(&self) -> PoolStats {
+        let pool = self.get();
+        let size = pool.size();
+        let idle = pool.num_idle();
+        PoolStats {
+            size,
+            idle,
+            in_use: size.saturating_sub(idle as u32),
+            waiting: self.counters.waiting.load(Ordering::Relaxed),
+            acquires: self.counters.acquires.load(Ordering::Relaxed),
+            slow_acquires: self.counters.slow_acquires.load(Ordering::Relaxed),
+            acquire_failures: self.counters.acquire_failures.load(Ordering::Relaxed),
+            failed_health_checks: self.counters.failed_health_checks.load(Ordering::Relaxed),
+            consecutive_failures: self.counters.consecutive_failures.load(Ordering::Relaxed),
+            rebuilds: self.counters.rebuilds.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 统计等待数、耗时和连续失败次数
+    async fn instrumented<T, F, Fut>(&self, acquire: F) -> Result<T, sqlx::Error>
+    where
+        F: FnOnce(Pool<Sqlite>) -> Fut,
+        Fut: std::future::Future<Output = Result<T, sqlx::Error>>,
+    {
+        let started = Instant::now();
+        self.counters.waiting.fetch_add(1, Ordering::Relaxed);
+        let result = acquire(self.get()).await;
+        self.counters.waiting.fetch_sub(1, Ordering::Relaxed);
+        self.counters.acquires.fetch_add(1, Ordering::Relaxed);
+
+        let elapsed = started.elapsed();
+        if elapsed >= self.slow_acquire_threshold {
+            self.counters.slow_acquires.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!("Slow database connection acquire: {:?}", elapsed);
+        }
+
+        match &result {
+            Ok(_) => self.counters.consecutive_failures.store(0, Ordering::Relaxed),
+            Err(e) => {
+                self.counters.acquire_failures.fetch_add(1, Ordering::Relaxed);
+                let failures = self.counters.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                tracing::warn!("Failed to acquire database connection ({} in a row): {}", failures, e);
+            }
+        }
+        result
+    }
+
+    /// 连续失败达到上限时重建连接池，重建成功返回true
+    async fn try_rebuild(&self) -> bool {
+        if self.max_consecutive_failures == 0
+            || self.counters.consecutive_failures.load(Ordering::Relaxed) < self.max_consecutive_failures
+        {
+            return false;
+        }
+        if let Some(last) = *self.last_rebuild.read().unwrap() {
+            if last.elapsed() < REBUILD_BACKOFF {
+                return false;
+            }
+        }
+        // 同一时间只允许一个重建
+        if self.rebuilding.swap(true, Ordering::AcqRel) {
+            return false;
+        }
+        *self.last_rebuild.write().unwrap() = Some(Instant::now());
+
+        let old = self.get();
+        let rebuilt = old.options().clone().connect_with((*old.connect_options()).clone()).await;
+        self.rebuilding.store(false, Ordering::Release);
+
+        match rebuilt {
+            Ok(pool) => {
+                *self.current.write().unwrap() = pool;
+                self.counters.consecutive_failures.store(0, Ordering::Relaxed);
+                self.counters.rebuilds.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!("Rebuilt database connection pool after repeated failures");
+                tokio::spawn(async move { old.close().await });
+                true
+            }
+            Err(e) => {
+                tracing::error!("Failed to rebuild database connection pool: {}", e);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rebuilds_after_repeated_failures() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .acquire_timeout(Duration::from_millis(50))
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let managed = ManagedPool::new(pool)
+            .with_slow_acquire_threshold(Duration::from_millis(20))
+            .with_max_consecutive_failures(2);
+
+        managed.check().await.unwrap();
+        // 唯一的连接被占用，获取超时
+        let held = managed.acquire().await.unwrap();
+        assert_eq!(managed.stats().in_use, 1);
+        assert!(managed.acquire().await.is_err());
+
+        // 第二次失败触发重建，并在新连接池上重试成功
+        let conn = managed.acquire().await.unwrap();
+        drop((held, conn));
+
+        let stats = managed.stats();
+        assert_eq!(stats.rebuilds, 1);
+        assert_eq!((stats.acquire_failures, stats.slow_acquires), (2, 2));
+        assert_eq!((stats.consecutive_failures, stats.waiting), (0, 0));
+    }
+}
@@ -0,0 +1,520 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::sync::{mpsc, Mutex};
+
+use crate::errors::{AppError, AppResult};
+
+/// 队列消息
+#[derive(Debug, Clone)]
+pub struct QueueMessage {
+    pub subject: String,
+    pub payload: Vec<u8>,
+}
+
+/// 消息队列特征
+#[async_trait::async_trait]
+pub trait MessageQueue: Send + Sync {
+    /// 发布消息
+    async fn publish(&self, subject: &str, payload: &[u8]) -> AppResult<()>;
+
+    /// 订阅主题，返回消息接收端
+    async fn subscribe(&self, subject: &str) -> AppResult<mpsc::Receiver<QueueMessage>>;
+
+    /// 处理失败后把消息放回订阅的主题，等待重新投递
+    async fn requeue(&self, message: &QueueMessage) -> AppResult<()> {
+        self.publish(&message.subject, &message.payload).await
+    }
+}
+
+/// 根据工作目录生成队列主题
+///
+/// 例如 `/home/user/project` 映射为 `tasks.home.user.project`，
+/// 目录名中的 `.`、空格等字符替换为 `_`，以满足 NATS/AMQP 的主题格式。
+pub fn subject_for_work_directory(prefix: &str, work_directory: &str) -> String {
+    let tokens: Vec<String> = work_directory
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.chars()
+                .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+                .collect()
+        })
+        .collect();
+
+    if tokens.is_empty() {
+        format!("{}._root", prefix)
+    } else {
+        format!("{}.{}", prefix, tokens.join("."))
+    }
+}
+
+/// RabbitMQ单次拉取的最大消息数，也是接收端的容量
+const RABBITMQ_MAX_FETCH: usize = 100;
+
+fn queue_error(message: impl Into<String>) -> AppError {
+    AppError::ServiceUnavailable(message.into())
+}
+
+/// 通过管理接口拉取一批消息
+///
+/// 认证失败、队列被删除等非成功状态返回包含状态码的错误，而不是当作空队列。
+async fn fetch_rabbitmq_messages(
+    client: &reqwest::Client,
+    get_url: &str,
+    username: &str,
+    password: &str,
+    count: usize,
+) -> AppResult<Vec<serde_json::Value>> {
+    let body = serde_json::json!({
+        "count": count,
+        "ackmode": "ack_requeue_false",
+        "encoding": "auto",
+    });
+    let response = client
+        .post(get_url)
+        .basic_auth(username, Some(password))
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| queue_error(format!("RabbitMQ poll failed: {}", e)))?;
+    let response = response.error_for_status().map_err(|e| {
+        let status = e.status().map(|status| status.to_string()).unwrap_or_default();
+        queue_error(format!("RabbitMQ poll returned status {}", status))
+    })?;
+    response
+        .json()
+        .await
+        .map_err(|e| queue_error(format!("Invalid RabbitMQ poll response: {}", e)))
+}
+
+/// 订阅表：订阅ID -> (主题, 消息发送端)
+type NatsSubscriptions = Arc<Mutex<HashMap<u64, (String, mpsc::Sender<QueueMessage>)>>>;
+
+/// NATS断线重连的初始间隔和最长间隔
+const NATS_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const NATS_MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// 解析 `MSG`/`HMSG` 行，返回 (主题, 订阅ID, 头部字节数, 总字节数)
+///
+/// - `MSG <subject> <sid> [reply-to] <#bytes>`
+/// - `HMSG <subject> <sid> [reply-to] <#header bytes> <#total bytes>`
+fn parse_message_line(parts: &[&str]) -> Option<(String, u64, usize, usize)> {
+    let (header_len, total) = match parts {
+        ["MSG", _, _, .., total] if parts.len() <= 5 => (0, total.parse().ok()?),
+        ["HMSG", _, _, .., header, total] if parts.len() >= 5 && parts.len() <= 6 => {
+            (header.parse().ok()?, total.parse().ok()?)
+        }
+        _ => return None,
+    };
+    if header_len > total {
+        return None;
+    }
+    Some((parts[1].to_string(), parts[2].parse().ok()?, header_len, total))
+}
+
+/// NATS消息队列实现（基于NATS文本协议）
+///
+/// 连接断开后按指数退避自动重连并恢复全部订阅；断线期间发布返回错误，
+/// 期间发往订阅主题的消息不会补发（NATS核心协议不持久化消息）。
+pub struct NatsQueue {
+    writer: Arc<Mutex<Option<OwnedWriteHalf>>>,
+    subscriptions: NatsSubscriptions,
+    next_sid: AtomicU64,
+}
+
+impl NatsQueue {
+    /// 连接NATS服务器，`url` 形如 `nats://127.0.0.1:4222`，首次连接失败时返回错误
+    pub async fn connect(url: &str, client_name: &str) -> AppResult<Self> {
+        let address = url.trim_start_matches("nats://").to_string();
+        let client_name = client_name.to_string();
+        let writer = Arc::new(Mutex::new(None));
+        let subscriptions: NatsSubscriptions = Arc::new(Mutex::new(HashMap::new()));
+        let mut reader = Self::open(&address, &client_name, &writer, &subscriptions).await?;
+
+        let conn_writer = writer.clone();
+        let conn_subscriptions = subscriptions.clone();
+        tokio::spawn(async move {
+            loop {
+                Self::read_loop(reader, &conn_writer, &conn_subscriptions).await;
+                *conn_writer.lock().await = None;
+
+                let mut delay = NATS_RECONNECT_DELAY;
+                reader = loop {
+                    tokio::time::sleep(delay).await;
+                    match Self::open(&address, &client_name, &conn_writer, &conn_subscriptions).await {
+                        Ok(reader) => {
+                            tracing::info!("Reconnected to NATS at {}", address);
+                            break reader;
+                        }
+                        Err(e) => {
+                            tracing::warn!("NATS reconnect failed, retrying in {:?}: {}", delay, e);
+                            delay = (delay * 2).min(NATS_MAX_RECONNECT_DELAY);
+                        }
+                    }
+                };
+            }
+        });
+
+        Ok(Self {
+            writer,
+            subscriptions,
+            next_sid: AtomicU64::new(1),
+        })
+    }
+
+    /// 建立连接、完成握手并恢复已有订阅，返回读取端
+    async fn open(
+        address: &str,
+        client_name: &str,
+        writer: &Mutex<Option<OwnedWriteHalf>>,
+        subscriptions: &NatsSubscriptions,
+    ) -> AppResult<OwnedReadHalf> {
+        let stream = TcpStream::connect(address)
+            .await
+            .map_err(|e| queue_error(format!("Failed to connect to NATS: {}", e)))?;
+        let (reader, mut write_half) = stream.into_split();
+
+        let connect = serde_json::json!({
+            "verbose": false,
+            "pedantic": false,
+            "name": client_name,
+            "lang": "rust",
+            "headers": true,
+        });
+        // 持有订阅表直到写端就绪，避免期间新增的订阅既不在握手中也发不出去
+        let subscriptions = subscriptions.lock().await;
+        let mut handshake = format!("CONNECT {}\r\nPING\r\n", connect);
+        for (sid, (subject, _)) in subscriptions.iter() {
+            handshake.push_str(&format!("SUB {} {}\r\n", subject, sid));
+        }
+        write_half
+            .write_all(handshake.as_bytes())
+            .await
+            .map_err(|e| queue_error(e.to_string()))?;
+        *writer.lock().await = Some(write_half);
+        Ok(reader)
+    }
+
+    /// 读取并分发服务器消息，连接断开或协议错误时返回
+    async fn read_loop(
+        reader: OwnedReadHalf,
+        writer: &Mutex<Option<OwnedWriteHalf>>,
+        subscriptions: &NatsSubscriptions,
+    ) {
+        let mut reader = BufReader::new(reader);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match reader.read_line(&mut line).await {
+                Ok(0) => {
+                    tracing::warn!("NATS connection closed");
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::error!("NATS read error: {}", e);
+                    return;
+                }
+            }
+
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.first().copied() {
+                Some("PING") => {
+                    if let Some(w) = writer.lock().await.as_mut() {
+                        let _ = w.write_all(b"PONG\r\n").await;
+                    }
+                }
+                Some("MSG" | "HMSG") => {
+                    let Some((subject, sid, header_len, total)) = parse_message_line(&parts) else {
+                        tracing::error!("Malformed NATS message line: {}", line.trim_end());
+                        return;
+                    };
+                    let mut payload = vec![0u8; total + 2];
+                    if reader.read_exact(&mut payload).await.is_err() {
+                        return;
+                    }
+                    payload.truncate(total);
+                    // 头部不属于消息内容
+                    payload.drain(..header_len);
+
+                    let sender = subscriptions.lock().await.get(&sid).map(|(_, sender)| sender.clone());
+                    if let Some(sender) = sender {
+                        let _ = sender.send(QueueMessage { subject, payload }).await;
+                    }
+                }
+                Some("-ERR") => {
+                    tracing::error!("NATS error: {}", line.trim_end());
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MessageQueue for NatsQueue {
+    async fn publish(&self, subject: &str, payload: &[u8]) -> AppResult<()> {
+        let mut frame = format!("PUB {} {}\r\n", subject, payload.len()).into_bytes();
+        frame.extend_from_slice(payload);
+        frame.extend_from_slice(b"\r\n");
+
+        let mut w = self.writer.lock().await;
+        let w = w.as_mut().ok_or_else(|| queue_error("NATS is not connected"))?;
+        w.write_all(&frame).await.map_err(|e| queue_error(e.to_string()))
+    }
+
+    async fn subscribe(&self, subject: &str) -> AppResult<mpsc::Receiver<QueueMessage>> {
+        let sid = self.next_sid.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = mpsc::channel(1024);
+        let mut subscriptions = self.subscriptions.lock().await;
+        subscriptions.insert(sid, (subject.to_string(), tx));
+
+        // 断线期间的订阅在重连握手时发送
+        if let Some(w) = self.writer.lock().await.as_mut() {
+            w.write_all(format!("SUB {} {}\r\n", subject, sid).as_bytes())
+                .await
+                .map_err(|e| queue_error(e.to_string()))?;
+        }
+        Ok(rx)
+    }
+}
+
+/// RabbitMQ消息队列实现（基于管理插件HTTP API）
+///
+/// 发布到主题交换机，路由键即主题；订阅时声明同名队列并绑定到交换机后轮询拉取。
+/// HTTP接口在拉取时即确认消息，处理失败的消息由 [`MessageQueue::requeue`] 重新放回同一队列；
+/// 为缩短已确认未处理的窗口，只按接收端的剩余容量拉取。
+pub struct RabbitMqQueue {
+    client: reqwest::Client,
+    base_url: String,
+    vhost: String,
+    exchange: String,
+    username: String,
+    password: String,
+    poll_interval: std::time::Duration,
+}
+
+impl RabbitMqQueue {
+    pub fn new(
+        base_url: String,
+        vhost: String,
+        exchange: String,
+        username: String,
+        password: String,
+        poll_interval: std::time::Duration,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            vhost,
+            exchange,
+            username,
+            password,
+            poll_interval,
+        }
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!("{}/api/{}", self.base_url, path)
+    }
+
+    fn encoded_vhost(&self) -> String {
+        self.vhost.replace('/', "%2F")
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> AppResult<reqwest::Response> {
+        let response = request
+            .basic_auth(&self.username, Some(&self.password))
+            .send()
+            .await
+            .map_err(|e| queue_error(format!("RabbitMQ request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(queue_error(format!("RabbitMQ returned status {}", response.status())));
+        }
+        Ok(response)
+    }
+
+    /// 声明交换机
+    pub async fn declare_exchange(&self) -> AppResult<()> {
+        let url = self.api_url(&format!("exchanges/{}/{}", self.encoded_vhost(), self.exchange));
+        self.send(self.client.put(url).json(&serde_json::json!({ "type": "topic", "durable": true })))
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl MessageQueue for RabbitMqQueue {
+    async fn publish(&self, subject: &str, payload: &[u8]) -> AppResult<()> {
+        let url = self.api_url(&format!("exchanges/{}/{}/publish", self.encoded_vhost(), self.exchange));
+        let body = serde_json::json!({
+            "properties": { "delivery_mode": 2 },
+            "routing_key": subject,
+            "payload": String::from_utf8_lossy(payload),
+            "payload_encoding": "string",
+        });
+        self.send(self.client.post(url).json(&body)).await?;
+        Ok(())
+    }
+
+    async fn subscribe(&self, subject: &str) -> AppResult<mpsc::Receiver<QueueMessage>> {
+        let vhost = self.encoded_vhost();
+        let queue_url = self.api_url(&format!("queues/{}/{}", vhost, subject));
+        self.send(self.client.put(queue_url).json(&serde_json::json!({ "durable": true })))
+            .await?;
+
+        let binding_url = self.api_url(&format!("bindings/{}/e/{}/q/{}", vhost, self.exchange, subject));
+        self.send(self.client.post(binding_url).json(&serde_json::json!({ "routing_key": subject })))
+            .await?;
+
+        let (tx, rx) = mpsc::channel(RABBITMQ_MAX_FETCH);
+        let get_url = self.api_url(&format!("queues/{}/{}/get", vhost, subject));
+        let client = self.client.clone();
+        let username = self.username.clone();
+        let password = self.password.clone();
+        let poll_interval = self.poll_interval;
+        let subject = subject.to_string();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_interval);
+            loop {
+                interval.tick().await;
+                let count = tx.capacity();
+                if count == 0 {
+                    continue;
+                }
+                let messages = match fetch_rabbitmq_messages(&client, &get_url, &username, &password, count).await {
+                    Ok(messages) => messages,
+                    Err(e) => {
+                        tracing::error!("{}", e);
+                        continue;
+                    }
+                };
+
+                for message in messages {
+                    let payload = message["payload"].as_str().unwrap_or_default().as_bytes().to_vec();
+                    if tx.send(QueueMessage { subject: subject.clone(), payload }).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    async fn requeue(&self, message: &QueueMessage) -> AppResult<()> {
+        // 经默认交换机按队列名投递，只回到原队列，不会重复投递给绑定到同一主题的其他队列
+        let url = self.api_url(&format!("exchanges/{}/amq.default/publish", self.encoded_vhost()));
+        let body = serde_json::json!({
+            "properties": { "delivery_mode": 2 },
+            "routing_key": message.subject,
+            "payload": String::from_utf8_lossy(&message.payload),
+            "payload_encoding": "string",
+        });
+        self.send(self.client.post(url).json(&body)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subject_for_work_directory() {
+        assert_eq!(subject_for_work_directory("tasks", "/home/user/project"), "tasks.home.user.project");
+        assert_eq!(subject_for_work_directory("tasks", "/srv/my app/v1.2"), "tasks.srv.my_app.v1_2");
+        assert_eq!(subject_for_work_directory("tasks", "/"), "tasks._root");
+    }
+
+    #[test]
+    fn test_parse_message_line() {
+        assert_eq!(parse_message_line(&["MSG", "tasks.ack", "3", "12"]), Some(("tasks.ack".to_string(), 3, 0, 12)));
+        assert_eq!(parse_message_line(&["MSG", "tasks.ack", "3", "_INBOX.1", "12"]), Some(("tasks.ack".to_string(), 3, 0, 12)));
+        assert_eq!(parse_message_line(&["HMSG", "tasks.ack", "3", "22", "34"]), Some(("tasks.ack".to_string(), 3, 22, 34)));
+        assert_eq!(parse_message_line(&["HMSG", "tasks.ack", "3", "_INBOX.1", "22", "34"]), Some(("tasks.ack".to_string(), 3, 22, 34)));
+        assert_eq!(parse_message_line(&["HMSG", "tasks.ack", "3", "40", "34"]), None);
+        assert_eq!(parse_message_line(&["MSG", "tasks.ack", "3", "x"]), None);
+        assert_eq!(parse_message_line(&["MSG", "tasks.ack"]), None);
+    }
+
+    /// 读取客户端发来的行直到出现以 `prefix` 开头的行
+    async fn read_until(reader: &mut BufReader<OwnedReadHalf>, prefix: &str) -> String {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            assert!(reader.read_line(&mut line).await.unwrap() > 0, "client closed before {}", prefix);
+            if line.starts_with(prefix) {
+                return line.trim_end().to_string();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nats_reconnects_and_resubscribes() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("nats://{}", listener.local_addr().unwrap());
+        let accept = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+            assert_eq!(read_until(&mut reader, "SUB").await, "SUB tasks.ack 1");
+            writer.write_all(b"MSG tasks.ack 1 5\r\nfirst\r\n").await.unwrap();
+            // 服务器断开连接，客户端应重连并在握手中恢复订阅
+            drop(writer);
+            drop(reader);
+
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+            assert!(read_until(&mut reader, "CONNECT").await.contains("\"headers\":true"));
+            assert_eq!(read_until(&mut reader, "SUB").await, "SUB tasks.ack 1");
+            writer.write_all(b"HMSG tasks.ack 1 12 18\r\nNATS/1.0\r\n\r\nsecond\r\n").await.unwrap();
+            read_until(&mut reader, "PUB").await
+        });
+
+        let queue = NatsQueue::connect(&url, "test").await.unwrap();
+        let mut receiver = queue.subscribe("tasks.ack").await.unwrap();
+        assert_eq!(receiver.recv().await.unwrap().payload, b"first");
+        assert_eq!(receiver.recv().await.unwrap().payload, b"second");
+        queue.publish("tasks.ack", b"ok").await.unwrap();
+        assert_eq!(accept.await.unwrap(), "PUB tasks.ack 2");
+    }
+
+    #[tokio::test]
+    async fn test_rabbitmq_poll_reports_error_status() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/queues/%2F/tasks.ack/get", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+            let mut content_length = 0;
+            let mut line = String::new();
+            while reader.read_line(&mut line).await.unwrap() > 2 {
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+                line.clear();
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).await.unwrap();
+            writer
+                .write_all(b"HTTP/1.1 401 Unauthorized\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+                .await
+                .unwrap();
+        });
+
+        // 错误状态不能被当作空队列
+        let err = fetch_rabbitmq_messages(&reqwest::Client::new(), &url, "guest", "wrong", 10)
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, AppError::ServiceUnavailable(message) if message.contains("401")), "{}", err);
+    }
+}
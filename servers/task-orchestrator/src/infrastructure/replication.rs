@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::TaskEvent;
+use crate::errors::{AppError, AppResult, ApiResponse};
+
+/// 主实例提供事件流的复制接口路径
+pub const REPLICATION_EVENTS_PATH: &str = "/api/v1/replication/events";
+
+/// 一批复制事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationBatch {
+    /// 按序列号升序排列的事件
+    pub events: Vec<TaskEvent>,
+    /// 主实例当前最新事件的序列号，用于计算复制延迟
+    pub last_sequence: u64,
+}
+
+/// 复制事件来源特征
+#[async_trait::async_trait]
+pub trait ReplicationSource: Send + Sync {
+    /// 拉取序列号 `after` 之后的至多 `limit` 条事件
+    async fn fetch(&self, after: u64, limit: u32) -> AppResult<ReplicationBatch>;
+}
+
+/// 通过HTTP复制接口从主实例拉取事件
+pub struct HttpReplicationSource {
+    client: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl HttpReplicationSource {
+    pub fn new(base_url: String, token: Option<String>, timeout: std::time::Duration) -> AppResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl ReplicationSource for HttpReplicationSource {
+    async fn fetch(&self, after: u64, limit: u32) -> AppResult<ReplicationBatch> {
+        let mut request = self.client
+            .get(format!("{}{}", self.base_url, REPLICATION_EVENTS_PATH))
+            .query(&[("after", after), ("limit", limit as u64)]);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(format!("Replication request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ServiceUnavailable(format!(
+                "Primary returned status {} for replication request",
+                response.status()
+            )));
+        }
+
+        let body: ApiResponse<ReplicationBatch> = response
+            .json()
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(format!("Invalid replication response: {}", e)))?;
+        body.data
+            .ok_or_else(|| AppError::ServiceUnavailable("Replication response has no data".to_string()))
+    }
+}
@@ -44,20 +44,36 @@
 //! export APP_SERVER_PORT=8080
 //! export RUST_LOG=info
 //! cargo run
+//!
+//! # 只运行启动自检，失败时退出码非零
+//! cargo run -- --check
+//!
+//! # 打印配置文件的JSON Schema / 部署前按Schema校验配置文件
+//! cargo run -- --print-config-schema
+//! cargo run -- --validate-config config/production.toml
 //! ```
 
 use std::sync::Arc;
+use std::future::IntoFuture;
 use std::net::SocketAddr;
 use tokio::signal;
 use tower_http::{trace::TraceLayer, cors::CorsLayer, compression::CompressionLayer, timeout::TimeoutLayer};
 use tower::ServiceBuilder;
 use tower_http::request_id::MakeRequestUuid;
 
-use crate::config::{ConfigManager, AppConfig};
-use crate::infrastructure::{TaskRepository, SqliteTaskRepository, SqliteLockManager};
-use crate::services::{TaskService, TaskScheduler, TaskMonitor};
-use crate::handlers::{create_routes, ApiState};
-use crate::utils::{LogManager, MetricsCollector, HealthChecker, ConcurrencyController, RateLimiter};
+use crate::config::{ConfigManager, AppConfig, QueueBackend, CacheType};
+use crate::infrastructure::{TaskRepository, EventStore, SqliteTaskRepository, SqliteLockManager, SqliteEventStore, EventSourcedTaskRepository, HttpReplicationSource, MessageQueue, NatsQueue, RabbitMqQueue, KafkaRestPublisher, S3ObjectStore, S3Settings, FieldCipher, CachedTaskRepository, ManagedPool, ServiceClients, ColdStorage};
+use crate::services::{TaskService, TaskScheduler, TaskMonitor, LeaderElector, QueueAckConsumer, TaskEventExporter, ExporterSettings, ArtifactStore, WorkDirectoryPolicy, SecretScanner, MaintenanceSchedule, PipelineRegistry, ApprovalPolicy, FailureClassifier, RetryPolicy, SlaTracker, HistoryWriter, HistoryWriterSettings, StandbyReplicator, run_self_check};
+use crate::handlers::{create_metrics_routes, create_routes, ApiState};
+use crate::handlers::recording::RequestRecorder;
+use crate::handlers::decompression::request_decompressor;
+#[cfg(unix)]
+use crate::utils::listener::{bind_tcp, ActivatedSockets};
+#[cfg(unix)]
+use unix_socket::{bind_unix_socket, remove_unix_socket, serve_unix_socket};
+use crate::utils::concurrency::RequestQuotas;
+use memory_watchdog::MemoryWatchdog;
+use crate::utils::{LogManager, MetricsCollector, HealthChecker, ConcurrencyController, RateLimiter, SystemdNotifier};
 
 mod config;
 mod domain;
@@ -107,6 +123,30 @@ mod utils;
 /// 4. 关闭数据库连接
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // 打印配置文件的JSON Schema后退出
+    if args.iter().any(|arg| arg == "--print-config-schema") {
+        println!("{}", serde_json::to_string_pretty(&AppConfig::json_schema())?);
+        return Ok(());
+    }
+
+    // 按Schema校验TOML/YAML配置文件后退出，不连接数据库，适合部署前的CI检查
+    if let Some(pos) = args.iter().position(|arg| arg == "--validate-config") {
+        let Some(path) = args.get(pos + 1) else {
+            eprintln!("--validate-config requires a config file path");
+            std::process::exit(2);
+        };
+        std::process::exit(config_schema::run_validate(&AppConfig::json_schema(), path));
+    }
+
+    // 自检模式：校验配置、数据库和迁移后退出，适合CI和容器入口
+    if args.iter().any(|arg| arg == "--check") {
+        let report = run_self_check().await;
+        println!("{}", report);
+        std::process::exit(if report.passed() { 0 } else { 1 });
+    }
+
     // 初始化配置
     let config_manager = ConfigManager::new()?;
     let config = config_manager.config().clone();
@@ -125,17 +165,103 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let pool = sqlx::sqlite::SqlitePoolOptions::new()
         .max_connections(config.database.max_connections)
         .min_connections(config.database.min_connections)
+        .acquire_timeout(std::time::Duration::from_secs(config.database.connection_timeout))
         .connect(&config.database.url)
         .await?;
+    // 仓库、事件存储和锁管理器共用同一个受监控的连接池，重建后一起切换
+    let pool = Arc::new(
+        ManagedPool::new(pool)
+            .with_slow_acquire_threshold(std::time::Duration::from_millis(config.database.slow_acquire_threshold_ms))
+            .with_max_consecutive_failures(config.database.max_consecutive_failures)
+    );
+    if config.database.health_check_interval > 0 {
+        pool.start_health_check(std::time::Duration::from_secs(config.database.health_check_interval));
+    }
+
+    // 创建字段加密器（静态数据加密）
+    let field_cipher = if config.security.encryption.enabled {
+        logger.log_info(&format!("Field encryption enabled, active key: {}", config.security.encryption.active_key_id), None);
+        Some(Arc::new(FieldCipher::from_config(&config.security.encryption)?))
+    } else {
+        None
+    };
 
     // 创建任务仓库
-    let task_repository: Arc<dyn TaskRepository> = Arc::new(
-        SqliteTaskRepository::with_pool(pool.clone()).await?
-    );
+    let mut sqlite_repository = SqliteTaskRepository::with_managed_pool(pool.clone()).await?;
+    let mut sqlite_event_store = SqliteEventStore::with_managed_pool(pool.clone());
+    if let Some(cipher) = &field_cipher {
+        sqlite_repository = sqlite_repository.with_cipher(cipher.clone());
+        sqlite_event_store = sqlite_event_store.with_cipher(cipher.clone());
+        if config.security.encryption.reencrypt_on_startup {
+            let tasks = sqlite_repository.reencrypt_existing().await?;
+            let events = sqlite_event_store.reencrypt_existing().await?;
+            logger.log_info(&format!("Re-encrypted {} task rows and {} event snapshots", tasks, events), None);
+        }
+    }
+    if config.cold_storage.enabled {
+        let cold_storage = ColdStorage::new(&config.cold_storage.directory)?;
+        logger.log_info(&format!("Cold storage enabled, directory: {}", config.cold_storage.directory), None);
+        sqlite_repository = sqlite_repository.with_cold_storage(Arc::new(cold_storage));
+    }
+    let sqlite_repository = Arc::new(sqlite_repository);
+
+    // 事件溯源模式：事件日志作为事实来源，任务表作为投影，两者共用连接池以便在同一事务中写入
+    let mut event_store: Option<Arc<dyn EventStore>> = None;
+    let mut event_sourced_repository = None;
+    let task_repository: Arc<dyn TaskRepository> = if config.database.enable_event_sourcing {
+        let sqlite_event_store = Arc::new(sqlite_event_store);
+        let store: Arc<dyn EventStore> = sqlite_event_store.clone();
+        let repository = Arc::new(EventSourcedTaskRepository::new(sqlite_event_store, sqlite_repository));
+        if config.database.rebuild_projection_on_startup {
+            let rebuilt = repository.rebuild_projection().await?;
+            logger.log_info(&format!("Rebuilt {} task projections from event log", rebuilt), None);
+        }
+        event_store = Some(store);
+        event_sourced_repository = Some(repository.clone());
+        repository
+    } else {
+        sqlite_repository
+    };
+
+    // 读缓存位于最外层，所有写入都经过它以保证失效
+    let task_cache = if config.cache.enable_cache && config.cache.cache_type == CacheType::Memory {
+        let cache = Arc::new(CachedTaskRepository::new(task_repository.clone(), &config.cache));
+        logger.log_info(&format!("Task read cache enabled, capacity: {}", config.cache.cache_size), None);
+        Some(cache)
+    } else {
+        None
+    };
+    let task_repository: Arc<dyn TaskRepository> = match &task_cache {
+        Some(cache) => cache.clone(),
+        None => task_repository,
+    };
+
+    // 热备模式：从主实例的事件流复制任务状态，提升前只提供只读接口
+    let standby = match (&event_sourced_repository, config.standby.enabled) {
+        (Some(repository), true) => {
+            let source = HttpReplicationSource::new(
+                config.standby.primary_url.clone(),
+                config.standby.token.clone(),
+                std::time::Duration::from_secs(config.standby.request_timeout),
+            )?;
+            let mut replicator = StandbyReplicator::new(repository.clone(), Arc::new(source), config.standby.primary_url.clone())
+                .with_batch_size(config.standby.batch_size)
+                .with_poll_interval(std::time::Duration::from_millis(config.standby.poll_interval_ms));
+            if let Some(cache) = &task_cache {
+                replicator = replicator.with_task_cache(cache.clone());
+            }
+            logger.log_info(&format!("Standby mode enabled, replicating from {}", config.standby.primary_url), None);
+            Some(Arc::new(replicator))
+        }
+        _ => None,
+    };
+
+    // 租约、锁和任务超时共用同一个时钟
+    let clock = crate::utils::clock::system_clock();
 
     // 创建锁管理器
     let lock_manager: Arc<SqliteLockManager> = Arc::new(
-        SqliteLockManager::with_pool(pool.clone()).await
+        SqliteLockManager::with_managed_pool(pool.clone()).with_clock(clock.clone())
     );
 
     // 创建并发控制器
@@ -145,6 +271,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         std::time::Duration::from_secs(config.task.worker_timeout),
         std::time::Duration::from_secs(config.task.heartbeat_interval),
     );
+    let concurrency_controller = if config.request_quotas.enabled {
+        concurrency_controller.with_request_quotas(RequestQuotas::new(&config.request_quotas))
+    } else {
+        concurrency_controller
+    };
 
     // 创建速率限制器
     let rate_limiter = RateLimiter::new(
@@ -152,48 +283,303 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         std::time::Duration::from_secs(60),
     );
 
+    let lock_manager_for_election = lock_manager.clone();
+
+    // 创建消息队列（队列分发模式）
+    let message_queue: Option<Arc<dyn MessageQueue>> = if config.queue.enabled {
+        let queue: Arc<dyn MessageQueue> = match config.queue.backend {
+            QueueBackend::Nats => Arc::new(
+                NatsQueue::connect(&config.queue.url, "task-orchestrator").await?
+            ),
+            QueueBackend::RabbitMq => {
+                let queue = RabbitMqQueue::new(
+                    config.queue.url.clone(),
+                    config.queue.rabbitmq_vhost.clone(),
+                    config.queue.rabbitmq_exchange.clone(),
+                    config.queue.username.clone().unwrap_or_else(|| "guest".to_string()),
+                    config.queue.password.clone().unwrap_or_else(|| "guest".to_string()),
+                    std::time::Duration::from_millis(config.queue.poll_interval_ms),
+                );
+                queue.declare_exchange().await?;
+                Arc::new(queue)
+            }
+        };
+        logger.log_info(&format!("Queue distribution enabled: {:?}", config.queue.backend), None);
+        Some(queue)
+    } else {
+        None
+    };
+
+    // 缓冲式历史写入器
+    let history_writer = config.task.enable_history_batching.then(|| {
+        Arc::new(HistoryWriter::start(task_repository.clone(), HistoryWriterSettings {
+            batch_size: config.task.history_batch_size,
+            flush_interval: std::time::Duration::from_millis(config.task.history_flush_interval_ms),
+            buffer_size: config.task.history_batch_size * 10,
+        }))
+    });
+
     // 创建任务服务
-    let task_service = Arc::new(TaskService::new(
+    let mut task_service = TaskService::new(
         task_repository,
         lock_manager,
         config.task.max_task_retries,
         config.task.default_task_timeout,
-    ));
+    )
+    .with_max_wait_timeout(config.task.max_wait_timeout)
+    .with_time_ordered_ids(config.task.time_ordered_ids)
+    .with_clock(clock.clone());
+    if let Some(queue) = &message_queue {
+        task_service = task_service.with_message_queue(queue.clone(), config.queue.subject_prefix.clone());
+    }
+    // 创建指标收集器
+    let metrics_collector = if config.monitoring.enable_prometheus && cfg!(feature = "metrics") {
+        Some(Arc::new(MetricsCollector::new(&config.monitoring.metric_labels)?))
+    } else {
+        if config.monitoring.enable_prometheus {
+            tracing::warn!("monitoring.enable_prometheus is set but the binary was built without the `metrics` feature");
+        }
+        None
+    };
+    let mut event_exporter = None;
+    if config.event_export.enabled {
+        let publisher = Arc::new(KafkaRestPublisher::new(
+            config.event_export.kafka_rest_url.clone(),
+            std::time::Duration::from_secs(config.event_export.request_timeout),
+        )?);
+        let exporter = TaskEventExporter::start(publisher, ExporterSettings {
+            topic: config.event_export.topic.clone(),
+            batch_size: config.event_export.batch_size,
+            flush_interval: std::time::Duration::from_millis(config.event_export.flush_interval_ms),
+            max_retries: config.event_export.max_retries,
+            retry_backoff: std::time::Duration::from_millis(config.event_export.retry_backoff_ms),
+            buffer_size: config.event_export.buffer_size,
+        }, metrics_collector.clone());
+        logger.log_info(&format!("Task event export enabled, topic: {}", config.event_export.topic), None);
+        let exporter = Arc::new(exporter);
+        task_service = task_service.with_event_exporter(exporter.clone());
+        event_exporter = Some(exporter);
+    }
+    if config.logging.redaction.enabled {
+        task_service = task_service.with_redactor(Arc::new(logger.redactor().clone()));
+    }
+    if config.security.work_directory_policy.enabled {
+        let policy = WorkDirectoryPolicy::from_config(&config.security.work_directory_policy)?;
+        task_service = task_service.with_path_policy(Arc::new(policy));
+    }
+    let maintenance = MaintenanceSchedule::from_config(&config.maintenance)?;
+    if !maintenance.is_empty() {
+        task_service = task_service.with_maintenance_schedule(Arc::new(maintenance));
+    }
+    let pipelines = PipelineRegistry::from_config(&config.pipelines)?;
+    if !pipelines.is_empty() {
+        task_service = task_service.with_pipelines(Arc::new(pipelines));
+    }
+    let approval = ApprovalPolicy::from_config(&config.approval)?;
+    task_service = task_service.with_approval_policy(Arc::new(approval));
+    let failure_classifier = FailureClassifier::from_config(&config.failure_classification)?;
+    task_service = task_service.with_failure_classifier(Arc::new(failure_classifier));
+    let retry_policy = RetryPolicy::from_config(&config.retry_policy)?;
+    if !retry_policy.is_empty() {
+        task_service = task_service.with_retry_policy(Arc::new(retry_policy));
+    }
+    let sla = SlaTracker::from_config(&config.sla)?;
+    if sla.is_enabled() {
+        task_service = task_service.with_sla_tracker(Arc::new(sla));
+        if let Some(service) = &config.sla.alert_service {
+            task_service = task_service.with_sla_alert_target(service.clone(), config.sla.alert_path.clone());
+        }
+    }
+    // 创建产物存储（对象存储）
+    let artifact_store = if config.object_storage.enabled {
+        let object_store = S3ObjectStore::new(S3Settings {
+            endpoint: config.object_storage.endpoint.clone(),
+            bucket: config.object_storage.bucket.clone(),
+            region: config.object_storage.region.clone(),
+            access_key_id: config.object_storage.access_key_id.clone(),
+            secret_access_key: config.object_storage.secret_access_key.clone(),
+            path_style: config.object_storage.path_style,
+            multipart_threshold: config.object_storage.multipart_threshold,
+            part_size: config.object_storage.part_size,
+        })?;
+        Some(Arc::new(ArtifactStore::new(
+            Arc::new(object_store),
+            config.object_storage.artifact_prefix.clone(),
+            std::time::Duration::from_secs(config.object_storage.presign_expiry),
+        ).with_max_size(config.object_storage.max_artifact_size)))
+    } else {
+        None
+    };
+    if let Some(artifact_store) = &artifact_store {
+        task_service = task_service.with_artifact_store(artifact_store.clone());
+    }
+    if config.cold_storage.enabled {
+        task_service = task_service.with_cold_storage(config.cold_storage.after_days, config.cold_storage.batch_size);
+    }
+    if config.security.secret_scanning.enabled {
+        let mut scanner = SecretScanner::from_config(&config.security.secret_scanning)?;
+        if let Some(metrics) = &metrics_collector {
+            scanner = scanner.with_metrics(metrics.clone());
+        }
+        task_service = task_service.with_secret_scanner(Arc::new(scanner));
+    }
+    if let Some(cache) = &task_cache {
+        task_service = task_service.with_task_cache(cache.clone());
+    }
+    if let Some(writer) = &history_writer {
+        task_service = task_service.with_history_writer(writer.clone());
+    }
+    task_service = task_service.with_database_pool(pool.clone());
+    if config.external_services.enable_external_services {
+        let clients = ServiceClients::from_config(&config.external_services)?;
+        logger.log_info(&format!("External services enabled: {}", config.external_services.services.len()), None);
+        task_service = task_service.with_service_clients(Arc::new(clients));
+    }
+    if let Some(metrics) = &metrics_collector {
+        task_service = task_service.with_metrics(metrics.clone());
+    }
+    crate::utils::crash::install_panic_hook(
+        config.logging.crash_report.clone(),
+        log_manager.recent_logs(),
+        metrics_collector.clone(),
+    );
+    let task_service = Arc::new(task_service);
+    let metadata_schemas = task_service.load_metadata_schemas().await?;
+    if metadata_schemas > 0 {
+        logger.log_info(&format!("Loaded {} metadata schemas", metadata_schemas), None);
+    }
+
+    // 创建领导者选举器（集群模式）
+    let leader_elector = if config.cluster.enable_leader_election {
+        let node_id = config.cluster.resolve_node_id();
+        logger.log_info(&format!("Leader election enabled, node id: {}", node_id), None);
+        Some(Arc::new(LeaderElector::new(
+            lock_manager_for_election,
+            node_id,
+            config.cluster.leader_lease_ttl,
+            config.cluster.leader_renew_interval,
+        ).with_clock(clock)))
+    } else {
+        None
+    };
 
     // 创建任务调度器
-    let task_scheduler = TaskScheduler::new(
+    let mut task_scheduler = TaskScheduler::new(
         task_service.clone(),
         config.task.task_cleanup_interval,
         config.task.heartbeat_interval,
     );
 
     // 创建任务监控器
-    let task_monitor = TaskMonitor::new(
+    let mut task_monitor = TaskMonitor::new(
         task_service.clone(),
         config.monitoring.metrics_collection_interval,
     );
 
-    // 创建指标收集器
-    let _metrics_collector = MetricsCollector::new()?;
+    if let Some(elector) = &leader_elector {
+        task_scheduler = task_scheduler.with_leader_elector(elector.clone());
+        task_monitor = task_monitor.with_leader_elector(elector.clone());
+    }
 
     // 创建健康检查器
     let _health_checker = HealthChecker::new();
 
+    let request_recorder = if config.request_recording.enabled {
+        logger.log_info(&format!("Request recording enabled, keeping last {} requests", config.request_recording.capacity), None);
+        Some(Arc::new(RequestRecorder::from_config(&config.request_recording, logger.redactor().clone())?))
+    } else {
+        None
+    };
+
+    // 创建内存自监控，内存压力升高时清空任务缓存
+    let memory_watchdog = if config.monitoring.memory_watchdog.enabled {
+        let watchdog = Arc::new(MemoryWatchdog::new(config.monitoring.memory_watchdog.limits()));
+        let metrics = metrics_collector.clone();
+        watchdog.clone().start(
+            move || {
+                if let Some(cache) = &task_cache {
+                    cache.evict_all();
+                }
+                std::future::ready(())
+            },
+            move |watchdog, evicted| {
+                if let Some(metrics) = &metrics {
+                    metrics.record_memory_sample(watchdog.rss_bytes(), watchdog.pressure(), evicted);
+                }
+            },
+        );
+        Some(watchdog)
+    } else {
+        None
+    };
+
     // 创建API状态
     let api_state = ApiState {
         task_service: task_service.clone(),
         logger: logger.clone(),
+        leader_elector: leader_elector.clone(),
+        artifact_store,
+        request_recorder,
+        request_quotas: concurrency_controller.request_quotas(),
+        request_decompressor: config
+            .request_decompression
+            .enabled
+            .then(|| request_decompressor(&config.request_decompression)),
+        // 设置了独立指标端口时，Prometheus端点只在指标监听器上提供
+        prometheus_endpoint: (metrics_collector.is_some() && config.monitoring.metrics_port.is_none())
+            .then(|| config.monitoring.prometheus_endpoint.clone()),
+        metrics: metrics_collector,
+        memory_watchdog,
+        event_store,
+        standby: standby.clone(),
+        replication_token: config.standby.token.clone(),
     };
 
-    // 启动后台任务
-    task_scheduler.start().await?;
-    task_monitor.start().await?;
+    // 启动后台任务；备机在提升为主实例之后才启动会修改任务状态的后台任务
+    let ack_subject = config.queue.ack_subject.clone();
+    let elector_for_start = leader_elector.clone();
+    let service_for_promotion = task_service.clone();
+    let primary_tasks = async move {
+        if let Some(elector) = &elector_for_start {
+            elector.start().await?;
+        }
+        task_scheduler.start().await?;
+        task_monitor.start().await?;
+        if let Some(queue) = &message_queue {
+            let consumer = Arc::new(QueueAckConsumer::new(task_service.clone(), queue.clone(), ack_subject));
+            consumer.start().await?;
+        }
+        Ok::<(), crate::errors::AppError>(())
+    };
+    match &standby {
+        Some(replicator) => {
+            replicator.start().await?;
+            let replicator = replicator.clone();
+            let standby_logger = logger.clone();
+            tokio::spawn(async move {
+                replicator.wait_for_promotion().await;
+                // 复制期间写入的元数据模式只在投影中，提升后重新载入内存
+                if let Err(e) = service_for_promotion.load_metadata_schemas().await {
+                    standby_logger.log_error("standby_promotion", &e.to_string(), None, None);
+                }
+                match primary_tasks.await {
+                    Ok(()) => standby_logger.log_info("Promoted to primary, background tasks started", None),
+                    Err(e) => standby_logger.log_error("standby_promotion", &e.to_string(), None, None),
+                }
+            });
+        }
+        None => primary_tasks.await?,
+    }
     concurrency_controller.start_cleanup_task().await?;
     rate_limiter.start_cleanup_task().await?;
 
     logger.log_info("Background tasks started", None);
 
     // 创建HTTP服务
+    let metrics_app = config
+        .monitoring
+        .metrics_port
+        .map(|port| (port, create_metrics_routes(api_state.clone(), &config.monitoring)));
     let app = create_routes(api_state);
 
     // 添加中间件 - 简化实现
@@ -207,8 +593,77 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     logger.log_info(&format!("Starting server on {}", addr), None);
 
-    // 启动服务器
+    // 套接字激活时使用systemd传入的监听套接字，否则自行绑定（可开启SO_REUSEPORT与旧进程并存）
+    #[cfg(unix)]
+    let mut activated_sockets = ActivatedSockets::from_env();
+    #[cfg(unix)]
+    let listener = match activated_sockets.take_tcp("api")? {
+        Some(listener) => {
+            logger.log_info(&format!("Using activated socket {}", listener.local_addr()?), None);
+            listener
+        }
+        None => bind_tcp(addr, config.server.reuse_port)?,
+    };
+    #[cfg(not(unix))]
     let listener = tokio::net::TcpListener::bind(addr).await?;
+
+    // 独立指标监听器，便于与API端口分别配置防火墙
+    let metrics_server = match metrics_app {
+        Some((port, metrics_app)) => {
+            let metrics_addr = SocketAddr::new(addr.ip(), port);
+            #[cfg(unix)]
+            let metrics_listener = match activated_sockets.take_tcp("metrics")? {
+                Some(listener) => listener,
+                None => bind_tcp(metrics_addr, config.server.reuse_port)?,
+            };
+            #[cfg(not(unix))]
+            let metrics_listener = tokio::net::TcpListener::bind(metrics_addr).await?;
+            logger.log_info(&format!("Starting metrics listener on {}", metrics_addr), None);
+            let metrics_logger = logger.clone();
+            Some(tokio::spawn(async move {
+                if let Err(e) = axum::serve(metrics_listener, metrics_app).await {
+                    metrics_logger.log_error("metrics_listener", &e.to_string(), None, None);
+                }
+            }))
+        }
+        None => None,
+    };
+
+    // Unix域套接字监听器，供同一主机或Pod内的边车访问API
+    #[cfg(unix)]
+    let unix_socket_server = match &config.server.unix_socket {
+        Some(path) => {
+            let unix_listener = bind_unix_socket(path, config.server.socket_mode()?)?;
+            logger.log_info(&format!("Starting unix socket listener on {}", path), None);
+            let unix_app = app.clone();
+            let unix_logger = logger.clone();
+            Some(tokio::spawn(async move {
+                if let Err(e) = serve_unix_socket(unix_listener, unix_app).await {
+                    unix_logger.log_error("unix_socket_listener", &e.to_string(), None, None);
+                }
+            }))
+        }
+        None => None,
+    };
+
+    // 监听器和后台任务均已启动，通知 systemd 服务就绪
+    let systemd_notifier = if config.systemd.enabled {
+        SystemdNotifier::from_env()
+    } else {
+        None
+    };
+    let mut watchdog = None;
+    if let Some(notifier) = &systemd_notifier {
+        if let Err(e) = notifier.ready() {
+            logger.log_info(&format!("Failed to notify systemd readiness: {}", e), None);
+        }
+        watchdog = notifier.start_watchdog(Some(pool.clone()));
+        if let Some(interval) = notifier.watchdog_interval() {
+            logger.log_info(&format!("Systemd watchdog keepalive every {:?}", interval), None);
+        }
+    }
+    let notifier_for_shutdown = systemd_notifier.clone();
+    let (drain_tx, drain_rx) = tokio::sync::oneshot::channel::<()>();
     
     // 优雅关闭处理
     let shutdown_signal = async move {
@@ -234,12 +689,57 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         }
 
         logger_for_shutdown.log_info("Shutdown signal received", None);
+        if let Some(notifier) = &notifier_for_shutdown {
+            let _ = notifier.stopping();
+        }
+        let _ = drain_tx.send(());
     };
 
-    // 启动服务器
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal)
-        .await?;
+    // 启动服务器；收到关闭信号后停止接受新连接，最多等待 shutdown_drain_timeout 秒处理完进行中的请求
+    let drain_timeout = config.server.shutdown_drain_timeout;
+    let drain_deadline = async move {
+        match drain_rx.await {
+            Ok(()) if drain_timeout > 0 => tokio::time::sleep(std::time::Duration::from_secs(drain_timeout)).await,
+            _ => std::future::pending::<()>().await,
+        }
+    };
+    tokio::select! {
+        result = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).with_graceful_shutdown(shutdown_signal).into_future() => result?,
+        _ = drain_deadline => {
+            logger.log_info(&format!("In-flight requests still running after {}s drain timeout, exiting", drain_timeout), None);
+        }
+    }
+
+    if let Some(metrics_server) = metrics_server {
+        metrics_server.abort();
+    }
+    if let Some(watchdog) = watchdog {
+        watchdog.abort();
+    }
+    #[cfg(unix)]
+    if let Some(unix_socket_server) = unix_socket_server {
+        unix_socket_server.abort();
+        if let Some(path) = &config.server.unix_socket {
+            remove_unix_socket(path);
+        }
+    }
+
+    // 写入缓冲区中剩余的任务历史
+    if let Some(writer) = &history_writer {
+        writer.shutdown().await;
+    }
+
+    // 发布导出缓冲区中剩余的任务事件
+    if let Some(exporter) = &event_exporter {
+        exporter.shutdown().await;
+    }
+
+    // 主动释放领导权，便于其他节点快速接管
+    if let Some(elector) = &leader_elector {
+        if let Err(e) = elector.resign().await {
+            logger.log_info(&format!("Failed to resign leadership: {}", e), None);
+        }
+    }
 
     logger.log_info("Server shutdown completed", None);
 
@@ -0,0 +1,726 @@
+//! # 任务过滤表达式
+//!
+//! 列表查询的 `filter` 参数，支持用布尔逻辑组合条件，例如：
+//!
+//! ```text
+//! status:failed AND (tag:urgent OR priority>=high) AND created>2025-01-01
+//! ```
+//!
+//! 语法（关键字不区分大小写，优先级 `NOT` > `AND` > `OR`，相邻条件之间省略 `AND` 时按 `AND` 处理）：
+//!
+//! ```text
+//! expr      := or
+//! or        := and ("OR" and)*
+//! and       := unary (["AND"] unary)*
+//! unary     := "NOT" unary | "(" expr ")" | condition
+//! condition := field op value
+//! op        := ":" | "=" | "!=" | ">" | ">=" | "<" | "<="
+//! value     := word | "quoted string"
+//! ```
+//!
+//! 字段与支持的运算符：
+//!
+//! | 字段 | 值 | 运算符 |
+//! |------|----|--------|
+//! | `status` | waiting / working / completed / failed / cancelled | `:` `=` `!=` |
+//! | `priority` | low / medium / high | 全部 |
+//! | `tag` | 标签 | `:` `=`（包含该标签）`!=`（不包含） |
+//! | `dir` / `work_directory` | 绝对路径 | `:` `=`（该目录及其子目录）`!=` |
+//! | `worker` / `worker_id` | 工作节点ID | `:` `=` `!=` |
+//! | `cancel_reason` | requested / expired / rejected | `:` `=` `!=` |
+//! | `failure` / `failure_category` | timeout / oom / tool_error / validation / upstream / unknown | `:` `=` `!=` |
+//! | `created` / `started` / `completed` / `expires` | RFC3339时间或 `YYYY-MM-DD`（UTC） | 全部 |
+//! | `retries` / `retry_count` | 非负整数 | 全部 |
+//!
+//! 时间字段的值为日期时，`:` / `=` 匹配当天全天，`!=` 匹配当天以外；比较运算符以当天零点为界。
+//! 时间为空（如尚未开始的任务的 `started`）时只有 `!=` 匹配。
+//!
+//! 表达式解析为 [`FilterExpr`] 后翻译为SQL条件（[`FilterExpr::to_sql`]）。测试中另有语义一致的内存求值
+//! （`FilterExpr::matches`），供内存仓库和SQL翻译一致性测试使用。解析失败时 [`FilterParseError`] 给出出错记号及其位置（从1开始的字符位置）。
+
+use chrono::{DateTime, NaiveDate, Utc};
+use thiserror::Error;
+
+use crate::domain::{CancelReason, FailureCategory, TaskPriority, TaskStatus};
+
+/// 表达式最大长度（字符）
+pub const MAX_FILTER_LENGTH: usize = 2000;
+
+/// 最大嵌套深度
+const MAX_DEPTH: usize = 32;
+
+/// 过滤表达式解析错误
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{message} at position {position} near '{token}'")]
+pub struct FilterParseError {
+    /// 出错记号的起始位置（从1开始的字符位置）
+    pub position: usize,
+    /// 出错的记号，表达式意外结束时为 `end of input`
+    pub token: String,
+    pub message: String,
+}
+
+/// 过滤表达式
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Condition(Condition),
+}
+
+/// 单个条件
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub field: FilterField,
+    pub op: FilterOp,
+    pub value: FilterValue,
+}
+
+/// 可过滤的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterField {
+    Status,
+    Priority,
+    Tag,
+    WorkDirectory,
+    Worker,
+    CancelReason,
+    FailureCategory,
+    Created,
+    Started,
+    Completed,
+    Expires,
+    Retries,
+}
+
+/// 比较运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// 按字段类型解析后的值
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterValue {
+    Status(TaskStatus),
+    Priority(TaskPriority),
+    CancelReason(CancelReason),
+    FailureCategory(FailureCategory),
+    Text(String),
+    Time(DateTime<Utc>),
+    Number(i64),
+}
+
+impl FilterField {
+    fn parse(name: &str) -> Option<Self> {
+        let field = match name.to_lowercase().as_str() {
+            "status" => Self::Status,
+            "priority" => Self::Priority,
+            "tag" => Self::Tag,
+            "dir" | "work_directory" => Self::WorkDirectory,
+            "worker" | "worker_id" => Self::Worker,
+            "cancel_reason" => Self::CancelReason,
+            "failure" | "failure_category" => Self::FailureCategory,
+            "created" => Self::Created,
+            "started" => Self::Started,
+            "completed" => Self::Completed,
+            "expires" => Self::Expires,
+            "retries" | "retry_count" => Self::Retries,
+            _ => return None,
+        };
+        Some(field)
+    }
+
+    /// 是否支持大小比较
+    fn is_ordered(self) -> bool {
+        matches!(self, Self::Priority | Self::Created | Self::Started | Self::Completed | Self::Expires | Self::Retries)
+    }
+
+    fn time_column(self) -> Option<&'static str> {
+        match self {
+            Self::Created => Some("created_at"),
+            Self::Started => Some("started_at"),
+            Self::Completed => Some("completed_at"),
+            Self::Expires => Some("expires_at"),
+            _ => None,
+        }
+    }
+}
+
+impl FilterOp {
+    fn sql(self) -> &'static str {
+        match self {
+            Self::Eq => "=",
+            Self::Ne => "<>",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+        }
+    }
+
+    #[cfg(test)]
+    fn compare<T: PartialOrd>(self, left: &T, right: &T) -> bool {
+        match self {
+            Self::Eq => left == right,
+            Self::Ne => left != right,
+            Self::Gt => left > right,
+            Self::Ge => left >= right,
+            Self::Lt => left < right,
+            Self::Le => left <= right,
+        }
+    }
+}
+
+impl FilterExpr {
+    /// 解析过滤表达式
+    pub fn parse(input: &str) -> Result<Self, FilterParseError> {
+        let length = input.chars().count();
+        if length > MAX_FILTER_LENGTH {
+            return Err(FilterParseError {
+                position: MAX_FILTER_LENGTH + 1,
+                token: input.chars().skip(MAX_FILTER_LENGTH).take(10).collect(),
+                message: format!("Filter is too long (max {} characters)", MAX_FILTER_LENGTH),
+            });
+        }
+
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, index: 0, end: length + 1, depth: 0 };
+        if parser.tokens.is_empty() {
+            return Err(parser.error_here("Empty filter expression"));
+        }
+        let expr = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) if token.kind == TokenKind::RParen => Err(parser.error_here("Unbalanced ')'")),
+            Some(_) => Err(parser.error_here("Expected AND, OR or end of filter")),
+        }
+    }
+
+    /// 翻译为SQL条件，参数按出现顺序追加到 `params`
+    pub fn to_sql(&self, params: &mut Vec<String>) -> String {
+        match self {
+            FilterExpr::And(left, right) => format!("({} AND {})", left.to_sql(params), right.to_sql(params)),
+            FilterExpr::Or(left, right) => format!("({} OR {})", left.to_sql(params), right.to_sql(params)),
+            FilterExpr::Not(inner) => format!("(NOT {})", inner.to_sql(params)),
+            FilterExpr::Condition(condition) => condition.to_sql(params),
+        }
+    }
+
+    /// 任务是否匹配，与 [`FilterExpr::to_sql`] 的语义一致，供测试中的内存仓库和SQL翻译一致性测试使用
+    #[cfg(test)]
+    pub fn matches(&self, task: &crate::domain::Task) -> bool {
+        match self {
+            FilterExpr::And(left, right) => left.matches(task) && right.matches(task),
+            FilterExpr::Or(left, right) => left.matches(task) || right.matches(task),
+            FilterExpr::Not(inner) => !inner.matches(task),
+            FilterExpr::Condition(condition) => condition.matches(task),
+        }
+    }
+}
+
+impl Condition {
+    /// 生成的SQL条件总是返回0或1，不返回NULL，保证 `NOT` 的语义与 [`Condition::matches`] 一致
+    fn to_sql(&self, params: &mut Vec<String>) -> String {
+        let negate = self.op == FilterOp::Ne;
+        let not = if negate { "NOT " } else { "" };
+        match (&self.field, &self.value) {
+            (FilterField::Status, FilterValue::Status(status)) => {
+                params.push(status.to_string());
+                format!("status {} ?", self.op.sql())
+            }
+            (FilterField::Priority, FilterValue::Priority(priority)) => {
+                params.push(priority.weight().to_string());
+                format!(
+                    "(CASE priority WHEN 'low' THEN 1 WHEN 'medium' THEN 2 WHEN 'high' THEN 3 ELSE 0 END) {} CAST(? AS INTEGER)",
+                    self.op.sql()
+                )
+            }
+            (FilterField::Tag, FilterValue::Text(tag)) => {
+                params.push(tag.clone());
+                format!("{}EXISTS (SELECT 1 FROM json_each(COALESCE(tasks.tags, '[]')) WHERE value = ?)", not)
+            }
+            (FilterField::WorkDirectory, FilterValue::Text(dir)) => {
+                let (dir, prefix) = directory_prefix(dir);
+                params.push(dir);
+                params.push(prefix);
+                format!("{}(work_directory = ? OR instr(work_directory, ?) = 1)", not)
+            }
+            (FilterField::Worker, FilterValue::Text(worker)) => {
+                params.push(worker.clone());
+                format!("worker_id IS {}?", not)
+            }
+            (FilterField::CancelReason, FilterValue::CancelReason(reason)) => {
+                params.push(reason.to_string());
+                format!("cancel_reason IS {}?", not)
+            }
+            (FilterField::FailureCategory, FilterValue::FailureCategory(category)) => {
+                params.push(category.to_string());
+                format!("failure_category IS {}?", not)
+            }
+            (field, FilterValue::Time(time)) => {
+                let column = field.time_column().unwrap_or("created_at");
+                params.push(time.to_rfc3339());
+                // 时间为空时只有 != 匹配
+                format!(
+                    "COALESCE(julianday({}) {} julianday(?), {})",
+                    column,
+                    self.op.sql(),
+                    if negate { 1 } else { 0 }
+                )
+            }
+            (FilterField::Retries, FilterValue::Number(n)) => {
+                params.push(n.to_string());
+                format!("retry_count {} CAST(? AS INTEGER)", self.op.sql())
+            }
+            // 解析时已按字段确定值类型，不会出现其他组合
+            _ => "0".to_string(),
+        }
+    }
+
+    #[cfg(test)]
+    fn matches(&self, task: &crate::domain::Task) -> bool {
+        let negate = self.op == FilterOp::Ne;
+        match (&self.field, &self.value) {
+            (FilterField::Status, FilterValue::Status(status)) => self.op.compare(&task.status.to_string(), &status.to_string()),
+            (FilterField::Priority, FilterValue::Priority(priority)) => {
+                self.op.compare(&task.priority.weight(), &priority.weight())
+            }
+            (FilterField::Tag, FilterValue::Text(tag)) => task.tags.iter().any(|t| t.as_str() == tag) != negate,
+            (FilterField::WorkDirectory, FilterValue::Text(dir)) => {
+                let (dir, _) = directory_prefix(dir);
+                std::path::Path::new(task.work_directory.as_str()).starts_with(&dir) != negate
+            }
+            (FilterField::Worker, FilterValue::Text(worker)) => {
+                (task.worker_id.as_ref().map(|w| w.as_str()) == Some(worker.as_str())) != negate
+            }
+            (FilterField::CancelReason, FilterValue::CancelReason(reason)) => {
+                (task.cancel_reason == Some(*reason)) != negate
+            }
+            (FilterField::FailureCategory, FilterValue::FailureCategory(category)) => {
+                (task.failure_category == Some(*category)) != negate
+            }
+            (field, FilterValue::Time(time)) => {
+                let value = match field {
+                    FilterField::Created => Some(task.created_at),
+                    FilterField::Started => task.started_at,
+                    FilterField::Completed => task.completed_at,
+                    FilterField::Expires => task.expires_at,
+                    _ => None,
+                };
+                match value {
+                    Some(value) => self.op.compare(&value, time),
+                    None => negate,
+                }
+            }
+            (FilterField::Retries, FilterValue::Number(n)) => self.op.compare(&(task.retry_count as i64), n),
+            _ => false,
+        }
+    }
+}
+
+/// 去掉目录末尾的 `/`，返回目录本身和匹配子目录用的前缀
+fn directory_prefix(dir: &str) -> (String, String) {
+    let trimmed = dir.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return ("/".to_string(), "/".to_string());
+    }
+    (trimmed.to_string(), format!("{}/", trimmed))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    LParen,
+    RParen,
+    Op(FilterOp),
+    Word(String),
+    Quoted(String),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// 原始文本
+    text: String,
+    /// 从1开始的字符位置
+    position: usize,
+}
+
+impl Token {
+    fn keyword(&self) -> Option<&'static str> {
+        match &self.kind {
+            TokenKind::Word(word) if word.eq_ignore_ascii_case("and") => Some("AND"),
+            TokenKind::Word(word) if word.eq_ignore_ascii_case("or") => Some("OR"),
+            TokenKind::Word(word) if word.eq_ignore_ascii_case("not") => Some("NOT"),
+            _ => None,
+        }
+    }
+}
+
+fn is_operator_char(c: char) -> bool {
+    matches!(c, ':' | '=' | '!' | '<' | '>')
+}
+
+/// 词法分析；运算符之后的值按整段读取，值中可以包含 `:` 等字符（如RFC3339时间）
+fn tokenize(input: &str) -> Result<Vec<Token>, FilterParseError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let after_op = matches!(tokens.last(), Some(Token { kind: TokenKind::Op(_), .. }));
+
+        if c == '"' {
+            let mut value = String::new();
+            let mut j = i + 1;
+            loop {
+                match chars.get(j) {
+                    None => {
+                        return Err(FilterParseError {
+                            position,
+                            token: chars[i..].iter().collect(),
+                            message: "Unterminated string".to_string(),
+                        });
+                    }
+                    Some('"') => break,
+                    Some('\\') if matches!(chars.get(j + 1), Some('"') | Some('\\')) => {
+                        value.push(chars[j + 1]);
+                        j += 2;
+                    }
+                    Some(&other) => {
+                        value.push(other);
+                        j += 1;
+                    }
+                }
+            }
+            tokens.push(Token { kind: TokenKind::Quoted(value), text: chars[i..=j].iter().collect(), position });
+            i = j + 1;
+        } else if c == '(' || c == ')' {
+            let kind = if c == '(' { TokenKind::LParen } else { TokenKind::RParen };
+            tokens.push(Token { kind, text: c.to_string(), position });
+            i += 1;
+        } else if is_operator_char(c) && !after_op {
+            let next = chars.get(i + 1).copied();
+            let (op, len) = match (c, next) {
+                (':', _) => (FilterOp::Eq, 1),
+                ('=', _) => (FilterOp::Eq, 1),
+                ('!', Some('=')) => (FilterOp::Ne, 2),
+                ('>', Some('=')) => (FilterOp::Ge, 2),
+                ('>', _) => (FilterOp::Gt, 1),
+                ('<', Some('=')) => (FilterOp::Le, 2),
+                ('<', _) => (FilterOp::Lt, 1),
+                _ => {
+                    return Err(FilterParseError {
+                        position,
+                        token: c.to_string(),
+                        message: "Unknown operator".to_string(),
+                    });
+                }
+            };
+            tokens.push(Token { kind: TokenKind::Op(op), text: chars[i..i + len].iter().collect(), position });
+            i += len;
+        } else {
+            // 字段名在运算符处结束，值读到空白或括号为止
+            let mut j = i;
+            while j < chars.len()
+                && !chars[j].is_whitespace()
+                && chars[j] != '('
+                && chars[j] != ')'
+                && chars[j] != '"'
+                && (after_op || !is_operator_char(chars[j]))
+            {
+                j += 1;
+            }
+            let text: String = chars[i..j].iter().collect();
+            tokens.push(Token { kind: TokenKind::Word(text.clone()), text, position });
+            i = j;
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    /// 表达式末尾的位置
+    end: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    fn error_at(token: &Token, message: impl Into<String>) -> FilterParseError {
+        FilterParseError { position: token.position, token: token.text.clone(), message: message.into() }
+    }
+
+    /// 在当前记号（或表达式末尾）处报错
+    fn error_here(&self, message: impl Into<String>) -> FilterParseError {
+        match self.peek() {
+            Some(token) => Self::error_at(token, message),
+            None => FilterParseError { position: self.end, token: "end of input".to_string(), message: message.into() },
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpr, FilterParseError> {
+        let mut expr = self.parse_and()?;
+        while self.peek().and_then(Token::keyword) == Some("OR") {
+            self.next();
+            let right = self.parse_and()?;
+            expr = FilterExpr::Or(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, FilterParseError> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            match self.peek() {
+                Some(token) if token.keyword() == Some("AND") => {
+                    self.next();
+                }
+                // 相邻条件省略 AND
+                Some(token) if token.keyword() != Some("OR") && token.kind != TokenKind::RParen => {}
+                _ => break,
+            }
+            let right = self.parse_unary()?;
+            expr = FilterExpr::And(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<FilterExpr, FilterParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error_here(format!("Filter is nested too deeply (max {} levels)", MAX_DEPTH)));
+        }
+        let result = self.parse_primary();
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_primary(&mut self) -> Result<FilterExpr, FilterParseError> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error_here("Expected a condition"));
+        };
+        if token.keyword() == Some("NOT") {
+            self.next();
+            return Ok(FilterExpr::Not(Box::new(self.parse_unary()?)));
+        }
+        match &token.kind {
+            TokenKind::LParen => {
+                self.next();
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token { kind: TokenKind::RParen, .. }) => Ok(expr),
+                    _ => {
+                        self.index -= 1;
+                        Err(self.error_here(format!("Expected ')' to close '(' at position {}", token.position)))
+                    }
+                }
+            }
+            TokenKind::Word(_) if token.keyword().is_some() => {
+                Err(Self::error_at(&token, "Expected a condition"))
+            }
+            TokenKind::Word(_) => self.parse_condition(),
+            _ => Err(Self::error_at(&token, "Expected a condition")),
+        }
+    }
+
+    fn parse_condition(&mut self) -> Result<FilterExpr, FilterParseError> {
+        let field_token = self.next().expect("caller checked the token");
+        let field = FilterField::parse(&field_token.text).ok_or_else(|| {
+            Self::error_at(
+                &field_token,
+                "Unknown field (expected status, priority, tag, dir, worker, cancel_reason, failure, created, started, completed, expires or retries)",
+            )
+        })?;
+
+        let op = match self.peek() {
+            Some(Token { kind: TokenKind::Op(op), .. }) => *op,
+            _ => return Err(self.error_here(format!("Expected an operator after '{}'", field_token.text))),
+        };
+        let op_token = self.next().expect("peeked above");
+        if !field.is_ordered() && !matches!(op, FilterOp::Eq | FilterOp::Ne) {
+            return Err(Self::error_at(
+                &op_token,
+                format!("Operator '{}' is not supported for field '{}'", op_token.text, field_token.text),
+            ));
+        }
+
+        let value_token = match self.peek() {
+            Some(token @ Token { kind: TokenKind::Word(_) | TokenKind::Quoted(_), .. }) => token.clone(),
+            _ => return Err(self.error_here(format!("Expected a value for '{}'", field_token.text))),
+        };
+        self.next();
+        let raw = match &value_token.kind {
+            TokenKind::Word(word) | TokenKind::Quoted(word) => word.as_str(),
+            _ => unreachable!(),
+        };
+        let invalid = |expected: &str| Self::error_at(&value_token, format!("Invalid value for '{}': expected {}", field_token.text, expected));
+
+        let condition = |value| FilterExpr::Condition(Condition { field, op, value });
+        let expr = match field {
+            FilterField::Status => condition(FilterValue::Status(
+                TaskStatus::from_str(raw).map_err(|_| invalid("waiting, working, completed, failed or cancelled"))?,
+            )),
+            FilterField::Priority => condition(FilterValue::Priority(
+                TaskPriority::from_str(raw).map_err(|_| invalid("low, medium or high"))?,
+            )),
+            FilterField::CancelReason => condition(FilterValue::CancelReason(
+                raw.to_lowercase().parse().map_err(|_| invalid("requested, expired or rejected"))?,
+            )),
+            FilterField::FailureCategory => condition(FilterValue::FailureCategory(
+                raw.to_lowercase()
+                    .parse()
+                    .map_err(|_| invalid("timeout, oom, tool_error, validation, upstream or unknown"))?,
+            )),
+            FilterField::Tag | FilterField::Worker => {
+                if raw.is_empty() {
+                    return Err(invalid("a non-empty string"));
+                }
+                condition(FilterValue::Text(raw.to_string()))
+            }
+            FilterField::WorkDirectory => {
+                if !raw.starts_with('/') {
+                    return Err(invalid("an absolute path"));
+                }
+                condition(FilterValue::Text(raw.to_string()))
+            }
+            FilterField::Retries => condition(FilterValue::Number(
+                raw.parse::<u32>().map_err(|_| invalid("a non-negative integer"))? as i64,
+            )),
+            FilterField::Created | FilterField::Started | FilterField::Completed | FilterField::Expires => {
+                if let Ok(time) = DateTime::parse_from_rfc3339(raw) {
+                    condition(FilterValue::Time(time.with_timezone(&Utc)))
+                } else if let Ok(date) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+                    date_condition(field, op, date)
+                } else {
+                    return Err(invalid("an RFC3339 time or YYYY-MM-DD date"));
+                }
+            }
+        };
+        Ok(expr)
+    }
+}
+
+/// 日期值：`=` 匹配当天全天，`!=` 匹配当天以外，其他运算符以当天零点为界
+fn date_condition(field: FilterField, op: FilterOp, date: NaiveDate) -> FilterExpr {
+    let start = date.and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc();
+    let end = start + chrono::Duration::days(1);
+    let condition = |op, time| FilterExpr::Condition(Condition { field, op, value: FilterValue::Time(time) });
+    match op {
+        FilterOp::Eq => FilterExpr::And(Box::new(condition(FilterOp::Ge, start)), Box::new(condition(FilterOp::Lt, end))),
+        FilterOp::Ne => FilterExpr::Not(Box::new(FilterExpr::And(
+            Box::new(condition(FilterOp::Ge, start)),
+            Box::new(condition(FilterOp::Lt, end)),
+        ))),
+        op => condition(op, start),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Prompt, Task, TaskTag, WorkDirectory};
+
+    #[test]
+    fn test_parse_and_match() {
+        let expr = FilterExpr::parse(
+            "status:failed AND (tag:urgent OR priority>=high) created>2025-01-01 NOT dir:\"/srv/old\"",
+        )
+        .unwrap();
+
+        let mut task = Task::new(
+            WorkDirectory::new("/srv/app".to_string()).unwrap(),
+            Prompt::new("Test".to_string()).unwrap(),
+            TaskPriority::Low,
+            vec![TaskTag::new("urgent".to_string()).unwrap()],
+        );
+        task.status = TaskStatus::Failed;
+        assert!(expr.matches(&task));
+
+        task.tags.clear();
+        assert!(!expr.matches(&task));
+        task.priority = TaskPriority::High;
+        assert!(expr.matches(&task));
+        task.work_directory = WorkDirectory::new("/srv/old/api".to_string()).unwrap();
+        assert!(!expr.matches(&task));
+
+        // OR 优先级低于 AND；日期相等匹配当天全天
+        let expr = FilterExpr::parse("retries>=1 or created:2025-03-01 and status=waiting").unwrap();
+        assert!(matches!(expr, FilterExpr::Or(_, _)));
+        task.created_at = "2025-03-01T23:59:59Z".parse().unwrap();
+        task.status = TaskStatus::Waiting;
+        assert!(expr.matches(&task));
+        task.created_at = "2025-03-02T00:00:00Z".parse().unwrap();
+        assert!(!expr.matches(&task));
+
+        // 时间为空时只有 != 匹配
+        assert!(!FilterExpr::parse("started<2030-01-01T00:00:00Z").unwrap().matches(&task));
+        assert!(FilterExpr::parse("started!=2030-01-01").unwrap().matches(&task));
+
+        task.failure_category = Some(FailureCategory::Oom);
+        assert!(FilterExpr::parse("failure:oom").unwrap().matches(&task));
+        assert!(!FilterExpr::parse("failure_category!=OOM").unwrap().matches(&task));
+
+        let mut params = Vec::new();
+        let sql = FilterExpr::parse("tag:a OR NOT worker:w1").unwrap().to_sql(&mut params);
+        assert_eq!(sql, "(EXISTS (SELECT 1 FROM json_each(COALESCE(tasks.tags, '[]')) WHERE value = ?) OR (NOT worker_id IS ?))");
+        assert_eq!(params, vec!["a".to_string(), "w1".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_errors_point_at_token() {
+        let error = |input: &str| FilterExpr::parse(input).unwrap_err();
+
+        let e = error("status:failed AND colour:red");
+        assert_eq!((e.position, e.token.as_str()), (19, "colour"));
+        assert!(e.message.starts_with("Unknown field"));
+
+        let e = error("status:done");
+        assert_eq!((e.position, e.token.as_str()), (8, "done"));
+
+        let e = error("tag>urgent");
+        assert_eq!((e.position, e.token.as_str()), (4, ">"));
+
+        let e = error("(status:failed OR tag:x");
+        assert_eq!((e.position, e.token.as_str()), (24, "end of input"));
+        assert!(e.message.contains("position 1"));
+
+        let e = error("status:failed)");
+        assert_eq!((e.position, e.token.as_str()), (14, ")"));
+
+        let e = error("status:failed AND");
+        assert_eq!(e.token, "end of input");
+
+        let e = error("dir:\"/srv");
+        assert_eq!((e.position, e.message.as_str()), (5, "Unterminated string"));
+
+        assert_eq!(error("created>yesterday").token, "yesterday");
+        assert_eq!(error("priority").message, "Expected an operator after 'priority'");
+        assert!(FilterExpr::parse(&"(".repeat(100)).is_err());
+        assert!(FilterExpr::parse("").is_err());
+    }
+}
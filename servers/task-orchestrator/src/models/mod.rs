@@ -3,7 +3,13 @@ use sqlx::FromRow;
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 
-use crate::domain::{TaskStatus, TaskPriority, TaskId, WorkDirectory, Prompt, TaskTag, WorkerId};
+pub mod filter_expr;
+pub mod sort;
+
+pub use filter_expr::{FilterExpr, FilterParseError};
+pub use sort::TaskSort;
+
+use crate::domain::{CancelReason, FailureCategory, TaskStatus, TaskPriority, TaskId, WorkDirectory, Prompt, TaskTag, WorkerId};
 
 /// 数据库任务记录
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
@@ -26,6 +32,11 @@ pub struct TaskRecord {
     pub metadata: Option<String>,
     pub version: i32,
     pub updated_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub cancel_reason: Option<String>,
+    pub concurrency_group: Option<String>,
+    pub failure_category: Option<String>,
+    pub retry_after: Option<DateTime<Utc>>,
 }
 
 impl TaskRecord {
@@ -48,6 +59,14 @@ impl TaskRecord {
             .map(|result| serde_json::from_str::<crate::domain::TaskResult>(&result))
             .transpose()?;
 
+        let cancel_reason = self.cancel_reason
+            .map(|reason| reason.parse::<CancelReason>())
+            .transpose()?;
+
+        let failure_category = self.failure_category
+            .map(|category| category.parse::<FailureCategory>())
+            .transpose()?;
+
         Ok(crate::domain::Task {
             id: TaskId::from_str(&self.task_id)?,
             work_directory: WorkDirectory::new(self.work_directory)?,
@@ -65,6 +84,11 @@ impl TaskRecord {
             max_retries: self.max_retries as u32,
             metadata,
             version: self.version as u32,
+            expires_at: self.expires_at,
+            cancel_reason,
+            concurrency_group: self.concurrency_group,
+            failure_category,
+            retry_after: self.retry_after,
         })
     }
 
@@ -103,6 +127,11 @@ impl TaskRecord {
             metadata,
             version: task.version as i32,
             updated_at: Utc::now(),
+            expires_at: task.expires_at,
+            cancel_reason: task.cancel_reason.map(|reason| reason.to_string()),
+            concurrency_group: task.concurrency_group.clone(),
+            failure_category: task.failure_category.map(|category| category.to_string()),
+            retry_after: task.retry_after,
         })
     }
 }
@@ -155,6 +184,185 @@ impl TaskHistoryRecord {
     }
 }
 
+/// 任务评论记录
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TaskCommentRecord {
+    pub id: i64,
+    pub task_id: String,
+    pub author: String,
+    pub text: String,
+    pub data: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaskCommentRecord {
+    /// 转换为领域模型
+    pub fn to_domain(self) -> Result<crate::domain::TaskComment, anyhow::Error> {
+        let data = self.data
+            .map(|data| serde_json::from_str::<serde_json::Value>(&data))
+            .transpose()?;
+
+        Ok(crate::domain::TaskComment {
+            id: self.id as u64,
+            task_id: TaskId::from_str(&self.task_id)?,
+            author: self.author,
+            text: self.text,
+            data,
+            created_at: self.created_at,
+        })
+    }
+
+    /// 从领域模型创建记录
+    pub fn from_domain(comment: &crate::domain::TaskComment) -> Result<Self, anyhow::Error> {
+        let data = comment.data
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+
+        Ok(Self {
+            id: comment.id as i64,
+            task_id: comment.task_id.to_string(),
+            author: comment.author.clone(),
+            text: comment.text.clone(),
+            data,
+            created_at: comment.created_at,
+        })
+    }
+}
+
+/// 元数据模式记录
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct MetadataSchemaRecord {
+    pub namespace: String,
+    pub schema: String,
+    pub strict: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MetadataSchemaRecord {
+    /// 转换为领域模型
+    pub fn to_domain(self) -> Result<crate::domain::MetadataSchema, anyhow::Error> {
+        Ok(crate::domain::MetadataSchema {
+            namespace: self.namespace,
+            schema: serde_json::from_str(&self.schema)?,
+            strict: self.strict,
+            updated_at: self.updated_at,
+        })
+    }
+
+    /// 从领域模型创建记录
+    pub fn from_domain(schema: &crate::domain::MetadataSchema) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            namespace: schema.namespace.clone(),
+            schema: serde_json::to_string(&schema.schema)?,
+            strict: schema.strict,
+            updated_at: schema.updated_at,
+        })
+    }
+}
+
+/// 流水线运行记录
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PipelineRunRecord {
+    pub run_id: String,
+    pub pipeline: String,
+    pub status: String,
+    pub steps: String,
+    pub skipped: String,
+    pub message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PipelineRunRecord {
+    /// 转换为领域模型
+    pub fn to_domain(self) -> Result<crate::domain::PipelineRun, anyhow::Error> {
+        Ok(crate::domain::PipelineRun {
+            run_id: self.run_id,
+            pipeline: self.pipeline,
+            status: self.status.parse()?,
+            steps: serde_json::from_str(&self.steps)?,
+            skipped: serde_json::from_str(&self.skipped)?,
+            message: self.message,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+
+    /// 从领域模型创建记录
+    pub fn from_domain(run: &crate::domain::PipelineRun) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            run_id: run.run_id.clone(),
+            pipeline: run.pipeline.clone(),
+            status: run.status.to_string(),
+            steps: serde_json::to_string(&run.steps)?,
+            skipped: serde_json::to_string(&run.skipped)?,
+            message: run.message.clone(),
+            created_at: run.created_at,
+            updated_at: run.updated_at,
+        })
+    }
+}
+
+/// 任务事件记录
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TaskEventRecord {
+    pub sequence: i64,
+    pub task_id: String,
+    pub event_type: String,
+    pub snapshot: Option<String>,
+    /// 评论事件的评论或记录事件的记录内容
+    pub payload: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl TaskEventRecord {
+    /// 转换为领域模型
+    pub fn to_domain(self) -> Result<crate::domain::TaskEvent, anyhow::Error> {
+        let snapshot = self.snapshot
+            .map(|snapshot| serde_json::from_str::<crate::domain::Task>(&snapshot))
+            .transpose()?;
+        let event_type = self.event_type.parse::<crate::domain::TaskEventType>()?;
+        let (comment, record) = match (event_type, self.payload) {
+            (crate::domain::TaskEventType::Commented, Some(payload)) => (Some(serde_json::from_str(&payload)?), None),
+            (crate::domain::TaskEventType::Recorded, Some(payload)) => (None, Some(serde_json::from_str(&payload)?)),
+            _ => (None, None),
+        };
+
+        Ok(crate::domain::TaskEvent {
+            sequence: self.sequence as u64,
+            task_id: TaskId::from_str(&self.task_id)?,
+            event_type,
+            snapshot,
+            occurred_at: self.occurred_at,
+            comment,
+            record,
+        })
+    }
+
+    /// 从领域模型创建记录
+    pub fn from_domain(event: &crate::domain::TaskEvent) -> Result<Self, anyhow::Error> {
+        let snapshot = event.snapshot
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let payload = match (&event.comment, &event.record) {
+            (Some(comment), _) => Some(serde_json::to_string(comment)?),
+            (None, Some(record)) => Some(serde_json::to_string(record)?),
+            (None, None) => None,
+        };
+
+        Ok(Self {
+            sequence: event.sequence as i64,
+            task_id: event.task_id.to_string(),
+            event_type: event.event_type.to_string(),
+            snapshot,
+            payload,
+            occurred_at: event.occurred_at,
+        })
+    }
+}
+
 /// 锁记录
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct LockRecord {
@@ -218,10 +426,16 @@ pub struct TaskFilter {
     pub created_after: Option<DateTime<Utc>>,
     pub created_before: Option<DateTime<Utc>>,
     pub worker_id: Option<String>,
+    pub cancel_reason: Option<CancelReason>,
+    pub failure_category: Option<FailureCategory>,
+    /// 有效期不晚于该时间的任务
+    pub expires_before: Option<DateTime<Utc>>,
+    /// 组合过滤表达式，与其他条件按 AND 组合
+    pub expression: Option<FilterExpr>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
-    pub sort_by: Option<String>,
-    pub sort_order: Option<String>,
+    /// 排序规则，末尾总是追加 `task_id` 保证顺序稳定
+    pub sort: TaskSort,
 }
 
 impl TaskFilter {
@@ -264,6 +478,26 @@ impl TaskFilter {
         self
     }
 
+    pub fn with_cancel_reason(mut self, cancel_reason: CancelReason) -> Self {
+        self.cancel_reason = Some(cancel_reason);
+        self
+    }
+
+    pub fn with_failure_category(mut self, failure_category: FailureCategory) -> Self {
+        self.failure_category = Some(failure_category);
+        self
+    }
+
+    pub fn with_expires_before(mut self, before: DateTime<Utc>) -> Self {
+        self.expires_before = Some(before);
+        self
+    }
+
+    pub fn with_expression(mut self, expression: FilterExpr) -> Self {
+        self.expression = Some(expression);
+        self
+    }
+
     pub fn with_limit(mut self, limit: i64) -> Self {
         self.limit = Some(limit);
         self
@@ -274,15 +508,34 @@ impl TaskFilter {
         self
     }
 
-    pub fn with_sort_by(mut self, sort_by: String) -> Self {
-        self.sort_by = Some(sort_by);
+    pub fn with_sort(mut self, sort: TaskSort) -> Self {
+        self.sort = sort;
         self
     }
+}
 
-    pub fn with_sort_order(mut self, sort_order: String) -> Self {
-        self.sort_order = Some(sort_order);
-        self
-    }
+/// SLA计算所需的任务时间点
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlaSample {
+    pub task_id: TaskId,
+    pub priority: TaskPriority,
+    pub status: TaskStatus,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// 最近一段时间的负载，用于容量预测
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoadSample {
+    /// 期间创建的任务数
+    pub arrivals: u64,
+    /// 期间执行结束（完成或最终失败）的任务数
+    pub completions: u64,
+    /// 这些任务从开始到结束的总耗时（秒）
+    pub service_seconds: f64,
+    /// 当前正在执行任务的工作节点数
+    pub busy_workers: u64,
 }
 
 /// 任务统计信息
@@ -292,9 +545,18 @@ pub struct TaskStatistics {
     pub completed_tasks: u64,
     pub failed_tasks: u64,
     pub cancelled_tasks: u64,
+    /// 因超过有效期被取消的任务数（包含在 `cancelled_tasks` 中）
+    pub expired_tasks: u64,
     pub active_tasks: u64,
     pub waiting_tasks: u64,
     pub working_tasks: u64,
+    /// 等待人工审批的任务数（不计入 `active_tasks`）
+    pub pending_approval_tasks: u64,
+    /// 按失败分类统计的任务数
+    pub failure_categories: std::collections::BTreeMap<String, u64>,
+    pub low_priority_tasks: u64,
+    pub medium_priority_tasks: u64,
+    pub high_priority_tasks: u64,
     pub success_rate: f64,
     pub avg_processing_time: f64,
     pub tasks_per_hour: f64,
@@ -307,9 +569,15 @@ impl TaskStatistics {
             completed_tasks: 0,
             failed_tasks: 0,
             cancelled_tasks: 0,
+            expired_tasks: 0,
             active_tasks: 0,
             waiting_tasks: 0,
             working_tasks: 0,
+            pending_approval_tasks: 0,
+            failure_categories: std::collections::BTreeMap::new(),
+            low_priority_tasks: 0,
+            medium_priority_tasks: 0,
+            high_priority_tasks: 0,
             success_rate: 0.0,
             avg_processing_time: 0.0,
             tasks_per_hour: 0.0,
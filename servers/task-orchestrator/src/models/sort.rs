@@ -0,0 +1,264 @@
+//! # 任务列表排序
+//!
+//! 列表查询的 `sort` 参数支持多个排序键，例如 `sort=priority:desc,created_at:asc`。
+//! 字段限定在 [`SortField`] 白名单内，方向默认 `asc`。排序键之后总是追加 `task_id` 升序，
+//! 排序值相同的任务在多次查询、分页之间的相对顺序保持不变。
+//!
+//! [`TaskSort::order_by_sql`] 中优先级按 low < medium < high 比较，空时间小于任何时间（升序时排在最前）。
+//! 测试中另有语义一致的内存排序（`TaskSort::compare`、`TaskSort::sort`），供内存仓库使用。
+
+#[cfg(test)]
+use std::cmp::Ordering;
+
+#[cfg(test)]
+use crate::domain::Task;
+use crate::errors::ValidationError;
+
+/// 最多排序键数（不含追加的 `task_id`）
+pub const MAX_SORT_KEYS: usize = 5;
+
+/// 可排序的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    TaskId,
+    CreatedAt,
+    StartedAt,
+    CompletedAt,
+    ExpiresAt,
+    Priority,
+    Status,
+    WorkDirectory,
+    RetryCount,
+}
+
+/// 排序方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+/// 单个排序键
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub direction: SortDirection,
+}
+
+/// 排序规则，默认按创建时间倒序
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskSort {
+    keys: Vec<SortKey>,
+}
+
+impl SortField {
+    /// 白名单中的字段名
+    pub const NAMES: &'static [&'static str] = &[
+        "task_id",
+        "created_at",
+        "started_at",
+        "completed_at",
+        "expires_at",
+        "priority",
+        "status",
+        "work_directory",
+        "retry_count",
+    ];
+
+    fn parse(name: &str) -> Option<Self> {
+        let field = match name {
+            "task_id" => Self::TaskId,
+            "created_at" => Self::CreatedAt,
+            "started_at" => Self::StartedAt,
+            "completed_at" => Self::CompletedAt,
+            "expires_at" => Self::ExpiresAt,
+            "priority" => Self::Priority,
+            "status" => Self::Status,
+            "work_directory" => Self::WorkDirectory,
+            "retry_count" => Self::RetryCount,
+            _ => return None,
+        };
+        Some(field)
+    }
+
+    fn sql(self) -> &'static str {
+        match self {
+            Self::TaskId => "task_id",
+            Self::CreatedAt => "created_at",
+            Self::StartedAt => "started_at",
+            Self::CompletedAt => "completed_at",
+            Self::ExpiresAt => "expires_at",
+            Self::Priority => "(CASE priority WHEN 'low' THEN 1 WHEN 'medium' THEN 2 WHEN 'high' THEN 3 ELSE 0 END)",
+            Self::Status => "status",
+            Self::WorkDirectory => "work_directory",
+            Self::RetryCount => "retry_count",
+        }
+    }
+
+    #[cfg(test)]
+    fn compare(self, a: &Task, b: &Task) -> Ordering {
+        match self {
+            Self::TaskId => a.id.to_string().cmp(&b.id.to_string()),
+            Self::CreatedAt => a.created_at.cmp(&b.created_at),
+            Self::StartedAt => a.started_at.cmp(&b.started_at),
+            Self::CompletedAt => a.completed_at.cmp(&b.completed_at),
+            Self::ExpiresAt => a.expires_at.cmp(&b.expires_at),
+            Self::Priority => a.priority.weight().cmp(&b.priority.weight()),
+            Self::Status => a.status.to_string().cmp(&b.status.to_string()),
+            Self::WorkDirectory => a.work_directory.as_str().cmp(b.work_directory.as_str()),
+            Self::RetryCount => a.retry_count.cmp(&b.retry_count),
+        }
+    }
+}
+
+impl SortDirection {
+    fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "asc" => Some(Self::Asc),
+            "desc" => Some(Self::Desc),
+            _ => None,
+        }
+    }
+
+    fn sql(self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+}
+
+impl TaskSort {
+    /// 解析 `field[:asc|desc],...` 格式的排序参数
+    pub fn parse(input: &str) -> Result<Self, ValidationError> {
+        let mut keys: Vec<SortKey> = Vec::new();
+        for part in input.split(',') {
+            let part = part.trim();
+            let (name, direction) = match part.split_once(':') {
+                Some((name, direction)) => (name.trim(), Some(direction.trim())),
+                None => (part, None),
+            };
+            keys.push(Self::key(name, direction)?);
+        }
+
+        if keys.len() > MAX_SORT_KEYS {
+            return Err(invalid(format!("Too many sort keys (max {})", MAX_SORT_KEYS)));
+        }
+        for (i, key) in keys.iter().enumerate() {
+            if keys[..i].iter().any(|k| k.field == key.field) {
+                return Err(invalid(format!("Duplicate sort field: {}", SortField::NAMES[key.field as usize])));
+            }
+        }
+        Ok(Self { keys })
+    }
+
+    /// 兼容旧的 `sort_by` / `sort_order` 参数
+    pub fn from_legacy(sort_by: &str, sort_order: Option<&str>) -> Result<Self, ValidationError> {
+        Ok(Self { keys: vec![Self::key(sort_by.trim(), sort_order.map(str::trim))?] })
+    }
+
+    fn key(name: &str, direction: Option<&str>) -> Result<SortKey, ValidationError> {
+        let field = SortField::parse(name).ok_or_else(|| {
+            invalid(format!("Invalid sort field: '{}' (allowed: {})", name, SortField::NAMES.join(", ")))
+        })?;
+        let direction = match direction {
+            None => SortDirection::Asc,
+            Some(direction) => SortDirection::parse(direction).ok_or_else(|| {
+                invalid(format!("Invalid sort direction: '{}' (expected asc or desc)", direction))
+            })?,
+        };
+        Ok(SortKey { field, direction })
+    }
+
+    /// 排序键，不含追加的 `task_id`
+    #[cfg(test)]
+    pub fn keys(&self) -> &[SortKey] {
+        &self.keys
+    }
+
+    /// 生成 `ORDER BY` 子句，末尾追加 `task_id` 保证顺序稳定
+    pub fn order_by_sql(&self) -> String {
+        let mut terms: Vec<String> = self
+            .keys
+            .iter()
+            .map(|key| format!("{} {}", key.field.sql(), key.direction.sql()))
+            .collect();
+        if !self.keys.iter().any(|key| key.field == SortField::TaskId) {
+            terms.push("task_id ASC".to_string());
+        }
+        format!(" ORDER BY {}", terms.join(", "))
+    }
+
+    /// 按相同规则比较两个任务
+    #[cfg(test)]
+    pub fn compare(&self, a: &Task, b: &Task) -> Ordering {
+        self.keys
+            .iter()
+            .map(|key| match key.direction {
+                SortDirection::Asc => key.field.compare(a, b),
+                SortDirection::Desc => key.field.compare(b, a),
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| SortField::TaskId.compare(a, b))
+    }
+
+    /// 在内存中排序任务
+    #[cfg(test)]
+    pub fn sort(&self, tasks: &mut [Task]) {
+        tasks.sort_by(|a, b| self.compare(a, b));
+    }
+}
+
+impl Default for TaskSort {
+    fn default() -> Self {
+        Self { keys: vec![SortKey { field: SortField::CreatedAt, direction: SortDirection::Desc }] }
+    }
+}
+
+fn invalid(message: String) -> ValidationError {
+    ValidationError::invalid_validation(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Prompt, TaskPriority, WorkDirectory};
+
+    #[test]
+    fn test_parse_and_compare() {
+        let sort = TaskSort::parse("priority:desc, created_at").unwrap();
+        assert_eq!(
+            sort.order_by_sql(),
+            " ORDER BY (CASE priority WHEN 'low' THEN 1 WHEN 'medium' THEN 2 WHEN 'high' THEN 3 ELSE 0 END) DESC, created_at ASC, task_id ASC"
+        );
+        assert_eq!(TaskSort::parse("task_id:DESC").unwrap().order_by_sql(), " ORDER BY task_id DESC");
+        assert_eq!(TaskSort::from_legacy("retry_count", Some("desc")).unwrap().keys().len(), 1);
+
+        let new_task = |priority| Task::new(
+            WorkDirectory::new("/test".to_string()).unwrap(),
+            Prompt::new("Test".to_string()).unwrap(),
+            priority,
+            vec![],
+        );
+        let (low, high) = (new_task(TaskPriority::Low), new_task(TaskPriority::High));
+        assert_eq!(sort.compare(&high, &low), Ordering::Less);
+
+        // 所有排序值相同时按 task_id 排序
+        let mut same = low.clone();
+        same.id = crate::domain::TaskId::new();
+        let expected = low.id.to_string().cmp(&same.id.to_string());
+        assert_eq!(TaskSort::parse("priority").unwrap().compare(&low, &same), expected);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_sort() {
+        let message = |input: &str| TaskSort::parse(input).unwrap_err().to_string();
+        assert!(message("created_at; DROP TABLE tasks").contains("Invalid sort field"));
+        assert!(message("prompt").contains("allowed: task_id"));
+        assert!(message("priority:up").contains("Invalid sort direction"));
+        assert!(message("priority,priority:desc").contains("Duplicate sort field: priority"));
+        assert!(message("created_at,").contains("Invalid sort field: ''"));
+        assert!(message("task_id,created_at,started_at,completed_at,expires_at,priority").contains("Too many"));
+        assert!(TaskSort::from_legacy("created_at desc", None).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+//! 人工审批策略
+//!
+//! 需要审批的任务创建后处于 `PendingApproval` 状态，审批通过后才进入等待队列。
+//! 策略负责识别审批人并给出自动拒绝的时限。
+
+use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::ApprovalConfig;
+use crate::errors::{AppError, AppResult};
+
+/// 审批策略
+#[derive(Debug, Default)]
+pub struct ApprovalPolicy {
+    /// API密钥 -> 审批人名称
+    approvers: HashMap<String, String>,
+    auto_reject_after: Option<Duration>,
+}
+
+impl ApprovalPolicy {
+    /// 根据配置构建策略
+    pub fn from_config(config: &ApprovalConfig) -> AppResult<Self> {
+        let mut approvers = HashMap::with_capacity(config.approvers.len());
+        for (name, key) in &config.approvers {
+            if name.trim().is_empty() || key.is_empty() {
+                return Err(AppError::Internal("Approver name and API key cannot be empty".to_string()));
+            }
+            if let Some(existing) = approvers.insert(key.clone(), name.clone()) {
+                return Err(AppError::Internal(format!(
+                    "Approvers '{}' and '{}' share the same API key",
+                    existing, name
+                )));
+            }
+        }
+
+        let auto_reject_after = match config.auto_reject_after {
+            0 => None,
+            seconds => Some(i64::try_from(seconds).ok().and_then(Duration::try_seconds).ok_or_else(|| {
+                AppError::Internal("approval.auto_reject_after is too large".to_string())
+            })?),
+        };
+
+        Ok(Self { approvers, auto_reject_after })
+    }
+
+    /// 识别审批人
+    ///
+    /// 配置了审批人时按调用方的API密钥识别，忽略请求中声明的审批人；
+    /// 未配置时不校验调用方，使用请求中声明的审批人。
+    pub fn identify(&self, api_key: Option<&str>, claimed: Option<&str>) -> AppResult<String> {
+        if self.approvers.is_empty() {
+            return claimed
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    AppError::Validation(crate::errors::ValidationError::missing_field("approver".to_string()))
+                });
+        }
+
+        api_key
+            .and_then(|key| self.approvers.get(key))
+            .cloned()
+            .ok_or_else(|| AppError::Authorization("Caller is not a configured approver".to_string()))
+    }
+
+    /// 创建时间早于该时刻的待审批任务应自动拒绝，未启用自动拒绝时返回 `None`
+    pub fn auto_reject_before(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.auto_reject_after.map(|after| now - after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify_approver() {
+        let open = ApprovalPolicy::default();
+        assert_eq!(open.identify(None, Some(" alice ")).unwrap(), "alice");
+        assert!(matches!(open.identify(None, Some("  ")), Err(AppError::Validation(_))));
+
+        let config = ApprovalConfig {
+            approvers: HashMap::from([("alice".to_string(), "key-a".to_string())]),
+            auto_reject_after: 60,
+        };
+        let policy = ApprovalPolicy::from_config(&config).unwrap();
+        assert_eq!(policy.identify(Some("key-a"), Some("mallory")).unwrap(), "alice");
+        assert!(matches!(policy.identify(Some("key-b"), Some("alice")), Err(AppError::Authorization(_))));
+        assert!(matches!(policy.identify(None, None), Err(AppError::Authorization(_))));
+
+        let now = Utc::now();
+        assert_eq!(policy.auto_reject_before(now), Some(now - Duration::seconds(60)));
+        assert_eq!(open.auto_reject_before(now), None);
+    }
+
+    #[test]
+    fn test_rejects_shared_keys() {
+        let config = ApprovalConfig {
+            approvers: HashMap::from([
+                ("alice".to_string(), "key".to_string()),
+                ("bob".to_string(), "key".to_string()),
+            ]),
+            auto_reject_after: 0,
+        };
+        assert!(ApprovalPolicy::from_config(&config).is_err());
+    }
+}
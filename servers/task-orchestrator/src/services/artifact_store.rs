@@ -0,0 +1,189 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::domain::TaskId;
+use crate::infrastructure::ObjectStore;
+use crate::errors::{AppError, AppResult};
+
+/// 默认的单个产物大小上限（256MB）
+pub const DEFAULT_MAX_ARTIFACT_SIZE: usize = 256 * 1024 * 1024;
+
+/// 已归档任务的产物所在目录
+const ARCHIVE_DIR: &str = "archived";
+
+/// 任务产物存储
+///
+/// 产物按 `<prefix>/<task_id>/<name>` 组织在对象存储中，下载通过签名URL完成。
+/// 任务移入冷存储后，产物随之移到 `<prefix>/archived/<task_id>/<name>`。
+pub struct ArtifactStore {
+    object_store: Arc<dyn ObjectStore>,
+    prefix: String,
+    presign_expiry: Duration,
+    max_size: usize,
+}
+
+impl ArtifactStore {
+    pub fn new(object_store: Arc<dyn ObjectStore>, prefix: String, presign_expiry: Duration) -> Self {
+        Self {
+            object_store,
+            prefix: prefix.trim_matches('/').to_string(),
+            presign_expiry,
+            max_size: DEFAULT_MAX_ARTIFACT_SIZE,
+        }
+    }
+
+    /// 设置单个产物的最大字节数
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// 单个产物的最大字节数
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// 产物对象键
+    pub fn artifact_key(&self, task_id: &TaskId, name: &str) -> AppResult<String> {
+        Self::validate_name(name)?;
+        Ok(format!("{}{}", self.task_prefix(task_id), name))
+    }
+
+    /// 已归档产物的对象键
+    pub fn archived_key(&self, task_id: &TaskId, name: &str) -> AppResult<String> {
+        Self::validate_name(name)?;
+        Ok(format!("{}{}", self.archived_prefix(task_id), name))
+    }
+
+    fn validate_name(name: &str) -> AppResult<()> {
+        if name.is_empty() || name.contains("..") || name.starts_with('/') {
+            return Err(AppError::Validation(
+                crate::errors::ValidationError::InvalidValidation(
+                    "Invalid artifact name".to_string()
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    /// 任务产物的键前缀，以 `/` 结尾
+    fn task_prefix(&self, task_id: &TaskId) -> String {
+        if self.prefix.is_empty() {
+            format!("{}/", task_id)
+        } else {
+            format!("{}/{}/", self.prefix, task_id)
+        }
+    }
+
+    /// 已归档任务产物的键前缀，以 `/` 结尾
+    fn archived_prefix(&self, task_id: &TaskId) -> String {
+        if self.prefix.is_empty() {
+            format!("{}/{}/", ARCHIVE_DIR, task_id)
+        } else {
+            format!("{}/{}/{}/", self.prefix, ARCHIVE_DIR, task_id)
+        }
+    }
+
+    /// 上传产物
+    pub async fn upload(&self, task_id: &TaskId, name: &str, data: Vec<u8>, content_type: &str) -> AppResult<String> {
+        let key = self.artifact_key(task_id, name)?;
+        self.object_store.put_object(&key, data, content_type).await?;
+        Ok(key)
+    }
+
+    /// 生成产物下载地址
+    pub fn download_url(&self, task_id: &TaskId, name: &str) -> AppResult<String> {
+        let key = self.artifact_key(task_id, name)?;
+        Ok(self.object_store.presigned_get_url(&key, self.presign_expiry)?)
+    }
+
+    /// 生成已存在产物的下载地址，产物不存在时返回 [`AppError::ArtifactNotFound`]
+    ///
+    /// 先查找任务目录，再查找归档目录，归档过程中断时两处都可能存有产物。
+    pub async fn existing_download_url(&self, task_id: &TaskId, name: &str) -> AppResult<(String, String)> {
+        let key = self.artifact_key(task_id, name)?;
+        let key = if self.object_store.head_object(&key).await? {
+            key
+        } else {
+            let archived = self.archived_key(task_id, name)?;
+            if !self.object_store.head_object(&archived).await? {
+                return Err(AppError::ArtifactNotFound(key));
+            }
+            archived
+        };
+        let url = self.object_store.presigned_get_url(&key, self.presign_expiry)?;
+        Ok((key, url))
+    }
+
+    /// 将任务的全部产物移到归档目录，返回移动的产物数
+    ///
+    /// 每个产物先复制再删除原对象，中途失败时产物仍可从原位置下载。
+    pub async fn archive_task(&self, task_id: &TaskId) -> AppResult<usize> {
+        let prefix = self.task_prefix(task_id);
+        let archived_prefix = self.archived_prefix(task_id);
+        let keys = self.object_store.list_objects(&prefix).await?;
+        for key in &keys {
+            let destination = format!("{}{}", archived_prefix, &key[prefix.len()..]);
+            if self.object_store.copy_object(key, &destination).await? {
+                self.object_store.delete_object(key).await?;
+            }
+        }
+        Ok(keys.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_storage::MemoryObjectStore;
+
+    #[tokio::test]
+    async fn test_upload_and_download_url() {
+        let object_store = Arc::new(MemoryObjectStore::new());
+        let store = ArtifactStore::new(object_store.clone(), "/artifacts/".to_string(), Duration::from_secs(60));
+        let task_id = TaskId::new();
+
+        let key = store.upload(&task_id, "report.json", b"{}".to_vec(), "application/json").await.unwrap();
+        assert_eq!(key, format!("artifacts/{}/report.json", task_id));
+        assert!(object_store.get_object(&key).await.unwrap().is_some());
+        assert_eq!(store.download_url(&task_id, "report.json").unwrap(), format!("memory://{}?expires=60", key));
+        assert!(store.artifact_key(&task_id, "../escape").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_missing_artifact_is_not_found() {
+        let store = ArtifactStore::new(Arc::new(MemoryObjectStore::new()), "artifacts".to_string(), Duration::from_secs(60));
+        let task_id = TaskId::new();
+
+        let result = store.existing_download_url(&task_id, "missing.txt").await;
+        assert!(matches!(result, Err(AppError::ArtifactNotFound(_))));
+
+        store.upload(&task_id, "present.txt", b"ok".to_vec(), "text/plain").await.unwrap();
+        let (key, url) = store.existing_download_url(&task_id, "present.txt").await.unwrap();
+        assert_eq!(url, format!("memory://{}?expires=60", key));
+    }
+
+    #[tokio::test]
+    async fn test_archived_artifacts_remain_downloadable() {
+        let object_store = Arc::new(MemoryObjectStore::new());
+        let store = ArtifactStore::new(object_store.clone(), "artifacts".to_string(), Duration::from_secs(60));
+        let task_id = TaskId::new();
+        let other_id = TaskId::new();
+        store.upload(&task_id, "report.json", b"{}".to_vec(), "application/json").await.unwrap();
+        store.upload(&task_id, "logs/run.txt", b"ok".to_vec(), "text/plain").await.unwrap();
+        store.upload(&other_id, "report.json", b"[]".to_vec(), "application/json").await.unwrap();
+
+        assert_eq!(store.archive_task(&task_id).await.unwrap(), 2);
+
+        let archived = format!("artifacts/archived/{}/logs/run.txt", task_id);
+        assert_eq!(object_store.get_object(&archived).await.unwrap(), Some(b"ok".to_vec()));
+        assert!(object_store.list_objects(&format!("artifacts/{}/", task_id)).await.unwrap().is_empty());
+        let (key, _) = store.existing_download_url(&task_id, "logs/run.txt").await.unwrap();
+        assert_eq!(key, archived);
+
+        // 其他任务的产物不受影响
+        let (key, _) = store.existing_download_url(&other_id, "report.json").await.unwrap();
+        assert_eq!(key, format!("artifacts/{}/report.json", other_id));
+        assert_eq!(store.archive_task(&task_id).await.unwrap(), 0);
+    }
+}
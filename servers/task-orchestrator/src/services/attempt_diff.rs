@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+
+use crate::domain::{TaskAttempt, TaskResult, TaskResultStatus};
+
+/// JSON差异类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// 单处JSON差异
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonChange {
+    /// JSON Pointer路径
+    pub path: String,
+    pub kind: JsonChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<serde_json::Value>,
+}
+
+/// 两次执行的输出差异
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttemptDiff {
+    pub from: u32,
+    pub to: u32,
+    pub from_status: TaskResultStatus,
+    pub to_status: TaskResultStatus,
+    /// 输出的统一格式文本差异，输出相同时为空
+    pub unified: String,
+    /// 两次输出都是JSON时的结构化差异
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json: Option<Vec<JsonChange>>,
+}
+
+/// 比较两次执行的输出；没有输出的执行（通常是失败）使用错误信息比较
+pub fn diff_attempts(from: &TaskAttempt, to: &TaskAttempt) -> AttemptDiff {
+    let (old, new) = (compared_text(&from.result), compared_text(&to.result));
+    let unified = TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("attempt {}", from.attempt), &format!("attempt {}", to.attempt))
+        .to_string();
+
+    let json = match (
+        serde_json::from_str::<serde_json::Value>(old),
+        serde_json::from_str::<serde_json::Value>(new),
+    ) {
+        (Ok(old), Ok(new)) => {
+            let mut changes = Vec::new();
+            diff_json(&old, &new, String::new(), &mut changes);
+            Some(changes)
+        }
+        _ => None,
+    };
+
+    AttemptDiff {
+        from: from.attempt,
+        to: to.attempt,
+        from_status: from.result.status,
+        to_status: to.result.status,
+        // 文本相同时 `similar` 仍会输出文件头
+        unified: if old == new { String::new() } else { unified },
+        json,
+    }
+}
+
+fn compared_text(result: &TaskResult) -> &str {
+    result.output.as_deref().or(result.error.as_deref()).unwrap_or_default()
+}
+
+/// 递归比较JSON，对象按键、数组按下标比较
+fn diff_json(old: &serde_json::Value, new: &serde_json::Value, path: String, changes: &mut Vec<JsonChange>) {
+    use serde_json::Value;
+
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                let child = format!("{}/{}", path, escape_pointer(key));
+                match new.get(key) {
+                    Some(new_value) => diff_json(old_value, new_value, child, changes),
+                    None => changes.push(JsonChange {
+                        path: child,
+                        kind: JsonChangeKind::Removed,
+                        from: Some(old_value.clone()),
+                        to: None,
+                    }),
+                }
+            }
+            for (key, new_value) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                changes.push(JsonChange {
+                    path: format!("{}/{}", path, escape_pointer(key)),
+                    kind: JsonChangeKind::Added,
+                    from: None,
+                    to: Some(new_value.clone()),
+                });
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for index in 0..old.len().max(new.len()) {
+                let child = format!("{}/{}", path, index);
+                match (old.get(index), new.get(index)) {
+                    (Some(old_value), Some(new_value)) => diff_json(old_value, new_value, child, changes),
+                    (Some(old_value), None) => changes.push(JsonChange {
+                        path: child,
+                        kind: JsonChangeKind::Removed,
+                        from: Some(old_value.clone()),
+                        to: None,
+                    }),
+                    (None, Some(new_value)) => changes.push(JsonChange {
+                        path: child,
+                        kind: JsonChangeKind::Added,
+                        from: None,
+                        to: Some(new_value.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if old != new => changes.push(JsonChange {
+            path,
+            kind: JsonChangeKind::Changed,
+            from: Some(old.clone()),
+            to: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::TaskStatus;
+
+    fn attempt(attempt: u32, result: TaskResult) -> TaskAttempt {
+        TaskAttempt {
+            attempt,
+            status: TaskStatus::Completed,
+            worker_id: None,
+            finished_at: chrono::Utc::now(),
+            result,
+        }
+    }
+
+    #[test]
+    fn test_diff_attempts() {
+        let first = attempt(1, TaskResult::success("line a\nline b\n".to_string()));
+        let second = attempt(2, TaskResult::success("line a\nline c\n".to_string()));
+        let diff = diff_attempts(&first, &second);
+        assert!(diff.unified.starts_with("--- attempt 1\n+++ attempt 2\n"));
+        assert!(diff.unified.contains("-line b\n+line c\n"));
+        assert!(diff.json.is_none());
+
+        assert!(diff_attempts(&first, &first).unified.is_empty());
+    }
+
+    #[test]
+    fn test_attempt_from_history() {
+        let task_id = crate::domain::TaskId::new();
+        let history = crate::domain::TaskHistory::new(task_id, TaskStatus::Waiting, None)
+            .with_attempt(2, &TaskResult::failed("timeout".to_string()));
+        let attempt = history.attempt().unwrap();
+        assert_eq!(attempt.attempt, 2);
+        assert_eq!(attempt.status, TaskStatus::Waiting);
+        assert_eq!(attempt.result.error.as_deref(), Some("timeout"));
+
+        assert!(crate::domain::TaskHistory::new(task_id, TaskStatus::Working, None).attempt().is_none());
+    }
+
+    #[test]
+    fn test_json_diff() {
+        let first = attempt(1, TaskResult::success(r#"{"files":["a.rs"],"ok":false,"a/b":1}"#.to_string()));
+        let second = attempt(2, TaskResult::success(r#"{"files":["a.rs","b.rs"],"ok":true}"#.to_string()));
+        let changes = diff_attempts(&first, &second).json.unwrap();
+
+        assert!(changes.contains(&JsonChange {
+            path: "/ok".to_string(),
+            kind: JsonChangeKind::Changed,
+            from: Some(serde_json::json!(false)),
+            to: Some(serde_json::json!(true)),
+        }));
+        assert!(changes.iter().any(|c| c.path == "/files/1" && c.kind == JsonChangeKind::Added));
+        assert!(changes.iter().any(|c| c.path == "/a~1b" && c.kind == JsonChangeKind::Removed));
+        assert_eq!(changes.len(), 3);
+    }
+}
@@ -0,0 +1,208 @@
+//! 容量预测
+//!
+//! 根据最近一段时间的任务到达率和平均执行耗时，用 M/M/c 排队模型（Erlang C）
+//! 估算当前工作节点数下的排队等待时间，并模拟增加工作节点后的效果。
+//! 模型假设到达和执行耗时都服从指数分布，结果只用于粗略的扩容参考。
+
+use serde::Serialize;
+
+use crate::models::LoadSample;
+
+/// 单个工作节点数下的预测结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CapacityScenario {
+    pub workers: u32,
+    /// 相对当前工作节点数增加的数量
+    pub added: u32,
+    /// 工作节点利用率（到达率 / 总处理能力），没有执行耗时数据时为空
+    pub utilization: Option<f64>,
+    /// 利用率低于 1 时队列才会收敛
+    pub stable: bool,
+    /// 新任务需要排队的概率
+    pub wait_probability: Option<f64>,
+    pub mean_wait_seconds: Option<f64>,
+    pub p50_wait_seconds: Option<f64>,
+    pub p95_wait_seconds: Option<f64>,
+}
+
+/// 容量预测报告
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CapacityForecast {
+    /// 统计窗口（秒）
+    pub window: u64,
+    pub arrivals: u64,
+    pub completions: u64,
+    /// 到达率（每分钟任务数）
+    pub arrival_rate_per_minute: f64,
+    /// 平均执行耗时，窗口内没有执行结束的任务时为空
+    pub mean_service_seconds: Option<f64>,
+    pub current_workers: u32,
+    pub scenarios: Vec<CapacityScenario>,
+}
+
+/// M/M/c 排队模型
+#[derive(Debug, Clone, Copy)]
+pub struct QueueModel {
+    /// 到达率（每秒）
+    arrival_rate: f64,
+    /// 平均执行耗时（秒）
+    service_seconds: Option<f64>,
+}
+
+impl QueueModel {
+    /// 根据负载样本构建模型
+    pub fn from_sample(sample: &LoadSample, window_seconds: u64) -> Self {
+        let service_seconds = (sample.completions > 0 && sample.service_seconds > 0.0)
+            .then(|| sample.service_seconds / sample.completions as f64);
+        Self {
+            arrival_rate: sample.arrivals as f64 / window_seconds.max(1) as f64,
+            service_seconds,
+        }
+    }
+
+    /// 估算 `workers` 个工作节点时的排队情况
+    pub fn scenario(&self, workers: u32, added: u32) -> CapacityScenario {
+        let mut scenario = CapacityScenario {
+            workers,
+            added,
+            utilization: None,
+            stable: false,
+            wait_probability: None,
+            mean_wait_seconds: None,
+            p50_wait_seconds: None,
+            p95_wait_seconds: None,
+        };
+
+        if self.arrival_rate <= 0.0 {
+            // 没有新任务时不会排队
+            scenario.utilization = self.service_seconds.map(|_| 0.0);
+            scenario.stable = true;
+            scenario.wait_probability = Some(0.0);
+            scenario.mean_wait_seconds = Some(0.0);
+            scenario.p50_wait_seconds = Some(0.0);
+            scenario.p95_wait_seconds = Some(0.0);
+            return scenario;
+        }
+        let Some(service_seconds) = self.service_seconds else {
+            return scenario;
+        };
+        if workers == 0 {
+            return scenario;
+        }
+
+        // 负载 a = λ/μ，利用率 ρ = a/c
+        let load = self.arrival_rate * service_seconds;
+        let c = workers as f64;
+        scenario.utilization = Some(load / c);
+        if load >= c {
+            return scenario;
+        }
+
+        let wait_probability = erlang_c(workers, load);
+        // 排队时的剩余处理速率 cμ - λ
+        let drain_rate = c / service_seconds - self.arrival_rate;
+        scenario.stable = true;
+        scenario.wait_probability = Some(wait_probability);
+        scenario.mean_wait_seconds = Some(wait_probability / drain_rate);
+        scenario.p50_wait_seconds = Some(wait_quantile(wait_probability, drain_rate, 0.50));
+        scenario.p95_wait_seconds = Some(wait_quantile(wait_probability, drain_rate, 0.95));
+        scenario
+    }
+}
+
+/// Erlang C 公式：`workers` 个节点、负载为 `load` 时新任务需要排队的概率，要求 `load < workers`
+fn erlang_c(workers: u32, load: f64) -> f64 {
+    // 先迭代计算 Erlang B，避免阶乘溢出
+    let mut blocking = 1.0;
+    for k in 1..=workers {
+        blocking = load * blocking / (k as f64 + load * blocking);
+    }
+    let c = workers as f64;
+    c * blocking / (c - load * (1.0 - blocking))
+}
+
+/// 等待时间的分位数：P(W > t) = C·e^{-(cμ-λ)t}
+fn wait_quantile(wait_probability: f64, drain_rate: f64, quantile: f64) -> f64 {
+    let tail = 1.0 - quantile;
+    if wait_probability <= tail {
+        0.0
+    } else {
+        (wait_probability / tail).ln() / drain_rate
+    }
+}
+
+/// 生成容量预测，`added` 为要模拟增加的工作节点数
+pub fn forecast(sample: &LoadSample, window_seconds: u64, current_workers: u32, added: &[u32]) -> CapacityForecast {
+    let model = QueueModel::from_sample(sample, window_seconds);
+    let mut extra: Vec<u32> = added.iter().copied().filter(|n| *n > 0).collect();
+    extra.sort_unstable();
+    extra.dedup();
+
+    let scenarios = std::iter::once(0)
+        .chain(extra)
+        .map(|n| model.scenario(current_workers.saturating_add(n), n))
+        .collect();
+
+    CapacityForecast {
+        window: window_seconds,
+        arrivals: sample.arrivals,
+        completions: sample.completions,
+        arrival_rate_per_minute: model.arrival_rate * 60.0,
+        mean_service_seconds: model.service_seconds,
+        current_workers,
+        scenarios,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(arrivals: u64, completions: u64, service_seconds: f64) -> LoadSample {
+        LoadSample { arrivals, completions, service_seconds, busy_workers: 0 }
+    }
+
+    #[test]
+    fn test_erlang_c() {
+        // 单节点时 C = ρ
+        assert!((erlang_c(1, 0.5) - 0.5).abs() < 1e-9);
+        // 经典算例：a = 2，c = 3 时 C ≈ 0.4444
+        assert!((erlang_c(3, 2.0) - 4.0 / 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_forecast_scenarios() {
+        // 一小时 3600 个任务（每秒 1 个），平均耗时 2 秒
+        let report = forecast(&sample(3600, 100, 200.0), 3600, 3, &[2, 1, 1, 0]);
+        assert_eq!(report.arrival_rate_per_minute, 60.0);
+        assert_eq!(report.mean_service_seconds, Some(2.0));
+        assert_eq!(
+            report.scenarios.iter().map(|s| (s.workers, s.added)).collect::<Vec<_>>(),
+            vec![(3, 0), (4, 1), (5, 2)]
+        );
+
+        let current = &report.scenarios[0];
+        assert!(current.stable);
+        assert!((current.mean_wait_seconds.unwrap() - 8.0 / 9.0).abs() < 1e-9);
+        assert_eq!(current.p50_wait_seconds, Some(0.0));
+        assert!(current.p95_wait_seconds.unwrap() > current.mean_wait_seconds.unwrap());
+        // 增加节点后等待时间下降
+        assert!(report.scenarios[2].p95_wait_seconds.unwrap() < current.p95_wait_seconds.unwrap());
+    }
+
+    #[test]
+    fn test_unstable_and_missing_data() {
+        let overloaded = forecast(&sample(3600, 10, 50.0), 3600, 4, &[]);
+        assert!(!overloaded.scenarios[0].stable);
+        assert_eq!(overloaded.scenarios[0].utilization, Some(1.25));
+        assert_eq!(overloaded.scenarios[0].p95_wait_seconds, None);
+
+        let no_service = forecast(&sample(10, 0, 0.0), 3600, 2, &[1]);
+        assert_eq!(no_service.mean_service_seconds, None);
+        assert!(no_service.scenarios.iter().all(|s| s.mean_wait_seconds.is_none()));
+
+        let idle = forecast(&sample(0, 0, 0.0), 3600, 0, &[]);
+        assert!(idle.scenarios[0].stable);
+        assert_eq!(idle.scenarios[0].p95_wait_seconds, Some(0.0));
+    }
+}
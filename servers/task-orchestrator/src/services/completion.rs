@@ -0,0 +1,89 @@
+//! # 任务完成通知
+//!
+//! 任务进入终态（完成、失败、取消）时广播任务ID，`GET /api/v1/tasks/:id/wait` 的长轮询据此立即返回，
+//! 客户端提交任务后可以直接等待结果，无需自行轮询或提供回调地址。
+//!
+//! 通知只在本进程内传递；多实例部署时其他实例完成的任务由等待方定期重新读取任务发现。
+
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+
+use crate::domain::TaskId;
+
+/// 广播通道容量，等待方处理不及时丢失通知时会重新读取任务
+const CHANNEL_CAPACITY: usize = 256;
+
+/// 未指定超时时的默认等待时间（秒）
+pub const DEFAULT_WAIT_TIMEOUT: u64 = 30;
+
+/// 未收到通知时重新读取任务的间隔
+pub const WAIT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 任务完成通知
+#[derive(Debug)]
+pub struct CompletionNotifier {
+    sender: broadcast::Sender<TaskId>,
+}
+
+impl CompletionNotifier {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    /// 通知任务已进入终态
+    pub fn notify(&self, task_id: TaskId) {
+        // 没有等待方时发送失败，可以忽略
+        let _ = self.sender.send(task_id);
+    }
+
+    /// 订阅完成通知，应在读取任务状态之前订阅，避免错过读取与订阅之间的通知
+    pub fn subscribe(&self) -> broadcast::Receiver<TaskId> {
+        self.sender.subscribe()
+    }
+
+    /// 当前等待中的订阅数
+    pub fn waiters(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl Default for CompletionNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 等待指定任务的通知，返回 `true` 表示收到通知或丢失了部分通知，需要重新读取任务
+pub async fn wait_for(receiver: &mut broadcast::Receiver<TaskId>, task_id: TaskId) -> bool {
+    loop {
+        match receiver.recv().await {
+            Ok(id) if id == task_id => return true,
+            Ok(_) => continue,
+            Err(broadcast::error::RecvError::Lagged(_)) => return true,
+            Err(broadcast::error::RecvError::Closed) => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_for_task_notification() {
+        let notifier = CompletionNotifier::new();
+        let task_id = TaskId::new();
+        let mut receiver = notifier.subscribe();
+        assert_eq!(notifier.waiters(), 1);
+
+        notifier.notify(TaskId::new());
+        notifier.notify(task_id);
+        assert!(tokio::time::timeout(Duration::from_secs(1), wait_for(&mut receiver, task_id)).await.unwrap());
+
+        // 没有对应通知时一直等待
+        notifier.notify(TaskId::new());
+        assert!(tokio::time::timeout(Duration::from_millis(50), wait_for(&mut receiver, task_id)).await.is_err());
+    }
+}
@@ -0,0 +1,313 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::domain::TaskEvent;
+use crate::infrastructure::EventBatchPublisher;
+use crate::utils::MetricsCollector;
+
+/// 导出器投递统计，配置了指标收集器时同步计入Prometheus
+#[derive(Default)]
+struct ExporterCounters {
+    enqueued: AtomicU64,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+    dropped: AtomicU64,
+    batches: AtomicU64,
+    retries: AtomicU64,
+    metrics: Option<Arc<MetricsCollector>>,
+}
+
+impl ExporterCounters {
+    fn add(&self, counter: &AtomicU64, outcome: &str, count: u64) {
+        counter.fetch_add(count, Ordering::Relaxed);
+        if let Some(metrics) = &self.metrics {
+            metrics.record_event_export(outcome, count);
+        }
+    }
+}
+
+enum ExporterCommand {
+    Export(Box<TaskEvent>),
+    Flush(oneshot::Sender<()>),
+}
+
+/// 导出器统计快照
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExporterStats {
+    pub enqueued: u64,
+    pub delivered: u64,
+    pub failed: u64,
+    pub dropped: u64,
+    pub batches: u64,
+    pub retries: u64,
+}
+
+/// 导出器配置
+#[derive(Debug, Clone)]
+pub struct ExporterSettings {
+    pub topic: String,
+    pub batch_size: usize,
+    pub flush_interval: Duration,
+    pub max_retries: u32,
+    pub retry_backoff: Duration,
+    pub buffer_size: usize,
+}
+
+/// 任务生命周期事件导出器
+///
+/// 事件先进入内存缓冲区，后台循环按批次大小或刷新间隔批量发布，
+/// 发布失败时按指数退避重试，超过重试次数的批次计入失败数。
+/// 关闭前调用 [`TaskEventExporter::shutdown`] 发布缓冲区中剩余的事件。
+pub struct TaskEventExporter {
+    sender: mpsc::Sender<ExporterCommand>,
+    counters: Arc<ExporterCounters>,
+}
+
+impl TaskEventExporter {
+    /// 创建导出器并启动后台发布循环，`metrics` 用于在Prometheus中导出投递统计
+    pub fn start(
+        publisher: Arc<dyn EventBatchPublisher>,
+        settings: ExporterSettings,
+        metrics: Option<Arc<MetricsCollector>>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(settings.buffer_size.max(1));
+        let counters = Arc::new(ExporterCounters { metrics, ..Default::default() });
+
+        tokio::spawn(run_export_loop(publisher, settings, receiver, counters.clone()));
+
+        Self { sender, counters }
+    }
+
+    /// 导出事件（非阻塞，缓冲区满时丢弃）
+    pub fn export(&self, event: TaskEvent) {
+        match self.sender.try_send(ExporterCommand::Export(Box::new(event))) {
+            Ok(()) => {
+                self.counters.add(&self.counters.enqueued, "enqueued", 1);
+            }
+            Err(_) => {
+                self.counters.add(&self.counters.dropped, "dropped", 1);
+                tracing::warn!("Task event export buffer full, event dropped");
+            }
+        }
+    }
+
+    /// 关闭前发布缓冲区中剩余的事件，发布循环处理完成（含重试）后返回
+    pub async fn shutdown(&self) {
+        let (done, wait) = oneshot::channel();
+        if self.sender.send(ExporterCommand::Flush(done)).await.is_err() || wait.await.is_err() {
+            tracing::error!("Task event exporter stopped before the final flush");
+            return;
+        }
+        let stats = self.stats();
+        let pending = stats.enqueued.saturating_sub(stats.delivered + stats.failed);
+        if pending > 0 {
+            tracing::error!("{} task events were not exported before shutdown", pending);
+        }
+    }
+
+    /// 获取统计快照
+    pub fn stats(&self) -> ExporterStats {
+        ExporterStats {
+            enqueued: self.counters.enqueued.load(Ordering::Relaxed),
+            delivered: self.counters.delivered.load(Ordering::Relaxed),
+            failed: self.counters.failed.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            batches: self.counters.batches.load(Ordering::Relaxed),
+            retries: self.counters.retries.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 将任务事件转换为导出记录（键为任务ID，保证同一任务的事件有序）
+pub fn event_to_record(event: &TaskEvent) -> (String, serde_json::Value) {
+    let mut value = serde_json::json!({
+        "event_type": event.event_type.to_string(),
+        "task_id": event.task_id.to_string(),
+        "occurred_at": event.occurred_at.to_rfc3339(),
+    });
+
+    if let Some(task) = &event.snapshot {
+        value["status"] = serde_json::json!(task.status.to_string());
+        value["work_directory"] = serde_json::json!(task.work_directory.as_str());
+        value["priority"] = serde_json::json!(task.priority.to_string());
+        value["worker_id"] = serde_json::json!(task.worker_id.as_ref().map(|w| w.as_str().to_string()));
+        value["created_at"] = serde_json::json!(task.created_at.to_rfc3339());
+        value["started_at"] = serde_json::json!(task.started_at.map(|t| t.to_rfc3339()));
+        value["completed_at"] = serde_json::json!(task.completed_at.map(|t| t.to_rfc3339()));
+        value["retry_count"] = serde_json::json!(task.retry_count);
+        value["error_message"] = serde_json::json!(task.error_message);
+        value["metadata"] = serde_json::json!(task.metadata);
+    }
+
+    (event.task_id.to_string(), value)
+}
+
+async fn run_export_loop(
+    publisher: Arc<dyn EventBatchPublisher>,
+    settings: ExporterSettings,
+    mut receiver: mpsc::Receiver<ExporterCommand>,
+    counters: Arc<ExporterCounters>,
+) {
+    let mut batch = Vec::with_capacity(settings.batch_size);
+    let mut interval = tokio::time::interval(settings.flush_interval);
+
+    loop {
+        tokio::select! {
+            command = receiver.recv() => {
+                match command {
+                    Some(ExporterCommand::Export(event)) => {
+                        batch.push(event_to_record(&event));
+                        if batch.len() >= settings.batch_size {
+                            flush(&publisher, &settings, &mut batch, &counters).await;
+                        }
+                    }
+                    Some(ExporterCommand::Flush(done)) => {
+                        flush(&publisher, &settings, &mut batch, &counters).await;
+                        let _ = done.send(());
+                    }
+                    None => {
+                        flush(&publisher, &settings, &mut batch, &counters).await;
+                        break;
+                    }
+                }
+            }
+            _ = interval.tick() => {
+                flush(&publisher, &settings, &mut batch, &counters).await;
+            }
+        }
+    }
+}
+
+async fn flush(
+    publisher: &Arc<dyn EventBatchPublisher>,
+    settings: &ExporterSettings,
+    batch: &mut Vec<(String, serde_json::Value)>,
+    counters: &ExporterCounters,
+) {
+    if batch.is_empty() {
+        return;
+    }
+
+    let size = batch.len() as u64;
+    let mut attempt = 0;
+    loop {
+        match publisher.publish_batch(&settings.topic, batch).await {
+            Ok(()) => {
+                counters.add(&counters.delivered, "delivered", size);
+                counters.batches.fetch_add(1, Ordering::Relaxed);
+                break;
+            }
+            Err(e) if attempt < settings.max_retries => {
+                attempt += 1;
+                counters.add(&counters.retries, "retried", 1);
+                tracing::warn!("Failed to export task events (attempt {}): {}", attempt, e);
+                tokio::time::sleep(settings.retry_backoff * 2u32.pow(attempt - 1)).await;
+            }
+            Err(e) => {
+                counters.add(&counters.failed, "failed", size);
+                tracing::error!("Dropping {} task events after {} retries: {}", size, attempt, e);
+                break;
+            }
+        }
+    }
+
+    batch.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Task, TaskEventType, TaskPriority, WorkDirectory, Prompt};
+    use crate::errors::{AppError, AppResult};
+    use std::sync::Mutex;
+
+    // 前若干次调用失败的发布器
+    struct FlakyPublisher {
+        failures: AtomicU64,
+        published: Mutex<Vec<(String, serde_json::Value)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl EventBatchPublisher for FlakyPublisher {
+        async fn publish_batch(&self, _topic: &str, records: &[(String, serde_json::Value)]) -> AppResult<()> {
+            if self.failures.load(Ordering::SeqCst) > 0 {
+                self.failures.fetch_sub(1, Ordering::SeqCst);
+                return Err(AppError::ServiceUnavailable("broker down".to_string()));
+            }
+            self.published.lock().unwrap().extend_from_slice(records);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batches_and_retries() {
+        let publisher = Arc::new(FlakyPublisher {
+            failures: AtomicU64::new(1),
+            published: Mutex::new(Vec::new()),
+        });
+        let exporter = TaskEventExporter::start(publisher.clone(), ExporterSettings {
+            topic: "task-events".to_string(),
+            batch_size: 2,
+            flush_interval: Duration::from_secs(60),
+            max_retries: 3,
+            retry_backoff: Duration::from_millis(1),
+            buffer_size: 16,
+        }, None);
+
+        let task = Task::new(
+            WorkDirectory::new("/test".to_string()).unwrap(),
+            Prompt::new("Test task".to_string()).unwrap(),
+            TaskPriority::High,
+            vec![],
+        );
+        exporter.export(TaskEvent::new(TaskEventType::Created, &task));
+        exporter.export(TaskEvent::new(TaskEventType::Cancelled, &task));
+
+        for _ in 0..100 {
+            if exporter.stats().delivered == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let stats = exporter.stats();
+        assert_eq!(stats.delivered, 2);
+        assert_eq!(stats.batches, 1);
+        assert_eq!(stats.retries, 1);
+
+        let published = publisher.published.lock().unwrap();
+        assert_eq!(published[0].1["event_type"], "created");
+        assert_eq!(published[1].1["priority"], "high");
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flushes_partial_batch() {
+        let publisher = Arc::new(FlakyPublisher {
+            failures: AtomicU64::new(0),
+            published: Mutex::new(Vec::new()),
+        });
+        let exporter = TaskEventExporter::start(publisher.clone(), ExporterSettings {
+            topic: "task-events".to_string(),
+            batch_size: 100,
+            flush_interval: Duration::from_secs(3600),
+            max_retries: 0,
+            retry_backoff: Duration::from_millis(1),
+            buffer_size: 16,
+        }, None);
+
+        let task = Task::new(
+            WorkDirectory::new("/test".to_string()).unwrap(),
+            Prompt::new("Test task".to_string()).unwrap(),
+            TaskPriority::High,
+            vec![],
+        );
+        exporter.export(TaskEvent::new(TaskEventType::Created, &task));
+        exporter.shutdown().await;
+
+        assert_eq!(exporter.stats().delivered, 1);
+        assert_eq!(publisher.published.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,100 @@
+//! 失败分类
+//!
+//! 任务失败时将错误信息归入固定的失败类别，便于统计和过滤。
+//! 执行器在结果中给出的类别优先；否则依次尝试自定义规则和内置规则，都不匹配时为 `unknown`。
+
+use std::str::FromStr;
+use regex::Regex;
+
+use crate::config::FailureClassificationConfig;
+use crate::domain::FailureCategory;
+use crate::errors::{AppError, AppResult};
+
+/// 内置规则（类别, 正则），按顺序匹配
+const BUILTIN_RULES: &[(FailureCategory, &str)] = &[
+    (FailureCategory::Timeout, r"(?i)\b(timed?\s*out|timeout|deadline exceeded)\b"),
+    (FailureCategory::Oom, r"(?i)(out of memory|\boom\b|oom-?kill|memory limit exceeded|cannot allocate memory)"),
+    (
+        FailureCategory::Upstream,
+        r"(?i)(\b(502|503|504|429)\b|bad gateway|service unavailable|rate limit|too many requests|connection (refused|reset))",
+    ),
+    (FailureCategory::Validation, r"(?i)(validation|invalid (input|argument|request)|schema)"),
+    (FailureCategory::ToolError, r"(?i)(exit (code|status)|command not found|tool (call )?(failed|error)|non-zero)"),
+];
+
+/// 失败分类器
+pub struct FailureClassifier {
+    rules: Vec<(FailureCategory, Regex)>,
+}
+
+impl Default for FailureClassifier {
+    fn default() -> Self {
+        Self::from_config(&FailureClassificationConfig::default()).expect("built-in failure rules are valid")
+    }
+}
+
+impl FailureClassifier {
+    /// 根据配置构建分类器（自定义规则 + 内置规则）
+    pub fn from_config(config: &FailureClassificationConfig) -> AppResult<Self> {
+        let mut rules = Vec::with_capacity(config.rules.len() + BUILTIN_RULES.len());
+        for rule in &config.rules {
+            let category = FailureCategory::from_str(&rule.category).map_err(|_| {
+                AppError::Internal(format!("Unknown failure category '{}'", rule.category))
+            })?;
+            let regex = Regex::new(&rule.pattern).map_err(|e| {
+                AppError::Internal(format!("Invalid failure rule for '{}': {}", rule.category, e))
+            })?;
+            rules.push((category, regex));
+        }
+        for (category, pattern) in BUILTIN_RULES {
+            rules.push((*category, Regex::new(pattern).expect("built-in failure rule is valid")));
+        }
+        Ok(Self { rules })
+    }
+
+    /// 对失败进行分类，`reported` 为执行器给出的类别
+    pub fn classify(&self, error: &str, reported: Option<FailureCategory>) -> FailureCategory {
+        reported.unwrap_or_else(|| {
+            self.rules
+                .iter()
+                .find(|(_, regex)| regex.is_match(error))
+                .map(|(category, _)| *category)
+                .unwrap_or(FailureCategory::Unknown)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FailureRuleConfig;
+
+    #[test]
+    fn test_builtin_rules() {
+        let classifier = FailureClassifier::default();
+        assert_eq!(classifier.classify("Task timed out after 3600s", None), FailureCategory::Timeout);
+        assert_eq!(classifier.classify("process was OOM-killed", None), FailureCategory::Oom);
+        assert_eq!(classifier.classify("HTTP 503 Service Unavailable", None), FailureCategory::Upstream);
+        assert_eq!(classifier.classify("cargo exited with exit code 101", None), FailureCategory::ToolError);
+        assert_eq!(classifier.classify("something went wrong", None), FailureCategory::Unknown);
+        assert_eq!(
+            classifier.classify("something went wrong", Some(FailureCategory::Validation)),
+            FailureCategory::Validation
+        );
+    }
+
+    #[test]
+    fn test_custom_rules_take_precedence() {
+        let config = FailureClassificationConfig {
+            rules: vec![FailureRuleConfig { category: "upstream".to_string(), pattern: "(?i)model timeout".to_string() }],
+        };
+        let classifier = FailureClassifier::from_config(&config).unwrap();
+        assert_eq!(classifier.classify("Model timeout from provider", None), FailureCategory::Upstream);
+        assert_eq!(classifier.classify("timeout", None), FailureCategory::Timeout);
+
+        let invalid = FailureClassificationConfig {
+            rules: vec![FailureRuleConfig { category: "disk".to_string(), pattern: "ENOSPC".to_string() }],
+        };
+        assert!(FailureClassifier::from_config(&invalid).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::domain::TaskHistory;
+use crate::errors::{AppError, AppResult};
+use crate::infrastructure::TaskRepository;
+
+/// 写入失败时缓冲区最多保留的批次数，超出部分丢弃
+const MAX_PENDING_BATCHES: usize = 10;
+
+/// 历史写入统计
+#[derive(Debug, Default)]
+struct WriterCounters {
+    recorded: AtomicU64,
+    written: AtomicU64,
+    batches: AtomicU64,
+    failed_flushes: AtomicU64,
+    dropped: AtomicU64,
+}
+
+/// 历史写入统计快照
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HistoryWriterStats {
+    pub recorded: u64,
+    pub written: u64,
+    pub batches: u64,
+    pub failed_flushes: u64,
+    pub dropped: u64,
+    /// 相比逐条写入节省的数据库往返次数
+    pub round_trips_saved: u64,
+}
+
+/// 历史写入配置
+#[derive(Debug, Clone)]
+pub struct HistoryWriterSettings {
+    pub batch_size: usize,
+    pub flush_interval: Duration,
+    pub buffer_size: usize,
+}
+
+enum WriterCommand {
+    Record(TaskHistory),
+    Flush(oneshot::Sender<()>),
+}
+
+/// 缓冲式任务历史写入器
+///
+/// 状态转换产生的历史记录先进入内存缓冲区，后台循环按批次大小或刷新间隔批量写入仓库。
+/// 缓冲区满时 `record` 等待而不是丢弃；写入失败的批次保留到下次刷新重试。
+/// 关闭前调用 [`HistoryWriter::shutdown`] 写入剩余记录。
+pub struct HistoryWriter {
+    sender: mpsc::Sender<WriterCommand>,
+    counters: Arc<WriterCounters>,
+}
+
+impl HistoryWriter {
+    /// 创建写入器并启动后台写入循环
+    pub fn start(repository: Arc<dyn TaskRepository>, settings: HistoryWriterSettings) -> Self {
+        let (sender, receiver) = mpsc::channel(settings.buffer_size.max(1));
+        let counters = Arc::new(WriterCounters::default());
+
+        tokio::spawn(run_write_loop(repository, settings, receiver, counters.clone()));
+
+        Self { sender, counters }
+    }
+
+    /// 缓冲一条历史记录
+    pub async fn record(&self, history: TaskHistory) -> AppResult<()> {
+        self.sender
+            .send(WriterCommand::Record(history))
+            .await
+            .map_err(|_| AppError::ServiceUnavailable("Task history writer stopped".to_string()))?;
+        self.counters.recorded.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// 立即写入缓冲区中的全部记录，写入循环处理完成后返回
+    pub async fn flush(&self) -> AppResult<()> {
+        let (done, wait) = oneshot::channel();
+        self.sender
+            .send(WriterCommand::Flush(done))
+            .await
+            .map_err(|_| AppError::ServiceUnavailable("Task history writer stopped".to_string()))?;
+        wait.await
+            .map_err(|_| AppError::ServiceUnavailable("Task history writer stopped".to_string()))
+    }
+
+    /// 关闭前写入剩余记录
+    pub async fn shutdown(&self) {
+        if let Err(e) = self.flush().await {
+            tracing::error!("Failed to flush task history on shutdown: {}", e);
+        }
+        let stats = self.stats();
+        let pending = stats.recorded.saturating_sub(stats.written + stats.dropped);
+        if pending > 0 {
+            tracing::error!("{} task history records were not written before shutdown", pending);
+        }
+    }
+
+    /// 获取统计快照
+    pub fn stats(&self) -> HistoryWriterStats {
+        let written = self.counters.written.load(Ordering::Relaxed);
+        let batches = self.counters.batches.load(Ordering::Relaxed);
+        HistoryWriterStats {
+            recorded: self.counters.recorded.load(Ordering::Relaxed),
+            written,
+            batches,
+            failed_flushes: self.counters.failed_flushes.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            round_trips_saved: written.saturating_sub(batches),
+        }
+    }
+}
+
+async fn run_write_loop(
+    repository: Arc<dyn TaskRepository>,
+    settings: HistoryWriterSettings,
+    mut receiver: mpsc::Receiver<WriterCommand>,
+    counters: Arc<WriterCounters>,
+) {
+    let batch_size = settings.batch_size.max(1);
+    let mut buffer = Vec::with_capacity(batch_size);
+    let mut interval = tokio::time::interval(settings.flush_interval);
+
+    loop {
+        tokio::select! {
+            command = receiver.recv() => {
+                match command {
+                    Some(WriterCommand::Record(history)) => {
+                        buffer.push(history);
+                        if buffer.len() >= batch_size {
+                            flush(&repository, batch_size, &mut buffer, &counters).await;
+                        }
+                    }
+                    Some(WriterCommand::Flush(done)) => {
+                        flush(&repository, batch_size, &mut buffer, &counters).await;
+                        let _ = done.send(());
+                    }
+                    None => {
+                        flush(&repository, batch_size, &mut buffer, &counters).await;
+                        break;
+                    }
+                }
+            }
+            _ = interval.tick() => {
+                flush(&repository, batch_size, &mut buffer, &counters).await;
+            }
+        }
+    }
+}
+
+async fn flush(
+    repository: &Arc<dyn TaskRepository>,
+    batch_size: usize,
+    buffer: &mut Vec<TaskHistory>,
+    counters: &WriterCounters,
+) {
+    while !buffer.is_empty() {
+        let size = buffer.len().min(batch_size);
+        match repository.create_task_histories(&buffer[..size]).await {
+            Ok(_) => {
+                counters.written.fetch_add(size as u64, Ordering::Relaxed);
+                counters.batches.fetch_add(1, Ordering::Relaxed);
+                buffer.drain(..size);
+            }
+            Err(e) => {
+                counters.failed_flushes.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!("Failed to write {} task history records, will retry: {}", size, e);
+                // 持续失败时丢弃最旧的记录，避免缓冲区无限增长
+                let limit = batch_size * MAX_PENDING_BATCHES;
+                if buffer.len() > limit {
+                    let dropped = buffer.len() - limit;
+                    buffer.drain(..dropped);
+                    counters.dropped.fetch_add(dropped as u64, Ordering::Relaxed);
+                    tracing::error!("Dropped {} task history records after repeated write failures", dropped);
+                }
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Prompt, Task, TaskId, TaskPriority, TaskStatus, WorkDirectory};
+    use crate::infrastructure::SqliteTaskRepository;
+
+    #[tokio::test]
+    async fn test_batches_and_flushes() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repository: Arc<dyn TaskRepository> = Arc::new(SqliteTaskRepository::with_pool(pool).await.unwrap());
+        let task = Task::new(
+            WorkDirectory::new("/test".to_string()).unwrap(),
+            Prompt::new("Test task".to_string()).unwrap(),
+            TaskPriority::Medium,
+            vec![],
+        );
+        let task_id: TaskId = repository.create_task(&task).await.unwrap();
+
+        let writer = HistoryWriter::start(repository.clone(), HistoryWriterSettings {
+            batch_size: 4,
+            flush_interval: Duration::from_secs(60),
+            buffer_size: 16,
+        });
+        for _ in 0..5 {
+            writer.record(TaskHistory::new(task_id, TaskStatus::Waiting, None)).await.unwrap();
+        }
+
+        // 第五条仍在缓冲区，刷新后才可见
+        writer.flush().await.unwrap();
+        assert_eq!(repository.get_task_history(&task_id).await.unwrap().len(), 5);
+
+        let stats = writer.stats();
+        assert_eq!((stats.recorded, stats.written, stats.batches), (5, 5, 2));
+        assert_eq!(stats.round_trips_saved, 3);
+    }
+}
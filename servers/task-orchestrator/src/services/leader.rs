@@ -0,0 +1,203 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::infrastructure::LockManager;
+use crate::errors::AppResult;
+use crate::utils::clock::{system_clock, Clock, Deadline};
+
+/// 调度器领导者锁资源名
+pub const LEADER_LOCK_RESOURCE: &str = "cluster:scheduler-leader";
+
+/// 集群状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterStatus {
+    pub node_id: String,
+    pub is_leader: bool,
+    pub leader_id: Option<String>,
+}
+
+/// 领导者选举器
+///
+/// 基于锁管理器的租约实现：持有领导者锁的节点负责运行后台调度任务，
+/// 租约过期后其他节点会自动接管。租约是否仍然有效按单调时钟判断：
+/// 超过租约时长未能续约时立即停止以领导者身份运行，不受系统时间跳变影响。
+pub struct LeaderElector {
+    lock_manager: Arc<dyn LockManager>,
+    node_id: String,
+    lease_ttl: u64,
+    renew_interval: u64,
+    is_leader: AtomicBool,
+    lease: std::sync::Mutex<Option<Deadline>>,
+    leader_id: RwLock<Option<String>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl LeaderElector {
+    /// 创建新的领导者选举器
+    pub fn new(
+        lock_manager: Arc<dyn LockManager>,
+        node_id: String,
+        lease_ttl: u64,
+        renew_interval: u64,
+    ) -> Self {
+        Self {
+            lock_manager,
+            node_id,
+            lease_ttl,
+            renew_interval,
+            is_leader: AtomicBool::new(false),
+            lease: std::sync::Mutex::new(None),
+            leader_id: RwLock::new(None),
+            clock: system_clock(),
+        }
+    }
+
+    /// 设置时钟
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 当前节点是否为领导者（持有租约且租约未到期）
+    pub fn is_leader(&self) -> bool {
+        self.is_leader.load(Ordering::SeqCst)
+            && self
+                .lease
+                .lock()
+                .unwrap()
+                .map(|lease| !lease.is_expired(self.clock.as_ref()))
+                .unwrap_or(false)
+    }
+
+    /// 最近一次观察到的领导者ID
+    pub async fn leader_id(&self) -> Option<String> {
+        self.leader_id.read().await.clone()
+    }
+
+    /// 获取集群状态
+    pub async fn status(&self) -> ClusterStatus {
+        ClusterStatus {
+            node_id: self.node_id.clone(),
+            is_leader: self.is_leader(),
+            leader_id: self.leader_id().await,
+        }
+    }
+
+    /// 执行一轮选举：领导者续约，非领导者尝试获取领导权
+    pub async fn elect(&self) -> AppResult<bool> {
+        // 截止时间从发起请求前开始计算，租约在存储中的实际到期时间只会更晚
+        let lease = Deadline::after(self.clock.as_ref(), std::time::Duration::from_secs(self.lease_ttl));
+        let is_leader = if self.is_leader.load(Ordering::SeqCst) {
+            self.lock_manager
+                .renew(LEADER_LOCK_RESOURCE, &self.node_id, self.lease_ttl)
+                .await?
+        } else {
+            self.lock_manager
+                .try_acquire(LEADER_LOCK_RESOURCE, &self.node_id, self.lease_ttl)
+                .await?
+        };
+
+        *self.lease.lock().unwrap() = is_leader.then_some(lease);
+        let was_leader = self.is_leader.swap(is_leader, Ordering::SeqCst);
+        if is_leader && !was_leader {
+            tracing::info!(node_id = %self.node_id, "Acquired scheduler leadership");
+        } else if !is_leader && was_leader {
+            tracing::warn!(node_id = %self.node_id, "Lost scheduler leadership");
+        }
+
+        let leader_id = if is_leader {
+            Some(self.node_id.clone())
+        } else {
+            self.lock_manager.check_lock(LEADER_LOCK_RESOURCE).await?
+        };
+        *self.leader_id.write().await = leader_id;
+
+        Ok(is_leader)
+    }
+
+    /// 主动放弃领导权
+    pub async fn resign(&self) -> AppResult<()> {
+        if self.is_leader.swap(false, Ordering::SeqCst) {
+            *self.lease.lock().unwrap() = None;
+            self.lock_manager.release(LEADER_LOCK_RESOURCE, &self.node_id).await?;
+            *self.leader_id.write().await = None;
+            tracing::info!(node_id = %self.node_id, "Resigned scheduler leadership");
+        }
+        Ok(())
+    }
+
+    /// 启动选举循环
+    pub async fn start(self: &Arc<Self>) -> AppResult<()> {
+        // 启动时先执行一次选举，避免调度器在首个周期内空转
+        if let Err(e) = self.elect().await {
+            tracing::error!("Leader election failed: {}", e);
+        }
+
+        let elector = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(elector.renew_interval));
+            loop {
+                interval.tick().await;
+                if let Err(e) = elector.elect().await {
+                    // 无法确认租约时视为失去领导权，防止出现双主
+                    elector.is_leader.store(false, Ordering::SeqCst);
+                    tracing::error!("Leader election failed: {}", e);
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use task_store::MemoryLockManager;
+
+    #[tokio::test]
+    async fn test_single_leader_and_failover() {
+        let lock_manager: Arc<dyn LockManager> = Arc::new(MemoryLockManager::new());
+        let node_a = LeaderElector::new(lock_manager.clone(), "node-a".to_string(), 30, 10);
+        let node_b = LeaderElector::new(lock_manager.clone(), "node-b".to_string(), 30, 10);
+
+        assert!(node_a.elect().await.unwrap());
+        assert!(!node_b.elect().await.unwrap());
+        assert_eq!(node_b.leader_id().await, Some("node-a".to_string()));
+
+        // 领导者续约
+        assert!(node_a.elect().await.unwrap());
+
+        // 领导者退出后，其他节点接管
+        node_a.resign().await.unwrap();
+        assert!(!node_a.is_leader());
+        assert!(node_b.elect().await.unwrap());
+
+        let status = node_b.status().await;
+        assert!(status.is_leader);
+        assert_eq!(status.leader_id, Some("node-b".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_lease_expires_on_monotonic_clock() {
+        use crate::utils::clock::ManualClock;
+
+        let clock = Arc::new(ManualClock::new());
+        let lock_manager: Arc<dyn LockManager> = Arc::new(MemoryLockManager::new());
+        let node = LeaderElector::new(lock_manager, "node-a".to_string(), 30, 10).with_clock(clock.clone());
+        assert!(node.elect().await.unwrap());
+
+        // 系统时间跳变不影响租约
+        clock.jump_wall_clock(chrono::Duration::hours(-1));
+        clock.advance(std::time::Duration::from_secs(29));
+        assert!(node.is_leader());
+
+        // 续约停滞超过租约时长后不再以领导者身份运行，续约成功后恢复
+        clock.advance(std::time::Duration::from_secs(1));
+        assert!(!node.is_leader());
+        assert!(node.elect().await.unwrap());
+        assert!(node.is_leader());
+    }
+}
@@ -0,0 +1,271 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Timelike, Utc};
+use config::ConfigError;
+
+use crate::config::{MaintenanceConfig, MaintenanceWindowConfig};
+use crate::domain::Task;
+use crate::errors::{AppError, AppResult};
+
+/// 星期缩写，下标与 `Weekday::num_days_from_monday` 一致
+const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// 维护窗口
+///
+/// 时间表格式为 `<星期> <开始>-<结束> [UTC]`，星期可以是 `*`、单日（`Sat`）、
+/// 范围（`Mon-Fri`、`Fri-Mon`）或逗号分隔的组合。结束时间早于开始时间表示跨越午夜，
+/// 此时窗口归属于开始那一天。所有时间均为UTC。
+#[derive(Debug, Clone)]
+pub struct MaintenanceWindow {
+    name: String,
+    days: [bool; 7],
+    start: u32,
+    end: u32,
+    work_directories: Vec<PathBuf>,
+    tags: Vec<String>,
+}
+
+impl MaintenanceWindow {
+    /// 根据配置构建维护窗口
+    pub fn from_config(config: &MaintenanceWindowConfig) -> AppResult<Self> {
+        let invalid = |reason: &str| {
+            AppError::Configuration(ConfigError::Message(format!(
+                "Invalid maintenance window '{}' schedule '{}': {}",
+                config.name, config.schedule, reason
+            )))
+        };
+
+        let mut parts = config.schedule.split_whitespace();
+        let (Some(days), Some(times)) = (parts.next(), parts.next()) else {
+            return Err(invalid("expected '<days> <HH:MM>-<HH:MM>'"));
+        };
+        match parts.next() {
+            None => {}
+            Some(zone) if zone.eq_ignore_ascii_case("utc") && parts.next().is_none() => {}
+            Some(_) => return Err(invalid("only UTC schedules are supported")),
+        }
+
+        let days = parse_days(days).ok_or_else(|| invalid("unknown weekday"))?;
+        // 允许使用短横线或en dash分隔时间
+        let (start, end) = times
+            .split_once('-')
+            .or_else(|| times.split_once('\u{2013}'))
+            .ok_or_else(|| invalid("expected '<HH:MM>-<HH:MM>'"))?;
+        let start = parse_minute(start).ok_or_else(|| invalid("invalid start time"))?;
+        let end = parse_minute(end).ok_or_else(|| invalid("invalid end time"))?;
+        if start == end {
+            return Err(invalid("window must not be empty"));
+        }
+
+        Ok(Self {
+            name: config.name.clone(),
+            days,
+            start,
+            end,
+            work_directories: config.work_directories.iter().map(PathBuf::from).collect(),
+            tags: config.tags.clone(),
+        })
+    }
+
+    /// 窗口在 `now` 时刻生效时返回结束时间
+    pub fn active_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let today = now.weekday().num_days_from_monday() as usize;
+        let yesterday = (today + 6) % 7;
+        let minute = now.hour() * 60 + now.minute();
+        let end_on = |days: i64| {
+            let date = now.date_naive() + Duration::days(days);
+            let end = NaiveTime::from_hms_opt(self.end / 60, self.end % 60, 0)?;
+            Some(date.and_time(end).and_utc())
+        };
+
+        if self.start < self.end {
+            (self.days[today] && (self.start..self.end).contains(&minute)).then(|| end_on(0)).flatten()
+        } else if self.days[today] && minute >= self.start {
+            end_on(1)
+        } else if self.days[yesterday] && minute < self.end {
+            end_on(0)
+        } else {
+            None
+        }
+    }
+
+    /// 是否只针对部分标签
+    pub fn is_tag_scoped(&self) -> bool {
+        !self.tags.is_empty()
+    }
+
+    /// 工作目录是否在窗口范围内（按路径组件匹配前缀）
+    pub fn covers_directory(&self, work_directory: &str) -> bool {
+        self.work_directories.is_empty()
+            || self.work_directories.iter().any(|prefix| Path::new(work_directory).starts_with(prefix))
+    }
+
+    /// 任务是否在窗口范围内
+    pub fn covers_task(&self, task: &Task) -> bool {
+        self.covers_directory(task.work_directory.as_str())
+            && (self.tags.is_empty() || task.tags.iter().any(|tag| self.tags.iter().any(|t| t == tag.as_str())))
+    }
+}
+
+/// 维护计划
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceSchedule {
+    windows: Vec<MaintenanceWindow>,
+}
+
+impl MaintenanceSchedule {
+    /// 根据配置构建维护计划
+    pub fn from_config(config: &MaintenanceConfig) -> AppResult<Self> {
+        let windows = config
+            .windows
+            .iter()
+            .map(MaintenanceWindow::from_config)
+            .collect::<AppResult<Vec<_>>>()?;
+        Ok(Self { windows })
+    }
+
+    /// 是否未配置任何窗口
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// 获取任务前检查：不限标签且覆盖该工作目录的窗口生效时拒绝
+    pub fn check_directory(&self, work_directory: &str, now: DateTime<Utc>) -> AppResult<()> {
+        self.check(now, |window| !window.is_tag_scoped() && window.covers_directory(work_directory))
+    }
+
+    /// 开始执行任务前检查：覆盖该任务的窗口生效时拒绝
+    pub fn check_task(&self, task: &Task, now: DateTime<Utc>) -> AppResult<()> {
+        self.check(now, |window| window.covers_task(task))
+    }
+
+    fn check(&self, now: DateTime<Utc>, applies: impl Fn(&MaintenanceWindow) -> bool) -> AppResult<()> {
+        // 多个窗口同时生效时报告最晚结束的窗口
+        let active = self
+            .windows
+            .iter()
+            .filter(|window| applies(window))
+            .filter_map(|window| window.active_until(now).map(|until| (window, until)))
+            .max_by_key(|(_, until)| *until);
+
+        match active {
+            Some((window, until)) => Err(AppError::Maintenance {
+                window: window.name.clone(),
+                resumes_at: until,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 解析星期表达式
+fn parse_days(expression: &str) -> Option<[bool; 7]> {
+    let mut days = [false; 7];
+    if expression == "*" {
+        return Some([true; 7]);
+    }
+    for item in expression.split(',') {
+        match item.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (weekday(from)?, weekday(to)?);
+                let mut day = from;
+                loop {
+                    days[day] = true;
+                    if day == to {
+                        break;
+                    }
+                    day = (day + 1) % 7;
+                }
+            }
+            None => days[weekday(item)?] = true,
+        }
+    }
+    Some(days)
+}
+
+fn weekday(name: &str) -> Option<usize> {
+    let name = name.trim().to_lowercase();
+    WEEKDAYS.iter().position(|day| name.starts_with(day) && name.len() >= 3)
+}
+
+/// 解析 `HH:MM`，返回当天的分钟数；`24:00` 表示午夜
+fn parse_minute(time: &str) -> Option<u32> {
+    let (hour, minute) = time.trim().split_once(':')?;
+    let (hour, minute): (u32, u32) = (hour.parse().ok()?, minute.parse().ok()?);
+    match (hour, minute) {
+        (24, 0) => Some(0),
+        (0..=23, 0..=59) => Some(hour * 60 + minute),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Prompt, TaskPriority, TaskTag, WorkDirectory};
+    use chrono::TimeZone;
+
+    fn window(schedule: &str, work_directories: &[&str], tags: &[&str]) -> MaintenanceWindow {
+        MaintenanceWindow::from_config(&MaintenanceWindowConfig {
+            name: "upgrade".to_string(),
+            schedule: schedule.to_string(),
+            work_directories: work_directories.iter().map(|s| s.to_string()).collect(),
+            tags: tags.iter().map(|s| s.to_string()).collect(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_window_schedule() {
+        // 2025-01-04 是星期六
+        let saturday = |h, m| Utc.with_ymd_and_hms(2025, 1, 4, h, m, 0).unwrap();
+        let w = window("Sat 02:00\u{2013}04:00 UTC", &[], &[]);
+        assert_eq!(w.active_until(saturday(2, 0)), Some(saturday(4, 0)));
+        assert_eq!(w.active_until(saturday(4, 0)), None);
+        assert_eq!(w.active_until(saturday(1, 59)), None);
+
+        // 跨越午夜的窗口归属于开始那一天
+        let w = window("Fri-Sat 23:00-01:00", &[], &[]);
+        assert_eq!(w.active_until(saturday(0, 30)), Some(saturday(1, 0)));
+        assert_eq!(w.active_until(saturday(23, 30)), Some(Utc.with_ymd_and_hms(2025, 1, 5, 1, 0, 0).unwrap()));
+        assert_eq!(w.active_until(Utc.with_ymd_and_hms(2025, 1, 5, 23, 30, 0).unwrap()), None);
+
+        for schedule in ["Sat", "Sat 02:00-02:00", "Funday 02:00-04:00", "Sat 02:00-04:00 CET"] {
+            let config = MaintenanceWindowConfig { schedule: schedule.to_string(), ..Default::default() };
+            assert!(MaintenanceWindow::from_config(&config).is_err(), "{}", schedule);
+        }
+    }
+
+    #[test]
+    fn test_schedule_scoping() {
+        let now = Utc.with_ymd_and_hms(2025, 1, 4, 3, 0, 0).unwrap();
+        let schedule = MaintenanceSchedule {
+            windows: vec![
+                window("* 02:00-04:00", &["/srv/db"], &[]),
+                window("Sat 00:00-06:00", &[], &["migration"]),
+            ],
+        };
+
+        assert!(matches!(
+            schedule.check_directory("/srv/db/main", now),
+            Err(AppError::Maintenance { .. })
+        ));
+        assert!(schedule.check_directory("/srv/dbx", now).is_ok());
+
+        let task = |tags: &[&str]| {
+            Task::new(
+                WorkDirectory::new("/srv/web".to_string()).unwrap(),
+                Prompt::new("deploy".to_string()).unwrap(),
+                TaskPriority::Medium,
+                tags.iter().map(|t| TaskTag::new(t.to_string()).unwrap()).collect(),
+            )
+        };
+        assert!(schedule.check_task(&task(&[]), now).is_ok());
+        match schedule.check_task(&task(&["migration"]), now) {
+            Err(AppError::Maintenance { resumes_at, .. }) => {
+                assert_eq!(resumes_at, Utc.with_ymd_and_hms(2025, 1, 4, 6, 0, 0).unwrap())
+            }
+            other => panic!("expected maintenance error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! # 元数据模式
+//!
+//! 管理员可以为命名空间注册JSON Schema，创建任务和更新元数据时按模式校验任务元数据。
+//! 命名空间是工作目录前缀，按路径组件匹配（`/srv/team-a` 匹配 `/srv/team-a/app`，不匹配 `/srv/team-a2`），
+//! 多个命名空间匹配时使用最长的一个；没有匹配的命名空间时不校验。
+//!
+//! 严格模式下拒绝模式中未声明的顶层键（相当于模式未设置 `additionalProperties` 时按 `false` 处理）。
+//! 服务自己写入的元数据（[`SYSTEM_METADATA_KEYS`]）不参与校验。
+//!
+//! 模式持久化在任务库中，启动时加载；通过管理接口修改只会立即作用于当前实例，其他实例重启后生效。
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use jsonschema::JSONSchema;
+
+use crate::domain::{MetadataSchema, WorkDirectory};
+use crate::errors::{AppError, AppResult, ValidationError};
+
+use super::{POLICY_VIOLATION_KEY, SECRET_FINDINGS_KEY, TRANSITION_HASH_KEY};
+
+/// 服务写入的元数据键，不参与模式校验
+pub const SYSTEM_METADATA_KEYS: &[&str] = &[POLICY_VIOLATION_KEY, SECRET_FINDINGS_KEY, TRANSITION_HASH_KEY];
+
+/// 拒绝客户端写入服务保留的元数据键
+///
+/// 幂等摘要、密钥扫描结果和策略违规记录只能由服务写入，客户端在创建或修改任务时
+/// 设置（包括以 `null` 删除）这些键会被拒绝，避免覆盖或伪造。
+pub fn reject_system_keys<'a>(keys: impl IntoIterator<Item = &'a String>) -> AppResult<()> {
+    let mut reserved: Vec<&str> = keys
+        .into_iter()
+        .map(String::as_str)
+        .filter(|key| SYSTEM_METADATA_KEYS.contains(key))
+        .collect();
+    if reserved.is_empty() {
+        return Ok(());
+    }
+    reserved.sort_unstable();
+    Err(invalid(format!("Metadata keys are reserved: {}", reserved.join(", "))))
+}
+
+/// 单次校验最多返回的错误数
+const MAX_REPORTED_ERRORS: usize = 20;
+
+struct CompiledSchema {
+    schema: MetadataSchema,
+    validator: JSONSchema,
+}
+
+/// 元数据模式注册表
+#[derive(Default)]
+pub struct MetadataSchemaRegistry {
+    schemas: RwLock<BTreeMap<PathBuf, CompiledSchema>>,
+}
+
+impl MetadataSchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 校验命名空间和模式，返回可以保存的模式
+    ///
+    /// 命名空间按工作目录规则校验，模式必须是对象且能编译。
+    pub fn prepare(namespace: String, schema: serde_json::Value, strict: bool) -> AppResult<MetadataSchema> {
+        let namespace = WorkDirectory::new(namespace)?.as_str().trim_end_matches('/').to_string();
+        let namespace = if namespace.is_empty() { "/".to_string() } else { namespace };
+        let schema = MetadataSchema::new(namespace, schema, strict);
+        compile(&schema)?;
+        Ok(schema)
+    }
+
+    /// 注册或替换模式
+    pub fn insert(&self, schema: MetadataSchema) -> AppResult<()> {
+        let validator = compile(&schema)?;
+        let mut schemas = self.schemas.write().unwrap();
+        schemas.insert(PathBuf::from(&schema.namespace), CompiledSchema { schema, validator });
+        Ok(())
+    }
+
+    /// 删除模式，返回是否存在
+    pub fn remove(&self, namespace: &str) -> bool {
+        self.schemas.write().unwrap().remove(Path::new(namespace)).is_some()
+    }
+
+    /// 用持久化的模式替换当前全部模式
+    pub fn replace_all(&self, schemas: Vec<MetadataSchema>) -> AppResult<()> {
+        let compiled = schemas
+            .into_iter()
+            .map(|schema| {
+                let validator = compile(&schema)?;
+                Ok((PathBuf::from(&schema.namespace), CompiledSchema { schema, validator }))
+            })
+            .collect::<AppResult<BTreeMap<_, _>>>()?;
+        *self.schemas.write().unwrap() = compiled;
+        Ok(())
+    }
+
+    /// 已注册的模式，按命名空间排序
+    pub fn list(&self) -> Vec<MetadataSchema> {
+        self.schemas.read().unwrap().values().map(|c| c.schema.clone()).collect()
+    }
+
+    /// 按工作目录匹配的模式校验元数据
+    pub fn validate(&self, work_directory: &str, metadata: &HashMap<String, serde_json::Value>) -> AppResult<()> {
+        let schemas = self.schemas.read().unwrap();
+        let work_directory = Path::new(work_directory);
+        let Some(compiled) = schemas
+            .iter()
+            .filter(|(namespace, _)| work_directory.starts_with(namespace))
+            .max_by_key(|(namespace, _)| namespace.components().count())
+            .map(|(_, compiled)| compiled)
+        else {
+            return Ok(());
+        };
+
+        let instance = serde_json::Value::Object(
+            metadata
+                .iter()
+                .filter(|(key, _)| !SYSTEM_METADATA_KEYS.contains(&key.as_str()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        );
+        if let Err(errors) = compiled.validator.validate(&instance) {
+            let errors = errors
+                .take(MAX_REPORTED_ERRORS)
+                .map(|e| {
+                    let path = e.instance_path.to_string();
+                    if path.is_empty() { e.to_string() } else { format!("{}: {}", path, e) }
+                })
+                .collect();
+            return Err(AppError::MetadataSchemaViolation {
+                namespace: compiled.schema.namespace.clone(),
+                errors,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// 编译模式，严格模式下未设置 `additionalProperties` 时补上 `false`
+fn compile(schema: &MetadataSchema) -> AppResult<JSONSchema> {
+    let mut document = schema.schema.clone();
+    let Some(object) = document.as_object_mut() else {
+        return Err(invalid("Metadata schema must be a JSON object".to_string()));
+    };
+    if schema.strict {
+        object
+            .entry("additionalProperties")
+            .or_insert(serde_json::Value::Bool(false));
+    }
+    JSONSchema::compile(&document).map_err(|e| invalid(format!("Invalid metadata schema: {}", e)))
+}
+
+fn invalid(message: String) -> AppError {
+    AppError::Validation(ValidationError::invalid_validation(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn metadata(value: serde_json::Value) -> HashMap<String, serde_json::Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_validate_uses_longest_namespace() {
+        let registry = MetadataSchemaRegistry::new();
+        let team = json!({"type": "object", "required": ["owner"], "properties": {"owner": {"type": "string"}}});
+        registry.insert(MetadataSchemaRegistry::prepare("/srv/team-a/".to_string(), team, false).unwrap()).unwrap();
+        let app = json!({"type": "object", "properties": {"ticket": {"type": "integer"}}});
+        registry.insert(MetadataSchemaRegistry::prepare("/srv/team-a/app".to_string(), app, true).unwrap()).unwrap();
+        assert_eq!(registry.list()[0].namespace, "/srv/team-a");
+
+        assert!(registry.validate("/srv/team-a/lib", &metadata(json!({"owner": "alice", "extra": 1}))).is_ok());
+        let error = registry.validate("/srv/team-a/lib", &metadata(json!({"owner": 1}))).unwrap_err();
+        match error {
+            AppError::MetadataSchemaViolation { namespace, errors } => {
+                assert_eq!(namespace, "/srv/team-a");
+                assert!(errors[0].starts_with("/owner: "));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        // 最长前缀优先，严格模式拒绝未声明的键，系统键不参与校验
+        assert!(registry.validate("/srv/team-a/app/src", &metadata(json!({"ticket": 7}))).is_ok());
+        assert!(registry.validate("/srv/team-a/app", &metadata(json!({"ticket": 7, "owner": "bob"}))).is_err());
+        assert!(registry
+            .validate("/srv/team-a/app", &metadata(json!({"ticket": 7, SECRET_FINDINGS_KEY: []})))
+            .is_ok());
+
+        // 未匹配的命名空间不校验，前缀按路径组件匹配
+        assert!(registry.validate("/srv/team-a2", &metadata(json!({"owner": 1}))).is_ok());
+        assert!(registry.remove("/srv/team-a"));
+        assert!(registry.validate("/srv/team-a/lib", &metadata(json!({"owner": 1}))).is_ok());
+    }
+
+    #[test]
+    fn test_prepare_rejects_invalid_schema() {
+        let prepare = |namespace: &str, schema| MetadataSchemaRegistry::prepare(namespace.to_string(), schema, false);
+        assert!(prepare("/srv", json!(true)).unwrap_err().to_string().contains("must be a JSON object"));
+        assert!(prepare("/srv", json!({"type": "no-such-type"})).unwrap_err().to_string().contains("Invalid metadata schema"));
+        assert!(prepare("relative", json!({})).is_err());
+        assert_eq!(prepare("/", json!({})).unwrap().namespace, "/");
+
+        // 已设置 additionalProperties 时严格模式不覆盖
+        let schema = MetadataSchemaRegistry::prepare(
+            "/srv".to_string(),
+            json!({"additionalProperties": {"type": "string"}}),
+            true,
+        )
+        .unwrap();
+        let registry = MetadataSchemaRegistry::new();
+        registry.insert(schema).unwrap();
+        assert!(registry.validate("/srv", &metadata(json!({"any": "text"}))).is_ok());
+        assert!(registry.validate("/srv", &metadata(json!({"any": 1}))).is_err());
+    }
+}
@@ -1,15 +1,122 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use chrono::{DateTime, Utc};
 use validator::Validate;
 
 use crate::domain::{
-    Task, TaskId, TaskStatus, TaskHistory, TaskResult,
+    Task, TaskId, TaskStatus, CancelReason, TaskHistory, TaskComment, MetadataSchema, PipelineRun, PipelineRunStatus, TaskResult, TaskResultStatus, FailureCategory, TaskEvent, TaskEventType, TaskAttempt,
     WorkDirectory, Prompt, TaskTag, WorkerId, CreateTaskRequest, 
-    CompleteTaskRequest, AcquireTaskRequest,
+    CompleteTaskRequest, AcquireTaskRequest, UpdateTaskRequest, CloneTaskRequest,
 };
-use crate::infrastructure::{TaskRepository, LockManager};
+use crate::infrastructure::{TaskRepository, LockManager, MessageQueue, CachedTaskRepository, CacheStats, ManagedPool, PoolStats, ServiceClient, ServiceClients, ServiceClientStats};
+use crate::infrastructure::queue::subject_for_work_directory;
 use crate::errors::{AppError, AppResult};
 use crate::models::{TaskFilter, TaskStatistics};
+use crate::config::PolicyAction;
+use crate::utils::{MetricsCollector, Redactor};
+use crate::utils::clock::{system_clock, Clock, Deadline};
+
+pub mod leader;
+pub mod queue_consumer;
+pub mod event_exporter;
+pub mod artifact_store;
+pub mod path_policy;
+pub mod maintenance;
+pub mod queue_control;
+pub mod attempt_diff;
+pub mod secret_scanner;
+pub mod history_writer;
+pub mod routing;
+pub mod self_check;
+pub mod completion;
+pub mod metadata_schema;
+pub mod pipeline;
+pub mod pipeline_condition;
+pub mod approval;
+pub mod failure_classifier;
+pub mod retry_policy;
+pub mod sla;
+pub mod capacity;
+pub mod standby;
+
+pub use leader::{LeaderElector, ClusterStatus};
+pub use queue_consumer::QueueAckConsumer;
+pub use event_exporter::{TaskEventExporter, ExporterSettings, ExporterStats};
+pub use artifact_store::ArtifactStore;
+pub use path_policy::WorkDirectoryPolicy;
+pub use maintenance::{MaintenanceSchedule, MaintenanceWindow};
+pub use queue_control::{QueueControl, QueueControlStatus};
+pub use attempt_diff::AttemptDiff;
+pub use secret_scanner::{SecretScanner, SecretScanStats};
+pub use history_writer::{HistoryWriter, HistoryWriterSettings, HistoryWriterStats};
+pub use routing::{WorkerCapabilities, RoutingStats};
+pub use self_check::run_self_check;
+pub use completion::CompletionNotifier;
+pub use metadata_schema::MetadataSchemaRegistry;
+pub use pipeline::{PipelineRegistry, PipelineRunRequest, PipelineRunStart, PipelineSummary};
+pub use approval::ApprovalPolicy;
+pub use failure_classifier::FailureClassifier;
+pub use retry_policy::RetryPolicy;
+pub use sla::{SlaTracker, SlaReport, SlaAlertEvent};
+pub use capacity::CapacityForecast;
+pub use standby::{StandbyReplicator, StandbyStatus};
+
+/// 并发组锁的资源ID前缀，锁持有者为正在执行的任务ID
+pub const CONCURRENCY_GROUP_LOCK_PREFIX: &str = "concurrency-group:";
+
+/// 任务元数据中记录工作目录策略违规原因的键
+pub const POLICY_VIOLATION_KEY: &str = "policy_violation";
+
+/// 任务元数据中记录密钥扫描结果的键
+pub const SECRET_FINDINGS_KEY: &str = "secret_findings";
+
+/// 任务元数据中记录克隆来源任务ID的键
+pub const CLONED_FROM_KEY: &str = "cloned_from";
+
+/// 任务元数据中记录最近一次终态转换请求摘要的键
+pub const TRANSITION_HASH_KEY: &str = "last_transition_hash";
+
+/// 计算终态转换请求的摘要（动作 + 请求内容）
+fn transition_hash<T: serde::Serialize>(action: &str, payload: &T) -> String {
+    use sha2::{Digest, Sha256};
+
+    // 先转换为 Value，保证对象键有序，摘要与字段顺序无关
+    let payload = serde_json::to_value(payload).unwrap_or(serde_json::Value::Null);
+    let mut hasher = Sha256::new();
+    hasher.update(action.as_bytes());
+    hasher.update(b":");
+    hasher.update(payload.to_string().as_bytes());
+    format!("{}:{}", action, hex::encode(hasher.finalize()))
+}
+
+/// 任务创建模拟结果
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TaskSimulation {
+    /// 将要创建的任务，未保存
+    pub task: Task,
+    /// 不阻止创建但需要注意的问题
+    pub warnings: Vec<String>,
+    /// 当前能够获取该任务的活跃节点
+    pub matching_workers: Vec<String>,
+}
+
+/// 判断是否为已处理过的重复终态转换
+fn is_duplicate_transition(task: &Task, hash: &str, expected: &[TaskStatus]) -> bool {
+    expected.contains(&task.status)
+        && task.metadata.get(TRANSITION_HASH_KEY).and_then(|v| v.as_str()) == Some(hash)
+}
+
+/// 记录被抑制的重复转换到审计日志
+fn audit_duplicate_transition(task: &Task, action: &str) {
+    tracing::info!(
+        target: "audit",
+        task_id = %task.id,
+        action = action,
+        status = %task.status,
+        worker_id = task.worker_id.as_ref().map(|w| w.as_str()).unwrap_or("-"),
+        "Duplicate task transition suppressed"
+    );
+}
 
 /// 任务服务
 pub struct TaskService {
@@ -20,6 +127,35 @@ pub struct TaskService {
     cleanup_interval: u64,
     timeout_check_interval: u64,
     metrics_interval: u64,
+    message_queue: Option<Arc<dyn MessageQueue>>,
+    subject_prefix: String,
+    event_exporter: Option<Arc<TaskEventExporter>>,
+    path_policy: Option<Arc<WorkDirectoryPolicy>>,
+    maintenance: Option<Arc<MaintenanceSchedule>>,
+    queue_control: Arc<QueueControl>,
+    metadata_schemas: Arc<MetadataSchemaRegistry>,
+    secret_scanner: Option<Arc<SecretScanner>>,
+    redactor: Option<Arc<Redactor>>,
+    task_cache: Option<Arc<CachedTaskRepository>>,
+    history_writer: Option<Arc<HistoryWriter>>,
+    database_pool: Option<Arc<ManagedPool>>,
+    service_clients: Option<Arc<ServiceClients>>,
+    worker_capabilities: WorkerCapabilities,
+    completion: Arc<CompletionNotifier>,
+    max_wait_timeout: u64,
+    time_ordered_ids: bool,
+    clock: Arc<dyn Clock>,
+    execution_deadlines: std::sync::Mutex<HashMap<TaskId, Deadline>>,
+    metrics: Option<Arc<MetricsCollector>>,
+    pipelines: Arc<PipelineRegistry>,
+    approval: Arc<ApprovalPolicy>,
+    failure_classifier: Arc<FailureClassifier>,
+    retry_policy: Arc<RetryPolicy>,
+    sla: Arc<SlaTracker>,
+    sla_alert_target: Option<(String, String)>,
+    /// 冷存储设置：(结束后天数, 每批任务数)，设置后清理周期移入冷存储而不是删除
+    cold_storage: Option<(u32, u32)>,
+    artifact_store: Option<Arc<ArtifactStore>>,
 }
 
 impl TaskService {
@@ -38,11 +174,580 @@ impl TaskService {
             cleanup_interval: 300, // 5分钟
             timeout_check_interval: 60, // 1分钟
             metrics_interval: 30, // 30秒
+            message_queue: None,
+            subject_prefix: "tasks".to_string(),
+            event_exporter: None,
+            path_policy: None,
+            maintenance: None,
+            queue_control: Arc::new(QueueControl::new()),
+            metadata_schemas: Arc::new(MetadataSchemaRegistry::new()),
+            secret_scanner: None,
+            redactor: None,
+            task_cache: None,
+            history_writer: None,
+            database_pool: None,
+            service_clients: None,
+            worker_capabilities: WorkerCapabilities::default(),
+            completion: Arc::new(CompletionNotifier::new()),
+            max_wait_timeout: 300, // 5分钟
+            time_ordered_ids: false,
+            clock: system_clock(),
+            execution_deadlines: std::sync::Mutex::new(HashMap::new()),
+            metrics: None,
+            pipelines: Arc::new(PipelineRegistry::default()),
+            approval: Arc::new(ApprovalPolicy::default()),
+            failure_classifier: Arc::new(FailureClassifier::default()),
+            retry_policy: Arc::new(RetryPolicy::default()),
+            sla: Arc::new(SlaTracker::default()),
+            sla_alert_target: None,
+            cold_storage: None,
+            artifact_store: None,
+        }
+    }
+
+    /// 新任务使用按创建时间排序的UUIDv7作为ID
+    pub fn with_time_ordered_ids(mut self, time_ordered_ids: bool) -> Self {
+        self.time_ordered_ids = time_ordered_ids;
+        self
+    }
+
+    /// 设置时钟
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置工作目录策略
+    pub fn with_path_policy(mut self, path_policy: Arc<WorkDirectoryPolicy>) -> Self {
+        self.path_policy = Some(path_policy);
+        self
+    }
+
+    /// 设置维护计划，维护窗口内暂停匹配任务的获取和执行
+    pub fn with_maintenance_schedule(mut self, maintenance: Arc<MaintenanceSchedule>) -> Self {
+        self.maintenance = Some(maintenance);
+        self
+    }
+
+    /// 设置等待任务完成的最长时间（秒）
+    pub fn with_max_wait_timeout(mut self, max_wait_timeout: u64) -> Self {
+        self.max_wait_timeout = max_wait_timeout;
+        self
+    }
+
+    /// 正在等待任务完成的请求数
+    pub fn completion_waiters(&self) -> usize {
+        self.completion.waiters()
+    }
+
+    /// 队列暂停与排空控制
+    pub fn queue_control(&self) -> &Arc<QueueControl> {
+        &self.queue_control
+    }
+
+    /// 元数据模式注册表
+    pub fn metadata_schemas(&self) -> &Arc<MetadataSchemaRegistry> {
+        &self.metadata_schemas
+    }
+
+    /// 从任务库加载元数据模式，启动时调用
+    pub async fn load_metadata_schemas(&self) -> AppResult<usize> {
+        let schemas = self.task_repository.list_metadata_schemas().await?;
+        let count = schemas.len();
+        self.metadata_schemas.replace_all(schemas)?;
+        Ok(count)
+    }
+
+    /// 注册或替换命名空间的元数据模式
+    pub async fn register_metadata_schema(
+        &self,
+        namespace: String,
+        schema: serde_json::Value,
+        strict: bool,
+    ) -> AppResult<MetadataSchema> {
+        let schema = MetadataSchemaRegistry::prepare(namespace, schema, strict)?;
+        self.task_repository.save_metadata_schema(&schema).await?;
+        self.metadata_schemas.insert(schema.clone())?;
+        Ok(schema)
+    }
+
+    /// 删除命名空间的元数据模式，返回是否存在
+    pub async fn delete_metadata_schema(&self, namespace: &str) -> AppResult<bool> {
+        let deleted = self.task_repository.delete_metadata_schema(namespace).await?;
+        Ok(self.metadata_schemas.remove(namespace) || deleted)
+    }
+
+    /// 设置密钥扫描器
+    pub fn with_secret_scanner(mut self, secret_scanner: Arc<SecretScanner>) -> Self {
+        self.secret_scanner = Some(secret_scanner);
+        self
+    }
+
+    /// 获取密钥扫描统计
+    pub fn secret_scanner_stats(&self) -> Option<SecretScanStats> {
+        self.secret_scanner.as_ref().map(|s| s.stats())
+    }
+
+    /// 设置敏感数据脱敏器，导出的事件快照将先脱敏
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// 设置任务缓存，仅用于上报缓存统计（仓库本身应已是该缓存）
+    pub fn with_task_cache(mut self, task_cache: Arc<CachedTaskRepository>) -> Self {
+        self.task_cache = Some(task_cache);
+        self
+    }
+
+    /// 获取任务缓存统计
+    pub fn task_cache_stats(&self) -> Option<CacheStats> {
+        self.task_cache.as_ref().map(|c| c.stats())
+    }
+
+    /// 设置缓冲式历史写入器，状态转换历史将批量写入
+    pub fn with_history_writer(mut self, history_writer: Arc<HistoryWriter>) -> Self {
+        self.history_writer = Some(history_writer);
+        self
+    }
+
+    /// 获取历史写入统计
+    pub fn history_writer_stats(&self) -> Option<HistoryWriterStats> {
+        self.history_writer.as_ref().map(|w| w.stats())
+    }
+
+    /// 设置数据库连接池，用于报告连接池状态
+    pub fn with_database_pool(mut self, database_pool: Arc<ManagedPool>) -> Self {
+        self.database_pool = Some(database_pool);
+        self
+    }
+
+    /// 获取连接池统计
+    pub fn database_pool_stats(&self) -> Option<PoolStats> {
+        self.database_pool.as_ref().map(|p| p.stats())
+    }
+
+    /// 设置外部服务客户端
+    pub fn with_service_clients(mut self, service_clients: Arc<ServiceClients>) -> Self {
+        self.service_clients = Some(service_clients);
+        self
+    }
+
+    /// 获取指定外部服务的客户端，供集成和执行器调用外部服务
+    pub fn service_client(&self, name: &str) -> Option<Arc<ServiceClient>> {
+        self.service_clients.as_ref().and_then(|c| c.get(name))
+    }
+
+    /// 获取外部服务客户端统计
+    pub async fn service_client_stats(&self) -> Option<HashMap<String, ServiceClientStats>> {
+        match &self.service_clients {
+            Some(clients) => Some(clients.stats().await),
+            None => None,
+        }
+    }
+
+    /// 记录任务历史，配置了写入器时进入缓冲区
+    async fn record_history(&self, history: TaskHistory) -> AppResult<()> {
+        match &self.history_writer {
+            Some(writer) => writer.record(history).await,
+            None => self.task_repository.create_task_history(&history).await.map(|_| ()),
+        }
+    }
+
+    /// 设置生命周期事件导出器
+    pub fn with_event_exporter(mut self, event_exporter: Arc<TaskEventExporter>) -> Self {
+        self.event_exporter = Some(event_exporter);
+        self
+    }
+
+    /// 获取事件导出统计
+    pub fn exporter_stats(&self) -> Option<ExporterStats> {
+        self.event_exporter.as_ref().map(|e| e.stats())
+    }
+
+    /// 设置任务流水线定义
+    pub fn with_pipelines(mut self, pipelines: Arc<PipelineRegistry>) -> Self {
+        self.pipelines = pipelines;
+        self
+    }
+
+    /// 任务流水线定义
+    pub fn pipelines(&self) -> &Arc<PipelineRegistry> {
+        &self.pipelines
+    }
+
+    /// 设置Prometheus指标收集器，生命周期事件同时计入指标
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// 按生命周期事件更新指标，完成耗时附带当前请求的追踪ID
+    fn record_metrics(&self, event_type: &TaskEventType, task: &Task) {
+        let Some(metrics) = &self.metrics else {
+            return;
+        };
+        let work_directory = task.work_directory.as_str();
+        let priority = task.priority.to_string();
+        match event_type {
+            TaskEventType::Created => metrics.record_task_created(work_directory, &priority),
+            TaskEventType::Started => {
+                let worker_id = task.worker_id.as_ref().map_or("", |w| w.as_str());
+                metrics.record_task_acquired(work_directory, worker_id);
+            }
+            TaskEventType::Completed => {
+                let duration = task.processing_duration().map_or(0.0, |d| d.num_milliseconds() as f64 / 1000.0);
+                let context = crate::errors::current_request_context();
+                metrics.record_task_completed(work_directory, &priority, duration, context.as_ref().map(|c| c.trace_id.as_str()));
+            }
+            TaskEventType::Failed => metrics.record_task_failed(work_directory, &priority),
+            TaskEventType::Cancelled => {
+                let reason = task.cancel_reason.map_or_else(|| "unknown".to_string(), |r| r.to_string());
+                metrics.record_task_cancelled(work_directory, &reason);
+            }
+            _ => {}
+        }
+    }
+
+    /// 导出生命周期事件，任务进入终态时通知等待方
+    fn export_event(&self, event_type: TaskEventType, task: &Task) {
+        if task.status.is_terminal() {
+            self.completion.notify(task.id);
+        }
+        self.record_metrics(&event_type, task);
+        if let Some(exporter) = &self.event_exporter {
+            match &self.redactor {
+                Some(redactor) => exporter.export(TaskEvent::new(event_type, &redactor.redact_task(task))),
+                None => exporter.export(TaskEvent::new(event_type, task)),
+            }
+        }
+    }
+
+    /// 设置审批策略
+    pub fn with_approval_policy(mut self, approval: Arc<ApprovalPolicy>) -> Self {
+        self.approval = approval;
+        self
+    }
+
+    /// 设置失败分类器
+    pub fn with_failure_classifier(mut self, failure_classifier: Arc<FailureClassifier>) -> Self {
+        self.failure_classifier = failure_classifier;
+        self
+    }
+
+    /// 设置按失败类别的重试策略
+    pub fn with_retry_policy(mut self, retry_policy: Arc<RetryPolicy>) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// 设置SLA跟踪器
+    pub fn with_sla_tracker(mut self, sla: Arc<SlaTracker>) -> Self {
+        self.sla = sla;
+        self
+    }
+
+    /// 设置SLA告警通知的外部服务名称和请求路径
+    pub fn with_sla_alert_target(mut self, service: String, path: String) -> Self {
+        self.sla_alert_target = Some((service, path));
+        self
+    }
+
+    /// 启用冷存储，结束超过 `after_days` 天的任务每批 `batch_size` 个移入冷存储
+    pub fn with_cold_storage(mut self, after_days: u32, batch_size: u32) -> Self {
+        self.cold_storage = Some((after_days, batch_size));
+        self
+    }
+
+    /// 设置产物存储，任务移入冷存储时产物随之移到归档目录
+    pub fn with_artifact_store(mut self, artifact_store: Arc<ArtifactStore>) -> Self {
+        self.artifact_store = Some(artifact_store);
+        self
+    }
+
+    /// 设置消息队列，新建和重新排队的任务将按工作目录发布到对应主题
+    pub fn with_message_queue(mut self, message_queue: Arc<dyn MessageQueue>, subject_prefix: String) -> Self {
+        self.message_queue = Some(message_queue);
+        self.subject_prefix = subject_prefix;
+        self
+    }
+
+    /// 发布任务到消息队列
+    ///
+    /// 失败后退避中的任务在 `retry_after` 到达后才发布，避免工作者收到暂时还不能获取的任务。
+    async fn publish_task(&self, task: &Task) -> AppResult<()> {
+        let Some(queue) = &self.message_queue else {
+            return Ok(());
+        };
+        let subject = subject_for_work_directory(&self.subject_prefix, task.work_directory.as_str());
+        let payload = serde_json::to_vec(task)
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        let delay = task.retry_after
+            .and_then(|retry_after| (retry_after - Utc::now()).to_std().ok())
+            .filter(|delay| !delay.is_zero());
+        let Some(delay) = delay else {
+            return queue.publish(&subject, &payload).await;
+        };
+        let queue = queue.clone();
+        let task_id = task.id;
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            if let Err(e) = queue.publish(&subject, &payload).await {
+                tracing::warn!("Failed to publish task {} to queue: {}", task_id, e);
+            }
+        });
+        Ok(())
+    }
+
+    /// 发布重新回到等待状态的任务，失败时任务仍可通过HTTP轮询获取
+    async fn publish_requeued(&self, task: &Task) {
+        if let Err(e) = self.publish_task(task).await {
+            tracing::warn!("Failed to publish task {} to queue: {}", task.id, e);
         }
     }
 
     /// 创建任务
     pub async fn create_task(&self, request: CreateTaskRequest) -> AppResult<Task> {
+        self.queue_control.check_create()?;
+        let task = self.build_task(request)?;
+        self.insert_task(task).await
+    }
+
+    /// 克隆任务
+    ///
+    /// 复制源任务的工作目录、提示词、优先级、标签、元数据和并发组，请求中设置的字段覆盖源任务的值；
+    /// 服务写入的元数据（流水线进度、策略违规、密钥扫描结果、转换摘要）不复制，克隆任务的
+    /// `cloned_from` 元数据记录源任务ID。源任务需要审批时克隆任务同样需要审批。
+    pub async fn clone_task(&self, task_id: &TaskId, request: CloneTaskRequest) -> AppResult<Task> {
+        self.queue_control.check_create()?;
+        request.validate().map_err(|e| {
+            AppError::Validation(crate::errors::ValidationError::invalid_validation(e.to_string()))
+        })?;
+
+        let source = self.get_task(task_id).await?;
+        let source_required_approval = self
+            .get_task_history(task_id)
+            .await?
+            .iter()
+            .any(|history| history.status == TaskStatus::PendingApproval);
+
+        let mut metadata = source.metadata;
+        for key in [pipeline::PIPELINE_KEY, POLICY_VIOLATION_KEY, SECRET_FINDINGS_KEY, TRANSITION_HASH_KEY] {
+            metadata.remove(key);
+        }
+        for (key, value) in request.metadata.unwrap_or_default() {
+            if value.is_null() {
+                metadata.remove(&key);
+            } else {
+                metadata.insert(key, value);
+            }
+        }
+        metadata.insert(CLONED_FROM_KEY.to_string(), serde_json::json!(source.id.to_string()));
+
+        let task = self.build_task(CreateTaskRequest {
+            work_directory: request.work_directory.unwrap_or_else(|| source.work_directory.as_str().to_string()),
+            prompt: request.prompt.unwrap_or_else(|| source.prompt.as_str().to_string()),
+            priority: Some(request.priority.unwrap_or(source.priority)),
+            tags: Some(request.tags.unwrap_or_else(|| source.tags.iter().map(|t| t.as_str().to_string()).collect())),
+            metadata: Some(metadata),
+            expires_at: request.expires_at,
+            concurrency_group: request.concurrency_group.or(source.concurrency_group),
+            requires_approval: request.requires_approval || source_required_approval,
+        })?;
+        let task = self.insert_task(task).await?;
+        tracing::info!("Task {} cloned from {}", task.id, source.id);
+        Ok(task)
+    }
+
+    /// 运行流水线，创建第一个条件成立的步骤的任务
+    pub async fn run_pipeline(&self, name: &str, request: PipelineRunRequest) -> AppResult<PipelineRunStart> {
+        let pipeline = self.pipelines.get(name).ok_or_else(|| AppError::PipelineNotFound(name.to_string()))?;
+        let run_id = uuid::Uuid::new_v4().to_string();
+        let mut run = PipelineRun::new(run_id.clone(), pipeline.name.clone());
+        let task = match pipeline.first_request(run_id.clone(), request).apply(&mut run) {
+            Some((step, request)) => {
+                let task = self.create_task(request).await?;
+                run.record_step(step, &task);
+                tracing::info!(pipeline = name, run_id = %run_id, task_id = %task.id, "Pipeline run started");
+                Some(task)
+            }
+            None => {
+                tracing::info!(pipeline = name, run_id = %run_id, "Pipeline run finished without steps");
+                None
+            }
+        };
+        self.task_repository.save_pipeline_run(&run).await?;
+        Ok(PipelineRunStart { run, task })
+    }
+
+    /// 获取流水线运行记录
+    ///
+    /// 步骤状态按任务当前状态刷新；运行中的最后一步已失败或取消时，运行状态显示为 `stopped`。
+    pub async fn get_pipeline_run(&self, run_id: &str) -> AppResult<PipelineRun> {
+        let mut run = self
+            .task_repository
+            .get_pipeline_run(run_id)
+            .await?
+            .ok_or_else(|| AppError::PipelineRunNotFound(run_id.to_string()))?;
+        for index in 0..run.steps.len() {
+            if run.steps[index].status.is_terminal() {
+                continue;
+            }
+            if let Some(task) = self.task_repository.get_task(&run.steps[index].task_id).await? {
+                run.update_step(&task);
+            }
+        }
+        if run.status == PipelineRunStatus::Running {
+            if let Some(step) = run.steps.last().filter(|s| matches!(s.status, TaskStatus::Failed | TaskStatus::Cancelled)) {
+                let message = format!("Step '{}' {}", step.name, step.status);
+                run.finish(PipelineRunStatus::Stopped, Some(message));
+            }
+        }
+        Ok(run)
+    }
+
+    /// 流水线中的任务成功完成后选择并创建下一步的任务
+    ///
+    /// 下一步属于已接受的运行，不受队列排空限制；创建失败时只记录错误，已完成的任务不受影响。
+    async fn advance_pipeline(&self, task: &Task) {
+        let Some(progress) = pipeline::PipelineProgress::from_task(task) else {
+            return;
+        };
+        let mut run = match self.task_repository.get_pipeline_run(&progress.run_id).await {
+            Ok(run) => run.unwrap_or_else(|| PipelineRun::new(progress.run_id.clone(), progress.name.clone())),
+            Err(e) => {
+                tracing::error!(pipeline = %progress.name, run_id = %progress.run_id, "Failed to load pipeline run: {}", e);
+                return;
+            }
+        };
+        run.update_step(task);
+        let step = self.pipelines.get(&progress.name).and_then(|p| p.steps.get(progress.step)).map(|s| s.name.clone());
+        let step = step.unwrap_or_else(|| format!("step-{}", progress.step + 1));
+
+        if task.result.as_ref().map(|r| r.status) != Some(crate::domain::TaskResultStatus::Success) {
+            tracing::info!(pipeline = %progress.name, run_id = %progress.run_id, task_id = %task.id, "Pipeline run stopped after unsuccessful step");
+            run.finish(PipelineRunStatus::Stopped, Some(format!("Step '{}' completed with a failed result", step)));
+        } else if let Some(pipeline) = self.pipelines.get(&progress.name) {
+            let internal_keys = [TRANSITION_HASH_KEY, SECRET_FINDINGS_KEY, POLICY_VIOLATION_KEY];
+            let advance = pipeline.next_request(task, &internal_keys).and_then(|advance| advance.apply(&mut run));
+            if let Some((step, request)) = advance {
+                match self.build_task(request) {
+                    Ok(next) => match self.insert_task(next).await {
+                        Ok(next) => {
+                            tracing::info!(
+                                pipeline = %progress.name,
+                                run_id = %progress.run_id,
+                                step = %step,
+                                task_id = %next.id,
+                                "Pipeline step created"
+                            );
+                            run.record_step(step, &next);
+                        }
+                        Err(e) => {
+                            tracing::error!(pipeline = %progress.name, run_id = %progress.run_id, "Failed to create pipeline step: {}", e);
+                            run.finish(PipelineRunStatus::Stopped, Some(format!("Failed to create step '{}': {}", step, e)));
+                        }
+                    },
+                    Err(e) => {
+                        tracing::error!(pipeline = %progress.name, run_id = %progress.run_id, "Failed to build pipeline step: {}", e);
+                        run.finish(PipelineRunStatus::Stopped, Some(format!("Failed to create step '{}': {}", step, e)));
+                    }
+                }
+            } else {
+                tracing::info!(pipeline = %progress.name, run_id = %progress.run_id, status = %run.status, "Pipeline run finished");
+            }
+        } else {
+            tracing::warn!(pipeline = %progress.name, run_id = %progress.run_id, "Pipeline is no longer configured, run stopped");
+            run.finish(PipelineRunStatus::Stopped, Some("Pipeline is no longer configured".to_string()));
+        }
+
+        if let Err(e) = self.task_repository.save_pipeline_run(&run).await {
+            tracing::error!(pipeline = %progress.name, run_id = %progress.run_id, "Failed to save pipeline run: {}", e);
+        }
+    }
+
+    /// 保存新任务、记录历史、导出事件并发布到消息队列
+    async fn insert_task(&self, mut task: Task) -> AppResult<Task> {
+        if let Some(findings) = task.get_metadata(SECRET_FINDINGS_KEY).and_then(|v| v.as_array()) {
+            tracing::warn!("Task {} contains {} potential secret(s)", task.id, findings.len());
+        }
+        if let Some(reason) = task.get_metadata(POLICY_VIOLATION_KEY).and_then(|v| v.as_str()) {
+            tracing::warn!("Task {} flagged by work directory policy: {}", task.id, reason);
+        }
+
+        // 保存到数据库
+        let task_id = self.task_repository.create_task(&task).await?;
+
+        // 创建任务历史记录
+        let history = TaskHistory::new(task_id, task.status, None);
+        self.record_history(history).await?;
+
+        // 设置任务ID
+        task.id = task_id;
+        self.export_event(TaskEventType::Created, &task);
+
+        // 发布到消息队列，失败时任务仍可通过HTTP轮询获取；待审批任务在审批通过后发布
+        if task.status == TaskStatus::Waiting {
+            if let Err(e) = self.publish_task(&task).await {
+                tracing::warn!("Failed to publish task {} to queue: {}", task.id, e);
+            }
+        }
+
+        Ok(task)
+    }
+
+    /// 模拟创建任务
+    ///
+    /// 执行与创建相同的校验、工作目录策略、密钥扫描和排空检查，查找工作目录和提示词相同、尚未结束的重复任务，
+    /// 并检查队列暂停、维护窗口和可获取该任务的活跃节点，不保存任务、不记录历史也不导出事件。
+    pub async fn simulate_task(&self, request: CreateTaskRequest) -> AppResult<TaskSimulation> {
+        self.queue_control.check_create()?;
+        let task = self.build_task(request)?;
+        let mut warnings = Vec::new();
+
+        if let Some(reason) = task.get_metadata(POLICY_VIOLATION_KEY).and_then(|v| v.as_str()) {
+            warnings.push(format!("Work directory policy violation: {}", reason));
+        }
+        if let Some(findings) = task.get_metadata(SECRET_FINDINGS_KEY).and_then(|v| v.as_array()) {
+            warnings.push(format!("{} potential secret(s) detected", findings.len()));
+        }
+        for duplicate in self.find_duplicates(&task).await? {
+            warnings.push(format!("Duplicate of unfinished task {}", duplicate));
+        }
+        if let Err(e) = self.check_start(&task) {
+            warnings.push(e.to_string());
+        }
+
+        let tags: Vec<String> = task.tags.iter().map(|t| t.to_string()).collect();
+        let matching_workers = self.worker_capabilities.matching_workers(&tags);
+        if matching_workers.is_empty() {
+            warnings.push("No active worker declares the capabilities required by this task".to_string());
+        }
+
+        Ok(TaskSimulation { task, warnings, matching_workers })
+    }
+
+    /// 工作目录和提示词与给定任务相同、尚未结束的任务ID
+    async fn find_duplicates(&self, task: &Task) -> AppResult<Vec<TaskId>> {
+        let mut duplicates = Vec::new();
+        for status in [TaskStatus::PendingApproval, TaskStatus::Waiting, TaskStatus::Working] {
+            let filter = TaskFilter::new()
+                .with_status(status)
+                .with_work_directory(task.work_directory.as_str().to_string());
+            let (tasks, _) = self.task_repository.list_tasks(&filter).await?;
+            duplicates.extend(
+                tasks
+                    .into_iter()
+                    .filter(|t| t.work_directory == task.work_directory && t.prompt == task.prompt)
+                    .map(|t| t.id),
+            );
+        }
+        Ok(duplicates)
+    }
+
+    /// 校验请求并构建待保存的任务
+    fn build_task(&self, request: CreateTaskRequest) -> AppResult<Task> {
         // 验证请求
         request.validate().map_err(|e| {
             AppError::Validation(crate::errors::ValidationError::invalid_validation(e.to_string()))
@@ -50,7 +755,39 @@ impl TaskService {
 
         // 创建值对象
         let work_directory = WorkDirectory::new(request.work_directory)?;
-        let prompt = Prompt::new(request.prompt)?;
+
+        // 检查工作目录策略
+        let violation = self.path_policy.as_ref().and_then(|policy| {
+            policy.evaluate(work_directory.as_str()).map(|reason| (policy.action(), reason))
+        });
+        if let Some((PolicyAction::Reject, reason)) = &violation {
+            return Err(AppError::Validation(
+                crate::errors::ValidationError::invalid_work_directory(reason.clone())
+            ));
+        }
+
+        // 按命名空间的模式校验元数据，再扫描提示词和元数据中的密钥
+        let mut metadata = request.metadata.unwrap_or_default();
+        metadata_schema::reject_system_keys(metadata.keys())?;
+        self.metadata_schemas.validate(work_directory.as_str(), &metadata)?;
+        let mut prompt = request.prompt;
+        let mut secret_findings = Vec::new();
+        if let Some(scanner) = &self.secret_scanner {
+            let outcome = scanner.scan(&prompt, &metadata)?;
+            prompt = outcome.prompt;
+            metadata = outcome.metadata;
+            secret_findings = outcome.findings;
+        }
+
+        if let Some(expires_at) = request.expires_at {
+            if expires_at <= Utc::now() {
+                return Err(AppError::Validation(
+                    crate::errors::ValidationError::invalid_validation("expires_at must be in the future".to_string())
+                ));
+            }
+        }
+
+        let prompt = Prompt::new(prompt)?;
         let priority = request.priority.unwrap_or_default();
         let tags = request.tags
             .unwrap_or_default()
@@ -60,21 +797,115 @@ impl TaskService {
 
         // 创建任务
         let mut task = Task::new(work_directory, prompt, priority, tags);
+        if self.time_ordered_ids {
+            task.id = TaskId::new_v7(task.created_at);
+        }
         task.max_retries = self.max_retries;
+        task.expires_at = request.expires_at;
+        task.concurrency_group = request.concurrency_group;
+        if request.requires_approval {
+            task.status = TaskStatus::PendingApproval;
+        }
+        task.metadata.extend(metadata);
+        if !secret_findings.is_empty() {
+            task.metadata.insert(SECRET_FINDINGS_KEY.to_string(), serde_json::json!(secret_findings));
+        }
+        if let Some((_, reason)) = violation {
+            task.metadata.insert(POLICY_VIOLATION_KEY.to_string(), serde_json::json!(reason));
+        }
 
-        // 保存到数据库
-        let task_id = self.task_repository.create_task(&task).await?;
+        Ok(task)
+    }
 
-        // 创建任务历史记录
-        let history = TaskHistory::new(task_id, task.status, None);
-        self.task_repository.create_task_history(&history).await?;
+    /// 开始执行任务前检查队列暂停和维护窗口
+    fn check_start(&self, task: &Task) -> AppResult<()> {
+        self.queue_control.check_task(task)?;
+        if let Some(maintenance) = &self.maintenance {
+            maintenance.check_task(task, Utc::now())?;
+        }
+        Ok(())
+    }
 
-        // 设置任务ID
-        task.id = task_id;
+    /// 通过锁管理器占用任务的并发组，锁以执行超时为有效期，组内已有任务在执行时返回错误
+    async fn claim_concurrency_group(&self, task: &Task) -> AppResult<()> {
+        let Some(group) = &task.concurrency_group else {
+            return Ok(());
+        };
+        let resource = format!("{}{}", CONCURRENCY_GROUP_LOCK_PREFIX, group);
+        if self.lock_manager.try_acquire(&resource, &task.id.to_string(), self.task_timeout).await? {
+            Ok(())
+        } else {
+            Err(AppError::ConcurrencyGroupBusy(group.clone()))
+        }
+    }
+
+    /// 任务离开执行状态后释放并发组，释放失败时锁在有效期后自动失效
+    async fn release_concurrency_group(&self, task: &Task) {
+        let Some(group) = &task.concurrency_group else {
+            return;
+        };
+        let resource = format!("{}{}", CONCURRENCY_GROUP_LOCK_PREFIX, group);
+        if let Err(e) = self.lock_manager.release(&resource, &task.id.to_string()).await {
+            tracing::warn!(task_id = %task.id, group = %group, "Failed to release concurrency group: {}", e);
+        }
+    }
+
+    /// 开始执行指定任务（队列模式下由工作者确认领取）
+    pub async fn start_task(&self, task_id: &TaskId, worker_id: String) -> AppResult<Task> {
+        let mut task = self.get_task(task_id).await?;
+
+        // 验证任务状态
+        if task.status != TaskStatus::Waiting {
+            return Err(AppError::Validation(
+                crate::errors::ValidationError::invalid_status_transition(
+                    task.status,
+                    TaskStatus::Working,
+                )
+            ));
+        }
+
+        self.check_start(&task)?;
+        task.start(WorkerId::new(worker_id)?)?;
+        self.claim_concurrency_group(&task).await?;
+
+        // 更新任务
+        if let Err(e) = self.task_repository.update_task(&task).await {
+            self.release_concurrency_group(&task).await;
+            return Err(e);
+        }
+
+        // 创建任务历史记录
+        let history = TaskHistory::new(task.id, task.status, task.worker_id.clone());
+        self.record_history(history).await?;
+        self.export_event(TaskEventType::Started, &task);
 
         Ok(task)
     }
 
+    /// 等待任务进入终态（完成、失败或取消），超时后返回当前状态的任务
+    ///
+    /// 未指定超时时等待 [`completion::DEFAULT_WAIT_TIMEOUT`] 秒，超时不超过 `max_wait_timeout`。
+    pub async fn wait_for_completion(&self, task_id: &TaskId, timeout: Option<u64>) -> AppResult<Task> {
+        let timeout = timeout.unwrap_or(completion::DEFAULT_WAIT_TIMEOUT).min(self.max_wait_timeout);
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(timeout);
+        // 先订阅再读取任务，读取之后完成的任务也能收到通知
+        let mut receiver = self.completion.subscribe();
+
+        loop {
+            let task = self.get_task(task_id).await?;
+            if task.status.is_terminal() || tokio::time::Instant::now() >= deadline {
+                return Ok(task);
+            }
+
+            // 其他实例完成的任务没有通知，定期重新读取
+            let wake_at = (tokio::time::Instant::now() + completion::WAIT_POLL_INTERVAL).min(deadline);
+            tokio::select! {
+                _ = completion::wait_for(&mut receiver, *task_id) => {}
+                _ = tokio::time::sleep_until(wake_at) => {}
+            }
+        }
+    }
+
     /// 获取任务
     pub async fn get_task(&self, task_id: &TaskId) -> AppResult<Task> {
         self.task_repository.get_task(task_id).await?.ok_or(AppError::TaskNotFound(*task_id))
@@ -86,19 +917,59 @@ impl TaskService {
             AppError::Validation(crate::errors::ValidationError::invalid_validation(e.to_string()))
         })?;
 
+        if let Some(policy) = &self.path_policy {
+            policy.check_acquire(&request.work_path)?;
+        }
+        if let Some(maintenance) = &self.maintenance {
+            maintenance.check_directory(&request.work_path, Utc::now())?;
+        }
+        self.queue_control.check_directory(&request.work_path)?;
+
         // 尝试获取任务
+        self.worker_capabilities.record(&request.worker_id, request.capabilities.as_deref());
         let task = self.task_repository
-            .get_next_task(&request.work_path, &request.worker_id)
+            .get_next_task(&request.work_path, &request.worker_id, request.capabilities.as_deref())
             .await?;
 
+        // 只针对部分标签的维护窗口和暂停的子目录需要看到任务后才能判断，命中时将任务放回队列
+        if let Some(task) = &task {
+            if let Err(e) = self.check_start(task) {
+                let mut released = self.get_task(&task.id).await?;
+                released.release()?;
+                self.task_repository.update_task(&released).await?;
+                return Err(e);
+            }
+        }
+
+        // 同一并发组的任务可能被多个实例同时选中，以锁管理器为准，未占到时将任务放回队列
+        if let Some(task) = &task {
+            if let Err(e) = self.claim_concurrency_group(task).await {
+                let mut released = self.get_task(&task.id).await?;
+                released.release()?;
+                self.task_repository.update_task(&released).await?;
+                return match e {
+                    AppError::ConcurrencyGroupBusy(group) => {
+                        tracing::debug!(task_id = %task.id, group = %group, "Concurrency group busy, task returned to queue");
+                        Ok(None)
+                    }
+                    e => Err(e),
+                };
+            }
+        }
+
         if let Some(ref task) = task {
+            // 本进程分发的任务按单调时钟判断执行超时
+            let deadline = Deadline::after(self.clock.as_ref(), std::time::Duration::from_secs(self.task_timeout));
+            self.execution_deadlines.lock().unwrap().insert(task.id, deadline);
+
             // 创建任务历史记录
             let history = TaskHistory::new(
                 task.id,
                 task.status,
                 Some(WorkerId::new(request.worker_id.clone())?),
             );
-            self.task_repository.create_task_history(&history).await?;
+            self.record_history(history).await?;
+            self.export_event(TaskEventType::Started, task);
         }
 
         Ok(task)
@@ -108,9 +979,15 @@ impl TaskService {
     pub async fn complete_task(&self, task_id: &TaskId, request: CompleteTaskRequest) -> AppResult<Task> {
         // 获取任务
         let mut task = self.get_task(task_id).await?;
+        let mut result = request.result.unwrap_or_else(|| TaskResult::success("Task completed".to_string()));
+        let hash = transition_hash("complete", &result);
 
-        // 验证任务状态
+        // 验证任务状态（重复提交相同结果时直接返回已存储的任务）
         if task.status != TaskStatus::Working {
+            if is_duplicate_transition(&task, &hash, &[TaskStatus::Completed]) {
+                audit_duplicate_transition(&task, "complete");
+                return Ok(task);
+            }
             return Err(AppError::Validation(
                 crate::errors::ValidationError::invalid_status_transition(
                     task.status,
@@ -130,26 +1007,45 @@ impl TaskService {
             }
         }
 
+        // 失败结果归类（执行器给出的类别优先）
+        if result.status == TaskResultStatus::Failed {
+            let error = result.error.as_deref().or(result.output.as_deref()).unwrap_or_default();
+            result.failure_category = Some(self.failure_classifier.classify(error, result.failure_category));
+        }
+
         // 完成任务
-        let result = request.result.unwrap_or_else(|| TaskResult::success("Task completed".to_string()));
-        task.complete(result)?;
+        let attempt = task.retry_count + 1;
+        task.complete(result.clone())?;
+        task.metadata.insert(TRANSITION_HASH_KEY.to_string(), serde_json::json!(hash));
 
         // 更新任务
         self.task_repository.update_task(&task).await?;
+        self.execution_deadlines.lock().unwrap().remove(task_id);
+        self.release_concurrency_group(&task).await;
 
-        // 创建任务历史记录
-        let history = TaskHistory::new(task.id, task.status, task.worker_id.clone());
-        self.task_repository.create_task_history(&history).await?;
+        // 创建任务历史记录（含本次执行结果）
+        let history = TaskHistory::new(task.id, task.status, task.worker_id.clone())
+            .with_attempt(attempt, &result);
+        self.record_history(history).await?;
+        self.export_event(TaskEventType::Completed, &task);
+        self.advance_pipeline(&task).await;
 
         Ok(task)
     }
 
     /// 任务失败
-    pub async fn fail_task(&self, task_id: &TaskId, error: String) -> AppResult<Task> {
+    ///
+    /// `category` 为上报方给出的失败类别，未给出时按错误信息归类；是否重新排队及退避时间由重试策略按类别决定。
+    pub async fn fail_task(&self, task_id: &TaskId, error: String, category: Option<FailureCategory>) -> AppResult<Task> {
         let mut task = self.get_task(task_id).await?;
+        let hash = transition_hash("fail", &error);
 
-        // 验证任务状态
+        // 验证任务状态（失败后可能已重新排队，重复上报同一错误视为成功）
         if task.status != TaskStatus::Working {
+            if is_duplicate_transition(&task, &hash, &[TaskStatus::Failed, TaskStatus::Waiting]) {
+                audit_duplicate_transition(&task, "fail");
+                return Ok(task);
+            }
             return Err(AppError::Validation(
                 crate::errors::ValidationError::invalid_status_transition(
                     task.status,
@@ -158,25 +1054,49 @@ impl TaskService {
             ));
         }
 
-        // 处理失败
-        task.fail(error)?;
+        // 处理失败（失败后重新排队会清空工作者，先记下本次执行信息）
+        let attempt = task.retry_count + 1;
+        let worker_id = task.worker_id.clone();
+        let category = self.failure_classifier.classify(&error, category);
+        let mut result = TaskResult::failed(error.clone());
+        result.failure_category = Some(category);
+        let decision = self.retry_policy.decide(&task, category, Utc::now());
+        task.fail_with(error, decision)?;
+        if task.status == TaskStatus::Failed {
+            task.failure_category = Some(category);
+        }
+        task.metadata.insert(TRANSITION_HASH_KEY.to_string(), serde_json::json!(hash));
 
         // 更新任务
         self.task_repository.update_task(&task).await?;
+        self.execution_deadlines.lock().unwrap().remove(task_id);
+        self.release_concurrency_group(&task).await;
 
-        // 创建任务历史记录
-        let history = TaskHistory::new(task.id, task.status, task.worker_id.clone());
-        self.task_repository.create_task_history(&history).await?;
+        // 创建任务历史记录（含本次执行结果）
+        let mut history = TaskHistory::new(task.id, task.status, worker_id).with_attempt(attempt, &result);
+        if let Some(retry_after) = task.retry_after {
+            history = history.with_detail("retry_after".to_string(), serde_json::json!(retry_after));
+        }
+        self.record_history(history).await?;
+        self.export_event(TaskEventType::Failed, &task);
 
+        if task.status == TaskStatus::Waiting {
+            self.publish_requeued(&task).await;
+        }
         Ok(task)
     }
 
     /// 取消任务
     pub async fn cancel_task(&self, task_id: &TaskId, reason: Option<String>) -> AppResult<Task> {
         let mut task = self.get_task(task_id).await?;
+        let hash = transition_hash("cancel", &reason);
 
         // 验证任务状态
         if task.status.is_terminal() {
+            if is_duplicate_transition(&task, &hash, &[TaskStatus::Cancelled]) {
+                audit_duplicate_transition(&task, "cancel");
+                return Ok(task);
+            }
             return Err(AppError::Validation(
                 crate::errors::ValidationError::invalid_status_transition(
                     task.status,
@@ -187,13 +1107,134 @@ impl TaskService {
 
         // 取消任务
         task.cancel(reason)?;
+        task.metadata.insert(TRANSITION_HASH_KEY.to_string(), serde_json::json!(hash));
 
         // 更新任务
         self.task_repository.update_task(&task).await?;
+        self.execution_deadlines.lock().unwrap().remove(task_id);
+        self.release_concurrency_group(&task).await;
 
         // 创建任务历史记录
         let history = TaskHistory::new(task.id, task.status, task.worker_id.clone());
-        self.task_repository.create_task_history(&history).await?;
+        self.record_history(history).await?;
+        self.export_event(TaskEventType::Cancelled, &task);
+
+        Ok(task)
+    }
+
+    /// 识别审批人，`api_key` 为调用方的API密钥，`claimed` 为请求中声明的审批人
+    pub fn identify_approver(&self, api_key: Option<&str>, claimed: Option<&str>) -> AppResult<String> {
+        self.approval.identify(api_key, claimed)
+    }
+
+    /// 审批通过，任务进入等待队列
+    pub async fn approve_task(&self, task_id: &TaskId, approver: String, comment: Option<String>) -> AppResult<Task> {
+        let mut task = self.get_task(task_id).await?;
+        task.approve()?;
+        self.task_repository.update_task(&task).await?;
+
+        let mut history = TaskHistory::new(task.id, task.status, None)
+            .with_detail("approver".to_string(), serde_json::json!(approver));
+        if let Some(comment) = comment {
+            history = history.with_detail("comment".to_string(), serde_json::json!(comment));
+        }
+        self.record_history(history).await?;
+        self.export_event(TaskEventType::Approved, &task);
+        tracing::info!("Task {} approved by {}", task.id, approver);
+
+        if let Err(e) = self.publish_task(&task).await {
+            tracing::warn!("Failed to publish task {} to queue: {}", task.id, e);
+        }
+        Ok(task)
+    }
+
+    /// 拒绝待审批任务，任务以 `rejected` 原因取消
+    pub async fn reject_task(&self, task_id: &TaskId, approver: String, reason: Option<String>) -> AppResult<Task> {
+        let mut task = self.get_task(task_id).await?;
+        task.reject(reason.clone().unwrap_or_else(|| format!("Rejected by {}", approver)))?;
+        self.task_repository.update_task(&task).await?;
+
+        let mut history = TaskHistory::new(task.id, task.status, None)
+            .with_detail("approver".to_string(), serde_json::json!(approver))
+            .with_detail("cancel_reason".to_string(), serde_json::json!(CancelReason::Rejected));
+        if let Some(reason) = reason {
+            history = history.with_detail("comment".to_string(), serde_json::json!(reason));
+        }
+        self.record_history(history).await?;
+        self.export_event(TaskEventType::Cancelled, &task);
+        tracing::info!("Task {} rejected by {}", task.id, approver);
+        Ok(task)
+    }
+
+    /// 自动拒绝超过审批时限的待审批任务，返回拒绝的任务数
+    pub async fn reject_overdue_approvals(&self) -> AppResult<u64> {
+        let Some(created_before) = self.approval.auto_reject_before(Utc::now()) else {
+            return Ok(0);
+        };
+        let filter = TaskFilter::new()
+            .with_status(TaskStatus::PendingApproval)
+            .with_created_before(created_before);
+
+        let (tasks, _) = self.list_tasks(filter).await?;
+        let reason = format!("Not approved before {}", created_before.to_rfc3339());
+        let mut rejected = 0;
+
+        for mut task in tasks {
+            if task.created_at > created_before || task.reject(reason.clone()).is_err() {
+                continue;
+            }
+            // 期间已被审批或取消的任务跳过
+            if let Err(e) = self.task_repository.update_task(&task).await {
+                tracing::debug!("Skipped auto-rejecting task {}: {}", task.id, e);
+                continue;
+            }
+
+            let history = TaskHistory::new(task.id, task.status, None)
+                .with_detail("cancel_reason".to_string(), serde_json::json!(CancelReason::Rejected));
+            self.record_history(history).await?;
+            self.export_event(TaskEventType::Cancelled, &task);
+            tracing::info!("Task {} auto-rejected after waiting for approval", task.id);
+            rejected += 1;
+        }
+
+        Ok(rejected)
+    }
+
+    /// 更新任务字段（优先级、标签、元数据、最大重试次数）
+    pub async fn update_task_fields(&self, task_id: &TaskId, request: UpdateTaskRequest) -> AppResult<Task> {
+        request.validate().map_err(|e| {
+            AppError::Validation(crate::errors::ValidationError::invalid_validation(e.to_string()))
+        })?;
+
+        let tags = request.tags
+            .map(|tags| tags.into_iter().map(TaskTag::new).collect::<Result<Vec<_>, _>>())
+            .transpose()?;
+        if let Some(metadata) = &request.metadata {
+            metadata_schema::reject_system_keys(metadata.keys())?;
+        }
+
+        let metadata_changed = request.metadata.is_some();
+        let mut task = self.get_task(task_id).await?;
+        let changes = task.update_fields(request.priority, tags, request.metadata, request.max_retries)?;
+        if changes.is_empty() {
+            return Ok(task);
+        }
+        if metadata_changed {
+            self.metadata_schemas.validate(task.work_directory.as_str(), &task.metadata)?;
+        }
+
+        // 更新任务
+        self.task_repository.update_task(&task).await?;
+
+        // 每个字段变更记录一条历史
+        for change in changes {
+            let history = TaskHistory::new(task.id, task.status, task.worker_id.clone())
+                .with_detail("field".to_string(), serde_json::json!(change.field))
+                .with_detail("old_value".to_string(), change.old_value)
+                .with_detail("new_value".to_string(), change.new_value);
+            self.record_history(history).await?;
+        }
+        self.export_event(TaskEventType::Updated, &task);
 
         Ok(task)
     }
@@ -220,11 +1261,63 @@ impl TaskService {
 
         // 创建任务历史记录
         let history = TaskHistory::new(task.id, task.status, None);
-        self.task_repository.create_task_history(&history).await?;
+        self.record_history(history).await?;
+        self.export_event(TaskEventType::Retried, &task);
+        self.publish_requeued(&task).await;
 
         Ok(task)
     }
 
+    /// 生成SLA报告
+    pub async fn sla_report(&self) -> AppResult<SlaReport> {
+        let now = Utc::now();
+        let samples = self.task_repository.sla_samples(self.sla.window_start(now)).await?;
+        Ok(self.sla.report(&samples, now))
+    }
+
+    /// 生成SLA报告、更新指标并评估告警，由监控器定期调用
+    pub async fn evaluate_sla(&self) -> AppResult<Vec<SlaAlertEvent>> {
+        if !self.sla.is_enabled() {
+            return Ok(Vec::new());
+        }
+
+        let report = self.sla_report().await?;
+        if let Some(metrics) = &self.metrics {
+            for priority in &report.priorities {
+                metrics.set_sla(&priority.priority, priority.start_breaches, priority.finish_breaches, priority.at_risk);
+            }
+        }
+
+        let events = self.sla.evaluate_alerts(&report);
+        for event in &events {
+            if event.state == "firing" {
+                tracing::warn!(alert = %event.alert, breaches = event.breaches, threshold = event.threshold, "SLA alert firing");
+                if let Some(metrics) = &self.metrics {
+                    metrics.record_sla_alert(&event.alert);
+                }
+            } else {
+                tracing::info!(alert = %event.alert, breaches = event.breaches, "SLA alert resolved");
+            }
+            self.notify_sla_alert(event).await;
+        }
+        Ok(events)
+    }
+
+    /// 将告警状态变化发送到配置的外部服务，失败时只记录日志
+    async fn notify_sla_alert(&self, event: &SlaAlertEvent) {
+        let Some((service, path)) = &self.sla_alert_target else {
+            return;
+        };
+        let Some(client) = self.service_client(service) else {
+            tracing::warn!("SLA alert service '{}' is not configured", service);
+            return;
+        };
+        let url = client.url(path);
+        if let Err(e) = client.send(|http| http.post(&url).json(event)).await {
+            tracing::error!("Failed to send SLA alert '{}' to '{}': {}", event.alert, client.name(), e);
+        }
+    }
+
     /// 列出任务
     pub async fn list_tasks(&self, filter: TaskFilter) -> AppResult<(Vec<Task>, u64)> {
         self.task_repository.list_tasks(&filter).await
@@ -235,19 +1328,162 @@ impl TaskService {
         self.task_repository.get_statistics().await
     }
 
+    /// 根据最近 `window_seconds` 秒的负载预测排队等待时间，并模拟增加 `added` 个工作节点的效果。
+    /// `workers` 为空时取活跃工作节点数和正在执行任务的节点数中较大者
+    pub async fn capacity_forecast(
+        &self,
+        window_seconds: u64,
+        workers: Option<u32>,
+        added: &[u32],
+    ) -> AppResult<CapacityForecast> {
+        let since = Utc::now() - chrono::Duration::seconds(window_seconds as i64);
+        let sample = self.task_repository.load_sample(since).await?;
+        let workers = workers.unwrap_or_else(|| {
+            let active = self.worker_capabilities.stats(&[]).active_workers as u64;
+            active.max(sample.busy_workers).min(u32::MAX as u64) as u32
+        });
+        Ok(capacity::forecast(&sample, window_seconds, workers, added))
+    }
+
+    /// 获取标签路由统计
+    pub async fn routing_stats(&self) -> AppResult<RoutingStats> {
+        let waiting = self.task_repository.waiting_tag_sets().await?;
+        Ok(self.worker_capabilities.stats(&waiting))
+    }
+
+    /// 校验并修复统计计数器
+    pub async fn reconcile_statistics(&self) -> AppResult<u64> {
+        self.task_repository.reconcile_statistics().await
+    }
+
     /// 获取任务历史
     pub async fn get_task_history(&self, task_id: &TaskId) -> AppResult<Vec<TaskHistory>> {
+        // 先写入缓冲区中的记录，保证读到自己的写入
+        if let Some(writer) = &self.history_writer {
+            writer.flush().await?;
+        }
         self.task_repository.get_task_history(task_id).await
     }
 
-    /// 清理过期任务
-    pub async fn cleanup_expired_tasks(&self, older_than: DateTime<Utc>) -> AppResult<u64> {
-        self.task_repository.cleanup_expired_tasks(older_than).await
+    /// 为任务添加评论
+    pub async fn add_comment(
+        &self,
+        task_id: &TaskId,
+        author: String,
+        text: String,
+        data: Option<serde_json::Value>,
+    ) -> AppResult<TaskComment> {
+        let invalid = |message: &str| {
+            AppError::Validation(crate::errors::ValidationError::invalid_validation(message.to_string()))
+        };
+        if author.trim().is_empty() || author.len() > 100 {
+            return Err(invalid("Comment author must be 1-100 characters"));
+        }
+        if text.trim().is_empty() || text.len() > 10000 {
+            return Err(invalid("Comment text must be 1-10000 characters"));
+        }
+
+        let task = self.get_task(task_id).await?;
+        let mut comment = TaskComment::new(task.id, author, text);
+        if let Some(data) = data {
+            comment = comment.with_data(data);
+        }
+        comment.id = self.task_repository.add_task_comment(&comment).await?;
+
+        if let Some(exporter) = &self.event_exporter {
+            let event = match &self.redactor {
+                Some(redactor) => {
+                    let mut redacted = comment.clone();
+                    redacted.text = redactor.redact_text(&comment.text).into_owned();
+                    redacted.data = comment.data.as_ref().map(|data| redactor.redact_value(data));
+                    TaskEvent::commented(&redactor.redact_task(&task), redacted)
+                }
+                None => TaskEvent::commented(&task, comment.clone()),
+            };
+            exporter.export(event);
+        }
+
+        Ok(comment)
+    }
+
+    /// 获取任务评论
+    pub async fn get_task_comments(&self, task_id: &TaskId) -> AppResult<Vec<TaskComment>> {
+        self.task_repository.get_task_comments(task_id).await
+    }
+
+    /// 获取任务的全部执行记录（按执行序号排序）
+    pub async fn get_task_attempts(&self, task_id: &TaskId) -> AppResult<Vec<TaskAttempt>> {
+        // 任务不存在时返回404，而不是空列表
+        self.get_task(task_id).await?;
+        let mut attempts: Vec<TaskAttempt> = self
+            .get_task_history(task_id)
+            .await?
+            .iter()
+            .filter_map(TaskHistory::attempt)
+            .collect();
+        attempts.sort_by_key(|a| a.attempt);
+        Ok(attempts)
+    }
+
+    /// 比较两次执行的输出
+    pub async fn diff_task_attempts(&self, task_id: &TaskId, from: u32, to: u32) -> AppResult<AttemptDiff> {
+        let attempts = self.get_task_attempts(task_id).await?;
+        let find = |attempt: u32| {
+            attempts.iter().find(|a| a.attempt == attempt).ok_or_else(|| {
+                AppError::Validation(crate::errors::ValidationError::invalid_validation(format!(
+                    "Task {} has no attempt {}",
+                    task_id, attempt
+                )))
+            })
+        };
+        Ok(attempt_diff::diff_attempts(find(from)?, find(to)?))
+    }
+
+    /// 清理过期任务
+    pub async fn cleanup_expired_tasks(&self, older_than: DateTime<Utc>) -> AppResult<u64> {
+        self.task_repository.cleanup_expired_tasks(older_than).await
+    }
+
+    /// 将结束较久的任务分批移入冷存储，直到没有满足条件的任务，返回移动的任务数
+    pub async fn archive_old_tasks(&self) -> AppResult<u64> {
+        let Some((after_days, batch_size)) = self.cold_storage else {
+            return Ok(0);
+        };
+        let older_than = Utc::now() - chrono::Duration::days(after_days as i64);
+        let mut total = 0;
+        loop {
+            let archived = self.task_repository.archive_tasks(older_than, batch_size).await?;
+            self.archive_artifacts(&archived).await;
+            total += archived.len() as u64;
+            if archived.len() < batch_size as usize {
+                return Ok(total);
+            }
+        }
+    }
+
+    /// 将已归档任务的产物移到归档目录，失败的产物留在原位置，下载时仍能找到
+    async fn archive_artifacts(&self, task_ids: &[TaskId]) {
+        let Some(artifact_store) = &self.artifact_store else {
+            return;
+        };
+        for task_id in task_ids {
+            if let Err(e) = artifact_store.archive_task(task_id).await {
+                tracing::warn!("Failed to archive artifacts of task {}: {}", task_id, e);
+            }
+        }
     }
 
-    /// 重试失败任务
+    /// 重试失败任务，返回重新排队的任务数
     pub async fn retry_failed_tasks(&self) -> AppResult<u64> {
-        self.task_repository.retry_failed_tasks(self.max_retries).await
+        let task_ids = self.task_repository.retry_failed_tasks(self.max_retries).await?;
+        if self.message_queue.is_some() {
+            for task_id in &task_ids {
+                if let Some(task) = self.task_repository.get_task(task_id).await? {
+                    self.publish_requeued(&task).await;
+                }
+            }
+        }
+        Ok(task_ids.len() as u64)
     }
 
     /// 检查任务是否过期
@@ -257,18 +1493,36 @@ impl TaskService {
     }
 
     /// 处理超时任务
+    ///
+    /// 本进程分发的任务按单调截止时间判断，其余任务（其他节点分发或重启前开始执行）按创建时间的墙上时间判断。
     pub async fn handle_timeout_tasks(&self) -> AppResult<u64> {
         // 查找所有超时的任务
         let filter = TaskFilter::new()
             .with_status(TaskStatus::Working)
-            .with_created_before(Utc::now() - chrono::Duration::seconds(self.task_timeout as i64));
+            .with_created_before(self.clock.now() - chrono::Duration::seconds(self.task_timeout as i64));
 
         let (tasks, _) = self.list_tasks(filter).await?;
-        let mut handled = 0;
+        let (mut candidates, expired) = {
+            let deadlines = self.execution_deadlines.lock().unwrap();
+            let candidates: Vec<TaskId> = tasks
+                .iter()
+                .map(|task| task.id)
+                .filter(|id| !deadlines.contains_key(id))
+                .collect();
+            let expired: Vec<TaskId> = deadlines
+                .iter()
+                .filter(|(_, deadline)| deadline.is_expired(self.clock.as_ref()))
+                .map(|(id, _)| *id)
+                .collect();
+            (candidates, expired)
+        };
+        candidates.extend(expired);
 
-        for task in tasks {
-            // 标记任务为失败
-            if let Err(_) = self.fail_task(&task.id, "Task timeout".to_string()).await {
+        let mut handled = 0;
+        for task_id in candidates {
+            // 标记任务为失败（已离开执行状态的任务只清理截止时间）
+            self.execution_deadlines.lock().unwrap().remove(&task_id);
+            if self.fail_task(&task_id, "Task timeout".to_string(), Some(FailureCategory::Timeout)).await.is_err() {
                 continue;
             }
             handled += 1;
@@ -276,6 +1530,37 @@ impl TaskService {
 
         Ok(handled)
     }
+
+    /// 取消超过有效期仍在等待的任务，返回取消的任务数
+    pub async fn expire_stale_tasks(&self) -> AppResult<u64> {
+        let now = Utc::now();
+        let filter = TaskFilter::new()
+            .with_status(TaskStatus::Waiting)
+            .with_expires_before(now);
+
+        let (tasks, _) = self.list_tasks(filter).await?;
+        let mut expired = 0;
+
+        for mut task in tasks {
+            if !task.is_past_expiry(now) || task.expire().is_err() {
+                continue;
+            }
+            // 期间被获取或修改的任务跳过
+            if let Err(e) = self.task_repository.update_task(&task).await {
+                tracing::debug!("Skipped expiring task {}: {}", task.id, e);
+                continue;
+            }
+
+            let history = TaskHistory::new(task.id, task.status, None)
+                .with_detail("cancel_reason".to_string(), serde_json::json!(CancelReason::Expired));
+            self.record_history(history).await?;
+            self.export_event(TaskEventType::Cancelled, &task);
+            tracing::info!("Task {} expired before it was started", task.id);
+            expired += 1;
+        }
+
+        Ok(expired)
+    }
 }
 
 /// 任务调度器
@@ -283,6 +1568,7 @@ pub struct TaskScheduler {
     task_service: Arc<TaskService>,
     cleanup_interval: u64,
     timeout_check_interval: u64,
+    leader_elector: Option<Arc<LeaderElector>>,
 }
 
 impl TaskScheduler {
@@ -295,33 +1581,65 @@ impl TaskScheduler {
             task_service,
             cleanup_interval,
             timeout_check_interval,
+            leader_elector: None,
         }
     }
 
+    /// 设置领导者选举器，集群模式下仅领导者执行调度任务
+    pub fn with_leader_elector(mut self, leader_elector: Arc<LeaderElector>) -> Self {
+        self.leader_elector = Some(leader_elector);
+        self
+    }
+
     /// 启动调度器
     pub async fn start(&self) -> AppResult<()> {
         let task_service = self.task_service.clone();
+        let leader_elector = self.leader_elector.clone();
         
         // 启动清理任务
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(task_service.cleanup_interval));
             loop {
                 interval.tick().await;
-                if let Err(e) = task_service.cleanup_expired_tasks(Utc::now() - chrono::Duration::days(30)).await {
+                if !is_leader(&leader_elector) {
+                    continue;
+                }
+                if task_service.cold_storage.is_some() {
+                    match task_service.archive_old_tasks().await {
+                        Ok(0) => {}
+                        Ok(archived) => tracing::info!("Moved {} finished tasks to cold storage", archived),
+                        Err(e) => tracing::error!("Failed to move tasks to cold storage: {}", e),
+                    }
+                } else if let Err(e) = task_service.cleanup_expired_tasks(Utc::now() - chrono::Duration::days(30)).await {
                     tracing::error!("Failed to cleanup expired tasks: {}", e);
                 }
+                match task_service.reconcile_statistics().await {
+                    Ok(0) => {}
+                    Ok(repaired) => tracing::warn!("Repaired {} drifted task counters", repaired),
+                    Err(e) => tracing::error!("Failed to reconcile task counters: {}", e),
+                }
             }
         });
 
         // 启动超时检查任务
         let task_service = self.task_service.clone();
+        let leader_elector = self.leader_elector.clone();
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(task_service.timeout_check_interval));
             loop {
                 interval.tick().await;
+                if !is_leader(&leader_elector) {
+                    continue;
+                }
                 if let Err(e) = task_service.handle_timeout_tasks().await {
                     tracing::error!("Failed to handle timeout tasks: {}", e);
                 }
+                if let Err(e) = task_service.expire_stale_tasks().await {
+                    tracing::error!("Failed to expire stale tasks: {}", e);
+                }
+                if let Err(e) = task_service.reject_overdue_approvals().await {
+                    tracing::error!("Failed to auto-reject overdue approvals: {}", e);
+                }
             }
         });
 
@@ -443,6 +1761,7 @@ impl TaskValidator {
 pub struct TaskMonitor {
     task_service: Arc<TaskService>,
     metrics_interval: u64,
+    leader_elector: Option<Arc<LeaderElector>>,
 }
 
 impl TaskMonitor {
@@ -450,17 +1769,28 @@ impl TaskMonitor {
         Self {
             task_service,
             metrics_interval,
+            leader_elector: None,
         }
     }
 
+    /// 设置领导者选举器，集群模式下仅领导者执行监控任务
+    pub fn with_leader_elector(mut self, leader_elector: Arc<LeaderElector>) -> Self {
+        self.leader_elector = Some(leader_elector);
+        self
+    }
+
     /// 启动监控
     pub async fn start(&self) -> AppResult<()> {
         let task_service = self.task_service.clone();
+        let leader_elector = self.leader_elector.clone();
         
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(task_service.metrics_interval));
             loop {
                 interval.tick().await;
+                if !is_leader(&leader_elector) {
+                    continue;
+                }
                 
                 match task_service.get_statistics().await {
                     Ok(stats) => {
@@ -472,11 +1802,19 @@ impl TaskMonitor {
                             active_tasks = stats.active_tasks,
                             "Task statistics"
                         );
+                        if let Some(metrics) = &task_service.metrics {
+                            metrics.set_active_tasks(stats.active_tasks);
+                            metrics.set_queue_size(stats.waiting_tasks);
+                        }
                     }
                     Err(e) => {
                         tracing::error!("Failed to get task statistics: {}", e);
                     }
                 }
+
+                if let Err(e) = task_service.evaluate_sla().await {
+                    tracing::error!("Failed to evaluate SLA: {}", e);
+                }
             }
         });
 
@@ -484,11 +1822,17 @@ impl TaskMonitor {
     }
 }
 
+/// 未启用选举时视为单节点领导者
+fn is_leader(leader_elector: &Option<Arc<LeaderElector>>) -> bool {
+    leader_elector.as_ref().map(|e| e.is_leader()).unwrap_or(true)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::infrastructure::{TaskRepository, SqliteLockManager};
     use crate::domain::TaskPriority;
+    use crate::models::{FilterExpr, TaskSort};
     use std::collections::HashMap;
     use std::sync::{Arc, Mutex};
 
@@ -496,12 +1840,14 @@ mod tests {
     #[derive(Clone)]
     struct MockTaskRepository {
         tasks: Arc<Mutex<HashMap<TaskId, Task>>>,
+        pipeline_runs: Arc<Mutex<HashMap<String, PipelineRun>>>,
     }
 
     impl MockTaskRepository {
         fn new() -> Self {
             Self {
                 tasks: Arc::new(Mutex::new(HashMap::new())),
+                pipeline_runs: Arc::new(Mutex::new(HashMap::new())),
             }
         }
     }
@@ -509,9 +1855,19 @@ mod tests {
     #[async_trait::async_trait]
     impl TaskRepository for MockTaskRepository {
         async fn create_task(&self, task: &Task) -> AppResult<TaskId> {
+            self.tasks.lock().unwrap().insert(task.id, task.clone());
             Ok(task.id)
         }
 
+        async fn save_pipeline_run(&self, run: &PipelineRun) -> AppResult<()> {
+            self.pipeline_runs.lock().unwrap().insert(run.run_id.clone(), run.clone());
+            Ok(())
+        }
+
+        async fn get_pipeline_run(&self, run_id: &str) -> AppResult<Option<PipelineRun>> {
+            Ok(self.pipeline_runs.lock().unwrap().get(run_id).cloned())
+        }
+
         async fn get_task(&self, task_id: &TaskId) -> AppResult<Option<Task>> {
             let tasks = self.tasks.lock().unwrap();
             Ok(tasks.get(task_id).cloned())
@@ -527,12 +1883,30 @@ mod tests {
             Ok(())
         }
 
-        async fn get_next_task(&self, _work_directory: &str, _worker_id: &str) -> AppResult<Option<Task>> {
+        async fn get_next_task(
+            &self,
+            _work_directory: &str,
+            _worker_id: &str,
+            _capabilities: Option<&[String]>,
+        ) -> AppResult<Option<Task>> {
             Ok(None)
         }
 
-        async fn list_tasks(&self, _filter: &TaskFilter) -> AppResult<(Vec<Task>, u64)> {
-            Ok((vec![], 0))
+        async fn list_tasks(&self, filter: &TaskFilter) -> AppResult<(Vec<Task>, u64)> {
+            let mut tasks: Vec<Task> = self
+                .tasks
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|task| filter.status.is_none_or(|status| task.status == status))
+                .filter(|task| filter.expression.as_ref().is_none_or(|expression| expression.matches(task)))
+                .cloned()
+                .collect();
+            filter.sort.sort(&mut tasks);
+            let total = tasks.len() as u64;
+            let offset = filter.offset.unwrap_or(0).max(0) as usize;
+            let limit = filter.limit.map_or(usize::MAX, |limit| limit.max(0) as usize);
+            Ok((tasks.into_iter().skip(offset).take(limit).collect(), total))
         }
 
         async fn get_statistics(&self) -> AppResult<TaskStatistics> {
@@ -547,12 +1921,20 @@ mod tests {
             Ok(vec![])
         }
 
+        async fn add_task_comment(&self, _comment: &TaskComment) -> AppResult<u64> {
+            Ok(1)
+        }
+
+        async fn get_task_comments(&self, _task_id: &TaskId) -> AppResult<Vec<TaskComment>> {
+            Ok(vec![])
+        }
+
         async fn cleanup_expired_tasks(&self, _older_than: DateTime<Utc>) -> AppResult<u64> {
             Ok(0)
         }
 
-        async fn retry_failed_tasks(&self, _max_retries: u32) -> AppResult<u64> {
-            Ok(0)
+        async fn retry_failed_tasks(&self, _max_retries: u32) -> AppResult<Vec<TaskId>> {
+            Ok(vec![])
         }
     }
 
@@ -561,19 +1943,23 @@ mod tests {
 
     #[async_trait::async_trait]
     impl crate::infrastructure::LockManager for MockLockManager {
-        async fn try_acquire(&self, _resource_id: &str, _owner_id: &str, _ttl_seconds: u64) -> AppResult<bool> {
+        async fn try_acquire(&self, _resource_id: &str, _owner_id: &str, _ttl_seconds: u64) -> task_store::StoreResult<bool> {
+            Ok(true)
+        }
+
+        async fn renew(&self, _resource_id: &str, _owner_id: &str, _ttl_seconds: u64) -> task_store::StoreResult<bool> {
             Ok(true)
         }
 
-        async fn release(&self, _resource_id: &str, _owner_id: &str) -> AppResult<bool> {
+        async fn release(&self, _resource_id: &str, _owner_id: &str) -> task_store::StoreResult<bool> {
             Ok(true)
         }
 
-        async fn check_lock(&self, _resource_id: &str) -> AppResult<Option<String>> {
+        async fn check_lock(&self, _resource_id: &str) -> task_store::StoreResult<Option<String>> {
             Ok(None)
         }
 
-        async fn cleanup_expired_locks(&self) -> AppResult<u64> {
+        async fn cleanup_expired_locks(&self) -> task_store::StoreResult<u64> {
             Ok(0)
         }
     }
@@ -589,6 +1975,10 @@ mod tests {
             prompt: "Test task".to_string(),
             priority: Some(TaskPriority::Medium),
             tags: Some(vec!["test".to_string()]),
+            metadata: None,
+            expires_at: None,
+            concurrency_group: None,
+            requires_approval: false,
         };
 
         let task = task_service.create_task(request).await.unwrap();
@@ -597,6 +1987,121 @@ mod tests {
         assert_eq!(task.tags.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_list_tasks_with_filter_expression() {
+        let task_repo = Arc::new(MockTaskRepository::new());
+        let lock_manager = Arc::new(MockLockManager);
+        let task_service = TaskService::new(task_repo, lock_manager, 3, 3600);
+
+        for (dir, priority) in [("/srv/api", TaskPriority::High), ("/srv/web", TaskPriority::High), ("/srv/api", TaskPriority::Low)] {
+            task_service.create_task(CreateTaskRequest {
+                work_directory: dir.to_string(),
+                prompt: "Filtered task".to_string(),
+                priority: Some(priority),
+                tags: None,
+                metadata: None,
+                expires_at: None,
+                concurrency_group: None,
+                requires_approval: false,
+            }).await.unwrap();
+        }
+
+        let filter = TaskFilter::new().with_expression(FilterExpr::parse("dir:/srv/api AND priority>=medium").unwrap());
+        let (tasks, total) = task_service.list_tasks(filter).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(tasks[0].work_directory.as_str(), "/srv/api");
+        assert_eq!(tasks[0].priority, TaskPriority::High);
+
+        let filter = TaskFilter::new().with_expression(FilterExpr::parse("NOT dir:/srv/api").unwrap()).with_limit(0);
+        let (tasks, total) = task_service.list_tasks(filter).await.unwrap();
+        assert_eq!((tasks.len(), total), (0, 1));
+
+        // 按排序规则分页
+        let filter = TaskFilter::new()
+            .with_sort(TaskSort::parse("priority:asc,work_directory:desc").unwrap())
+            .with_offset(1);
+        let (tasks, total) = task_service.list_tasks(filter).await.unwrap();
+        assert_eq!(total, 3);
+        let order: Vec<_> = tasks.iter().map(|t| (t.priority, t.work_directory.as_str())).collect();
+        assert_eq!(order, vec![(TaskPriority::High, "/srv/web"), (TaskPriority::High, "/srv/api")]);
+    }
+
+    #[tokio::test]
+    async fn test_time_ordered_task_ids() {
+        let task_repo = Arc::new(MockTaskRepository::new());
+        let lock_manager = Arc::new(MockLockManager);
+        let task_service = TaskService::new(task_repo, lock_manager, 3, 3600).with_time_ordered_ids(true);
+
+        let request = || CreateTaskRequest {
+            work_directory: "/test".to_string(),
+            prompt: "Test task".to_string(),
+            priority: None,
+            tags: None,
+            metadata: None,
+            expires_at: None,
+            concurrency_group: None,
+            requires_approval: false,
+        };
+        let first = task_service.create_task(request()).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        let second = task_service.create_task(request()).await.unwrap();
+        assert_eq!(first.id.as_uuid().get_version_num(), 7);
+        assert!(first.id.to_string() < second.id.to_string());
+
+        // ID中的时间戳与创建时间一致，v4和v7的ID都能解析
+        let (secs, _) = first.id.as_uuid().get_timestamp().unwrap().to_unix();
+        assert_eq!(secs as i64, first.created_at.timestamp());
+        assert_eq!(TaskId::from_str(&first.id.to_string()).unwrap(), first.id);
+        let legacy = TaskId::new();
+        assert_eq!(TaskId::from_str(&legacy.to_string()).unwrap().as_uuid().get_version_num(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_task() {
+        let task_repo = Arc::new(MockTaskRepository::new());
+        let lock_manager = Arc::new(MockLockManager);
+        let task_service = TaskService::new(task_repo, lock_manager, 3, 3600);
+        task_service.acquire_task(AcquireTaskRequest {
+            work_path: "/test".to_string(),
+            worker_id: "worker-1".to_string(),
+            capabilities: Some(vec!["rust".to_string()]),
+        }).await.unwrap();
+
+        let request = |tag: &str, work_directory: &str| CreateTaskRequest {
+            work_directory: work_directory.to_string(),
+            prompt: "Test task".to_string(),
+            priority: Some(TaskPriority::High),
+            tags: Some(vec![tag.to_string()]),
+            metadata: None,
+            expires_at: None,
+            concurrency_group: None,
+            requires_approval: false,
+        };
+
+        let simulation = task_service.simulate_task(request("rust", "/test")).await.unwrap();
+        assert_eq!(simulation.task.priority, TaskPriority::High);
+        assert_eq!(simulation.matching_workers, vec!["worker-1".to_string()]);
+        assert!(simulation.warnings.is_empty());
+        assert!(task_service.get_task(&simulation.task.id).await.is_err());
+
+        let simulation = task_service.simulate_task(request("gpu", "/test")).await.unwrap();
+        assert!(simulation.matching_workers.is_empty());
+        assert_eq!(simulation.warnings.len(), 1);
+
+        assert!(task_service.simulate_task(request("rust", "relative/path")).await.is_err());
+
+        // 已有相同的未结束任务时报告重复，排空模式下与创建一样被拒绝
+        let existing = task_service.create_task(request("rust", "/test")).await.unwrap();
+        let simulation = task_service.simulate_task(request("rust", "/test")).await.unwrap();
+        assert_eq!(simulation.warnings, vec![format!("Duplicate of unfinished task {}", existing.id)]);
+
+        task_service.queue_control().drain();
+        assert!(matches!(
+            task_service.simulate_task(request("rust", "/test")).await,
+            Err(AppError::QueueDraining)
+        ));
+    }
+
     #[tokio::test]
     async fn test_complete_task() {
         let task_repo = Arc::new(MockTaskRepository::new());
@@ -626,4 +2131,918 @@ mod tests {
         let completed = task_service.complete_task(&task_id, request).await.unwrap();
         assert_eq!(completed.status, TaskStatus::Completed);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_duplicate_completion_is_idempotent() {
+        let task_repo = Arc::new(MockTaskRepository::new());
+        let lock_manager = Arc::new(MockLockManager);
+        let task_service = TaskService::new(task_repo.clone(), lock_manager, 3, 3600);
+
+        let mut task = Task::new(
+            WorkDirectory::new("/test".to_string()).unwrap(),
+            Prompt::new("Test task".to_string()).unwrap(),
+            TaskPriority::Medium,
+            vec![],
+        );
+        task.start(WorkerId::new("worker-1".to_string()).unwrap()).unwrap();
+        task_repo.update_task(&task).await.unwrap();
+
+        let request = || CompleteTaskRequest {
+            original_prompt: None,
+            result: Some(TaskResult::success("Completed".to_string())),
+        };
+
+        let first = task_service.complete_task(&task.id, request()).await.unwrap();
+        let second = task_service.complete_task(&task.id, request()).await.unwrap();
+        assert_eq!(second.status, TaskStatus::Completed);
+        assert_eq!(second.version, first.version);
+
+        // 不同的结果仍然视为非法转换
+        let conflicting = CompleteTaskRequest {
+            original_prompt: None,
+            result: Some(TaskResult::success("Other output".to_string())),
+        };
+        assert!(task_service.complete_task(&task.id, conflicting).await.is_err());
+        assert!(task_service.cancel_task(&task.id, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_creates_next_step_on_success() {
+        let task_repo = Arc::new(MockTaskRepository::new());
+        let lock_manager = Arc::new(MockLockManager);
+        let step = |name: &str, prompt: &str| crate::config::PipelineStepConfig {
+            name: name.to_string(),
+            prompt: prompt.to_string(),
+            ..Default::default()
+        };
+        let pipelines = PipelineRegistry::from_config(&HashMap::from([(
+            "review".to_string(),
+            crate::config::PipelineConfig {
+                steps: vec![
+                    step("analyze", "Analyze {{input}}"),
+                    crate::config::PipelineStepConfig {
+                        when: Some("result.details.issues > 0".to_string()),
+                        ..step("fix", "Fix: {{previous_output}}")
+                    },
+                    step("summarize", "Summarize"),
+                ],
+                ..Default::default()
+            },
+        )]))
+        .unwrap();
+        let task_service =
+            TaskService::new(task_repo.clone(), lock_manager, 3, 3600).with_pipelines(Arc::new(pipelines));
+
+        let missing = task_service.run_pipeline("missing", PipelineRunRequest::default()).await;
+        assert!(matches!(missing, Err(AppError::PipelineNotFound(_))));
+        assert!(matches!(task_service.get_pipeline_run("missing").await, Err(AppError::PipelineRunNotFound(_))));
+
+        let request = PipelineRunRequest {
+            work_directory: "/repo".to_string(),
+            input: Some("lib.rs".to_string()),
+            ..PipelineRunRequest::default()
+        };
+        let start = task_service.run_pipeline("review", request.clone()).await.unwrap();
+        let first = start.task.unwrap();
+        assert_eq!(first.prompt.as_str(), "Analyze lib.rs");
+        let run_id = start.run.run_id;
+
+        let tasks_for_run = |run_id: &str| {
+            let tasks = task_repo.tasks.lock().unwrap();
+            let mut tasks: Vec<Task> = tasks
+                .values()
+                .filter(|t| pipeline::PipelineProgress::from_task(t).is_some_and(|p| p.run_id == run_id))
+                .cloned()
+                .collect();
+            tasks.sort_by_key(|t| pipeline::PipelineProgress::from_task(t).map(|p| p.executed));
+            tasks
+        };
+        let complete = |task: &Task, result: TaskResult| {
+            let mut task = task.clone();
+            task.start(WorkerId::new("worker-1".to_string()).unwrap()).unwrap();
+            let task_repo = task_repo.clone();
+            let task_service = &task_service;
+            async move {
+                task_repo.update_task(&task).await.unwrap();
+                task_service
+                    .complete_task(&task.id, CompleteTaskRequest { original_prompt: None, result: Some(result) })
+                    .await
+                    .unwrap();
+            }
+        };
+
+        let issues = |n: i32| TaskResult::success(format!("{} issues", n)).with_detail("issues".to_string(), n.into());
+        complete(&first, issues(2)).await;
+        let tasks = tasks_for_run(&run_id);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[1].prompt.as_str(), "Fix: 2 issues");
+        assert_eq!(tasks[1].status, TaskStatus::Waiting);
+
+        // 以失败结果完成时流水线停止
+        complete(&tasks[1], TaskResult::failed("could not fix".to_string())).await;
+        assert_eq!(tasks_for_run(&run_id).len(), 2);
+        let run = task_service.get_pipeline_run(&run_id).await.unwrap();
+        assert_eq!(run.status, PipelineRunStatus::Stopped);
+        assert_eq!(
+            run.steps.iter().map(|s| (s.name.as_str(), s.status)).collect::<Vec<_>>(),
+            vec![("analyze", TaskStatus::Completed), ("fix", TaskStatus::Completed)]
+        );
+
+        // 条件不成立时跳过修复步骤
+        let start = task_service.run_pipeline("review", request).await.unwrap();
+        complete(start.task.as_ref().unwrap(), issues(0)).await;
+        let tasks = tasks_for_run(&start.run.run_id);
+        assert_eq!(tasks[1].prompt.as_str(), "Summarize\n\nPrevious step output:\n0 issues");
+        complete(&tasks[1], TaskResult::success("done".to_string())).await;
+        let run = task_service.get_pipeline_run(&start.run.run_id).await.unwrap();
+        assert_eq!(run.status, PipelineRunStatus::Completed);
+        assert_eq!(run.skipped, vec!["fix".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_update_task_fields() {
+        let task_repo = Arc::new(MockTaskRepository::new());
+        let lock_manager = Arc::new(MockLockManager);
+        let task_service = TaskService::new(task_repo.clone(), lock_manager, 3, 3600);
+
+        let mut task = Task::new(
+            WorkDirectory::new("/test".to_string()).unwrap(),
+            Prompt::new("Test task".to_string()).unwrap(),
+            TaskPriority::Low,
+            vec![],
+        );
+        task.metadata.insert("owner".to_string(), serde_json::json!("alice"));
+        task_repo.update_task(&task).await.unwrap();
+
+        let update = UpdateTaskRequest {
+            priority: Some(TaskPriority::High),
+            tags: Some(vec!["urgent".to_string()]),
+            metadata: Some(HashMap::from([
+                ("owner".to_string(), serde_json::Value::Null),
+                ("team".to_string(), serde_json::json!("infra")),
+            ])),
+            max_retries: Some(5),
+        };
+        let updated = task_service.update_task_fields(&task.id, update).await.unwrap();
+        assert_eq!(updated.priority, TaskPriority::High);
+        assert_eq!(updated.tags[0].as_str(), "urgent");
+        assert!(!updated.metadata.contains_key("owner"));
+        assert_eq!(updated.metadata["team"], "infra");
+        assert_eq!(updated.max_retries, 5);
+        assert_eq!(updated.version, task.version + 1);
+
+        // 终态任务不允许修改
+        task_service.cancel_task(&task.id, None).await.unwrap();
+        let update = UpdateTaskRequest {
+            priority: Some(TaskPriority::Low),
+            ..Default::default()
+        };
+        assert!(task_service.update_task_fields(&task.id, update).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_flagged_work_directory_can_be_acquired() {
+        use crate::infrastructure::{SqliteTaskRepository, SqliteLockManager};
+
+        let config = |action| crate::config::WorkDirectoryPolicyConfig {
+            enabled: true,
+            allowed_prefixes: vec!["/srv/repos".to_string()],
+            action,
+            ..Default::default()
+        };
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = Arc::new(SqliteTaskRepository::with_pool(pool.clone()).await.unwrap());
+        let lock_manager = Arc::new(SqliteLockManager::with_pool(pool).await);
+        let policy = WorkDirectoryPolicy::from_config(&config(crate::config::PolicyAction::Flag)).unwrap();
+        let task_service = TaskService::new(repo.clone(), lock_manager.clone(), 3, 3600)
+            .with_path_policy(Arc::new(policy));
+
+        let task = task_service.create_task(CreateTaskRequest {
+            work_directory: "/test".to_string(),
+            prompt: "Test task".to_string(),
+            priority: None,
+            tags: None,
+            metadata: None,
+            expires_at: None,
+            concurrency_group: None,
+            requires_approval: false,
+        }).await.unwrap();
+        assert!(task.metadata.contains_key(POLICY_VIOLATION_KEY));
+
+        let acquire = || AcquireTaskRequest {
+            work_path: "/test".to_string(),
+            worker_id: "worker-1".to_string(),
+            capabilities: None,
+        };
+        let acquired = task_service.acquire_task(acquire()).await.unwrap().unwrap();
+        assert_eq!(acquired.id, task.id);
+
+        // 拒绝模式下同一目录无法获取任务
+        let policy = WorkDirectoryPolicy::from_config(&config(crate::config::PolicyAction::Reject)).unwrap();
+        let task_service = TaskService::new(repo, lock_manager, 3, 3600).with_path_policy(Arc::new(policy));
+        assert!(task_service.acquire_task(acquire()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reserved_metadata_keys_rejected() {
+        let task_repo = Arc::new(MockTaskRepository::new());
+        let lock_manager = Arc::new(MockLockManager);
+        let task_service = TaskService::new(task_repo.clone(), lock_manager, 3, 3600);
+        let request = |metadata: HashMap<String, serde_json::Value>| CreateTaskRequest {
+            work_directory: "/test".to_string(),
+            prompt: "Test task".to_string(),
+            priority: None,
+            tags: None,
+            metadata: Some(metadata),
+            expires_at: None,
+            concurrency_group: None,
+            requires_approval: false,
+        };
+
+        let forged = HashMap::from([(TRANSITION_HASH_KEY.to_string(), serde_json::json!("complete:forged"))]);
+        let error = task_service.create_task(request(forged)).await.unwrap_err();
+        assert!(error.to_string().contains(TRANSITION_HASH_KEY));
+
+        let task = task_service
+            .create_task(request(HashMap::from([("team".to_string(), serde_json::json!("infra"))])))
+            .await
+            .unwrap();
+        for key in [SECRET_FINDINGS_KEY, POLICY_VIOLATION_KEY, TRANSITION_HASH_KEY] {
+            let update = UpdateTaskRequest {
+                metadata: Some(HashMap::from([(key.to_string(), serde_json::Value::Null)])),
+                ..Default::default()
+            };
+            assert!(task_service.update_task_fields(&task.id, update).await.is_err(), "{}", key);
+        }
+        assert_eq!(task_service.get_task(&task.id).await.unwrap().version, task.version);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_schema_validation() {
+        let task_repo = Arc::new(MockTaskRepository::new());
+        let lock_manager = Arc::new(MockLockManager);
+        let task_service = TaskService::new(task_repo.clone(), lock_manager, 3, 3600);
+        let schema = serde_json::json!({
+            "type": "object",
+            "required": ["team"],
+            "properties": {"team": {"type": "string"}},
+        });
+        task_service.register_metadata_schema("/srv/infra".to_string(), schema, true).await.unwrap();
+
+        let request = |metadata: serde_json::Value| CreateTaskRequest {
+            work_directory: "/srv/infra/deploy".to_string(),
+            prompt: "Test task".to_string(),
+            priority: None,
+            tags: None,
+            metadata: Some(serde_json::from_value(metadata).unwrap()),
+            expires_at: None,
+            concurrency_group: None,
+            requires_approval: false,
+        };
+        let error = task_service.create_task(request(serde_json::json!({"owner": "alice"}))).await.unwrap_err();
+        assert!(matches!(error, AppError::MetadataSchemaViolation { ref namespace, .. } if namespace == "/srv/infra"));
+
+        let task = task_service.create_task(request(serde_json::json!({"team": "infra"}))).await.unwrap();
+        task_repo.update_task(&task).await.unwrap();
+        let update = UpdateTaskRequest {
+            metadata: Some(HashMap::from([("team".to_string(), serde_json::json!(1))])),
+            ..Default::default()
+        };
+        let error = task_service.update_task_fields(&task.id, update).await.unwrap_err();
+        assert!(matches!(error, AppError::MetadataSchemaViolation { .. }));
+        assert_eq!(task_service.get_task(&task.id).await.unwrap().metadata["team"], "infra");
+
+        assert!(task_service.delete_metadata_schema("/srv/infra").await.unwrap());
+        assert!(task_service.create_task(request(serde_json::json!({"owner": "alice"}))).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_completion() {
+        let task_repo = Arc::new(MockTaskRepository::new());
+        let lock_manager = Arc::new(MockLockManager);
+        let task_service = Arc::new(TaskService::new(task_repo.clone(), lock_manager, 3, 3600));
+
+        let task = Task::new(
+            WorkDirectory::new("/test".to_string()).unwrap(),
+            Prompt::new("Test task".to_string()).unwrap(),
+            TaskPriority::Medium,
+            vec![],
+        );
+        task_repo.update_task(&task).await.unwrap();
+
+        // 超时后返回当前状态
+        let waited = task_service.wait_for_completion(&task.id, Some(0)).await.unwrap();
+        assert_eq!(waited.status, TaskStatus::Waiting);
+
+        let waiter = {
+            let task_service = task_service.clone();
+            tokio::spawn(async move { task_service.wait_for_completion(&task.id, Some(60)).await })
+        };
+        while task_service.completion_waiters() == 0 {
+            tokio::task::yield_now().await;
+        }
+        task_service.cancel_task(&task.id, None).await.unwrap();
+
+        let waited = tokio::time::timeout(std::time::Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(waited.status, TaskStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_uses_monotonic_deadline() {
+        use crate::infrastructure::{SqliteTaskRepository, SqliteLockManager};
+        use crate::utils::clock::ManualClock;
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let clock = Arc::new(ManualClock::new());
+        let repo = Arc::new(SqliteTaskRepository::with_pool(pool.clone()).await.unwrap());
+        let lock_manager = Arc::new(SqliteLockManager::with_pool(pool).await);
+        let task_service = TaskService::new(repo.clone(), lock_manager, 0, 60).with_clock(clock.clone());
+
+        let request = CreateTaskRequest {
+            work_directory: "/test".to_string(),
+            prompt: "Test task".to_string(),
+            priority: None,
+            tags: None,
+            metadata: None,
+            expires_at: None,
+            concurrency_group: None,
+            requires_approval: false,
+        };
+        let task = task_service.create_task(request).await.unwrap();
+        let acquire = AcquireTaskRequest {
+            work_path: "/test".to_string(),
+            worker_id: "worker-1".to_string(),
+            capabilities: None,
+        };
+        assert_eq!(task_service.acquire_task(acquire).await.unwrap().unwrap().id, task.id);
+
+        // 系统时间向前跳变，按墙上时间已超时，但单调截止时间未到
+        clock.jump_wall_clock(chrono::Duration::hours(2));
+        assert_eq!(task_service.handle_timeout_tasks().await.unwrap(), 0);
+
+        // 系统时间回拨后墙上时间不会判定超时，单调截止时间到期后仍然处理
+        clock.jump_wall_clock(chrono::Duration::hours(-4));
+        clock.advance(std::time::Duration::from_secs(61));
+        assert_eq!(task_service.handle_timeout_tasks().await.unwrap(), 1);
+        assert_eq!(task_service.get_task(&task.id).await.unwrap().status, TaskStatus::Failed);
+        assert_eq!(task_service.handle_timeout_tasks().await.unwrap(), 0);
+
+        // 其他节点分发的任务没有单调截止时间，按墙上时间判断
+        let mut orphan = Task::new(
+            WorkDirectory::new("/test".to_string()).unwrap(),
+            Prompt::new("Orphan task".to_string()).unwrap(),
+            TaskPriority::High,
+            vec![],
+        );
+        orphan.created_at = Utc::now() - chrono::Duration::hours(1);
+        repo.create_task(&orphan).await.unwrap();
+        orphan.start(WorkerId::new("worker-2".to_string()).unwrap()).unwrap();
+        repo.update_task(&orphan).await.unwrap();
+        clock.jump_wall_clock(chrono::Duration::hours(2));
+        assert_eq!(task_service.handle_timeout_tasks().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expire_stale_tasks() {
+        use crate::infrastructure::{SqliteTaskRepository, SqliteLockManager};
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = Arc::new(SqliteTaskRepository::with_pool(pool.clone()).await.unwrap());
+        let lock_manager = Arc::new(SqliteLockManager::with_pool(pool).await);
+        let task_service = TaskService::new(repo.clone(), lock_manager, 3, 3600);
+
+        let request = |expires_at| CreateTaskRequest {
+            work_directory: "/test".to_string(),
+            prompt: "Test task".to_string(),
+            priority: None,
+            tags: None,
+            metadata: None,
+            expires_at,
+            concurrency_group: None,
+            requires_approval: false,
+        };
+        assert!(task_service.create_task(request(Some(Utc::now() - chrono::Duration::seconds(1)))).await.is_err());
+        let fresh = task_service.create_task(request(Some(Utc::now() + chrono::Duration::hours(1)))).await.unwrap();
+        let cancelled = task_service.create_task(request(None)).await.unwrap();
+        task_service.cancel_task(&cancelled.id, None).await.unwrap();
+
+        // 绕过创建校验写入已过期的任务，过期任务不会被获取
+        let mut stale = Task::new(
+            WorkDirectory::new("/test".to_string()).unwrap(),
+            Prompt::new("Stale task".to_string()).unwrap(),
+            TaskPriority::High,
+            vec![],
+        );
+        stale.expires_at = Some(Utc::now() - chrono::Duration::minutes(5));
+        repo.create_task(&stale).await.unwrap();
+        let acquired = repo.get_next_task("/test", "worker-1", None).await.unwrap();
+        assert_eq!(acquired.unwrap().id, fresh.id);
+
+        assert_eq!(task_service.expire_stale_tasks().await.unwrap(), 1);
+        assert_eq!(task_service.expire_stale_tasks().await.unwrap(), 0);
+
+        let expired = task_service.get_task(&stale.id).await.unwrap();
+        assert_eq!(expired.status, TaskStatus::Cancelled);
+        assert_eq!(expired.cancel_reason, Some(CancelReason::Expired));
+        assert_eq!(task_service.get_task(&fresh.id).await.unwrap().status, TaskStatus::Working);
+
+        let (tasks, total) = task_service
+            .list_tasks(TaskFilter::new().with_cancel_reason(CancelReason::Expired))
+            .await
+            .unwrap();
+        assert_eq!((tasks[0].id, total), (stale.id, 1));
+
+        let stats = task_service.get_statistics().await.unwrap();
+        assert_eq!((stats.cancelled_tasks, stats.expired_tasks), (2, 1));
+        assert_eq!(task_service.reconcile_statistics().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_groups() {
+        use crate::infrastructure::{SqliteTaskRepository, SqliteLockManager};
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = Arc::new(SqliteTaskRepository::with_pool(pool.clone()).await.unwrap());
+        let lock_manager = Arc::new(SqliteLockManager::with_pool(pool).await);
+        let task_service = TaskService::new(repo.clone(), lock_manager.clone(), 3, 3600);
+
+        let create = |work_directory: &str, group: Option<&str>| CreateTaskRequest {
+            work_directory: work_directory.to_string(),
+            prompt: "Deploy".to_string(),
+            priority: None,
+            tags: None,
+            metadata: None,
+            expires_at: None,
+            concurrency_group: group.map(str::to_string),
+            requires_approval: false,
+        };
+        let acquire = |work_path: &str| AcquireTaskRequest {
+            work_path: work_path.to_string(),
+            worker_id: "worker-1".to_string(),
+            capabilities: None,
+        };
+        assert!(task_service.create_task(create("/a", Some("deploy prod"))).await.is_err());
+        let first = task_service.create_task(create("/a", Some("deploy-prod"))).await.unwrap();
+        let second = task_service.create_task(create("/b", Some("deploy-prod"))).await.unwrap();
+        let other = task_service.create_task(create("/b", None)).await.unwrap();
+
+        // 组内已有任务在执行时，其他工作目录跳过该组的任务
+        assert_eq!(task_service.acquire_task(acquire("/a")).await.unwrap().unwrap().id, first.id);
+        assert_eq!(task_service.acquire_task(acquire("/b")).await.unwrap().unwrap().id, other.id);
+        assert!(task_service.acquire_task(acquire("/b")).await.unwrap().is_none());
+        assert_eq!(
+            lock_manager.check_lock("concurrency-group:deploy-prod").await.unwrap(),
+            Some(first.id.to_string())
+        );
+
+        task_service.complete_task(&first.id, CompleteTaskRequest { original_prompt: None, result: None }).await.unwrap();
+        let acquired = task_service.acquire_task(acquire("/b")).await.unwrap().unwrap();
+        assert_eq!((acquired.id, acquired.concurrency_group.as_deref()), (second.id, Some("deploy-prod")));
+
+        // 锁被其他实例占用时任务放回队列
+        let migration = task_service.create_task(create("/c", Some("db"))).await.unwrap();
+        assert!(lock_manager.try_acquire("concurrency-group:db", "other-instance", 60).await.unwrap());
+        assert!(task_service.acquire_task(acquire("/c")).await.unwrap().is_none());
+        assert_eq!(task_service.get_task(&migration.id).await.unwrap().status, TaskStatus::Waiting);
+        assert!(matches!(
+            task_service.start_task(&migration.id, "worker-2".to_string()).await,
+            Err(AppError::ConcurrencyGroupBusy(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_approval_workflow() {
+        use crate::infrastructure::{SqliteTaskRepository, SqliteLockManager};
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = Arc::new(SqliteTaskRepository::with_pool(pool.clone()).await.unwrap());
+        let lock_manager = Arc::new(SqliteLockManager::with_pool(pool).await);
+        let policy = ApprovalPolicy::from_config(&crate::config::ApprovalConfig {
+            approvers: HashMap::new(),
+            auto_reject_after: 60,
+        })
+        .unwrap();
+        let task_service = TaskService::new(repo.clone(), lock_manager, 3, 3600)
+            .with_approval_policy(Arc::new(policy));
+
+        let create = || CreateTaskRequest {
+            work_directory: "/deploy".to_string(),
+            prompt: "Rotate production credentials".to_string(),
+            priority: None,
+            tags: None,
+            metadata: None,
+            expires_at: None,
+            concurrency_group: None,
+            requires_approval: true,
+        };
+        let acquire = || AcquireTaskRequest {
+            work_path: "/deploy".to_string(),
+            worker_id: "worker-1".to_string(),
+            capabilities: None,
+        };
+
+        // 待审批任务不会被获取
+        let approved = task_service.create_task(create()).await.unwrap();
+        assert_eq!(approved.status, TaskStatus::PendingApproval);
+        assert!(task_service.acquire_task(acquire()).await.unwrap().is_none());
+        assert!(task_service.start_task(&approved.id, "worker-1".to_string()).await.is_err());
+
+        let task = task_service.approve_task(&approved.id, "alice".to_string(), Some("LGTM".to_string())).await.unwrap();
+        assert_eq!(task.status, TaskStatus::Waiting);
+        assert!(task_service.approve_task(&approved.id, "alice".to_string(), None).await.is_err());
+        let history = task_service.get_task_history(&approved.id).await.unwrap();
+        assert!(history.iter().any(|h| h.details.get("approver") == Some(&serde_json::json!("alice"))));
+        assert_eq!(task_service.acquire_task(acquire()).await.unwrap().unwrap().id, approved.id);
+
+        let rejected = task_service.create_task(create()).await.unwrap();
+        let task = task_service.reject_task(&rejected.id, "bob".to_string(), None).await.unwrap();
+        assert_eq!((task.status, task.cancel_reason), (TaskStatus::Cancelled, Some(CancelReason::Rejected)));
+        assert_eq!(task.error_message.as_deref(), Some("Rejected by bob"));
+
+        // 超过审批时限的任务自动拒绝
+        let pending = task_service.create_task(create()).await.unwrap();
+        let mut overdue = Task::new(
+            WorkDirectory::new("/deploy".to_string()).unwrap(),
+            Prompt::new("Overdue task".to_string()).unwrap(),
+            TaskPriority::High,
+            vec![],
+        );
+        overdue.status = TaskStatus::PendingApproval;
+        overdue.created_at = Utc::now() - chrono::Duration::minutes(5);
+        repo.create_task(&overdue).await.unwrap();
+
+        assert_eq!(task_service.reject_overdue_approvals().await.unwrap(), 1);
+        assert_eq!(task_service.reject_overdue_approvals().await.unwrap(), 0);
+        assert_eq!(task_service.get_task(&overdue.id).await.unwrap().cancel_reason, Some(CancelReason::Rejected));
+        assert_eq!(task_service.get_task(&pending.id).await.unwrap().status, TaskStatus::PendingApproval);
+
+        let stats = task_service.get_statistics().await.unwrap();
+        assert_eq!((stats.pending_approval_tasks, stats.cancelled_tasks), (1, 2));
+        assert_eq!(task_service.reconcile_statistics().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_clone_task() {
+        use crate::infrastructure::{SqliteTaskRepository, SqliteLockManager};
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = Arc::new(SqliteTaskRepository::with_pool(pool.clone()).await.unwrap());
+        let lock_manager = Arc::new(SqliteLockManager::with_pool(pool).await);
+        let task_service = TaskService::new(repo, lock_manager, 3, 3600);
+
+        let source = task_service.create_task(CreateTaskRequest {
+            work_directory: "/repo/app".to_string(),
+            prompt: "Run the benchmark".to_string(),
+            priority: Some(TaskPriority::Low),
+            tags: Some(vec!["bench".to_string()]),
+            metadata: Some(HashMap::from([
+                ("branch".to_string(), serde_json::json!("main")),
+                ("runs".to_string(), serde_json::json!(3)),
+            ])),
+            expires_at: Some(Utc::now() + chrono::Duration::hours(1)),
+            concurrency_group: Some("bench".to_string()),
+            requires_approval: false,
+        }).await.unwrap();
+        task_service.cancel_task(&source.id, None).await.unwrap();
+
+        // 终态任务也可以克隆，未覆盖的字段沿用源任务
+        let clone = task_service.clone_task(&source.id, CloneTaskRequest {
+            priority: Some(TaskPriority::High),
+            metadata: Some(HashMap::from([
+                ("runs".to_string(), serde_json::Value::Null),
+                ("profile".to_string(), serde_json::json!("release")),
+            ])),
+            ..Default::default()
+        }).await.unwrap();
+        assert_ne!(clone.id, source.id);
+        assert_eq!(clone.status, TaskStatus::Waiting);
+        assert_eq!((clone.work_directory.as_str(), clone.prompt.as_str()), ("/repo/app", "Run the benchmark"));
+        assert_eq!(clone.priority, TaskPriority::High);
+        assert_eq!(clone.tags, source.tags);
+        assert_eq!(clone.concurrency_group.as_deref(), Some("bench"));
+        assert_eq!(clone.expires_at, None);
+        assert_eq!(clone.get_metadata(CLONED_FROM_KEY), Some(&serde_json::json!(source.id.to_string())));
+        assert_eq!(clone.get_metadata("branch"), Some(&serde_json::json!("main")));
+        assert_eq!(clone.get_metadata("profile"), Some(&serde_json::json!("release")));
+        assert!(clone.get_metadata("runs").is_none());
+        assert!(clone.get_metadata(TRANSITION_HASH_KEY).is_none());
+
+        // 需要审批的任务的克隆同样需要审批
+        let gated = task_service.create_task(CreateTaskRequest {
+            work_directory: "/repo/app".to_string(),
+            prompt: "Deploy".to_string(),
+            priority: None,
+            tags: None,
+            metadata: None,
+            expires_at: None,
+            concurrency_group: None,
+            requires_approval: true,
+        }).await.unwrap();
+        task_service.approve_task(&gated.id, "alice".to_string(), None).await.unwrap();
+        let clone = task_service.clone_task(&gated.id, CloneTaskRequest::default()).await.unwrap();
+        assert_eq!(clone.status, TaskStatus::PendingApproval);
+
+        let invalid = CloneTaskRequest { prompt: Some(String::new()), ..Default::default() };
+        assert!(task_service.clone_task(&source.id, invalid).await.is_err());
+        assert!(matches!(
+            task_service.clone_task(&TaskId::new(), CloneTaskRequest::default()).await,
+            Err(AppError::TaskNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failure_classification() {
+        use crate::infrastructure::{SqliteTaskRepository, SqliteLockManager};
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = Arc::new(SqliteTaskRepository::with_pool(pool.clone()).await.unwrap());
+        let lock_manager = Arc::new(SqliteLockManager::with_pool(pool).await);
+        let task_service = TaskService::new(repo, lock_manager, 1, 3600);
+
+        let create = || CreateTaskRequest {
+            work_directory: "/repo/app".to_string(),
+            prompt: "Run the test suite".to_string(),
+            priority: None,
+            tags: None,
+            metadata: None,
+            expires_at: None,
+            concurrency_group: None,
+            requires_approval: false,
+        };
+
+        // 重新排队的失败只记录在执行历史中，最终失败时才写入任务
+        let task = task_service.create_task(create()).await.unwrap();
+        task_service.start_task(&task.id, "worker-1".to_string()).await.unwrap();
+        let requeued = task_service.fail_task(&task.id, "Killed: out of memory".to_string(), None).await.unwrap();
+        assert_eq!((requeued.status, requeued.failure_category), (TaskStatus::Waiting, None));
+        task_service.start_task(&task.id, "worker-1".to_string()).await.unwrap();
+        let failed = task_service.fail_task(&task.id, "Killed: out of memory".to_string(), None).await.unwrap();
+        assert_eq!((failed.status, failed.failure_category), (TaskStatus::Failed, Some(FailureCategory::Oom)));
+        let attempts = task_service.get_task_attempts(&task.id).await.unwrap();
+        assert_eq!(attempts.len(), 2);
+        assert!(attempts.iter().all(|a| a.result.failure_category == Some(FailureCategory::Oom)));
+
+        // 执行器给出的类别优先于按错误信息归类
+        let task = task_service.create_task(create()).await.unwrap();
+        task_service.start_task(&task.id, "worker-1".to_string()).await.unwrap();
+        let mut result = TaskResult::failed("upstream returned 503".to_string());
+        result.failure_category = Some(FailureCategory::ToolError);
+        let reported = task_service
+            .complete_task(&task.id, CompleteTaskRequest { original_prompt: None, result: Some(result) })
+            .await
+            .unwrap();
+        assert_eq!(reported.failure_category, Some(FailureCategory::ToolError));
+
+        let (tasks, total) = task_service
+            .list_tasks(TaskFilter::new().with_failure_category(FailureCategory::Oom))
+            .await
+            .unwrap();
+        assert_eq!((tasks[0].id, total), (failed.id, 1));
+
+        let stats = task_service.get_statistics().await.unwrap();
+        assert_eq!(stats.failure_categories.get("oom"), Some(&1));
+        assert_eq!(stats.failure_categories.get("tool_error"), Some(&1));
+        assert_eq!(task_service.reconcile_statistics().await.unwrap(), 0);
+
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_by_category() {
+        use crate::config::CategoryRetryConfig;
+        use crate::infrastructure::{SqliteTaskRepository, SqliteLockManager};
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = Arc::new(SqliteTaskRepository::with_pool(pool.clone()).await.unwrap());
+        let lock_manager = Arc::new(SqliteLockManager::with_pool(pool).await);
+        let policy = RetryPolicy::from_config(&HashMap::from([
+            ("timeout".to_string(), CategoryRetryConfig {
+                max_retries: Some(5),
+                backoff_seconds: 600,
+                ..Default::default()
+            }),
+            ("validation".to_string(), CategoryRetryConfig {
+                max_retries: Some(0),
+                ..Default::default()
+            }),
+        ]))
+        .unwrap();
+        let task_service = TaskService::new(repo, lock_manager, 3, 3600).with_retry_policy(Arc::new(policy));
+
+        let create = || CreateTaskRequest {
+            work_directory: "/repo/app".to_string(),
+            prompt: "Run the test suite".to_string(),
+            priority: None,
+            tags: None,
+            metadata: None,
+            expires_at: None,
+            concurrency_group: None,
+            requires_approval: false,
+        };
+        let acquire = || AcquireTaskRequest {
+            work_path: "/repo/app".to_string(),
+            worker_id: "worker-1".to_string(),
+            capabilities: None,
+        };
+
+        // 校验失败不重试
+        let task = task_service.create_task(create()).await.unwrap();
+        task_service.start_task(&task.id, "worker-1".to_string()).await.unwrap();
+        let failed = task_service
+            .fail_task(&task.id, "bad input".to_string(), Some(FailureCategory::Validation))
+            .await
+            .unwrap();
+        assert_eq!((failed.status, failed.retry_count), (TaskStatus::Failed, 0));
+
+        // 超时退避后重新排队，退避期间不会被获取
+        let task = task_service.create_task(create()).await.unwrap();
+        task_service.start_task(&task.id, "worker-1".to_string()).await.unwrap();
+        let requeued = task_service.fail_task(&task.id, "Task timeout".to_string(), None).await.unwrap();
+        assert_eq!((requeued.status, requeued.retry_count), (TaskStatus::Waiting, 1));
+        assert!(requeued.retry_after.unwrap() > Utc::now() + chrono::Duration::minutes(9));
+        assert!(task_service.acquire_task(acquire()).await.unwrap().is_none());
+        let history = task_service.get_task_history(&task.id).await.unwrap();
+        assert!(history.iter().any(|h| h.details.contains_key("retry_after")));
+
+        // 未配置的类别沿用任务的 max_retries，立即重新排队
+        let task = task_service.create_task(create()).await.unwrap();
+        task_service.start_task(&task.id, "worker-1".to_string()).await.unwrap();
+        let requeued = task_service.fail_task(&task.id, "exit code 1".to_string(), None).await.unwrap();
+        assert_eq!((requeued.status, requeued.retry_after), (TaskStatus::Waiting, None));
+        let acquired = task_service.acquire_task(acquire()).await.unwrap().unwrap();
+        assert_eq!(acquired.id, task.id);
+    }
+
+    #[tokio::test]
+    async fn test_sla_report() {
+        use crate::config::{SlaAlertConfig, SlaConfig, SlaTargetConfig};
+        use crate::infrastructure::{SqliteTaskRepository, SqliteLockManager};
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = Arc::new(SqliteTaskRepository::with_pool(pool.clone()).await.unwrap());
+        let lock_manager = Arc::new(SqliteLockManager::with_pool(pool).await);
+        let sla = SlaTracker::from_config(&SlaConfig {
+            targets: HashMap::from([("high".to_string(), SlaTargetConfig { start_within: 300, finish_within: 3600 })]),
+            window: 3600,
+            alerts: vec![SlaAlertConfig { name: "high-late".to_string(), ..Default::default() }],
+            ..Default::default()
+        })
+        .unwrap();
+        let task_service = TaskService::new(repo.clone(), lock_manager, 3, 3600).with_sla_tracker(Arc::new(sla));
+
+        let task = |minutes_ago: i64| {
+            let mut task = Task::new(
+                WorkDirectory::new("/repo/app".to_string()).unwrap(),
+                Prompt::new("Hotfix".to_string()).unwrap(),
+                TaskPriority::High,
+                vec![],
+            );
+            task.created_at = Utc::now() - chrono::Duration::minutes(minutes_ago);
+            task
+        };
+        // 等待超过开始时限；窗口外已完成的任务不统计；窗口外仍在等待的任务照常统计
+        repo.create_task(&task(10)).await.unwrap();
+        let mut old = task(120);
+        old.status = TaskStatus::Completed;
+        old.started_at = Some(old.created_at);
+        old.completed_at = Some(old.created_at + chrono::Duration::hours(1) + chrono::Duration::minutes(1));
+        repo.create_task(&old).await.unwrap();
+        repo.create_task(&task(180)).await.unwrap();
+
+        let report = task_service.sla_report().await.unwrap();
+        let high = &report.priorities[0];
+        assert_eq!((high.tasks, high.start_breaches, high.finish_breaches), (2, 2, 1));
+
+        let events = task_service.evaluate_sla().await.unwrap();
+        assert_eq!((events.len(), events[0].breaches), (1, 3));
+        assert_eq!(task_service.sla_report().await.unwrap().firing_alerts, vec!["high-late".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_capacity_forecast() {
+        use crate::infrastructure::{SqliteTaskRepository, SqliteLockManager};
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = Arc::new(SqliteTaskRepository::with_pool(pool.clone()).await.unwrap());
+        let lock_manager = Arc::new(SqliteLockManager::with_pool(pool).await);
+        let task_service = TaskService::new(repo.clone(), lock_manager, 3, 3600);
+
+        let task = |minutes_ago: i64, status: TaskStatus| {
+            let mut task = Task::new(
+                WorkDirectory::new("/repo/app".to_string()).unwrap(),
+                Prompt::new("Build".to_string()).unwrap(),
+                TaskPriority::Medium,
+                vec![],
+            );
+            task.created_at = Utc::now() - chrono::Duration::minutes(minutes_ago);
+            task.status = status;
+            task.started_at = Some(task.created_at);
+            if status == TaskStatus::Working {
+                task.worker_id = Some(WorkerId::new("worker-1".to_string()).unwrap());
+            } else {
+                task.completed_at = Some(task.created_at + chrono::Duration::seconds(30));
+            }
+            task
+        };
+        repo.create_task(&task(10, TaskStatus::Completed)).await.unwrap();
+        repo.create_task(&task(20, TaskStatus::Failed)).await.unwrap();
+        repo.create_task(&task(5, TaskStatus::Working)).await.unwrap();
+        // 窗口外的任务不统计
+        repo.create_task(&task(120, TaskStatus::Completed)).await.unwrap();
+
+        let forecast = task_service.capacity_forecast(3600, None, &[1]).await.unwrap();
+        assert_eq!((forecast.arrivals, forecast.completions, forecast.current_workers), (3, 2, 1));
+        assert!((forecast.mean_service_seconds.unwrap() - 30.0).abs() < 0.01);
+        assert_eq!(forecast.scenarios.len(), 2);
+        assert!(forecast.scenarios.iter().all(|s| s.stable));
+
+        let overridden = task_service.capacity_forecast(3600, Some(4), &[]).await.unwrap();
+        assert_eq!((overridden.current_workers, overridden.scenarios[0].workers), (4, 4));
+    }
+
+    #[tokio::test]
+    async fn test_archived_tasks_take_their_artifacts() {
+        use crate::infrastructure::{ColdStorage, SqliteTaskRepository, SqliteLockManager};
+        use object_storage::{MemoryObjectStore, ObjectStore};
+
+        let dir = tempfile::tempdir().unwrap();
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = Arc::new(
+            SqliteTaskRepository::with_pool(pool.clone()).await.unwrap()
+                .with_cold_storage(Arc::new(ColdStorage::new(dir.path()).unwrap())),
+        );
+        let lock_manager = Arc::new(SqliteLockManager::with_pool(pool).await);
+        let object_store = Arc::new(MemoryObjectStore::new());
+        let artifact_store = Arc::new(ArtifactStore::new(object_store.clone(), "artifacts".to_string(), std::time::Duration::from_secs(60)));
+        let task_service = TaskService::new(repo.clone(), lock_manager, 3, 3600)
+            .with_cold_storage(30, 10)
+            .with_artifact_store(artifact_store.clone());
+
+        let mut task = Task::new(
+            WorkDirectory::new("/repo/app".to_string()).unwrap(),
+            Prompt::new("Build".to_string()).unwrap(),
+            TaskPriority::Medium,
+            vec![],
+        );
+        task.status = TaskStatus::Completed;
+        task.completed_at = Some(Utc::now() - chrono::Duration::days(40));
+        repo.create_task(&task).await.unwrap();
+        artifact_store.upload(&task.id, "report.json", b"{}".to_vec(), "application/json").await.unwrap();
+
+        assert_eq!(task_service.archive_old_tasks().await.unwrap(), 1);
+
+        // 产物随任务移到归档目录，按任务ID仍能取回任务和产物
+        assert!(object_store.list_objects(&format!("artifacts/{}/", task.id)).await.unwrap().is_empty());
+        assert_eq!(task_service.get_task(&task.id).await.unwrap().status, TaskStatus::Completed);
+        let (key, _) = artifact_store.existing_download_url(&task.id, "report.json").await.unwrap();
+        assert_eq!(key, format!("artifacts/archived/{}/report.json", task.id));
+        assert_eq!(object_store.get_object(&key).await.unwrap(), Some(b"{}".to_vec()));
+    }
+}
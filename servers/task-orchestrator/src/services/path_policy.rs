@@ -0,0 +1,123 @@
+use std::path::{Component, Path, PathBuf};
+use regex::Regex;
+
+use crate::config::{PolicyAction, WorkDirectoryPolicyConfig};
+use crate::errors::{AppError, AppResult};
+
+/// 工作目录策略
+///
+/// 路径先做词法规范化（处理 `.` 和 `..`），再依次检查禁止模式和允许前缀。
+/// 前缀按路径组件匹配，`/srv/repos` 允许 `/srv/repos/app`，但不允许 `/srv/repos2`。
+pub struct WorkDirectoryPolicy {
+    allowed_prefixes: Vec<PathBuf>,
+    denied_patterns: Vec<Regex>,
+    action: PolicyAction,
+    check_exists_on_acquire: bool,
+}
+
+impl WorkDirectoryPolicy {
+    /// 根据配置构建策略
+    pub fn from_config(config: &WorkDirectoryPolicyConfig) -> AppResult<Self> {
+        let denied_patterns = config
+            .denied_patterns
+            .iter()
+            .map(|p| {
+                Regex::new(p).map_err(|e| {
+                    AppError::Internal(format!("Invalid denied work directory pattern '{}': {}", p, e))
+                })
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(Self {
+            allowed_prefixes: config.allowed_prefixes.iter().map(|p| normalize(Path::new(p))).collect(),
+            denied_patterns,
+            action: config.action,
+            check_exists_on_acquire: config.check_exists_on_acquire,
+        })
+    }
+
+    /// 违规处理方式
+    pub fn action(&self) -> PolicyAction {
+        self.action
+    }
+
+    /// 检查路径，返回违规原因
+    pub fn evaluate(&self, work_directory: &str) -> Option<String> {
+        let normalized = normalize(Path::new(work_directory));
+        let display = normalized.to_string_lossy();
+
+        if let Some(pattern) = self.denied_patterns.iter().find(|p| p.is_match(&display)) {
+            return Some(format!("{} matches denied pattern '{}'", display, pattern.as_str()));
+        }
+
+        if !self.allowed_prefixes.is_empty()
+            && !self.allowed_prefixes.iter().any(|prefix| normalized.starts_with(prefix))
+        {
+            return Some(format!("{} is outside the permitted roots", display));
+        }
+
+        None
+    }
+
+    /// 获取任务时检查工作目录
+    ///
+    /// 违规时按配置处理：`Reject` 拒绝获取，`Flag` 只记录警告，与创建任务时的处理一致。
+    /// 目录是否存在的检查与处理方式无关。
+    pub fn check_acquire(&self, work_path: &str) -> AppResult<()> {
+        if let Some(reason) = self.evaluate(work_path) {
+            match self.action {
+                PolicyAction::Reject => {
+                    return Err(AppError::Validation(crate::errors::ValidationError::invalid_work_directory(reason)));
+                }
+                PolicyAction::Flag => {
+                    tracing::warn!(work_path = %work_path, "Work directory policy violation on acquire: {}", reason);
+                }
+            }
+        }
+
+        if self.check_exists_on_acquire && !Path::new(work_path).is_dir() {
+            return Err(AppError::Validation(crate::errors::ValidationError::invalid_work_directory(
+                format!("{} does not exist on this worker", work_path),
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// 词法规范化路径（不访问文件系统）
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowlist_and_denied_patterns() {
+        let policy = WorkDirectoryPolicy::from_config(&WorkDirectoryPolicyConfig {
+            enabled: true,
+            allowed_prefixes: vec!["/srv/repos".to_string()],
+            denied_patterns: vec!["/\\.ssh(/|$)".to_string()],
+            action: PolicyAction::Reject,
+            check_exists_on_acquire: false,
+        })
+        .unwrap();
+
+        assert!(policy.evaluate("/srv/repos/app").is_none());
+        assert!(policy.evaluate("/srv/repos2/app").is_some());
+        assert!(policy.evaluate("/srv/repos/../../etc").is_some());
+        assert!(policy.evaluate("/srv/repos/app/.ssh").is_some());
+    }
+}
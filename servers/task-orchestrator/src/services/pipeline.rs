@@ -0,0 +1,599 @@
+//! # 任务流水线
+//!
+//! 流水线是在配置中命名的一组任务模板。`POST /api/v1/pipelines/:name/run` 创建第一步的任务，
+//! 之后每一步的任务成功完成时，编排器自动创建下一步的任务，并把上一步的输出填入下一步的提示词。
+//! 某一步失败、取消或以失败结果完成时，流水线停止，不再创建后续任务。
+//!
+//! 步骤可以带条件（见 [`pipeline_condition`](super::pipeline_condition)）：`when` 不成立的步骤被跳过；
+//! 步骤成功后按顺序判断 `branches`，第一个成立的分支跳转到指定步骤，没有成立的分支时按顺序继续。
+//! 分支可以跳回前面的步骤形成循环，单次运行创建的步骤数达到 `max_steps` 时运行中止。
+//!
+//! 运行进度记录在任务元数据的 `pipeline` 键中，包括流水线名称、运行ID、步骤序号和上一步的任务ID；
+//! 运行状态另存为 [`PipelineRun`] 记录，通过 `GET /api/v1/pipeline-runs/:run_id` 查询。
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use config::ConfigError;
+use serde::{Deserialize, Serialize};
+
+use super::pipeline_condition::PipelineCondition;
+use crate::config::PipelineConfig;
+use crate::domain::{CreateTaskRequest, PipelineRun, PipelineRunStatus, Task, TaskPriority, MAX_PROMPT_LENGTH};
+use crate::errors::{AppError, AppResult};
+
+/// 任务元数据中记录流水线进度的键
+pub const PIPELINE_KEY: &str = "pipeline";
+
+/// 提示词模板中运行输入的占位符
+const INPUT_PLACEHOLDER: &str = "{{input}}";
+
+/// 提示词模板中上一步输出的占位符
+const PREVIOUS_OUTPUT_PLACEHOLDER: &str = "{{previous_output}}";
+
+/// 模板中没有占位符时，追加上一步输出前的标题
+const PREVIOUS_OUTPUT_HEADER: &str = "\n\nPrevious step output:\n";
+
+/// 上一步输出被截断时替代开头部分的标记
+const TRUNCATION_MARKER: &str = "[... earlier output truncated ...]\n";
+
+/// 流水线步骤
+#[derive(Debug, Clone)]
+pub struct PipelineStep {
+    pub name: String,
+    pub prompt: String,
+    pub priority: Option<TaskPriority>,
+    pub tags: Vec<String>,
+    /// 执行条件
+    pub when: Option<PipelineCondition>,
+    /// 成功后的分支
+    pub branches: Vec<PipelineBranch>,
+    /// 并发组，已合并流水线的并发组
+    pub concurrency_group: Option<String>,
+    /// 是否需要人工审批
+    pub requires_approval: bool,
+}
+
+/// 流水线分支
+#[derive(Debug, Clone)]
+pub struct PipelineBranch {
+    pub when: PipelineCondition,
+    /// 目标步骤序号
+    pub goto: usize,
+}
+
+/// 流水线定义
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    pub name: String,
+    pub description: Option<String>,
+    pub steps: Vec<PipelineStep>,
+    /// 单次运行最多创建的步骤数
+    pub max_steps: usize,
+}
+
+/// 流水线概要，用于列表接口
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineSummary {
+    pub name: String,
+    pub description: Option<String>,
+    pub steps: Vec<String>,
+}
+
+/// 运行流水线的请求
+#[derive(Debug, Clone, Default)]
+pub struct PipelineRunRequest {
+    pub work_directory: String,
+    /// 填入 `{{input}}` 占位符的输入
+    pub input: Option<String>,
+    pub priority: Option<TaskPriority>,
+    pub tags: Vec<String>,
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+/// 启动流水线的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineRunStart {
+    pub run: PipelineRun,
+    /// 第一步的任务，所有步骤都被跳过时为 `None`
+    pub task: Option<Task>,
+}
+
+/// 选择下一步的结果
+#[derive(Debug)]
+pub enum PipelineAdvance {
+    /// 创建下一步的任务
+    Next {
+        step: String,
+        skipped: Vec<String>,
+        request: CreateTaskRequest,
+    },
+    /// 没有需要执行的后续步骤
+    Completed { skipped: Vec<String> },
+    /// 创建的步骤数达到上限
+    Aborted { max_steps: usize },
+}
+
+impl PipelineAdvance {
+    /// 把选择结果记入运行记录，返回需要创建的步骤名称和任务创建请求
+    pub fn apply(self, run: &mut PipelineRun) -> Option<(String, CreateTaskRequest)> {
+        match self {
+            PipelineAdvance::Next { step, skipped, request } => {
+                run.skipped.extend(skipped);
+                Some((step, request))
+            }
+            PipelineAdvance::Completed { skipped } => {
+                run.skipped.extend(skipped);
+                run.finish(PipelineRunStatus::Completed, None);
+                None
+            }
+            PipelineAdvance::Aborted { max_steps } => {
+                run.finish(
+                    PipelineRunStatus::Aborted,
+                    Some(format!("Pipeline run reached the limit of {} steps", max_steps)),
+                );
+                None
+            }
+        }
+    }
+}
+
+/// 任务元数据中记录的流水线进度
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineProgress {
+    pub name: String,
+    pub run_id: String,
+    /// 当前步骤序号，从0开始
+    pub step: usize,
+    pub steps: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+    /// 运行请求的优先级，步骤未设置优先级时使用
+    pub priority: TaskPriority,
+    /// 运行请求的标签
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_task_id: Option<String>,
+    /// 本次运行已创建的步骤数（含当前步骤）
+    #[serde(default)]
+    pub executed: usize,
+}
+
+impl PipelineProgress {
+    /// 读取任务所属的流水线进度
+    pub fn from_task(task: &Task) -> Option<Self> {
+        serde_json::from_value(task.metadata.get(PIPELINE_KEY)?.clone()).ok()
+    }
+}
+
+impl Pipeline {
+    /// 构建第 `progress.step` 步的任务创建请求
+    fn step_request(
+        &self,
+        progress: &PipelineProgress,
+        work_directory: String,
+        mut metadata: HashMap<String, serde_json::Value>,
+        previous_output: Option<&str>,
+    ) -> CreateTaskRequest {
+        let step = &self.steps[progress.step];
+        metadata.insert(PIPELINE_KEY.to_string(), serde_json::json!(progress));
+        CreateTaskRequest {
+            work_directory,
+            prompt: render_prompt(&step.prompt, progress.input.as_deref(), previous_output),
+            priority: Some(step.priority.unwrap_or(progress.priority)),
+            tags: Some(progress.tags.iter().chain(&step.tags).cloned().collect()),
+            metadata: Some(metadata),
+            expires_at: None,
+            concurrency_group: step.concurrency_group.clone(),
+            requires_approval: step.requires_approval,
+        }
+    }
+
+    /// 选择下一步：先判断当前步骤的分支，再从当前步骤之后按顺序找第一个条件成立的步骤
+    fn select(&self, current: Option<usize>, document: &serde_json::Value) -> (Option<usize>, Vec<String>) {
+        if let Some(index) = current {
+            if let Some(branch) = self.steps[index].branches.iter().find(|b| b.when.evaluate(document)) {
+                return (Some(branch.goto), Vec::new());
+            }
+        }
+        let mut skipped = Vec::new();
+        for index in current.map_or(0, |i| i + 1)..self.steps.len() {
+            let step = &self.steps[index];
+            if step.when.as_ref().is_none_or(|when| when.evaluate(document)) {
+                return (Some(index), skipped);
+            }
+            skipped.push(step.name.clone());
+        }
+        (None, skipped)
+    }
+
+    /// 第一个条件成立的步骤，条件中的 `result` 为 `null`
+    pub fn first_request(&self, run_id: String, request: PipelineRunRequest) -> PipelineAdvance {
+        let document = condition_document(request.input.as_deref(), None);
+        let (step, skipped) = self.select(None, &document);
+        let Some(step) = step else {
+            return PipelineAdvance::Completed { skipped };
+        };
+        let progress = PipelineProgress {
+            name: self.name.clone(),
+            run_id,
+            step,
+            steps: self.steps.len(),
+            input: request.input,
+            priority: request.priority.unwrap_or_default(),
+            tags: request.tags,
+            previous_task_id: None,
+            executed: 1,
+        };
+        PipelineAdvance::Next {
+            step: self.steps[step].name.clone(),
+            skipped,
+            request: self.step_request(&progress, request.work_directory, request.metadata, None),
+        }
+    }
+
+    /// 上一步成功完成后选择下一步，上一步不属于流水线时返回 `None`
+    ///
+    /// 下一步沿用上一步的工作目录和元数据，`internal_keys` 中的服务内部元数据不会带入。
+    pub fn next_request(&self, previous: &Task, internal_keys: &[&str]) -> Option<PipelineAdvance> {
+        let progress = PipelineProgress::from_task(previous)?;
+        let current = progress.step.min(self.steps.len().saturating_sub(1));
+        let document = condition_document(progress.input.as_deref(), Some(previous));
+        let (step, skipped) = self.select(Some(current), &document);
+        let Some(step) = step else {
+            return Some(PipelineAdvance::Completed { skipped });
+        };
+        let executed = progress.executed.max(1);
+        if executed >= self.max_steps {
+            return Some(PipelineAdvance::Aborted { max_steps: self.max_steps });
+        }
+        let next = PipelineProgress {
+            step,
+            steps: self.steps.len(),
+            previous_task_id: Some(previous.id.to_string()),
+            executed: executed + 1,
+            ..progress
+        };
+        let metadata = previous
+            .metadata
+            .iter()
+            .filter(|(key, _)| key.as_str() != PIPELINE_KEY && !internal_keys.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        let output = previous.result.as_ref().and_then(|r| r.output.as_deref()).unwrap_or_default();
+        Some(PipelineAdvance::Next {
+            step: self.steps[step].name.clone(),
+            skipped,
+            request: self.step_request(&next, previous.work_directory.to_string(), metadata, Some(output)),
+        })
+    }
+}
+
+/// 条件表达式求值的文档：`{"input": 运行输入, "result": 上一步结果}`
+fn condition_document(input: Option<&str>, previous: Option<&Task>) -> serde_json::Value {
+    serde_json::json!({
+        "input": input,
+        "result": previous.and_then(|task| task.result.as_ref()),
+    })
+}
+
+/// 填充提示词模板
+///
+/// 模板中没有 `{{previous_output}}` 时，上一步的输出追加在提示词末尾作为上下文。上一步的输出过长时
+/// 只保留末尾部分并加上截断标记，使提示词不超过 [`MAX_PROMPT_LENGTH`]。
+pub fn render_prompt(template: &str, input: Option<&str>, previous_output: Option<&str>) -> String {
+    let mut prompt = template.replace(INPUT_PLACEHOLDER, input.unwrap_or_default());
+    if let Some(output) = previous_output {
+        let slots = prompt.matches(PREVIOUS_OUTPUT_PLACEHOLDER).count();
+        if slots > 0 {
+            let fixed = prompt.len() - slots * PREVIOUS_OUTPUT_PLACEHOLDER.len();
+            let budget = MAX_PROMPT_LENGTH.saturating_sub(fixed) / slots;
+            prompt = prompt.replace(PREVIOUS_OUTPUT_PLACEHOLDER, &truncate_output(output, budget));
+        } else {
+            prompt.push_str(PREVIOUS_OUTPUT_HEADER);
+            let budget = MAX_PROMPT_LENGTH.saturating_sub(prompt.len());
+            prompt.push_str(&truncate_output(output, budget));
+        }
+    }
+    prompt
+}
+
+/// 输出超过 `budget` 字节时保留末尾部分，开头替换为截断标记
+///
+/// 结论通常在输出末尾，所以丢弃的是开头部分。
+fn truncate_output(output: &str, budget: usize) -> Cow<'_, str> {
+    if output.len() <= budget {
+        return Cow::Borrowed(output);
+    }
+    let mut start = output.len() - budget.saturating_sub(TRUNCATION_MARKER.len());
+    while !output.is_char_boundary(start) {
+        start += 1;
+    }
+    Cow::Owned(format!("{}{}", TRUNCATION_MARKER, &output[start..]))
+}
+
+/// 流水线注册表
+#[derive(Debug, Default)]
+pub struct PipelineRegistry {
+    pipelines: HashMap<String, Pipeline>,
+}
+
+impl PipelineRegistry {
+    /// 根据配置构建注册表，步骤为空、步骤名称重复、提示词为空、优先级、条件或并发组无效、分支目标不存在时返回配置错误
+    pub fn from_config(config: &HashMap<String, PipelineConfig>) -> AppResult<Self> {
+        let mut pipelines = HashMap::new();
+        for (name, pipeline) in config {
+            let invalid = |reason: String| {
+                AppError::Configuration(ConfigError::Message(format!("Invalid pipeline '{}': {}", name, reason)))
+            };
+            if pipeline.steps.is_empty() {
+                return Err(invalid("at least one step is required".to_string()));
+            }
+            if pipeline.max_steps == 0 {
+                return Err(invalid("max_steps must be greater than 0".to_string()));
+            }
+            let valid_group = |group: &Option<String>| {
+                group.as_deref().is_none_or(|g| crate::domain::validate_concurrency_group(g).is_ok())
+            };
+            if !valid_group(&pipeline.concurrency_group) {
+                return Err(invalid("invalid concurrency_group".to_string()));
+            }
+            let names: Vec<String> = pipeline
+                .steps
+                .iter()
+                .enumerate()
+                .map(|(index, step)| if step.name.is_empty() { format!("step-{}", index + 1) } else { step.name.clone() })
+                .collect();
+            let mut steps = Vec::with_capacity(pipeline.steps.len());
+            for (index, (step, step_name)) in pipeline.steps.iter().zip(&names).enumerate() {
+                if names[..index].contains(step_name) {
+                    return Err(invalid(format!("duplicate step name '{}'", step_name)));
+                }
+                if step.prompt.trim().is_empty() {
+                    return Err(invalid(format!("step '{}' has an empty prompt", step_name)));
+                }
+                let priority = step
+                    .priority
+                    .as_deref()
+                    .map(TaskPriority::from_str)
+                    .transpose()
+                    .map_err(|e| invalid(format!("step '{}': {}", step_name, e)))?;
+                let condition = |expression: &str| {
+                    PipelineCondition::parse(expression)
+                        .map_err(|e| invalid(format!("step '{}': invalid condition '{}': {}", step_name, expression, e)))
+                };
+                if !valid_group(&step.concurrency_group) {
+                    return Err(invalid(format!("step '{}' has an invalid concurrency_group", step_name)));
+                }
+                let when = step.when.as_deref().map(condition).transpose()?;
+                let mut branches = Vec::with_capacity(step.branches.len());
+                for branch in &step.branches {
+                    let goto = names.iter().position(|n| *n == branch.goto).ok_or_else(|| {
+                        invalid(format!("step '{}' branches to unknown step '{}'", step_name, branch.goto))
+                    })?;
+                    branches.push(PipelineBranch { when: condition(&branch.when)?, goto });
+                }
+                steps.push(PipelineStep {
+                    name: step_name.clone(),
+                    prompt: step.prompt.clone(),
+                    priority,
+                    tags: step.tags.clone(),
+                    when,
+                    branches,
+                    concurrency_group: step.concurrency_group.clone().or_else(|| pipeline.concurrency_group.clone()),
+                    requires_approval: step.requires_approval,
+                });
+            }
+            pipelines.insert(
+                name.clone(),
+                Pipeline {
+                    name: name.clone(),
+                    description: pipeline.description.clone(),
+                    steps,
+                    max_steps: pipeline.max_steps,
+                },
+            );
+        }
+        Ok(Self { pipelines })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Pipeline> {
+        self.pipelines.get(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+
+    /// 按名称排序的流水线概要
+    pub fn summaries(&self) -> Vec<PipelineSummary> {
+        let mut summaries: Vec<_> = self
+            .pipelines
+            .values()
+            .map(|pipeline| PipelineSummary {
+                name: pipeline.name.clone(),
+                description: pipeline.description.clone(),
+                steps: pipeline.steps.iter().map(|step| step.name.clone()).collect(),
+            })
+            .collect();
+        summaries.sort_by(|a, b| a.name.cmp(&b.name));
+        summaries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{PipelineBranchConfig, PipelineStepConfig};
+    use crate::domain::{Prompt, TaskResult, WorkDirectory};
+
+    fn step(name: &str, prompt: &str) -> PipelineStepConfig {
+        PipelineStepConfig {
+            name: name.to_string(),
+            prompt: prompt.to_string(),
+            tags: vec![name.to_string()],
+            ..PipelineStepConfig::default()
+        }
+    }
+
+    fn registry(steps: Vec<PipelineStepConfig>) -> PipelineRegistry {
+        let config = HashMap::from([(
+            "review".to_string(),
+            PipelineConfig {
+                steps,
+                max_steps: 4,
+                ..PipelineConfig::default()
+            },
+        )]);
+        PipelineRegistry::from_config(&config).unwrap()
+    }
+
+    fn task_from(request: CreateTaskRequest) -> Task {
+        let mut task = Task::new(
+            WorkDirectory::new(request.work_directory).unwrap(),
+            Prompt::new(request.prompt).unwrap(),
+            request.priority.unwrap(),
+            Vec::new(),
+        );
+        task.metadata = request.metadata.unwrap();
+        task
+    }
+
+    fn expect_next(advance: Option<PipelineAdvance>) -> (String, Vec<String>, CreateTaskRequest) {
+        match advance {
+            Some(PipelineAdvance::Next { step, skipped, request }) => (step, skipped, request),
+            other => panic!("expected next step, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_pipeline_steps() {
+        let registry = registry(vec![
+            step("analyze", "Analyze {{input}}"),
+            PipelineStepConfig { priority: Some("high".to_string()), ..step("fix", "Fix the issues found") },
+        ]);
+        let pipeline = registry.get("review").unwrap();
+        let (name, _, request) = expect_next(Some(pipeline.first_request(
+            "run-1".to_string(),
+            PipelineRunRequest {
+                work_directory: "/repo".to_string(),
+                input: Some("src/lib.rs".to_string()),
+                tags: vec!["ci".to_string()],
+                metadata: HashMap::from([("owner".to_string(), serde_json::json!("alice"))]),
+                ..PipelineRunRequest::default()
+            },
+        )));
+        assert_eq!(name, "analyze");
+        assert_eq!(request.prompt, "Analyze src/lib.rs");
+        assert_eq!(request.priority, Some(TaskPriority::Medium));
+        assert_eq!(request.tags, Some(vec!["ci".to_string(), "analyze".to_string()]));
+
+        let mut first = task_from(request);
+        first.metadata.insert("last_transition_hash".to_string(), serde_json::json!("x"));
+        first.result = Some(TaskResult::success("2 issues".to_string()));
+        let (_, _, next) = expect_next(pipeline.next_request(&first, &["last_transition_hash"]));
+        assert_eq!(next.prompt, "Fix the issues found\n\nPrevious step output:\n2 issues");
+        assert_eq!(next.priority, Some(TaskPriority::High));
+        assert_eq!(next.tags, Some(vec!["ci".to_string(), "fix".to_string()]));
+        let metadata = next.metadata.clone().unwrap();
+        assert_eq!(metadata["owner"], "alice");
+        assert!(!metadata.contains_key("last_transition_hash"));
+
+        let second = task_from(next);
+        let progress = PipelineProgress::from_task(&second).unwrap();
+        assert_eq!((progress.step, progress.steps, progress.executed), (1, 2, 2));
+        assert_eq!(progress.previous_task_id, Some(first.id.to_string()));
+        assert!(matches!(pipeline.next_request(&second, &[]), Some(PipelineAdvance::Completed { .. })));
+    }
+
+    #[test]
+    fn test_conditional_steps_and_loop_protection() {
+        let registry = registry(vec![
+            PipelineStepConfig { when: Some("input == 'skip'".to_string()), ..step("lint", "Lint") },
+            step("test", "Run tests"),
+            PipelineStepConfig { when: Some("result.details.tests_failed > 0".to_string()), ..step("fix", "Fix tests") },
+            PipelineStepConfig {
+                branches: vec![PipelineBranchConfig {
+                    when: "result.details.retest".to_string(),
+                    goto: "test".to_string(),
+                }],
+                ..step("report", "Report")
+            },
+        ]);
+        let pipeline = registry.get("review").unwrap();
+        let request = PipelineRunRequest { work_directory: "/repo".to_string(), ..PipelineRunRequest::default() };
+        let (name, skipped, request) = expect_next(Some(pipeline.first_request("run-1".to_string(), request)));
+        assert_eq!((name.as_str(), skipped), ("test", vec!["lint".to_string()]));
+
+        // 测试全部通过时跳过修复
+        let mut test = task_from(request);
+        test.result = Some(TaskResult::success("ok".to_string()).with_detail("tests_failed".to_string(), 0.into()));
+        let (name, skipped, request) = expect_next(pipeline.next_request(&test, &[]));
+        assert_eq!((name.as_str(), skipped), ("report", vec!["fix".to_string()]));
+
+        // 分支跳回测试步骤，形成循环
+        let mut report = task_from(request);
+        report.result = Some(TaskResult::success("done".to_string()).with_detail("retest".to_string(), true.into()));
+        let (name, _, request) = expect_next(pipeline.next_request(&report, &[]));
+        assert_eq!(name, "test");
+
+        let mut retest = task_from(request);
+        retest.result = Some(TaskResult::success("1 failed".to_string()).with_detail("tests_failed".to_string(), 1.into()));
+        let (name, _, request) = expect_next(pipeline.next_request(&retest, &[]));
+        assert_eq!(name, "fix");
+
+        // 已创建4步，达到 max_steps
+        let mut fix = task_from(request);
+        fix.result = Some(TaskResult::success("fixed".to_string()));
+        assert!(matches!(pipeline.next_request(&fix, &[]), Some(PipelineAdvance::Aborted { max_steps: 4 })));
+
+        let mut run = PipelineRun::new("run-1".to_string(), "review".to_string());
+        assert!(pipeline.next_request(&fix, &[]).unwrap().apply(&mut run).is_none());
+        assert_eq!(run.status, PipelineRunStatus::Aborted);
+    }
+
+    #[test]
+    fn test_render_prompt() {
+        assert_eq!(render_prompt("Use {{previous_output}} for {{input}}", Some("x"), Some("y")), "Use y for x");
+        assert_eq!(render_prompt("Summarize {{input}}", None, None), "Summarize ");
+    }
+
+    #[test]
+    fn test_render_prompt_truncates_long_output() {
+        let output = format!("{}结论：2 issues", "日志".repeat(4000));
+
+        let prompt = render_prompt("Review {{input}}:\n{{previous_output}}", Some("main"), Some(&output));
+        assert!(prompt.len() <= MAX_PROMPT_LENGTH);
+        assert!(prompt.starts_with(&format!("Review main:\n{}", TRUNCATION_MARKER)));
+        assert!(prompt.ends_with("结论：2 issues"));
+        assert!(Prompt::new(prompt).is_ok());
+
+        let prompt = render_prompt("Fix the issues found", None, Some(&output));
+        assert!(prompt.len() <= MAX_PROMPT_LENGTH);
+        assert!(prompt.starts_with(&format!("Fix the issues found{}{}", PREVIOUS_OUTPUT_HEADER, TRUNCATION_MARKER)));
+        assert!(prompt.ends_with("结论：2 issues"));
+
+        let short = render_prompt("Fix the issues found", None, Some("2 issues"));
+        assert!(!short.contains(TRUNCATION_MARKER));
+    }
+
+    #[test]
+    fn test_invalid_pipeline_config() {
+        let config = HashMap::from([("empty".to_string(), PipelineConfig::default())]);
+        assert!(PipelineRegistry::from_config(&config).is_err());
+
+        let invalid_steps = [
+            vec![PipelineStepConfig { priority: Some("urgent".to_string()), ..step("a", "Do it") }],
+            vec![step("a", "Do it"), step("a", "Do it again")],
+            vec![PipelineStepConfig { when: Some("result >".to_string()), ..step("a", "Do it") }],
+            vec![PipelineStepConfig {
+                branches: vec![PipelineBranchConfig { when: "result".to_string(), goto: "missing".to_string() }],
+                ..step("a", "Do it")
+            }],
+        ];
+        for steps in invalid_steps {
+            let config = HashMap::from([("bad".to_string(), PipelineConfig { steps, ..PipelineConfig::default() })]);
+            assert!(PipelineRegistry::from_config(&config).is_err());
+        }
+    }
+}
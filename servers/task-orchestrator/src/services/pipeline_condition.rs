@@ -0,0 +1,369 @@
+//! # 流水线条件表达式
+//!
+//! 流水线步骤的 `when` 和分支条件，对上一步的结果求值，例如：
+//!
+//! ```text
+//! result.details.tests_failed > 0 && result.details.language == "rust"
+//! ```
+//!
+//! 语法（`!` > `&&` > `||`）：
+//!
+//! ```text
+//! expr       := or
+//! or         := and ("||" and)*
+//! and        := unary ("&&" unary)*
+//! unary      := "!" unary | "(" expr ")" | comparison
+//! comparison := path [op literal]
+//! path       := ["$."] key ("." key | "[" index "]")*
+//! op         := "==" | "!=" | ">" | ">=" | "<" | "<="
+//! literal    := number | "string" | 'string' | true | false | null
+//! ```
+//!
+//! 求值的文档为 `{"input": 运行输入, "status": 上一步任务状态, "result": 上一步结果}`，第一步之前
+//! `status` 和 `result` 为 `null`。路径不存在时取 `null`；只有路径没有运算符时按真值判断
+//! （`null`、`false`、`0`、空字符串和空数组为假）。大小比较只对两个数字或两个字符串成立。
+
+use std::cmp::Ordering;
+
+use serde_json::Value;
+
+/// 表达式最大长度（字符）
+const MAX_CONDITION_LENGTH: usize = 1000;
+
+/// 最大嵌套深度
+const MAX_DEPTH: usize = 16;
+
+/// 路径片段
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// 比较运算符
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// 语法树节点
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Not(Box<Node>),
+    Truthy(Vec<Segment>),
+    Compare(Vec<Segment>, CompareOp, Value),
+}
+
+/// 解析后的条件表达式
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineCondition {
+    root: Node,
+}
+
+/// 词法记号
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    Op(CompareOp),
+    Path(Vec<Segment>),
+    Literal(Value),
+}
+
+impl PipelineCondition {
+    /// 解析条件表达式
+    pub fn parse(input: &str) -> Result<Self, String> {
+        if input.chars().count() > MAX_CONDITION_LENGTH {
+            return Err(format!("condition is longer than {} characters", MAX_CONDITION_LENGTH));
+        }
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, position: 0, depth: 0 };
+        let root = parser.parse_or()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(Self { root }),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+        }
+    }
+
+    /// 对文档求值
+    pub fn evaluate(&self, document: &Value) -> bool {
+        self.root.evaluate(document)
+    }
+}
+
+impl Node {
+    fn evaluate(&self, document: &Value) -> bool {
+        match self {
+            Node::And(left, right) => left.evaluate(document) && right.evaluate(document),
+            Node::Or(left, right) => left.evaluate(document) || right.evaluate(document),
+            Node::Not(inner) => !inner.evaluate(document),
+            Node::Truthy(path) => is_truthy(lookup(document, path)),
+            Node::Compare(path, op, literal) => compare(lookup(document, path), *op, literal),
+        }
+    }
+}
+
+fn lookup<'a>(document: &'a Value, path: &[Segment]) -> &'a Value {
+    path.iter()
+        .try_fold(document, |value, segment| match segment {
+            Segment::Key(key) => value.get(key.as_str()),
+            Segment::Index(index) => value.get(*index),
+        })
+        .unwrap_or(&Value::Null)
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+fn compare(value: &Value, op: CompareOp, literal: &Value) -> bool {
+    let ordering = match (value, literal) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().zip(b.as_f64()).and_then(|(a, b)| a.partial_cmp(&b)),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+    match op {
+        CompareOp::Eq => ordering.map_or(value == literal, |o| o == Ordering::Equal),
+        CompareOp::Ne => ordering.map_or(value != literal, |o| o != Ordering::Equal),
+        CompareOp::Gt => ordering == Some(Ordering::Greater),
+        CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        CompareOp::Lt => ordering == Some(Ordering::Less),
+        CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '&' if next == Some('&') => {
+                tokens.push(Token::And);
+                i += 2;
+            }
+            '|' if next == Some('|') => {
+                tokens.push(Token::Or);
+                i += 2;
+            }
+            '=' if next == Some('=') => {
+                tokens.push(Token::Op(CompareOp::Eq));
+                i += 2;
+            }
+            '!' if next == Some('=') => {
+                tokens.push(Token::Op(CompareOp::Ne));
+                i += 2;
+            }
+            '!' => {
+                tokens.push(Token::Not);
+                i += 1;
+            }
+            '>' | '<' => {
+                let inclusive = next == Some('=');
+                tokens.push(Token::Op(match (c, inclusive) {
+                    ('>', false) => CompareOp::Gt,
+                    ('>', true) => CompareOp::Ge,
+                    ('<', false) => CompareOp::Lt,
+                    _ => CompareOp::Le,
+                }));
+                i += if inclusive { 2 } else { 1 };
+            }
+            '"' | '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&ch| ch == c)
+                    .ok_or_else(|| format!("unterminated string at position {}", i + 1))?;
+                let text: String = chars[i + 1..i + 1 + end].iter().collect();
+                tokens.push(Token::Literal(Value::String(text)));
+                i += end + 2;
+            }
+            _ if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || matches!(chars[i], '.' | 'e' | 'E' | '+' | '-')) {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let number = serde_json::from_str::<serde_json::Number>(&text)
+                    .map_err(|_| format!("invalid number '{}' at position {}", text, start + 1))?;
+                tokens.push(Token::Literal(Value::Number(number)));
+            }
+            _ if c == '$' || c == '_' || c.is_alphabetic() => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '-' | '.' | '$' | '[' | ']')) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(match word.as_str() {
+                    "true" => Token::Literal(Value::Bool(true)),
+                    "false" => Token::Literal(Value::Bool(false)),
+                    "null" => Token::Literal(Value::Null),
+                    _ => Token::Path(parse_path(&word).map_err(|e| format!("{} at position {}", e, start + 1))?),
+                });
+            }
+            _ => return Err(format!("unexpected character '{}' at position {}", c, i + 1)),
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    let path = path.strip_prefix("$.").unwrap_or(path);
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let (key, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if key.is_empty() || key.contains(['$', ']']) {
+            return Err(format!("invalid path '{}'", path));
+        }
+        segments.push(Segment::Key(key.to_string()));
+        while !rest.is_empty() {
+            let close = rest.find(']').ok_or_else(|| format!("invalid path '{}'", path))?;
+            let index = rest[1..close].parse().map_err(|_| format!("invalid index in path '{}'", path))?;
+            segments.push(Segment::Index(index));
+            rest = &rest[close + 1..];
+            if !rest.is_empty() && !rest.starts_with('[') {
+                return Err(format!("invalid path '{}'", path));
+            }
+        }
+    }
+    Ok(segments)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn next_if(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.position) == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Node, String> {
+        let mut left = self.parse_and()?;
+        while self.next_if(&Token::Or) {
+            left = Node::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Node, String> {
+        let mut left = self.parse_unary()?;
+        while self.next_if(&Token::And) {
+            left = Node::And(Box::new(left), Box::new(self.parse_unary()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Node, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("condition is nested too deeply".to_string());
+        }
+        let condition = if self.next_if(&Token::Not) {
+            Node::Not(Box::new(self.parse_unary()?))
+        } else if self.next_if(&Token::LParen) {
+            let inner = self.parse_or()?;
+            if !self.next_if(&Token::RParen) {
+                return Err("expected ')'".to_string());
+            }
+            inner
+        } else {
+            self.parse_comparison()?
+        };
+        self.depth -= 1;
+        Ok(condition)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Node, String> {
+        let path = match self.tokens.get(self.position).cloned() {
+            Some(Token::Path(path)) => path,
+            Some(token) => return Err(format!("expected a path, found {:?}", token)),
+            None => return Err("unexpected end of condition".to_string()),
+        };
+        self.position += 1;
+        let Some(Token::Op(op)) = self.tokens.get(self.position).cloned() else {
+            return Ok(Node::Truthy(path));
+        };
+        self.position += 1;
+        match self.tokens.get(self.position).cloned() {
+            Some(Token::Literal(literal)) => {
+                self.position += 1;
+                Ok(Node::Compare(path, op, literal))
+            }
+            _ => Err("expected a literal after the comparison operator".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(condition: &str, document: &Value) -> bool {
+        PipelineCondition::parse(condition).unwrap().evaluate(document)
+    }
+
+    #[test]
+    fn test_evaluate_conditions() {
+        let document = serde_json::json!({
+            "status": "completed",
+            "result": {
+                "output": "ok",
+                "details": {"tests_failed": 3, "language": "rust", "files": ["a.rs"], "clean": false}
+            }
+        });
+        assert!(eval("result.details.tests_failed > 0", &document));
+        assert!(eval("$.result.details.tests_failed >= 3 && result.details.language == 'rust'", &document));
+        assert!(!eval("result.details.tests_failed < 1 || result.details.clean", &document));
+        assert!(eval("!result.details.clean && result.details.files[0] == \"a.rs\"", &document));
+        assert!(eval("!(result.details.missing) && result.details.missing == null", &document));
+        assert!(eval("status != 'failed' && result.details.tests_failed != 'three'", &document));
+        // 类型不同时不比较大小
+        assert!(!eval("result.output > 1", &document));
+        assert!(eval("result.details.tests_failed == 3.0", &document));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for invalid in ["", "result >", "result == 'x", "(result", "result ==", "1 == result", "result..x", "a[x]"] {
+            assert!(PipelineCondition::parse(invalid).is_err(), "{}", invalid);
+        }
+        let deep = format!("{}x{}", "(".repeat(20), ")".repeat(20));
+        assert!(PipelineCondition::parse(&deep).is_err());
+    }
+}
@@ -0,0 +1,336 @@
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{TaskId, TaskResult, CompleteTaskRequest, FailureCategory};
+use crate::infrastructure::{MessageQueue, QueueMessage};
+use crate::errors::{AppError, AppResult};
+use super::TaskService;
+
+/// 工作者确认动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskAckAction {
+    /// 已领取任务，开始执行
+    Start,
+    /// 执行成功
+    Complete,
+    /// 执行失败
+    Fail,
+}
+
+/// 工作者确认消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskAck {
+    pub task_id: String,
+    pub worker_id: String,
+    pub action: TaskAckAction,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    /// 失败类别，未给出时按错误信息归类
+    #[serde(default)]
+    pub failure_category: Option<FailureCategory>,
+}
+
+/// 队列确认消费者
+///
+/// 订阅确认主题，将工作者的确认消息映射为任务的开始、完成和失败操作。
+/// 因数据库等暂时性故障处理失败的消息放回队列重新投递，格式错误或状态不允许的消息直接丢弃。
+pub struct QueueAckConsumer {
+    task_service: Arc<TaskService>,
+    message_queue: Arc<dyn MessageQueue>,
+    ack_subject: String,
+}
+
+impl QueueAckConsumer {
+    pub fn new(task_service: Arc<TaskService>, message_queue: Arc<dyn MessageQueue>, ack_subject: String) -> Self {
+        Self {
+            task_service,
+            message_queue,
+            ack_subject,
+        }
+    }
+
+    /// 处理单条确认消息
+    pub async fn handle_ack(&self, ack: TaskAck) -> AppResult<()> {
+        let task_id = TaskId::from_str(&ack.task_id)?;
+        match ack.action {
+            TaskAckAction::Start => {
+                self.task_service.start_task(&task_id, ack.worker_id).await?;
+            }
+            TaskAckAction::Complete => {
+                let output = ack.output.unwrap_or_else(|| "Task completed".to_string());
+                let request = CompleteTaskRequest {
+                    original_prompt: None,
+                    result: Some(TaskResult::success(output)),
+                };
+                self.task_service.complete_task(&task_id, request).await?;
+            }
+            TaskAckAction::Fail => {
+                let error = ack.error.unwrap_or_else(|| "Task failed".to_string());
+                self.task_service.fail_task(&task_id, error, ack.failure_category).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 处理一条队列消息，暂时性故障时放回队列，返回是否已放回
+    pub async fn process(&self, message: QueueMessage) -> bool {
+        let ack = match serde_json::from_slice::<TaskAck>(&message.payload) {
+            Ok(ack) => ack,
+            Err(e) => {
+                tracing::warn!("Invalid task ack on {}: {}", message.subject, e);
+                return false;
+            }
+        };
+
+        let task_id = ack.task_id.clone();
+        let Err(e) = self.handle_ack(ack).await else {
+            return false;
+        };
+        if !is_transient(&e) {
+            tracing::error!("Failed to handle ack for task {}: {}", task_id, e);
+            return false;
+        }
+
+        tracing::warn!("Failed to handle ack for task {}, requeueing: {}", task_id, e);
+        match self.message_queue.requeue(&message).await {
+            Ok(()) => true,
+            Err(requeue_error) => {
+                tracing::error!("Failed to requeue ack for task {}: {}", task_id, requeue_error);
+                false
+            }
+        }
+    }
+
+    /// 启动消费循环
+    pub async fn start(self: Arc<Self>) -> AppResult<()> {
+        let mut receiver = self.message_queue.subscribe(&self.ack_subject).await?;
+
+        tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                self.process(message).await;
+            }
+        });
+
+        Ok(())
+    }
+}
+
+/// 重试后可能成功的错误
+fn is_transient(error: &AppError) -> bool {
+    matches!(
+        error,
+        AppError::Database(_) | AppError::ServiceUnavailable(_) | AppError::ConcurrencyConflict
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CategoryRetryConfig;
+    use crate::domain::{TaskStatus, CreateTaskRequest};
+    use crate::infrastructure::{SqliteTaskRepository, SqliteLockManager};
+    use crate::services::RetryPolicy;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use tokio::sync::mpsc;
+
+    // 记录发布内容的内存队列
+    // 记录发布内容的内存队列
+    #[derive(Default)]
+    struct RecordingQueue {
+        published: Mutex<Vec<(String, Vec<u8>)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl MessageQueue for RecordingQueue {
+        async fn publish(&self, subject: &str, payload: &[u8]) -> AppResult<()> {
+            self.published.lock().unwrap().push((subject.to_string(), payload.to_vec()));
+            Ok(())
+        }
+
+        async fn subscribe(&self, _subject: &str) -> AppResult<mpsc::Receiver<QueueMessage>> {
+            let (_tx, rx) = mpsc::channel(1);
+            Ok(rx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_and_ack_lifecycle() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = Arc::new(SqliteTaskRepository::with_pool(pool.clone()).await.unwrap());
+        let lock_manager = Arc::new(SqliteLockManager::with_pool(pool).await);
+        let queue = Arc::new(RecordingQueue::default());
+        let task_service = Arc::new(
+            TaskService::new(repo, lock_manager, 3, 3600)
+                .with_message_queue(queue.clone(), "tasks".to_string()),
+        );
+
+        let task = task_service.create_task(CreateTaskRequest {
+            work_directory: "/repo/app".to_string(),
+            prompt: "Build".to_string(),
+            priority: None,
+            tags: None,
+            metadata: None,
+            expires_at: None,
+            concurrency_group: None,
+            requires_approval: false,
+        }).await.unwrap();
+
+        {
+            let published = queue.published.lock().unwrap();
+            assert_eq!(published.len(), 1);
+            assert_eq!(published[0].0, "tasks.repo.app");
+        }
+
+        let consumer = QueueAckConsumer::new(task_service.clone(), queue, "tasks.ack".to_string());
+        let ack = |action| TaskAck {
+            task_id: task.id.to_string(),
+            worker_id: "worker-1".to_string(),
+            action,
+            output: Some("ok".to_string()),
+            error: None,
+            failure_category: None,
+        };
+
+        consumer.handle_ack(ack(TaskAckAction::Start)).await.unwrap();
+        assert_eq!(task_service.get_task(&task.id).await.unwrap().status, TaskStatus::Working);
+
+        consumer.handle_ack(ack(TaskAckAction::Complete)).await.unwrap();
+        assert_eq!(task_service.get_task(&task.id).await.unwrap().status, TaskStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_requeued() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = Arc::new(SqliteTaskRepository::with_pool(pool.clone()).await.unwrap());
+        let lock_manager = Arc::new(SqliteLockManager::with_pool(pool.clone()).await);
+        let queue = Arc::new(RecordingQueue::default());
+        let task_service = Arc::new(TaskService::new(repo, lock_manager, 3, 3600));
+        let consumer = QueueAckConsumer::new(task_service, queue.clone(), "tasks.ack".to_string());
+        let message = |payload: serde_json::Value| QueueMessage {
+            subject: "tasks.ack".to_string(),
+            payload: payload.to_string().into_bytes(),
+        };
+        let start = serde_json::json!({
+            "task_id": TaskId::new().to_string(),
+            "worker_id": "worker-1",
+            "action": "start",
+        });
+
+        // 格式错误和任务不存在不会因重试而成功，直接丢弃
+        assert!(!consumer.process(message(serde_json::json!({ "task_id": 1 }))).await);
+        assert!(!consumer.process(message(start.clone())).await);
+        assert!(queue.published.lock().unwrap().is_empty());
+
+        // 数据库不可用时放回原主题
+        pool.close().await;
+        assert!(consumer.process(message(start)).await);
+        let published = queue.published.lock().unwrap();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].0, "tasks.ack");
+    }
+
+    // 使用内存数据库和记录队列的服务，超时失败退避1秒后重试，校验失败不重试
+    async fn requeue_service(queue: Arc<RecordingQueue>) -> Arc<TaskService> {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = Arc::new(SqliteTaskRepository::with_pool(pool.clone()).await.unwrap());
+        let lock_manager = Arc::new(SqliteLockManager::with_pool(pool).await);
+        let retry_policy = RetryPolicy::from_config(&HashMap::from([
+            ("timeout".to_string(), CategoryRetryConfig { backoff_seconds: 1, ..Default::default() }),
+            ("validation".to_string(), CategoryRetryConfig { max_retries: Some(0), ..Default::default() }),
+        ]))
+        .unwrap();
+        Arc::new(
+            TaskService::new(repo, lock_manager, 3, 3600)
+                .with_retry_policy(Arc::new(retry_policy))
+                .with_message_queue(queue, "tasks".to_string()),
+        )
+    }
+
+    // 创建并开始执行一个任务，返回任务ID
+    async fn started_task(task_service: &TaskService) -> TaskId {
+        let task = task_service.create_task(CreateTaskRequest {
+            work_directory: "/repo/app".to_string(),
+            prompt: "Build".to_string(),
+            priority: None,
+            tags: None,
+            metadata: None,
+            expires_at: None,
+            concurrency_group: None,
+            requires_approval: false,
+        }).await.unwrap();
+        task_service.start_task(&task.id, "worker-1".to_string()).await.unwrap();
+        task.id
+    }
+
+    #[tokio::test]
+    async fn test_failed_task_is_republished_after_backoff() {
+        let queue = Arc::new(RecordingQueue::default());
+        let task_service = requeue_service(queue.clone()).await;
+        let task_id = started_task(&task_service).await;
+
+        let task = task_service
+            .fail_task(&task_id, "Task timeout".to_string(), Some(FailureCategory::Timeout))
+            .await
+            .unwrap();
+        assert_eq!(task.status, TaskStatus::Waiting);
+        assert!(task.retry_after.is_some());
+
+        // 退避结束前不发布，到期后发布到原主题
+        assert_eq!(queue.published.lock().unwrap().len(), 1);
+        tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+        let published = queue.published.lock().unwrap();
+        assert_eq!(published.len(), 2);
+        assert_eq!(published[1].0, "tasks.repo.app");
+    }
+
+    #[tokio::test]
+    async fn test_retried_task_is_republished() {
+        let queue = Arc::new(RecordingQueue::default());
+        let task_service = requeue_service(queue.clone()).await;
+        let task_id = started_task(&task_service).await;
+
+        let failed = task_service
+            .fail_task(&task_id, "Bad input".to_string(), Some(FailureCategory::Validation))
+            .await
+            .unwrap();
+        assert_eq!(failed.status, TaskStatus::Failed);
+        assert_eq!(queue.published.lock().unwrap().len(), 1);
+
+        task_service.retry_task(&task_id).await.unwrap();
+        let published = queue.published.lock().unwrap();
+        assert_eq!(published.len(), 2);
+        assert_eq!(published[1].0, "tasks.repo.app");
+    }
+
+    #[tokio::test]
+    async fn test_bulk_retried_tasks_are_republished() {
+        let queue = Arc::new(RecordingQueue::default());
+        let task_service = requeue_service(queue.clone()).await;
+        let task_id = started_task(&task_service).await;
+        task_service
+            .fail_task(&task_id, "Bad input".to_string(), Some(FailureCategory::Validation))
+            .await
+            .unwrap();
+
+        assert_eq!(task_service.retry_failed_tasks().await.unwrap(), 1);
+        let published = queue.published.lock().unwrap();
+        assert_eq!(published.len(), 2);
+        let republished: crate::domain::Task = serde_json::from_slice(&published[1].1).unwrap();
+        assert_eq!(republished.id, task_id);
+        assert_eq!(republished.status, TaskStatus::Waiting);
+    }
+}
@@ -0,0 +1,167 @@
+//! # 队列暂停与排空
+//!
+//! 运维时手动控制任务队列，状态只保存在本进程内，重启后恢复正常：
+//!
+//! - 暂停：全局或按工作目录（路径前缀）停止分发任务，仍接受创建，已在执行的任务不受影响
+//! - 排空：拒绝创建新任务，等待中的任务继续分发，直到队列清空
+//!
+//! 与按时间表生效的维护窗口（[`MaintenanceSchedule`](super::MaintenanceSchedule)）互相独立，两者任一生效都会阻止分发。
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::domain::Task;
+use crate::errors::{AppError, AppResult};
+
+/// 队列控制状态
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct QueueControlStatus {
+    /// 是否全局暂停分发
+    pub paused: bool,
+    /// 单独暂停分发的工作目录
+    pub paused_directories: Vec<String>,
+    /// 是否处于排空模式（拒绝创建新任务）
+    pub draining: bool,
+    /// 最近一次变更时间
+    pub changed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct QueueControlState {
+    paused: bool,
+    paused_directories: BTreeSet<PathBuf>,
+    draining: bool,
+    changed_at: Option<DateTime<Utc>>,
+}
+
+/// 队列控制
+#[derive(Debug, Default)]
+pub struct QueueControl {
+    state: RwLock<QueueControlState>,
+}
+
+impl QueueControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 暂停分发，未指定工作目录时全局暂停
+    pub fn pause(&self, work_directory: Option<&str>) -> QueueControlStatus {
+        self.update(|state| match work_directory {
+            Some(dir) => {
+                state.paused_directories.insert(PathBuf::from(dir));
+            }
+            None => state.paused = true,
+        })
+    }
+
+    /// 恢复分发
+    ///
+    /// 指定工作目录时只取消该目录的暂停；未指定时取消全局暂停、所有目录暂停和排空模式，恢复正常运行。
+    pub fn resume(&self, work_directory: Option<&str>) -> QueueControlStatus {
+        self.update(|state| match work_directory {
+            Some(dir) => {
+                state.paused_directories.remove(Path::new(dir));
+            }
+            None => {
+                state.paused = false;
+                state.paused_directories.clear();
+                state.draining = false;
+            }
+        })
+    }
+
+    /// 进入排空模式
+    pub fn drain(&self) -> QueueControlStatus {
+        self.update(|state| state.draining = true)
+    }
+
+    /// 当前状态
+    pub fn status(&self) -> QueueControlStatus {
+        let state = self.state.read().unwrap();
+        QueueControlStatus {
+            paused: state.paused,
+            paused_directories: state.paused_directories.iter().map(|dir| dir.display().to_string()).collect(),
+            draining: state.draining,
+            changed_at: state.changed_at,
+        }
+    }
+
+    /// 创建任务前检查：排空模式下拒绝
+    pub fn check_create(&self) -> AppResult<()> {
+        if self.state.read().unwrap().draining {
+            return Err(AppError::QueueDraining);
+        }
+        Ok(())
+    }
+
+    /// 获取任务前检查：全局暂停或该目录位于暂停的目录下时拒绝
+    pub fn check_directory(&self, work_directory: &str) -> AppResult<()> {
+        let state = self.state.read().unwrap();
+        if state.paused {
+            return Err(AppError::QueuePaused("all work directories".to_string()));
+        }
+        let path = Path::new(work_directory);
+        match state.paused_directories.iter().find(|dir| path.starts_with(dir)) {
+            Some(dir) => Err(AppError::QueuePaused(dir.display().to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// 开始执行任务前检查
+    pub fn check_task(&self, task: &Task) -> AppResult<()> {
+        self.check_directory(task.work_directory.as_str())
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut QueueControlState)) -> QueueControlStatus {
+        {
+            let mut state = self.state.write().unwrap();
+            apply(&mut state);
+            state.changed_at = Some(Utc::now());
+        }
+        let status = self.status();
+        tracing::info!(
+            target: "audit",
+            paused = status.paused,
+            paused_directories = ?status.paused_directories,
+            draining = status.draining,
+            "Task queue control changed"
+        );
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_resume_and_drain() {
+        let control = QueueControl::new();
+        assert!(control.check_directory("/srv/repos/api").is_ok());
+        assert!(control.check_create().is_ok());
+
+        control.pause(Some("/srv/repos"));
+        assert!(matches!(control.check_directory("/srv/repos/api"), Err(AppError::QueuePaused(dir)) if dir == "/srv/repos"));
+        assert!(control.check_directory("/srv/repository").is_ok());
+        assert!(control.check_create().is_ok());
+
+        control.pause(None);
+        assert!(control.check_directory("/home/user").is_err());
+        control.resume(Some("/srv/repos"));
+        assert!(control.check_directory("/srv/repos/api").is_err());
+
+        let status = control.drain();
+        assert!(status.paused && status.draining && status.paused_directories.is_empty());
+        assert!(matches!(control.check_create(), Err(AppError::QueueDraining)));
+
+        let status = control.resume(None);
+        assert!(!status.paused && !status.draining);
+        assert!(control.check_directory("/srv/repos/api").is_ok());
+        assert!(control.check_create().is_ok());
+    }
+}
@@ -0,0 +1,141 @@
+//! 按失败类别的重试策略
+//!
+//! 任务执行失败时按失败类别决定是否重新排队以及重新排队前的退避时间，
+//! 例如超时可以多次退避重试，校验失败则不再重试。未配置的类别沿用任务的 `max_retries` 并立即重新排队。
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::CategoryRetryConfig;
+use crate::domain::{FailureCategory, RetryDecision, Task};
+use crate::errors::{AppError, AppResult};
+
+/// 单个类别的重试规则
+#[derive(Debug, Clone)]
+struct CategoryRetry {
+    max_retries: Option<u32>,
+    backoff_seconds: f64,
+    multiplier: f64,
+    max_backoff_seconds: f64,
+}
+
+impl CategoryRetry {
+    /// 第 `retry_count + 1` 次重试前的等待时间
+    fn backoff(&self, retry_count: u32) -> Option<Duration> {
+        if self.backoff_seconds <= 0.0 {
+            return None;
+        }
+        let seconds = (self.backoff_seconds * self.multiplier.powi(retry_count as i32)).min(self.max_backoff_seconds);
+        Some(Duration::milliseconds((seconds * 1000.0) as i64))
+    }
+}
+
+/// 重试策略
+#[derive(Debug, Default)]
+pub struct RetryPolicy {
+    categories: HashMap<FailureCategory, CategoryRetry>,
+}
+
+impl RetryPolicy {
+    /// 根据配置构建策略
+    pub fn from_config(config: &HashMap<String, CategoryRetryConfig>) -> AppResult<Self> {
+        let mut categories = HashMap::with_capacity(config.len());
+        for (name, retry) in config {
+            let category = FailureCategory::from_str(name)
+                .map_err(|_| AppError::Internal(format!("Unknown failure category '{}' in retry policy", name)))?;
+            if !retry.backoff_multiplier.is_finite() || retry.backoff_multiplier < 1.0 {
+                return Err(AppError::Internal(format!(
+                    "retry_policy.{}.backoff_multiplier must be at least 1.0",
+                    name
+                )));
+            }
+            categories.insert(category, CategoryRetry {
+                max_retries: retry.max_retries,
+                backoff_seconds: retry.backoff_seconds as f64,
+                multiplier: retry.backoff_multiplier,
+                max_backoff_seconds: retry.max_backoff_seconds as f64,
+            });
+        }
+        Ok(Self { categories })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.categories.is_empty()
+    }
+
+    /// 决定失败的任务是否重新排队
+    pub fn decide(&self, task: &Task, category: FailureCategory, now: DateTime<Utc>) -> RetryDecision {
+        let rule = self.categories.get(&category);
+        let max_retries = rule.and_then(|r| r.max_retries).unwrap_or(task.max_retries);
+        if task.retry_count >= max_retries {
+            return RetryDecision::GiveUp;
+        }
+        RetryDecision::Retry {
+            not_before: rule.and_then(|r| r.backoff(task.retry_count)).map(|delay| now + delay),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Prompt, TaskPriority, WorkDirectory};
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::from_config(&HashMap::from([
+            ("timeout".to_string(), CategoryRetryConfig {
+                max_retries: Some(5),
+                backoff_seconds: 10,
+                backoff_multiplier: 2.0,
+                max_backoff_seconds: 60,
+            }),
+            ("validation".to_string(), CategoryRetryConfig {
+                max_retries: Some(0),
+                ..Default::default()
+            }),
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_decide_by_category() {
+        let policy = policy();
+        let now = Utc::now();
+        let mut task = Task::new(
+            WorkDirectory::new("/repo".to_string()).unwrap(),
+            Prompt::new("Test".to_string()).unwrap(),
+            TaskPriority::Medium,
+            vec![],
+        );
+
+        assert_eq!(policy.decide(&task, FailureCategory::Validation, now), RetryDecision::GiveUp);
+        assert_eq!(policy.decide(&task, FailureCategory::Oom, now), RetryDecision::Retry { not_before: None });
+        assert_eq!(
+            policy.decide(&task, FailureCategory::Timeout, now),
+            RetryDecision::Retry { not_before: Some(now + Duration::seconds(10)) }
+        );
+
+        // 超过任务的 max_retries 后超时仍按类别上限重试，退避时间不超过上限
+        task.retry_count = 4;
+        assert_eq!(policy.decide(&task, FailureCategory::Oom, now), RetryDecision::GiveUp);
+        assert_eq!(
+            policy.decide(&task, FailureCategory::Timeout, now),
+            RetryDecision::Retry { not_before: Some(now + Duration::seconds(60)) }
+        );
+        task.retry_count = 5;
+        assert_eq!(policy.decide(&task, FailureCategory::Timeout, now), RetryDecision::GiveUp);
+    }
+
+    #[test]
+    fn test_rejects_invalid_config() {
+        let unknown = HashMap::from([("disk".to_string(), CategoryRetryConfig::default())]);
+        assert!(RetryPolicy::from_config(&unknown).is_err());
+
+        let shrinking = HashMap::from([(
+            "timeout".to_string(),
+            CategoryRetryConfig { backoff_multiplier: 0.5, ..Default::default() },
+        )]);
+        assert!(RetryPolicy::from_config(&shrinking).is_err());
+    }
+}
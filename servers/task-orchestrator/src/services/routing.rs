@@ -0,0 +1,152 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// 工作节点能力记录的默认有效期，超过后视为节点已离线
+pub const DEFAULT_CAPABILITY_TTL: Duration = Duration::from_secs(600);
+
+/// 标签路由统计
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RoutingStats {
+    /// 有效期内获取过任务的工作节点数
+    pub active_workers: usize,
+    /// 没有任何活跃节点能够处理的等待任务数
+    pub unmatchable_tasks: u64,
+    /// 等待任务需要、但所有活跃节点都不具备的标签
+    pub missing_capabilities: Vec<String>,
+}
+
+/// 节点声明的能力标签（未声明时为 `None`）和最近一次获取任务的时间
+type WorkerEntry = (Option<BTreeSet<String>>, Instant);
+
+/// 工作节点能力登记
+///
+/// 记录每个节点最近一次获取任务时声明的能力标签，用于统计无法匹配的任务。
+/// 未声明能力的节点按旧行为处理任意任务，只要存在这样的活跃节点，所有任务都视为可匹配。
+pub struct WorkerCapabilities {
+    ttl: Duration,
+    workers: RwLock<HashMap<String, WorkerEntry>>,
+}
+
+impl WorkerCapabilities {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            workers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 记录节点声明的能力，同时清理过期节点
+    pub fn record(&self, worker_id: &str, capabilities: Option<&[String]>) {
+        let capabilities = capabilities.map(|c| c.iter().cloned().collect());
+        let mut workers = self.workers.write().unwrap();
+        workers.retain(|_, (_, seen)| seen.elapsed() < self.ttl);
+        workers.insert(worker_id.to_string(), (capabilities, Instant::now()));
+    }
+
+    /// 能够获取指定标签任务的活跃节点，按节点ID排序
+    pub fn matching_workers(&self, tags: &[String]) -> Vec<String> {
+        let workers = self.workers.read().unwrap();
+        let mut matching: Vec<String> = workers
+            .iter()
+            .filter(|(_, (_, seen))| seen.elapsed() < self.ttl)
+            .filter(|(_, (capabilities, _))| match capabilities {
+                Some(capabilities) => tags.iter().all(|tag| capabilities.contains(tag)),
+                None => true,
+            })
+            .map(|(worker_id, _)| worker_id.clone())
+            .collect();
+        matching.sort();
+        matching
+    }
+
+    /// 根据等待任务的标签分布计算路由统计
+    ///
+    /// `waiting` 为 (任务标签, 任务数) 列表，没有标签的任务任意节点都能处理。
+    pub fn stats(&self, waiting: &[(Vec<String>, u64)]) -> RoutingStats {
+        let workers = self.workers.read().unwrap();
+        let active: Vec<&Option<BTreeSet<String>>> = workers
+            .values()
+            .filter(|(_, seen)| seen.elapsed() < self.ttl)
+            .map(|(capabilities, _)| capabilities)
+            .collect();
+
+        let mut stats = RoutingStats {
+            active_workers: active.len(),
+            unmatchable_tasks: 0,
+            missing_capabilities: Vec::new(),
+        };
+        if active.iter().any(|capabilities| capabilities.is_none()) {
+            return stats;
+        }
+
+        let declared: Vec<&BTreeSet<String>> = active.into_iter().flatten().collect();
+        let mut missing = BTreeSet::new();
+        for (tags, count) in waiting {
+            if tags.is_empty() {
+                continue;
+            }
+            let matchable = declared
+                .iter()
+                .any(|capabilities| tags.iter().all(|tag| capabilities.contains(tag)));
+            if !matchable {
+                stats.unmatchable_tasks += count;
+                missing.extend(
+                    tags.iter()
+                        .filter(|tag| !declared.iter().any(|capabilities| capabilities.contains(*tag)))
+                        .cloned(),
+                );
+            }
+        }
+        stats.missing_capabilities = missing.into_iter().collect();
+        stats
+    }
+}
+
+impl Default for WorkerCapabilities {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPABILITY_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_unmatchable_tasks() {
+        let registry = WorkerCapabilities::default();
+        let waiting = vec![
+            (tags(&[]), 4),
+            (tags(&["rust"]), 2),
+            (tags(&["gpu", "rust"]), 3),
+            (tags(&["python"]), 1),
+        ];
+
+        // 没有活跃节点时，所有带标签的任务都无法匹配
+        let stats = registry.stats(&waiting);
+        assert_eq!((stats.active_workers, stats.unmatchable_tasks), (0, 6));
+
+        registry.record("worker-1", Some(&tags(&["rust", "claude"])));
+        registry.record("worker-2", Some(&tags(&["gpu"])));
+        let stats = registry.stats(&waiting);
+        assert_eq!((stats.active_workers, stats.unmatchable_tasks), (2, 4));
+        assert_eq!(stats.missing_capabilities, tags(&["python"]));
+        assert_eq!(registry.matching_workers(&tags(&["rust"])), tags(&["worker-1"]));
+        assert!(registry.matching_workers(&tags(&["gpu", "rust"])).is_empty());
+
+        // 未声明能力的节点可以处理任意任务
+        registry.record("legacy", None);
+        assert_eq!(registry.stats(&waiting).unmatchable_tasks, 0);
+
+        let expired = WorkerCapabilities::new(Duration::ZERO);
+        expired.record("legacy", None);
+        assert_eq!(expired.stats(&waiting).active_workers, 0);
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use sqlx::migrate::Migrator;
+use sqlx::{Pool, Row, Sqlite};
+
+use crate::config::{AppConfig, ConfigManager};
+use crate::errors::{AppError, AppResult};
+use crate::infrastructure::{ColdStorage, FieldCipher, ServiceClients};
+use crate::services::{ApprovalPolicy, FailureClassifier, MaintenanceSchedule, RetryPolicy, SlaTracker, SecretScanner, WorkDirectoryPolicy};
+
+/// 与启动时相同的迁移集合，只读比对，不执行
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// 单项检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// 前置检查失败，未执行
+    Skipped,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStatus::Pass => write!(f, "PASS"),
+            CheckStatus::Fail => write!(f, "FAIL"),
+            CheckStatus::Skipped => write!(f, "SKIP"),
+        }
+    }
+}
+
+/// 单项检查
+#[derive(Debug, Clone)]
+pub struct CheckItem {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+/// 启动自检报告
+#[derive(Debug, Clone, Default)]
+pub struct CheckReport {
+    pub items: Vec<CheckItem>,
+}
+
+impl CheckReport {
+    fn record<T>(&mut self, name: &str, result: AppResult<T>, detail: impl FnOnce(&T) -> String) -> Option<T> {
+        let (status, detail, value) = match result {
+            Ok(value) => (CheckStatus::Pass, detail(&value), Some(value)),
+            Err(e) => (CheckStatus::Fail, e.to_string(), None),
+        };
+        self.items.push(CheckItem { name: name.to_string(), status, detail });
+        value
+    }
+
+    fn skip(&mut self, name: &str, reason: &str) {
+        self.items.push(CheckItem {
+            name: name.to_string(),
+            status: CheckStatus::Skipped,
+            detail: reason.to_string(),
+        });
+    }
+
+    /// 没有失败项时为真
+    pub fn passed(&self) -> bool {
+        self.items.iter().all(|item| item.status != CheckStatus::Fail)
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.items.iter().map(|item| item.name.len()).max().unwrap_or(0);
+        for item in &self.items {
+            writeln!(f, "[{}] {:<width$}  {}", item.status, item.name, item.detail, width = width)?;
+        }
+        let failed = self.items.iter().filter(|item| item.status == CheckStatus::Fail).count();
+        if failed == 0 {
+            write!(f, "Self-check passed ({} checks)", self.items.len())
+        } else {
+            write!(f, "Self-check failed: {} of {} checks failed", failed, self.items.len())
+        }
+    }
+}
+
+/// 启动自检
+///
+/// 依次加载并校验配置、构建由配置驱动的组件、连接数据库并以只读方式比对迁移状态，
+/// 不启动任何后台任务，也不写入数据库。
+pub async fn run_self_check() -> CheckReport {
+    let mut report = CheckReport::default();
+
+    let Some(config) = report.record("config", ConfigManager::new(), |manager| {
+        format!("environment {:?}, version {}", manager.config().environment, manager.config().version)
+    }) else {
+        report.skip("components", "configuration is invalid");
+        report.skip("database", "configuration is invalid");
+        report.skip("migrations", "configuration is invalid");
+        return report;
+    };
+    let config = config.config().clone();
+
+    report.record("components", check_components(&config), |enabled| {
+        if enabled.is_empty() {
+            "no optional components enabled".to_string()
+        } else {
+            format!("built {}", enabled.join(", "))
+        }
+    });
+
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(Duration::from_secs(config.database.connection_timeout))
+        .connect(&config.database.url)
+        .await
+        .map_err(AppError::from);
+    let Some(pool) = report.record("database", pool, |_| format!("connected to {}", config.database.url)) else {
+        report.skip("migrations", "database is unreachable");
+        return report;
+    };
+
+    report.record("migrations", pending_migrations(&pool).await, |pending| {
+        if pending.is_empty() {
+            "schema is up to date".to_string()
+        } else {
+            format!("{} pending, applied on startup: {}", pending.len(), pending.join(", "))
+        }
+    });
+    pool.close().await;
+
+    report
+}
+
+/// 按启动流程构建已启用的组件，返回已构建的组件名
+fn check_components(config: &AppConfig) -> AppResult<Vec<&'static str>> {
+    let mut enabled = Vec::new();
+    if config.security.encryption.enabled {
+        FieldCipher::from_config(&config.security.encryption)?;
+        enabled.push("field encryption");
+    }
+    if config.security.work_directory_policy.enabled {
+        WorkDirectoryPolicy::from_config(&config.security.work_directory_policy)?;
+        enabled.push("work directory policy");
+    }
+    if config.security.secret_scanning.enabled {
+        SecretScanner::from_config(&config.security.secret_scanning)?;
+        enabled.push("secret scanning");
+    }
+    if !MaintenanceSchedule::from_config(&config.maintenance)?.is_empty() {
+        enabled.push("maintenance windows");
+    }
+    ApprovalPolicy::from_config(&config.approval)?;
+    if !config.approval.approvers.is_empty() {
+        enabled.push("approvers");
+    }
+    FailureClassifier::from_config(&config.failure_classification)?;
+    if !config.failure_classification.rules.is_empty() {
+        enabled.push("failure rules");
+    }
+    if !RetryPolicy::from_config(&config.retry_policy)?.is_empty() {
+        enabled.push("retry policy");
+    }
+    if SlaTracker::from_config(&config.sla)?.is_enabled() {
+        enabled.push("SLA tracking");
+    }
+    if config.cold_storage.enabled {
+        ColdStorage::new(&config.cold_storage.directory)?;
+        enabled.push("cold storage");
+    }
+    if config.external_services.enable_external_services {
+        ServiceClients::from_config(&config.external_services)?;
+        enabled.push("external services");
+    }
+    Ok(enabled)
+}
+
+/// 比对已应用的迁移，返回启动时将要应用的迁移
+///
+/// 已应用迁移的校验和不一致、迁移未成功完成或数据库中存在未知迁移时返回错误，
+/// 这些情况都会导致启动时迁移失败。
+async fn pending_migrations(pool: &Pool<Sqlite>) -> AppResult<Vec<String>> {
+    let exists: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let mut applied: HashMap<i64, (bool, Vec<u8>)> = HashMap::new();
+    if exists > 0 {
+        let rows = sqlx::query("SELECT version, success, checksum FROM _sqlx_migrations")
+            .fetch_all(pool)
+            .await?;
+        for row in rows {
+            applied.insert(row.try_get("version")?, (row.try_get("success")?, row.try_get("checksum")?));
+        }
+    }
+
+    let mut pending = Vec::new();
+    for migration in MIGRATOR.iter().filter(|m| !m.migration_type.is_down_migration()) {
+        match applied.remove(&migration.version) {
+            Some((false, _)) => {
+                return Err(AppError::Internal(format!(
+                    "Migration {} did not complete successfully", migration.version
+                )));
+            }
+            Some((true, checksum)) if checksum != *migration.checksum => {
+                return Err(AppError::Internal(format!(
+                    "Migration {} was modified after it was applied", migration.version
+                )));
+            }
+            Some(_) => {}
+            None => pending.push(format!("{}_{}", migration.version, migration.description)),
+        }
+    }
+    if let Some(version) = applied.keys().min() {
+        return Err(AppError::Internal(format!(
+            "Migration {} is applied but missing from this build", version
+        )));
+    }
+
+    Ok(pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pending_migrations() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        let total = MIGRATOR.iter().filter(|m| !m.migration_type.is_down_migration()).count();
+        assert_eq!(pending_migrations(&pool).await.unwrap().len(), total);
+
+        MIGRATOR.run(&pool).await.unwrap();
+        assert!(pending_migrations(&pool).await.unwrap().is_empty());
+
+        sqlx::query("UPDATE _sqlx_migrations SET checksum = x'00' WHERE version = (SELECT MIN(version) FROM _sqlx_migrations)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(pending_migrations(&pool).await.is_err());
+
+        let mut report = CheckReport::default();
+        report.record("database", Ok(()), |_| "connected".to_string());
+        report.skip("migrations", "not run");
+        assert!(report.passed());
+        report.record::<()>("config", Err(AppError::Internal("bad".to_string())), |_| String::new());
+        assert!(!report.passed());
+        assert!(report.to_string().ends_with("1 of 3 checks failed"));
+    }
+}
@@ -0,0 +1,406 @@
+//! SLA跟踪
+//!
+//! 按优先级配置开始和结束时限（从任务创建时起算），统计窗口内违约的任务数和当前有违约风险的任务，
+//! 并按告警规则在违约数达到阈值时触发告警、回落后解除。待审批和已取消的任务不参与统计。
+
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Mutex;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::SlaConfig;
+use crate::domain::{TaskPriority, TaskStatus};
+use crate::errors::{AppError, AppResult};
+use crate::models::SlaSample;
+
+/// 在途风险任务列表的最大长度
+const MAX_AT_RISK_TASKS: usize = 100;
+
+/// 违约类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, strum::EnumString, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum SlaKind {
+    /// 未按时开始
+    Start,
+    /// 未按时结束
+    Finish,
+    /// 任一类型（仅用于告警规则）
+    Any,
+}
+
+/// 单个优先级的目标
+#[derive(Debug, Clone, Copy)]
+struct SlaTarget {
+    start_within: Option<Duration>,
+    finish_within: Option<Duration>,
+}
+
+/// 告警规则
+#[derive(Debug, Clone)]
+struct SlaAlertRule {
+    name: String,
+    priority: Option<TaskPriority>,
+    kind: SlaKind,
+    threshold: u64,
+}
+
+/// 单个优先级的SLA统计
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PrioritySla {
+    pub priority: String,
+    /// 开始时限（秒）
+    pub start_within: Option<i64>,
+    /// 结束时限（秒）
+    pub finish_within: Option<i64>,
+    /// 参与统计的任务数
+    pub tasks: u64,
+    pub start_breaches: u64,
+    pub finish_breaches: u64,
+    pub at_risk: u64,
+}
+
+/// 有违约风险的任务
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SlaAtRiskTask {
+    pub task_id: String,
+    pub priority: String,
+    pub status: String,
+    pub kind: SlaKind,
+    /// 时限到期时间
+    pub due_at: DateTime<Utc>,
+}
+
+/// SLA报告
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SlaReport {
+    pub generated_at: DateTime<Utc>,
+    /// 统计窗口（秒）
+    pub window: i64,
+    pub priorities: Vec<PrioritySla>,
+    /// 按到期时间排序，最多100个
+    pub at_risk_tasks: Vec<SlaAtRiskTask>,
+    /// 正在触发的告警
+    pub firing_alerts: Vec<String>,
+}
+
+/// 告警状态变化
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SlaAlertEvent {
+    pub alert: String,
+    /// `firing` 或 `resolved`
+    pub state: String,
+    pub priority: Option<String>,
+    pub kind: SlaKind,
+    pub breaches: u64,
+    pub threshold: u64,
+    pub at: DateTime<Utc>,
+}
+
+/// SLA跟踪器
+pub struct SlaTracker {
+    /// 按优先级从高到低排列
+    targets: Vec<(TaskPriority, SlaTarget)>,
+    window: Duration,
+    at_risk_ratio: f64,
+    alerts: Vec<SlaAlertRule>,
+    firing: Mutex<HashSet<String>>,
+}
+
+impl Default for SlaTracker {
+    fn default() -> Self {
+        Self::from_config(&SlaConfig::default()).expect("default SLA config is valid")
+    }
+}
+
+impl SlaTracker {
+    /// 根据配置构建跟踪器
+    pub fn from_config(config: &SlaConfig) -> AppResult<Self> {
+        let seconds = |value: u64, field: &str| -> AppResult<Option<Duration>> {
+            match value {
+                0 => Ok(None),
+                value => i64::try_from(value)
+                    .ok()
+                    .and_then(Duration::try_seconds)
+                    .map(Some)
+                    .ok_or_else(|| AppError::Internal(format!("sla.{} is too large", field))),
+            }
+        };
+
+        let mut targets = Vec::with_capacity(config.targets.len());
+        for (name, target) in &config.targets {
+            let priority = TaskPriority::from_str(name)
+                .map_err(|_| AppError::Internal(format!("Unknown priority '{}' in SLA targets", name)))?;
+            targets.push((priority, SlaTarget {
+                start_within: seconds(target.start_within, &format!("targets.{}.start_within", name))?,
+                finish_within: seconds(target.finish_within, &format!("targets.{}.finish_within", name))?,
+            }));
+        }
+        targets.sort_by_key(|(priority, _)| std::cmp::Reverse(priority.weight()));
+
+        if !(config.at_risk_ratio > 0.0 && config.at_risk_ratio <= 1.0) {
+            return Err(AppError::Internal("sla.at_risk_ratio must be in (0, 1]".to_string()));
+        }
+        let window = seconds(config.window, "window")?
+            .ok_or_else(|| AppError::Internal("sla.window cannot be zero".to_string()))?;
+
+        let mut names = HashSet::new();
+        let mut alerts = Vec::with_capacity(config.alerts.len());
+        for alert in &config.alerts {
+            if alert.name.trim().is_empty() || !names.insert(alert.name.clone()) {
+                return Err(AppError::Internal(format!("SLA alert names must be unique and non-empty: '{}'", alert.name)));
+            }
+            let priority = alert
+                .priority
+                .as_deref()
+                .map(|p| TaskPriority::from_str(p).map_err(|_| AppError::Internal(format!("Unknown priority '{}' in SLA alert '{}'", p, alert.name))))
+                .transpose()?;
+            let kind = SlaKind::from_str(&alert.kind)
+                .map_err(|_| AppError::Internal(format!("Unknown SLA alert kind '{}' (expected start, finish or any)", alert.kind)))?;
+            alerts.push(SlaAlertRule {
+                name: alert.name.clone(),
+                priority,
+                kind,
+                threshold: alert.threshold.max(1),
+            });
+        }
+
+        Ok(Self {
+            targets,
+            window,
+            at_risk_ratio: config.at_risk_ratio,
+            alerts,
+            firing: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// 是否配置了SLA目标
+    pub fn is_enabled(&self) -> bool {
+        !self.targets.is_empty()
+    }
+
+    /// 只统计该时间之后创建的已结束任务
+    pub fn window_start(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - self.window
+    }
+
+    /// 根据任务时间点生成报告
+    pub fn report(&self, samples: &[SlaSample], now: DateTime<Utc>) -> SlaReport {
+        let mut priorities: Vec<PrioritySla> = self
+            .targets
+            .iter()
+            .map(|(priority, target)| {
+                PrioritySla {
+                    priority: priority.to_string(),
+                    start_within: target.start_within.map(|d| d.num_seconds()),
+                    finish_within: target.finish_within.map(|d| d.num_seconds()),
+                    tasks: 0,
+                    start_breaches: 0,
+                    finish_breaches: 0,
+                    at_risk: 0,
+                }
+            })
+            .collect();
+        let mut at_risk_tasks = Vec::new();
+
+        for sample in samples {
+            if matches!(sample.status, TaskStatus::PendingApproval | TaskStatus::Cancelled) {
+                continue;
+            }
+            let Some(index) = self.targets.iter().position(|(priority, _)| *priority == sample.priority) else {
+                continue;
+            };
+            let (target, stats) = (&self.targets[index].1, &mut priorities[index]);
+            stats.tasks += 1;
+
+            let checks = [
+                (SlaKind::Start, target.start_within, sample.started_at),
+                (SlaKind::Finish, target.finish_within, sample.completed_at),
+            ];
+            let mut at_risk = false;
+            for (kind, within, reached_at) in checks {
+                let Some(within) = within else { continue };
+                let due_at = sample.created_at + within;
+                let breached = match reached_at {
+                    Some(reached_at) => reached_at > due_at,
+                    // 尚未开始或结束的任务按当前时间判断
+                    None => now > due_at,
+                };
+                if breached {
+                    match kind {
+                        SlaKind::Start => stats.start_breaches += 1,
+                        _ => stats.finish_breaches += 1,
+                    }
+                } else if reached_at.is_none() && sample.status.is_active() && self.near_due(sample.created_at, within, now) {
+                    at_risk = true;
+                    at_risk_tasks.push(SlaAtRiskTask {
+                        task_id: sample.task_id.to_string(),
+                        priority: sample.priority.to_string(),
+                        status: sample.status.to_string(),
+                        kind,
+                        due_at,
+                    });
+                }
+            }
+            if at_risk {
+                stats.at_risk += 1;
+            }
+        }
+
+        at_risk_tasks.sort_by_key(|task| task.due_at);
+        at_risk_tasks.truncate(MAX_AT_RISK_TASKS);
+        let mut firing_alerts: Vec<String> = self.firing.lock().unwrap().iter().cloned().collect();
+        firing_alerts.sort();
+
+        SlaReport {
+            generated_at: now,
+            window: self.window.num_seconds(),
+            priorities,
+            at_risk_tasks,
+            firing_alerts,
+        }
+    }
+
+    /// 已用时间是否达到时限的风险比例
+    fn near_due(&self, created_at: DateTime<Utc>, within: Duration, now: DateTime<Utc>) -> bool {
+        let elapsed = (now - created_at).num_milliseconds() as f64;
+        elapsed >= within.num_milliseconds() as f64 * self.at_risk_ratio
+    }
+
+    /// 按报告评估告警规则，返回状态发生变化的告警
+    pub fn evaluate_alerts(&self, report: &SlaReport) -> Vec<SlaAlertEvent> {
+        let mut firing = self.firing.lock().unwrap();
+        let mut events = Vec::new();
+
+        for rule in &self.alerts {
+            let breaches: u64 = report
+                .priorities
+                .iter()
+                .filter(|p| rule.priority.is_none_or(|priority| p.priority == priority.to_string()))
+                .map(|p| match rule.kind {
+                    SlaKind::Start => p.start_breaches,
+                    SlaKind::Finish => p.finish_breaches,
+                    SlaKind::Any => p.start_breaches + p.finish_breaches,
+                })
+                .sum();
+
+            let state = match (breaches >= rule.threshold, firing.contains(&rule.name)) {
+                (true, false) => {
+                    firing.insert(rule.name.clone());
+                    "firing"
+                }
+                (false, true) => {
+                    firing.remove(&rule.name);
+                    "resolved"
+                }
+                _ => continue,
+            };
+            events.push(SlaAlertEvent {
+                alert: rule.name.clone(),
+                state: state.to_string(),
+                priority: rule.priority.map(|p| p.to_string()),
+                kind: rule.kind,
+                breaches,
+                threshold: rule.threshold,
+                at: report.generated_at,
+            });
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::config::{SlaAlertConfig, SlaTargetConfig};
+    use crate::domain::TaskId;
+
+    fn tracker() -> SlaTracker {
+        SlaTracker::from_config(&SlaConfig {
+            targets: HashMap::from([(
+                "high".to_string(),
+                SlaTargetConfig { start_within: 300, finish_within: 3600 },
+            )]),
+            alerts: vec![SlaAlertConfig {
+                name: "high-start".to_string(),
+                priority: Some("high".to_string()),
+                kind: "start".to_string(),
+                threshold: 1,
+            }],
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn sample(priority: TaskPriority, status: TaskStatus, created_at: DateTime<Utc>) -> SlaSample {
+        SlaSample {
+            task_id: TaskId::new(),
+            priority,
+            status,
+            created_at,
+            started_at: None,
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_report_breaches_and_risk() {
+        let tracker = tracker();
+        let now = Utc::now();
+
+        // 按时开始但超时结束
+        let mut late_finish = sample(TaskPriority::High, TaskStatus::Completed, now - Duration::hours(3));
+        late_finish.started_at = Some(late_finish.created_at + Duration::minutes(1));
+        late_finish.completed_at = Some(late_finish.created_at + Duration::hours(2));
+        // 等待了4分钟，已超过开始时限的80%
+        let at_risk = sample(TaskPriority::High, TaskStatus::Waiting, now - Duration::minutes(4));
+        // 等待了10分钟，开始已违约
+        let late_start = sample(TaskPriority::High, TaskStatus::Waiting, now - Duration::minutes(10));
+        // 未配置目标的优先级和已取消的任务不统计
+        let low = sample(TaskPriority::Low, TaskStatus::Waiting, now - Duration::days(1));
+        let cancelled = sample(TaskPriority::High, TaskStatus::Cancelled, now - Duration::days(1));
+
+        let report = tracker.report(&[late_finish, at_risk.clone(), late_start, low, cancelled], now);
+        assert_eq!(report.priorities.len(), 1);
+        let high = &report.priorities[0];
+        assert_eq!((high.tasks, high.start_breaches, high.finish_breaches, high.at_risk), (3, 1, 1, 1));
+        assert_eq!(report.at_risk_tasks.len(), 1);
+        assert_eq!(report.at_risk_tasks[0].task_id, at_risk.task_id.to_string());
+        assert_eq!(report.at_risk_tasks[0].due_at, at_risk.created_at + Duration::minutes(5));
+    }
+
+    #[test]
+    fn test_alerts_fire_and_resolve_once() {
+        let tracker = tracker();
+        let now = Utc::now();
+        let late = sample(TaskPriority::High, TaskStatus::Waiting, now - Duration::minutes(10));
+
+        let report = tracker.report(std::slice::from_ref(&late), now);
+        let events = tracker.evaluate_alerts(&report);
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].state.as_str(), events[0].breaches), ("firing", 1));
+        assert!(tracker.evaluate_alerts(&tracker.report(&[late], now)).is_empty());
+        assert_eq!(tracker.report(&[], now).firing_alerts, vec!["high-start".to_string()]);
+
+        let events = tracker.evaluate_alerts(&tracker.report(&[], now));
+        assert_eq!(events[0].state, "resolved");
+        assert!(tracker.report(&[], now).firing_alerts.is_empty());
+    }
+
+    #[test]
+    fn test_rejects_invalid_config() {
+        let config = |f: fn(&mut SlaConfig)| {
+            let mut config = SlaConfig::default();
+            f(&mut config);
+            SlaTracker::from_config(&config)
+        };
+        assert!(config(|c| { c.targets.insert("urgent".to_string(), SlaTargetConfig::default()); }).is_err());
+        assert!(config(|c| c.at_risk_ratio = 1.5).is_err());
+        assert!(config(|c| c.alerts.push(SlaAlertConfig { kind: "late".to_string(), name: "a".to_string(), ..Default::default() })).is_err());
+        assert!(config(|c| c.alerts.push(SlaAlertConfig::default())).is_err());
+        assert!(config(|_| {}).is_ok());
+    }
+}
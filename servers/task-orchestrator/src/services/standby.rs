@@ -0,0 +1,397 @@
+//! # 热备复制
+//!
+//! 备机按序列号从主实例拉取事件，按原序列号写入本地事件日志并更新投影。备机的事件日志因此始终是主实例的前缀，
+//! 重启后从本地最新序列号继续复制；提升为主实例后，新事件的序列号接着主实例的序列号增长。
+//!
+//! 备机只提供只读接口。主实例故障时调用提升接口：尽力完成最后一次同步后停止复制、开放写入，
+//! 并由启动流程接着启动调度器等后台任务。
+
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::errors::AppResult;
+use crate::infrastructure::{CachedTaskRepository, EventSourcedTaskRepository, ReplicationSource};
+
+/// 节点角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeRole {
+    Primary,
+    Standby,
+}
+
+/// 热备复制状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandbyStatus {
+    pub role: NodeRole,
+    pub primary_url: String,
+    /// 本地已写入的最新事件序列号
+    pub applied_sequence: u64,
+    /// 最近一次同步时主实例的最新事件序列号
+    pub primary_sequence: u64,
+    /// 落后主实例的事件数
+    pub lag: u64,
+    pub last_sync_at: Option<DateTime<Utc>>,
+    /// 最近一次同步失败的原因，同步成功后清空
+    pub last_error: Option<String>,
+    pub promoted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct SyncProgress {
+    applied_sequence: u64,
+    primary_sequence: u64,
+    last_sync_at: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+    promoted_at: Option<DateTime<Utc>>,
+}
+
+/// 热备复制器
+pub struct StandbyReplicator {
+    repository: Arc<EventSourcedTaskRepository>,
+    source: Arc<dyn ReplicationSource>,
+    primary_url: String,
+    batch_size: u32,
+    poll_interval: Duration,
+    task_cache: Option<Arc<CachedTaskRepository>>,
+    promoted: watch::Sender<bool>,
+    /// 串行化复制循环与提升时的最后一次同步
+    sync_lock: tokio::sync::Mutex<()>,
+    progress: std::sync::Mutex<SyncProgress>,
+}
+
+impl StandbyReplicator {
+    /// 创建新的热备复制器，初始角色为备机
+    pub fn new(
+        repository: Arc<EventSourcedTaskRepository>,
+        source: Arc<dyn ReplicationSource>,
+        primary_url: String,
+    ) -> Self {
+        Self {
+            repository,
+            source,
+            primary_url,
+            batch_size: 500,
+            poll_interval: Duration::from_secs(1),
+            task_cache: None,
+            promoted: watch::Sender::new(false),
+            sync_lock: tokio::sync::Mutex::new(()),
+            progress: std::sync::Mutex::new(SyncProgress::default()),
+        }
+    }
+
+    /// 设置每次拉取的最大事件数
+    pub fn with_batch_size(mut self, batch_size: u32) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// 设置拉取间隔
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// 设置任务读缓存，复制的事件绕过缓存写入投影，写入后清空缓存
+    pub fn with_task_cache(mut self, task_cache: Arc<CachedTaskRepository>) -> Self {
+        self.task_cache = Some(task_cache);
+        self
+    }
+
+    /// 是否仍处于备机角色（只读）
+    pub fn is_standby(&self) -> bool {
+        !*self.promoted.borrow()
+    }
+
+    /// 当前复制状态
+    pub fn status(&self) -> StandbyStatus {
+        let progress = self.progress.lock().unwrap();
+        StandbyStatus {
+            role: if self.is_standby() { NodeRole::Standby } else { NodeRole::Primary },
+            primary_url: self.primary_url.clone(),
+            applied_sequence: progress.applied_sequence,
+            primary_sequence: progress.primary_sequence,
+            lag: progress.primary_sequence.saturating_sub(progress.applied_sequence),
+            last_sync_at: progress.last_sync_at,
+            last_error: progress.last_error.clone(),
+            promoted_at: progress.promoted_at,
+        }
+    }
+
+    /// 拉取并写入主实例的新事件，直到追平，返回写入的事件数；提升后不再复制
+    pub async fn sync_once(&self) -> AppResult<u64> {
+        let _guard = self.sync_lock.lock().await;
+        if !self.is_standby() {
+            return Ok(0);
+        }
+
+        let result = self.pull().await;
+        let mut progress = self.progress.lock().unwrap();
+        match &result {
+            Ok(_) => {
+                progress.last_sync_at = Some(Utc::now());
+                progress.last_error = None;
+            }
+            Err(e) => progress.last_error = Some(e.to_string()),
+        }
+        result
+    }
+
+    async fn pull(&self) -> AppResult<u64> {
+        let mut after = self.repository.last_sequence().await?;
+        self.progress.lock().unwrap().applied_sequence = after;
+
+        let mut applied = 0;
+        loop {
+            let batch = self.source.fetch(after, self.batch_size).await?;
+            let written = self.repository.apply_replicated(&batch.events).await?;
+            if written > 0 {
+                if let Some(cache) = &self.task_cache {
+                    cache.evict_all();
+                }
+            }
+            applied += written;
+
+            if let Some(last) = batch.events.last() {
+                after = last.sequence;
+            }
+            {
+                let mut progress = self.progress.lock().unwrap();
+                progress.applied_sequence = after;
+                progress.primary_sequence = batch.last_sequence.max(after);
+            }
+            if (batch.events.len() as u32) < self.batch_size {
+                break;
+            }
+        }
+        Ok(applied)
+    }
+
+    /// 启动复制循环，提升后自动退出
+    pub async fn start(self: &Arc<Self>) -> AppResult<()> {
+        if let Err(e) = self.sync_once().await {
+            tracing::warn!("Initial standby sync failed: {}", e);
+        }
+
+        let replicator = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(replicator.poll_interval);
+            let mut promoted = replicator.promoted.subscribe();
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = promoted.wait_for(|promoted| *promoted) => break,
+                }
+                match replicator.sync_once().await {
+                    Ok(0) => {}
+                    Ok(applied) => tracing::debug!("Replicated {} events from primary", applied),
+                    Err(e) => tracing::warn!("Standby sync failed: {}", e),
+                }
+            }
+            tracing::info!("Standby replication stopped");
+        });
+
+        Ok(())
+    }
+
+    /// 提升为主实例：尽力完成最后一次同步后开放写入，已经是主实例时直接返回当前状态
+    pub async fn promote(&self) -> StandbyStatus {
+        if let Err(e) = self.sync_once().await {
+            // 主实例通常已经不可用，提升继续进行，未复制的事件会丢失
+            tracing::warn!("Final sync before promotion failed: {}", e);
+        }
+
+        let _guard = self.sync_lock.lock().await;
+        if self.is_standby() {
+            self.progress.lock().unwrap().promoted_at = Some(Utc::now());
+            self.promoted.send_replace(true);
+            let status = self.status();
+            tracing::warn!(
+                applied_sequence = status.applied_sequence,
+                lag = status.lag,
+                "Promoted standby to primary"
+            );
+        }
+        self.status()
+    }
+
+    /// 等待本节点被提升为主实例
+    pub async fn wait_for_promotion(&self) {
+        let mut promoted = self.promoted.subscribe();
+        let _ = promoted.wait_for(|promoted| *promoted).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{MetadataSchema, PipelineRun, PipelineRunStatus, Task, TaskComment, TaskPriority, TaskResult, TaskStatus, WorkDirectory, Prompt, WorkerId};
+    use crate::models::TaskFilter;
+    use crate::errors::AppError;
+    use crate::infrastructure::{ColdStorage, EventStore, ManagedPool, ReplicationBatch, SqliteEventStore, SqliteTaskRepository, TaskRepository};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// 直接读取另一个事件日志的复制来源，可模拟主实例宕机
+    struct LocalSource {
+        event_store: Arc<dyn EventStore>,
+        down: AtomicBool,
+    }
+
+    #[async_trait::async_trait]
+    impl ReplicationSource for LocalSource {
+        async fn fetch(&self, after: u64, limit: u32) -> AppResult<ReplicationBatch> {
+            if self.down.load(Ordering::SeqCst) {
+                return Err(AppError::ServiceUnavailable("primary is down".to_string()));
+            }
+            Ok(ReplicationBatch {
+                events: self.event_store.load_events_page(after, limit).await?,
+                last_sequence: self.event_store.last_sequence().await?,
+            })
+        }
+    }
+
+    async fn create_node() -> (Arc<EventSourcedTaskRepository>, Arc<dyn EventStore>) {
+        create_node_with(None).await
+    }
+
+    async fn create_node_with(cold_storage: Option<Arc<ColdStorage>>) -> (Arc<EventSourcedTaskRepository>, Arc<dyn EventStore>) {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let pool = Arc::new(ManagedPool::new(pool));
+        let mut projection = SqliteTaskRepository::with_managed_pool(pool.clone()).await.unwrap();
+        if let Some(cold_storage) = cold_storage {
+            projection = projection.with_cold_storage(cold_storage);
+        }
+        let projection = Arc::new(projection);
+        let event_store = Arc::new(SqliteEventStore::with_managed_pool(pool));
+        (Arc::new(EventSourcedTaskRepository::new(event_store.clone(), projection)), event_store)
+    }
+
+    fn new_task(prompt: &str) -> Task {
+        Task::new(
+            WorkDirectory::new("/test".to_string()).unwrap(),
+            Prompt::new(prompt.to_string()).unwrap(),
+            TaskPriority::Medium,
+            vec![],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_replicate_and_promote() {
+        let (primary, primary_events) = create_node().await;
+        let (standby, _) = create_node().await;
+        let source = Arc::new(LocalSource { event_store: primary_events, down: AtomicBool::new(false) });
+        let replicator = StandbyReplicator::new(standby.clone(), source.clone(), "http://primary".to_string())
+            .with_batch_size(2);
+
+        let mut started = new_task("started");
+        primary.create_task(&started).await.unwrap();
+        started.start(WorkerId::new("worker-1".to_string()).unwrap()).unwrap();
+        primary.update_task(&started).await.unwrap();
+        let deleted = new_task("deleted");
+        primary.create_task(&deleted).await.unwrap();
+        primary.delete_task(&deleted.id).await.unwrap();
+        let waiting = new_task("waiting");
+        primary.create_task(&waiting).await.unwrap();
+
+        // 分页拉取直到追平，重复同步不会重复写入
+        assert_eq!(replicator.sync_once().await.unwrap(), 5);
+        assert_eq!(replicator.sync_once().await.unwrap(), 0);
+        let status = replicator.status();
+        assert_eq!((status.role, status.applied_sequence, status.lag), (NodeRole::Standby, 5, 0));
+        assert_eq!(standby.get_task(&started.id).await.unwrap().unwrap().status, TaskStatus::Working);
+        assert!(standby.get_task(&deleted.id).await.unwrap().is_none());
+        assert!(standby.get_task(&waiting.id).await.unwrap().is_some());
+
+        // 主实例宕机后仍可提升，提升后的新事件接着主实例的序列号
+        source.down.store(true, Ordering::SeqCst);
+        let status = replicator.promote().await;
+        assert_eq!(status.role, NodeRole::Primary);
+        assert!(status.promoted_at.is_some());
+        assert!(!replicator.is_standby());
+        replicator.wait_for_promotion().await;
+        assert_eq!(replicator.sync_once().await.unwrap(), 0);
+
+        standby.create_task(&new_task("after promotion")).await.unwrap();
+        assert_eq!(standby.last_sequence().await.unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_promoted_standby_keeps_comments_and_records() {
+        let (primary, primary_events) = create_node().await;
+        let (standby, _) = create_node().await;
+        let source = Arc::new(LocalSource { event_store: primary_events, down: AtomicBool::new(false) });
+        let replicator = StandbyReplicator::new(standby.clone(), source.clone(), "http://primary".to_string());
+
+        let task = new_task("commented");
+        primary.create_task(&task).await.unwrap();
+        let comment = TaskComment::new(task.id, "alice".to_string(), "looks good".to_string())
+            .with_data(serde_json::json!({"score": 5}));
+        let comment_id = primary.add_task_comment(&comment).await.unwrap();
+        let mut run = PipelineRun::new("run-1".to_string(), "build".to_string());
+        run.record_step("compile".to_string(), &task);
+        primary.save_pipeline_run(&run).await.unwrap();
+        run.status = PipelineRunStatus::Completed;
+        primary.save_pipeline_run(&run).await.unwrap();
+        let schema = MetadataSchema::new("/test".to_string(), serde_json::json!({"type": "object"}), true);
+        primary.save_metadata_schema(&schema).await.unwrap();
+        let removed = MetadataSchema::new("/removed".to_string(), serde_json::json!({}), false);
+        primary.save_metadata_schema(&removed).await.unwrap();
+        assert!(primary.delete_metadata_schema("/removed").await.unwrap());
+
+        // 评论、流水线运行和元数据模式随事件流复制，不改变任务的历史
+        assert_eq!(replicator.sync_once().await.unwrap(), 7);
+        source.down.store(true, Ordering::SeqCst);
+        replicator.promote().await;
+
+        let comments = standby.get_task_comments(&task.id).await.unwrap();
+        assert_eq!(comments, vec![TaskComment { id: comment_id, ..comment }]);
+        assert_eq!(standby.get_pipeline_run("run-1").await.unwrap(), Some(run));
+        assert_eq!(standby.list_metadata_schemas().await.unwrap(), vec![schema]);
+        assert_eq!(standby.get_task_history(&task.id).await.unwrap().len(), 1);
+
+        // 提升后新增的评论沿用主实例之后的评论ID
+        let next = TaskComment::new(task.id, "bob".to_string(), "after promotion".to_string());
+        assert_eq!(standby.add_task_comment(&next).await.unwrap(), comment_id + 1);
+    }
+
+    #[tokio::test]
+    async fn test_promoted_standby_serves_archived_tasks() {
+        // 段文件只写在主实例本地，热备没有配置冷存储
+        let dir = tempfile::tempdir().unwrap();
+        let (primary, primary_events) = create_node_with(Some(Arc::new(ColdStorage::new(dir.path()).unwrap()))).await;
+        let (standby, _) = create_node().await;
+        let source = Arc::new(LocalSource { event_store: primary_events, down: AtomicBool::new(false) });
+        let replicator = StandbyReplicator::new(standby.clone(), source.clone(), "http://primary".to_string());
+
+        let mut task = new_task("archived");
+        primary.create_task(&task).await.unwrap();
+        task.start(WorkerId::new("worker-1".to_string()).unwrap()).unwrap();
+        primary.update_task(&task).await.unwrap();
+        task.complete(TaskResult::success("Done".to_string())).unwrap();
+        task.completed_at = Some(Utc::now() - chrono::Duration::days(40));
+        primary.update_task(&task).await.unwrap();
+        let comment = TaskComment::new(task.id, "alice".to_string(), "shipped".to_string());
+        let comment_id = primary.add_task_comment(&comment).await.unwrap();
+        assert_eq!(primary.archive_tasks(Utc::now() - chrono::Duration::days(30), 100).await.unwrap(), vec![task.id]);
+
+        replicator.sync_once().await.unwrap();
+        source.down.store(true, Ordering::SeqCst);
+        replicator.promote().await;
+
+        // 已归档的任务不在热表中，按ID仍能读取任务、历史和评论
+        let (hot, _) = standby.list_tasks(&TaskFilter::new()).await.unwrap();
+        assert!(hot.is_empty());
+        let archived = standby.get_task(&task.id).await.unwrap().unwrap();
+        assert_eq!((archived.status, archived.prompt.as_str()), (TaskStatus::Completed, "archived"));
+        assert_eq!(standby.get_task_history(&task.id).await.unwrap().len(), 3);
+        assert_eq!(standby.get_task_comments(&task.id).await.unwrap(), vec![TaskComment { id: comment_id, ..comment }]);
+        assert!(standby.get_task(&crate::domain::TaskId::new()).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,135 @@
+//! # 时钟
+//!
+//! 租约、锁和任务超时按单调时钟（`Instant`）判断是否到期，系统时间向前或向后跳变都不会让它们提前或推迟到期。
+//! 需要持久化或跨节点共享的到期时间仍然记录墙上时间；进程内没有对应的单调截止时间时
+//! （其他节点持有的锁、重启前开始执行的任务），回退到按墙上时间比较。
+//!
+//! 时钟通过 [`Clock`] 注入，测试中用 [`ManualClock`] 分别调整墙上时间和单调时间来模拟时钟跳变。
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+/// 时钟
+pub trait Clock: Send + Sync {
+    /// 当前墙上时间，用于持久化和跨节点比较
+    fn now(&self) -> DateTime<Utc>;
+
+    /// 当前单调时间，用于进程内的超时判断
+    fn instant(&self) -> Instant;
+}
+
+/// 系统时钟
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// 默认使用的系统时钟
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// 截止时间
+///
+/// 同时记录单调截止时间和对应的墙上到期时间，前者用于判断是否到期，后者用于持久化。
+#[derive(Debug, Clone, Copy)]
+pub struct Deadline {
+    instant: Instant,
+    expires_at: DateTime<Utc>,
+}
+
+impl Deadline {
+    /// 从当前时间起 `ttl` 后到期
+    pub fn after(clock: &dyn Clock, ttl: Duration) -> Self {
+        let ttl_chrono = chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX);
+        Self {
+            instant: clock.instant() + ttl,
+            expires_at: clock.now().checked_add_signed(ttl_chrono).unwrap_or(DateTime::<Utc>::MAX_UTC),
+        }
+    }
+
+    /// 按单调时钟判断是否已到期
+    pub fn is_expired(&self, clock: &dyn Clock) -> bool {
+        clock.instant() >= self.instant
+    }
+
+    /// 持久化使用的墙上到期时间
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+}
+
+/// 手动时钟，墙上时间和单调时间可以分别调整
+#[cfg(test)]
+pub struct ManualClock {
+    origin: Instant,
+    state: std::sync::Mutex<(DateTime<Utc>, Duration)>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    /// 从当前系统时间开始
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            state: std::sync::Mutex::new((Utc::now(), Duration::ZERO)),
+        }
+    }
+
+    /// 时间正常流逝，墙上时间和单调时间同时前进
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.0 += chrono::Duration::from_std(duration).unwrap();
+        state.1 += duration;
+    }
+
+    /// 系统时间跳变，只调整墙上时间
+    pub fn jump_wall_clock(&self, delta: chrono::Duration) {
+        self.state.lock().unwrap().0 += delta;
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.state.lock().unwrap().0
+    }
+
+    fn instant(&self) -> Instant {
+        self.origin + self.state.lock().unwrap().1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadline_ignores_wall_clock_jumps() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+        let deadline = Deadline::after(&clock, Duration::from_secs(30));
+        assert_eq!(deadline.expires_at(), start + chrono::Duration::seconds(30));
+
+        // 墙上时间前后跳变都不影响到期判断
+        clock.jump_wall_clock(chrono::Duration::hours(2));
+        assert!(!deadline.is_expired(&clock));
+        clock.jump_wall_clock(chrono::Duration::hours(-4));
+        clock.advance(Duration::from_secs(29));
+        assert!(!deadline.is_expired(&clock));
+        assert!(clock.now() < start);
+
+        clock.advance(Duration::from_secs(1));
+        assert!(deadline.is_expired(&clock));
+    }
+}
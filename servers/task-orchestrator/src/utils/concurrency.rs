@@ -1,10 +1,11 @@
 use std::sync::Arc;
-use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::collections::{BTreeMap, HashMap};
 use std::time::{Duration, Instant};
-use tokio::sync::{Mutex, Semaphore};
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
 use tokio::time::timeout;
-use chrono::Utc;
 
+use crate::config::RequestQuotaConfig;
 use crate::domain::{TaskId, WorkerId};
 use crate::infrastructure::{LockManager, SqliteLockManager};
 use crate::errors::{AppError, AppResult};
@@ -17,7 +18,8 @@ pub struct ConcurrencyController {
     max_concurrent_tasks: usize,
     lock_timeout: Duration,
     cleanup_interval: Duration,
-    last_cleanup: Arc<Mutex<chrono::DateTime<chrono::Utc>>>,
+    last_cleanup: Arc<Mutex<Instant>>,
+    request_quotas: Option<Arc<RequestQuotas>>,
 }
 
 impl ConcurrencyController {
@@ -36,7 +38,8 @@ impl ConcurrencyController {
             max_concurrent_tasks,
             lock_timeout,
             cleanup_interval,
-            last_cleanup: Arc::new(Mutex::new(Utc::now())),
+            last_cleanup: Arc::new(Mutex::new(Instant::now())),
+            request_quotas: None,
         }
     }
 
@@ -55,10 +58,22 @@ impl ConcurrencyController {
             max_concurrent_tasks,
             lock_timeout,
             cleanup_interval,
-            last_cleanup: Arc::new(Mutex::new(Utc::now())),
+            last_cleanup: Arc::new(Mutex::new(Instant::now())),
+            request_quotas: None,
         }
     }
 
+    /// 启用HTTP请求并发配额
+    pub fn with_request_quotas(mut self, quotas: RequestQuotas) -> Self {
+        self.request_quotas = Some(Arc::new(quotas));
+        self
+    }
+
+    /// HTTP请求并发配额，未启用时为 `None`
+    pub fn request_quotas(&self) -> Option<Arc<RequestQuotas>> {
+        self.request_quotas.clone()
+    }
+
     /// 尝试获取任务锁
     pub async fn acquire_task_lock(&self, task_id: &TaskId, worker_id: &WorkerId) -> AppResult<TaskLockHandle> {
         let task_key = task_id.to_string();
@@ -146,7 +161,7 @@ impl ConcurrencyController {
         }
 
         // 检查分布式锁
-        let lock_result = self.lock_manager.check_lock(&task_key).await;
+        let lock_result = self.lock_manager.check_lock(&task_key).await.map_err(AppError::from);
         lock_result.map(|opt| opt.map(|s| WorkerId::new(s).unwrap_or_else(|_| WorkerId::new("unknown".to_string()).unwrap())))
     }
 
@@ -222,6 +237,7 @@ impl Clone for ConcurrencyController {
             lock_timeout: self.lock_timeout,
             cleanup_interval: self.cleanup_interval,
             last_cleanup: self.last_cleanup.clone(),
+            request_quotas: self.request_quotas.clone(),
         }
     }
 }
@@ -303,6 +319,209 @@ pub struct LockStatistics {
     pub available_permits: usize,
 }
 
+/// 请求路由组
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RouteGroup {
+    /// 获取、完成任务和上传产物等占用执行槽位的请求
+    Execute,
+    /// 其他写请求
+    Write,
+    /// 只读请求
+    Read,
+}
+
+impl RouteGroup {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteGroup::Execute => "execute",
+            RouteGroup::Write => "write",
+            RouteGroup::Read => "read",
+        }
+    }
+}
+
+/// 单个路由组的配额
+struct GroupQuota {
+    limit: usize,
+    semaphore: Option<Arc<Semaphore>>,
+    queued: AtomicUsize,
+    rejected: AtomicU64,
+}
+
+impl GroupQuota {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            semaphore: (limit > 0).then(|| Arc::new(Semaphore::new(limit))),
+            queued: AtomicUsize::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+}
+
+/// 排队计数守卫，请求被取消时同样减少排队数
+struct QueuedGuard<'a>(&'a AtomicUsize);
+
+impl Drop for QueuedGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// HTTP请求并发配额
+///
+/// 每个路由组和每个租户（API密钥或客户端地址）各有一个信号量。请求先取租户许可再取路由组许可，
+/// 单个客户端的突发请求只会在自己的租户信号量上排队，不会占满执行槽位。
+/// 排队超过 `queue_timeout` 或排队数达到 `max_queue_depth` 时拒绝请求。限额为0表示不限制。
+pub struct RequestQuotas {
+    groups: BTreeMap<RouteGroup, GroupQuota>,
+    tenant_limit: usize,
+    tenants: std::sync::Mutex<HashMap<String, Arc<Semaphore>>>,
+    queue_timeout: Duration,
+    max_queue_depth: usize,
+    tenant_rejections: AtomicU64,
+}
+
+/// 租户信号量数量超过该值时清理空闲租户
+const MAX_IDLE_TENANTS: usize = 4096;
+
+impl RequestQuotas {
+    /// 根据配置创建请求配额
+    pub fn new(config: &RequestQuotaConfig) -> Self {
+        let groups = BTreeMap::from([
+            (RouteGroup::Execute, GroupQuota::new(config.execute_limit)),
+            (RouteGroup::Write, GroupQuota::new(config.write_limit)),
+            (RouteGroup::Read, GroupQuota::new(config.read_limit)),
+        ]);
+        Self {
+            groups,
+            tenant_limit: config.per_tenant_limit,
+            tenants: std::sync::Mutex::new(HashMap::new()),
+            queue_timeout: Duration::from_millis(config.queue_timeout_ms),
+            max_queue_depth: config.max_queue_depth,
+            tenant_rejections: AtomicU64::new(0),
+        }
+    }
+
+    /// 获取请求许可，许可在请求处理完成后随 [`RequestPermit`] 释放
+    pub async fn acquire(&self, group: RouteGroup, tenant: &str) -> AppResult<RequestPermit> {
+        let quota = &self.groups[&group];
+        let deadline = tokio::time::Instant::now() + self.queue_timeout;
+
+        let queued = quota.queued.fetch_add(1, Ordering::Relaxed);
+        if self.max_queue_depth > 0 && queued >= self.max_queue_depth {
+            quota.queued.fetch_sub(1, Ordering::Relaxed);
+            quota.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(AppError::RateLimitExceeded);
+        }
+        let _queued = QueuedGuard(&quota.queued);
+
+        let tenant_permit = match self.tenant_semaphore(tenant) {
+            Some(semaphore) => match tokio::time::timeout_at(deadline, semaphore.acquire_owned()).await {
+                Ok(permit) => Some(permit.map_err(|_| AppError::ServiceUnavailable("Semaphore closed".to_string()))?),
+                Err(_) => {
+                    self.tenant_rejections.fetch_add(1, Ordering::Relaxed);
+                    return Err(AppError::RateLimitExceeded);
+                }
+            },
+            None => None,
+        };
+
+        let group_permit = match &quota.semaphore {
+            Some(semaphore) => match tokio::time::timeout_at(deadline, semaphore.clone().acquire_owned()).await {
+                Ok(permit) => Some(permit.map_err(|_| AppError::ServiceUnavailable("Semaphore closed".to_string()))?),
+                Err(_) => {
+                    quota.rejected.fetch_add(1, Ordering::Relaxed);
+                    return Err(AppError::RateLimitExceeded);
+                }
+            },
+            None => None,
+        };
+
+        Ok(RequestPermit {
+            _tenant: tenant_permit,
+            _group: group_permit,
+        })
+    }
+
+    fn tenant_semaphore(&self, tenant: &str) -> Option<Arc<Semaphore>> {
+        if self.tenant_limit == 0 {
+            return None;
+        }
+        let mut tenants = self.tenants.lock().unwrap();
+        if tenants.len() >= MAX_IDLE_TENANTS && !tenants.contains_key(tenant) {
+            let limit = self.tenant_limit;
+            tenants.retain(|_, semaphore| semaphore.available_permits() < limit || Arc::strong_count(semaphore) > 1);
+        }
+        Some(
+            tenants
+                .entry(tenant.to_string())
+                .or_insert_with(|| Arc::new(Semaphore::new(self.tenant_limit)))
+                .clone(),
+        )
+    }
+
+    /// 配额统计，包括各路由组的进行中请求数和排队深度
+    pub fn stats(&self) -> RequestQuotaStats {
+        let groups = self
+            .groups
+            .iter()
+            .map(|(group, quota)| {
+                let in_flight = quota
+                    .semaphore
+                    .as_ref()
+                    .map(|semaphore| quota.limit - semaphore.available_permits())
+                    .unwrap_or(0);
+                let stats = GroupQuotaStats {
+                    limit: quota.limit,
+                    in_flight,
+                    queued: quota.queued.load(Ordering::Relaxed),
+                    rejected: quota.rejected.load(Ordering::Relaxed),
+                };
+                (group.as_str().to_string(), stats)
+            })
+            .collect();
+        let active_tenants = self
+            .tenants
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|semaphore| semaphore.available_permits() < self.tenant_limit)
+            .count();
+        RequestQuotaStats {
+            groups,
+            per_tenant_limit: self.tenant_limit,
+            active_tenants,
+            tenant_rejections: self.tenant_rejections.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 请求许可
+#[derive(Debug)]
+pub struct RequestPermit {
+    _tenant: Option<OwnedSemaphorePermit>,
+    _group: Option<OwnedSemaphorePermit>,
+}
+
+/// 路由组配额统计
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GroupQuotaStats {
+    pub limit: usize,
+    pub in_flight: usize,
+    pub queued: usize,
+    pub rejected: u64,
+}
+
+/// 请求配额统计
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RequestQuotaStats {
+    pub groups: BTreeMap<String, GroupQuotaStats>,
+    pub per_tenant_limit: usize,
+    pub active_tenants: usize,
+    pub tenant_rejections: u64,
+}
+
 /// 速率限制器
 pub struct RateLimiter {
     requests_per_minute: u32,
@@ -529,6 +748,59 @@ impl CircuitBreaker {
         panic!("Circuit breaker is open");
     }
 
+    /// 是否允许请求通过；打开状态超过恢复时间后转为半开，放行试探请求
+    pub async fn allow_request(&self) -> bool {
+        let mut state = self.state.lock().await;
+        match *state {
+            CircuitBreakerState::Open { opened_at } if opened_at.elapsed() > self.recovery_timeout => {
+                *state = CircuitBreakerState::HalfOpen;
+                true
+            }
+            CircuitBreakerState::Open { .. } => false,
+            _ => true,
+        }
+    }
+
+    /// 记录一次成功请求
+    pub async fn record_success(&self) {
+        let mut state = self.state.lock().await;
+        if matches!(*state, CircuitBreakerState::HalfOpen) {
+            tracing::info!("Circuit breaker closed after successful request");
+        }
+        *state = CircuitBreakerState::Closed {
+            failures: 0,
+            last_failure_time: None,
+        };
+    }
+
+    /// 记录一次失败请求
+    pub async fn record_failure(&self) {
+        let mut state = self.state.lock().await;
+        match *state {
+            CircuitBreakerState::Closed { failures, .. } => {
+                let new_failures = failures + 1;
+                if new_failures >= self.failure_threshold {
+                    *state = CircuitBreakerState::Open {
+                        opened_at: Instant::now(),
+                    };
+                    tracing::warn!("Circuit breaker opened after {} failures", new_failures);
+                } else {
+                    *state = CircuitBreakerState::Closed {
+                        failures: new_failures,
+                        last_failure_time: Some(Instant::now()),
+                    };
+                }
+            }
+            CircuitBreakerState::HalfOpen => {
+                *state = CircuitBreakerState::Open {
+                    opened_at: Instant::now(),
+                };
+                tracing::warn!("Circuit breaker reopened after failed request in half-open state");
+            }
+            CircuitBreakerState::Open { .. } => {}
+        }
+    }
+
     /// 获取熔断器状态
     pub async fn get_state(&self) -> CircuitBreakerState {
         self.state.lock().await.clone()
@@ -569,19 +841,23 @@ mod tests {
 
     #[async_trait::async_trait]
     impl LockManager for MockLockManager {
-        async fn try_acquire(&self, _resource_id: &str, _owner_id: &str, _ttl_seconds: u64) -> AppResult<bool> {
+        async fn try_acquire(&self, _resource_id: &str, _owner_id: &str, _ttl_seconds: u64) -> task_store::StoreResult<bool> {
+            Ok(true)
+        }
+
+        async fn renew(&self, _resource_id: &str, _owner_id: &str, _ttl_seconds: u64) -> task_store::StoreResult<bool> {
             Ok(true)
         }
 
-        async fn release(&self, _resource_id: &str, _owner_id: &str) -> AppResult<bool> {
+        async fn release(&self, _resource_id: &str, _owner_id: &str) -> task_store::StoreResult<bool> {
             Ok(true)
         }
 
-        async fn check_lock(&self, _resource_id: &str) -> AppResult<Option<String>> {
+        async fn check_lock(&self, _resource_id: &str) -> task_store::StoreResult<Option<String>> {
             Ok(None)
         }
 
-        async fn cleanup_expired_locks(&self) -> AppResult<u64> {
+        async fn cleanup_expired_locks(&self) -> task_store::StoreResult<u64> {
             Ok(0)
         }
     }
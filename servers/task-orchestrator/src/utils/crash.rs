@@ -0,0 +1,83 @@
+//! # 崩溃处理
+//!
+//! 请求处理中的panic由 [`panic_response`] 转换为带追踪ID的500问题详情，连接不会被直接断开。
+//! 全局panic钩子（[`install_panic_hook`]，基于共享的 `crash-report` crate）记录错误日志、计入 `panics_total`，
+//! 并在开启崩溃报告时写入包含panic信息、所在请求的追踪ID、回溯和最近日志的报告文件。
+
+use std::any::Any;
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use crash_report::{PanicHook, RecentLogs};
+
+use super::i18n;
+use super::metrics::MetricsCollector;
+use crate::config::CrashReportConfig;
+use crate::errors::{current_request_context, request_problem};
+
+/// 安装全局panic钩子
+///
+/// 钩子在发生panic的线程上执行，请求处理中的panic可以取得当前请求的追踪ID。
+pub fn install_panic_hook(
+    config: CrashReportConfig,
+    recent_logs: Option<Arc<RecentLogs>>,
+    metrics: Option<Arc<MetricsCollector>>,
+) {
+    let mut hook = PanicHook::new("Task Orchestrator", env!("CARGO_PKG_VERSION"))
+        .with_trace_id(|| current_request_context().map(|ctx| ctx.trace_id));
+    if config.enabled {
+        hook = hook.with_reports(config.directory);
+    }
+    if let Some(recent_logs) = recent_logs {
+        hook = hook.with_recent_logs(recent_logs);
+    }
+    if let Some(metrics) = metrics {
+        hook = hook.with_on_panic(move || metrics.record_panic());
+    }
+    hook.install();
+}
+
+/// 请求处理panic时返回的响应，用于 `CatchPanicLayer::custom`
+///
+/// 详细信息已由panic钩子记录，响应中不包含panic消息。
+pub fn panic_response(_payload: Box<dyn Any + Send + 'static>) -> Response {
+    let locale = current_request_context().map(|ctx| ctx.locale).unwrap_or_default();
+    request_problem(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "INTERNAL_ERROR",
+        i18n::translate(locale, "error.panic", &[]),
+    )
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_handler_panic_returns_problem_details() {
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+        use tower_http::catch_panic::CatchPanicLayer;
+
+        async fn panicking_handler() -> &'static str {
+            panic!("handler bug")
+        }
+
+        let app = axum::Router::new()
+            .route("/boom", axum::routing::get(panicking_handler))
+            .layer(CatchPanicLayer::custom(panic_response))
+            .layer(axum::middleware::from_fn(crate::errors::trace_id_middleware));
+        let request = Request::builder().uri("/boom").header("x-trace-id", "trace-panic").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["code"], "INTERNAL_ERROR");
+        assert_eq!(problem["trace_id"], "trace-panic");
+        assert!(!problem["detail"].as_str().unwrap().contains("handler bug"));
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::OnceLock;
+
+use message_catalog::MessageCatalog;
+
+pub use message_catalog::Locale;
+
+fn catalog() -> &'static MessageCatalog {
+    static CATALOG: OnceLock<MessageCatalog> = OnceLock::new();
+    CATALOG.get_or_init(|| {
+        MessageCatalog::from_sources(&[
+            (Locale::En, include_str!("../../locales/en.json")),
+            (Locale::ZhCn, include_str!("../../locales/zh-CN.json")),
+        ])
+    })
+}
+
+/// 查找消息模板并填充 `{0}`、`{1}` 等占位符
+///
+/// 当前语言缺少该消息时回退到英文，英文也缺少时返回消息键本身。
+pub fn translate(locale: Locale, key: &str, args: &[String]) -> String {
+    catalog().translate(locale, key, args)
+}
+
+/// 查找消息模板，不存在时返回 `None`
+pub fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    catalog().lookup(locale, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalogs_have_same_keys() {
+        let missing = catalog().inconsistent_keys();
+        assert!(missing.is_empty(), "catalog keys differ: {:?}", missing);
+    }
+
+    #[test]
+    fn test_translate_with_args() {
+        let args = vec!["waiting".to_string(), "completed".to_string()];
+        assert_eq!(
+            translate(Locale::ZhCn, "task.invalid_status_transition", &args),
+            "无法从 waiting 状态转换到 completed 状态"
+        );
+        assert_eq!(translate(Locale::En, "missing.key", &[]), "missing.key");
+    }
+}
@@ -0,0 +1,113 @@
+use std::io;
+use std::net::SocketAddr;
+use std::os::unix::io::{FromRawFd, RawFd};
+
+use tokio::net::{TcpListener, TcpSocket};
+
+/// 套接字激活传入的第一个文件描述符（sd_listen_fds 协议）
+const LISTEN_FDS_START: RawFd = 3;
+
+/// 与TCP默认值一致的等待连接队列长度
+const LISTEN_BACKLOG: u32 = 1024;
+
+/// systemd 套接字激活传入的监听套接字
+///
+/// 按 `LISTEN_PID`/`LISTEN_FDS`/`LISTEN_FDNAMES` 读取，读取后清除这些环境变量，避免子进程误用。
+/// 套接字由 `.socket` 单元持有，服务重启期间新连接在内核队列中等待，不会被拒绝。
+#[derive(Debug, Default)]
+pub struct ActivatedSockets {
+    fds: Vec<(String, RawFd)>,
+}
+
+impl ActivatedSockets {
+    /// 从环境变量读取，未通过套接字激活启动时为空
+    pub fn from_env() -> Self {
+        let sockets = Self::parse(
+            std::env::var("LISTEN_PID").ok().as_deref(),
+            std::env::var("LISTEN_FDS").ok().as_deref(),
+            std::env::var("LISTEN_FDNAMES").ok().as_deref(),
+            std::process::id(),
+        );
+        for name in ["LISTEN_PID", "LISTEN_FDS", "LISTEN_FDNAMES"] {
+            std::env::remove_var(name);
+        }
+        sockets
+    }
+
+    fn parse(pid: Option<&str>, count: Option<&str>, names: Option<&str>, current_pid: u32) -> Self {
+        if pid.and_then(|pid| pid.parse::<u32>().ok()) != Some(current_pid) {
+            return Self::default();
+        }
+        let count: RawFd = count.and_then(|count| count.parse().ok()).unwrap_or(0);
+        let mut names = names.unwrap_or_default().split(':');
+        let fds = (0..count)
+            .map(|offset| {
+                let name = names.next().filter(|name| !name.is_empty()).unwrap_or("unknown");
+                (name.to_string(), LISTEN_FDS_START + offset)
+            })
+            .collect();
+        Self { fds }
+    }
+
+    /// 取出指定名称（`.socket` 单元中的 `FileDescriptorName=`）的TCP监听套接字
+    ///
+    /// `api` 在没有同名套接字时取第一个未命名的套接字，因此只传入一个套接字时无需命名。
+    pub fn take_tcp(&mut self, name: &str) -> io::Result<Option<TcpListener>> {
+        let position = self.fds.iter().position(|(fd_name, _)| fd_name == name).or_else(|| {
+            (name == "api").then(|| self.fds.iter().position(|(fd_name, _)| fd_name == "unknown")).flatten()
+        });
+        let Some(position) = position else {
+            return Ok(None);
+        };
+        let (_, fd) = self.fds.remove(position);
+
+        // 套接字激活约定由服务独占这些描述符，所有权在此转移给监听器
+        let listener = unsafe { std::net::TcpListener::from_raw_fd(fd) };
+        listener.local_addr().map_err(|e| {
+            io::Error::new(e.kind(), format!("Activated socket '{}' (fd {}) is not a TCP listener: {}", name, fd, e))
+        })?;
+        listener.set_nonblocking(true)?;
+        TcpListener::from_std(listener).map(Some)
+    }
+}
+
+/// 绑定TCP监听器
+///
+/// `reuse_port` 开启 `SO_REUSEPORT`，允许新旧进程同时监听同一端口：新进程就绪后再向旧进程发送关闭信号，
+/// 旧进程停止接受新连接并处理完进行中的请求后退出。
+pub fn bind_tcp(addr: SocketAddr, reuse_port: bool) -> io::Result<TcpListener> {
+    let socket = match addr {
+        SocketAddr::V4(_) => TcpSocket::new_v4()?,
+        SocketAddr::V6(_) => TcpSocket::new_v6()?,
+    };
+    socket.set_reuseaddr(true)?;
+    if reuse_port {
+        socket.set_reuseport(true)?;
+    }
+    socket.bind(addr)?;
+    socket.listen(LISTEN_BACKLOG)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_activated_sockets_and_reuse_port() {
+        assert!(ActivatedSockets::parse(Some("7"), Some("1"), None, 42).fds.is_empty());
+        assert!(ActivatedSockets::parse(None, Some("1"), None, 42).fds.is_empty());
+        let sockets = ActivatedSockets::parse(Some("42"), Some("2"), Some("metrics:"), 42);
+        assert_eq!(
+            sockets.fds,
+            vec![("metrics".to_string(), 3), ("unknown".to_string(), 4)]
+        );
+
+        // 两个进程可以通过 SO_REUSEPORT 同时监听同一端口
+        let first = bind_tcp("127.0.0.1:0".parse().unwrap(), true).unwrap();
+        let addr = first.local_addr().unwrap();
+        let second = bind_tcp(addr, true).unwrap();
+        assert_eq!(second.local_addr().unwrap(), addr);
+        drop(second);
+        assert!(bind_tcp(addr, false).is_err());
+    }
+}
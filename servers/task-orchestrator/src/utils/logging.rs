@@ -1,18 +1,31 @@
 use tracing::{info, warn, error, debug, instrument, Span};
-use tracing_subscriber::{layer::SubscriberExt, EnvFilter};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 use std::path::Path;
+use std::sync::Arc;
 
 use crate::config::LoggingConfig;
+use crash_report::{RecentLogs, RecentLogsLayer};
+use super::redaction::Redactor;
 
 /// 日志管理器
 pub struct LogManager {
     config: LoggingConfig,
+    recent_logs: Option<Arc<RecentLogs>>,
 }
 
 impl LogManager {
     /// 创建新的日志管理器
     pub fn new(config: LoggingConfig) -> Self {
-        Self { config }
+        let recent_logs = config
+            .crash_report
+            .enabled
+            .then(|| Arc::new(RecentLogs::new(config.crash_report.recent_log_lines)));
+        Self { config, recent_logs }
+    }
+
+    /// 崩溃报告使用的最近日志缓冲区，未开启崩溃报告时为空
+    pub fn recent_logs(&self) -> Option<Arc<RecentLogs>> {
+        self.recent_logs.clone()
     }
 
     /// 初始化日志系统
@@ -25,10 +38,13 @@ impl LogManager {
                     .add_directive("task_orchestrator=info".parse().unwrap())
             });
 
-        // 初始化全局订阅者 - 简化实现
-        tracing_subscriber::fmt()
-            .with_env_filter(env_filter)
-            .init();
+        // 初始化全局订阅者 - 简化实现，开启崩溃报告时额外保留最近日志
+        let recent_logs = self.recent_logs.clone().map(RecentLogsLayer::new);
+        tracing_subscriber::registry()
+            .with(env_filter)
+            .with(tracing_subscriber::fmt::layer())
+            .with(recent_logs)
+            .try_init()?;
 
         info!("Logging system initialized with level: {}", self.config.level);
         Ok(())
@@ -44,18 +60,27 @@ impl LogManager {
 #[derive(Clone)]
 pub struct StructuredLogger {
     config: LoggingConfig,
+    redactor: Arc<Redactor>,
 }
 
 impl StructuredLogger {
     pub fn new(config: &LoggingConfig) -> Self {
         Self {
             config: config.clone(),
+            redactor: Arc::new(Redactor::from_config(&config.redaction)),
         }
     }
 
+    /// 获取敏感数据脱敏器
+    pub fn redactor(&self) -> &Redactor {
+        &self.redactor
+    }
+
     /// 记录一般信息日志
     pub fn log_info(&self, message: &str, context: Option<&str>) {
-        if let Some(ctx) = context {
+        let message = self.redactor.redact_text(message);
+        let context = context.map(|c| self.redactor.redact_text(c));
+        if let Some(ctx) = &context {
             info!(message = %message, context = %ctx, "Info");
         } else {
             info!(message = %message, "Info");
@@ -124,6 +149,8 @@ impl StructuredLogger {
         error_message
     ))]
     pub fn log_task_completed(&self, task_id: &str, worker_id: &str, status: &str, processing_time_ms: u64, result_status: &str, error_message: Option<&str>) {
+        let redacted_error = error_message.map(|e| self.redactor.redact_text(e));
+        let error_message = redacted_error.as_deref();
         let span = Span::current();
         span.record("task_id", task_id);
         span.record("worker_id", worker_id);
@@ -155,6 +182,8 @@ impl StructuredLogger {
         max_retries
     ))]
     pub fn log_task_failed(&self, task_id: &str, worker_id: &str, error: &str, retry_count: u32, max_retries: u32) {
+        let redacted_error = self.redactor.redact_text(error);
+        let error = redacted_error.as_ref();
         let span = Span::current();
         span.record("task_id", task_id);
         span.record("worker_id", worker_id);
@@ -179,6 +208,8 @@ impl StructuredLogger {
         cancelled_by
     ))]
     pub fn log_task_cancelled(&self, task_id: &str, reason: Option<&str>, cancelled_by: Option<&str>) {
+        let redacted_reason = reason.map(|r| self.redactor.redact_text(r));
+        let reason = redacted_reason.as_deref();
         let span = Span::current();
         span.record("task_id", task_id);
         
@@ -267,6 +298,10 @@ impl StructuredLogger {
         context
     ))]
     pub fn log_error(&self, error_type: &str, error_message: &str, stack_trace: Option<&str>, context: Option<&str>) {
+        let redacted_message = self.redactor.redact_text(error_message);
+        let error_message = redacted_message.as_ref();
+        let redacted_context = context.map(|c| self.redactor.redact_text(c));
+        let context = redacted_context.as_deref();
         let span = Span::current();
         span.record("error_type", error_type);
         span.record("error_message", error_message);
@@ -354,137 +389,6 @@ impl<B> tower_http::trace::MakeSpan<B> for LoggingMiddleware {
     }
 }
 
-/// 监控指标收集器
-pub struct MetricsCollector {
-    task_created_counter: prometheus::Counter,
-    task_completed_counter: prometheus::Counter,
-    task_failed_counter: prometheus::Counter,
-    task_cancelled_counter: prometheus::Counter,
-    task_acquired_counter: prometheus::Counter,
-    response_time_histogram: prometheus::Histogram,
-    active_tasks_gauge: prometheus::Gauge,
-    queue_size_gauge: prometheus::Gauge,
-    error_counter: prometheus::Counter,
-    database_query_duration: prometheus::Histogram,
-}
-
-impl MetricsCollector {
-    /// 创建新的指标收集器
-    pub fn new() -> Result<Self, prometheus::Error> {
-        Ok(Self {
-            task_created_counter: prometheus::Counter::with_opts(
-                prometheus::Opts::new("task_created_total", "Total number of tasks created")
-                    .const_label("service", "task_orchestrator")
-            )?,
-            
-            task_completed_counter: prometheus::Counter::with_opts(
-                prometheus::Opts::new("task_completed_total", "Total number of tasks completed")
-                    .const_label("service", "task_orchestrator")
-            )?,
-            
-            task_failed_counter: prometheus::Counter::with_opts(
-                prometheus::Opts::new("task_failed_total", "Total number of tasks failed")
-                    .const_label("service", "task_orchestrator")
-            )?,
-            
-            task_cancelled_counter: prometheus::Counter::with_opts(
-                prometheus::Opts::new("task_cancelled_total", "Total number of tasks cancelled")
-                    .const_label("service", "task_orchestrator")
-            )?,
-            
-            task_acquired_counter: prometheus::Counter::with_opts(
-                prometheus::Opts::new("task_acquired_total", "Total number of tasks acquired")
-                    .const_label("service", "task_orchestrator")
-            )?,
-            
-            response_time_histogram: prometheus::Histogram::with_opts(
-                prometheus::HistogramOpts::new("response_time_seconds", "HTTP response time")
-                    .const_label("service", "task_orchestrator")
-                    .buckets(vec![0.1, 0.5, 1.0, 2.5, 5.0, 10.0])
-            )?,
-            
-            active_tasks_gauge: prometheus::Gauge::with_opts(
-                prometheus::Opts::new("active_tasks", "Number of currently active tasks")
-                    .const_label("service", "task_orchestrator")
-            )?,
-            
-            queue_size_gauge: prometheus::Gauge::with_opts(
-                prometheus::Opts::new("queue_size", "Current queue size")
-                    .const_label("service", "task_orchestrator")
-            )?,
-            
-            error_counter: prometheus::Counter::with_opts(
-                prometheus::Opts::new("errors_total", "Total number of errors")
-                    .const_label("service", "task_orchestrator")
-            )?,
-            
-            database_query_duration: prometheus::Histogram::with_opts(
-                prometheus::HistogramOpts::new("database_query_duration_seconds", "Database query duration")
-                    .const_label("service", "task_orchestrator")
-                    .buckets(vec![0.001, 0.01, 0.1, 1.0])
-            )?,
-        })
-    }
-
-    /// 记录任务创建
-    pub fn record_task_created(&self, _priority: &str) {
-        self.task_created_counter.inc();
-    }
-
-    /// 记录任务完成
-    pub fn record_task_completed(&self, _priority: &str, processing_time: f64) {
-        self.task_completed_counter.inc();
-        self.response_time_histogram.observe(processing_time);
-    }
-
-    /// 记录任务失败
-    pub fn record_task_failed(&self, _priority: &str, _error_type: &str) {
-        self.task_failed_counter.inc();
-    }
-
-    /// 记录任务取消
-    pub fn record_task_cancelled(&self, _reason: &str) {
-        self.task_cancelled_counter.inc();
-    }
-
-    /// 记录任务获取
-    pub fn record_task_acquired(&self, _worker_id: &str) {
-        self.task_acquired_counter.inc();
-    }
-
-    /// 设置活跃任务数量
-    pub fn set_active_tasks(&self, count: u64) {
-        self.active_tasks_gauge.set(count as f64);
-    }
-
-    /// 设置队列大小
-    pub fn set_queue_size(&self, size: u64) {
-        self.queue_size_gauge.set(size as f64);
-    }
-
-    /// 记录错误
-    pub fn record_error(&self, _error_type: &str, _endpoint: &str) {
-        self.error_counter.inc();
-    }
-
-    /// 记录数据库查询时间
-    pub fn record_database_query(&self, _operation: &str, _table: &str, duration: f64) {
-        self.database_query_duration.observe(duration);
-    }
-
-    /// 获取所有指标的文本格式
-    pub fn gather_metrics(&self) -> String {
-        use prometheus::Encoder;
-        
-        let encoder = prometheus::TextEncoder::new();
-        let metric_families = prometheus::gather();
-        let mut buffer = Vec::new();
-        
-        encoder.encode(&metric_families, &mut buffer).unwrap();
-        String::from_utf8(buffer).unwrap()
-    }
-}
-
 /// 健康检查器
 pub struct HealthChecker {
     database_healthy: bool,
@@ -592,12 +496,6 @@ mod tests {
         assert!(logger.config.enable_json);
     }
 
-    #[test]
-    fn test_metrics_collector_creation() {
-        let collector = MetricsCollector::new();
-        assert!(collector.is_ok());
-    }
-
     #[test]
     fn test_health_checker_creation() {
         let checker = HealthChecker::new();
@@ -0,0 +1,641 @@
+//! # Prometheus指标
+//!
+//! 任务生命周期和HTTP请求的指标集中在 [`MetricsCollector`] 中，并通过两道限制控制标签基数：
+//!
+//! - 标签白名单：只有配置中允许的标签会出现在指标上，未允许的标签在注册时就被去掉（例如默认不输出 `worker_id`）
+//! - 取值上限：每个标签记录的不同取值数达到上限后，新取值统一记为 [`OVERFLOW_LABEL_VALUE`]，
+//!   并计入 `metric_label_overflow_total`
+//!
+//! `work_directory` 标签先截断到前几级路径，可选输出路径哈希，避免每个项目目录产生一组时间序列。
+//!
+//! 直方图样本可以附带追踪ID示例（exemplar），在Grafana中从延迟分布跳转到对应请求的追踪。
+//! Prometheus文本格式不支持示例，客户端在 `Accept` 中声明 `application/openmetrics-text` 时才输出。
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use prometheus::proto::{MetricFamily, MetricType};
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder};
+use sha2::{Digest, Sha256};
+
+use memory_watchdog::MemoryPressure;
+use crate::config::MetricLabelsConfig;
+use crate::infrastructure::CacheStats;
+
+/// 超出取值上限的标签值
+pub const OVERFLOW_LABEL_VALUE: &str = "__overflow__";
+
+/// Prometheus文本格式的Content-Type
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// OpenMetrics文本格式的Content-Type
+pub const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// 所有指标携带的服务标签
+const SERVICE_LABEL: (&str, &str) = ("service", "task_orchestrator");
+
+/// 示例中追踪ID的最大长度，OpenMetrics限制示例标签总长不超过128个字符
+const MAX_EXEMPLAR_TRACE_ID_LEN: usize = 100;
+
+const TASK_DURATION_BUCKETS: &[f64] = &[1.0, 5.0, 15.0, 60.0, 300.0, 900.0, 3600.0];
+const RESPONSE_TIME_BUCKETS: &[f64] = &[0.005, 0.025, 0.1, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// 标签基数限制
+pub struct LabelGuard {
+    allowed: HashSet<String>,
+    max_values: usize,
+    work_directory_depth: usize,
+    hash_work_directory: bool,
+    seen: Mutex<HashMap<&'static str, HashSet<String>>>,
+    overflow: IntCounterVec,
+}
+
+impl LabelGuard {
+    fn new(config: &MetricLabelsConfig, overflow: IntCounterVec) -> Self {
+        Self {
+            allowed: config.allowed_labels.iter().cloned().collect(),
+            max_values: config.max_values_per_label,
+            work_directory_depth: config.work_directory_depth.max(1),
+            hash_work_directory: config.hash_work_directory,
+            seen: Mutex::new(HashMap::new()),
+            overflow,
+        }
+    }
+
+    /// 过滤出允许的标签名，保持原有顺序
+    fn allowed_names(&self, candidates: &[&'static str]) -> Vec<&'static str> {
+        candidates.iter().copied().filter(|name| self.allowed.contains(*name)).collect()
+    }
+
+    /// 按允许的标签名取出并限制标签值
+    fn values(&self, names: &[&'static str], labels: &[(&'static str, &str)]) -> Vec<String> {
+        names
+            .iter()
+            .map(|name| {
+                let raw = labels.iter().find(|(label, _)| label == name).map_or("", |(_, value)| *value);
+                self.limit(name, raw)
+            })
+            .collect()
+    }
+
+    fn limit(&self, name: &'static str, raw: &str) -> String {
+        let value = if name == "work_directory" {
+            self.normalize_work_directory(raw)
+        } else {
+            raw.to_string()
+        };
+
+        let mut seen = self.seen.lock().unwrap();
+        let values = seen.entry(name).or_default();
+        if values.contains(&value) {
+            return value;
+        }
+        if values.len() >= self.max_values {
+            self.overflow.with_label_values(&[name]).inc();
+            return OVERFLOW_LABEL_VALUE.to_string();
+        }
+        values.insert(value.clone());
+        value
+    }
+
+    /// 截断到前几级路径，按配置输出路径哈希
+    fn normalize_work_directory(&self, path: &str) -> String {
+        let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+        let prefix = format!("/{}", components[..components.len().min(self.work_directory_depth)].join("/"));
+        if self.hash_work_directory {
+            let digest = Sha256::digest(prefix.as_bytes());
+            format!("wd-{}", &hex::encode(digest)[..12])
+        } else {
+            prefix
+        }
+    }
+}
+
+/// 直方图样本示例
+#[derive(Debug, Clone)]
+struct Exemplar {
+    trace_id: String,
+    value: f64,
+    timestamp: f64,
+}
+
+/// 按时间序列和桶保存最近一次的示例
+#[derive(Default)]
+struct ExemplarStore {
+    exemplars: Mutex<HashMap<String, HashMap<usize, Exemplar>>>,
+}
+
+impl ExemplarStore {
+    fn record(&self, series: String, bucket: usize, trace_id: &str, value: f64) {
+        let trace_id: String = trace_id.chars().take(MAX_EXEMPLAR_TRACE_ID_LEN).collect();
+        let exemplar = Exemplar {
+            trace_id,
+            value,
+            timestamp: chrono::Utc::now().timestamp_millis() as f64 / 1000.0,
+        };
+        self.exemplars.lock().unwrap().entry(series).or_default().insert(bucket, exemplar);
+    }
+
+    fn get(&self, series: &str, bucket: usize) -> Option<Exemplar> {
+        self.exemplars.lock().unwrap().get(series)?.get(&bucket).cloned()
+    }
+}
+
+/// 时间序列键：指标名加按名称排序的全部标签
+fn series_key<'a>(name: &str, labels: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    let mut labels: Vec<_> = labels.into_iter().collect();
+    labels.sort();
+    let labels: Vec<String> = labels.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    format!("{}{{{}}}", name, labels.join(","))
+}
+
+/// 经过标签限制的计数器
+struct GuardedCounter {
+    metric: IntCounterVec,
+    labels: Vec<&'static str>,
+}
+
+/// 经过标签限制、可附带示例的直方图
+struct GuardedHistogram {
+    name: &'static str,
+    metric: HistogramVec,
+    labels: Vec<&'static str>,
+    buckets: &'static [f64],
+}
+
+/// 监控指标收集器
+pub struct MetricsCollector {
+    registry: Registry,
+    guard: LabelGuard,
+    exemplars: Option<ExemplarStore>,
+    task_created: GuardedCounter,
+    task_completed: GuardedCounter,
+    task_failed: GuardedCounter,
+    task_cancelled: GuardedCounter,
+    task_acquired: GuardedCounter,
+    task_duration: GuardedHistogram,
+    response_time: GuardedHistogram,
+    active_tasks_gauge: IntGauge,
+    queue_size_gauge: IntGauge,
+    panics: IntCounter,
+    memory_rss_gauge: IntGauge,
+    memory_pressure_gauge: IntGauge,
+    memory_evictions: IntCounter,
+    sla_breaches: IntGaugeVec,
+    sla_at_risk: IntGaugeVec,
+    sla_alerts: IntCounterVec,
+    event_export: IntCounterVec,
+    secret_findings: IntCounterVec,
+    cache_lookups: IntCounterVec,
+    cache_invalidations: IntCounter,
+    /// 同步缓存计数时持有，避免并发抓取重复累加差值
+    cache_sync: Mutex<()>,
+}
+
+impl MetricsCollector {
+    /// 创建新的指标收集器
+    pub fn new(config: &MetricLabelsConfig) -> Result<Self, prometheus::Error> {
+        let registry = Registry::new();
+        let overflow = IntCounterVec::new(
+            Opts::new("metric_label_overflow_total", "Label values replaced because the per-label limit was reached")
+                .const_label(SERVICE_LABEL.0, SERVICE_LABEL.1),
+            &["label"],
+        )?;
+        registry.register(Box::new(overflow.clone()))?;
+        let guard = LabelGuard::new(config, overflow);
+
+        let counter = |name: &str, help: &str, labels: &[&'static str]| -> Result<GuardedCounter, prometheus::Error> {
+            let labels = guard.allowed_names(labels);
+            let metric = IntCounterVec::new(Opts::new(name, help).const_label(SERVICE_LABEL.0, SERVICE_LABEL.1), &labels)?;
+            registry.register(Box::new(metric.clone()))?;
+            Ok(GuardedCounter { metric, labels })
+        };
+        let task_created = counter("task_created_total", "Total number of tasks created", &["work_directory", "priority"])?;
+        let task_completed = counter("task_completed_total", "Total number of tasks completed", &["work_directory", "priority"])?;
+        let task_failed = counter("task_failed_total", "Total number of tasks failed", &["work_directory", "priority"])?;
+        let task_cancelled = counter("task_cancelled_total", "Total number of tasks cancelled", &["work_directory", "reason"])?;
+        let task_acquired = counter("task_acquired_total", "Total number of tasks acquired", &["work_directory", "worker_id"])?;
+
+        let histogram = |name: &'static str, help: &str, labels: &[&'static str], buckets: &'static [f64]| -> Result<GuardedHistogram, prometheus::Error> {
+            let labels = guard.allowed_names(labels);
+            let opts = HistogramOpts::new(name, help)
+                .const_label(SERVICE_LABEL.0, SERVICE_LABEL.1)
+                .buckets(buckets.to_vec());
+            let metric = HistogramVec::new(opts, &labels)?;
+            registry.register(Box::new(metric.clone()))?;
+            Ok(GuardedHistogram { name, metric, labels, buckets })
+        };
+        let task_duration = histogram(
+            "task_duration_seconds",
+            "Task processing time from start to completion",
+            &["work_directory", "priority"],
+            TASK_DURATION_BUCKETS,
+        )?;
+        let response_time = histogram(
+            "response_time_seconds",
+            "HTTP response time",
+            &["method", "route", "status"],
+            RESPONSE_TIME_BUCKETS,
+        )?;
+
+        let gauge = |name: &str, help: &str| -> Result<IntGauge, prometheus::Error> {
+            let metric = IntGauge::with_opts(Opts::new(name, help).const_label(SERVICE_LABEL.0, SERVICE_LABEL.1))?;
+            registry.register(Box::new(metric.clone()))?;
+            Ok(metric)
+        };
+        let active_tasks_gauge = gauge("active_tasks", "Number of currently active tasks")?;
+        let queue_size_gauge = gauge("queue_size", "Number of tasks waiting to be acquired")?;
+        let memory_rss_gauge = gauge("memory_rss_bytes", "Resident memory of the process")?;
+        let memory_pressure_gauge = gauge("memory_pressure", "Memory pressure level (0 normal, 1 elevated, 2 critical)")?;
+        let plain_counter = |name: &str, help: &str| -> Result<IntCounter, prometheus::Error> {
+            let metric = IntCounter::with_opts(Opts::new(name, help).const_label(SERVICE_LABEL.0, SERVICE_LABEL.1))?;
+            registry.register(Box::new(metric.clone()))?;
+            Ok(metric)
+        };
+        let panics = plain_counter("panics_total", "Total number of panics")?;
+        let memory_evictions = plain_counter("memory_cache_evictions_total", "In-memory cache evictions triggered by memory pressure")?;
+
+        // SLA指标的标签取值由配置决定，数量有限，不经过标签限制
+        let sla_breaches = IntGaugeVec::new(
+            Opts::new("sla_breaches", "Tasks that missed their SLA target within the SLA window")
+                .const_label(SERVICE_LABEL.0, SERVICE_LABEL.1),
+            &["priority", "kind"],
+        )?;
+        registry.register(Box::new(sla_breaches.clone()))?;
+        let sla_at_risk = IntGaugeVec::new(
+            Opts::new("sla_at_risk_tasks", "Active tasks close to missing their SLA target")
+                .const_label(SERVICE_LABEL.0, SERVICE_LABEL.1),
+            &["priority"],
+        )?;
+        registry.register(Box::new(sla_at_risk.clone()))?;
+        let sla_alerts = IntCounterVec::new(
+            Opts::new("sla_alerts_fired_total", "Number of times an SLA alert started firing")
+                .const_label(SERVICE_LABEL.0, SERVICE_LABEL.1),
+            &["alert"],
+        )?;
+        registry.register(Box::new(sla_alerts.clone()))?;
+        let event_export = IntCounterVec::new(
+            Opts::new("task_event_export_total", "Task lifecycle events handled by the exporter, by outcome")
+                .const_label(SERVICE_LABEL.0, SERVICE_LABEL.1),
+            &["outcome"],
+        )?;
+        registry.register(Box::new(event_export.clone()))?;
+        // 规则名来自内置规则和配置，数量有限
+        let secret_findings = IntCounterVec::new(
+            Opts::new("secret_scan_findings_total", "Potential secrets found in task prompts and metadata, by rule and action")
+                .const_label(SERVICE_LABEL.0, SERVICE_LABEL.1),
+            &["rule", "action"],
+        )?;
+        registry.register(Box::new(secret_findings.clone()))?;
+        let cache_lookups = IntCounterVec::new(
+            Opts::new("task_cache_lookups_total", "Task cache lookups, by cache and result")
+                .const_label(SERVICE_LABEL.0, SERVICE_LABEL.1),
+            &["cache", "result"],
+        )?;
+        registry.register(Box::new(cache_lookups.clone()))?;
+        let cache_invalidations = plain_counter("task_cache_invalidations_total", "Task cache invalidations caused by writes")?;
+
+        Ok(Self {
+            registry,
+            exemplars: config.enable_exemplars.then(ExemplarStore::default),
+            guard,
+            task_created,
+            task_completed,
+            task_failed,
+            task_cancelled,
+            task_acquired,
+            task_duration,
+            response_time,
+            active_tasks_gauge,
+            queue_size_gauge,
+            panics,
+            memory_rss_gauge,
+            memory_pressure_gauge,
+            memory_evictions,
+            sla_breaches,
+            sla_at_risk,
+            sla_alerts,
+            event_export,
+            secret_findings,
+            cache_lookups,
+            cache_invalidations,
+            cache_sync: Mutex::new(()),
+        })
+    }
+
+    fn inc(&self, counter: &GuardedCounter, labels: &[(&'static str, &str)]) {
+        let values = self.guard.values(&counter.labels, labels);
+        let values: Vec<&str> = values.iter().map(String::as_str).collect();
+        counter.metric.with_label_values(&values).inc();
+    }
+
+    fn observe(&self, histogram: &GuardedHistogram, labels: &[(&'static str, &str)], value: f64, trace_id: Option<&str>) {
+        let values = self.guard.values(&histogram.labels, labels);
+        let refs: Vec<&str> = values.iter().map(String::as_str).collect();
+        histogram.metric.with_label_values(&refs).observe(value);
+
+        if let (Some(store), Some(trace_id)) = (&self.exemplars, trace_id) {
+            // 与导出时的桶序号一致，最后一个是 +Inf 桶
+            let bucket = histogram.buckets.iter().position(|bound| value <= *bound).unwrap_or(histogram.buckets.len());
+            let series = series_key(
+                histogram.name,
+                histogram.labels.iter().copied().zip(refs.iter().copied()).chain([SERVICE_LABEL]),
+            );
+            store.record(series, bucket, trace_id, value);
+        }
+    }
+
+    /// 记录任务创建
+    pub fn record_task_created(&self, work_directory: &str, priority: &str) {
+        self.inc(&self.task_created, &[("work_directory", work_directory), ("priority", priority)]);
+    }
+
+    /// 记录任务完成，处理时间附带追踪ID示例
+    pub fn record_task_completed(&self, work_directory: &str, priority: &str, processing_time: f64, trace_id: Option<&str>) {
+        let labels = [("work_directory", work_directory), ("priority", priority)];
+        self.inc(&self.task_completed, &labels);
+        self.observe(&self.task_duration, &labels, processing_time, trace_id);
+    }
+
+    /// 记录任务失败
+    pub fn record_task_failed(&self, work_directory: &str, priority: &str) {
+        self.inc(&self.task_failed, &[("work_directory", work_directory), ("priority", priority)]);
+    }
+
+    /// 记录任务取消
+    pub fn record_task_cancelled(&self, work_directory: &str, reason: &str) {
+        self.inc(&self.task_cancelled, &[("work_directory", work_directory), ("reason", reason)]);
+    }
+
+    /// 记录任务获取
+    pub fn record_task_acquired(&self, work_directory: &str, worker_id: &str) {
+        self.inc(&self.task_acquired, &[("work_directory", work_directory), ("worker_id", worker_id)]);
+    }
+
+    /// 记录HTTP请求耗时，`route` 应为路由模板而不是实际路径
+    pub fn record_http_request(&self, method: &str, route: &str, status: u16, duration: f64, trace_id: Option<&str>) {
+        let status = status.to_string();
+        let labels = [("method", method), ("route", route), ("status", status.as_str())];
+        self.observe(&self.response_time, &labels, duration, trace_id);
+    }
+
+    /// 设置活跃任务数量
+    pub fn set_active_tasks(&self, count: u64) {
+        self.active_tasks_gauge.set(count as i64);
+    }
+
+    /// 设置队列大小
+    pub fn set_queue_size(&self, size: u64) {
+        self.queue_size_gauge.set(size as i64);
+    }
+
+    /// 记录一次panic
+    pub fn record_panic(&self) {
+        self.panics.inc();
+    }
+
+    /// 记录内存自监控读数
+    pub fn record_memory_sample(&self, rss_bytes: u64, pressure: MemoryPressure, evicted: bool) {
+        self.memory_rss_gauge.set(rss_bytes as i64);
+        self.memory_pressure_gauge.set(pressure as i64);
+        if evicted {
+            self.memory_evictions.inc();
+        }
+    }
+
+    /// 记录某优先级的SLA违约数和风险任务数
+    pub fn set_sla(&self, priority: &str, start_breaches: u64, finish_breaches: u64, at_risk: u64) {
+        self.sla_breaches.with_label_values(&[priority, "start"]).set(start_breaches as i64);
+        self.sla_breaches.with_label_values(&[priority, "finish"]).set(finish_breaches as i64);
+        self.sla_at_risk.with_label_values(&[priority]).set(at_risk as i64);
+    }
+
+    /// 记录一次SLA告警触发
+    pub fn record_sla_alert(&self, alert: &str) {
+        self.sla_alerts.with_label_values(&[alert]).inc();
+    }
+
+    /// 记录事件导出结果（enqueued、delivered、failed、dropped、retried）
+    pub fn record_event_export(&self, outcome: &str, count: u64) {
+        self.event_export.with_label_values(&[outcome]).inc_by(count);
+    }
+
+    /// 记录一条密钥扫描结果
+    pub fn record_secret_finding(&self, rule: &str, action: &str) {
+        self.secret_findings.with_label_values(&[rule, action]).inc();
+    }
+
+    /// 把任务缓存的累计命中、未命中和失效次数同步到计数器，在导出前调用
+    pub fn set_task_cache_stats(&self, stats: &CacheStats) {
+        let _sync = self.cache_sync.lock().unwrap();
+        let advance = |counter: &IntCounter, value: u64| {
+            let current = counter.get();
+            if value > current {
+                counter.inc_by(value - current);
+            }
+        };
+        advance(&self.cache_lookups.with_label_values(&["task", "hit"]), stats.task_hits);
+        advance(&self.cache_lookups.with_label_values(&["task", "miss"]), stats.task_misses);
+        advance(&self.cache_lookups.with_label_values(&["list", "hit"]), stats.list_hits);
+        advance(&self.cache_lookups.with_label_values(&["list", "miss"]), stats.list_misses);
+        advance(&self.cache_invalidations, stats.invalidations);
+    }
+
+    /// 导出全部指标，`openmetrics` 为真时使用OpenMetrics格式并输出示例
+    pub fn render(&self, openmetrics: bool) -> String {
+        let families = self.registry.gather();
+        if openmetrics {
+            return encode_openmetrics(&families, self.exemplars.as_ref());
+        }
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&families, &mut buffer)
+            .expect("text encoding does not fail");
+        String::from_utf8(buffer).expect("prometheus text output is utf-8")
+    }
+}
+
+/// 客户端是否接受OpenMetrics格式
+pub fn accepts_openmetrics(accept: Option<&str>) -> bool {
+    accept.is_some_and(|accept| accept.contains("application/openmetrics-text"))
+}
+
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn format_labels(labels: &[(&str, String)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_label_value(value)))
+        .collect();
+    format!("{{{}}}", labels.join(","))
+}
+
+/// 按OpenMetrics 1.0文本格式编码
+fn encode_openmetrics(families: &[MetricFamily], exemplars: Option<&ExemplarStore>) -> String {
+    let mut out = String::new();
+    for family in families {
+        let name = family.get_name();
+        let (family_name, kind) = match family.get_field_type() {
+            MetricType::COUNTER => (name.strip_suffix("_total").unwrap_or(name), "counter"),
+            MetricType::GAUGE => (name, "gauge"),
+            MetricType::HISTOGRAM => (name, "histogram"),
+            _ => (name, "unknown"),
+        };
+        out.push_str(&format!("# TYPE {} {}\n", family_name, kind));
+        out.push_str(&format!("# HELP {} {}\n", family_name, family.get_help().replace('\\', "\\\\").replace('\n', "\\n")));
+
+        for metric in family.get_metric() {
+            let labels: Vec<(&str, String)> = metric
+                .get_label()
+                .iter()
+                .map(|pair| (pair.get_name(), pair.get_value().to_string()))
+                .collect();
+            match family.get_field_type() {
+                MetricType::COUNTER => {
+                    let value = format_value(metric.get_counter().get_value());
+                    out.push_str(&format!("{}_total{} {}\n", family_name, format_labels(&labels), value));
+                }
+                MetricType::GAUGE => {
+                    let value = format_value(metric.get_gauge().get_value());
+                    out.push_str(&format!("{}{} {}\n", name, format_labels(&labels), value));
+                }
+                MetricType::HISTOGRAM => {
+                    let histogram = metric.get_histogram();
+                    let series = series_key(name, labels.iter().map(|(k, v)| (*k, v.as_str())));
+                    let bounds = histogram
+                        .get_bucket()
+                        .iter()
+                        .map(|bucket| (bucket.get_upper_bound(), bucket.get_cumulative_count()))
+                        .chain([(f64::INFINITY, histogram.get_sample_count())]);
+                    for (index, (bound, count)) in bounds.enumerate() {
+                        let mut bucket_labels = labels.clone();
+                        bucket_labels.push(("le", format_value(bound)));
+                        out.push_str(&format!("{}_bucket{} {}", name, format_labels(&bucket_labels), count));
+                        if let Some(exemplar) = exemplars.and_then(|store| store.get(&series, index)) {
+                            out.push_str(&format!(
+                                " # {{trace_id=\"{}\"}} {} {:.3}",
+                                escape_label_value(&exemplar.trace_id),
+                                format_value(exemplar.value),
+                                exemplar.timestamp
+                            ));
+                        }
+                        out.push('\n');
+                    }
+                    out.push_str(&format!("{}_count{} {}\n", name, format_labels(&labels), histogram.get_sample_count()));
+                    out.push_str(&format!("{}_sum{} {}\n", name, format_labels(&labels), format_value(histogram.get_sample_sum())));
+                }
+                _ => {
+                    let value = format_value(metric.get_untyped().get_value());
+                    out.push_str(&format!("{}{} {}\n", name, format_labels(&labels), value));
+                }
+            }
+        }
+    }
+    out.push_str("# EOF\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> MetricLabelsConfig {
+        MetricLabelsConfig {
+            max_values_per_label: 2,
+            work_directory_depth: 2,
+            ..MetricLabelsConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_label_guardrails() {
+        let metrics = MetricsCollector::new(&config()).unwrap();
+        metrics.record_task_created("/srv/team-a/app-1", "high");
+        metrics.record_task_created("/srv/team-a/app-2", "high");
+        metrics.record_task_created("/srv/team-b/app", "low");
+        metrics.record_task_created("/srv/team-c", "medium");
+        metrics.record_task_acquired("/srv/team-a", "worker-1");
+
+        let text = metrics.render(false);
+        // 路径截断后合并，超出取值上限的记为 __overflow__
+        assert!(text.contains(r#"task_created_total{priority="high",service="task_orchestrator",work_directory="/srv/team-a"} 2"#));
+        assert!(text.contains(r#"task_created_total{priority="low",service="task_orchestrator",work_directory="/srv/team-b"} 1"#));
+        assert!(text.contains(r#"task_created_total{priority="__overflow__",service="task_orchestrator",work_directory="__overflow__"} 1"#));
+        assert!(text.contains(r#"metric_label_overflow_total{label="work_directory",service="task_orchestrator"} 1"#));
+        // worker_id 不在白名单中
+        assert!(text.contains(r#"task_acquired_total{service="task_orchestrator",work_directory="/srv/team-a"} 1"#));
+        assert!(!text.contains("worker-1"));
+
+        let hashed = MetricsCollector::new(&MetricLabelsConfig { hash_work_directory: true, ..config() }).unwrap();
+        hashed.record_task_created("/srv/team-a/app", "high");
+        let expected = format!("wd-{}", &hex::encode(Sha256::digest(b"/srv/team-a"))[..12]);
+        assert!(hashed.render(false).contains(&format!("work_directory=\"{}\"", expected)));
+    }
+
+    #[test]
+    fn test_openmetrics_exemplars() {
+        let metrics = MetricsCollector::new(&config()).unwrap();
+        metrics.record_http_request("GET", "/api/v1/tasks/:task_id", 200, 0.02, Some("trace-1"));
+        metrics.record_http_request("GET", "/api/v1/tasks/:task_id", 200, 20.0, Some("trace-2"));
+        metrics.record_task_completed("/srv/app", "high", 3.0, None);
+
+        let text = metrics.render(true);
+        assert!(text.starts_with("# TYPE "));
+        assert!(text.ends_with("# EOF\n"));
+        assert!(text.contains("# TYPE task_completed counter\n"));
+        assert!(text.contains("task_completed_total{"));
+        assert!(text.contains(
+            r#"response_time_seconds_bucket{method="GET",route="/api/v1/tasks/:task_id",service="task_orchestrator",status="200",le="0.025"} 1 # {trace_id="trace-1"} 0.02 "#
+        ));
+        assert!(text.contains(
+            r#"response_time_seconds_bucket{method="GET",route="/api/v1/tasks/:task_id",service="task_orchestrator",status="200",le="+Inf"} 2 # {trace_id="trace-2"} 20 "#
+        ));
+        assert!(!text.contains("task_duration_seconds_bucket{priority=\"high\",service=\"task_orchestrator\",work_directory=\"/srv/app\",le=\"5\"} 1 #"));
+
+        // Prometheus文本格式不输出示例
+        assert!(!metrics.render(false).contains("trace-1"));
+        let disabled = MetricsCollector::new(&MetricLabelsConfig { enable_exemplars: false, ..config() }).unwrap();
+        disabled.record_http_request("GET", "/health", 200, 0.01, Some("trace-3"));
+        assert!(!disabled.render(true).contains("trace-3"));
+    }
+
+    #[test]
+    fn test_task_cache_counters() {
+        let metrics = MetricsCollector::new(&config()).unwrap();
+        let mut stats = CacheStats {
+            task_hits: 3,
+            task_misses: 1,
+            list_hits: 0,
+            list_misses: 2,
+            invalidations: 1,
+            hit_rate: 0.5,
+        };
+        metrics.set_task_cache_stats(&stats);
+        // 重复同步同一快照不会重复累加
+        metrics.set_task_cache_stats(&stats);
+        stats.task_hits = 5;
+        metrics.set_task_cache_stats(&stats);
+
+        let text = metrics.render(false);
+        assert!(text.contains(r#"task_cache_lookups_total{cache="task",result="hit",service="task_orchestrator"} 5"#));
+        assert!(text.contains(r#"task_cache_lookups_total{cache="task",result="miss",service="task_orchestrator"} 1"#));
+        assert!(text.contains(r#"task_cache_lookups_total{cache="list",result="miss",service="task_orchestrator"} 2"#));
+        assert!(text.contains(r#"task_cache_invalidations_total{service="task_orchestrator"} 1"#));
+    }
+}
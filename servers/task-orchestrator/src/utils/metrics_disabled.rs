@@ -0,0 +1,99 @@
+//! # Prometheus指标（未启用）
+//!
+//! 未启用 `metrics` feature 时使用的占位实现：[`MetricsCollector`] 无法构造，
+//! 持有 `Option<Arc<MetricsCollector>>` 的代码始终走 `None` 分支，Prometheus端点返回404。
+
+use memory_watchdog::MemoryPressure;
+use crate::config::MetricLabelsConfig;
+use crate::infrastructure::CacheStats;
+
+/// Prometheus文本格式的Content-Type
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// OpenMetrics文本格式的Content-Type
+pub const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// 未编译指标支持时的错误
+#[derive(Debug, thiserror::Error)]
+#[error("Prometheus metrics are not available: built without the `metrics` feature")]
+pub struct MetricsDisabled;
+
+/// 指标收集器占位，不存在实例
+pub struct MetricsCollector {
+    never: std::convert::Infallible,
+}
+
+impl MetricsCollector {
+    pub fn new(_config: &MetricLabelsConfig) -> Result<Self, MetricsDisabled> {
+        Err(MetricsDisabled)
+    }
+
+    pub fn record_task_created(&self, _work_directory: &str, _priority: &str) {
+        match self.never {}
+    }
+
+    pub fn record_task_completed(&self, _work_directory: &str, _priority: &str, _processing_time: f64, _trace_id: Option<&str>) {
+        match self.never {}
+    }
+
+    pub fn record_task_failed(&self, _work_directory: &str, _priority: &str) {
+        match self.never {}
+    }
+
+    pub fn record_task_cancelled(&self, _work_directory: &str, _reason: &str) {
+        match self.never {}
+    }
+
+    pub fn record_task_acquired(&self, _work_directory: &str, _worker_id: &str) {
+        match self.never {}
+    }
+
+    pub fn record_http_request(&self, _method: &str, _route: &str, _status: u16, _duration: f64, _trace_id: Option<&str>) {
+        match self.never {}
+    }
+
+    pub fn set_active_tasks(&self, _count: u64) {
+        match self.never {}
+    }
+
+    pub fn set_queue_size(&self, _size: u64) {
+        match self.never {}
+    }
+
+    pub fn record_panic(&self) {
+        match self.never {}
+    }
+
+    pub fn record_memory_sample(&self, _rss_bytes: u64, _pressure: MemoryPressure, _evicted: bool) {
+        match self.never {}
+    }
+
+    pub fn set_sla(&self, _priority: &str, _start_breaches: u64, _finish_breaches: u64, _at_risk: u64) {
+        match self.never {}
+    }
+
+    pub fn record_sla_alert(&self, _alert: &str) {
+        match self.never {}
+    }
+
+    pub fn record_event_export(&self, _outcome: &str, _count: u64) {
+        match self.never {}
+    }
+
+    pub fn set_task_cache_stats(&self, _stats: &CacheStats) {
+        match self.never {}
+    }
+
+    pub fn record_secret_finding(&self, _rule: &str, _action: &str) {
+        match self.never {}
+    }
+
+    pub fn render(&self, _openmetrics: bool) -> String {
+        match self.never {}
+    }
+}
+
+/// 客户端是否接受OpenMetrics格式
+pub fn accepts_openmetrics(accept: Option<&str>) -> bool {
+    accept.is_some_and(|accept| accept.contains("application/openmetrics-text"))
+}
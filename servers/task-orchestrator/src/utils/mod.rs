@@ -1,5 +1,18 @@
 pub mod logging;
+pub mod clock;
+pub mod crash;
+#[cfg_attr(not(feature = "metrics"), path = "metrics_disabled.rs")]
+pub mod metrics;
 pub mod concurrency;
+pub mod i18n;
+pub mod redaction;
+pub mod systemd;
+pub mod timestamp;
+#[cfg(unix)]
+pub mod listener;
 
-pub use logging::{LogManager, StructuredLogger, MetricsCollector, HealthChecker};
-pub use concurrency::{ConcurrencyController, RateLimiter, CircuitBreaker};
\ No newline at end of file
+pub use logging::{LogManager, HealthChecker};
+pub use metrics::MetricsCollector;
+pub use concurrency::{ConcurrencyController, RateLimiter, CircuitBreaker};
+pub use redaction::Redactor;
+pub use systemd::SystemdNotifier;
\ No newline at end of file
@@ -0,0 +1,272 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use regex::{Captures, Regex};
+
+use crate::config::RedactionConfig;
+use crate::domain::{Prompt, Task};
+
+/// 服务写入的元数据键，内容不含密钥（密钥扫描只记录规则名和位置），不按键名掩码
+const SERVICE_METADATA_KEYS: &[&str] = &[crate::services::SECRET_FINDINGS_KEY];
+
+/// 敏感数据脱敏器
+///
+/// 按键名掩码元数据中的敏感字段，并掩码文本中 `key=value` 形式的敏感赋值
+/// 以及形似密钥的长字符串（同时包含字母和数字，UUID、绝对路径和十六进制摘要除外）。
+///
+/// 键名按片段匹配：键名以 `_`、`-`、`.` 和驼峰边界切分后，包含敏感键的全部片段（连续出现）才命中，
+/// 因此 `token` 命中 `github_token` 和 `authToken`，但不命中 `max_tokens`。
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    enabled: bool,
+    sensitive_keys: Vec<Vec<String>>,
+    assignment: Option<Regex>,
+    secret_like: Regex,
+    mask: String,
+}
+
+impl Redactor {
+    /// 根据配置构建脱敏器
+    pub fn from_config(config: &RedactionConfig) -> Self {
+        let sensitive_keys: Vec<Vec<String>> = config
+            .sensitive_keys
+            .iter()
+            .map(|k| key_segments(k))
+            .filter(|segments| !segments.is_empty())
+            .collect();
+
+        // 正则只用于找出候选赋值，键名是否敏感按片段再判断一次
+        let assignment = (!sensitive_keys.is_empty()).then(|| {
+            let alternatives: Vec<String> = config
+                .sensitive_keys
+                .iter()
+                .filter(|k| !k.is_empty())
+                .map(|k| regex::escape(k))
+                .collect();
+            Regex::new(&format!(
+                r#"(?i)([A-Za-z0-9_-]*(?:{})[A-Za-z0-9_-]*)(\s*[:=]\s*)("[^"]*"|'[^']*'|[^\s,;&]+)"#,
+                alternatives.join("|")
+            ))
+            .expect("escaped sensitive keys form a valid pattern")
+        });
+
+        let secret_like = Regex::new(&format!(r"[A-Za-z0-9+/=_-]{{{},}}", config.min_secret_length.max(8)))
+            .expect("valid secret pattern");
+
+        Self {
+            enabled: config.enabled,
+            sensitive_keys,
+            assignment,
+            secret_like,
+            mask: config.mask.clone(),
+        }
+    }
+
+    /// 键名是否为敏感字段
+    pub fn is_sensitive_key(&self, key: &str) -> bool {
+        if !self.enabled || SERVICE_METADATA_KEYS.contains(&key) {
+            return false;
+        }
+        let segments = key_segments(key);
+        self.sensitive_keys
+            .iter()
+            .any(|sensitive| segments.windows(sensitive.len()).any(|window| window == sensitive.as_slice()))
+    }
+
+    /// 掩码文本中的敏感赋值和形似密钥的字符串
+    pub fn redact_text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if !self.enabled {
+            return Cow::Borrowed(text);
+        }
+
+        let text = match &self.assignment {
+            Some(assignment) => assignment.replace_all(text, |caps: &Captures| {
+                if self.is_sensitive_key(&caps[1]) {
+                    format!("{}{}{}", &caps[1], &caps[2], self.mask)
+                } else {
+                    caps[0].to_string()
+                }
+            }),
+            None => Cow::Borrowed(text),
+        };
+
+        if !self.secret_like.find_iter(&text).any(|m| looks_like_secret(m.as_str())) {
+            return text;
+        }
+        let redacted = self.secret_like.replace_all(&text, |caps: &Captures| {
+            if looks_like_secret(&caps[0]) {
+                self.mask.clone()
+            } else {
+                caps[0].to_string()
+            }
+        });
+        Cow::Owned(redacted.into_owned())
+    }
+
+    /// 递归脱敏JSON值：敏感键的值整体掩码，其余字符串按文本脱敏
+    pub fn redact_value(&self, value: &serde_json::Value) -> serde_json::Value {
+        if !self.enabled {
+            return value.clone();
+        }
+
+        match value {
+            serde_json::Value::String(s) => serde_json::Value::String(self.redact_text(s).into_owned()),
+            serde_json::Value::Array(items) => {
+                serde_json::Value::Array(items.iter().map(|v| self.redact_value(v)).collect())
+            }
+            serde_json::Value::Object(map) => serde_json::Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), self.redact_entry(k, v)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    /// 脱敏任务元数据
+    pub fn redact_metadata(&self, metadata: &HashMap<String, serde_json::Value>) -> HashMap<String, serde_json::Value> {
+        metadata
+            .iter()
+            .map(|(k, v)| (k.clone(), self.redact_entry(k, v)))
+            .collect()
+    }
+
+    /// 脱敏任务副本（提示词、元数据、结果和错误信息），用于导出事件等离开服务的数据
+    pub fn redact_task(&self, task: &Task) -> Task {
+        let mut task = task.clone();
+        if !self.enabled {
+            return task;
+        }
+
+        if let Cow::Owned(prompt) = self.redact_text(task.prompt.as_str()) {
+            if let Ok(prompt) = Prompt::new(prompt) {
+                task.prompt = prompt;
+            }
+        }
+        task.metadata = self.redact_metadata(&task.metadata);
+        task.error_message = task.error_message.map(|e| self.redact_text(&e).into_owned());
+        if let Some(result) = task.result.as_mut() {
+            result.output = result.output.as_ref().map(|o| self.redact_text(o).into_owned());
+            result.error = result.error.as_ref().map(|e| self.redact_text(e).into_owned());
+            result.details = self.redact_metadata(&result.details);
+            result.metadata = self.redact_metadata(&result.metadata);
+        }
+        task
+    }
+
+    fn redact_entry(&self, key: &str, value: &serde_json::Value) -> serde_json::Value {
+        if self.is_sensitive_key(key) && !value.is_null() {
+            serde_json::Value::String(self.mask.clone())
+        } else {
+            self.redact_value(value)
+        }
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::from_config(&RedactionConfig::default())
+    }
+}
+
+/// 将键名切分为小写片段：按非字母数字字符和驼峰边界切分
+fn key_segments(key: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in key.chars() {
+        if !c.is_alphanumeric() {
+            segments.extend((!current.is_empty()).then(|| std::mem::take(&mut current)));
+            previous_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lower {
+            segments.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.extend(c.to_lowercase());
+    }
+    segments.extend((!current.is_empty()).then_some(current));
+    segments
+}
+
+/// 是否为十六进制摘要（SHA-1/git提交、SHA-256、SHA-512），如终态转换请求摘要
+fn is_hex_digest(token: &str) -> bool {
+    matches!(token.len(), 40 | 64 | 128) && token.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// 是否形似密钥：同时包含字母和数字，且不是UUID、绝对路径或十六进制摘要
+fn looks_like_secret(token: &str) -> bool {
+    token.chars().any(|c| c.is_ascii_alphabetic())
+        && token.chars().any(|c| c.is_ascii_digit())
+        && !token.starts_with('/')
+        && !is_hex_digest(token)
+        && uuid::Uuid::parse_str(token).is_err()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_text() {
+        let redactor = Redactor::default();
+        assert_eq!(
+            redactor.redact_text("deploy with api_key=abc123 and TOKEN: \"xyz\""),
+            "deploy with api_key=[REDACTED] and TOKEN: [REDACTED]"
+        );
+        assert_eq!(
+            redactor.redact_text("use Zx8kP2mQ9vL4nR7tY1wB5cF3hJ6dG0sA for auth"),
+            "use [REDACTED] for auth"
+        );
+
+        let unchanged = "task 550e8400-e29b-41d4-a716-446655440000 in /home/user/projects/my-project-2024-backend";
+        assert!(matches!(redactor.redact_text(unchanged), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_redact_metadata_keys() {
+        let redactor = Redactor::default();
+        let metadata = HashMap::from([
+            ("OPENAI_API_KEY".to_string(), serde_json::json!("sk-live")),
+            ("env".to_string(), serde_json::json!({ "db_password": "hunter2", "region": "eu" })),
+            ("owner".to_string(), serde_json::json!("alice")),
+        ]);
+
+        let redacted = redactor.redact_metadata(&metadata);
+        assert_eq!(redacted["OPENAI_API_KEY"], "[REDACTED]");
+        assert_eq!(redacted["env"]["db_password"], "[REDACTED]");
+        assert_eq!(redacted["env"]["region"], "eu");
+        assert_eq!(redacted["owner"], "alice");
+    }
+
+    #[test]
+    fn test_key_segments_and_digests() {
+        let redactor = Redactor::default();
+        let metadata = HashMap::from([
+            ("authToken".to_string(), serde_json::json!("abc")),
+            ("client-secret".to_string(), serde_json::json!("abc")),
+            ("max_tokens".to_string(), serde_json::json!(4096)),
+            ("secretary".to_string(), serde_json::json!("bob")),
+            (
+                crate::services::SECRET_FINDINGS_KEY.to_string(),
+                serde_json::json!([{"rule": "github_token", "location": "prompt"}]),
+            ),
+        ]);
+
+        let redacted = redactor.redact_metadata(&metadata);
+        assert_eq!(redacted["authToken"], "[REDACTED]");
+        assert_eq!(redacted["client-secret"], "[REDACTED]");
+        assert_eq!(redacted["max_tokens"], 4096);
+        assert_eq!(redacted["secretary"], "bob");
+        assert_eq!(redacted[crate::services::SECRET_FINDINGS_KEY], metadata[crate::services::SECRET_FINDINGS_KEY]);
+
+        assert_eq!(redactor.redact_text("set max_tokens=4096"), "set max_tokens=4096");
+        assert_eq!(redactor.redact_text("set github_token=abc"), "set github_token=[REDACTED]");
+
+        // 十六进制摘要不是密钥
+        let hash = format!("complete:{}", "3f".repeat(32));
+        assert!(matches!(redactor.redact_text(&hash), Cow::Borrowed(_)));
+        let commit = "checked out 9fceb02d0ae598e95dc970b74767f19372d61af8";
+        assert!(matches!(redactor.redact_text(commit), Cow::Borrowed(_)));
+    }
+}
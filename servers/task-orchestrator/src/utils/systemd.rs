@@ -0,0 +1,148 @@
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use crate::infrastructure::ManagedPool;
+
+/// systemd 通知客户端（sd_notify 协议）
+///
+/// 向 `NOTIFY_SOCKET` 指向的数据报套接字发送状态消息，支持以 `@` 开头的抽象命名空间地址。
+/// 进程不是由 systemd 以 `Type=notify` 启动时没有该环境变量，此时不创建通知客户端。
+#[derive(Debug, Clone)]
+pub struct SystemdNotifier {
+    socket_path: String,
+    watchdog_interval: Option<Duration>,
+}
+
+impl SystemdNotifier {
+    /// 从环境变量创建，未设置 `NOTIFY_SOCKET` 时返回 `None`
+    pub fn from_env() -> Option<Self> {
+        let socket_path = std::env::var("NOTIFY_SOCKET").ok().filter(|path| !path.is_empty())?;
+        let watchdog_interval = watchdog_interval(
+            std::env::var("WATCHDOG_USEC").ok().as_deref(),
+            std::env::var("WATCHDOG_PID").ok().as_deref(),
+            std::process::id(),
+        );
+        Some(Self { socket_path, watchdog_interval })
+    }
+
+    /// 看门狗保活间隔，为 `WATCHDOG_USEC` 的一半；未启用看门狗时为 `None`
+    pub fn watchdog_interval(&self) -> Option<Duration> {
+        self.watchdog_interval
+    }
+
+    /// 服务已就绪
+    pub fn ready(&self) -> io::Result<()> {
+        self.notify(&format!("READY=1\nMAINPID={}", std::process::id()))
+    }
+
+    /// 服务开始优雅关闭
+    pub fn stopping(&self) -> io::Result<()> {
+        self.notify("STOPPING=1")
+    }
+
+    /// 看门狗保活
+    pub fn watchdog(&self) -> io::Result<()> {
+        self.notify("WATCHDOG=1")
+    }
+
+    /// 发送原始状态消息，多个字段以换行分隔
+    #[cfg(unix)]
+    pub fn notify(&self, state: &str) -> io::Result<()> {
+        use std::os::unix::net::UnixDatagram;
+
+        let socket = UnixDatagram::unbound()?;
+        match self.socket_path.strip_prefix('@') {
+            #[cfg(target_os = "linux")]
+            Some(name) => {
+                use std::os::linux::net::SocketAddrExt;
+                let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())?;
+                socket.send_to_addr(state.as_bytes(), &addr)?;
+            }
+            #[cfg(not(target_os = "linux"))]
+            Some(_) => {
+                return Err(io::Error::new(io::ErrorKind::Unsupported, "abstract notify socket requires Linux"));
+            }
+            None => {
+                socket.send_to(state.as_bytes(), &self.socket_path)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn notify(&self, _state: &str) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "sd_notify requires a Unix platform"))
+    }
+
+    /// 启动看门狗保活循环
+    ///
+    /// 每个周期先执行一次数据库存活查询，成功后才发送 `WATCHDOG=1`；数据库持续不可用时停止保活，
+    /// 由 systemd 在 `WatchdogSec` 超时后按 `Restart=` 策略重启服务。未启用看门狗时返回 `None`。
+    pub fn start_watchdog(&self, pool: Option<Arc<ManagedPool>>) -> Option<JoinHandle<()>> {
+        let interval = self.watchdog_interval?;
+        let notifier = self.clone();
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Some(pool) = &pool {
+                    if pool.check().await.is_err() {
+                        tracing::warn!("Skipping systemd watchdog keepalive: database health check failed");
+                        continue;
+                    }
+                }
+                if let Err(e) = notifier.watchdog() {
+                    tracing::warn!("Failed to send systemd watchdog keepalive: {}", e);
+                }
+            }
+        }))
+    }
+}
+
+/// 根据 `WATCHDOG_USEC`/`WATCHDOG_PID` 计算保活间隔
+///
+/// `WATCHDOG_PID` 存在且不是当前进程时，看门狗属于其他进程，不发送保活。
+fn watchdog_interval(usec: Option<&str>, pid: Option<&str>, current_pid: u32) -> Option<Duration> {
+    if let Some(pid) = pid {
+        if pid.parse::<u32>().ok()? != current_pid {
+            return None;
+        }
+    }
+    let usec: u64 = usec?.parse().ok().filter(|usec| *usec > 0)?;
+    Some(Duration::from_micros(usec / 2))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixDatagram;
+
+    #[test]
+    fn test_notify_and_watchdog_interval() {
+        assert_eq!(watchdog_interval(Some("10000000"), None, 42), Some(Duration::from_secs(5)));
+        assert_eq!(watchdog_interval(Some("10000000"), Some("42"), 42), Some(Duration::from_secs(5)));
+        assert_eq!(watchdog_interval(Some("10000000"), Some("7"), 42), None);
+        assert_eq!(watchdog_interval(Some("0"), None, 42), None);
+        assert_eq!(watchdog_interval(None, None, 42), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notify.sock");
+        let server = UnixDatagram::bind(&path).unwrap();
+        let notifier = SystemdNotifier {
+            socket_path: path.display().to_string(),
+            watchdog_interval: None,
+        };
+
+        let mut buf = [0u8; 256];
+        notifier.stopping().unwrap();
+        let len = server.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"STOPPING=1");
+
+        notifier.ready().unwrap();
+        let len = server.recv(&mut buf).unwrap();
+        assert!(buf[..len].starts_with(b"READY=1\nMAINPID="));
+    }
+}
@@ -0,0 +1,223 @@
+//! # 响应时间格式
+//!
+//! 客户端可以按请求选择响应中时间字段的格式和时区：
+//!
+//! - `time_format` 查询参数或 `X-Time-Format` 请求头：`rfc3339`（默认）或 `epoch_millis`（Unix毫秒时间戳，输出为数字）
+//! - `tz` 查询参数或 `X-Timezone` 请求头：固定UTC偏移，如 `+08:00`、`-0530`、`Z`、`UTC`，仅对 `rfc3339` 生效
+//!
+//! 查询参数优先于请求头，无法识别的值被忽略。未协商时各字段保持原有输出，不受影响。
+//! 协商结果保存在请求上下文中，[`ApiTimestamp`] 和 [`serialize_utc`] 在序列化响应时读取。
+
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::errors::current_request_context;
+
+/// 时间格式查询参数
+pub const TIME_FORMAT_PARAM: &str = "time_format";
+/// 时区查询参数
+pub const TIMEZONE_PARAM: &str = "tz";
+/// 时间格式请求头
+pub const TIME_FORMAT_HEADER: &str = "x-time-format";
+/// 时区请求头
+pub const TIMEZONE_HEADER: &str = "x-timezone";
+
+/// 时间表示方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampStyle {
+    #[default]
+    Rfc3339,
+    EpochMillis,
+}
+
+impl TimestampStyle {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "rfc3339" | "iso8601" => Some(TimestampStyle::Rfc3339),
+            "epoch_millis" | "epoch_ms" | "millis" => Some(TimestampStyle::EpochMillis),
+            _ => None,
+        }
+    }
+}
+
+/// 协商得到的响应时间格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampFormat {
+    pub style: TimestampStyle,
+    pub offset: FixedOffset,
+}
+
+impl Default for TimestampFormat {
+    fn default() -> Self {
+        Self {
+            style: TimestampStyle::default(),
+            offset: FixedOffset::east_opt(0).unwrap(),
+        }
+    }
+}
+
+impl TimestampFormat {
+    /// 由时间格式和时区组合，两者都未指定时返回 `None`
+    pub fn negotiate(style: Option<TimestampStyle>, offset: Option<FixedOffset>) -> Option<Self> {
+        if style.is_none() && offset.is_none() {
+            return None;
+        }
+        let default = Self::default();
+        Some(Self {
+            style: style.unwrap_or(default.style),
+            offset: offset.unwrap_or(default.offset),
+        })
+    }
+
+    /// 按格式输出时间
+    pub fn serialize<S: Serializer>(&self, timestamp: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.style {
+            TimestampStyle::Rfc3339 => serializer.serialize_str(&timestamp.with_timezone(&self.offset).to_rfc3339()),
+            TimestampStyle::EpochMillis => serializer.serialize_i64(timestamp.timestamp_millis()),
+        }
+    }
+}
+
+/// 解析固定UTC偏移，如 `+08:00`、`+0800`、`-05`、`Z`、`UTC`、`UTC+8`
+pub fn parse_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.trim();
+    let upper = value.to_ascii_uppercase();
+    let rest = upper.strip_prefix("UTC").or_else(|| upper.strip_prefix("GMT")).unwrap_or(&upper);
+    if rest.is_empty() || rest == "Z" {
+        return FixedOffset::east_opt(0);
+    }
+
+    let (sign, digits) = match rest.as_bytes()[0] {
+        b'+' => (1, &rest[1..]),
+        b'-' => (-1, &rest[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = match digits.split_once(':') {
+        Some((h, m)) => (h, m),
+        None if digits.len() == 4 => digits.split_at(2),
+        None => (digits, "0"),
+    };
+    if hours.is_empty() || hours.len() > 2 || minutes.len() > 2 {
+        return None;
+    }
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if hours > 14 || minutes > 59 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+fn negotiated_format() -> Option<TimestampFormat> {
+    current_request_context().and_then(|ctx| ctx.timestamp_format)
+}
+
+/// 响应中的时间字段
+///
+/// 按当前请求协商的格式输出；未协商时输出RFC 3339 UTC（`+00:00` 偏移）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ApiTimestamp(pub DateTime<Utc>);
+
+impl From<DateTime<Utc>> for ApiTimestamp {
+    fn from(timestamp: DateTime<Utc>) -> Self {
+        Self(timestamp)
+    }
+}
+
+impl Serialize for ApiTimestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match negotiated_format() {
+            Some(format) => format.serialize(&self.0, serializer),
+            None => serializer.serialize_str(&self.0.to_rfc3339()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ApiTimestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Millis(i64),
+            Text(String),
+        }
+
+        let timestamp = match Raw::deserialize(deserializer)? {
+            Raw::Millis(millis) => DateTime::from_timestamp_millis(millis)
+                .ok_or_else(|| serde::de::Error::custom("timestamp out of range"))?,
+            Raw::Text(text) => DateTime::parse_from_rfc3339(&text)
+                .map_err(serde::de::Error::custom)?
+                .with_timezone(&Utc),
+        };
+        Ok(Self(timestamp))
+    }
+}
+
+/// 用于 `#[serde(serialize_with)]` 的 `DateTime<Utc>` 序列化函数
+///
+/// 未协商时与chrono默认的序列化结果相同（`Z` 后缀）。
+pub fn serialize_utc<S: Serializer>(timestamp: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+    match negotiated_format() {
+        Some(format) => format.serialize(timestamp, serializer),
+        None => timestamp.serialize(serializer),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::{RequestContext, REQUEST_CONTEXT};
+    use crate::utils::i18n::Locale;
+
+    fn context(timestamp_format: Option<TimestampFormat>) -> RequestContext {
+        RequestContext {
+            trace_id: "trace".to_string(),
+            correlation_id: None,
+            instance: "/".to_string(),
+            locale: Locale::En,
+            timestamp_format,
+        }
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        let east = |secs| FixedOffset::east_opt(secs);
+        assert_eq!(parse_offset("+08:00"), east(8 * 3600));
+        assert_eq!(parse_offset("-0530"), east(-(5 * 3600 + 30 * 60)));
+        assert_eq!(parse_offset("UTC+8"), east(8 * 3600));
+        assert_eq!(parse_offset("z"), east(0));
+        assert_eq!(parse_offset("utc"), east(0));
+        assert_eq!(parse_offset("Asia/Shanghai"), None);
+        assert_eq!(parse_offset("+15:00"), None);
+        assert_eq!(parse_offset("+08:60"), None);
+    }
+
+    #[tokio::test]
+    async fn test_serialize_with_negotiated_format() {
+        let timestamp = ApiTimestamp(DateTime::parse_from_rfc3339("2024-01-01T00:00:00.250Z").unwrap().with_timezone(&Utc));
+
+        // 请求上下文之外和未协商时保持原有格式
+        assert_eq!(serde_json::to_value(timestamp).unwrap(), "2024-01-01T00:00:00.250+00:00");
+        let legacy = REQUEST_CONTEXT
+            .scope(context(None), async { serde_json::to_value(timestamp).unwrap() })
+            .await;
+        assert_eq!(legacy, "2024-01-01T00:00:00.250+00:00");
+
+        let shanghai = TimestampFormat::negotiate(None, parse_offset("+08:00"));
+        let value = REQUEST_CONTEXT
+            .scope(context(shanghai), async { serde_json::to_value(timestamp).unwrap() })
+            .await;
+        assert_eq!(value, "2024-01-01T08:00:00.250+08:00");
+
+        let millis = TimestampFormat::negotiate(TimestampStyle::parse("epoch-millis"), parse_offset("+08:00"));
+        let value = REQUEST_CONTEXT
+            .scope(context(millis), async { serde_json::to_value(timestamp).unwrap() })
+            .await;
+        assert_eq!(value, 1704067200250i64);
+
+        // 两种格式都能反序列化
+        assert_eq!(serde_json::from_value::<ApiTimestamp>(value).unwrap(), timestamp);
+        let parsed: ApiTimestamp = serde_json::from_str("\"2024-01-01T08:00:00.250+08:00\"").unwrap();
+        assert_eq!(parsed, timestamp);
+    }
+}